@@ -0,0 +1,10 @@
+-- Tit-for-Tat as a Lua agent, to be played with e.g.
+--   the-duel duel "lua(experiments/tit_for_tat.lua)" attack
+-- or in a roster with
+--   { kind = "lua", script = "experiments/tit_for_tat.lua" }
+-- The sandbox calls decide_action every turn with a table describing the
+-- duel and expects the name of the action in return.
+function decide_action(observation)
+  -- Open with a finch, then repeat the opponent's last action
+  return observation.opposing_action or "FINCH"
+end
@@ -0,0 +1,15 @@
+#!/usr/bin/env python3
+# Tit-for-Tat as an external agent, to be played with e.g.
+#   the-duel duel "external(python3 experiments/tit_for_tat.py)" attack
+# or in a roster with
+#   { kind = "external", command = "python3", args = ["experiments/tit_for_tat.py"] }
+# Every turn, the sandbox sends one line of JSON describing the duel and
+# expects one line of JSON with the action in return.
+import json
+import sys
+
+for line in sys.stdin:
+    observation = json.loads(line)
+    # Open with a finch, then repeat the opponent's last action
+    action = observation["opposing_action"] or "FINCH"
+    print(json.dumps({"action": action}), flush=True)
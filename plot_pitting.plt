@@ -5,6 +5,6 @@ load 'viridis.pal'
 set palette maxcolors 20
 
 set pm3d map
-splot "pitting-results.csv" matrix
+splot "pitting-results.csv" matrix rowheaders columnheaders
 
 pause -1
\ No newline at end of file
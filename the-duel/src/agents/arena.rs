@@ -0,0 +1,105 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+use rand::seq::IndexedRandom;
+
+use crate::agents::GameAgent;
+use crate::arena::{Arena, Fighter, Move, winding_up};
+use crate::history::HistoryView;
+
+// Plays one of its legal moves uniformly at random.
+pub struct RandomFighter<T: Rng + 'static> {
+    pub current_random: Rc<RefCell<T>>,
+}
+
+impl<T: Rng> RandomFighter<T> {
+    pub fn new(current_random: Rc<RefCell<T>>) -> Self {
+        Self { current_random }
+    }
+}
+
+impl<T: Rng> GameAgent<Arena> for RandomFighter<T> {
+    fn decide_action(
+        &mut self,
+        own_player_state: &Fighter,
+        _opposing_player_actions: &Option<Move>,
+        _opposing_player_state: &Option<Fighter>,
+        _history: &HistoryView<Arena>,
+    ) -> Move {
+        *own_player_state
+            .legal_moves()
+            .choose(&mut *self.current_random.borrow_mut())
+            .expect("a fighter always has a legal move")
+    }
+
+    fn strategy_name(&self) -> String {
+        String::from("Random fighter")
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent<Arena>> {
+        Box::new(Self::new(self.current_random.clone()))
+    }
+}
+
+// Expects the opponent to repeat its last move and plays the legal move with
+// the best exchange against it, i.e. the most damage dealt and hit points
+// healed for the least damage taken. Before the first turn it expects a finch.
+#[derive(Clone)]
+pub struct TacticianAgent {
+    pub rules: Arena,
+}
+
+impl TacticianAgent {
+    pub fn new(rules: Arena) -> Self {
+        Self { rules }
+    }
+
+    fn exchange(&self, own: &Move, own_state: &Fighter, expected: &Move, releases: bool) -> i64 {
+        let dealt =
+            self.rules
+                .damage_taken(expected, own, own_state.charging && *own == Move::CHARGE);
+        let taken = self.rules.damage_taken(own, expected, releases);
+        let healed = match own {
+            Move::HEAL => self
+                .rules
+                .move_info(own)
+                .healing
+                .min(own_state.max_hit_points - own_state.current_hit_points + taken),
+            _ => 0,
+        };
+        dealt + healed - taken
+    }
+}
+
+impl GameAgent<Arena> for TacticianAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &Fighter,
+        opposing_player_actions: &Option<Move>,
+        _opposing_player_state: &Option<Fighter>,
+        history: &HistoryView<Arena>,
+    ) -> Move {
+        // A charge wound up last turn is released this turn
+        let releases = winding_up(history.opposing_actions);
+        let expected = match (releases, opposing_player_actions) {
+            (true, _) => Move::CHARGE,
+            (false, Some(action)) => *action,
+            (false, None) => Move::FINCH,
+        };
+        own_player_state
+            .legal_moves()
+            .into_iter()
+            .rev()
+            .max_by_key(|own| self.exchange(own, own_player_state, &expected, releases))
+            .expect("a fighter always has a legal move")
+    }
+
+    fn strategy_name(&self) -> String {
+        String::from("Tactician")
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent<Arena>> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,26 @@
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+#[derive(Clone)]
+pub struct AttackAgent;
+
+impl GameAgent for AttackAgent {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        _history: &HistoryView,
+    ) -> Action {
+        Action::ATTACK
+    }
+
+    fn strategy_name(&self) -> String {
+        String::from("Always Attack")
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {})
+    }
+}
@@ -0,0 +1,105 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::{GameAgent, opposing_hit_points};
+use crate::cfr::DuelSolution;
+use crate::duel::{Action, DamageMatrix, PlayerState};
+use crate::history::HistoryView;
+
+// Plays the equilibrium strategy found by counterfactual regret minimization.
+// The duel is solved for the agent's hit points the first time it plays, and
+// copies share the solution. Hidden opposing hit points are recounted from
+// the actions so far.
+#[derive(Clone)]
+pub struct CfrAgent<T: Rng + 'static> {
+    pub current_random: Rc<RefCell<T>>,
+    pub iterations: usize,
+    // The damage matrix the duel is solved for.
+    pub damage: DamageMatrix,
+    pub solution: Rc<RefCell<Option<Rc<DuelSolution>>>>,
+}
+
+impl<T: Rng> CfrAgent<T> {
+    pub fn new(current_random: Rc<RefCell<T>>, iterations: usize) -> Self {
+        Self {
+            current_random,
+            iterations,
+            damage: DamageMatrix::default(),
+            solution: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    pub fn with_damage(mut self, damage: DamageMatrix) -> Self {
+        self.damage = damage;
+        self.solution = Rc::new(RefCell::new(None));
+        self
+    }
+
+    fn solution(&self, max_hit_points: i64) -> Rc<DuelSolution> {
+        let mut solution = self.solution.borrow_mut();
+        match &*solution {
+            Some(solved) if solved.max_hit_points == max_hit_points => solved.clone(),
+            _ => {
+                let solved = Rc::new(DuelSolution::solve_with_damage(
+                    max_hit_points,
+                    &self.damage,
+                    self.iterations,
+                ));
+                *solution = Some(solved.clone());
+                solved
+            }
+        }
+    }
+}
+
+impl<T: Rng> GameAgent for CfrAgent<T> {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let solution = self.solution(own_player_state.max_hit_points);
+        let opposing_hit_points = opposing_hit_points(
+            own_player_state,
+            opposing_player_state,
+            history,
+            &self.damage,
+        );
+        let probability =
+            solution.attack_probability(own_player_state.current_hit_points, opposing_hit_points);
+        if self
+            .current_random
+            .borrow_mut()
+            .random_bool(probability.clamp(0.0, 1.0))
+        {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        let name = format!(
+            "Counterfactual regret minimization equilibrium after {} iterations",
+            self.iterations
+        );
+        if self.damage == DamageMatrix::default() {
+            name
+        } else {
+            format!("{} for damage {:?}", name, self.damage.0)
+        }
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            current_random: self.current_random.clone(),
+            iterations: self.iterations,
+            damage: self.damage.clone(),
+            solution: self.solution.clone(),
+        })
+    }
+}
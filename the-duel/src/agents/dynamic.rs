@@ -0,0 +1,249 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::agents::{DecisionExplanation, GameAgent, opposing_hit_points};
+use crate::cfr::{MAX_SWEEPS, SWEEP_TOLERANCE};
+use crate::duel::{Action, DamageMatrix, PlayerState};
+use crate::history::HistoryView;
+
+// How the agent models the opponent's probability of attack.
+#[derive(Clone, Copy)]
+pub enum AttackModel {
+    Fixed(f64),
+    // Mean of a Beta posterior over the observed actions, with the given
+    // pseudo-counts of attacks and finches as prior.
+    Estimated {
+        prior_attacks: f64,
+        prior_finches: f64,
+    },
+}
+
+// Whether attacking maximizes the chance of winning, a tie counting as half a
+// win, in every (own, opposing) hit point state, computed by backward
+// induction against an opponent attacking with a fixed probability.
+pub struct DuelPolicy {
+    pub max_hit_points: i64,
+    pub probability_of_attack: f64,
+    // The damage matrix the duel is solved for, seen as player one.
+    pub damage: DamageMatrix,
+    attack: Vec<bool>,
+    values: Vec<f64>,
+}
+
+impl DuelPolicy {
+    pub fn solve(max_hit_points: i64, probability_of_attack: f64) -> Self {
+        Self::solve_with_damage(
+            max_hit_points,
+            &DamageMatrix::default(),
+            probability_of_attack,
+        )
+    }
+
+    // As long as every exchange costs somebody hit points, all successors of
+    // a state are solved before it. Other damage matrices are solved by
+    // sweeping over all states until the values settle, as in crate::cfr.
+    pub fn solve_with_damage(
+        max_hit_points: i64,
+        damage: &DamageMatrix,
+        probability_of_attack: f64,
+    ) -> Self {
+        let size = (max_hit_points + 1) as usize;
+        let mut policy = Self {
+            max_hit_points,
+            probability_of_attack,
+            damage: damage.clone(),
+            attack: vec![false; size * size],
+            values: vec![0.0; size * size],
+        };
+        let progressive = damage.0.iter().flatten().all(|[own_loss, opposing_loss]| {
+            *own_loss >= 0 && *opposing_loss >= 0 && own_loss + opposing_loss > 0
+        });
+        let sweeps = if progressive { 1 } else { MAX_SWEEPS };
+
+        for _ in 0..sweeps {
+            let mut change: f64 = 0.0;
+            for own in 1..=max_hit_points {
+                for opposing in 1..=max_hit_points {
+                    let (attacking, finching) = policy.action_values(own, opposing);
+                    let index = policy.index(own, opposing);
+                    policy.attack[index] = attacking > finching;
+                    change = change.max((policy.values[index] - attacking.max(finching)).abs());
+                    policy.values[index] = attacking.max(finching);
+                }
+            }
+            if change < SWEEP_TOLERANCE {
+                break;
+            }
+        }
+        policy
+    }
+
+    fn index(&self, own: i64, opposing: i64) -> usize {
+        let own = own.clamp(0, self.max_hit_points) as usize;
+        let opposing = opposing.clamp(0, self.max_hit_points) as usize;
+        own * (self.max_hit_points + 1) as usize + opposing
+    }
+
+    pub fn attacks(&self, own: i64, opposing: i64) -> bool {
+        self.attack[self.index(own, opposing)]
+    }
+
+    // The chance of winning, a tie counting as half a win, in the given hit
+    // points with optimal play.
+    pub fn value(&self, own: i64, opposing: i64) -> f64 {
+        match (own <= 0, opposing <= 0) {
+            (true, true) => 0.5,
+            (true, false) => 0.0,
+            (false, true) => 1.0,
+            (false, false) => self.values[self.index(own, opposing)],
+        }
+    }
+
+    // The chances of winning after attacking and after finching in the
+    // given hit points, with optimal play afterwards.
+    pub fn action_values(&self, own: i64, opposing: i64) -> (f64, f64) {
+        let q = self.probability_of_attack;
+        let after = |own_action: &Action, opposing_action: &Action| {
+            let [own_loss, opposing_loss] = self.damage.damage(own_action, opposing_action);
+            self.value(own - own_loss, opposing - opposing_loss)
+        };
+        let value = |own_action: &Action| {
+            q * after(own_action, &Action::ATTACK) + (1.0 - q) * after(own_action, &Action::FINCH)
+        };
+        (value(&Action::ATTACK), value(&Action::FINCH))
+    }
+}
+
+// Policies by hit points and attack probability in steps of `resolution`.
+pub type PolicyCache = HashMap<(i64, i64), Rc<DuelPolicy>>;
+
+// A multi-step decision process: instead of comparing the rewards of the
+// next turn only, plays the optimal policy over the whole remaining duel
+// against its model of the opponent. Policies are solved once per attack
+// probability rounded to `resolution` and shared between copies.
+#[derive(Clone)]
+pub struct DynamicProgrammingAgent {
+    pub model: AttackModel,
+    pub resolution: f64,
+    // The damage matrix of the duel the agent plans for.
+    pub damage: DamageMatrix,
+    pub num_turns: i64,
+    pub num_attacks: i64,
+    pub policies: Rc<RefCell<PolicyCache>>,
+    pub last_decision: Option<DecisionExplanation>,
+}
+
+impl DynamicProgrammingAgent {
+    pub fn new(model: AttackModel, resolution: f64) -> Self {
+        Self {
+            model,
+            resolution,
+            damage: DamageMatrix::default(),
+            num_turns: 0,
+            num_attacks: 0,
+            policies: Rc::new(RefCell::new(PolicyCache::new())),
+            last_decision: None,
+        }
+    }
+
+    pub fn with_damage(mut self, damage: DamageMatrix) -> Self {
+        self.damage = damage;
+        self.policies = Rc::new(RefCell::new(PolicyCache::new()));
+        self
+    }
+
+    pub fn probability_of_attack(&self) -> f64 {
+        match self.model {
+            AttackModel::Fixed(probability) => probability,
+            AttackModel::Estimated {
+                prior_attacks,
+                prior_finches,
+            } => {
+                (prior_attacks + self.num_attacks as f64)
+                    / (prior_attacks + prior_finches + self.num_turns as f64)
+            }
+        }
+    }
+
+    fn policy(&self, max_hit_points: i64) -> Rc<DuelPolicy> {
+        let step = (self.probability_of_attack() / self.resolution).round() as i64;
+        self.policies
+            .borrow_mut()
+            .entry((max_hit_points, step))
+            .or_insert_with(|| {
+                let probability = (step as f64 * self.resolution).clamp(0.0, 1.0);
+                Rc::new(DuelPolicy::solve_with_damage(
+                    max_hit_points,
+                    &self.damage,
+                    probability,
+                ))
+            })
+            .clone()
+    }
+}
+
+impl GameAgent for DynamicProgrammingAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        if let Some(action) = opposing_player_actions {
+            self.num_turns += 1;
+            if *action == Action::ATTACK {
+                self.num_attacks += 1;
+            }
+        }
+        let policy = self.policy(own_player_state.max_hit_points);
+        let own_hit_points = own_player_state.current_hit_points;
+        let opposing_hit_points = opposing_hit_points(
+            own_player_state,
+            opposing_player_state,
+            history,
+            &self.damage,
+        );
+        let (attacking, finching) = policy.action_values(own_hit_points, opposing_hit_points);
+        self.last_decision = Some(DecisionExplanation {
+            beliefs: vec![
+                (String::from("p"), policy.probability_of_attack),
+                (String::from("opposing_hp"), opposing_hit_points as f64),
+            ],
+            expected_rewards: vec![
+                (format!("{:?}", Action::ATTACK), attacking),
+                (format!("{:?}", Action::FINCH), finching),
+            ],
+        });
+        if policy.attacks(own_hit_points, opposing_hit_points) {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn explain_last_decision(&self) -> Option<DecisionExplanation> {
+        self.last_decision.clone()
+    }
+
+    fn belief_snapshot(&self) -> Vec<(String, f64)> {
+        vec![(String::from("p"), self.probability_of_attack())]
+    }
+
+    fn strategy_name(&self) -> String {
+        match self.model {
+            AttackModel::Fixed(probability) => format!(
+                "Plan the whole duel by dynamic programming against attack probability {}",
+                probability
+            ),
+            AttackModel::Estimated { .. } => String::from(
+                "Estimate Probability of Attack, and plan the whole duel by dynamic programming.",
+            ),
+        }
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(self.clone())
+    }
+}
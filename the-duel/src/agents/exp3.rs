@@ -0,0 +1,125 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+const ACTIONS: [Action; 2] = [Action::ATTACK, Action::FINCH];
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Exp3Variant {
+    // Auer et al.: importance weighted rewards, mixed with uniform exploration.
+    Exp3,
+    // Neu: implicitly explores by biasing the importance weighted losses.
+    Exp3Ix,
+}
+
+// Adversarial two-armed bandit over attacking and finching. Unlike the
+// frequency estimate of the one-step decision process, it makes no
+// assumption on how the opponent picks its actions. Only the payoff of the
+// action taken is used: 1 for finching into an attack, 0 for attacking into
+// a finch and 1/2 otherwise.
+#[derive(Clone)]
+pub struct Exp3Agent<T: Rng + 'static> {
+    pub current_random: Rc<RefCell<T>>,
+    pub variant: Exp3Variant,
+    // EXP3: share of uniform exploration; EXP3-IX: bias of the loss estimates.
+    pub exploration: f64,
+    // Scale of the EXP3-IX weights; EXP3 scales by exploration / 2 instead.
+    pub learning_rate: f64,
+    // Importance weighted cumulative reward (EXP3) or loss (EXP3-IX) of every action.
+    pub estimates: [f64; 2],
+    // Action taken last turn and the probability it had.
+    previous: Option<(usize, f64)>,
+}
+
+impl<T: Rng> Exp3Agent<T> {
+    pub fn new(
+        current_random: Rc<RefCell<T>>,
+        variant: Exp3Variant,
+        exploration: f64,
+        learning_rate: f64,
+    ) -> Self {
+        Self {
+            current_random,
+            variant,
+            exploration,
+            learning_rate,
+            estimates: [0.0; 2],
+            previous: None,
+        }
+    }
+
+    pub fn probabilities(&self) -> [f64; 2] {
+        let k = ACTIONS.len() as f64;
+        let scores = match self.variant {
+            Exp3Variant::Exp3 => self.estimates.map(|e| self.exploration / k * e),
+            Exp3Variant::Exp3Ix => self.estimates.map(|e| -self.learning_rate * e),
+        };
+        // Shift by the largest score so that the weights cannot overflow
+        let top = scores[0].max(scores[1]);
+        let weights = scores.map(|s| (s - top).exp());
+        let total: f64 = weights.iter().sum();
+        match self.variant {
+            Exp3Variant::Exp3 => {
+                weights.map(|w| (1.0 - self.exploration) * w / total + self.exploration / k)
+            }
+            Exp3Variant::Exp3Ix => weights.map(|w| w / total),
+        }
+    }
+}
+
+impl<T: Rng> GameAgent for Exp3Agent<T> {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        _history: &HistoryView,
+    ) -> Action {
+        if let (Some((taken, probability)), Some(opposing)) =
+            (self.previous, opposing_player_actions)
+        {
+            let reward = match (&ACTIONS[taken], opposing) {
+                (Action::FINCH, Action::ATTACK) => 1.0,
+                (Action::ATTACK, Action::FINCH) => 0.0,
+                _ => 0.5,
+            };
+            self.estimates[taken] += match self.variant {
+                Exp3Variant::Exp3 => reward / probability,
+                Exp3Variant::Exp3Ix => (1.0 - reward) / (probability + self.exploration),
+            };
+        }
+
+        let probabilities = self.probabilities();
+        let action = if self.current_random.borrow_mut().random::<f64>() < probabilities[0] {
+            0
+        } else {
+            1
+        };
+        self.previous = Some((action, probabilities[action]));
+        ACTIONS[action].clone()
+    }
+
+    fn strategy_name(&self) -> String {
+        match self.variant {
+            Exp3Variant::Exp3 => format!("EXP3 with exploration {}", self.exploration),
+            Exp3Variant::Exp3Ix => format!(
+                "EXP3-IX with learning rate {}, exploration {}",
+                self.learning_rate, self.exploration
+            ),
+        }
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            current_random: self.current_random.clone(),
+            ..*self
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use crate::agents::{GameAgent, OpponentModel, opposing_hit_points};
+use crate::duel::{Action, DamageMatrix, PlayerState};
+use crate::history::HistoryView;
+
+const ACTIONS: [Action; 2] = [Action::ATTACK, Action::FINCH];
+
+// Searches `depth` turns ahead over the hit point states, taking the action
+// of highest expected value against the opponent model's prediction of every
+// future opponent action. States at the search horizon are valued by the
+// share of the remaining hit points that are the agent's own.
+pub struct ExpectimaxAgent {
+    pub model: Box<dyn OpponentModel>,
+    pub depth: usize,
+    // The damage matrix of the duel searched, seen as player one.
+    pub damage: DamageMatrix,
+}
+
+impl ExpectimaxAgent {
+    pub fn new(model: Box<dyn OpponentModel>, depth: usize) -> Self {
+        Self {
+            model,
+            depth,
+            damage: DamageMatrix::default(),
+        }
+    }
+
+    pub fn with_damage(mut self, damage: DamageMatrix) -> Self {
+        self.damage = damage;
+        self
+    }
+
+    // Expected value of the state before the opponent's `opposing_actions`.
+    fn value(
+        &self,
+        own: i64,
+        opposing: i64,
+        depth: usize,
+        opposing_actions: &mut Vec<Action>,
+    ) -> f64 {
+        match (own <= 0, opposing <= 0) {
+            (true, true) => return 0.5,
+            (true, false) => return 0.0,
+            (false, true) => return 1.0,
+            (false, false) => {}
+        }
+        if depth == 0 {
+            return own as f64 / (own + opposing) as f64;
+        }
+        ACTIONS
+            .iter()
+            .map(|action| self.action_value(action, own, opposing, depth, opposing_actions))
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    fn action_value(
+        &self,
+        action: &Action,
+        own: i64,
+        opposing: i64,
+        depth: usize,
+        opposing_actions: &mut Vec<Action>,
+    ) -> f64 {
+        let attack = self.model.attack_probability(opposing_actions);
+        ACTIONS
+            .iter()
+            .zip([attack, 1.0 - attack])
+            .filter(|(_, probability)| *probability > 0.0)
+            .map(|(response, probability)| {
+                let [own_loss, opposing_loss] = self.damage.damage(action, response);
+                opposing_actions.push(response.clone());
+                let value = self.value(
+                    own - own_loss,
+                    opposing - opposing_loss,
+                    depth - 1,
+                    opposing_actions,
+                );
+                opposing_actions.pop();
+                probability * value
+            })
+            .sum()
+    }
+}
+
+impl GameAgent for ExpectimaxAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        self.model.observe(history.opposing_actions);
+        let own = own_player_state.current_hit_points;
+        let opposing = opposing_hit_points(
+            own_player_state,
+            opposing_player_state,
+            history,
+            &self.damage,
+        );
+        let mut opposing_actions = history.opposing_actions.to_vec();
+        let attacking = self.action_value(
+            &Action::ATTACK,
+            own,
+            opposing,
+            self.depth.max(1),
+            &mut opposing_actions,
+        );
+        let finching = self.action_value(
+            &Action::FINCH,
+            own,
+            opposing,
+            self.depth.max(1),
+            &mut opposing_actions,
+        );
+        if attacking > finching {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!(
+            "Expectimax search {} turns ahead against a {} opponent model",
+            self.depth,
+            self.model.name()
+        )
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            model: self.model.copy_model(),
+            depth: self.depth,
+            damage: self.damage.clone(),
+        })
+    }
+}
@@ -0,0 +1,164 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// What the external process is sent every turn, as one line of JSON, e.g.
+//   {"turn":3,"max_hit_points":600,"own_hit_points":598,"opposing_hit_points":null,
+//    "own_stamina":null,"opposing_stamina":null,"own_action":"FINCH","opposing_action":"ATTACK"}
+// Turn 0 starts a new game. The opponent's hit points and stamina are only
+// sent when they are observable, stamina only when the rules use it. The last
+// actions are null in turn 0.
+#[derive(Serialize)]
+pub struct Observation {
+    pub turn: usize,
+    pub max_hit_points: i64,
+    pub own_hit_points: i64,
+    pub opposing_hit_points: Option<i64>,
+    pub own_stamina: Option<i64>,
+    pub opposing_stamina: Option<i64>,
+    pub own_action: Option<Action>,
+    pub opposing_action: Option<Action>,
+}
+
+impl Observation {
+    // What an agent deciding with the arguments of `GameAgent::decide_action` observes.
+    pub fn new(
+        own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Self {
+        Self {
+            turn: history.turns(),
+            max_hit_points: own_player_state.max_hit_points,
+            own_hit_points: own_player_state.current_hit_points,
+            opposing_hit_points: opposing_player_state
+                .as_ref()
+                .map(|state| state.current_hit_points),
+            own_stamina: own_player_state.stamina,
+            opposing_stamina: opposing_player_state
+                .as_ref()
+                .and_then(|state| state.stamina),
+            own_action: history.own_actions.last().cloned(),
+            opposing_action: opposing_player_actions.clone(),
+        }
+    }
+}
+
+// What the external process answers with, as one line of JSON, e.g.
+//   {"action":"ATTACK"}
+#[derive(Deserialize)]
+pub struct Decision {
+    pub action: Action,
+}
+
+struct Process {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        // The process may wait for more games forever
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// Plays by asking an arbitrary executable, e.g. a Python script, for every
+// action over its standard input and output. Every copy of the agent starts
+// its own process on its first turn, which is killed when the copy is
+// dropped; the process' standard error is passed through for debugging.
+pub struct ExternalAgent {
+    pub command: Vec<String>,
+    process: Option<Process>,
+}
+
+impl ExternalAgent {
+    // `command` is the program followed by its arguments.
+    pub fn new(command: Vec<String>) -> Self {
+        Self {
+            command,
+            process: None,
+        }
+    }
+
+    fn spawn(&self) -> std::io::Result<Process> {
+        let (program, args) = self.command.split_first().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "empty command")
+        })?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        Ok(Process {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    fn ask(&mut self, observation: &Observation) -> Result<Action, String> {
+        if self.process.is_none() {
+            self.process = Some(self.spawn().map_err(|err| err.to_string())?);
+        }
+        let process = self.process.as_mut().expect("spawned above");
+        let line = serde_json::to_string(observation).map_err(|err| err.to_string())?;
+        writeln!(process.stdin, "{}", line).map_err(|err| err.to_string())?;
+        process.stdin.flush().map_err(|err| err.to_string())?;
+
+        let mut answer = String::new();
+        if process
+            .stdout
+            .read_line(&mut answer)
+            .map_err(|err| err.to_string())?
+            == 0
+        {
+            return Err(String::from("the process exited"));
+        }
+        serde_json::from_str::<Decision>(&answer)
+            .map(|decision| decision.action)
+            .map_err(|err| format!("invalid answer '{}': {}", answer.trim(), err))
+    }
+}
+
+impl GameAgent for ExternalAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let observation = Observation::new(
+            own_player_state,
+            opposing_player_actions,
+            opposing_player_state,
+            history,
+        );
+        self.ask(&observation).unwrap_or_else(|err| {
+            panic!(
+                "external agent '{}' failed: {}",
+                self.command.join(" "),
+                err
+            )
+        })
+    }
+
+    fn strategy_name(&self) -> String {
+        format!("External process {}", self.command.join(" "))
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self::new(self.command.clone()))
+    }
+}
@@ -0,0 +1,52 @@
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// Finches until the opponent attacks for the first time, and attacks for the
+// rest of the game from then on.
+#[derive(Clone)]
+pub struct GrimTriggerAgent {
+    pub triggered: bool,
+}
+
+impl GrimTriggerAgent {
+    pub fn new() -> Self {
+        Self { triggered: false }
+    }
+}
+
+impl Default for GrimTriggerAgent {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameAgent for GrimTriggerAgent {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        if history.turns() == 0 {
+            self.triggered = false;
+        }
+        if history.opposing_actions.last() == Some(&Action::ATTACK) {
+            self.triggered = true;
+        }
+        if self.triggered {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        String::from("Grim Trigger: Finch until the opponent attacks, then always Attack")
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(self.clone())
+    }
+}
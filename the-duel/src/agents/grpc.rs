@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::grpc::{self, AgentClient, GrpcError, Observation, ResetRequest};
+use crate::history::HistoryView;
+
+// Sessions are told apart by the process and a counter.
+static SESSIONS: AtomicU64 = AtomicU64::new(0);
+
+fn new_session() -> String {
+    format!(
+        "{}-{}",
+        std::process::id(),
+        SESSIONS.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+// Plays by asking a long-lived agent service over gRPC, see
+// proto/agent.proto. Every copy of the agent plays in a session of its own,
+// which is reset before every game.
+pub struct GrpcAgent {
+    pub client: AgentClient,
+    pub remote_name: String,
+    pub session: String,
+}
+
+impl GrpcAgent {
+    // Connects right away, so that an unreachable agent fails early.
+    pub fn connect(endpoint: &str) -> Result<Self, GrpcError> {
+        let client = AgentClient::connect(endpoint)?;
+        let remote_name = client.info()?.strategy_name;
+        Ok(Self {
+            client,
+            remote_name,
+            session: new_session(),
+        })
+    }
+}
+
+impl GameAgent for GrpcAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let ask = || {
+            if history.turns() == 0 {
+                self.client.reset(ResetRequest {
+                    session: self.session.clone(),
+                    max_hit_points: own_player_state.max_hit_points,
+                })?;
+            }
+            let encode = |action: Option<&Action>| {
+                action.map_or(grpc::Action::Unspecified, grpc::Action::from) as i32
+            };
+            self.client.decide_action(Observation {
+                session: self.session.clone(),
+                turn: history.turns() as u64,
+                max_hit_points: own_player_state.max_hit_points,
+                own_hit_points: own_player_state.current_hit_points,
+                opposing_hit_points: opposing_player_state
+                    .as_ref()
+                    .map(|state| state.current_hit_points),
+                own_action: encode(history.own_actions.last()),
+                opposing_action: encode(opposing_player_actions.as_ref()),
+                own_stamina: own_player_state.stamina,
+                opposing_stamina: opposing_player_state
+                    .as_ref()
+                    .and_then(|state| state.stamina),
+            })
+        };
+        ask().unwrap_or_else(|err| panic!("gRPC agent {} failed: {}", self.client.endpoint, err))
+    }
+
+    fn strategy_name(&self) -> String {
+        format!("{} at {}", self.remote_name, self.client.endpoint)
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            client: self.client.clone(),
+            remote_name: self.remote_name.clone(),
+            session: new_session(),
+        })
+    }
+}
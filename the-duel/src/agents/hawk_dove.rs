@@ -0,0 +1,123 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::GameAgent;
+use crate::hawk_dove::{Contestant, HawkDove, HawkDovePayoffs, Posture};
+use crate::history::HistoryView;
+
+// Maynard Smith's strategies of the contest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HawkDoveStrategy {
+    Hawk,
+    Dove,
+    // Plays hawk with the share of hawks in the ESS of the payoffs.
+    Ess,
+    // Plays hawk with the given probability.
+    Mixed(f64),
+    // Plays dove, but escalates against an opponent who did.
+    Retaliator,
+    // Plays hawk, but retreats from an opponent who escalated.
+    Bully,
+}
+
+impl HawkDoveStrategy {
+    pub const ALL: [HawkDoveStrategy; 5] = [
+        HawkDoveStrategy::Hawk,
+        HawkDoveStrategy::Dove,
+        HawkDoveStrategy::Ess,
+        HawkDoveStrategy::Retaliator,
+        HawkDoveStrategy::Bully,
+    ];
+
+    // Parses names like `retaliator` or `mixed(0.3)`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (name, argument) = match spec.split_once('(') {
+            Some((name, rest)) => (name.trim(), Some(rest.strip_suffix(')')?.trim())),
+            None => (spec.trim(), None),
+        };
+        match (name, argument) {
+            ("hawk", None) => Some(HawkDoveStrategy::Hawk),
+            ("dove", None) => Some(HawkDoveStrategy::Dove),
+            ("ess", None) => Some(HawkDoveStrategy::Ess),
+            ("retaliator", None) => Some(HawkDoveStrategy::Retaliator),
+            ("bully", None) => Some(HawkDoveStrategy::Bully),
+            ("mixed", Some(argument)) => argument
+                .parse()
+                .ok()
+                .filter(|p| (0.0..=1.0).contains(p))
+                .map(HawkDoveStrategy::Mixed),
+            _ => None,
+        }
+    }
+}
+
+pub struct HawkDoveAgent<T: Rng + 'static> {
+    pub strategy: HawkDoveStrategy,
+    pub payoffs: HawkDovePayoffs,
+    pub current_random: Rc<RefCell<T>>,
+}
+
+impl<T: Rng> HawkDoveAgent<T> {
+    pub fn new(
+        strategy: HawkDoveStrategy,
+        payoffs: HawkDovePayoffs,
+        current_random: Rc<RefCell<T>>,
+    ) -> Self {
+        Self {
+            strategy,
+            payoffs,
+            current_random,
+        }
+    }
+
+    fn hawk_with(&self, probability: f64) -> Posture {
+        if self.current_random.borrow_mut().random_bool(probability) {
+            Posture::HAWK
+        } else {
+            Posture::DOVE
+        }
+    }
+}
+
+impl<T: Rng> GameAgent<HawkDove> for HawkDoveAgent<T> {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &Contestant,
+        _opposing_player_actions: &Option<Posture>,
+        _opposing_player_state: &Option<Contestant>,
+        history: &HistoryView<HawkDove>,
+    ) -> Posture {
+        let escalated = history.opposing_actions.last() == Some(&Posture::HAWK);
+        match self.strategy {
+            HawkDoveStrategy::Hawk => Posture::HAWK,
+            HawkDoveStrategy::Dove => Posture::DOVE,
+            HawkDoveStrategy::Ess => self.hawk_with(self.payoffs.ess()),
+            HawkDoveStrategy::Mixed(p) => self.hawk_with(p),
+            HawkDoveStrategy::Retaliator if escalated => Posture::HAWK,
+            HawkDoveStrategy::Retaliator => Posture::DOVE,
+            HawkDoveStrategy::Bully if escalated => Posture::DOVE,
+            HawkDoveStrategy::Bully => Posture::HAWK,
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        match self.strategy {
+            HawkDoveStrategy::Hawk => String::from("Hawk"),
+            HawkDoveStrategy::Dove => String::from("Dove"),
+            HawkDoveStrategy::Ess => String::from("ESS mix"),
+            HawkDoveStrategy::Mixed(p) => format!("Hawk with probability {}", p),
+            HawkDoveStrategy::Retaliator => String::from("Retaliator"),
+            HawkDoveStrategy::Bully => String::from("Bully"),
+        }
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent<HawkDove>> {
+        Box::new(Self::new(
+            self.strategy,
+            self.payoffs.clone(),
+            self.current_random.clone(),
+        ))
+    }
+}
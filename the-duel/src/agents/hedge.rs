@@ -0,0 +1,128 @@
+use crate::agents::{GameAgent, NGramModel, OpponentModel};
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// Predicts the opponent's next action by the Hedge algorithm over a set of
+// experts: the frequency, Markov and order-k Markov models, and the
+// assumption that the opponent mirrors the agent's last action. Every turn,
+// each expert's weight shrinks exponentially in the absolute error of its
+// last prediction, and the agent plays the optimal one-step decision against
+// the weighted prediction.
+pub struct HedgeAgent {
+    pub models: Vec<Box<dyn OpponentModel>>,
+    pub learning_rate: f64,
+    // Weights of the models followed by the mirror assumption.
+    pub weights: Vec<f64>,
+    pub predictions: Vec<f64>,
+    pub cost_losing_hp: f64,
+    pub cost_not_losing_hp: f64,
+    pub cost_equivalent_exchange: f64,
+}
+
+impl HedgeAgent {
+    pub fn new(
+        learning_rate: f64,
+        order: usize,
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+    ) -> Self {
+        let models: Vec<Box<dyn OpponentModel>> = vec![
+            Box::new(NGramModel::new(0)),
+            Box::new(NGramModel::new(1)),
+            Box::new(NGramModel::new(order.max(2))),
+        ];
+        let experts = models.len() + 1;
+        Self {
+            models,
+            learning_rate,
+            weights: vec![1.0 / experts as f64; experts],
+            predictions: Vec::new(),
+            cost_losing_hp,
+            cost_not_losing_hp,
+            cost_equivalent_exchange,
+        }
+    }
+
+    pub fn expert_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.models.iter().map(|m| m.name()).collect();
+        names.push(String::from("mirror"));
+        names
+    }
+
+    fn reweigh(&mut self, latest: &Action) {
+        let outcome = if *latest == Action::ATTACK { 1.0 } else { 0.0 };
+        for (weight, prediction) in self.weights.iter_mut().zip(&self.predictions) {
+            *weight *= (-self.learning_rate * (prediction - outcome).abs()).exp();
+        }
+        // Keeps the weights from underflowing over long games
+        let total: f64 = self.weights.iter().sum();
+        if total > 0.0 {
+            self.weights.iter_mut().for_each(|w| *w /= total);
+        }
+    }
+}
+
+impl GameAgent for HedgeAgent {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        if let Some(latest) = history.opposing_actions.last()
+            && !self.predictions.is_empty()
+        {
+            self.reweigh(latest);
+        }
+        for model in self.models.iter_mut() {
+            model.observe(history.opposing_actions);
+        }
+        let mirror = match history.own_actions.last() {
+            Some(Action::ATTACK) => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+        self.predictions = self
+            .models
+            .iter()
+            .map(|m| m.attack_probability(history.opposing_actions))
+            .chain([mirror])
+            .collect();
+        let prob: f64 = self
+            .weights
+            .iter()
+            .zip(&self.predictions)
+            .map(|(w, p)| w * p)
+            .sum::<f64>()
+            / self.weights.iter().sum::<f64>();
+
+        let attack_reward =
+            self.cost_losing_hp * (1.0 - prob) + self.cost_equivalent_exchange * prob;
+        let finch_reward =
+            self.cost_not_losing_hp * prob + self.cost_equivalent_exchange * (1.0 - prob);
+
+        if attack_reward > finch_reward {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!(
+            "Predict the next action by Hedge over {} experts, and design optimal one-step decision.",
+            self.expert_names().join(", ")
+        )
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            models: self.models.iter().map(|m| m.copy_model()).collect(),
+            weights: self.weights.clone(),
+            predictions: self.predictions.clone(),
+            ..*self
+        })
+    }
+}
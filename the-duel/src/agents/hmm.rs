@@ -0,0 +1,74 @@
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+use crate::hmm::HiddenMarkovModel;
+
+// Fits a two-state hidden Markov model to the opponent's actions of the
+// current game every `refit_interval` turns, each time continuing from the
+// previous fit, and plays the optimal one-step decision against the model's
+// prediction of the next action.
+#[derive(Clone)]
+pub struct HmmAgent {
+    pub model: HiddenMarkovModel,
+    pub refit_interval: usize,
+    pub iterations: usize,
+    pub cost_losing_hp: f64,
+    pub cost_not_losing_hp: f64,
+    pub cost_equivalent_exchange: f64,
+}
+
+impl HmmAgent {
+    pub fn new(
+        refit_interval: usize,
+        iterations: usize,
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+    ) -> Self {
+        Self {
+            model: HiddenMarkovModel::new(),
+            refit_interval: refit_interval.max(1),
+            iterations,
+            cost_losing_hp,
+            cost_not_losing_hp,
+            cost_equivalent_exchange,
+        }
+    }
+}
+
+impl GameAgent for HmmAgent {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let turns = history.turns();
+        if turns > 0 && turns.is_multiple_of(self.refit_interval) {
+            self.model.fit(history.opposing_actions, self.iterations);
+        }
+        let prob = self.model.attack_probability(history.opposing_actions);
+
+        let attack_reward =
+            self.cost_losing_hp * (1.0 - prob) + self.cost_equivalent_exchange * prob;
+        let finch_reward =
+            self.cost_not_losing_hp * prob + self.cost_equivalent_exchange * (1.0 - prob);
+
+        if attack_reward > finch_reward {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        String::from(
+            "Fit a hidden Markov model to the opposing actions, and design optimal one-step decision.",
+        )
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,80 @@
+use std::io::{self, BufRead, Write};
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// Asks a person on the terminal for every action, showing the hit points and
+// the opponent's last action, e.g. with
+//   the-duel duel human "markov(0.3, 0.6)"
+#[derive(Clone, Default)]
+pub struct HumanAgent;
+
+impl HumanAgent {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn parse(input: &str) -> Option<Action> {
+        match input.trim().to_lowercase().as_str() {
+            "a" | "attack" => Some(Action::ATTACK),
+            "f" | "finch" => Some(Action::FINCH),
+            _ => None,
+        }
+    }
+}
+
+impl GameAgent for HumanAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        println!();
+        println!(
+            "Turn {}: you have {}/{} HP",
+            history.turns() + 1,
+            own_player_state.current_hit_points,
+            own_player_state.max_hit_points
+        );
+        if let Some(opposing) = opposing_player_state {
+            println!(
+                " your opponent has {}/{} HP",
+                opposing.current_hit_points, opposing.max_hit_points
+            );
+        }
+        match opposing_player_actions {
+            Some(action) => println!(" your opponent's last action was {:?}", action),
+            None => println!(" your opponent has not acted yet"),
+        }
+
+        let stdin = io::stdin();
+        let mut line = String::new();
+        loop {
+            print!("[a]ttack or [f]inch? ");
+            io::stdout().flush().unwrap();
+            line.clear();
+            let read = stdin
+                .lock()
+                .read_line(&mut line)
+                .unwrap_or_else(|err| panic!("could not read the action: {}", err));
+            if read == 0 {
+                panic!("standard input closed before the game was decided");
+            }
+            if let Some(action) = Self::parse(&line) {
+                return action;
+            }
+            println!("'{}' is neither an attack nor a finch", line.trim());
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        String::from("Human at the terminal")
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(self.clone())
+    }
+}
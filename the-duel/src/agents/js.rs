@@ -0,0 +1,61 @@
+use js_sys::{Function, JSON};
+use wasm_bindgen::JsValue;
+
+use crate::agents::{GameAgent, Observation};
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// Plays by calling a JavaScript function for every action. The function gets
+// the same observation an external process is sent, as an object, and returns
+// "ATTACK" or "FINCH".
+pub struct JsAgent {
+    pub name: String,
+    decide: Function,
+}
+
+impl JsAgent {
+    pub fn new(name: String, decide: Function) -> Self {
+        Self { name, decide }
+    }
+
+    fn ask(&self, observation: &Observation) -> Result<Action, String> {
+        let text = serde_json::to_string(observation).map_err(|err| err.to_string())?;
+        let argument = JSON::parse(&text).map_err(|err| format!("{:?}", err))?;
+        let answer = self
+            .decide
+            .call1(&JsValue::NULL, &argument)
+            .map_err(|err| format!("{:?}", err))?;
+        match answer.as_string().map(|name| name.to_ascii_uppercase()) {
+            Some(name) if name == "ATTACK" => Ok(Action::ATTACK),
+            Some(name) if name == "FINCH" => Ok(Action::FINCH),
+            _ => Err(format!("invalid answer {:?}", answer)),
+        }
+    }
+}
+
+impl GameAgent for JsAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let observation = Observation::new(
+            own_player_state,
+            opposing_player_actions,
+            opposing_player_state,
+            history,
+        );
+        self.ask(&observation)
+            .unwrap_or_else(|err| panic!("JavaScript agent '{}' failed: {}", self.name, err))
+    }
+
+    fn strategy_name(&self) -> String {
+        format!("JavaScript agent {}", self.name)
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self::new(self.name.clone(), self.decide.clone()))
+    }
+}
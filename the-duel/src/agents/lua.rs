@@ -0,0 +1,112 @@
+use std::fs;
+
+use mlua::{Function, Lua, Table};
+use rand::Rng;
+
+use crate::agents::{GameAgent, Observation, SharedRng};
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// Plays by a Lua script defining a global `decide_action(observation)`,
+// which gets the same observation an external process is sent, as a table
+// with nil for missing values, and returns "ATTACK" or "FINCH". Scripts draw
+// random numbers from the experiment's generator by calling `random()`,
+// which returns a number in [0, 1). Every copy of the agent runs the script
+// in a fresh interpreter, so globals do not leak between games played by
+// different copies.
+pub struct LuaAgent {
+    pub path: String,
+    source: String,
+    rng: SharedRng,
+    lua: Lua,
+}
+
+impl LuaAgent {
+    pub fn load(rng: SharedRng, path: &str) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|err| err.to_string())?;
+        Self::from_source(rng, path, source)
+    }
+
+    fn from_source(rng: SharedRng, path: &str, source: String) -> Result<Self, String> {
+        let lua = Lua::new();
+        let random_rng = rng.clone();
+        let random = lua
+            .create_function(move |_, ()| Ok(random_rng.borrow_mut().random::<f64>()))
+            .map_err(|err| err.to_string())?;
+        lua.globals()
+            .set("random", random)
+            .map_err(|err| err.to_string())?;
+        lua.load(&source)
+            .set_name(path)
+            .exec()
+            .map_err(|err| err.to_string())?;
+        lua.globals()
+            .get::<Function>("decide_action")
+            .map_err(|_| String::from("the script defines no function decide_action"))?;
+        Ok(Self {
+            path: path.to_string(),
+            source,
+            rng,
+            lua,
+        })
+    }
+
+    fn ask(&self, observation: &Observation) -> mlua::Result<Action> {
+        let table: Table = self.lua.create_table()?;
+        table.set("turn", observation.turn)?;
+        table.set("max_hit_points", observation.max_hit_points)?;
+        table.set("own_hit_points", observation.own_hit_points)?;
+        table.set("opposing_hit_points", observation.opposing_hit_points)?;
+        table.set("own_stamina", observation.own_stamina)?;
+        table.set("opposing_stamina", observation.opposing_stamina)?;
+        table.set(
+            "own_action",
+            observation.own_action.as_ref().map(|a| format!("{:?}", a)),
+        )?;
+        table.set(
+            "opposing_action",
+            observation
+                .opposing_action
+                .as_ref()
+                .map(|a| format!("{:?}", a)),
+        )?;
+        let decide: Function = self.lua.globals().get("decide_action")?;
+        let answer: String = decide.call(table)?;
+        match answer.to_ascii_uppercase().as_str() {
+            "ATTACK" => Ok(Action::ATTACK),
+            "FINCH" => Ok(Action::FINCH),
+            _ => Err(mlua::Error::runtime(format!("invalid answer '{}'", answer))),
+        }
+    }
+}
+
+impl GameAgent for LuaAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let observation = Observation::new(
+            own_player_state,
+            opposing_player_actions,
+            opposing_player_state,
+            history,
+        );
+        self.ask(&observation)
+            .unwrap_or_else(|err| panic!("Lua agent '{}' failed: {}", self.path, err))
+    }
+
+    fn strategy_name(&self) -> String {
+        format!("Lua script {}", self.path)
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        // The script already ran once, so it runs again
+        Box::new(
+            Self::from_source(self.rng.clone(), &self.path, self.source.clone())
+                .unwrap_or_else(|err| panic!("Lua agent '{}' failed: {}", self.path, err)),
+        )
+    }
+}
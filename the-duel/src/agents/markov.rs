@@ -0,0 +1,81 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+#[derive(Clone)]
+pub struct MarkovRandomAgent<T: Rng + 'static> {
+    pub current_random: Rc<RefCell<T>>,
+    pub change_to_attack_prob: f64,
+    pub change_to_finch_prob: f64,
+    pub current_strategy: Action,
+}
+
+impl<T: Rng> MarkovRandomAgent<T> {
+    pub fn new(
+        current_random: Rc<RefCell<T>>,
+        change_to_attack_prob: f64,
+        change_to_finch_prob: f64,
+        current_strategy: Action,
+    ) -> Self {
+        Self {
+            current_random,
+            change_to_attack_prob,
+            change_to_finch_prob,
+            current_strategy,
+        }
+    }
+}
+
+impl<T: Rng + 'static> GameAgent for MarkovRandomAgent<T> {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        _history: &HistoryView,
+    ) -> Action {
+        match self.current_strategy {
+            Action::ATTACK => {
+                let decision = self
+                    .current_random
+                    .borrow_mut()
+                    .random_bool(self.change_to_finch_prob);
+                if decision {
+                    self.current_strategy = Action::FINCH;
+                }
+            }
+            Action::FINCH => {
+                let decision = self
+                    .current_random
+                    .borrow_mut()
+                    .random_bool(self.change_to_attack_prob);
+                if decision {
+                    self.current_strategy = Action::ATTACK;
+                }
+            }
+        }
+
+        self.current_strategy.clone()
+    }
+
+    fn strategy_name(&self) -> String {
+        format!(
+            "Markov Chain with probabilities {}, {}",
+            self.change_to_attack_prob, self.change_to_finch_prob
+        )
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            current_random: self.current_random.clone(),
+            change_to_attack_prob: self.change_to_attack_prob,
+            change_to_finch_prob: self.change_to_finch_prob,
+            current_strategy: self.current_strategy.clone(),
+        })
+    }
+}
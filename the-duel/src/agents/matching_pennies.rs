@@ -0,0 +1,164 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::GameAgent;
+use crate::history::HistoryView;
+use crate::matching_pennies::{Coin, MatchingPennies, PenniesPayoffs, Penny};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PenniesStrategy {
+    // Always shows the same side.
+    Constant(Penny),
+    // Shows heads and tails in turn.
+    Alternate,
+    // Shows heads with the equilibrium probability of its role.
+    Equilibrium,
+    // Fictitious play: best response to the frequencies of all the
+    // opponent's sides so far.
+    FictitiousPlay,
+    // Regret matching: shows every side in proportion to how much more it
+    // would have scored by always showing it so far.
+    RegretMatching,
+}
+
+impl PenniesStrategy {
+    pub const ALL: [PenniesStrategy; 6] = [
+        PenniesStrategy::Constant(Penny::HEADS),
+        PenniesStrategy::Constant(Penny::TAILS),
+        PenniesStrategy::Alternate,
+        PenniesStrategy::Equilibrium,
+        PenniesStrategy::FictitiousPlay,
+        PenniesStrategy::RegretMatching,
+    ];
+
+    pub fn parse(spec: &str) -> Option<Self> {
+        match spec.trim() {
+            "heads" => Some(PenniesStrategy::Constant(Penny::HEADS)),
+            "tails" => Some(PenniesStrategy::Constant(Penny::TAILS)),
+            "alternate" => Some(PenniesStrategy::Alternate),
+            "equilibrium" => Some(PenniesStrategy::Equilibrium),
+            "fictitious_play" => Some(PenniesStrategy::FictitiousPlay),
+            "regret_matching" => Some(PenniesStrategy::RegretMatching),
+            _ => None,
+        }
+    }
+}
+
+pub struct PenniesAgent<T: Rng + 'static> {
+    pub strategy: PenniesStrategy,
+    pub payoffs: PenniesPayoffs,
+    pub current_random: Rc<RefCell<T>>,
+}
+
+impl<T: Rng> PenniesAgent<T> {
+    pub fn new(
+        strategy: PenniesStrategy,
+        payoffs: PenniesPayoffs,
+        current_random: Rc<RefCell<T>>,
+    ) -> Self {
+        Self {
+            strategy,
+            payoffs,
+            current_random,
+        }
+    }
+
+    // The player's own payoff, whichever role it has.
+    fn payoff(&self, own_state: &Coin, own: &Penny, opposing: &Penny) -> f64 {
+        if own_state.matcher {
+            self.payoffs.payoff(own, opposing) as f64
+        } else {
+            -self.payoffs.payoff(opposing, own) as f64
+        }
+    }
+
+    fn heads_with(&self, probability: f64) -> Penny {
+        if self.current_random.borrow_mut().random_bool(probability) {
+            Penny::HEADS
+        } else {
+            Penny::TAILS
+        }
+    }
+
+    fn equilibrium_penny(&self, own_state: &Coin) -> Penny {
+        let equilibrium = self.payoffs.equilibrium();
+        if own_state.matcher {
+            self.heads_with(equilibrium.matcher_heads)
+        } else {
+            self.heads_with(equilibrium.mismatcher_heads)
+        }
+    }
+}
+
+impl<T: Rng> GameAgent<MatchingPennies> for PenniesAgent<T> {
+    fn decide_action(
+        &mut self,
+        own_player_state: &Coin,
+        _opposing_player_actions: &Option<Penny>,
+        _opposing_player_state: &Option<Coin>,
+        history: &HistoryView<MatchingPennies>,
+    ) -> Penny {
+        let opposing = history.opposing_actions;
+        match self.strategy {
+            PenniesStrategy::Constant(penny) => penny,
+            PenniesStrategy::Alternate => Penny::ALL[history.turns() % Penny::ALL.len()],
+            PenniesStrategy::Equilibrium => self.equilibrium_penny(own_player_state),
+            PenniesStrategy::FictitiousPlay => {
+                if opposing.is_empty() {
+                    return self.equilibrium_penny(own_player_state);
+                }
+                let total = |own: &Penny| -> f64 {
+                    opposing
+                        .iter()
+                        .map(|side| self.payoff(own_player_state, own, side))
+                        .sum()
+                };
+                if total(&Penny::HEADS) >= total(&Penny::TAILS) {
+                    Penny::HEADS
+                } else {
+                    Penny::TAILS
+                }
+            }
+            PenniesStrategy::RegretMatching => {
+                let regret = |own: &Penny| -> f64 {
+                    history
+                        .own_actions
+                        .iter()
+                        .zip(opposing)
+                        .map(|(played, side)| {
+                            self.payoff(own_player_state, own, side)
+                                - self.payoff(own_player_state, played, side)
+                        })
+                        .sum::<f64>()
+                        .max(0.0)
+                };
+                let (heads, tails) = (regret(&Penny::HEADS), regret(&Penny::TAILS));
+                if heads + tails > 0.0 {
+                    self.heads_with(heads / (heads + tails))
+                } else {
+                    self.heads_with(0.5)
+                }
+            }
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        match self.strategy {
+            PenniesStrategy::Constant(penny) => format!("Always {}", penny),
+            PenniesStrategy::Alternate => String::from("Alternate"),
+            PenniesStrategy::Equilibrium => String::from("Equilibrium mix"),
+            PenniesStrategy::FictitiousPlay => String::from("Fictitious play"),
+            PenniesStrategy::RegretMatching => String::from("Regret matching"),
+        }
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent<MatchingPennies>> {
+        Box::new(Self::new(
+            self.strategy,
+            self.payoffs.clone(),
+            self.current_random.clone(),
+        ))
+    }
+}
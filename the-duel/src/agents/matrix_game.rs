@@ -0,0 +1,194 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::GameAgent;
+use crate::history::HistoryView;
+use crate::matrix_game::{MatrixGame, MatrixPlayer, Strategy};
+
+// Strategies that play any matrix game, knowing only its payoffs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatrixStrategy {
+    // Always plays the strategy of the given index.
+    Constant(usize),
+    // Draws every strategy uniformly.
+    Random,
+    // Plays the index of the opponent's last strategy, like tit-for-tat in
+    // games whose players share their strategies.
+    Mirror,
+    // Best response to the opponent's last strategy.
+    BestResponse,
+    // Best response to the frequencies of all the opponent's strategies.
+    FictitiousPlay,
+    // Plays every strategy in proportion to how much more it would have
+    // scored than the strategies played so far.
+    RegretMatching,
+}
+
+impl MatrixStrategy {
+    // The strategies playing any game, without the constant ones.
+    pub const ADAPTIVE: [MatrixStrategy; 5] = [
+        MatrixStrategy::Random,
+        MatrixStrategy::Mirror,
+        MatrixStrategy::BestResponse,
+        MatrixStrategy::FictitiousPlay,
+        MatrixStrategy::RegretMatching,
+    ];
+
+    // Parses names like `fictitious_play` or `constant(1)`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (name, argument) = match spec.split_once('(') {
+            Some((name, rest)) => (name.trim(), Some(rest.strip_suffix(')')?.trim())),
+            None => (spec.trim(), None),
+        };
+        match (name, argument) {
+            ("constant", Some(argument)) => argument.parse().ok().map(MatrixStrategy::Constant),
+            ("random", None) => Some(MatrixStrategy::Random),
+            ("mirror", None) => Some(MatrixStrategy::Mirror),
+            ("best_response", None) => Some(MatrixStrategy::BestResponse),
+            ("fictitious_play", None) => Some(MatrixStrategy::FictitiousPlay),
+            ("regret_matching", None) => Some(MatrixStrategy::RegretMatching),
+            _ => None,
+        }
+    }
+
+    // Whether the strategy can play either side of `game`.
+    pub fn validate(&self, game: &MatrixGame) -> Result<(), String> {
+        match self {
+            MatrixStrategy::Constant(index)
+                if *index >= game.num_strategies(true).min(game.num_strategies(false)) =>
+            {
+                Err(format!(
+                    "constant({}) is out of range, the game has {} rows and {} columns",
+                    index,
+                    game.num_strategies(true),
+                    game.num_strategies(false)
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct MatrixAgent<T: Rng + 'static> {
+    pub strategy: MatrixStrategy,
+    pub game: MatrixGame,
+    pub current_random: Rc<RefCell<T>>,
+}
+
+impl<T: Rng> MatrixAgent<T> {
+    pub fn new(strategy: MatrixStrategy, game: MatrixGame, current_random: Rc<RefCell<T>>) -> Self {
+        Self {
+            strategy,
+            game,
+            current_random,
+        }
+    }
+
+    fn random(&self, count: usize) -> Strategy {
+        Strategy(self.current_random.borrow_mut().random_range(0..count))
+    }
+
+    // The strategy scoring most against `opposing`, the first of equals.
+    fn best_response<'a>(
+        &self,
+        picks_rows: bool,
+        opposing: impl Iterator<Item = &'a Strategy> + Clone,
+    ) -> Strategy {
+        let total = |own: usize| -> f64 {
+            opposing
+                .clone()
+                .map(|other| self.game.payoff(picks_rows, Strategy(own), *other))
+                .sum()
+        };
+        let best = (0..self.game.num_strategies(picks_rows))
+            .rev()
+            .max_by(|a, b| total(*a).total_cmp(&total(*b)))
+            .unwrap_or(0);
+        Strategy(best)
+    }
+}
+
+impl<T: Rng> GameAgent<MatrixGame> for MatrixAgent<T> {
+    fn decide_action(
+        &mut self,
+        own_player_state: &MatrixPlayer,
+        _opposing_player_actions: &Option<Strategy>,
+        _opposing_player_state: &Option<MatrixPlayer>,
+        history: &HistoryView<MatrixGame>,
+    ) -> Strategy {
+        let picks_rows = own_player_state.picks_rows;
+        let count = self.game.num_strategies(picks_rows);
+        let opposing = history.opposing_actions;
+        match self.strategy {
+            MatrixStrategy::Constant(index) => Strategy(index),
+            MatrixStrategy::Random => self.random(count),
+            MatrixStrategy::Mirror => match opposing.last() {
+                Some(last) => Strategy(last.0.min(count - 1)),
+                None => Strategy(0),
+            },
+            MatrixStrategy::BestResponse => match opposing.last() {
+                Some(last) => self.best_response(picks_rows, std::iter::once(last)),
+                None => self.random(count),
+            },
+            MatrixStrategy::FictitiousPlay if opposing.is_empty() => self.random(count),
+            MatrixStrategy::FictitiousPlay => self.best_response(picks_rows, opposing.iter()),
+            MatrixStrategy::RegretMatching => {
+                let regrets: Vec<f64> = (0..count)
+                    .map(|own| {
+                        history
+                            .own_actions
+                            .iter()
+                            .zip(opposing)
+                            .map(|(played, other)| {
+                                self.game.payoff(picks_rows, Strategy(own), *other)
+                                    - self.game.payoff(picks_rows, *played, *other)
+                            })
+                            .sum::<f64>()
+                            .max(0.0)
+                    })
+                    .collect();
+                let total: f64 = regrets.iter().sum();
+                if total <= 0.0 {
+                    return self.random(count);
+                }
+                let mut draw = self.current_random.borrow_mut().random::<f64>() * total;
+                for (own, regret) in regrets.iter().enumerate() {
+                    if draw < *regret {
+                        return Strategy(own);
+                    }
+                    draw -= regret;
+                }
+                Strategy(count - 1)
+            }
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        match self.strategy {
+            MatrixStrategy::Constant(index) => {
+                let row = &self.game.rows[index.min(self.game.rows.len() - 1)];
+                let column = &self.game.columns[index.min(self.game.columns.len() - 1)];
+                if row == column {
+                    format!("Always {}", row)
+                } else {
+                    format!("Always {} / {}", row, column)
+                }
+            }
+            MatrixStrategy::Random => String::from("Random"),
+            MatrixStrategy::Mirror => String::from("Mirror"),
+            MatrixStrategy::BestResponse => String::from("Best response to last"),
+            MatrixStrategy::FictitiousPlay => String::from("Fictitious play"),
+            MatrixStrategy::RegretMatching => String::from("Regret matching"),
+        }
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent<MatrixGame>> {
+        Box::new(Self::new(
+            self.strategy,
+            self.game.clone(),
+            self.current_random.clone(),
+        ))
+    }
+}
@@ -0,0 +1,30 @@
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+#[derive(Clone)]
+pub struct MirrorAgent;
+
+impl GameAgent for MirrorAgent {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        _history: &HistoryView,
+    ) -> Action {
+        if let Some(action) = opposing_player_actions {
+            action.clone()
+        } else {
+            Action::ATTACK
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        String::from("Always Mirror the opposing action")
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {})
+    }
+}
@@ -0,0 +1,184 @@
+mod arena;
+mod attack;
+mod cfr;
+mod dynamic;
+mod exp3;
+mod expectimax;
+mod external;
+mod grim_trigger;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod hawk_dove;
+mod hedge;
+mod hmm;
+mod human;
+#[cfg(feature = "wasm")]
+mod js;
+#[cfg(feature = "lua")]
+mod lua;
+mod markov;
+mod matching_pennies;
+mod matrix_game;
+mod mirror;
+mod model;
+#[cfg(feature = "neural")]
+mod neural;
+mod one_step;
+#[cfg(feature = "onnx")]
+mod onnx;
+mod particle;
+mod pattern;
+mod pavlov;
+mod portfolio;
+mod predictor;
+mod prisoners_dilemma;
+mod public_goods;
+#[cfg(feature = "python")]
+mod python;
+mod random;
+mod rock_paper_scissors;
+mod rule;
+mod td;
+mod thompson;
+mod tit_for_tat;
+mod ultimatum;
+
+pub use arena::{RandomFighter, TacticianAgent};
+pub use attack::AttackAgent;
+pub use cfr::CfrAgent;
+pub use dynamic::{AttackModel, DuelPolicy, DynamicProgrammingAgent};
+pub use exp3::{Exp3Agent, Exp3Variant};
+pub use expectimax::ExpectimaxAgent;
+pub use external::{Decision, ExternalAgent, Observation};
+pub use grim_trigger::GrimTriggerAgent;
+#[cfg(feature = "grpc")]
+pub use grpc::GrpcAgent;
+pub use hawk_dove::{HawkDoveAgent, HawkDoveStrategy};
+pub use hedge::HedgeAgent;
+pub use hmm::HmmAgent;
+pub use human::HumanAgent;
+#[cfg(feature = "wasm")]
+pub use js::JsAgent;
+#[cfg(feature = "lua")]
+pub use lua::LuaAgent;
+pub use markov::MarkovRandomAgent;
+pub use matching_pennies::{PenniesAgent, PenniesStrategy};
+pub use matrix_game::{MatrixAgent, MatrixStrategy};
+pub use mirror::MirrorAgent;
+pub use model::{ModelKind, NGramModel, OpponentModel};
+#[cfg(feature = "neural")]
+pub use neural::NeuralAgent;
+pub use one_step::{Estimator, OneStepDecisionProcessAgent};
+#[cfg(feature = "onnx")]
+pub use onnx::{OnnxAgent, OnnxPlan};
+pub use particle::{Particle, ParticleFilterAgent};
+pub use pattern::PatternMatchingAgent;
+pub use pavlov::PavlovAgent;
+pub use portfolio::PortfolioAgent;
+pub use predictor::MarkovPredictorAgent;
+pub use prisoners_dilemma::{PrisonerAgent, PrisonerStrategy};
+pub use public_goods::{PublicGoodsAgent, PublicGoodsStrategy};
+#[cfg(feature = "python")]
+pub use python::PythonAgent;
+pub use random::RandomAgent;
+pub use rock_paper_scissors::{RpsAgent, RpsStrategy};
+pub use rule::RuleAgent;
+pub use td::{DuelState, QTable, TdAgent, TdRule};
+pub use thompson::ThompsonSamplingAgent;
+pub use tit_for_tat::TitForTatAgent;
+pub use ultimatum::{UltimatumAgent, UltimatumStrategy};
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::duel::{DamageMatrix, Duel, PlayerState};
+use crate::game::{AgentMode, SimultaneousGame};
+use crate::history::HistoryView;
+use crate::rng_audit::AuditedRng;
+
+// The random number generator shared by all agents of an experiment.
+pub type SharedRng = Rc<RefCell<AuditedRng>>;
+
+// Why an agent chose its last action: the quantities it believed in, e.g.
+// its estimate `p` of the opponent's probability of attack, and the expected
+// rewards of the actions it compared, by action name.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DecisionExplanation {
+    pub beliefs: Vec<(String, f64)>,
+    pub expected_rewards: Vec<(String, f64)>,
+}
+
+impl fmt::Display for DecisionExplanation {
+    // e.g. `p=0.420; ATTACK -1.160, FINCH -1.580`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let beliefs: Vec<String> = self
+            .beliefs
+            .iter()
+            .map(|(name, value)| format!("{}={:.3}", name, value))
+            .collect();
+        let rewards: Vec<String> = self
+            .expected_rewards
+            .iter()
+            .map(|(action, reward)| format!("{} {:.3}", action, reward))
+            .collect();
+        match (beliefs.is_empty(), rewards.is_empty()) {
+            (false, false) => write!(f, "{}; {}", beliefs.join(", "), rewards.join(", ")),
+            _ => write!(f, "{}{}", beliefs.join(", "), rewards.join(", ")),
+        }
+    }
+}
+
+pub trait GameAgent<G: SimultaneousGame = Duel> {
+    fn decide_action(
+        &mut self,
+        own_player_state: &G::PlayerState,
+        opposing_player_actions: &Option<G::Action>,
+        opposing_player_state: &Option<G::PlayerState>,
+        history: &HistoryView<G>,
+    ) -> G::Action;
+
+    fn strategy_name(&self) -> String;
+
+    // Called by the engine before every game. Learning agents only change
+    // what they carry over between games in `AgentMode::Train`.
+    fn set_mode(&mut self, _mode: AgentMode) {}
+
+    // Why the agent chose the action it decided on last, for agents that
+    // can tell. The engine reports it to observers after every decision.
+    fn explain_last_decision(&self) -> Option<DecisionExplanation> {
+        None
+    }
+
+    // What the agent currently believes about its opponent, e.g. its
+    // estimate of the probability of attack, by name.
+    fn belief_snapshot(&self) -> Vec<(String, f64)> {
+        Vec::new()
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent<G>>;
+}
+
+// The opponent's hit points, recounted from the actions so far by `damage`,
+// seen as player one, if hidden.
+pub(crate) fn opposing_hit_points(
+    own_player_state: &PlayerState,
+    opposing_player_state: &Option<PlayerState>,
+    history: &HistoryView,
+    damage: &DamageMatrix,
+) -> i64 {
+    match opposing_player_state {
+        Some(state) => state.current_hit_points,
+        None => {
+            let dealt: i64 = history
+                .own_actions
+                .iter()
+                .zip(history.opposing_actions)
+                .map(|(own, opposing)| damage.damage(own, opposing)[1])
+                .sum();
+            own_player_state.max_hit_points - dealt
+        }
+    }
+}
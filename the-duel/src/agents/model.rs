@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::duel::Action;
+
+// A learned prediction of the opponent's next action.
+pub trait OpponentModel {
+    // Learns from the latest of the opponent's actions so far, oldest first.
+    fn observe(&mut self, opposing_actions: &[Action]);
+
+    // Probability that the opponent attacks after `opposing_actions`, which
+    // may extend the actual actions by hypothetical ones.
+    fn attack_probability(&self, opposing_actions: &[Action]) -> f64;
+
+    fn name(&self) -> String;
+
+    fn copy_model(&self) -> Box<dyn OpponentModel>;
+}
+
+// Which opponent model to build.
+#[derive(Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelKind {
+    // How often the opponent attacked at all.
+    #[default]
+    Frequency,
+    // How often the opponent attacked after its last action.
+    Markov,
+    // How often the opponent attacked after its last `order` actions.
+    NGram,
+}
+
+impl ModelKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "frequency" => Some(ModelKind::Frequency),
+            "markov" => Some(ModelKind::Markov),
+            "ngram" | "n_gram" => Some(ModelKind::NGram),
+            _ => None,
+        }
+    }
+
+    pub fn build(&self, order: usize) -> Box<dyn OpponentModel> {
+        match self {
+            ModelKind::Frequency => Box::new(NGramModel::new(0)),
+            ModelKind::Markov => Box::new(NGramModel::new(1)),
+            ModelKind::NGram => Box::new(NGramModel::new(order)),
+        }
+    }
+}
+
+// Counts the opponent's actions after each of its last `order` actions. A
+// context never seen before backs off to the longest seen suffix of it, down
+// to the plain frequency, and an unseen opponent attacks half the time.
+#[derive(Clone)]
+pub struct NGramModel {
+    pub order: usize,
+    // Attacks and finches after every context of up to `order` actions,
+    // true standing for an attack.
+    pub counts: HashMap<Vec<bool>, [u64; 2]>,
+}
+
+impl NGramModel {
+    pub fn new(order: usize) -> Self {
+        Self {
+            order,
+            counts: HashMap::new(),
+        }
+    }
+
+    fn context(actions: &[Action]) -> Vec<bool> {
+        actions.iter().map(|a| *a == Action::ATTACK).collect()
+    }
+}
+
+impl OpponentModel for NGramModel {
+    fn observe(&mut self, opposing_actions: &[Action]) {
+        let Some((latest, before)) = opposing_actions.split_last() else {
+            return;
+        };
+        let outcome = usize::from(*latest != Action::ATTACK);
+        for length in 0..=self.order.min(before.len()) {
+            let context = Self::context(&before[before.len() - length..]);
+            self.counts.entry(context).or_default()[outcome] += 1;
+        }
+    }
+
+    fn attack_probability(&self, opposing_actions: &[Action]) -> f64 {
+        (0..=self.order.min(opposing_actions.len()))
+            .rev()
+            .find_map(|length| {
+                let context = Self::context(&opposing_actions[opposing_actions.len() - length..]);
+                self.counts.get(&context)
+            })
+            .map(|[attacks, finches]| *attacks as f64 / (attacks + finches) as f64)
+            .unwrap_or(0.5)
+    }
+
+    fn name(&self) -> String {
+        match self.order {
+            0 => String::from("frequency"),
+            1 => String::from("Markov"),
+            order => format!("order-{} Markov", order),
+        }
+    }
+
+    fn copy_model(&self) -> Box<dyn OpponentModel> {
+        Box::new(self.clone())
+    }
+}
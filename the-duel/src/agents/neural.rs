@@ -0,0 +1,108 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, DamageMatrix, PlayerState};
+use crate::game::AgentMode;
+use crate::history::HistoryView;
+use crate::neural::{Policy, Trajectory, observation};
+
+// Plays by a neural network policy, drawing every action from the network's
+// probabilities or, if `greedy`, taking the more probable one. In
+// `AgentMode::Train`, the observations and actions are recorded into
+// `trajectory`.
+pub struct NeuralAgent<T: Rng + 'static> {
+    pub current_random: Rc<RefCell<T>>,
+    pub policy: Rc<Policy>,
+    pub greedy: bool,
+    pub trajectory: Option<Rc<RefCell<Trajectory>>>,
+    pub mode: AgentMode,
+    // The damage matrix hidden opposing hit points are recounted by.
+    pub damage: DamageMatrix,
+}
+
+impl<T: Rng> NeuralAgent<T> {
+    pub fn new(current_random: Rc<RefCell<T>>, policy: Rc<Policy>, greedy: bool) -> Self {
+        Self {
+            current_random,
+            policy,
+            greedy,
+            trajectory: None,
+            mode: AgentMode::default(),
+            damage: DamageMatrix::default(),
+        }
+    }
+
+    pub fn with_damage(mut self, damage: DamageMatrix) -> Self {
+        self.damage = damage;
+        self
+    }
+
+    pub fn with_trajectory(mut self, trajectory: Rc<RefCell<Trajectory>>) -> Self {
+        self.trajectory = Some(trajectory);
+        self
+    }
+}
+
+impl<T: Rng> GameAgent for NeuralAgent<T> {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let observation = observation(
+            own_player_state,
+            opposing_player_state,
+            history,
+            &self.damage,
+            self.policy.history,
+        );
+        let prob = self.policy.attack_probability(&observation);
+        let attack = if self.greedy {
+            prob > 0.5
+        } else {
+            self.current_random
+                .borrow_mut()
+                .random_bool(prob.clamp(0.0, 1.0))
+        };
+        if let Some(trajectory) = &self.trajectory
+            && self.mode == AgentMode::Train
+        {
+            trajectory.borrow_mut().push((observation, attack));
+        }
+
+        if attack {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!(
+            "Neural network policy with {} hidden units observing {} turns{}",
+            self.policy.hidden,
+            self.policy.history,
+            if self.greedy { ", greedy" } else { "" }
+        )
+    }
+
+    fn set_mode(&mut self, mode: AgentMode) {
+        self.mode = mode;
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            current_random: self.current_random.clone(),
+            policy: self.policy.clone(),
+            greedy: self.greedy,
+            trajectory: self.trajectory.clone(),
+            mode: self.mode,
+            damage: self.damage.clone(),
+        })
+    }
+}
@@ -0,0 +1,194 @@
+use serde::Deserialize;
+
+use crate::agents::{DecisionExplanation, GameAgent};
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// How the opponent's probability of attack is estimated from its actions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Estimator {
+    // The share of attacks over the whole game.
+    #[default]
+    Full,
+    // The share of attacks among the last actions only, to follow opponents
+    // that change their behaviour during the game.
+    Window(usize),
+    // The share of attacks with every older action weighing `decay` times
+    // less than the next one.
+    Decay(f64),
+}
+
+#[derive(Clone)]
+pub struct OneStepDecisionProcessAgent {
+    pub cost_losing_hp: f64,
+    pub cost_not_losing_hp: f64,
+    pub cost_equivalent_exchange: f64,
+    pub num_turns: i64,
+    pub num_attacks: i64,
+    pub estimator: Estimator,
+    // Decayed counts of attacks and actions of the decay estimator.
+    pub weighted_attacks: f64,
+    pub weighted_turns: f64,
+    // Pseudo-counts of attacks and finches of a Beta prior added to every
+    // estimate, so that the first turns do not rest on a handful of actions.
+    pub prior_attacks: f64,
+    pub prior_finches: f64,
+    // Turns at the start of a game played with `burn_in_action` regardless
+    // of the estimate.
+    pub burn_in: usize,
+    pub burn_in_action: Action,
+    // The estimate and the expected rewards of attacking and finching the
+    // last decision rested on, none during the burn-in.
+    pub last_estimate: Option<(f64, f64, f64)>,
+}
+
+impl OneStepDecisionProcessAgent {
+    pub fn new(
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+    ) -> Self {
+        Self {
+            cost_losing_hp,
+            cost_not_losing_hp,
+            cost_equivalent_exchange,
+            num_turns: 0,
+            num_attacks: 0,
+            estimator: Estimator::Full,
+            weighted_attacks: 0.0,
+            weighted_turns: 0.0,
+            prior_attacks: 0.0,
+            prior_finches: 0.0,
+            burn_in: 0,
+            burn_in_action: Action::ATTACK,
+            last_estimate: None,
+        }
+    }
+
+    pub fn with_prior(mut self, prior_attacks: f64, prior_finches: f64) -> Self {
+        self.prior_attacks = prior_attacks;
+        self.prior_finches = prior_finches;
+        self
+    }
+
+    pub fn with_burn_in(mut self, burn_in: usize, burn_in_action: Action) -> Self {
+        self.burn_in = burn_in;
+        self.burn_in_action = burn_in_action;
+        self
+    }
+
+    pub fn with_estimator(mut self, estimator: Estimator) -> Self {
+        self.estimator = estimator;
+        self
+    }
+}
+
+impl GameAgent for OneStepDecisionProcessAgent {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        if let Some(ack) = opposing_player_actions {
+            let attacked = match ack {
+                Action::ATTACK => {
+                    self.num_attacks += 1;
+                    1.0
+                }
+                Action::FINCH => 0.0,
+            };
+            if let Estimator::Decay(decay) = self.estimator {
+                self.weighted_attacks = decay * self.weighted_attacks + attacked;
+                self.weighted_turns = decay * self.weighted_turns + 1.0;
+            }
+        }
+        self.num_turns += 1;
+        if history.turns() < self.burn_in {
+            self.last_estimate = None;
+            return self.burn_in_action.clone();
+        }
+
+        // Guesstimate probability of attack
+        let (attacks, turns) = match self.estimator {
+            Estimator::Full => (self.num_attacks as f64, self.num_turns as f64),
+            Estimator::Window(window) => {
+                let recent = history.recent_opposing_actions(window);
+                let attacks = recent.iter().filter(|a| **a == Action::ATTACK).count();
+                (attacks as f64, recent.len() as f64)
+            }
+            Estimator::Decay(_) => (self.weighted_attacks, self.weighted_turns),
+        };
+        let pseudo_turns = turns + self.prior_attacks + self.prior_finches;
+        let prob = if pseudo_turns > 0.0 {
+            (attacks + self.prior_attacks) / pseudo_turns
+        } else {
+            0.0
+        };
+
+        let attack_reward =
+            self.cost_losing_hp * (1.0 - prob) + self.cost_equivalent_exchange * prob;
+        let finch_reward =
+            self.cost_not_losing_hp * prob + self.cost_equivalent_exchange * (1.0 - prob);
+        self.last_estimate = Some((prob, attack_reward, finch_reward));
+
+        if attack_reward > finch_reward {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn explain_last_decision(&self) -> Option<DecisionExplanation> {
+        let (prob, attack_reward, finch_reward) = self.last_estimate?;
+        Some(DecisionExplanation {
+            beliefs: vec![(String::from("p"), prob)],
+            expected_rewards: vec![
+                (format!("{:?}", Action::ATTACK), attack_reward),
+                (format!("{:?}", Action::FINCH), finch_reward),
+            ],
+        })
+    }
+
+    fn belief_snapshot(&self) -> Vec<(String, f64)> {
+        self.last_estimate
+            .map(|(prob, _, _)| vec![(String::from("p"), prob)])
+            .unwrap_or_default()
+    }
+
+    fn strategy_name(&self) -> String {
+        match self.estimator {
+            Estimator::Full => String::from(
+                "Estimate Probability of Attack, and design optimal one-step decision.",
+            ),
+            Estimator::Window(window) => format!(
+                "Estimate Probability of Attack over the last {} turns, and design optimal one-step decision.",
+                window
+            ),
+            Estimator::Decay(decay) => format!(
+                "Estimate Probability of Attack with decay {}, and design optimal one-step decision.",
+                decay
+            ),
+        }
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            cost_losing_hp: self.cost_losing_hp,
+            cost_not_losing_hp: self.cost_not_losing_hp,
+            cost_equivalent_exchange: self.cost_equivalent_exchange,
+            num_turns: self.num_turns,
+            num_attacks: self.num_attacks,
+            estimator: self.estimator,
+            weighted_attacks: self.weighted_attacks,
+            weighted_turns: self.weighted_turns,
+            prior_attacks: self.prior_attacks,
+            prior_finches: self.prior_finches,
+            burn_in: self.burn_in,
+            burn_in_action: self.burn_in_action.clone(),
+            last_estimate: self.last_estimate,
+        })
+    }
+}
@@ -0,0 +1,118 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+use tract_onnx::prelude::*;
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, DamageMatrix, PlayerState};
+use crate::history::HistoryView;
+use crate::neural::observation;
+
+pub type OnnxPlan = TypedRunnableModel<TypedModel>;
+
+// Plays by a policy exported to ONNX, e.g. from a network trained in Python.
+// The model takes a float tensor of shape [1, 2 + 2 * history] holding the
+// observation of crate::neural::observation, and returns the probabilities
+// (or any non-negative weights) of attacking and finching, shape [1, 2].
+pub struct OnnxAgent<T: Rng + 'static> {
+    pub current_random: Rc<RefCell<T>>,
+    pub model: Rc<OnnxPlan>,
+    pub path: String,
+    pub history: usize,
+    pub greedy: bool,
+    // The damage matrix hidden opposing hit points are recounted by.
+    pub damage: DamageMatrix,
+}
+
+impl<T: Rng> OnnxAgent<T> {
+    pub fn load(
+        current_random: Rc<RefCell<T>>,
+        path: &str,
+        history: usize,
+        greedy: bool,
+    ) -> TractResult<Self> {
+        let model = tract_onnx::onnx()
+            .model_for_path(path)?
+            .with_input_fact(0, f32::fact([1, 2 + 2 * history]).into())?
+            .into_optimized()?
+            .into_runnable()?;
+        Ok(Self {
+            current_random,
+            model: Rc::new(model),
+            path: path.to_string(),
+            history,
+            greedy,
+            damage: DamageMatrix::default(),
+        })
+    }
+
+    pub fn with_damage(mut self, damage: DamageMatrix) -> Self {
+        self.damage = damage;
+        self
+    }
+
+    pub fn attack_probability(&self, observation: Vec<f32>) -> TractResult<f64> {
+        let input = Tensor::from_shape(&[1, observation.len()], &observation)?;
+        let outputs = self.model.run(tvec!(input.into()))?;
+        let weights = outputs[0].to_array_view::<f32>()?;
+        let attack = f64::from(weights.iter().next().copied().unwrap_or(0.0)).max(0.0);
+        let finch = f64::from(weights.iter().nth(1).copied().unwrap_or(0.0)).max(0.0);
+        Ok(if attack + finch > 0.0 {
+            attack / (attack + finch)
+        } else {
+            0.5
+        })
+    }
+}
+
+impl<T: Rng> GameAgent for OnnxAgent<T> {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let observation = observation(
+            own_player_state,
+            opposing_player_state,
+            history,
+            &self.damage,
+            self.history,
+        );
+        // The shapes were checked when loading, so only a broken model fails
+        let prob = self
+            .attack_probability(observation)
+            .unwrap_or_else(|err| panic!("ONNX model {} failed: {}", self.path, err));
+        let attack = if self.greedy {
+            prob > 0.5
+        } else {
+            self.current_random.borrow_mut().random_bool(prob)
+        };
+
+        if attack {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!(
+            "ONNX policy {}{}",
+            self.path,
+            if self.greedy { ", greedy" } else { "" }
+        )
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            current_random: self.current_random.clone(),
+            model: self.model.clone(),
+            path: self.path.clone(),
+            damage: self.damage.clone(),
+            ..*self
+        })
+    }
+}
@@ -0,0 +1,271 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// A hypothesis about the opponent: its type, parameters and internal state.
+#[derive(Clone, Copy)]
+pub enum Particle {
+    Random {
+        probability_of_attack: f64,
+    },
+    // The state is the action the chain is in, true standing for an attack.
+    Markov {
+        change_to_attack_prob: f64,
+        change_to_finch_prob: f64,
+        initial: bool,
+        state: bool,
+    },
+    // Repeats the agent's previous action, attacking first.
+    Mirror,
+}
+
+impl Particle {
+    fn sample<R: Rng>(rng: &mut R) -> Self {
+        match rng.random_range(0..3) {
+            0 => Particle::Random {
+                probability_of_attack: rng.random(),
+            },
+            1 => {
+                let initial = rng.random_bool(0.5);
+                Particle::Markov {
+                    change_to_attack_prob: rng.random(),
+                    change_to_finch_prob: rng.random(),
+                    initial,
+                    state: initial,
+                }
+            }
+            _ => Particle::Mirror,
+        }
+    }
+
+    // Probability that the opponent attacks on the turn after the agent
+    // played `own_previous`.
+    fn attack_probability(&self, own_previous: Option<&Action>) -> f64 {
+        match self {
+            Particle::Random {
+                probability_of_attack,
+            } => *probability_of_attack,
+            Particle::Markov {
+                change_to_attack_prob,
+                change_to_finch_prob,
+                state,
+                ..
+            } => {
+                if *state {
+                    1.0 - change_to_finch_prob
+                } else {
+                    *change_to_attack_prob
+                }
+            }
+            Particle::Mirror => match own_previous {
+                Some(action) => f64::from(*action == Action::ATTACK),
+                None => 1.0,
+            },
+        }
+    }
+
+    fn restart(&mut self) {
+        if let Particle::Markov { initial, state, .. } = self {
+            *state = *initial;
+        }
+    }
+
+    fn jitter<R: Rng>(&mut self, jitter: f64, rng: &mut R) {
+        let mut perturb = |p: &mut f64| {
+            *p = (*p + rng.random_range(-jitter..=jitter)).clamp(0.0, 1.0);
+        };
+        match self {
+            Particle::Random {
+                probability_of_attack,
+            } => perturb(probability_of_attack),
+            Particle::Markov {
+                change_to_attack_prob,
+                change_to_finch_prob,
+                ..
+            } => {
+                perturb(change_to_attack_prob);
+                perturb(change_to_finch_prob);
+            }
+            Particle::Mirror => {}
+        }
+    }
+}
+
+// Keeps a belief over the opponent's type, parameters and internal state as
+// a weighted set of particles, reweighted every turn by the likelihood of the
+// opponent's observed action. An observed action is taken to be the played
+// one with probability `accuracy`, which keeps a single surprising action
+// from wiping out a hypothesis. Particles are resampled when their effective
+// number falls below half, perturbing the parameters by up to `jitter`
+// against impoverishment. Plays the optimal one-step decision against the
+// belief's prediction of the next action.
+pub struct ParticleFilterAgent<T: Rng + 'static> {
+    pub current_random: Rc<RefCell<T>>,
+    pub particles: Vec<Particle>,
+    pub weights: Vec<f64>,
+    pub accuracy: f64,
+    pub jitter: f64,
+    pub cost_losing_hp: f64,
+    pub cost_not_losing_hp: f64,
+    pub cost_equivalent_exchange: f64,
+}
+
+impl<T: Rng> ParticleFilterAgent<T> {
+    pub fn new(
+        current_random: Rc<RefCell<T>>,
+        num_particles: usize,
+        accuracy: f64,
+        jitter: f64,
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+    ) -> Self {
+        let num_particles = num_particles.max(1);
+        let particles = {
+            let mut rng = current_random.borrow_mut();
+            (0..num_particles)
+                .map(|_| Particle::sample(&mut *rng))
+                .collect()
+        };
+        Self {
+            current_random,
+            particles,
+            weights: vec![1.0 / num_particles as f64; num_particles],
+            accuracy,
+            jitter,
+            cost_losing_hp,
+            cost_not_losing_hp,
+            cost_equivalent_exchange,
+        }
+    }
+
+    // Posterior probability of the random, Markov and mirror types.
+    pub fn type_probabilities(&self) -> [f64; 3] {
+        let mut probabilities = [0.0; 3];
+        for (particle, weight) in self.particles.iter().zip(&self.weights) {
+            let index = match particle {
+                Particle::Random { .. } => 0,
+                Particle::Markov { .. } => 1,
+                Particle::Mirror => 2,
+            };
+            probabilities[index] += weight;
+        }
+        probabilities
+    }
+
+    fn update(&mut self, observed: &Action, own_previous: Option<&Action>) {
+        let mut rng = self.current_random.borrow_mut();
+        let attacked = *observed == Action::ATTACK;
+        // Likelihood of the observation if the opponent attacked or finched
+        let (if_attacked, if_finched) = if attacked {
+            (self.accuracy, 1.0 - self.accuracy)
+        } else {
+            (1.0 - self.accuracy, self.accuracy)
+        };
+        for (particle, weight) in self.particles.iter_mut().zip(self.weights.iter_mut()) {
+            let p = particle.attack_probability(own_previous);
+            let likelihood = p * if_attacked + (1.0 - p) * if_finched;
+            *weight *= likelihood;
+            // The chain moved to the action it played, drawn from its posterior
+            if let Particle::Markov { state, .. } = particle
+                && likelihood > 0.0
+            {
+                *state = rng.random_bool((p * if_attacked / likelihood).clamp(0.0, 1.0));
+            }
+        }
+
+        let total: f64 = self.weights.iter().sum();
+        if total <= 0.0 {
+            // Every hypothesis was ruled out, start over
+            let n = self.particles.len();
+            self.particles = (0..n).map(|_| Particle::sample(&mut *rng)).collect();
+            self.weights = vec![1.0 / n as f64; n];
+            return;
+        }
+        self.weights.iter_mut().for_each(|w| *w /= total);
+
+        let effective = 1.0 / self.weights.iter().map(|w| w * w).sum::<f64>();
+        if effective < self.particles.len() as f64 / 2.0 {
+            // Systematic resampling
+            let n = self.particles.len();
+            let offset: f64 = rng.random::<f64>() / n as f64;
+            let mut resampled = Vec::with_capacity(n);
+            let mut cumulative = 0.0;
+            let mut index = 0;
+            for k in 0..n {
+                let target = offset + k as f64 / n as f64;
+                while index < n - 1 && cumulative + self.weights[index] < target {
+                    cumulative += self.weights[index];
+                    index += 1;
+                }
+                let mut particle = self.particles[index];
+                particle.jitter(self.jitter, &mut *rng);
+                resampled.push(particle);
+            }
+            self.particles = resampled;
+            self.weights = vec![1.0 / n as f64; n];
+        }
+    }
+}
+
+impl<T: Rng> GameAgent for ParticleFilterAgent<T> {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        match history.opposing_actions.split_last() {
+            None => self.particles.iter_mut().for_each(Particle::restart),
+            Some((observed, before)) => {
+                // The opponent answered the agent's action of the turn before
+                let own_previous = before
+                    .len()
+                    .checked_sub(1)
+                    .and_then(|turn| history.own_actions.get(turn));
+                self.update(observed, own_previous);
+            }
+        }
+
+        let own_last = history.own_actions.last();
+        let prob: f64 = self
+            .particles
+            .iter()
+            .zip(&self.weights)
+            .map(|(particle, weight)| weight * particle.attack_probability(own_last))
+            .sum();
+
+        let attack_reward =
+            self.cost_losing_hp * (1.0 - prob) + self.cost_equivalent_exchange * prob;
+        let finch_reward =
+            self.cost_not_losing_hp * prob + self.cost_equivalent_exchange * (1.0 - prob);
+
+        if attack_reward > finch_reward {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!(
+            "Track the opponent with {} particles, and design optimal one-step decision.",
+            self.particles.len()
+        )
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            current_random: self.current_random.clone(),
+            particles: self.particles.clone(),
+            weights: self.weights.clone(),
+            ..*self
+        })
+    }
+}
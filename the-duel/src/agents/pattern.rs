@@ -0,0 +1,92 @@
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// Searches the joint history of both players' actions for earlier
+// occurrences of its longest recent context, up to `max_length` turns, and
+// predicts the opponent's next action by what the opponent played after
+// them. Plays the optimal one-step decision against the prediction. Agents
+// answering the previous turn deterministically, such as MirrorAgent, are
+// predicted exactly after a few turns.
+#[derive(Clone)]
+pub struct PatternMatchingAgent {
+    pub max_length: usize,
+    pub cost_losing_hp: f64,
+    pub cost_not_losing_hp: f64,
+    pub cost_equivalent_exchange: f64,
+}
+
+impl PatternMatchingAgent {
+    pub fn new(
+        max_length: usize,
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+    ) -> Self {
+        Self {
+            max_length,
+            cost_losing_hp,
+            cost_not_losing_hp,
+            cost_equivalent_exchange,
+        }
+    }
+
+    // Share of attacks after the earlier occurrences of the longest matching
+    // context, half if nothing matches.
+    pub fn attack_probability(&self, history: &HistoryView) -> f64 {
+        let joint: Vec<(bool, bool)> = history
+            .own_actions
+            .iter()
+            .zip(history.opposing_actions)
+            .map(|(own, opposing)| (*own == Action::ATTACK, *opposing == Action::ATTACK))
+            .collect();
+        let turns = joint.len();
+        for length in (1..=self.max_length.min(turns.saturating_sub(1))).rev() {
+            let context = &joint[turns - length..];
+            // Contexts ending at `end` were followed by the opponent's action at `end`
+            let followers: Vec<bool> = (length..turns)
+                .filter(|end| &joint[end - length..*end] == context)
+                .map(|end| joint[end].1)
+                .collect();
+            if !followers.is_empty() {
+                let attacks = followers.iter().filter(|a| **a).count();
+                return attacks as f64 / followers.len() as f64;
+            }
+        }
+        0.5
+    }
+}
+
+impl GameAgent for PatternMatchingAgent {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let prob = self.attack_probability(history);
+
+        let attack_reward =
+            self.cost_losing_hp * (1.0 - prob) + self.cost_equivalent_exchange * prob;
+        let finch_reward =
+            self.cost_not_losing_hp * prob + self.cost_equivalent_exchange * (1.0 - prob);
+
+        if attack_reward > finch_reward {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!(
+            "Match the last {} turns against the history, and design optimal one-step decision.",
+            self.max_length
+        )
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(self.clone())
+    }
+}
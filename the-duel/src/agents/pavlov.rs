@@ -0,0 +1,45 @@
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// Win-stay, lose-shift: opens with `initial`, repeats its previous action if
+// that did not cost a hit point and switches to the other action otherwise.
+// Only finching into an attack spares the agent, see Duel::resolve_actions.
+#[derive(Clone)]
+pub struct PavlovAgent {
+    pub initial: Action,
+}
+
+impl PavlovAgent {
+    pub fn new(initial: Action) -> Self {
+        Self { initial }
+    }
+}
+
+impl GameAgent for PavlovAgent {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        match (history.own_actions.last(), history.opposing_actions.last()) {
+            (Some(Action::FINCH), Some(Action::ATTACK)) => Action::FINCH,
+            (Some(Action::FINCH), Some(_)) => Action::ATTACK,
+            (Some(Action::ATTACK), Some(_)) => Action::FINCH,
+            _ => self.initial.clone(),
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!(
+            "Pavlov (win-stay, lose-shift) opening with {:?}",
+            self.initial
+        )
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,112 @@
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::game::AgentMode;
+use crate::history::HistoryView;
+
+// Hit points gained over the opponent by `own` against `opposing`, see
+// Duel::resolve_actions.
+fn advantage(own: &Action, opposing: &Action) -> f64 {
+    match (own, opposing) {
+        (Action::FINCH, Action::ATTACK) => 1.0,
+        (Action::ATTACK, Action::FINCH) => -1.0,
+        _ => 0.0,
+    }
+}
+
+// Wraps several agents and lets the one with the best hypothetical
+// performance control play. Every turn all agents propose an action, and each
+// proposal is scored against the opponent's actual response by the hit
+// points it would have gained over the opponent, discounted by `decay` per
+// turn. The agents see the history of the actions actually played.
+pub struct PortfolioAgent {
+    pub agents: Vec<Box<dyn GameAgent>>,
+    pub decay: f64,
+    pub scores: Vec<f64>,
+    pub proposals: Vec<Action>,
+    pub controller: usize,
+    // How often control changed hands so far.
+    pub switches: u64,
+}
+
+impl PortfolioAgent {
+    pub fn new(agents: Vec<Box<dyn GameAgent>>, decay: f64) -> Self {
+        let scores = vec![0.0; agents.len()];
+        Self {
+            agents,
+            decay,
+            scores,
+            proposals: Vec::new(),
+            controller: 0,
+            switches: 0,
+        }
+    }
+
+    fn score(&mut self, opposing: &Action) {
+        for (score, proposal) in self.scores.iter_mut().zip(&self.proposals) {
+            *score = self.decay * *score + advantage(proposal, opposing);
+        }
+        // Ties keep the current controller
+        let best = (0..self.scores.len())
+            .filter(|i| self.scores[*i] > self.scores[self.controller])
+            .max_by(|a, b| self.scores[*a].total_cmp(&self.scores[*b]));
+        if let Some(best) = best {
+            self.controller = best;
+            self.switches += 1;
+        }
+    }
+}
+
+impl GameAgent for PortfolioAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        if let Some(latest) = history.opposing_actions.last()
+            && !self.proposals.is_empty()
+        {
+            self.score(latest);
+        }
+        self.proposals = self
+            .agents
+            .iter_mut()
+            .map(|agent| {
+                agent.decide_action(
+                    own_player_state,
+                    opposing_player_actions,
+                    opposing_player_state,
+                    history,
+                )
+            })
+            .collect();
+        self.proposals
+            .get(self.controller)
+            .cloned()
+            .unwrap_or(Action::FINCH)
+    }
+
+    fn strategy_name(&self) -> String {
+        let names: Vec<String> = self.agents.iter().map(|a| a.strategy_name()).collect();
+        format!(
+            "Let the best performing of [{}] control play",
+            names.join("; ")
+        )
+    }
+
+    fn set_mode(&mut self, mode: AgentMode) {
+        self.agents
+            .iter_mut()
+            .for_each(|agent| agent.set_mode(mode));
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            agents: self.agents.iter().map(|a| a.copy_self_to_anom()).collect(),
+            scores: self.scores.clone(),
+            proposals: self.proposals.clone(),
+            ..*self
+        })
+    }
+}
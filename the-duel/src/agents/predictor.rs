@@ -0,0 +1,67 @@
+use crate::agents::{GameAgent, NGramModel, OpponentModel};
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// Predicts the opponent's next action from its last `order` actions with an
+// order-k Markov model, and plays the optimal one-step decision against the
+// prediction. Unlike the overall frequency of attacks, the prediction follows
+// opponents whose actions depend on their previous ones, such as Markov
+// chains and mirrors.
+#[derive(Clone)]
+pub struct MarkovPredictorAgent {
+    pub model: NGramModel,
+    pub cost_losing_hp: f64,
+    pub cost_not_losing_hp: f64,
+    pub cost_equivalent_exchange: f64,
+}
+
+impl MarkovPredictorAgent {
+    pub fn new(
+        order: usize,
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+    ) -> Self {
+        Self {
+            model: NGramModel::new(order),
+            cost_losing_hp,
+            cost_not_losing_hp,
+            cost_equivalent_exchange,
+        }
+    }
+}
+
+impl GameAgent for MarkovPredictorAgent {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        self.model.observe(history.opposing_actions);
+        let prob = self.model.attack_probability(history.opposing_actions);
+
+        let attack_reward =
+            self.cost_losing_hp * (1.0 - prob) + self.cost_equivalent_exchange * prob;
+        let finch_reward =
+            self.cost_not_losing_hp * prob + self.cost_equivalent_exchange * (1.0 - prob);
+
+        if attack_reward > finch_reward {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!(
+            "Predict the next action with an order-{} Markov model, and design optimal one-step decision.",
+            self.model.order
+        )
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,158 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::GameAgent;
+use crate::history::HistoryView;
+use crate::prisoners_dilemma::{Choice, Prisoner, PrisonersDilemma};
+
+// The classic entries of Axelrod's tournaments.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrisonerStrategy {
+    AlwaysCooperate,
+    AlwaysDefect,
+    // Opens with cooperation and repeats the opponent's last choice.
+    TitForTat,
+    // Tit-for-tat opening with defection.
+    SuspiciousTitForTat,
+    // Defects only after two defections of the opponent in a row.
+    TitForTwoTats,
+    // Cooperates until the opponent defects once, then always defects.
+    GrimTrigger,
+    // Win-stay, lose-shift: repeats its choice after the reward or the
+    // temptation, switches otherwise.
+    Pavlov,
+    // Tit-for-tat that defects instead of cooperating with the given
+    // probability.
+    Joss(f64),
+    // Cooperates with the given probability.
+    Random(f64),
+}
+
+impl PrisonerStrategy {
+    // Every strategy, the random ones with Axelrod's parameters.
+    pub const ALL: [PrisonerStrategy; 9] = [
+        PrisonerStrategy::AlwaysCooperate,
+        PrisonerStrategy::AlwaysDefect,
+        PrisonerStrategy::TitForTat,
+        PrisonerStrategy::SuspiciousTitForTat,
+        PrisonerStrategy::TitForTwoTats,
+        PrisonerStrategy::GrimTrigger,
+        PrisonerStrategy::Pavlov,
+        PrisonerStrategy::Joss(0.1),
+        PrisonerStrategy::Random(0.5),
+    ];
+
+    // Parses names like `tit_for_tat` or `joss(0.1)`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (name, argument) = match spec.split_once('(') {
+            Some((name, rest)) => (name.trim(), Some(rest.strip_suffix(')')?.trim())),
+            None => (spec.trim(), None),
+        };
+        let probability =
+            || -> Option<f64> { argument?.parse().ok().filter(|p| (0.0..=1.0).contains(p)) };
+        match (name, argument) {
+            ("cooperate", None) => Some(PrisonerStrategy::AlwaysCooperate),
+            ("defect", None) => Some(PrisonerStrategy::AlwaysDefect),
+            ("tit_for_tat", None) => Some(PrisonerStrategy::TitForTat),
+            ("suspicious_tit_for_tat", None) => Some(PrisonerStrategy::SuspiciousTitForTat),
+            ("tit_for_two_tats", None) => Some(PrisonerStrategy::TitForTwoTats),
+            ("grim_trigger", None) => Some(PrisonerStrategy::GrimTrigger),
+            ("pavlov", None) => Some(PrisonerStrategy::Pavlov),
+            ("joss", Some(_)) => probability().map(PrisonerStrategy::Joss),
+            ("random", Some(_)) => probability().map(PrisonerStrategy::Random),
+            _ => None,
+        }
+    }
+}
+
+pub struct PrisonerAgent<T: Rng + 'static> {
+    pub strategy: PrisonerStrategy,
+    pub current_random: Rc<RefCell<T>>,
+}
+
+impl<T: Rng> PrisonerAgent<T> {
+    pub fn new(strategy: PrisonerStrategy, current_random: Rc<RefCell<T>>) -> Self {
+        Self {
+            strategy,
+            current_random,
+        }
+    }
+}
+
+impl<T: Rng> GameAgent<PrisonersDilemma> for PrisonerAgent<T> {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &Prisoner,
+        _opposing_player_actions: &Option<Choice>,
+        _opposing_player_state: &Option<Prisoner>,
+        history: &HistoryView<PrisonersDilemma>,
+    ) -> Choice {
+        let last = history.opposing_actions.last();
+        let tit_for_tat = last.copied().unwrap_or(Choice::COOPERATE);
+        match self.strategy {
+            PrisonerStrategy::AlwaysCooperate => Choice::COOPERATE,
+            PrisonerStrategy::AlwaysDefect => Choice::DEFECT,
+            PrisonerStrategy::TitForTat => tit_for_tat,
+            PrisonerStrategy::SuspiciousTitForTat => last.copied().unwrap_or(Choice::DEFECT),
+            PrisonerStrategy::TitForTwoTats => {
+                if history
+                    .opposing_actions
+                    .ends_with(&[Choice::DEFECT, Choice::DEFECT])
+                {
+                    Choice::DEFECT
+                } else {
+                    Choice::COOPERATE
+                }
+            }
+            PrisonerStrategy::GrimTrigger => {
+                if history.opposing_actions.contains(&Choice::DEFECT) {
+                    Choice::DEFECT
+                } else {
+                    Choice::COOPERATE
+                }
+            }
+            // Staying after the opponent cooperated, shifting after it defected
+            PrisonerStrategy::Pavlov => match (history.own_actions.last(), last) {
+                (Some(own), Some(Choice::COOPERATE)) => *own,
+                (Some(Choice::COOPERATE), Some(Choice::DEFECT)) => Choice::DEFECT,
+                _ => Choice::COOPERATE,
+            },
+            PrisonerStrategy::Joss(p) => {
+                if tit_for_tat == Choice::COOPERATE
+                    && self.current_random.borrow_mut().random_bool(p)
+                {
+                    Choice::DEFECT
+                } else {
+                    tit_for_tat
+                }
+            }
+            PrisonerStrategy::Random(p) => {
+                if self.current_random.borrow_mut().random_bool(p) {
+                    Choice::COOPERATE
+                } else {
+                    Choice::DEFECT
+                }
+            }
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        match self.strategy {
+            PrisonerStrategy::AlwaysCooperate => String::from("Always Cooperate"),
+            PrisonerStrategy::AlwaysDefect => String::from("Always Defect"),
+            PrisonerStrategy::TitForTat => String::from("Tit-for-Tat"),
+            PrisonerStrategy::SuspiciousTitForTat => String::from("Suspicious Tit-for-Tat"),
+            PrisonerStrategy::TitForTwoTats => String::from("Tit-for-Two-Tats"),
+            PrisonerStrategy::GrimTrigger => String::from("Grim Trigger"),
+            PrisonerStrategy::Pavlov => String::from("Pavlov"),
+            PrisonerStrategy::Joss(p) => format!("Joss defecting with probability {}", p),
+            PrisonerStrategy::Random(p) => format!("Cooperate with probability {}", p),
+        }
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent<PrisonersDilemma>> {
+        Box::new(Self::new(self.strategy, self.current_random.clone()))
+    }
+}
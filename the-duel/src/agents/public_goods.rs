@@ -0,0 +1,100 @@
+use rand::Rng;
+
+use crate::agents::SharedRng;
+use crate::public_goods::{Contribution, GroupState, PublicGoods};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PublicGoodsStrategy {
+    // Never contributes.
+    FreeRider,
+    // Always contributes its whole endowment.
+    Cooperator,
+    // Opens with its whole endowment, then matches what the others
+    // contributed on average in the last round.
+    ConditionalCooperator,
+    // Contributes its whole endowment and punishes every player who
+    // contributed less than half of theirs in the last round.
+    Punisher,
+    // Contributes an amount drawn uniformly.
+    Random,
+}
+
+impl PublicGoodsStrategy {
+    pub const ALL: [PublicGoodsStrategy; 5] = [
+        PublicGoodsStrategy::FreeRider,
+        PublicGoodsStrategy::Cooperator,
+        PublicGoodsStrategy::ConditionalCooperator,
+        PublicGoodsStrategy::Punisher,
+        PublicGoodsStrategy::Random,
+    ];
+
+    pub fn parse(spec: &str) -> Option<Self> {
+        match spec.trim() {
+            "free_rider" => Some(PublicGoodsStrategy::FreeRider),
+            "cooperator" => Some(PublicGoodsStrategy::Cooperator),
+            "conditional_cooperator" => Some(PublicGoodsStrategy::ConditionalCooperator),
+            "punisher" => Some(PublicGoodsStrategy::Punisher),
+            "random" => Some(PublicGoodsStrategy::Random),
+            _ => None,
+        }
+    }
+}
+
+// A player of the public goods game. Unlike the agents of the two-player
+// games, it sees the whole group's state and its own seat.
+#[derive(Clone)]
+pub struct PublicGoodsAgent {
+    pub strategy: PublicGoodsStrategy,
+    pub current_random: SharedRng,
+}
+
+impl PublicGoodsAgent {
+    pub fn new(strategy: PublicGoodsStrategy, current_random: SharedRng) -> Self {
+        Self {
+            strategy,
+            current_random,
+        }
+    }
+
+    pub fn decide(&mut self, rules: &PublicGoods, seat: usize, state: &GroupState) -> Contribution {
+        let endowment = rules.endowment;
+        match self.strategy {
+            PublicGoodsStrategy::FreeRider => Contribution::default(),
+            PublicGoodsStrategy::Cooperator => Contribution {
+                amount: endowment,
+                punish: Vec::new(),
+            },
+            PublicGoodsStrategy::ConditionalCooperator => Contribution {
+                amount: state
+                    .others_average(seat)
+                    .map_or(endowment, |average| average.round() as u32),
+                punish: Vec::new(),
+            },
+            PublicGoodsStrategy::Punisher => Contribution {
+                amount: endowment,
+                punish: state
+                    .last_amounts()
+                    .unwrap_or_default()
+                    .iter()
+                    .enumerate()
+                    .filter(|(other, amount)| *other != seat && 2 * **amount < endowment)
+                    .map(|(other, _)| other)
+                    .collect(),
+            },
+            PublicGoodsStrategy::Random => Contribution {
+                amount: self.current_random.borrow_mut().random_range(0..=endowment),
+                punish: Vec::new(),
+            },
+        }
+    }
+
+    pub fn strategy_name(&self) -> String {
+        match self.strategy {
+            PublicGoodsStrategy::FreeRider => String::from("Free rider"),
+            PublicGoodsStrategy::Cooperator => String::from("Cooperator"),
+            PublicGoodsStrategy::ConditionalCooperator => String::from("Conditional cooperator"),
+            PublicGoodsStrategy::Punisher => String::from("Punisher"),
+            PublicGoodsStrategy::Random => String::from("Random"),
+        }
+    }
+}
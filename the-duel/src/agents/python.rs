@@ -0,0 +1,86 @@
+use pyo3::prelude::*;
+
+use crate::agents::{GameAgent, Observation};
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// Plays by calling the `decide_action` method of a Python object for every
+// action. The method gets the same observation an external process is sent,
+// as a dict, and returns "ATTACK" or "FINCH". Every copy of the agent works
+// on a deep copy of the object, so state kept on it does not leak between
+// games played by different copies.
+pub struct PythonAgent {
+    object: Py<PyAny>,
+}
+
+impl PythonAgent {
+    pub fn new(object: Py<PyAny>) -> Self {
+        Self { object }
+    }
+
+    fn ask(&self, py: Python<'_>, observation: &Observation) -> PyResult<Action> {
+        let text = serde_json::to_string(observation)
+            .map_err(|err| pyo3::exceptions::PyValueError::new_err(err.to_string()))?;
+        let observation = py.import("json")?.call_method1("loads", (text,))?;
+        let answer: String = self
+            .object
+            .call_method1(py, "decide_action", (observation,))?
+            .extract(py)?;
+        match answer.to_ascii_uppercase().as_str() {
+            "ATTACK" => Ok(Action::ATTACK),
+            "FINCH" => Ok(Action::FINCH),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "invalid answer '{}'",
+                answer
+            ))),
+        }
+    }
+}
+
+impl GameAgent for PythonAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let observation = Observation::new(
+            own_player_state,
+            opposing_player_actions,
+            opposing_player_state,
+            history,
+        );
+        Python::with_gil(|py| {
+            self.ask(py, &observation).unwrap_or_else(|err| {
+                panic!("Python agent '{}' failed: {}", self.strategy_name(), err)
+            })
+        })
+    }
+
+    fn strategy_name(&self) -> String {
+        Python::with_gil(|py| {
+            let object = self.object.bind(py);
+            object
+                .getattr("strategy_name")
+                .and_then(|name| name.extract())
+                .or_else(|_| {
+                    object
+                        .get_type()
+                        .name()
+                        .map(|name| format!("Python agent {}", name))
+                })
+                .unwrap_or_else(|_| String::from("Python agent"))
+        })
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Python::with_gil(|py| {
+            let copy = py
+                .import("copy")
+                .and_then(|copy| copy.call_method1("deepcopy", (self.object.bind(py),)))
+                .unwrap_or_else(|err| panic!("could not copy Python agent: {}", err));
+            Box::new(Self::new(copy.unbind()))
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+#[derive(Clone)]
+pub struct RandomAgent<T: Rng + 'static> {
+    pub current_random: Rc<RefCell<T>>,
+    pub probability_of_attack: f64,
+}
+
+impl<T: Rng> RandomAgent<T> {
+    pub fn new(current_random: Rc<RefCell<T>>, probability_of_attack: f64) -> Self {
+        Self {
+            current_random,
+            probability_of_attack,
+        }
+    }
+}
+
+impl<T: Rng> GameAgent for RandomAgent<T> {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        _history: &HistoryView,
+    ) -> Action {
+        let decision = self
+            .current_random
+            .borrow_mut()
+            .random_bool(self.probability_of_attack);
+        if decision {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!("Attack with probability {}", self.probability_of_attack)
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            current_random: self.current_random.clone(),
+            probability_of_attack: self.probability_of_attack,
+        })
+    }
+}
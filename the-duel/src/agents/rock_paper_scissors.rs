@@ -0,0 +1,154 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+
+use crate::agents::GameAgent;
+use crate::history::HistoryView;
+use crate::rock_paper_scissors::{Hand, HandPayoffs, RockPaperScissors, RpsPlayer};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RpsStrategy {
+    // Always plays the same hand.
+    Constant(Hand),
+    // Plays rock, paper and scissors in turn.
+    Cycle,
+    // Draws every hand from the equilibrium mix of the payoffs.
+    Equilibrium,
+    // Best response to the opponent's last hand, which chases a repeating
+    // opponent around the cycle.
+    BeatLast,
+    // Fictitious play: best response to the frequencies of all the
+    // opponent's hands so far.
+    FictitiousPlay,
+    // Predicts the opponent's next hand from what followed its last hand
+    // before, and plays the best response to the prediction.
+    MarkovPredictor,
+}
+
+impl RpsStrategy {
+    pub const ALL: [RpsStrategy; 8] = [
+        RpsStrategy::Constant(Hand::ROCK),
+        RpsStrategy::Constant(Hand::PAPER),
+        RpsStrategy::Constant(Hand::SCISSORS),
+        RpsStrategy::Cycle,
+        RpsStrategy::Equilibrium,
+        RpsStrategy::BeatLast,
+        RpsStrategy::FictitiousPlay,
+        RpsStrategy::MarkovPredictor,
+    ];
+
+    pub fn parse(spec: &str) -> Option<Self> {
+        match spec.trim() {
+            "rock" => Some(RpsStrategy::Constant(Hand::ROCK)),
+            "paper" => Some(RpsStrategy::Constant(Hand::PAPER)),
+            "scissors" => Some(RpsStrategy::Constant(Hand::SCISSORS)),
+            "cycle" => Some(RpsStrategy::Cycle),
+            "equilibrium" => Some(RpsStrategy::Equilibrium),
+            "beat_last" => Some(RpsStrategy::BeatLast),
+            "fictitious_play" => Some(RpsStrategy::FictitiousPlay),
+            "markov_predictor" => Some(RpsStrategy::MarkovPredictor),
+            _ => None,
+        }
+    }
+}
+
+pub struct RpsAgent<T: Rng + 'static> {
+    pub strategy: RpsStrategy,
+    pub payoffs: HandPayoffs,
+    pub current_random: Rc<RefCell<T>>,
+}
+
+impl<T: Rng> RpsAgent<T> {
+    pub fn new(
+        strategy: RpsStrategy,
+        payoffs: HandPayoffs,
+        current_random: Rc<RefCell<T>>,
+    ) -> Self {
+        Self {
+            strategy,
+            payoffs,
+            current_random,
+        }
+    }
+
+    fn equilibrium_hand(&self) -> Hand {
+        let mix = WeightedIndex::new(self.payoffs.equilibrium()).expect("payoffs are positive");
+        Hand::ALL[mix.sample(&mut *self.current_random.borrow_mut())]
+    }
+}
+
+// Frequencies of `hands`, in the order of `Hand::ALL`; uniform without any.
+fn frequencies<'a>(hands: impl Iterator<Item = &'a Hand>) -> [f64; 3] {
+    let mut counts = [0.0; 3];
+    for hand in hands {
+        counts[hand.index()] += 1.0;
+    }
+    let total: f64 = counts.iter().sum();
+    if total == 0.0 {
+        return [1.0 / 3.0; 3];
+    }
+    counts.map(|count| count / total)
+}
+
+impl<T: Rng> GameAgent<RockPaperScissors> for RpsAgent<T> {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &RpsPlayer,
+        _opposing_player_actions: &Option<Hand>,
+        _opposing_player_state: &Option<RpsPlayer>,
+        history: &HistoryView<RockPaperScissors>,
+    ) -> Hand {
+        let opposing = history.opposing_actions;
+        match self.strategy {
+            RpsStrategy::Constant(hand) => hand,
+            RpsStrategy::Cycle => Hand::ALL[history.turns() % Hand::ALL.len()],
+            RpsStrategy::Equilibrium => self.equilibrium_hand(),
+            RpsStrategy::BeatLast => match opposing.last() {
+                Some(last) => last.beaten_by(),
+                None => self.equilibrium_hand(),
+            },
+            RpsStrategy::FictitiousPlay => match opposing.last() {
+                Some(_) => self.payoffs.best_response(&frequencies(opposing.iter())),
+                None => self.equilibrium_hand(),
+            },
+            RpsStrategy::MarkovPredictor => {
+                let Some(last) = opposing.last() else {
+                    return self.equilibrium_hand();
+                };
+                let followers: Vec<&Hand> = opposing
+                    .windows(2)
+                    .filter(|pair| pair[0] == *last)
+                    .map(|pair| &pair[1])
+                    .collect();
+                if followers.is_empty() {
+                    self.equilibrium_hand()
+                } else {
+                    self.payoffs
+                        .best_response(&frequencies(followers.into_iter()))
+                }
+            }
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        match self.strategy {
+            RpsStrategy::Constant(hand) => format!("Always {}", hand),
+            RpsStrategy::Cycle => String::from("Cycle"),
+            RpsStrategy::Equilibrium => String::from("Equilibrium mix"),
+            RpsStrategy::BeatLast => String::from("Beat last hand"),
+            RpsStrategy::FictitiousPlay => String::from("Fictitious play"),
+            RpsStrategy::MarkovPredictor => String::from("Markov predictor"),
+        }
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent<RockPaperScissors>> {
+        Box::new(Self::new(
+            self.strategy,
+            self.payoffs.clone(),
+            self.current_random.clone(),
+        ))
+    }
+}
@@ -0,0 +1,74 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::{GameAgent, opposing_hit_points};
+use crate::dsl::{DslError, Rule, Situation};
+use crate::duel::{Action, DamageMatrix, PlayerState};
+use crate::history::HistoryView;
+
+// Plays by a rule of the strategy language, see crate::dsl.
+pub struct RuleAgent<T: Rng + 'static> {
+    pub current_random: Rc<RefCell<T>>,
+    pub source: String,
+    pub rule: Rule,
+    // The damage matrix hidden opposing hit points are recounted by.
+    pub damage: DamageMatrix,
+}
+
+impl<T: Rng> RuleAgent<T> {
+    pub fn new(current_random: Rc<RefCell<T>>, source: &str) -> Result<Self, DslError> {
+        Ok(Self {
+            current_random,
+            source: source.trim().to_string(),
+            rule: Rule::parse(source)?,
+            damage: DamageMatrix::default(),
+        })
+    }
+
+    pub fn with_damage(mut self, damage: DamageMatrix) -> Self {
+        self.damage = damage;
+        self
+    }
+}
+
+impl<T: Rng> GameAgent for RuleAgent<T> {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let situation = Situation {
+            own_hit_points: own_player_state.current_hit_points,
+            opposing_hit_points: opposing_hit_points(
+                own_player_state,
+                opposing_player_state,
+                history,
+                &self.damage,
+            ),
+            history,
+        };
+        let probability = self.rule.attack_probability(&situation).clamp(0.0, 1.0);
+        if self.current_random.borrow_mut().random_bool(probability) {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!("Rule '{}'", self.source)
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            current_random: self.current_random.clone(),
+            source: self.source.clone(),
+            rule: self.rule.clone(),
+            damage: self.damage.clone(),
+        })
+    }
+}
@@ -0,0 +1,267 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agents::{GameAgent, SharedRng};
+use crate::duel::{Action, PlayerState};
+use crate::experience::{ExperienceBuffer, SharedExperience, Transition};
+use crate::game::AgentMode;
+use crate::history::HistoryView;
+
+// What a temporal difference learner sees of the duel: both hit points, if
+// the opponent's are observable, and the opponent's last action.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DuelState {
+    pub own_hit_points: i64,
+    pub opposing_hit_points: Option<i64>,
+    pub opposing_attacked: Option<bool>,
+}
+
+impl DuelState {
+    pub fn encode(
+        own_player_state: &PlayerState,
+        opposing_player_action: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+    ) -> Self {
+        Self {
+            own_hit_points: own_player_state.current_hit_points,
+            opposing_hit_points: opposing_player_state
+                .as_ref()
+                .map(|state| state.current_hit_points),
+            opposing_attacked: opposing_player_action
+                .as_ref()
+                .map(|action| *action == Action::ATTACK),
+        }
+    }
+
+    // Hit points the opponent lost minus hit points lost on the way to `next`.
+    fn reward(&self, next: &DuelState) -> f64 {
+        let lost = (self.own_hit_points - next.own_hit_points) as f64;
+        let dealt = match (self.opposing_hit_points, next.opposing_hit_points) {
+            (Some(before), Some(after)) => (before - after) as f64,
+            _ => 0.0,
+        };
+        dealt - lost
+    }
+}
+
+const ACTIONS: [Action; 2] = [Action::ATTACK, Action::FINCH];
+
+// Estimated return of attacking and finching in every state seen so far.
+pub type QTable = HashMap<DuelState, [f64; 2]>;
+
+// How the value of the next state enters the temporal difference target.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TdRule {
+    // Off-policy: the value of the greedy action.
+    QLearning,
+    // On-policy: the value of the action actually taken next.
+    Sarsa,
+    // On-policy: the value of the next action expected under the epsilon-greedy policy.
+    ExpectedSarsa,
+}
+
+impl TdRule {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TdRule::QLearning => "Q-learning",
+            TdRule::Sarsa => "SARSA",
+            TdRule::ExpectedSarsa => "Expected SARSA",
+        }
+    }
+}
+
+// Learns action values by temporal difference updates with an epsilon-greedy
+// policy, rewarded by the hit point difference of every turn. Copies share
+// their table, so a roster entry trained over a whole tournament learns from
+// all its games; evaluations leave the table alone. With experience replay,
+// every update is followed by `replay` updates on transitions drawn from the
+// shared buffer. The last turn of a game is never observed and so never
+// learned from.
+#[derive(Clone)]
+pub struct TdAgent {
+    pub rng: SharedRng,
+    pub rule: TdRule,
+    pub learning_rate: f64,
+    pub discount: f64,
+    pub exploration: f64,
+    pub table: Rc<RefCell<QTable>>,
+    pub mode: AgentMode,
+    pub experience: Option<SharedExperience<DuelState>>,
+    pub replay: usize,
+    // State and action of the previous turn of the current game.
+    previous: Option<(DuelState, usize)>,
+}
+
+impl TdAgent {
+    pub fn new(
+        rng: SharedRng,
+        rule: TdRule,
+        learning_rate: f64,
+        discount: f64,
+        exploration: f64,
+    ) -> Self {
+        Self {
+            rng,
+            rule,
+            learning_rate,
+            discount,
+            exploration,
+            table: Rc::new(RefCell::new(QTable::new())),
+            mode: AgentMode::default(),
+            experience: None,
+            replay: 0,
+            previous: None,
+        }
+    }
+
+    pub fn with_table(mut self, table: Rc<RefCell<QTable>>) -> Self {
+        self.table = table;
+        self
+    }
+
+    // Keeps the last `capacity` transitions and replays `replay` of them
+    // after every update.
+    pub fn with_replay(mut self, capacity: usize, replay: usize) -> Self {
+        self.experience = Some(Rc::new(RefCell::new(ExperienceBuffer::new(capacity))));
+        self.replay = replay;
+        self
+    }
+
+    // A greedy copy of the current table that neither explores nor learns.
+    pub fn frozen(&self) -> Self {
+        Self {
+            learning_rate: 0.0,
+            exploration: 0.0,
+            table: Rc::new(RefCell::new(self.table.borrow().clone())),
+            experience: None,
+            replay: 0,
+            previous: None,
+            ..self.clone()
+        }
+    }
+
+    fn greedy(values: &[f64; 2]) -> usize {
+        if values[1] > values[0] { 1 } else { 0 }
+    }
+
+    fn choose(&self, values: &[f64; 2]) -> usize {
+        let mut rng = self.rng.borrow_mut();
+        if rng.random::<f64>() < self.exploration {
+            rng.random_range(0..ACTIONS.len())
+        } else {
+            Self::greedy(values)
+        }
+    }
+
+    fn learn(&self, transition: &Transition<DuelState>) {
+        let mut table = self.table.borrow_mut();
+        let values = *table.entry(transition.next).or_default();
+        let target =
+            transition.reward + self.discount * self.next_value(&values, transition.next_action);
+        let estimate = &mut table.entry(transition.state).or_default()[transition.action];
+        *estimate += self.learning_rate * (target - *estimate);
+    }
+
+    // Value of `state` for the target of `rule`, `action` being the next action taken.
+    fn next_value(&self, values: &[f64; 2], action: usize) -> f64 {
+        match self.rule {
+            TdRule::QLearning => values[Self::greedy(values)],
+            TdRule::Sarsa => values[action],
+            TdRule::ExpectedSarsa => {
+                let greedy = Self::greedy(values);
+                let explore = self.exploration / ACTIONS.len() as f64;
+                (0..ACTIONS.len())
+                    .map(|a| {
+                        let probability = if a == greedy {
+                            1.0 - self.exploration + explore
+                        } else {
+                            explore
+                        };
+                        probability * values[a]
+                    })
+                    .sum()
+            }
+        }
+    }
+}
+
+impl GameAgent for TdAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        if history.turns() == 0 {
+            self.previous = None;
+        }
+        let state = DuelState::encode(
+            own_player_state,
+            opposing_player_actions,
+            opposing_player_state,
+        );
+        // Evaluations must not add the states they visit to the shared table.
+        let values = match self.mode {
+            AgentMode::Train => *self.table.borrow_mut().entry(state).or_default(),
+            AgentMode::Eval => self.table.borrow().get(&state).copied().unwrap_or_default(),
+        };
+        let action = self.choose(&values);
+
+        if let Some((previous, taken)) = self.previous
+            && self.mode == AgentMode::Train
+        {
+            let transition = Transition {
+                state: previous,
+                action: taken,
+                reward: previous.reward(&state),
+                next: state,
+                next_action: action,
+            };
+            self.learn(&transition);
+            if let Some(experience) = &self.experience {
+                let replayed = {
+                    let mut experience = experience.borrow_mut();
+                    experience.push(transition);
+                    experience.sample(self.replay, &mut *self.rng.borrow_mut())
+                };
+                replayed
+                    .iter()
+                    .for_each(|transition| self.learn(transition));
+            }
+        }
+        self.previous = Some((state, action));
+        ACTIONS[action].clone()
+    }
+
+    fn strategy_name(&self) -> String {
+        format!(
+            "{} with learning rate {}, discount {}, exploration {}{}",
+            self.rule.name(),
+            self.learning_rate,
+            self.discount,
+            self.exploration,
+            if self.replay > 0 {
+                format!(", replaying {}", self.replay)
+            } else {
+                String::new()
+            }
+        )
+    }
+
+    fn set_mode(&mut self, mode: AgentMode) {
+        self.mode = mode;
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            previous: None,
+            ..self.clone()
+        })
+    }
+}
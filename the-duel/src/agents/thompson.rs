@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// Bayesian counterpart of the one-step decision process: keeps a Beta
+// posterior over the opponent's probability of attack, draws a probability
+// from it every turn and plays the best one-step response to the draw.
+#[derive(Clone)]
+pub struct ThompsonSamplingAgent<T: Rng + 'static> {
+    pub current_random: Rc<RefCell<T>>,
+    pub cost_losing_hp: f64,
+    pub cost_not_losing_hp: f64,
+    pub cost_equivalent_exchange: f64,
+    // Pseudo-counts of the Beta prior.
+    pub prior_attacks: f64,
+    pub prior_finches: f64,
+    pub num_attacks: i64,
+    pub num_finches: i64,
+}
+
+impl<T: Rng> ThompsonSamplingAgent<T> {
+    pub fn new(
+        current_random: Rc<RefCell<T>>,
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+        prior_attacks: f64,
+        prior_finches: f64,
+    ) -> Self {
+        Self {
+            current_random,
+            cost_losing_hp,
+            cost_not_losing_hp,
+            cost_equivalent_exchange,
+            prior_attacks,
+            prior_finches,
+            num_attacks: 0,
+            num_finches: 0,
+        }
+    }
+}
+
+impl<T: Rng> GameAgent for ThompsonSamplingAgent<T> {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        _history: &HistoryView,
+    ) -> Action {
+        match opposing_player_actions {
+            Some(Action::ATTACK) => self.num_attacks += 1,
+            Some(Action::FINCH) => self.num_finches += 1,
+            None => {}
+        }
+
+        let prob = {
+            let mut rng = self.current_random.borrow_mut();
+            let attacks = sample_gamma(self.prior_attacks + self.num_attacks as f64, &mut *rng);
+            let finches = sample_gamma(self.prior_finches + self.num_finches as f64, &mut *rng);
+            attacks / (attacks + finches)
+        };
+
+        let attack_reward =
+            self.cost_losing_hp * (1.0 - prob) + self.cost_equivalent_exchange * prob;
+        let finch_reward =
+            self.cost_not_losing_hp * prob + self.cost_equivalent_exchange * (1.0 - prob);
+
+        if attack_reward > finch_reward {
+            Action::ATTACK
+        } else {
+            Action::FINCH
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!(
+            "Sample the probability of attack from a Beta({}, {}) posterior, and design optimal one-step decision.",
+            self.prior_attacks, self.prior_finches
+        )
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self {
+            current_random: self.current_random.clone(),
+            ..*self
+        })
+    }
+}
+
+// Gamma(shape, 1) distributed sample by Marsaglia and Tsang's method, boosted
+// for shapes below one.
+fn sample_gamma<R: Rng>(shape: f64, rng: &mut R) -> f64 {
+    if shape < 1.0 {
+        let u: f64 = 1.0 - rng.random::<f64>();
+        return sample_gamma(shape + 1.0, rng) * u.powf(1.0 / shape);
+    }
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        // Box-Muller transform of two uniform samples
+        let u: f64 = 1.0 - rng.random::<f64>();
+        let v: f64 = rng.random();
+        let x = (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos();
+        let cube = (1.0 + c * x).powi(3);
+        if cube <= 0.0 {
+            continue;
+        }
+        let w: f64 = 1.0 - rng.random::<f64>();
+        if w.ln() < 0.5 * x * x + d - d * cube + d * cube.ln() {
+            return d * cube;
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use crate::agents::GameAgent;
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+
+// Opens with `initial` and then repeats the opponent's previous action. Unlike
+// MirrorAgent, it reads the action from the history, so it also responds in
+// kind when the opponent's actions are not observed directly.
+#[derive(Clone)]
+pub struct TitForTatAgent {
+    pub initial: Action,
+}
+
+impl TitForTatAgent {
+    pub fn new(initial: Action) -> Self {
+        Self { initial }
+    }
+}
+
+impl GameAgent for TitForTatAgent {
+    fn decide_action(
+        &mut self,
+        _own_player_state: &PlayerState,
+        _opposing_player_actions: &Option<Action>,
+        _opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        history
+            .opposing_actions
+            .last()
+            .cloned()
+            .unwrap_or_else(|| self.initial.clone())
+    }
+
+    fn strategy_name(&self) -> String {
+        format!("Tit-for-Tat opening with {:?}", self.initial)
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(self.clone())
+    }
+}
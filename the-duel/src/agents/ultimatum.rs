@@ -0,0 +1,168 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rand::Rng;
+
+use crate::agents::GameAgent;
+use crate::history::HistoryView;
+use crate::ultimatum::{Bargainer, Bid, Role, Ultimatum};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UltimatumStrategy {
+    // The subgame perfect play: offers the smallest positive share and
+    // accepts anything.
+    Rational,
+    // Offers half of the pie and accepts no less.
+    Fair,
+    // Offers the given fraction of the pie and accepts no less.
+    Threshold(f64),
+    // Offers a share drawn uniformly and accepts every other offer.
+    Random,
+    // Starts with the smallest positive offer, raises it after every
+    // rejection and lowers it after every acceptance; as responder, accepts
+    // offers of at least what the opponent last accepted from it.
+    Adaptive,
+}
+
+impl UltimatumStrategy {
+    pub const ALL: [UltimatumStrategy; 5] = [
+        UltimatumStrategy::Rational,
+        UltimatumStrategy::Fair,
+        UltimatumStrategy::Threshold(0.3),
+        UltimatumStrategy::Random,
+        UltimatumStrategy::Adaptive,
+    ];
+
+    // Parses names like `fair` or `threshold(0.3)`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (name, argument) = match spec.split_once('(') {
+            Some((name, rest)) => (name.trim(), Some(rest.strip_suffix(')')?.trim())),
+            None => (spec.trim(), None),
+        };
+        match (name, argument) {
+            ("rational", None) => Some(UltimatumStrategy::Rational),
+            ("fair", None) => Some(UltimatumStrategy::Fair),
+            ("random", None) => Some(UltimatumStrategy::Random),
+            ("adaptive", None) => Some(UltimatumStrategy::Adaptive),
+            ("threshold", Some(argument)) => argument
+                .parse()
+                .ok()
+                .filter(|f| (0.0..=1.0).contains(f))
+                .map(UltimatumStrategy::Threshold),
+            _ => None,
+        }
+    }
+}
+
+pub struct UltimatumAgent<T: Rng + 'static> {
+    pub strategy: UltimatumStrategy,
+    pub pie: u32,
+    pub current_random: Rc<RefCell<T>>,
+}
+
+impl<T: Rng> UltimatumAgent<T> {
+    pub fn new(strategy: UltimatumStrategy, pie: u32, current_random: Rc<RefCell<T>>) -> Self {
+        Self {
+            strategy,
+            pie,
+            current_random,
+        }
+    }
+
+    fn share(&self, fraction: f64) -> u32 {
+        (fraction * self.pie as f64).round() as u32
+    }
+
+    // The adaptive offer: its last offer, one more if it was rejected and
+    // one less if it was accepted.
+    fn adaptive_offer(&self, history: &HistoryView<Ultimatum>) -> u32 {
+        let last_offer = history
+            .own_actions
+            .iter()
+            .zip(history.opposing_actions.iter().skip(1))
+            .filter_map(|(own, answer)| match own {
+                Bid::OFFER(share) => Some((*share, *answer)),
+                _ => None,
+            })
+            .next_back();
+        match last_offer {
+            Some((share, Bid::REJECT)) => (share + 1).min(self.pie),
+            Some((share, _)) => share.saturating_sub(1).max(1),
+            None => 1,
+        }
+    }
+
+    fn propose(&self, history: &HistoryView<Ultimatum>) -> u32 {
+        match self.strategy {
+            UltimatumStrategy::Rational => 1.min(self.pie),
+            UltimatumStrategy::Fair => self.pie / 2,
+            UltimatumStrategy::Threshold(fraction) => self.share(fraction),
+            UltimatumStrategy::Random => {
+                self.current_random.borrow_mut().random_range(0..=self.pie)
+            }
+            UltimatumStrategy::Adaptive => self.adaptive_offer(history),
+        }
+    }
+
+    fn accepts(&self, share: u32, history: &HistoryView<Ultimatum>) -> bool {
+        match self.strategy {
+            UltimatumStrategy::Rational => true,
+            UltimatumStrategy::Fair => share >= self.pie / 2,
+            UltimatumStrategy::Threshold(fraction) => share >= self.share(fraction),
+            UltimatumStrategy::Random => self.current_random.borrow_mut().random_bool(0.5),
+            UltimatumStrategy::Adaptive => {
+                // Offers accepted before, the current one excluded
+                let accepted = history
+                    .opposing_actions
+                    .iter()
+                    .zip(history.own_actions.iter().skip(1))
+                    .filter_map(|(offer, answer)| match (offer, answer) {
+                        (Bid::OFFER(share), Bid::ACCEPT) => Some(*share),
+                        _ => None,
+                    })
+                    .next_back();
+                share >= accepted.unwrap_or(0)
+            }
+        }
+    }
+}
+
+impl<T: Rng> GameAgent<Ultimatum> for UltimatumAgent<T> {
+    fn decide_action(
+        &mut self,
+        own_player_state: &Bargainer,
+        opposing_player_actions: &Option<Bid>,
+        _opposing_player_state: &Option<Bargainer>,
+        history: &HistoryView<Ultimatum>,
+    ) -> Bid {
+        match (own_player_state.role, opposing_player_actions) {
+            (Role::Proposer, _) => Bid::OFFER(self.propose(history)),
+            (Role::Responder, Some(Bid::OFFER(share))) => {
+                if self.accepts(*share, history) {
+                    Bid::ACCEPT
+                } else {
+                    Bid::REJECT
+                }
+            }
+            (Role::Responder, _) => Bid::REJECT,
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        match self.strategy {
+            UltimatumStrategy::Rational => String::from("Rational"),
+            UltimatumStrategy::Fair => String::from("Fair"),
+            UltimatumStrategy::Threshold(fraction) => format!("Threshold at {}", fraction),
+            UltimatumStrategy::Random => String::from("Random"),
+            UltimatumStrategy::Adaptive => String::from("Adaptive"),
+        }
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent<Ultimatum>> {
+        Box::new(Self::new(
+            self.strategy,
+            self.pie,
+            self.current_random.clone(),
+        ))
+    }
+}
@@ -0,0 +1,177 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct AlphaRankConfig {
+    #[serde(default = "default_population_size")]
+    pub population_size: usize,
+    // Ranking intensity: how strongly fitter mutants are favoured. Large
+    // values rank by the payoffs' order alone, small ones approach chance.
+    #[serde(default = "default_alpha")]
+    pub alpha: f64,
+    // The stationary distribution is iterated until no mass moves by more.
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: usize,
+}
+
+fn default_population_size() -> usize {
+    50
+}
+
+fn default_alpha() -> f64 {
+    100.0
+}
+
+fn default_tolerance() -> f64 {
+    1e-12
+}
+
+fn default_max_iterations() -> usize {
+    1_000_000
+}
+
+// Alpha-Rank (Omidshafiei et al., 2019) of the strategies of a symmetric
+// meta-game, `payoffs[i][j]` being the payoff of strategy i against j. In
+// the limit of rare mutations, a population of `population_size` is
+// monomorphic almost all the time, and a single mutant either dies out or
+// takes over before the next one appears. The population then walks a
+// Markov chain over the strategies, moving from a resident to each other
+// strategy with the chance that a single mutant of it fixates under
+// Fermi selection of intensity `alpha`. Strategies are ranked by the mass
+// of the chain's stationary distribution, which stays well defined where
+// the payoffs are intransitive: the strategies of a cycle share its mass.
+pub struct AlphaRank {
+    pub payoffs: Vec<Vec<f64>>,
+    pub population_size: usize,
+    pub alpha: f64,
+}
+
+pub struct AlphaRanking {
+    // `fixation[i][j]` is the chance that a single mutant of strategy i takes
+    // over a population of strategy j.
+    pub fixation: Vec<Vec<f64>>,
+    // The stationary distribution of the chain over the strategies.
+    pub mass: Vec<f64>,
+    pub iterations: usize,
+}
+
+impl AlphaRanking {
+    // Strategies by decreasing mass.
+    pub fn order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.mass.len()).collect();
+        order.sort_by(|a, b| self.mass[*b].total_cmp(&self.mass[*a]));
+        order
+    }
+}
+
+impl AlphaRank {
+    pub fn new(payoffs: Vec<Vec<f64>>, config: &AlphaRankConfig) -> Self {
+        Self {
+            payoffs,
+            population_size: config.population_size,
+            alpha: config.alpha,
+        }
+    }
+
+    pub fn num_strategies(&self) -> usize {
+        self.payoffs.len()
+    }
+
+    // The chance that a single `mutant` takes over a population of
+    // `resident`s. With k mutants, every individual's fitness is its
+    // average payoff against the m - 1 others, and the chance of fixation
+    // is 1 / (1 + sum over l < m of the product over k <= l of
+    // exp(-alpha * (mutant fitness - resident fitness))), summed in log
+    // space so that large intensities neither overflow nor underflow.
+    pub fn fixation_probability(&self, mutant: usize, resident: usize) -> f64 {
+        let m = self.population_size;
+        if m < 2 {
+            return 1.0;
+        }
+        let others = (m - 1) as f64;
+        let payoffs = &self.payoffs;
+        let mut exponent = 0.0;
+        let mut exponents = vec![0.0];
+        for k in 1..m {
+            let mutants = k as f64;
+            let residents = (m - k) as f64;
+            let mutant_fitness = ((mutants - 1.0) * payoffs[mutant][mutant]
+                + residents * payoffs[mutant][resident])
+                / others;
+            let resident_fitness = (mutants * payoffs[resident][mutant]
+                + (residents - 1.0) * payoffs[resident][resident])
+                / others;
+            exponent -= self.alpha * (mutant_fitness - resident_fitness);
+            exponents.push(exponent);
+        }
+        let largest = exponents.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let log_sum = largest
+            + exponents
+                .iter()
+                .map(|exponent| (exponent - largest).exp())
+                .sum::<f64>()
+                .ln();
+        (-log_sum).exp()
+    }
+
+    // Fixation probabilities and the stationary distribution, iterated from
+    // equal mass on every strategy.
+    pub fn rank(&self, tolerance: f64, max_iterations: usize) -> AlphaRanking {
+        let num_strategies = self.num_strategies();
+        let fixation: Vec<Vec<f64>> = (0..num_strategies)
+            .map(|mutant| {
+                (0..num_strategies)
+                    .map(|resident| {
+                        if mutant == resident {
+                            0.0
+                        } else {
+                            self.fixation_probability(mutant, resident)
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        // Every other strategy is the next mutant with equal chance.
+        let mutation = 1.0 / (num_strategies - 1).max(1) as f64;
+        let stay: Vec<f64> = (0..num_strategies)
+            .map(|resident| {
+                1.0 - mutation
+                    * (0..num_strategies)
+                        .map(|mutant| fixation[mutant][resident])
+                        .sum::<f64>()
+            })
+            .collect();
+
+        let mut mass = vec![1.0 / num_strategies as f64; num_strategies];
+        let mut iterations = 0;
+        while iterations < max_iterations {
+            iterations += 1;
+            let next: Vec<f64> = (0..num_strategies)
+                .map(|strategy| {
+                    mass[strategy] * stay[strategy]
+                        + (0..num_strategies)
+                            .filter(|&resident| resident != strategy)
+                            .map(|resident| {
+                                mass[resident] * mutation * fixation[strategy][resident]
+                            })
+                            .sum::<f64>()
+                })
+                .collect();
+            let change = next
+                .iter()
+                .zip(&mass)
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f64::max);
+            mass = next;
+            if change < tolerance {
+                break;
+            }
+        }
+        AlphaRanking {
+            fixation,
+            mass,
+            iterations,
+        }
+    }
+}
@@ -0,0 +1,362 @@
+// The arena: the duel with a larger set of moves. Next to attacking and
+// finching, fighters may heal, parry and charge:
+//
+//   ATTACK  deals 1 damage.
+//   FINCH   counters an attack, costing the attacker 1, and blocks a charged
+//           attack. Two finches cost both fighters 1, as in the duel.
+//   HEAL    restores `heal_amount` hit points after the exchange, up to the
+//           maximum. Healing is then unavailable for `heal_cooldown` turns.
+//   PARRY   reflects an attack, costing the attacker 2. Parrying anything
+//           else leaves the fighter open and costs it 1.
+//   CHARGE  winds up for a turn and releases `charge_damage` damage on the
+//           next one, which only a finch blocks. A fighter winding up has to
+//           release, i.e. play CHARGE again.
+//
+// Played with attacks and finches only, the arena is the standard duel.
+// Illegal moves are replaced by FINCH, or by the release of a charge.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{GameOutcome, GameSettings, GameState, SimultaneousGame, bucket_hit_points};
+use crate::openspiel::SpielGame;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Move {
+    ATTACK,
+    FINCH,
+    HEAL,
+    PARRY,
+    CHARGE,
+}
+
+impl Move {
+    pub const ALL: [Move; 5] = [
+        Move::ATTACK,
+        Move::FINCH,
+        Move::HEAL,
+        Move::PARRY,
+        Move::CHARGE,
+    ];
+
+    // Position of the move in `ALL`.
+    pub fn index(&self) -> usize {
+        match self {
+            Move::ATTACK => 0,
+            Move::FINCH => 1,
+            Move::HEAL => 2,
+            Move::PARRY => 3,
+            Move::CHARGE => 4,
+        }
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// Whether a fighter that played `moves` is winding up a charge, i.e. has to
+// release it next turn. Agents use this to tell an opponent's wind-up from
+// its release.
+pub fn winding_up(moves: &[Move]) -> bool {
+    let charges = moves
+        .iter()
+        .rev()
+        .take_while(|played| **played == Move::CHARGE)
+        .count();
+    charges % 2 == 1
+}
+
+// What agents get to know about a move under the rules of an arena.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MoveInfo {
+    pub name: String,
+    pub description: &'static str,
+    // The most damage the move deals to the opponent.
+    pub damage: i64,
+    pub healing: i64,
+    // Turns the move is unavailable after playing it.
+    pub cooldown: usize,
+    // Turns from playing the move to its effect.
+    pub turns: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fighter {
+    pub max_hit_points: i64,
+    pub current_hit_points: i64,
+    // Turns until healing is available again.
+    pub heal_cooldown: usize,
+    // Wound up a charge last turn and releases it this turn.
+    pub charging: bool,
+}
+
+impl Fighter {
+    pub fn new(max_hit_points: i64) -> Self {
+        Self {
+            max_hit_points,
+            current_hit_points: max_hit_points,
+            heal_cooldown: 0,
+            charging: false,
+        }
+    }
+
+    // The moves the fighter may play this turn, in the order of `Move::ALL`.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.charging {
+            return vec![Move::CHARGE];
+        }
+        Move::ALL
+            .into_iter()
+            .filter(|action| *action != Move::HEAL || self.heal_cooldown == 0)
+            .collect()
+    }
+
+    pub fn is_legal(&self, action: &Move) -> bool {
+        self.legal_moves().contains(action)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Arena {
+    pub max_hit_points: i64,
+    pub heal_amount: i64,
+    pub heal_cooldown: usize,
+    pub charge_damage: i64,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self {
+            max_hit_points: 600,
+            heal_amount: 2,
+            heal_cooldown: 3,
+            charge_damage: 3,
+        }
+    }
+}
+
+impl Arena {
+    pub fn new(max_hit_points: i64) -> Self {
+        Self {
+            max_hit_points,
+            ..Self::default()
+        }
+    }
+
+    pub fn move_info(&self, action: &Move) -> MoveInfo {
+        let (description, damage, healing, cooldown, turns) = match action {
+            Move::ATTACK => ("deals damage unless finched or parried", 1, 0, 0, 1),
+            Move::FINCH => ("counters attacks and blocks charged attacks", 0, 0, 0, 1),
+            Move::HEAL => (
+                "restores hit points, then needs to cool down",
+                0,
+                self.heal_amount,
+                self.heal_cooldown,
+                1,
+            ),
+            Move::PARRY => ("reflects attacks, costs hit points otherwise", 2, 0, 0, 1),
+            Move::CHARGE => (
+                "winds up for a turn, then releases a heavy blow",
+                self.charge_damage,
+                0,
+                0,
+                2,
+            ),
+        };
+        MoveInfo {
+            name: action.to_string(),
+            description,
+            damage,
+            healing,
+            cooldown,
+            turns,
+        }
+    }
+
+    // The damage taken by a fighter playing `own` against `opposing`, where
+    // `opposing_releases` tells whether an opposing CHARGE releases a charge.
+    pub fn damage_taken(&self, own: &Move, opposing: &Move, opposing_releases: bool) -> i64 {
+        match (opposing, own) {
+            (Move::CHARGE, Move::FINCH) if opposing_releases => 0,
+            (Move::CHARGE, _) if opposing_releases => self.charge_damage,
+            (Move::ATTACK, Move::FINCH | Move::PARRY) => 0,
+            (Move::ATTACK, _) => 1,
+            (Move::FINCH, Move::ATTACK | Move::FINCH) => 1,
+            (Move::PARRY, Move::ATTACK) => 2,
+            (_, Move::PARRY) => 1,
+            _ => 0,
+        }
+    }
+
+    fn resolve(&self, fighter: &mut Fighter, own: &Move, damage: i64) {
+        fighter.current_hit_points -= damage;
+        if *own == Move::HEAL {
+            fighter.current_hit_points =
+                (fighter.current_hit_points + self.heal_amount).min(fighter.max_hit_points);
+            fighter.heal_cooldown = self.heal_cooldown;
+        } else {
+            fighter.heal_cooldown = fighter.heal_cooldown.saturating_sub(1);
+        }
+        fighter.charging = *own == Move::CHARGE && !fighter.charging;
+    }
+}
+
+impl SimultaneousGame for Arena {
+    type PlayerState = Fighter;
+    type Action = Move;
+
+    fn initial_state(&self) -> GameState<Self> {
+        GameState::new(
+            Fighter::new(self.max_hit_points),
+            Fighter::new(self.max_hit_points),
+        )
+    }
+
+    fn resolve_actions(
+        &self,
+        state: &mut GameState<Self>,
+        player_one_action: Move,
+        player_two_action: Move,
+    ) {
+        let player_one_damage = self.damage_taken(
+            &player_one_action,
+            &player_two_action,
+            state.player_two_state.charging,
+        );
+        let player_two_damage = self.damage_taken(
+            &player_two_action,
+            &player_one_action,
+            state.player_one_state.charging,
+        );
+        self.resolve(
+            &mut state.player_one_state,
+            &player_one_action,
+            player_one_damage,
+        );
+        self.resolve(
+            &mut state.player_two_state,
+            &player_two_action,
+            player_two_damage,
+        );
+    }
+
+    fn check_end_condition(&self, state: &GameState<Self>) -> GameOutcome {
+        let player_one_down = state.player_one_state.current_hit_points <= 0;
+        let player_two_down = state.player_two_state.current_hit_points <= 0;
+        match (player_one_down, player_two_down) {
+            (true, true) => GameOutcome::TIE,
+            (true, false) => GameOutcome::WIN(2),
+            (false, true) => GameOutcome::WIN(1),
+            (false, false) => GameOutcome::CONTINUE,
+        }
+    }
+
+    fn enforce_legality(&self, state: &GameState<Self>, player: u64, action: Move) -> Move {
+        let fighter = if player == 1 {
+            &state.player_one_state
+        } else {
+            &state.player_two_state
+        };
+        if fighter.is_legal(&action) {
+            action
+        } else if fighter.charging {
+            Move::CHARGE
+        } else {
+            Move::FINCH
+        }
+    }
+
+    fn bucket_state(fighter: &Fighter, buckets: usize) -> Fighter {
+        Fighter {
+            current_hit_points: bucket_hit_points(
+                fighter.current_hit_points,
+                fighter.max_hit_points,
+                buckets,
+            ),
+            ..fighter.clone()
+        }
+    }
+}
+
+// Moves are numbered as in `Move::ALL`. The observation holds the own hit
+// point fraction, heal cooldown and charge, whether the opponent's state is
+// observed and, if so, its hit point fraction and charge, followed by the
+// opponent's last move one-hot encoded.
+impl SpielGame for Arena {
+    fn num_distinct_actions(&self) -> usize {
+        Move::ALL.len()
+    }
+
+    fn action_id(&self, action: &Move) -> usize {
+        action.index()
+    }
+
+    fn action_from_id(&self, id: usize) -> Option<Move> {
+        Move::ALL.get(id).cloned()
+    }
+
+    fn action_to_string(&self, id: usize) -> String {
+        match self.action_from_id(id) {
+            Some(action) => action.to_string(),
+            None => format!("invalid action {}", id),
+        }
+    }
+
+    fn observation_tensor_size(&self) -> usize {
+        6 + Move::ALL.len()
+    }
+
+    fn observation_tensor(
+        &self,
+        state: &GameState<Self>,
+        settings: &GameSettings,
+        player: u64,
+    ) -> Vec<f32> {
+        let own_state = if player == 1 {
+            &state.player_one_state
+        } else {
+            &state.player_two_state
+        };
+        let opposing_action = state.observed_opposing_action(player);
+        let fraction = |fighter: &Fighter| {
+            fighter.current_hit_points as f32 / fighter.max_hit_points.max(1) as f32
+        };
+        let mut tensor = vec![
+            fraction(own_state),
+            own_state.heal_cooldown as f32 / self.heal_cooldown.max(1) as f32,
+            own_state.charging as u8 as f32,
+        ];
+        match settings.observe_opponent(state, player) {
+            Some(opposing_state) => tensor.extend([
+                1.0,
+                fraction(&opposing_state),
+                opposing_state.charging as u8 as f32,
+            ]),
+            None => tensor.extend([0.0; 3]),
+        }
+        tensor.extend(
+            Move::ALL
+                .iter()
+                .map(|action| (opposing_action.as_ref() == Some(action)) as u8 as f32),
+        );
+        tensor
+    }
+
+    fn legal_actions(&self, state: &GameState<Self>, player: u64) -> Vec<usize> {
+        let fighter = if player == 1 {
+            &state.player_one_state
+        } else {
+            &state.player_two_state
+        };
+        fighter
+            .legal_moves()
+            .iter()
+            .map(|action| action.index())
+            .collect()
+    }
+}
@@ -0,0 +1,286 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::agents::GameAgent;
+use crate::game::{GameOutcome, GameSettings, SimultaneousGame};
+use crate::matches::{Match, MatchFormat};
+use crate::observer::{GameObserver, NullObserver};
+use crate::tournament::PairingRecord;
+
+#[derive(Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Elimination {
+    // Agents are out after their first lost match.
+    #[default]
+    Single,
+    // Agents are out after their second lost match; a first loss drops them
+    // into the losers bracket, whose winner meets the winners bracket's
+    // champion in the grand final.
+    Double,
+}
+
+// One match of the bracket. A missing player is a bye, which the other player
+// advances past without playing.
+#[derive(Clone, Serialize)]
+pub struct BracketMatch {
+    pub player_one: Option<usize>,
+    pub player_two: Option<usize>,
+    pub record: PairingRecord,
+    pub winner: Option<usize>,
+    pub loser: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct BracketRound {
+    pub name: String,
+    pub matches: Vec<BracketMatch>,
+}
+
+#[derive(Serialize)]
+pub struct BracketResults {
+    pub agent_names: Vec<String>,
+    // Roster indices from the first to the last seed.
+    pub seeding: Vec<usize>,
+    pub rounds: Vec<BracketRound>,
+    pub champion: Option<usize>,
+}
+
+impl BracketResults {
+    fn seed_of(&self, agent: usize) -> usize {
+        self.seeding.iter().position(|&a| a == agent).unwrap() + 1
+    }
+
+    fn label(&self, agent: Option<usize>) -> String {
+        match agent {
+            Some(agent) => format!("[{}] {}", self.seed_of(agent), self.agent_names[agent]),
+            None => "bye".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for BracketResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for round in &self.rounds {
+            writeln!(f, "{}:", round.name)?;
+            for bracket_match in &round.matches {
+                match (bracket_match.player_one, bracket_match.player_two) {
+                    (Some(_), Some(_)) => writeln!(
+                        f,
+                        " {} vs {}: {}-{}-{} -> {}",
+                        self.label(bracket_match.player_one),
+                        self.label(bracket_match.player_two),
+                        bracket_match.record.wins,
+                        bracket_match.record.ties,
+                        bracket_match.record.losses,
+                        self.label(bracket_match.winner)
+                    )?,
+                    (None, None) => {}
+                    _ => writeln!(f, " {} advances on a bye", self.label(bracket_match.winner))?,
+                }
+            }
+        }
+        write!(f, "Champion: {}", self.label(self.champion))
+    }
+}
+
+// Seed numbers (starting at 1) in bracket order, placed so that the top seeds
+// can only meet in the late rounds: 1 v 8, 4 v 5, 2 v 7, 3 v 6 for eight slots.
+fn bracket_order(size: usize) -> Vec<usize> {
+    let mut order = vec![1];
+    while order.len() < size {
+        let slots = order.len() * 2;
+        order = order.iter().flat_map(|&s| [s, slots + 1 - s]).collect();
+    }
+    order
+}
+
+pub struct Bracket<G: SimultaneousGame + Clone> {
+    pub rules: G,
+    pub settings: GameSettings,
+    pub agents: Vec<Box<dyn GameAgent<G>>>,
+    pub elimination: Elimination,
+    // Roster indices from the first to the last seed. When a match ends level,
+    // the better seeded agent advances.
+    pub seeding: Vec<usize>,
+    pub format: MatchFormat,
+}
+
+impl<G: SimultaneousGame + Clone> Bracket<G> {
+    // Seeds the agents in roster order.
+    pub fn new(rules: G, agents: Vec<Box<dyn GameAgent<G>>>, elimination: Elimination) -> Self {
+        let seeding = (0..agents.len()).collect();
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            agents,
+            elimination,
+            seeding,
+            format: MatchFormat::default(),
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_seeding(mut self, seeding: Vec<usize>) -> Self {
+        self.seeding = seeding;
+        self
+    }
+
+    pub fn with_match_format(mut self, format: MatchFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn run(&self) -> BracketResults {
+        self.run_observed(&mut NullObserver)
+    }
+
+    pub fn run_observed(&self, observer: &mut dyn GameObserver<G>) -> BracketResults {
+        let size = self.seeding.len().next_power_of_two();
+        let mut winners: Vec<Option<usize>> = bracket_order(size)
+            .into_iter()
+            .map(|seed| self.seeding.get(seed - 1).copied())
+            .collect();
+        let mut losers: Vec<Option<usize>> = Vec::new();
+        let mut rounds = Vec::new();
+
+        let mut round = 1;
+        while winners.len() > 1 {
+            let (next, dropped) = self.play_round(
+                &mut rounds,
+                format!("Winners round {}", round),
+                &winners,
+                observer,
+            );
+            winners = next;
+
+            if self.elimination == Elimination::Double {
+                if losers.is_empty() {
+                    losers = dropped;
+                } else {
+                    // Fresh losers meet the losers bracket in reverse order to
+                    // put off rematches of the winners bracket.
+                    let entrants: Vec<Option<usize>> = losers
+                        .iter()
+                        .zip(dropped.iter().rev())
+                        .flat_map(|(&survivor, &dropped)| [survivor, dropped])
+                        .collect();
+                    losers = self
+                        .play_round(
+                            &mut rounds,
+                            format!("Losers round {} (major)", round),
+                            &entrants,
+                            observer,
+                        )
+                        .0;
+                }
+                if losers.len() > 1 && (winners.len() > 1 || round == 1) {
+                    losers = self
+                        .play_round(
+                            &mut rounds,
+                            format!("Losers round {}", round),
+                            &losers,
+                            observer,
+                        )
+                        .0;
+                }
+            }
+            round += 1;
+        }
+
+        let mut champion = winners.first().copied().flatten();
+        if self.elimination == Elimination::Double && !losers.is_empty() {
+            let final_match = self.play_match(champion, losers[0], observer);
+            champion = final_match.winner;
+            let reset = final_match.winner.is_some() && final_match.winner == losers[0];
+            rounds.push(BracketRound {
+                name: "Grand final".to_string(),
+                matches: vec![final_match.clone()],
+            });
+            // Both finalists have lost once now, so the final is played again
+            if reset {
+                let reset_match = self.play_match(final_match.loser, final_match.winner, observer);
+                champion = reset_match.winner;
+                rounds.push(BracketRound {
+                    name: "Grand final reset".to_string(),
+                    matches: vec![reset_match],
+                });
+            }
+        }
+
+        BracketResults {
+            agent_names: self.agents.iter().map(|a| a.strategy_name()).collect(),
+            seeding: self.seeding.clone(),
+            rounds,
+            champion,
+        }
+    }
+
+    // Plays neighbouring slots against each other, returning the winners and
+    // the losers of the round in bracket order.
+    fn play_round(
+        &self,
+        rounds: &mut Vec<BracketRound>,
+        name: String,
+        slots: &[Option<usize>],
+        observer: &mut dyn GameObserver<G>,
+    ) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+        let matches: Vec<BracketMatch> = slots
+            .chunks(2)
+            .map(|pair| self.play_match(pair[0], pair[1], observer))
+            .collect();
+        let winners = matches.iter().map(|m| m.winner).collect();
+        let losers = matches.iter().map(|m| m.loser).collect();
+        rounds.push(BracketRound { name, matches });
+        (winners, losers)
+    }
+
+    fn play_match(
+        &self,
+        player_one: Option<usize>,
+        player_two: Option<usize>,
+        observer: &mut dyn GameObserver<G>,
+    ) -> BracketMatch {
+        let (Some(one), Some(two)) = (player_one, player_two) else {
+            return BracketMatch {
+                player_one,
+                player_two,
+                record: PairingRecord::default(),
+                winner: player_one.or(player_two),
+                loser: None,
+            };
+        };
+
+        observer.on_match_start(one, two);
+        let result = Match::new(
+            &self.rules,
+            &self.settings,
+            &self.format,
+            self.agents[one].as_ref(),
+            self.agents[two].as_ref(),
+        )
+        .play_observed(observer, (one, two));
+        let mut record = PairingRecord::default();
+        record.record(&result.outcome);
+        observer.on_match_end(one, two, &record);
+
+        let seed = |agent| self.seeding.iter().position(|&a| a == agent);
+        let one_advances = match result.outcome {
+            GameOutcome::WIN(1) => true,
+            GameOutcome::WIN(_) => false,
+            _ => seed(one) < seed(two),
+        };
+        let (winner, loser) = if one_advances { (one, two) } else { (two, one) };
+        BracketMatch {
+            player_one,
+            player_two,
+            record: result.games,
+            winner: Some(winner),
+            loser: Some(loser),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// Live events of a running tournament over WebSocket, so that dashboards can
+// follow it without polling output files. Every connected client receives one
+// JSON text message per event, tagged by its `event` field:
+//
+//   {"event": "roster", "agent_names": [...], "max_hit_points": 600}
+//   {"event": "match_start", "player_one": 0, "player_two": 1}
+//   {"event": "game_start", "player_one": 0, "player_two": 1}
+//   {"event": "turn", "turn": 1, "player_one_action": "ATTACK", ...}
+//   {"event": "game_end", "turns": 12, "outcome": {"WIN": 1}}
+//   {"event": "match_end", "player_one": 0, "player_two": 1, "record": {...}}
+//   {"event": "round_end", "round": 3}
+//
+// The roster is sent to every client as soon as it connects. Clients are
+// accepted on a background thread and greeted on a thread of their own. The
+// tournament waits for clients to take its events, but a client that hangs
+// up or takes longer than `TIMEOUT` for its handshake or an event is
+// dropped. The broadcast needs the `websocket` feature.
+
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use tungstenite::{Message, WebSocket};
+
+use crate::duel::{Action, Duel};
+use crate::game::{GameOutcome, GameState};
+use crate::observer::GameObserver;
+use crate::tournament::PairingRecord;
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    Roster {
+        agent_names: &'a [String],
+        max_hit_points: i64,
+    },
+    MatchStart {
+        player_one: usize,
+        player_two: usize,
+    },
+    GameStart {
+        player_one: usize,
+        player_two: usize,
+    },
+    Turn {
+        turn: usize,
+        player_one_action: &'a Option<Action>,
+        player_two_action: &'a Option<Action>,
+        player_one_hit_points: i64,
+        player_two_hit_points: i64,
+    },
+    GameEnd {
+        turns: usize,
+        outcome: &'a GameOutcome,
+    },
+    MatchEnd {
+        player_one: usize,
+        player_two: usize,
+        record: &'a PairingRecord,
+    },
+    RoundEnd {
+        round: u64,
+    },
+}
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+type Clients = Arc<Mutex<Vec<WebSocket<TcpStream>>>>;
+
+// Completes the handshake of a client and greets it with the roster, giving
+// up on clients that fail or time out.
+fn greet(stream: TcpStream, roster: &str) -> Option<WebSocket<TcpStream>> {
+    stream.set_nodelay(true).ok()?;
+    stream.set_read_timeout(Some(TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(TIMEOUT)).ok()?;
+    let mut client = tungstenite::accept(stream).ok()?;
+    client.send(Message::text(roster)).ok()?;
+    Some(client)
+}
+
+// Observer sending the events of a tournament to all connected WebSocket clients.
+pub struct BroadcastObserver {
+    clients: Clients,
+    local_address: String,
+}
+
+impl BroadcastObserver {
+    // Starts accepting clients at `address`; the roster is greeted to each of them.
+    pub fn bind(address: &str, agent_names: Vec<String>, max_hit_points: i64) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let local_address = listener.local_addr()?.to_string();
+        let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+        let accepted = Arc::clone(&clients);
+        let roster = serde_json::to_string(&Event::Roster {
+            agent_names: &agent_names,
+            max_hit_points,
+        })
+        .map_err(io::Error::other)?;
+        let roster: Arc<str> = roster.into();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let roster = Arc::clone(&roster);
+                let accepted = Arc::clone(&accepted);
+                thread::spawn(move || {
+                    if let Some(client) = greet(stream, &roster) {
+                        accepted.lock().expect("no client panicked").push(client);
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            clients,
+            local_address,
+        })
+    }
+
+    // The address clients connect to, with the port resolved if 0 was given.
+    pub fn local_address(&self) -> &str {
+        &self.local_address
+    }
+
+    fn send(&self, event: &Event) {
+        let Ok(text) = serde_json::to_string(event) else {
+            return;
+        };
+        let mut clients = self.clients.lock().expect("no client panicked");
+        clients.retain_mut(|client| client.send(Message::text(text.clone())).is_ok());
+    }
+}
+
+impl GameObserver<Duel> for BroadcastObserver {
+    fn on_game_start(&mut self, player_one: usize, player_two: usize) {
+        self.send(&Event::GameStart {
+            player_one,
+            player_two,
+        });
+    }
+
+    fn on_turn(&mut self, state: &GameState<Duel>) {
+        self.send(&Event::Turn {
+            turn: state.history.turns(),
+            player_one_action: &state.player_one_action,
+            player_two_action: &state.player_two_action,
+            player_one_hit_points: state.player_one_state.current_hit_points,
+            player_two_hit_points: state.player_two_state.current_hit_points,
+        });
+    }
+
+    fn on_game_end(&mut self, state: &GameState<Duel>, outcome: &GameOutcome) {
+        self.send(&Event::GameEnd {
+            turns: state.history.turns(),
+            outcome,
+        });
+    }
+
+    fn on_match_start(&mut self, player_one: usize, player_two: usize) {
+        self.send(&Event::MatchStart {
+            player_one,
+            player_two,
+        });
+    }
+
+    fn on_match_end(&mut self, player_one: usize, player_two: usize, record: &PairingRecord) {
+        self.send(&Event::MatchEnd {
+            player_one,
+            player_two,
+            record,
+        });
+    }
+
+    fn on_round_end(&mut self, round: u64) {
+        self.send(&Event::RoundEnd { round });
+    }
+}
@@ -0,0 +1,258 @@
+// Equilibrium strategies by counterfactual regret minimization.
+//
+// A zero-sum matrix game is solved by regret matching in self-play: every
+// iteration, both players play each action in proportion to its positive
+// cumulative regret, and their average strategies converge to an
+// equilibrium. As in CFR+, cumulative regrets are floored at zero and later
+// iterations weigh more in the average, which converges much faster.
+//
+// The duel is solved stage by stage: since every turn costs a hit point,
+// each (own, opposing) hit point state only leads to states with fewer hit
+// points, whose equilibrium values make up the payoffs of its stage game.
+// Damage matrices with exchanges costing nobody, or healing, are solved by
+// sweeping over all states until the values settle.
+
+use crate::duel::{Action, DamageMatrix};
+
+// Limits of solving damage matrices that do not cost hit points every turn.
+pub(crate) const MAX_SWEEPS: usize = 200;
+pub(crate) const SWEEP_TOLERANCE: f64 = 1e-6;
+
+// Mixed equilibrium strategies of both players and the row player's value.
+pub struct MatrixSolution {
+    pub row: Vec<f64>,
+    pub column: Vec<f64>,
+    pub value: f64,
+}
+
+// Strategy playing every action in proportion to its positive regret,
+// uniformly if there is none.
+pub(crate) fn regret_matching(regrets: &[f64]) -> Vec<f64> {
+    let positive: f64 = regrets.iter().map(|r| r.max(0.0)).sum();
+    if positive > 0.0 {
+        regrets.iter().map(|r| r.max(0.0) / positive).collect()
+    } else {
+        vec![1.0 / regrets.len() as f64; regrets.len()]
+    }
+}
+
+fn normalize(sums: &[f64]) -> Vec<f64> {
+    let total: f64 = sums.iter().sum();
+    sums.iter().map(|s| s / total).collect()
+}
+
+// Solves the zero-sum game in which the row player receives `payoffs[i][j]`
+// and the column player pays it.
+pub fn solve_matrix_game(payoffs: &[Vec<f64>], iterations: usize) -> MatrixSolution {
+    let rows = payoffs.len();
+    let columns = payoffs[0].len();
+    let mut row_regrets = vec![0.0; rows];
+    let mut column_regrets = vec![0.0; columns];
+    let mut row_sums = vec![0.0; rows];
+    let mut column_sums = vec![0.0; columns];
+
+    for iteration in 1..=iterations.max(1) {
+        let weight = iteration as f64;
+        let row = regret_matching(&row_regrets);
+        let column = regret_matching(&column_regrets);
+        let row_utilities: Vec<f64> = (0..rows)
+            .map(|i| (0..columns).map(|j| payoffs[i][j] * column[j]).sum())
+            .collect();
+        let column_utilities: Vec<f64> = (0..columns)
+            .map(|j| -(0..rows).map(|i| payoffs[i][j] * row[i]).sum::<f64>())
+            .collect();
+        let row_value: f64 = row.iter().zip(&row_utilities).map(|(p, u)| p * u).sum();
+        let column_value: f64 = column
+            .iter()
+            .zip(&column_utilities)
+            .map(|(p, u)| p * u)
+            .sum();
+        for i in 0..rows {
+            row_regrets[i] = (row_regrets[i] + row_utilities[i] - row_value).max(0.0);
+            row_sums[i] += weight * row[i];
+        }
+        for j in 0..columns {
+            column_regrets[j] = (column_regrets[j] + column_utilities[j] - column_value).max(0.0);
+            column_sums[j] += weight * column[j];
+        }
+    }
+
+    solution(payoffs, &row_sums, &column_sums)
+}
+
+fn solution(payoffs: &[Vec<f64>], row_sums: &[f64], column_sums: &[f64]) -> MatrixSolution {
+    let row = normalize(row_sums);
+    let column = normalize(column_sums);
+    let value = expected_value(payoffs, &row, &column);
+    MatrixSolution { row, column, value }
+}
+
+fn expected_value(payoffs: &[Vec<f64>], row: &[f64], column: &[f64]) -> f64 {
+    (0..row.len())
+        .flat_map(|i| (0..column.len()).map(move |j| (i, j)))
+        .map(|(i, j)| row[i] * payoffs[i][j] * column[j])
+        .sum()
+}
+
+// Plain regret matching, without flooring the regrets or weighing the
+// average, as the baseline that `solve_matrix_game` improves on.
+pub fn solve_by_regret_matching(payoffs: &[Vec<f64>], iterations: usize) -> MatrixSolution {
+    let rows = payoffs.len();
+    let columns = payoffs[0].len();
+    let mut row_regrets = vec![0.0; rows];
+    let mut column_regrets = vec![0.0; columns];
+    let mut row_sums = vec![0.0; rows];
+    let mut column_sums = vec![0.0; columns];
+
+    for _ in 0..iterations.max(1) {
+        let row = regret_matching(&row_regrets);
+        let column = regret_matching(&column_regrets);
+        let value = expected_value(payoffs, &row, &column);
+        for i in 0..rows {
+            let utility: f64 = (0..columns).map(|j| payoffs[i][j] * column[j]).sum();
+            row_regrets[i] += utility - value;
+            row_sums[i] += row[i];
+        }
+        for j in 0..columns {
+            let utility: f64 = -(0..rows).map(|i| payoffs[i][j] * row[i]).sum::<f64>();
+            column_regrets[j] += utility + value;
+            column_sums[j] += column[j];
+        }
+    }
+
+    solution(payoffs, &row_sums, &column_sums)
+}
+
+// Fictitious play: every iteration, both players play a best response to
+// the empirical frequencies of the other's past actions, and those
+// frequencies converge to an equilibrium, if slowly.
+pub fn solve_by_fictitious_play(payoffs: &[Vec<f64>], iterations: usize) -> MatrixSolution {
+    let rows = payoffs.len();
+    let columns = payoffs[0].len();
+    let mut row_counts = vec![0.0; rows];
+    let mut column_counts = vec![0.0; columns];
+    row_counts[0] = 1.0;
+    column_counts[0] = 1.0;
+
+    for _ in 1..iterations.max(1) {
+        let best_row = (0..rows)
+            .max_by(|&a, &b| {
+                let utility = |i: usize| -> f64 {
+                    (0..columns).map(|j| payoffs[i][j] * column_counts[j]).sum()
+                };
+                utility(a).total_cmp(&utility(b))
+            })
+            .unwrap();
+        let best_column = (0..columns)
+            .min_by(|&a, &b| {
+                let loss =
+                    |j: usize| -> f64 { (0..rows).map(|i| payoffs[i][j] * row_counts[i]).sum() };
+                loss(a).total_cmp(&loss(b))
+            })
+            .unwrap();
+        row_counts[best_row] += 1.0;
+        column_counts[best_column] += 1.0;
+    }
+
+    solution(payoffs, &row_counts, &column_counts)
+}
+
+// How much the players could gain together by deviating to best responses,
+// zero exactly at an equilibrium.
+pub fn exploitability(payoffs: &[Vec<f64>], row: &[f64], column: &[f64]) -> f64 {
+    let best_row = payoffs
+        .iter()
+        .map(|line| line.iter().zip(column).map(|(u, q)| u * q).sum::<f64>())
+        .fold(f64::NEG_INFINITY, f64::max);
+    let best_column = (0..column.len())
+        .map(|j| (0..row.len()).map(|i| payoffs[i][j] * row[i]).sum::<f64>())
+        .fold(f64::INFINITY, f64::min);
+    best_row - best_column
+}
+
+// Equilibrium probability of attack and winning chance, a tie counting as
+// half a win, of a player in every hit point state of the duel.
+pub struct DuelSolution {
+    pub max_hit_points: i64,
+    pub damage: DamageMatrix,
+    attack: Vec<f64>,
+    values: Vec<f64>,
+}
+
+impl DuelSolution {
+    pub fn solve(max_hit_points: i64, iterations: usize) -> Self {
+        Self::solve_with_damage(max_hit_points, &DamageMatrix::default(), iterations)
+    }
+
+    // Solves the duel played with `damage`, seen as player one. For damage
+    // matrices favouring neither player, this is the view of player two as well.
+    pub fn solve_with_damage(
+        max_hit_points: i64,
+        damage: &DamageMatrix,
+        iterations: usize,
+    ) -> Self {
+        let size = (max_hit_points + 1) as usize;
+        let index = |own: i64, opposing: i64| own as usize * size + opposing as usize;
+        let mut attack = vec![0.0; size * size];
+        let mut values = vec![0.0; size * size];
+        let value = |values: &[f64], own: i64, opposing: i64| match (own <= 0, opposing <= 0) {
+            (true, true) => 0.5,
+            (true, false) => 0.0,
+            (false, true) => 1.0,
+            (false, false) => values[index(own.min(max_hit_points), opposing.min(max_hit_points))],
+        };
+        let progressive = damage.0.iter().flatten().all(|[own_loss, opposing_loss]| {
+            *own_loss >= 0 && *opposing_loss >= 0 && own_loss + opposing_loss > 0
+        });
+        let sweeps = if progressive { 1 } else { MAX_SWEEPS };
+
+        for _ in 0..sweeps {
+            let mut change: f64 = 0.0;
+            for own in 1..=max_hit_points {
+                for opposing in 1..=max_hit_points {
+                    // Rows and columns are the actions in the order of Action::ALL
+                    let payoffs: Vec<Vec<f64>> = Action::ALL
+                        .iter()
+                        .map(|own_action| {
+                            Action::ALL
+                                .iter()
+                                .map(|opposing_action| {
+                                    let [own_loss, opposing_loss] =
+                                        damage.damage(own_action, opposing_action);
+                                    value(&values, own - own_loss, opposing - opposing_loss)
+                                })
+                                .collect()
+                        })
+                        .collect();
+                    let solution = solve_matrix_game(&payoffs, iterations);
+                    attack[index(own, opposing)] = solution.row[Action::ATTACK.index()];
+                    change = change.max((values[index(own, opposing)] - solution.value).abs());
+                    values[index(own, opposing)] = solution.value;
+                }
+            }
+            if change < SWEEP_TOLERANCE {
+                break;
+            }
+        }
+        Self {
+            max_hit_points,
+            damage: damage.clone(),
+            attack,
+            values,
+        }
+    }
+
+    fn index(&self, own: i64, opposing: i64) -> usize {
+        let own = own.clamp(0, self.max_hit_points) as usize;
+        let opposing = opposing.clamp(0, self.max_hit_points) as usize;
+        own * (self.max_hit_points + 1) as usize + opposing
+    }
+
+    pub fn attack_probability(&self, own: i64, opposing: i64) -> f64 {
+        self.attack[self.index(own, opposing)]
+    }
+
+    pub fn value(&self, own: i64, opposing: i64) -> f64 {
+        self.values[self.index(own, opposing)]
+    }
+}
@@ -0,0 +1,210 @@
+use rand::Rng;
+
+// Covariance matrix adaptation evolution strategy maximizing a function over
+// the unit cube, following Hansen's tutorial. Every generation, candidates are
+// sampled from a multivariate normal distribution, whose mean, step size and
+// covariance then move towards the best scoring candidates. Candidates are
+// clamped to the unit cube before being scored.
+pub struct CmaEs {
+    pub mean: Vec<f64>,
+    pub sigma: f64,
+    pub covariance: Vec<Vec<f64>>,
+    pub population_size: usize,
+    weights: Vec<f64>,
+    mueff: f64,
+    cc: f64,
+    cs: f64,
+    c1: f64,
+    cmu: f64,
+    damps: f64,
+    chi_n: f64,
+    // Evolution paths of the covariance and the step size.
+    pc: Vec<f64>,
+    ps: Vec<f64>,
+    // Eigenvectors as columns and square roots of the eigenvalues of the covariance.
+    basis: Vec<Vec<f64>>,
+    scales: Vec<f64>,
+    generation: usize,
+}
+
+impl CmaEs {
+    // Starts at `mean` with step size `sigma`; `population_size` defaults to
+    // 4 + 3 ln(n) candidates per generation.
+    pub fn new(mean: Vec<f64>, sigma: f64, population_size: Option<usize>) -> Self {
+        let n = mean.len();
+        let dimensions = n as f64;
+        let population_size = population_size
+            .unwrap_or(4 + (3.0 * dimensions.ln()).floor() as usize)
+            .max(2);
+        let parents = population_size / 2;
+        let raw: Vec<f64> = (1..=parents)
+            .map(|i| (parents as f64 + 0.5).ln() - (i as f64).ln())
+            .collect();
+        let total: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|w| w / total).collect();
+        let mueff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let cc = (4.0 + mueff / dimensions) / (dimensions + 4.0 + 2.0 * mueff / dimensions);
+        let cs = (mueff + 2.0) / (dimensions + mueff + 5.0);
+        let c1 = 2.0 / ((dimensions + 1.3).powi(2) + mueff);
+        let cmu = (1.0 - c1)
+            .min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((dimensions + 2.0).powi(2) + mueff));
+        let damps = 1.0 + 2.0 * (((mueff - 1.0) / (dimensions + 1.0)).sqrt() - 1.0).max(0.0) + cs;
+        let chi_n = dimensions.sqrt()
+            * (1.0 - 1.0 / (4.0 * dimensions) + 1.0 / (21.0 * dimensions * dimensions));
+
+        Self {
+            mean,
+            sigma,
+            covariance: identity(n),
+            population_size,
+            weights,
+            mueff,
+            cc,
+            cs,
+            c1,
+            cmu,
+            damps,
+            chi_n,
+            pc: vec![0.0; n],
+            ps: vec![0.0; n],
+            basis: identity(n),
+            scales: vec![1.0; n],
+            generation: 0,
+        }
+    }
+
+    // The candidates of the next generation.
+    pub fn ask<R: Rng>(&self, rng: &mut R) -> Vec<Vec<f64>> {
+        let n = self.mean.len();
+        (0..self.population_size)
+            .map(|_| {
+                let z: Vec<f64> = (0..n)
+                    .map(|i| self.scales[i] * standard_normal(rng))
+                    .collect();
+                (0..n)
+                    .map(|i| {
+                        let y: f64 = (0..n).map(|j| self.basis[i][j] * z[j]).sum();
+                        (self.mean[i] + self.sigma * y).clamp(0.0, 1.0)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Updates the distribution from the scores of the candidates of `ask`.
+    pub fn tell(&mut self, candidates: &[Vec<f64>], scores: &[f64]) {
+        let n = self.mean.len();
+        let mut ranking: Vec<usize> = (0..candidates.len()).collect();
+        ranking.sort_by(|a, b| scores[*b].total_cmp(&scores[*a]));
+        let steps: Vec<Vec<f64>> = ranking
+            .iter()
+            .take(self.weights.len())
+            .map(|&c| {
+                (0..n)
+                    .map(|i| (candidates[c][i] - self.mean[i]) / self.sigma)
+                    .collect()
+            })
+            .collect();
+        let step: Vec<f64> = (0..n)
+            .map(|i| self.weights.iter().zip(&steps).map(|(w, y)| w * y[i]).sum())
+            .collect();
+        for (mean, step) in self.mean.iter_mut().zip(&step) {
+            *mean += self.sigma * step;
+        }
+
+        // C^-1/2 step = B D^-1 B^T step
+        let rotated: Vec<f64> = (0..n)
+            .map(|j| (0..n).map(|i| self.basis[i][j] * step[i]).sum::<f64>() / self.scales[j])
+            .collect();
+        let whitened: Vec<f64> = (0..n)
+            .map(|i| (0..n).map(|j| self.basis[i][j] * rotated[j]).sum())
+            .collect();
+        let ps_factor = (self.cs * (2.0 - self.cs) * self.mueff).sqrt();
+        for (ps, whitened) in self.ps.iter_mut().zip(&whitened) {
+            *ps = (1.0 - self.cs) * *ps + ps_factor * whitened;
+        }
+        let ps_norm = self.ps.iter().map(|p| p * p).sum::<f64>().sqrt();
+        self.generation += 1;
+        let stalled =
+            ps_norm / (1.0 - (1.0 - self.cs).powi(2 * self.generation as i32)).sqrt() / self.chi_n
+                >= 1.4 + 2.0 / (n as f64 + 1.0);
+        let hsig = if stalled { 0.0 } else { 1.0 };
+        let pc_factor = (self.cc * (2.0 - self.cc) * self.mueff).sqrt();
+        for (pc, step) in self.pc.iter_mut().zip(&step) {
+            *pc = (1.0 - self.cc) * *pc + hsig * pc_factor * step;
+        }
+
+        let correction = (1.0 - hsig) * self.cc * (2.0 - self.cc);
+        for i in 0..n {
+            for j in 0..n {
+                let rank_mu: f64 = self
+                    .weights
+                    .iter()
+                    .zip(&steps)
+                    .map(|(w, y)| w * y[i] * y[j])
+                    .sum();
+                self.covariance[i][j] = (1.0 - self.c1 - self.cmu) * self.covariance[i][j]
+                    + self.c1 * (self.pc[i] * self.pc[j] + correction * self.covariance[i][j])
+                    + self.cmu * rank_mu;
+            }
+        }
+        self.sigma *= ((self.cs / self.damps) * (ps_norm / self.chi_n - 1.0)).exp();
+
+        let (values, vectors) = symmetric_eigen(&self.covariance);
+        self.scales = values.iter().map(|v| v.max(1e-20).sqrt()).collect();
+        self.basis = vectors;
+    }
+}
+
+fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect()
+}
+
+// Box-Muller transform of two uniform samples.
+fn standard_normal<R: Rng>(rng: &mut R) -> f64 {
+    let u: f64 = 1.0 - rng.random::<f64>();
+    let v: f64 = rng.random();
+    (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+}
+
+// Eigenvalues and eigenvectors, as columns, of a symmetric matrix by cyclic
+// Jacobi rotations.
+fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut vectors = identity(n);
+    for _ in 0..100 {
+        let off_diagonal: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        if off_diagonal < 1e-30 {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut().chain(vectors.iter_mut()) {
+                    let (vp, vq) = (row[p], row[q]);
+                    row[p] = c * vp - s * vq;
+                    row[q] = s * vp + c * vq;
+                }
+                let (row_p, row_q) = (a[p].clone(), a[q].clone());
+                for k in 0..n {
+                    a[p][k] = c * row_p[k] - s * row_q[k];
+                    a[q][k] = s * row_p[k] + c * row_q[k];
+                }
+            }
+        }
+    }
+    ((0..n).map(|i| a[i][i]).collect(), vectors)
+}
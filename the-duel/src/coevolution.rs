@@ -0,0 +1,170 @@
+use std::fs;
+use std::path::Path;
+
+use rand::seq::IndexedRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::agents::{GameAgent, SharedRng};
+use crate::config::ConfigError;
+use crate::duel::Duel;
+use crate::evolution::{EvolutionConfig, GenerationSummary, Genome, average_score, breed};
+use crate::game::GameSettings;
+use crate::registry::AgentRegistry;
+
+#[derive(Deserialize, Clone)]
+pub struct CoevolutionConfig {
+    // Population size, generations, games and breeding of both populations;
+    // `families` names the family of each of the two populations.
+    #[serde(flatten)]
+    pub evolution: EvolutionConfig,
+    // Members of the opposing population every genome is rated against.
+    #[serde(default = "default_sample")]
+    pub opponent_sample: usize,
+    // Archived opposing champions every genome is additionally rated against.
+    #[serde(default = "default_sample")]
+    pub archive_sample: usize,
+    // JSON file the hall of fame is loaded from and saved to after every generation.
+    pub archive: Option<String>,
+}
+
+fn default_sample() -> usize {
+    5
+}
+
+// Champions of past generations of both populations.
+#[derive(Default, Serialize, Deserialize)]
+pub struct HallOfFame {
+    pub champions: [Vec<Genome>; 2],
+}
+
+impl HallOfFame {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+        serde_json::from_str(&contents).map_err(|err| ConfigError::Invalid(err.to_string()))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|err| ConfigError::Invalid(err.to_string()))?;
+        fs::write(path, contents).map_err(ConfigError::Io)
+    }
+}
+
+// Evolves two populations against each other. Every genome is rated against
+// a sample of the current opposing population and a sample of the opposing
+// hall of fame, so that strategies beaten long ago cannot quietly return.
+pub struct Coevolution<'a> {
+    pub rules: Duel,
+    pub settings: GameSettings,
+    pub registry: &'a AgentRegistry,
+    pub config: CoevolutionConfig,
+    pub hall_of_fame: HallOfFame,
+}
+
+impl<'a> Coevolution<'a> {
+    pub fn new(rules: Duel, registry: &'a AgentRegistry, config: CoevolutionConfig) -> Self {
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            registry,
+            config,
+            hall_of_fame: HallOfFame::default(),
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_hall_of_fame(mut self, hall_of_fame: HallOfFame) -> Self {
+        self.hall_of_fame = hall_of_fame;
+        self
+    }
+
+    fn build(&self, genome: &Genome, rng: &SharedRng) -> Result<Box<dyn GameAgent>, ConfigError> {
+        self.registry
+            .build_with_damage(&genome.spec(), rng, &self.rules.damage)
+            .map_err(ConfigError::Agent)
+    }
+
+    // Average score of every genome of `population` against a sample of
+    // `opponents` and of the opposing champions.
+    fn fitness(
+        &self,
+        side: usize,
+        population: &[Genome],
+        opponents: &[Genome],
+        rng: &SharedRng,
+    ) -> Result<Vec<f64>, ConfigError> {
+        let archive = &self.hall_of_fame.champions[1 - side];
+        population
+            .iter()
+            .map(|genome| {
+                let (current, archived) = {
+                    let mut rng = rng.borrow_mut();
+                    let current: Vec<Genome> = opponents
+                        .choose_multiple(&mut *rng, self.config.opponent_sample)
+                        .cloned()
+                        .collect();
+                    let archived: Vec<Genome> = archive
+                        .choose_multiple(&mut *rng, self.config.archive_sample)
+                        .cloned()
+                        .collect();
+                    (current, archived)
+                };
+                let agent = self.build(genome, rng)?;
+                let mut total = 0.0;
+                for opponent in current.iter().chain(&archived) {
+                    let opponent = self.build(opponent, rng)?;
+                    total += average_score(
+                        &self.rules,
+                        &self.settings,
+                        agent.as_ref(),
+                        opponent.as_ref(),
+                        self.config.evolution.games,
+                    );
+                }
+                Ok(total / (current.len() + archived.len()).max(1) as f64)
+            })
+            .collect()
+    }
+
+    pub fn run(&mut self, rng: &SharedRng) -> Result<Vec<GenerationSummary>, ConfigError> {
+        let evolution = &self.config.evolution;
+        let [first, second] = evolution.families[..] else {
+            return Err(ConfigError::Invalid(
+                "coevolution needs exactly two families".to_string(),
+            ));
+        };
+        let mut populations: [Vec<Genome>; 2] = [first, second].map(|family| {
+            (0..evolution.population_size)
+                .map(|_| Genome::random(family, &mut *rng.borrow_mut()))
+                .collect()
+        });
+
+        let mut summaries = Vec::new();
+        for generation in 0..=evolution.generations {
+            let fitness = [
+                self.fitness(0, &populations[0], &populations[1], rng)?,
+                self.fitness(1, &populations[1], &populations[0], rng)?,
+            ];
+            for side in 0..2 {
+                let summary =
+                    GenerationSummary::of(side, generation, &populations[side], &fitness[side]);
+                self.hall_of_fame.champions[side].push(summary.best.clone());
+                summaries.push(summary);
+            }
+            if let Some(path) = &self.config.archive {
+                self.hall_of_fame.save(path)?;
+            }
+            if generation == evolution.generations {
+                break;
+            }
+            for side in 0..2 {
+                populations[side] = breed(&populations[side], &fitness[side], evolution, rng);
+            }
+        }
+        Ok(summaries)
+    }
+}
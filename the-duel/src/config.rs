@@ -0,0 +1,1573 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+use rand::SeedableRng;
+use serde::Deserialize;
+
+#[cfg(feature = "grpc")]
+use crate::agents::GrpcAgent;
+#[cfg(feature = "lua")]
+use crate::agents::LuaAgent;
+#[cfg(feature = "neural")]
+use crate::agents::NeuralAgent;
+#[cfg(feature = "onnx")]
+use crate::agents::OnnxAgent;
+use crate::agents::{
+    AttackAgent, AttackModel, CfrAgent, DynamicProgrammingAgent, Estimator, Exp3Agent, Exp3Variant,
+    ExpectimaxAgent, ExternalAgent, GameAgent, GrimTriggerAgent, HedgeAgent, HmmAgent, HumanAgent,
+    MarkovPredictorAgent, MarkovRandomAgent, MirrorAgent, ModelKind, OneStepDecisionProcessAgent,
+    ParticleFilterAgent, PatternMatchingAgent, PavlovAgent, PortfolioAgent, RandomAgent, RuleAgent,
+    SharedRng, TdAgent, TdRule, ThompsonSamplingAgent, TitForTatAgent,
+};
+use crate::alpha_rank::AlphaRankConfig;
+use crate::bracket::{Bracket, Elimination};
+use crate::coevolution::{Coevolution, CoevolutionConfig, HallOfFame};
+use crate::duel::{
+    Action, DamageMatrix, Duel, EXECUTION_NOISE_STREAM, OBSERVATION_NOISE_STREAM, Overtime,
+    OvertimeMode, RuleVariant, STOCHASTIC_DAMAGE_STREAM, StaminaRules, StartingConditions,
+    StochasticDamage,
+};
+use crate::ecology::EcologyConfig;
+use crate::evolution::{Evolution, EvolutionConfig};
+use crate::free_for_all::{FreeForAllConfig, FreeForAllTournament};
+use crate::game::{GameSettings, seeded_stream};
+use crate::handicap::{HandicapSearch, HandicapSearchConfig};
+use crate::lattice::{Lattice, LatticeConfig};
+use crate::logging::LoggingConfig;
+use crate::matches::MatchFormat;
+use crate::moran::MoranConfig;
+use crate::nash_averaging::NashAveragingConfig;
+use crate::neural::TrainingConfig;
+#[cfg(any(feature = "neural", feature = "onnx"))]
+use crate::neural::default_history;
+#[cfg(feature = "neural")]
+use crate::neural::{Policy, Training, default_hidden};
+use crate::optimize::{Optimization, OptimizationConfig};
+use crate::oracle::{DoubleOracle, OracleConfig};
+use crate::output::{OutputFormat, ResultMetadata};
+use crate::page_rank::PageRankConfig;
+use crate::rating::RatingConfig;
+use crate::registry::{
+    AgentRegistry, NON_NEGATIVE, POSITIVE, PROBABILITY, RATE, RegistryError, check_range,
+};
+use crate::replicator::ReplicatorConfig;
+use crate::rng_audit::AuditedRng;
+use crate::self_play::{Checkpoint, SelfPlay, SelfPlayConfig};
+use crate::swiss::SwissTournament;
+use crate::team::{TeamConfig, TeamDuel};
+use crate::tournament::{PairingSchedule, Tournament};
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Agent(RegistryError),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "could not read experiment file: {}", err),
+            ConfigError::Parse(err) => write!(f, "invalid experiment file: {}", err),
+            ConfigError::Agent(err) => write!(f, "invalid agent in experiment file: {}", err),
+            ConfigError::Invalid(reason) => write!(f, "invalid experiment file: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// One entry of the agent roster, e.g.
+//   { kind = "markov", change_to_attack_prob = 0.5, change_to_finch_prob = 0.1 }
+#[derive(Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AgentConfig {
+    Attack,
+    Mirror,
+    TitForTat {
+        #[serde(default = "default_opening")]
+        initial: Action,
+    },
+    GrimTrigger,
+    // Prompts on the terminal for every action.
+    Human,
+    Pavlov {
+        #[serde(default = "default_opening")]
+        initial: Action,
+    },
+    Random {
+        probability_of_attack: f64,
+    },
+    // Plays by a rule of the strategy language, see crate::dsl, e.g.
+    //   { kind = "rule", rule = "if opponent attacked twice then FINCH else ATTACK with p=0.7" }
+    Rule {
+        rule: String,
+    },
+    Markov {
+        change_to_attack_prob: f64,
+        change_to_finch_prob: f64,
+        #[serde(default = "default_initial_strategy")]
+        initial_strategy: Action,
+    },
+    OneStep {
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+        // "full", { window = 50 } or { decay = 0.95 }
+        #[serde(default)]
+        estimator: Estimator,
+        // Pseudo-counts of a Beta prior on the estimate.
+        #[serde(default)]
+        prior_attacks: f64,
+        #[serde(default)]
+        prior_finches: f64,
+        // Turns played with the burn-in action before trusting the estimate.
+        #[serde(default)]
+        burn_in: usize,
+        #[serde(default = "default_initial_strategy")]
+        burn_in_action: Action,
+    },
+    Predictor {
+        #[serde(default = "default_order")]
+        order: usize,
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+    },
+    Pattern {
+        #[serde(default = "default_max_length")]
+        max_length: usize,
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+    },
+    Hmm {
+        #[serde(default = "default_refit_interval")]
+        refit_interval: usize,
+        #[serde(default = "default_hmm_iterations")]
+        iterations: usize,
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+    },
+    Particle {
+        #[serde(default = "default_num_particles")]
+        num_particles: usize,
+        #[serde(default = "default_accuracy")]
+        accuracy: f64,
+        #[serde(default = "default_jitter")]
+        jitter: f64,
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+    },
+    Hedge {
+        #[serde(default = "default_hedge_learning_rate")]
+        learning_rate: f64,
+        #[serde(default = "default_hedge_order")]
+        order: usize,
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+    },
+    Thompson {
+        cost_losing_hp: f64,
+        cost_not_losing_hp: f64,
+        cost_equivalent_exchange: f64,
+        #[serde(default = "default_prior")]
+        prior_attacks: f64,
+        #[serde(default = "default_prior")]
+        prior_finches: f64,
+    },
+    Exp3 {
+        #[serde(default = "default_exp3_exploration")]
+        exploration: f64,
+    },
+    Exp3Ix {
+        #[serde(default = "default_exp3_ix_learning_rate")]
+        learning_rate: f64,
+        #[serde(default = "default_exp3_ix_exploration")]
+        exploration: f64,
+    },
+    // Solves the experiment's damage matrix, its variant included, with
+    // `variant` applied on top.
+    Cfr {
+        #[serde(default = "default_cfr_iterations")]
+        iterations: usize,
+        #[serde(default)]
+        variant: RuleVariant,
+    },
+    // Plans against a fixed attack probability if given, an estimated one otherwise.
+    Dynamic {
+        probability_of_attack: Option<f64>,
+        #[serde(default = "default_prior")]
+        prior_attacks: f64,
+        #[serde(default = "default_prior")]
+        prior_finches: f64,
+        #[serde(default = "default_resolution")]
+        resolution: f64,
+    },
+    Expectimax {
+        #[serde(default = "default_depth")]
+        depth: usize,
+        #[serde(default)]
+        model: ModelKind,
+        // Opponent actions the "ngram" model conditions on.
+        #[serde(default = "default_order")]
+        order: usize,
+    },
+    QLearning(TdConfig),
+    Sarsa(TdConfig),
+    ExpectedSarsa(TdConfig),
+    // Plays by network weights saved by a training, with the shape they were
+    // trained with.
+    #[cfg(feature = "neural")]
+    Neural {
+        weights: String,
+        #[serde(default = "default_hidden")]
+        hidden: usize,
+        #[serde(default = "default_history")]
+        history: usize,
+        #[serde(default)]
+        greedy: bool,
+    },
+    // Plays by a policy exported to ONNX, see OnnxAgent.
+    #[cfg(feature = "onnx")]
+    Onnx {
+        model: String,
+        #[serde(default = "default_history")]
+        history: usize,
+        #[serde(default)]
+        greedy: bool,
+    },
+    // Asks an executable for every action over its standard input and
+    // output, see ExternalAgent, e.g.
+    //   { kind = "external", command = "python3", args = ["bot.py"] }
+    External {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    // Plays by a Lua script (needs the lua feature), see LuaAgent, e.g.
+    //   { kind = "lua", script = "experiments/tit_for_tat.lua" }
+    #[cfg(feature = "lua")]
+    Lua {
+        script: String,
+    },
+    // Asks an agent service over gRPC (needs the grpc feature), e.g.
+    //   { kind = "grpc", endpoint = "http://localhost:50051" }
+    #[cfg(feature = "grpc")]
+    Grpc {
+        endpoint: String,
+    },
+    // Lets the best performing of `agents` control play, e.g.
+    //   { kind = "portfolio", agents = ["mirror", "hedge()"] }
+    Portfolio {
+        agents: Vec<AgentEntry>,
+        #[serde(default = "default_portfolio_decay")]
+        decay: f64,
+    },
+}
+
+fn default_initial_strategy() -> Action {
+    Action::ATTACK
+}
+
+fn default_prior() -> f64 {
+    1.0
+}
+
+fn default_exp3_exploration() -> f64 {
+    0.05
+}
+
+fn default_exp3_ix_learning_rate() -> f64 {
+    0.05
+}
+
+fn default_exp3_ix_exploration() -> f64 {
+    0.025
+}
+
+fn default_resolution() -> f64 {
+    0.01
+}
+
+fn default_depth() -> usize {
+    3
+}
+
+fn default_opening() -> Action {
+    Action::FINCH
+}
+
+fn default_order() -> usize {
+    2
+}
+
+fn default_max_length() -> usize {
+    8
+}
+
+fn default_refit_interval() -> usize {
+    10
+}
+
+fn default_hmm_iterations() -> usize {
+    20
+}
+
+fn default_num_particles() -> usize {
+    500
+}
+
+fn default_accuracy() -> f64 {
+    0.95
+}
+
+fn default_jitter() -> f64 {
+    0.02
+}
+
+fn default_hedge_learning_rate() -> f64 {
+    0.5
+}
+
+fn default_hedge_order() -> usize {
+    3
+}
+
+fn default_portfolio_decay() -> f64 {
+    0.9
+}
+
+fn default_cfr_iterations() -> usize {
+    100
+}
+
+// Parameters of the temporal difference learners.
+#[derive(Deserialize, Clone)]
+pub struct TdConfig {
+    #[serde(default = "default_learning_rate")]
+    pub learning_rate: f64,
+    #[serde(default = "default_discount")]
+    pub discount: f64,
+    // Chance of a uniformly random action instead of the greedy one.
+    #[serde(default = "default_exploration")]
+    pub exploration: f64,
+    // Checkpoint of a self-play training to start from.
+    pub table: Option<String>,
+    // Past transitions replayed after every update, none without a buffer.
+    #[serde(default)]
+    pub replay: usize,
+    #[serde(default = "default_replay_capacity")]
+    pub replay_capacity: usize,
+}
+
+fn default_learning_rate() -> f64 {
+    0.1
+}
+
+fn default_discount() -> f64 {
+    0.9
+}
+
+fn default_exploration() -> f64 {
+    0.1
+}
+
+fn default_replay_capacity() -> usize {
+    1000
+}
+
+impl TdConfig {
+    // Checks the parameters against the ranges the registry enforces for
+    // the learner called `agent`.
+    fn validate(&self, agent: &str) -> Result<(), RegistryError> {
+        check_range(agent, "learning_rate", self.learning_rate, RATE)?;
+        check_range(agent, "discount", self.discount, PROBABILITY)?;
+        check_range(agent, "exploration", self.exploration, PROBABILITY)?;
+        if self.replay > 0 {
+            check_range(agent, "replay_capacity", self.replay_capacity, 1..)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn learner(&self, rng: &SharedRng, rule: TdRule) -> TdAgent {
+        let agent = TdAgent::new(
+            rng.clone(),
+            rule,
+            self.learning_rate,
+            self.discount,
+            self.exploration,
+        );
+        if self.replay > 0 {
+            agent.with_replay(self.replay_capacity, self.replay)
+        } else {
+            agent
+        }
+    }
+
+    fn build(&self, rng: &SharedRng, rule: TdRule) -> Result<Box<dyn GameAgent>, RegistryError> {
+        let agent = self.learner(rng, rule);
+        Ok(Box::new(match &self.table {
+            Some(path) => {
+                let checkpoint =
+                    Checkpoint::load(path).map_err(|_| RegistryError::InvalidArgument {
+                        agent: String::from(rule.name()),
+                        argument: String::from("table"),
+                        value: path.clone(),
+                    })?;
+                agent.with_table(Rc::new(RefCell::new(checkpoint.table())))
+            }
+            None => agent,
+        }))
+    }
+}
+
+// The costs of the decision process agents, which only have to be numbers.
+fn check_costs(agent: &str, costs: [f64; 3]) -> Result<(), RegistryError> {
+    for (argument, cost) in [
+        "cost_losing_hp",
+        "cost_not_losing_hp",
+        "cost_equivalent_exchange",
+    ]
+    .into_iter()
+    .zip(costs)
+    {
+        if !cost.is_finite() {
+            return Err(RegistryError::InvalidArgument {
+                agent: agent.to_string(),
+                argument: argument.to_string(),
+                value: cost.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+impl AgentConfig {
+    // Checks the parameters against the same ranges as the registry does
+    // for specifications, so that no agent panics once the games start.
+    pub fn validate(&self) -> Result<(), RegistryError> {
+        match self {
+            AgentConfig::Random {
+                probability_of_attack,
+            } => {
+                check_range(
+                    "random",
+                    "probability_of_attack",
+                    *probability_of_attack,
+                    PROBABILITY,
+                )?;
+            }
+            AgentConfig::Markov {
+                change_to_attack_prob,
+                change_to_finch_prob,
+                ..
+            } => {
+                check_range(
+                    "markov",
+                    "change_to_attack_prob",
+                    *change_to_attack_prob,
+                    PROBABILITY,
+                )?;
+                check_range(
+                    "markov",
+                    "change_to_finch_prob",
+                    *change_to_finch_prob,
+                    PROBABILITY,
+                )?;
+            }
+            AgentConfig::OneStep {
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+                estimator,
+                prior_attacks,
+                prior_finches,
+                ..
+            } => {
+                check_costs(
+                    "one_step",
+                    [
+                        *cost_losing_hp,
+                        *cost_not_losing_hp,
+                        *cost_equivalent_exchange,
+                    ],
+                )?;
+                match estimator {
+                    Estimator::Full => {}
+                    Estimator::Window(window) => {
+                        check_range("one_step", "window", *window, 1..)?;
+                    }
+                    Estimator::Decay(decay) => {
+                        check_range("one_step", "decay", *decay, RATE)?;
+                    }
+                }
+                check_range("one_step", "prior_attacks", *prior_attacks, NON_NEGATIVE)?;
+                check_range("one_step", "prior_finches", *prior_finches, NON_NEGATIVE)?;
+            }
+            AgentConfig::Predictor {
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+                ..
+            } => check_costs(
+                "predictor",
+                [
+                    *cost_losing_hp,
+                    *cost_not_losing_hp,
+                    *cost_equivalent_exchange,
+                ],
+            )?,
+            AgentConfig::Pattern {
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+                ..
+            } => check_costs(
+                "pattern",
+                [
+                    *cost_losing_hp,
+                    *cost_not_losing_hp,
+                    *cost_equivalent_exchange,
+                ],
+            )?,
+            AgentConfig::Hmm {
+                refit_interval,
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+                ..
+            } => {
+                check_range("hmm", "refit_interval", *refit_interval, 1..)?;
+                check_costs(
+                    "hmm",
+                    [
+                        *cost_losing_hp,
+                        *cost_not_losing_hp,
+                        *cost_equivalent_exchange,
+                    ],
+                )?;
+            }
+            AgentConfig::Particle {
+                num_particles,
+                accuracy,
+                jitter,
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+            } => {
+                check_range("particle", "num_particles", *num_particles, 1..)?;
+                check_range("particle", "accuracy", *accuracy, PROBABILITY)?;
+                check_range("particle", "jitter", *jitter, PROBABILITY)?;
+                check_costs(
+                    "particle",
+                    [
+                        *cost_losing_hp,
+                        *cost_not_losing_hp,
+                        *cost_equivalent_exchange,
+                    ],
+                )?;
+            }
+            AgentConfig::Hedge {
+                learning_rate,
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+                ..
+            } => {
+                check_range("hedge", "learning_rate", *learning_rate, NON_NEGATIVE)?;
+                check_costs(
+                    "hedge",
+                    [
+                        *cost_losing_hp,
+                        *cost_not_losing_hp,
+                        *cost_equivalent_exchange,
+                    ],
+                )?;
+            }
+            AgentConfig::Thompson {
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+                prior_attacks,
+                prior_finches,
+            } => {
+                check_costs(
+                    "thompson",
+                    [
+                        *cost_losing_hp,
+                        *cost_not_losing_hp,
+                        *cost_equivalent_exchange,
+                    ],
+                )?;
+                check_range("thompson", "prior_attacks", *prior_attacks, POSITIVE)?;
+                check_range("thompson", "prior_finches", *prior_finches, POSITIVE)?;
+            }
+            AgentConfig::Exp3 { exploration } => {
+                check_range("exp3", "exploration", *exploration, RATE)?;
+            }
+            AgentConfig::Exp3Ix {
+                learning_rate,
+                exploration,
+            } => {
+                check_range("exp3_ix", "learning_rate", *learning_rate, NON_NEGATIVE)?;
+                check_range("exp3_ix", "exploration", *exploration, NON_NEGATIVE)?;
+            }
+            AgentConfig::Cfr {
+                iterations,
+                variant,
+            } => {
+                check_range("cfr", "iterations", *iterations, 1..)?;
+                if let RuleVariant::Riposte { counter_damage } = variant {
+                    check_range("cfr", "counter_damage", *counter_damage, 0..)?;
+                }
+            }
+            AgentConfig::Dynamic {
+                probability_of_attack,
+                prior_attacks,
+                prior_finches,
+                resolution,
+            } => {
+                if let Some(probability) = probability_of_attack {
+                    check_range(
+                        "dynamic",
+                        "probability_of_attack",
+                        *probability,
+                        PROBABILITY,
+                    )?;
+                }
+                check_range("dynamic", "prior_attacks", *prior_attacks, POSITIVE)?;
+                check_range("dynamic", "prior_finches", *prior_finches, POSITIVE)?;
+                check_range("dynamic", "resolution", *resolution, RATE)?;
+            }
+            AgentConfig::QLearning(td) => td.validate("q_learning")?,
+            AgentConfig::Sarsa(td) => td.validate("sarsa")?,
+            AgentConfig::ExpectedSarsa(td) => td.validate("expected_sarsa")?,
+            #[cfg(feature = "neural")]
+            AgentConfig::Neural { hidden, .. } => {
+                check_range("neural", "hidden", *hidden, 1..)?;
+            }
+            AgentConfig::Portfolio { agents, decay } => {
+                check_range("portfolio", "decay", *decay, PROBABILITY)?;
+                for agent in agents {
+                    if let AgentEntry::Table(config) = agent {
+                        config.validate()?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Builds the agent for a game played by `damage`.
+    pub fn build(
+        &self,
+        registry: &AgentRegistry,
+        rng: &SharedRng,
+        damage: &DamageMatrix,
+    ) -> Result<Box<dyn GameAgent>, RegistryError> {
+        Ok(match self {
+            AgentConfig::Attack => Box::new(AttackAgent),
+            AgentConfig::Mirror => Box::new(MirrorAgent),
+            AgentConfig::TitForTat { initial } => Box::new(TitForTatAgent::new(initial.clone())),
+            AgentConfig::GrimTrigger => Box::new(GrimTriggerAgent::new()),
+            AgentConfig::Human => Box::new(HumanAgent::new()),
+            AgentConfig::Pavlov { initial } => Box::new(PavlovAgent::new(initial.clone())),
+            AgentConfig::Random {
+                probability_of_attack,
+            } => Box::new(RandomAgent::new(rng.clone(), *probability_of_attack)),
+            AgentConfig::Rule { rule } => Box::new(
+                RuleAgent::new(rng.clone(), rule)
+                    .map_err(|_| RegistryError::InvalidArgument {
+                        agent: String::from("rule"),
+                        argument: String::from("rule"),
+                        value: rule.clone(),
+                    })?
+                    .with_damage(damage.clone()),
+            ),
+            AgentConfig::Markov {
+                change_to_attack_prob,
+                change_to_finch_prob,
+                initial_strategy,
+            } => Box::new(MarkovRandomAgent::new(
+                rng.clone(),
+                *change_to_attack_prob,
+                *change_to_finch_prob,
+                initial_strategy.clone(),
+            )),
+            AgentConfig::OneStep {
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+                estimator,
+                prior_attacks,
+                prior_finches,
+                burn_in,
+                burn_in_action,
+            } => Box::new(
+                OneStepDecisionProcessAgent::new(
+                    *cost_losing_hp,
+                    *cost_not_losing_hp,
+                    *cost_equivalent_exchange,
+                )
+                .with_estimator(*estimator)
+                .with_prior(*prior_attacks, *prior_finches)
+                .with_burn_in(*burn_in, burn_in_action.clone()),
+            ),
+            AgentConfig::Predictor {
+                order,
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+            } => Box::new(MarkovPredictorAgent::new(
+                *order,
+                *cost_losing_hp,
+                *cost_not_losing_hp,
+                *cost_equivalent_exchange,
+            )),
+            AgentConfig::Pattern {
+                max_length,
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+            } => Box::new(PatternMatchingAgent::new(
+                *max_length,
+                *cost_losing_hp,
+                *cost_not_losing_hp,
+                *cost_equivalent_exchange,
+            )),
+            AgentConfig::Hmm {
+                refit_interval,
+                iterations,
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+            } => Box::new(HmmAgent::new(
+                *refit_interval,
+                *iterations,
+                *cost_losing_hp,
+                *cost_not_losing_hp,
+                *cost_equivalent_exchange,
+            )),
+            AgentConfig::Particle {
+                num_particles,
+                accuracy,
+                jitter,
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+            } => Box::new(ParticleFilterAgent::new(
+                rng.clone(),
+                *num_particles,
+                *accuracy,
+                *jitter,
+                *cost_losing_hp,
+                *cost_not_losing_hp,
+                *cost_equivalent_exchange,
+            )),
+            AgentConfig::Hedge {
+                learning_rate,
+                order,
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+            } => Box::new(HedgeAgent::new(
+                *learning_rate,
+                *order,
+                *cost_losing_hp,
+                *cost_not_losing_hp,
+                *cost_equivalent_exchange,
+            )),
+            AgentConfig::Thompson {
+                cost_losing_hp,
+                cost_not_losing_hp,
+                cost_equivalent_exchange,
+                prior_attacks,
+                prior_finches,
+            } => Box::new(ThompsonSamplingAgent::new(
+                rng.clone(),
+                *cost_losing_hp,
+                *cost_not_losing_hp,
+                *cost_equivalent_exchange,
+                *prior_attacks,
+                *prior_finches,
+            )),
+            AgentConfig::Exp3 { exploration } => Box::new(Exp3Agent::new(
+                rng.clone(),
+                Exp3Variant::Exp3,
+                *exploration,
+                0.0,
+            )),
+            AgentConfig::Exp3Ix {
+                learning_rate,
+                exploration,
+            } => Box::new(Exp3Agent::new(
+                rng.clone(),
+                Exp3Variant::Exp3Ix,
+                *exploration,
+                *learning_rate,
+            )),
+            AgentConfig::Cfr {
+                iterations,
+                variant,
+            } => {
+                Box::new(CfrAgent::new(rng.clone(), *iterations).with_damage(variant.apply(damage)))
+            }
+            AgentConfig::Dynamic {
+                probability_of_attack,
+                prior_attacks,
+                prior_finches,
+                resolution,
+            } => {
+                let model = match probability_of_attack {
+                    Some(probability) => AttackModel::Fixed(*probability),
+                    None => AttackModel::Estimated {
+                        prior_attacks: *prior_attacks,
+                        prior_finches: *prior_finches,
+                    },
+                };
+                Box::new(
+                    DynamicProgrammingAgent::new(model, *resolution).with_damage(damage.clone()),
+                )
+            }
+            AgentConfig::Expectimax {
+                depth,
+                model,
+                order,
+            } => Box::new(
+                ExpectimaxAgent::new(model.build(*order), *depth).with_damage(damage.clone()),
+            ),
+            AgentConfig::QLearning(td) => td.build(rng, TdRule::QLearning)?,
+            AgentConfig::Sarsa(td) => td.build(rng, TdRule::Sarsa)?,
+            AgentConfig::ExpectedSarsa(td) => td.build(rng, TdRule::ExpectedSarsa)?,
+            #[cfg(feature = "neural")]
+            AgentConfig::Neural {
+                weights,
+                hidden,
+                history,
+                greedy,
+            } => {
+                let policy = Policy::load(weights, *hidden, *history).map_err(|_| {
+                    RegistryError::InvalidArgument {
+                        agent: String::from("neural"),
+                        argument: String::from("weights"),
+                        value: weights.clone(),
+                    }
+                })?;
+                Box::new(
+                    NeuralAgent::new(rng.clone(), Rc::new(policy), *greedy)
+                        .with_damage(damage.clone()),
+                )
+            }
+            #[cfg(feature = "onnx")]
+            AgentConfig::Onnx {
+                model,
+                history,
+                greedy,
+            } => Box::new(
+                OnnxAgent::load(rng.clone(), model, *history, *greedy)
+                    .map_err(|_| RegistryError::InvalidArgument {
+                        agent: String::from("onnx"),
+                        argument: String::from("model"),
+                        value: model.clone(),
+                    })?
+                    .with_damage(damage.clone()),
+            ),
+            AgentConfig::External { command, args } => Box::new(ExternalAgent::new(
+                std::iter::once(command.clone())
+                    .chain(args.iter().cloned())
+                    .collect(),
+            )),
+            #[cfg(feature = "lua")]
+            AgentConfig::Lua { script } => {
+                Box::new(LuaAgent::load(rng.clone(), script).map_err(|_| {
+                    RegistryError::InvalidArgument {
+                        agent: String::from("lua"),
+                        argument: String::from("script"),
+                        value: script.clone(),
+                    }
+                })?)
+            }
+            #[cfg(feature = "grpc")]
+            AgentConfig::Grpc { endpoint } => {
+                Box::new(GrpcAgent::connect(endpoint).map_err(|_| {
+                    RegistryError::InvalidArgument {
+                        agent: String::from("grpc"),
+                        argument: String::from("endpoint"),
+                        value: endpoint.clone(),
+                    }
+                })?)
+            }
+            AgentConfig::Portfolio { agents, decay } => Box::new(PortfolioAgent::new(
+                agents
+                    .iter()
+                    .map(|agent| agent.build(registry, rng, damage))
+                    .collect::<Result<_, _>>()?,
+                *decay,
+            )),
+        })
+    }
+}
+
+// A roster entry is either a registry specification such as "random(p=0.3)"
+// or a table spelling out the parameters.
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+pub enum AgentEntry {
+    Spec(String),
+    Table(AgentConfig),
+}
+
+impl AgentEntry {
+    pub fn build(
+        &self,
+        registry: &AgentRegistry,
+        rng: &SharedRng,
+        damage: &DamageMatrix,
+    ) -> Result<Box<dyn GameAgent>, RegistryError> {
+        match self {
+            AgentEntry::Spec(spec) => registry.build_with_damage(spec, rng, damage),
+            AgentEntry::Table(config) => config.build(registry, rng, damage),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct OutputConfig {
+    // File receiving the tournament results, one column per agent. The
+    // extension is replaced by the one of each output format.
+    pub win_matrix: Option<String>,
+    // Formats the results are written in.
+    #[serde(default = "default_formats")]
+    pub formats: Vec<OutputFormat>,
+    // CSV file receiving win rates, confidence intervals and p-values of every pairing.
+    pub statistics: Option<String>,
+    // CSV file receiving game length and victory margin percentiles of every pairing.
+    pub distributions: Option<String>,
+    // CSV file receiving the population shares of every ecology generation.
+    pub ecology: Option<String>,
+    // CSV file receiving every point of every replicator dynamics trajectory.
+    pub replicator: Option<String>,
+    // CSV file receiving the Moran fixation probability of every mutant and resident.
+    pub moran: Option<String>,
+    // CSV file receiving the alpha-rank mass of every agent.
+    pub alpha_rank: Option<String>,
+    // CSV file receiving the Nash averaging share and rating of every agent.
+    pub nash_averaging: Option<String>,
+    // CSV file receiving the PageRank of every agent.
+    pub page_rank: Option<String>,
+    // CSV file receiving the lattice strategy map of every generation.
+    pub lattice: Option<String>,
+    // SVG file receiving the lattice strategy map of the last generation.
+    pub lattice_plot: Option<String>,
+    // CSV file receiving the best and mean fitness of every evolved generation.
+    pub evolution: Option<String>,
+    // CSV file receiving the best and mean fitness of every coevolved generation.
+    pub coevolution: Option<String>,
+    // CSV file receiving every evaluation of the parameter optimization.
+    pub optimization: Option<String>,
+    // CSV file receiving the best response of every double oracle iteration.
+    pub oracle: Option<String>,
+    // CSV file receiving every hit point ratio tried by the handicap search.
+    pub handicap_search: Option<String>,
+    // CSV file receiving the table record of every agent in the free-for-all.
+    pub free_for_all: Option<String>,
+    // CSV file receiving the record of every team against every other.
+    pub team_duel: Option<String>,
+    // CSV file receiving the score of every training episode.
+    pub training: Option<String>,
+    // CSV file receiving the learning curve of the train command.
+    pub self_play: Option<String>,
+    // Confidence level of the reported intervals.
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+}
+
+fn default_formats() -> Vec<OutputFormat> {
+    vec![OutputFormat::Csv]
+}
+
+fn default_confidence() -> f64 {
+    0.95
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            win_matrix: None,
+            formats: default_formats(),
+            statistics: None,
+            distributions: None,
+            ecology: None,
+            replicator: None,
+            moran: None,
+            alpha_rank: None,
+            nash_averaging: None,
+            page_rank: None,
+            lattice: None,
+            lattice_plot: None,
+            evolution: None,
+            coevolution: None,
+            optimization: None,
+            oracle: None,
+            handicap_search: None,
+            free_for_all: None,
+            team_duel: None,
+            training: None,
+            self_play: None,
+            confidence: default_confidence(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct ExperimentConfig {
+    pub seed: u64,
+    pub max_hit_points: i64,
+    pub num_retrials: u64,
+    #[serde(default = "default_schedule")]
+    pub schedule: PairingSchedule,
+    #[serde(default)]
+    pub game: GameSettings,
+    // Hit points lost for every pair of actions, see DamageMatrix.
+    #[serde(default)]
+    pub damage: DamageMatrix,
+    // Variant of the rules changing the damage matrix, e.g.
+    //   variant = { kind = "riposte", counter_damage = 1 }
+    #[serde(default)]
+    pub variant: RuleVariant,
+    // Stamina rules, without stamina if missing.
+    pub stamina: Option<StaminaRules>,
+    // Random damage and critical hits, fixed damage if missing.
+    pub stochastic_damage: Option<StochasticDamage>,
+    // Sudden death after a number of turns, see Overtime.
+    pub overtime: Option<Overtime>,
+    // Probability that an agent's chosen action is flipped before it is
+    // played. Agents see the executed action in the history.
+    #[serde(default)]
+    pub execution_noise: f64,
+    // Probability that an agent observes the opposite of its opponent's
+    // last action, making the duel a partially observed game.
+    #[serde(default)]
+    pub observation_noise: f64,
+    #[serde(default, rename = "match")]
+    pub match_format: MatchFormat,
+    pub agents: Vec<AgentEntry>,
+    // Starting conditions of agents in their pairings of the schedule.
+    #[serde(default)]
+    pub handicaps: Vec<HandicapEntry>,
+    #[serde(default)]
+    pub output: OutputConfig,
+    // Structured logs of the games and pairings, see crate::logging.
+    pub logging: Option<LoggingConfig>,
+    // Rate the roster with Glicko-2 while the tournament runs.
+    pub rating: Option<RatingConfig>,
+    // Evolve population shares over generations from the tournament's payoffs.
+    pub ecology: Option<EcologyConfig>,
+    // Run replicator dynamics on the tournament's payoffs.
+    pub replicator: Option<ReplicatorConfig>,
+    // Simulate a finite population evolving by a Moran process on the tournament's payoffs.
+    pub moran: Option<MoranConfig>,
+    // Rank the roster by alpha-rank on the tournament's payoffs.
+    pub alpha_rank: Option<AlphaRankConfig>,
+    // Rate the roster against the maximum entropy Nash equilibrium of the
+    // tournament's payoffs.
+    pub nash_averaging: Option<NashAveragingConfig>,
+    // Rank the roster by PageRank on the graph of who beats whom.
+    pub page_rank: Option<PageRankConfig>,
+    // Set every agent's share of attacks against the ESS of a turn of the
+    // duel seen as a Hawk-Dove game.
+    #[serde(default)]
+    pub hawk_dove: bool,
+    // Set every agent's share of attacks against the Nash equilibria of a
+    // turn of the duel seen as a bimatrix game.
+    #[serde(default)]
+    pub nash: bool,
+    // Rank the roster by Copeland score and report a Condorcet winner, the
+    // Smith set and cycles of the pairwise results.
+    #[serde(default)]
+    pub voting: bool,
+    // Print how often the best response to every agent's policy recorded
+    // over the tournament beats it.
+    #[serde(default)]
+    pub exploitability: bool,
+    // Play an elimination bracket instead of the pairing schedule.
+    pub bracket: Option<BracketConfig>,
+    // Play this many Swiss rounds instead of the pairing schedule.
+    pub swiss: Option<SwissConfig>,
+    // Evolve strategies on a grid by imitating the best neighbor instead of
+    // playing the pairing schedule.
+    pub lattice: Option<LatticeConfig>,
+    // Evolve parameters of agent families against the roster instead of
+    // playing the pairing schedule.
+    pub evolution: Option<EvolutionConfig>,
+    // Evolve two populations against each other instead of playing the
+    // pairing schedule.
+    pub coevolution: Option<CoevolutionConfig>,
+    // Tune the parameters of an agent family against the roster instead of
+    // playing the pairing schedule.
+    pub optimization: Option<OptimizationConfig>,
+    // Grow the roster by best responses to the equilibrium of its meta-game
+    // instead of playing the pairing schedule.
+    pub oracle: Option<OracleConfig>,
+    // Train a neural network policy against the roster instead of playing
+    // the pairing schedule.
+    pub training: Option<TrainingConfig>,
+    // Search the hit point ratio evening out two agents of the roster
+    // instead of playing the pairing schedule.
+    pub handicap_search: Option<HandicapSearchConfig>,
+    // Play tables of more than two agents sampled from the roster instead of
+    // playing the pairing schedule.
+    pub free_for_all: Option<FreeForAllConfig>,
+    // Play teams of two agents of the roster against each other instead of
+    // playing the pairing schedule.
+    pub team_duel: Option<TeamConfig>,
+    // Train a learner by self-play with the train command, evaluating it
+    // against the roster. Ignored when running the experiment.
+    pub self_play: Option<SelfPlayConfig>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct BracketConfig {
+    #[serde(default)]
+    pub elimination: Elimination,
+    // Roster indices from the first to the last seed; roster order if omitted.
+    pub seeding: Option<Vec<usize>>,
+}
+
+// Starting conditions of two agents whenever they meet, whichever seat they
+// take, e.g.
+//   [[handicaps]]
+//   agents = [0, 2]
+//   first = { max_hit_points = 300 }
+#[derive(Deserialize, Clone)]
+pub struct HandicapEntry {
+    // Roster indices of the two agents.
+    pub agents: (usize, usize),
+    #[serde(default)]
+    pub first: StartingConditions,
+    #[serde(default)]
+    pub second: StartingConditions,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct SwissConfig {
+    pub rounds: usize,
+}
+
+fn default_schedule() -> PairingSchedule {
+    PairingSchedule::RoundRobin
+}
+
+impl ExperimentConfig {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, ConfigError> {
+        let config: Self = toml::from_str(contents).map_err(ConfigError::Parse)?;
+        config
+            .damage_matrix()
+            .validate()
+            .map_err(ConfigError::Invalid)?;
+        if let Some(stamina) = &config.stamina {
+            stamina.validate().map_err(ConfigError::Invalid)?;
+        }
+        if let Some(damage) = &config.stochastic_damage {
+            damage.validate().map_err(ConfigError::Invalid)?;
+        }
+        if !(0.0..=1.0).contains(&config.execution_noise) {
+            return Err(ConfigError::Invalid(format!(
+                "execution noise {} is not between 0 and 1",
+                config.execution_noise
+            )));
+        }
+        config
+            .game
+            .observability
+            .validate()
+            .map_err(ConfigError::Invalid)?;
+        if !(0.0..=1.0).contains(&config.observation_noise) {
+            return Err(ConfigError::Invalid(format!(
+                "observation noise {} is not between 0 and 1",
+                config.observation_noise
+            )));
+        }
+        if let Some(overtime) = &config.overtime {
+            overtime.validate().map_err(ConfigError::Invalid)?;
+        }
+        config
+            .match_format
+            .validate()
+            .map_err(ConfigError::Invalid)?;
+        let may_stall = config.damage_matrix().has_harmless_exchange()
+            || config
+                .stochastic_damage
+                .as_ref()
+                .is_some_and(StochasticDamage::can_draw_zero);
+        let attrition = config
+            .overtime
+            .as_ref()
+            .is_some_and(|overtime| overtime.mode == OvertimeMode::Attrition);
+        if may_stall && config.game.max_turns.is_none() && !attrition {
+            return Err(ConfigError::Invalid(String::from(
+                "exchanges may cost nobody hit points, so games need a turn limit, \
+                 set game.max_turns or an attrition overtime",
+            )));
+        }
+        config
+            .schedule
+            .validate(config.agents.len())
+            .map_err(ConfigError::Invalid)?;
+        for agent in &config.agents {
+            if let AgentEntry::Table(agent) = agent {
+                agent.validate().map_err(ConfigError::Agent)?;
+            }
+        }
+        for handicap in &config.handicaps {
+            let (first, second) = handicap.agents;
+            if first >= config.agents.len() || second >= config.agents.len() || first == second {
+                return Err(ConfigError::Invalid(format!(
+                    "a handicap names agents {} and {}, which are not two of the {} agents",
+                    first,
+                    second,
+                    config.agents.len()
+                )));
+            }
+            for start in [&handicap.first, &handicap.second] {
+                start
+                    .validate(config.stamina.as_ref())
+                    .map_err(ConfigError::Invalid)?;
+            }
+        }
+        if let Some(search) = &config.handicap_search {
+            search
+                .validate(config.agents.len())
+                .map_err(ConfigError::Invalid)?;
+        }
+        if let Some(free_for_all) = &config.free_for_all {
+            free_for_all
+                .validate(config.agents.len())
+                .map_err(ConfigError::Invalid)?;
+        }
+        if let Some(team_duel) = &config.team_duel {
+            team_duel
+                .validate(config.agents.len())
+                .map_err(ConfigError::Invalid)?;
+        }
+        Ok(config)
+    }
+
+    // The damage matrix with the rule variant applied.
+    pub fn damage_matrix(&self) -> DamageMatrix {
+        self.variant.apply(&self.damage)
+    }
+
+    // The rules every game of the experiment is played by.
+    pub fn rules(&self) -> Duel {
+        let mut rules = Duel::new(self.max_hit_points).with_damage(self.damage_matrix());
+        if let Some(stamina) = &self.stamina {
+            rules = rules.with_stamina(stamina.clone());
+        }
+        if let Some(overtime) = &self.overtime {
+            rules = rules.with_overtime(overtime.clone());
+        }
+        if let Some(damage) = &self.stochastic_damage {
+            let rng = seeded_stream(self.seed, STOCHASTIC_DAMAGE_STREAM);
+            rules = rules.with_stochastic_damage(damage.clone(), Rc::new(RefCell::new(rng)));
+        }
+        if self.execution_noise > 0.0 {
+            let rng = seeded_stream(self.seed, EXECUTION_NOISE_STREAM);
+            rules = rules.with_execution_noise(self.execution_noise, Rc::new(RefCell::new(rng)));
+        }
+        if self.observation_noise > 0.0 {
+            let rng = seeded_stream(self.seed, OBSERVATION_NOISE_STREAM);
+            rules =
+                rules.with_observation_noise(self.observation_noise, Rc::new(RefCell::new(rng)));
+        }
+        rules
+    }
+
+    // The rules of every seating with a handicap, by (player one, player two).
+    pub fn pairing_rules(&self) -> Vec<((usize, usize), Duel)> {
+        let rules = self.rules();
+        self.handicaps
+            .iter()
+            .flat_map(|handicap| {
+                let (first, second) = handicap.agents;
+                [
+                    (
+                        (first, second),
+                        rules.clone().with_starting_conditions(
+                            handicap.first.clone(),
+                            handicap.second.clone(),
+                        ),
+                    ),
+                    (
+                        (second, first),
+                        rules.clone().with_starting_conditions(
+                            handicap.second.clone(),
+                            handicap.first.clone(),
+                        ),
+                    ),
+                ]
+            })
+            .collect()
+    }
+
+    pub fn rng(&self) -> SharedRng {
+        Rc::new(RefCell::new(AuditedRng::seed_from_u64(self.seed)))
+    }
+
+    pub fn metadata(&self) -> ResultMetadata {
+        ResultMetadata {
+            seed: self.seed,
+            max_hit_points: self.max_hit_points,
+            num_retrials: self.num_retrials,
+            best_of: self.match_format.best_of,
+            variant: self.variant.to_string(),
+        }
+    }
+
+    pub fn build_agents(
+        &self,
+        registry: &AgentRegistry,
+        rng: &SharedRng,
+    ) -> Result<Vec<Box<dyn GameAgent>>, ConfigError> {
+        let damage = self.damage_matrix();
+        self.agents
+            .iter()
+            .map(|agent| {
+                agent
+                    .build(registry, rng, &damage)
+                    .map_err(ConfigError::Agent)
+            })
+            .collect()
+    }
+
+    pub fn build_tournament(
+        &self,
+        registry: &AgentRegistry,
+    ) -> Result<Tournament<Duel>, ConfigError> {
+        let rng = self.rng();
+        let tournament = Tournament::new(
+            self.rules(),
+            self.build_agents(registry, &rng)?,
+            self.num_retrials,
+            self.schedule.clone(),
+        )
+        .with_settings(self.game.clone())
+        .with_match_format(self.match_format.clone())
+        .with_interleaving(self.rating.is_some())
+        .with_game_seeds(self.seed, rng);
+        Ok(self.pairing_rules().into_iter().fold(
+            tournament,
+            |tournament, ((player_one, player_two), rules)| {
+                tournament.with_pairing_rules(player_one, player_two, rules)
+            },
+        ))
+    }
+
+    pub fn build_free_for_all(
+        &self,
+        registry: &AgentRegistry,
+        free_for_all: &FreeForAllConfig,
+        rng: &SharedRng,
+    ) -> Result<FreeForAllTournament, ConfigError> {
+        Ok(FreeForAllTournament::new(
+            self.rules(),
+            self.build_agents(registry, rng)?,
+            free_for_all.clone(),
+        )
+        .with_settings(self.game.clone()))
+    }
+
+    pub fn build_team_duel(
+        &self,
+        registry: &AgentRegistry,
+        team_duel: &TeamConfig,
+        rng: &SharedRng,
+    ) -> Result<TeamDuel, ConfigError> {
+        Ok(TeamDuel::new(
+            self.rules(),
+            self.build_agents(registry, rng)?,
+            team_duel.clone(),
+        )
+        .with_settings(self.game.clone()))
+    }
+
+    pub fn build_handicap_search(
+        &self,
+        registry: &AgentRegistry,
+        search: &HandicapSearchConfig,
+    ) -> Result<HandicapSearch, ConfigError> {
+        let rng = self.rng();
+        let mut agents = self.build_agents(registry, &rng)?;
+        let (first, second) = search.agents;
+        let second_agent = agents[second].copy_self_to_anom();
+        Ok(HandicapSearch::new(
+            self.rules(),
+            agents.swap_remove(first),
+            second_agent,
+            search.clone(),
+        )
+        .with_settings(self.game.clone()))
+    }
+
+    pub fn build_swiss(
+        &self,
+        registry: &AgentRegistry,
+        swiss: &SwissConfig,
+    ) -> Result<SwissTournament<Duel>, ConfigError> {
+        let rng = self.rng();
+        Ok(SwissTournament::new(
+            self.rules(),
+            self.build_agents(registry, &rng)?,
+            swiss.rounds,
+        )
+        .with_settings(self.game.clone())
+        .with_match_format(self.match_format.clone()))
+    }
+
+    pub fn build_lattice(
+        &self,
+        registry: &AgentRegistry,
+        lattice: &LatticeConfig,
+    ) -> Result<Lattice<Duel>, ConfigError> {
+        let rng = self.rng();
+        let agents = self.build_agents(registry, &rng)?;
+        let lattice = Lattice::new(self.rules(), agents, lattice, &mut *rng.borrow_mut());
+        Ok(lattice.with_settings(self.game.clone()))
+    }
+
+    // The roster becomes the fixed opponent pool of the evolution.
+    pub fn build_evolution<'a>(
+        &self,
+        registry: &'a AgentRegistry,
+        evolution: &EvolutionConfig,
+        rng: &SharedRng,
+    ) -> Result<Evolution<'a>, ConfigError> {
+        Ok(Evolution::new(
+            self.rules(),
+            registry,
+            self.build_agents(registry, rng)?,
+            evolution.clone(),
+        )
+        .with_settings(self.game.clone()))
+    }
+
+    // The roster becomes the opponent set the parameters are tuned against.
+    pub fn build_optimization<'a>(
+        &self,
+        registry: &'a AgentRegistry,
+        optimization: &OptimizationConfig,
+        rng: &SharedRng,
+    ) -> Result<Optimization<'a>, ConfigError> {
+        Ok(Optimization::new(
+            self.rules(),
+            registry,
+            self.build_agents(registry, rng)?,
+            optimization.clone(),
+        )
+        .with_settings(self.game.clone()))
+    }
+
+    // The roster becomes the initial pool of the double oracle.
+    pub fn build_oracle<'a>(
+        &self,
+        registry: &'a AgentRegistry,
+        oracle: &OracleConfig,
+        rng: &SharedRng,
+    ) -> Result<DoubleOracle<'a>, ConfigError> {
+        Ok(DoubleOracle::new(
+            self.rules(),
+            registry,
+            self.build_agents(registry, rng)?,
+            oracle.clone(),
+        )
+        .with_settings(self.game.clone()))
+    }
+
+    // The roster becomes the opponent pool of the training.
+    #[cfg(feature = "neural")]
+    pub fn build_training(
+        &self,
+        registry: &AgentRegistry,
+        training: &TrainingConfig,
+        rng: &SharedRng,
+    ) -> Result<Training, ConfigError> {
+        Ok(Training::new(
+            self.rules(),
+            self.build_agents(registry, rng)?,
+            training.clone(),
+        )
+        .with_settings(self.game.clone()))
+    }
+
+    // The roster becomes the evaluation opponents of the learner.
+    pub fn build_self_play(
+        &self,
+        registry: &AgentRegistry,
+        self_play: &SelfPlayConfig,
+        rng: &SharedRng,
+    ) -> Result<SelfPlay, ConfigError> {
+        Ok(SelfPlay::new(
+            self.rules(),
+            self.build_agents(registry, rng)?,
+            self_play.clone(),
+        )
+        .with_settings(self.game.clone()))
+    }
+
+    // Resumes from the hall of fame archive if it already exists.
+    pub fn build_coevolution<'a>(
+        &self,
+        registry: &'a AgentRegistry,
+        coevolution: &CoevolutionConfig,
+    ) -> Result<Coevolution<'a>, ConfigError> {
+        let hall_of_fame = match &coevolution.archive {
+            Some(path) if Path::new(path).exists() => HallOfFame::load(path)?,
+            _ => HallOfFame::default(),
+        };
+        Ok(
+            Coevolution::new(self.rules(), registry, coevolution.clone())
+                .with_settings(self.game.clone())
+                .with_hall_of_fame(hall_of_fame),
+        )
+    }
+
+    pub fn build_bracket(
+        &self,
+        registry: &AgentRegistry,
+        bracket: &BracketConfig,
+    ) -> Result<Bracket<Duel>, ConfigError> {
+        let rng = self.rng();
+        let agents = self.build_agents(registry, &rng)?;
+        let seeding = match &bracket.seeding {
+            Some(seeding) => {
+                let mut sorted = seeding.clone();
+                sorted.sort();
+                if sorted != (0..agents.len()).collect::<Vec<_>>() {
+                    return Err(ConfigError::Invalid(
+                        "bracket seeding must list every roster index exactly once".to_string(),
+                    ));
+                }
+                seeding.clone()
+            }
+            None => (0..agents.len()).collect(),
+        };
+        Ok(Bracket::new(self.rules(), agents, bracket.elimination)
+            .with_settings(self.game.clone())
+            .with_seeding(seeding)
+            .with_match_format(self.match_format.clone()))
+    }
+}
@@ -0,0 +1,409 @@
+// A tiny language for rule-based strategies, so that strategies can be
+// written in configuration files without any programming, e.g.
+//
+//   if opponent attacked twice in a row then FINCH else ATTACK with p=0.7
+//   if opponent hp < own hp and not i finched then ATTACK else FINCH
+//
+// A rule is either an action, optionally played with probability `p` (the
+// other action otherwise), or `if <condition> then <rule> else <rule>`.
+// Conditions combine with `and`, `or`, `not` and parentheses, from
+//
+//   opponent attacked / opponent finched / i attacked / i finched
+//       the last action, or the last n with `twice`, `thrice`, `<n> times`,
+//       optionally followed by `in a row`
+//   <quantity> <comparison> <quantity>
+//       with the quantities `own hp`, `opponent hp`, `turn` and numbers and
+//       the comparisons <, <=, >, >=, = and !=
+//
+// Keywords and actions are case-insensitive. Before enough turns were played
+// to look back on, conditions on past actions are false.
+
+use std::fmt;
+
+use crate::duel::Action;
+use crate::history::HistoryView;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DslError(pub String);
+
+impl fmt::Display for DslError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid strategy rule: {}", self.0)
+    }
+}
+
+impl std::error::Error for DslError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Quantity {
+    OwnHitPoints,
+    OpposingHitPoints,
+    Turn,
+    Number(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    // The last `times` actions of the agent (`own`) or its opponent were all `action`.
+    Played {
+        own: bool,
+        action: Action,
+        times: usize,
+    },
+    Compare(Quantity, Comparison, Quantity),
+    Not(Box<Condition>),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    // Plays `action` with `probability`, the other action otherwise.
+    Play {
+        action: Action,
+        probability: f64,
+    },
+    If {
+        condition: Condition,
+        then: Box<Rule>,
+        otherwise: Box<Rule>,
+    },
+}
+
+// What a rule gets to know about the game.
+pub struct Situation<'a> {
+    pub own_hit_points: i64,
+    pub opposing_hit_points: i64,
+    pub history: &'a HistoryView<'a>,
+}
+
+impl Quantity {
+    fn value(&self, situation: &Situation) -> f64 {
+        match self {
+            Quantity::OwnHitPoints => situation.own_hit_points as f64,
+            Quantity::OpposingHitPoints => situation.opposing_hit_points as f64,
+            Quantity::Turn => situation.history.turns() as f64,
+            Quantity::Number(value) => *value,
+        }
+    }
+}
+
+impl Condition {
+    pub fn holds(&self, situation: &Situation) -> bool {
+        match self {
+            Condition::Played { own, action, times } => {
+                let actions = if *own {
+                    situation.history.own_actions
+                } else {
+                    situation.history.opposing_actions
+                };
+                actions.len() >= *times
+                    && actions[actions.len() - times..].iter().all(|a| a == action)
+            }
+            Condition::Compare(left, comparison, right) => {
+                let (left, right) = (left.value(situation), right.value(situation));
+                match comparison {
+                    Comparison::Less => left < right,
+                    Comparison::LessEqual => left <= right,
+                    Comparison::Greater => left > right,
+                    Comparison::GreaterEqual => left >= right,
+                    Comparison::Equal => left == right,
+                    Comparison::NotEqual => left != right,
+                }
+            }
+            Condition::Not(condition) => !condition.holds(situation),
+            Condition::And(left, right) => left.holds(situation) && right.holds(situation),
+            Condition::Or(left, right) => left.holds(situation) || right.holds(situation),
+        }
+    }
+}
+
+impl Rule {
+    pub fn parse(source: &str) -> Result<Self, DslError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        let rule = parser.rule()?;
+        match parser.peek() {
+            None => Ok(rule),
+            Some(token) => Err(DslError(format!("unexpected '{}' after the rule", token))),
+        }
+    }
+
+    // The probability of attacking in `situation`.
+    pub fn attack_probability(&self, situation: &Situation) -> f64 {
+        match self {
+            Rule::Play {
+                action: Action::ATTACK,
+                probability,
+            } => *probability,
+            Rule::Play {
+                action: Action::FINCH,
+                probability,
+            } => 1.0 - probability,
+            Rule::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                if condition.holds(situation) {
+                    then.attack_probability(situation)
+                } else {
+                    otherwise.attack_probability(situation)
+                }
+            }
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<String>, DslError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_alphanumeric() || c == '_' || c == '.' {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                    break;
+                }
+                word.push(c.to_ascii_lowercase());
+                chars.next();
+            }
+            tokens.push(word);
+        } else if "()".contains(c) {
+            tokens.push(c.to_string());
+            chars.next();
+        } else if "<>=!".contains(c) {
+            let mut operator = c.to_string();
+            chars.next();
+            if chars.peek() == Some(&'=') {
+                operator.push('=');
+                chars.next();
+            }
+            tokens.push(operator);
+        } else {
+            return Err(DslError(format!("unexpected character '{}'", c)));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&str> {
+        self.tokens.get(self.position + offset).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Result<String, DslError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| DslError(String::from("unexpected end of the rule")))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn accept(&mut self, keyword: &str) -> bool {
+        if self.peek() == Some(keyword) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, keyword: &str) -> Result<(), DslError> {
+        let token = self.next()?;
+        if token == keyword {
+            Ok(())
+        } else {
+            Err(DslError(format!(
+                "expected '{}', found '{}'",
+                keyword, token
+            )))
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, DslError> {
+        let token = self.next()?;
+        token
+            .parse()
+            .map_err(|_| DslError(format!("expected a number, found '{}'", token)))
+    }
+
+    fn rule(&mut self) -> Result<Rule, DslError> {
+        if self.accept("if") {
+            let condition = self.condition()?;
+            self.expect("then")?;
+            let then = self.rule()?;
+            self.expect("else")?;
+            let otherwise = self.rule()?;
+            return Ok(Rule::If {
+                condition,
+                then: Box::new(then),
+                otherwise: Box::new(otherwise),
+            });
+        }
+        let token = self.next()?;
+        let action = match token.as_str() {
+            "attack" => Action::ATTACK,
+            "finch" => Action::FINCH,
+            _ => {
+                return Err(DslError(format!(
+                    "expected an action or 'if', found '{}'",
+                    token
+                )));
+            }
+        };
+        let mut probability = 1.0;
+        if self.accept("with") {
+            if !self.accept("probability") {
+                self.expect("p")?;
+                self.expect("=")?;
+            }
+            probability = self.number()?;
+            if !(0.0..=1.0).contains(&probability) {
+                return Err(DslError(format!(
+                    "probability {} is not between 0 and 1",
+                    probability
+                )));
+            }
+        }
+        Ok(Rule::Play {
+            action,
+            probability,
+        })
+    }
+
+    fn condition(&mut self) -> Result<Condition, DslError> {
+        let mut condition = self.conjunction()?;
+        while self.accept("or") {
+            condition = Condition::Or(Box::new(condition), Box::new(self.conjunction()?));
+        }
+        Ok(condition)
+    }
+
+    fn conjunction(&mut self) -> Result<Condition, DslError> {
+        let mut condition = self.atom()?;
+        while self.accept("and") {
+            condition = Condition::And(Box::new(condition), Box::new(self.atom()?));
+        }
+        Ok(condition)
+    }
+
+    fn atom(&mut self) -> Result<Condition, DslError> {
+        if self.accept("not") {
+            return Ok(Condition::Not(Box::new(self.atom()?)));
+        }
+        if self.accept("(") {
+            let condition = self.condition()?;
+            self.expect(")")?;
+            return Ok(condition);
+        }
+        let own = match (self.peek(), self.peek_at(1)) {
+            (Some("i"), _) => Some(true),
+            (Some("opponent"), Some("attacked" | "finched")) => Some(false),
+            _ => None,
+        };
+        match own {
+            Some(own) => {
+                self.position += 1;
+                self.played(own)
+            }
+            None => {
+                let left = self.quantity()?;
+                let token = self.next()?;
+                let comparison = match token.as_str() {
+                    "<" => Comparison::Less,
+                    "<=" => Comparison::LessEqual,
+                    ">" => Comparison::Greater,
+                    ">=" => Comparison::GreaterEqual,
+                    "=" | "==" => Comparison::Equal,
+                    "!=" => Comparison::NotEqual,
+                    _ => {
+                        return Err(DslError(format!(
+                            "expected a comparison, found '{}'",
+                            token
+                        )));
+                    }
+                };
+                Ok(Condition::Compare(left, comparison, self.quantity()?))
+            }
+        }
+    }
+
+    fn played(&mut self, own: bool) -> Result<Condition, DslError> {
+        let token = self.next()?;
+        let action = match token.as_str() {
+            "attacked" => Action::ATTACK,
+            "finched" => Action::FINCH,
+            _ => {
+                return Err(DslError(format!(
+                    "expected 'attacked' or 'finched', found '{}'",
+                    token
+                )));
+            }
+        };
+        let times = if self.accept("once") {
+            1
+        } else if self.accept("twice") {
+            2
+        } else if self.accept("thrice") {
+            3
+        } else if let Some(times) = self.peek().and_then(|token| token.parse::<usize>().ok()) {
+            self.position += 1;
+            self.expect("times")?;
+            times
+        } else {
+            1
+        };
+        if self.accept("in") {
+            self.expect("a")?;
+            self.expect("row")?;
+        }
+        Ok(Condition::Played { own, action, times })
+    }
+
+    fn quantity(&mut self) -> Result<Quantity, DslError> {
+        let token = self.next()?;
+        match token.as_str() {
+            "own" | "my" => {
+                self.expect("hp")?;
+                Ok(Quantity::OwnHitPoints)
+            }
+            "opponent" => {
+                self.expect("hp")?;
+                Ok(Quantity::OpposingHitPoints)
+            }
+            "turn" => Ok(Quantity::Turn),
+            _ => token.parse().map(Quantity::Number).map_err(|_| {
+                DslError(format!(
+                    "expected a quantity or a condition, found '{}'",
+                    token
+                ))
+            }),
+        }
+    }
+}
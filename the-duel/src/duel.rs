@@ -0,0 +1,596 @@
+use rand::Rng;
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+use serde::{Deserialize, Serialize};
+
+use crate::agents::SharedRng;
+use crate::game::{GameOutcome, GameState, SimultaneousGame, bucket_hit_points, seeded_stream};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    ATTACK,
+    FINCH,
+}
+
+#[derive(Clone)]
+pub struct PlayerState {
+    pub max_hit_points: i64,
+    pub current_hit_points: i64,
+    // Only tracked when the rules use stamina, see StaminaRules.
+    pub stamina: Option<i64>,
+}
+
+impl PlayerState {
+    pub fn new(max_hit_points: i64) -> Self {
+        Self {
+            max_hit_points,
+            current_hit_points: max_hit_points,
+            stamina: None,
+        }
+    }
+
+    pub fn with_stamina(mut self, stamina: i64) -> Self {
+        self.stamina = Some(stamina);
+        self
+    }
+}
+
+impl Action {
+    pub const ALL: [Action; 2] = [Action::ATTACK, Action::FINCH];
+
+    // Position of the action in `ALL`.
+    pub fn index(&self) -> usize {
+        match self {
+            Action::ATTACK => 0,
+            Action::FINCH => 1,
+        }
+    }
+
+    pub fn opposite(&self) -> Action {
+        match self {
+            Action::ATTACK => Action::FINCH,
+            Action::FINCH => Action::ATTACK,
+        }
+    }
+}
+
+// The hit points both players lose for every pair of actions, as
+// `[player one, player two]` indexed by player one's and then player two's
+// action, in the order of `Action::ALL`. In TOML, the standard rules read
+//   damage = [[[1, 1], [1, 0]], [[0, 1], [1, 1]]]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DamageMatrix(pub Vec<Vec<[i64; 2]>>);
+
+impl DamageMatrix {
+    pub fn damage(&self, player_one_action: &Action, player_two_action: &Action) -> [i64; 2] {
+        self.0[player_one_action.index()][player_two_action.index()]
+    }
+
+    // Checks that there is an entry for every pair of actions and that no
+    // exchange heals.
+    pub fn validate(&self) -> Result<(), String> {
+        let size = Action::ALL.len();
+        if self.0.len() != size || self.0.iter().any(|row| row.len() != size) {
+            return Err(format!(
+                "the damage matrix needs {} rows of {} entries",
+                size, size
+            ));
+        }
+        if self.0.iter().flatten().flatten().any(|loss| *loss < 0) {
+            return Err(String::from(
+                "the damage matrix must not have negative entries",
+            ));
+        }
+        Ok(())
+    }
+
+    // Whether some exchange costs nobody hit points, so that games may never
+    // end.
+    pub fn has_harmless_exchange(&self) -> bool {
+        self.0.iter().flatten().any(|losses| *losses == [0, 0])
+    }
+}
+
+// Attacking into a finch costs the attacker, every other exchange costs both.
+impl Default for DamageMatrix {
+    fn default() -> Self {
+        Self(vec![vec![[1, 1], [1, 0]], vec![[0, 1], [1, 1]]])
+    }
+}
+
+// Named variants of the rules, changing the damage matrix they are played with.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuleVariant {
+    #[default]
+    Standard,
+    // Finching into an attack not only blocks it but deals `counter_damage`
+    // on top of what the attacker loses anyway.
+    Riposte {
+        #[serde(default = "default_counter_damage")]
+        counter_damage: i64,
+    },
+}
+
+fn default_counter_damage() -> i64 {
+    1
+}
+
+impl RuleVariant {
+    // `damage` changed by the variant.
+    pub fn apply(&self, damage: &DamageMatrix) -> DamageMatrix {
+        let mut damage = damage.clone();
+        if let RuleVariant::Riposte { counter_damage } = self {
+            let (attack, finch) = (Action::ATTACK.index(), Action::FINCH.index());
+            damage.0[attack][finch][0] += counter_damage;
+            damage.0[finch][attack][1] += counter_damage;
+        }
+        damage
+    }
+}
+
+impl std::fmt::Display for RuleVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleVariant::Standard => write!(f, "standard"),
+            RuleVariant::Riposte { counter_damage } => write!(f, "riposte({})", counter_damage),
+        }
+    }
+}
+
+// The factor every hit of the damage matrix is multiplied with.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DamageDistribution {
+    // Uniformly between `min` and `max`, both included.
+    Uniform { min: i64, max: i64 },
+    // One of `values`, each with its weight.
+    Weighted { values: Vec<i64>, weights: Vec<f64> },
+}
+
+impl DamageDistribution {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            DamageDistribution::Uniform { min, max } if min < &0 || min > max => Err(format!(
+                "uniform damage needs 0 <= min <= max, got {} and {}",
+                min, max
+            )),
+            DamageDistribution::Weighted { values, weights } => {
+                if values.len() != weights.len() {
+                    return Err(format!(
+                        "weighted damage needs one weight per value, got {} values and {} weights",
+                        values.len(),
+                        weights.len()
+                    ));
+                }
+                if values.iter().any(|value| *value < 0) {
+                    return Err(String::from("weighted damage values must not be negative"));
+                }
+                WeightedIndex::new(weights)
+                    .map(|_| ())
+                    .map_err(|err| format!("invalid damage weights: {}", err))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn can_draw_zero(&self) -> bool {
+        match self {
+            DamageDistribution::Uniform { min, .. } => *min == 0,
+            DamageDistribution::Weighted { values, weights } => values
+                .iter()
+                .zip(weights)
+                .any(|(value, weight)| *value == 0 && *weight > 0.0),
+        }
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R) -> i64 {
+        match self {
+            DamageDistribution::Uniform { min, max } => rng.random_range(*min..=*max),
+            DamageDistribution::Weighted { values, weights } => {
+                let index = WeightedIndex::new(weights).expect("validated weights");
+                values[index.sample(rng)]
+            }
+        }
+    }
+}
+
+// Random damage: every hit of the damage matrix is multiplied by a factor
+// drawn from `distribution`, and by `critical_multiplier` on a critical hit,
+// which lands with `critical_probability`. In TOML, e.g.
+//   [stochastic_damage]
+//   distribution = { kind = "uniform", min = 1, max = 3 }
+//   critical_probability = 0.05
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StochasticDamage {
+    pub distribution: DamageDistribution,
+    pub critical_probability: f64,
+    pub critical_multiplier: i64,
+}
+
+impl Default for StochasticDamage {
+    fn default() -> Self {
+        Self {
+            distribution: DamageDistribution::Uniform { min: 1, max: 1 },
+            critical_probability: 0.0,
+            critical_multiplier: 2,
+        }
+    }
+}
+
+impl StochasticDamage {
+    pub fn validate(&self) -> Result<(), String> {
+        self.distribution.validate()?;
+        if !(0.0..=1.0).contains(&self.critical_probability) {
+            return Err(format!(
+                "critical hit probability {} is not between 0 and 1",
+                self.critical_probability
+            ));
+        }
+        if self.critical_multiplier < 0 {
+            return Err(String::from(
+                "the critical hit multiplier must not be negative",
+            ));
+        }
+        Ok(())
+    }
+
+    // Whether a hit may end up dealing no damage at all.
+    pub fn can_draw_zero(&self) -> bool {
+        self.distribution.can_draw_zero()
+            || (self.critical_multiplier == 0 && self.critical_probability > 0.0)
+    }
+
+    // The damage of a hit the damage matrix rates `damage`. Exchanges
+    // without damage draw nothing.
+    pub fn draw<R: Rng>(&self, damage: i64, rng: &mut R) -> i64 {
+        if damage <= 0 {
+            return damage;
+        }
+        let damage = damage * self.distribution.sample(rng);
+        if rng.random_bool(self.critical_probability) {
+            damage * self.critical_multiplier
+        } else {
+            damage
+        }
+    }
+}
+
+// Optional stamina: attacking costs `attack_cost`, finching regenerates
+// `finch_regeneration` up to `max_stamina`. Players short of the stamina for
+// an attack are exhausted and forced to finch.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StaminaRules {
+    pub max_stamina: i64,
+    pub attack_cost: i64,
+    pub finch_regeneration: i64,
+}
+
+impl Default for StaminaRules {
+    fn default() -> Self {
+        Self {
+            max_stamina: 10,
+            attack_cost: 2,
+            finch_regeneration: 1,
+        }
+    }
+}
+
+impl StaminaRules {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_stamina < self.attack_cost {
+            return Err(format!(
+                "a maximum stamina of {} never affords an attack costing {}",
+                self.max_stamina, self.attack_cost
+            ));
+        }
+        if self.attack_cost < 0 || self.finch_regeneration < 0 {
+            return Err(String::from(
+                "stamina costs and regeneration must not be negative",
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn is_exhausted(&self, state: &PlayerState) -> bool {
+        state
+            .stamina
+            .is_some_and(|stamina| stamina < self.attack_cost)
+    }
+
+    fn spend(&self, state: &mut PlayerState, action: &Action) {
+        if let Some(stamina) = &mut state.stamina {
+            *stamina = match action {
+                Action::ATTACK => *stamina - self.attack_cost,
+                Action::FINCH => (*stamina + self.finch_regeneration).min(self.max_stamina),
+            };
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OvertimeMode {
+    // Damage doubles with every overtime turn.
+    #[default]
+    DoubleDamage,
+    // Both players lose `attrition` hit points every overtime turn, which
+    // also decides games in which nobody takes damage.
+    Attrition,
+}
+
+// Sudden death once `after_turns` turns were played, so that games end even
+// if the rules let them stall, e.g.
+//   overtime = { after_turns = 1000, mode = "attrition", attrition = 5 }
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Overtime {
+    pub after_turns: usize,
+    #[serde(default)]
+    pub mode: OvertimeMode,
+    #[serde(default = "default_attrition")]
+    pub attrition: i64,
+}
+
+fn default_attrition() -> i64 {
+    1
+}
+
+impl Overtime {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.mode == OvertimeMode::Attrition && self.attrition <= 0 {
+            return Err(format!(
+                "overtime attrition needs to cost hit points, got {}",
+                self.attrition
+            ));
+        }
+        Ok(())
+    }
+
+    // The damage of the exchange in turn `turn`, counted from 0, and the
+    // hit points both players lose on top.
+    pub fn apply(&self, turn: usize, damage: i64) -> (i64, i64) {
+        if turn < self.after_turns {
+            return (damage, 0);
+        }
+        match self.mode {
+            OvertimeMode::DoubleDamage => {
+                let doublings = (turn - self.after_turns + 1).min(62) as u32;
+                (damage.saturating_mul(1 << doublings), 0)
+            }
+            OvertimeMode::Attrition => (damage, self.attrition),
+        }
+    }
+}
+
+// What a player starts a game with, where it differs from the rules, e.g. a
+// handicap of fewer hit points. Starting stamina needs stamina rules.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StartingConditions {
+    pub max_hit_points: Option<i64>,
+    pub stamina: Option<i64>,
+}
+
+impl StartingConditions {
+    pub fn validate(&self, stamina: Option<&StaminaRules>) -> Result<(), String> {
+        if let Some(max_hit_points) = self.max_hit_points
+            && max_hit_points <= 0
+        {
+            return Err(format!(
+                "players need to start with hit points, got {}",
+                max_hit_points
+            ));
+        }
+        match (self.stamina, stamina) {
+            (Some(_), None) => Err(String::from("starting stamina needs a [stamina] section")),
+            (Some(start), Some(rules)) if !(0..=rules.max_stamina).contains(&start) => {
+                Err(format!(
+                    "starting stamina {} is not between 0 and {}",
+                    start, rules.max_stamina
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// Streams of the experiment's seed the random rules draw from, see
+// `seeded_stream`.
+pub const STOCHASTIC_DAMAGE_STREAM: u64 = 1;
+pub const EXECUTION_NOISE_STREAM: u64 = 2;
+pub const OBSERVATION_NOISE_STREAM: u64 = 3;
+
+// The attack/finch duel: every exchange costs somebody hit points, the last
+// one standing wins.
+#[derive(Clone)]
+pub struct Duel {
+    pub max_hit_points: i64,
+    pub damage: DamageMatrix,
+    pub stamina: Option<StaminaRules>,
+    // Drawing from the given generator, which should be seeded by the
+    // experiment so that games can be reproduced.
+    pub stochastic_damage: Option<(StochasticDamage, SharedRng)>,
+    // Of player one and player two.
+    pub starting_conditions: [StartingConditions; 2],
+    pub overtime: Option<Overtime>,
+    // Trembling hand: every chosen action is flipped with the given
+    // probability before it is played, drawing from the given generator.
+    pub execution_noise: Option<(f64, SharedRng)>,
+    // Every action a player observes of its opponent is flipped with the
+    // given probability, drawing from the given generator.
+    pub observation_noise: Option<(f64, SharedRng)>,
+}
+
+impl Duel {
+    pub fn new(max_hit_points: i64) -> Self {
+        Self {
+            max_hit_points,
+            damage: DamageMatrix::default(),
+            stamina: None,
+            stochastic_damage: None,
+            starting_conditions: Default::default(),
+            overtime: None,
+            execution_noise: None,
+            observation_noise: None,
+        }
+    }
+
+    pub fn with_execution_noise(mut self, epsilon: f64, rng: SharedRng) -> Self {
+        self.execution_noise = Some((epsilon, rng));
+        self
+    }
+
+    pub fn with_observation_noise(mut self, epsilon: f64, rng: SharedRng) -> Self {
+        self.observation_noise = Some((epsilon, rng));
+        self
+    }
+
+    pub fn with_overtime(mut self, overtime: Overtime) -> Self {
+        self.overtime = Some(overtime);
+        self
+    }
+
+    pub fn with_starting_conditions(
+        mut self,
+        player_one: StartingConditions,
+        player_two: StartingConditions,
+    ) -> Self {
+        self.starting_conditions = [player_one, player_two];
+        self
+    }
+
+    pub fn with_stochastic_damage(mut self, damage: StochasticDamage, rng: SharedRng) -> Self {
+        self.stochastic_damage = Some((damage, rng));
+        self
+    }
+
+    pub fn with_stamina(mut self, stamina: StaminaRules) -> Self {
+        self.stamina = Some(stamina);
+        self
+    }
+
+    fn player_state(&self, start: &StartingConditions) -> PlayerState {
+        let state = PlayerState::new(start.max_hit_points.unwrap_or(self.max_hit_points));
+        match &self.stamina {
+            Some(stamina) => state.with_stamina(start.stamina.unwrap_or(stamina.max_stamina)),
+            None => state,
+        }
+    }
+
+    pub fn with_damage(mut self, damage: DamageMatrix) -> Self {
+        self.damage = damage;
+        self
+    }
+}
+
+impl SimultaneousGame for Duel {
+    type PlayerState = PlayerState;
+    type Action = Action;
+
+    fn initial_state(&self) -> GameState<Self> {
+        let [player_one, player_two] = &self.starting_conditions;
+        GameState::new(self.player_state(player_one), self.player_state(player_two))
+    }
+
+    fn resolve_actions(
+        &self,
+        state: &mut GameState<Self>,
+        player_one_action: Action,
+        player_two_action: Action,
+    ) {
+        let [mut player_one_damage, mut player_two_damage] =
+            self.damage.damage(&player_one_action, &player_two_action);
+        if let Some((stochastic, rng)) = &self.stochastic_damage {
+            let mut rng = rng.borrow_mut();
+            player_one_damage = stochastic.draw(player_one_damage, &mut *rng);
+            player_two_damage = stochastic.draw(player_two_damage, &mut *rng);
+        }
+        if let Some(overtime) = &self.overtime {
+            let turn = state.history.turns();
+            let (one, attrition) = overtime.apply(turn, player_one_damage);
+            let (two, _) = overtime.apply(turn, player_two_damage);
+            player_one_damage = one + attrition;
+            player_two_damage = two + attrition;
+        }
+        state.player_one_state.current_hit_points -= player_one_damage;
+        state.player_two_state.current_hit_points -= player_two_damage;
+        if let Some(stamina) = &self.stamina {
+            stamina.spend(&mut state.player_one_state, &player_one_action);
+            stamina.spend(&mut state.player_two_state, &player_two_action);
+        }
+    }
+
+    fn check_end_condition(&self, state: &GameState<Self>) -> GameOutcome {
+        if state.player_one_state.current_hit_points <= 0
+            && state.player_two_state.current_hit_points <= 0
+        {
+            return GameOutcome::TIE;
+        }
+        if state.player_one_state.current_hit_points <= 0 {
+            return GameOutcome::WIN(2);
+        }
+        if state.player_two_state.current_hit_points <= 0 {
+            return GameOutcome::WIN(1);
+        }
+        GameOutcome::CONTINUE
+    }
+
+    fn enforce_legality(&self, state: &GameState<Self>, player: u64, action: Action) -> Action {
+        let player_state = if player == 1 {
+            &state.player_one_state
+        } else {
+            &state.player_two_state
+        };
+        let action = match &self.execution_noise {
+            Some((epsilon, rng)) if rng.borrow_mut().random_bool(*epsilon) => action.opposite(),
+            _ => action,
+        };
+        match &self.stamina {
+            Some(stamina) if stamina.is_exhausted(player_state) => Action::FINCH,
+            _ => action,
+        }
+    }
+
+    fn bucket_state(state: &PlayerState, buckets: usize) -> PlayerState {
+        PlayerState {
+            current_hit_points: bucket_hit_points(
+                state.current_hit_points,
+                state.max_hit_points,
+                buckets,
+            ),
+            ..state.clone()
+        }
+    }
+
+    fn observe_action(&self, _observer: u64, action: &Action) -> Action {
+        match &self.observation_noise {
+            Some((epsilon, rng)) if rng.borrow_mut().random_bool(*epsilon) => action.opposite(),
+            _ => action.clone(),
+        }
+    }
+
+    fn reseed(&self, seed: u64) {
+        let generators = [
+            (
+                STOCHASTIC_DAMAGE_STREAM,
+                self.stochastic_damage.as_ref().map(|(_, rng)| rng),
+            ),
+            (
+                EXECUTION_NOISE_STREAM,
+                self.execution_noise.as_ref().map(|(_, rng)| rng),
+            ),
+            (
+                OBSERVATION_NOISE_STREAM,
+                self.observation_noise.as_ref().map(|(_, rng)| rng),
+            ),
+        ];
+        for (stream, rng) in generators {
+            if let Some(rng) = rng {
+                *rng.borrow_mut() = seeded_stream(seed, stream);
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use serde::Deserialize;
+
+use crate::tournament::TournamentResults;
+
+#[derive(Deserialize, Clone)]
+pub struct EcologyConfig {
+    pub generations: usize,
+    // Shares below this count as extinct and are removed from the population.
+    #[serde(default = "default_extinction_share")]
+    pub extinction_share: f64,
+}
+
+fn default_extinction_share() -> f64 {
+    1e-6
+}
+
+// `payoffs[i][j]` is the expected score of agent i against agent j, counting
+// a tie as half a win. Pairings that were never played score as a tie.
+pub fn payoff_matrix(results: &TournamentResults) -> Vec<Vec<f64>> {
+    let num_agents = results.num_agents();
+    (0..num_agents)
+        .map(|agent| {
+            (0..num_agents)
+                .map(|opponent| {
+                    let record = results.record(agent, opponent);
+                    if record.games() == 0 {
+                        0.5
+                    } else {
+                        (record.wins as f64 + 0.5 * record.ties as f64) / record.games() as f64
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// An Axelrod-style ecological tournament: every generation, each strategy's
+// share of the population grows in proportion to its average payoff against
+// the current population.
+pub struct Ecology {
+    pub payoffs: Vec<Vec<f64>>,
+    pub shares: Vec<f64>,
+    pub extinction_share: f64,
+}
+
+impl Ecology {
+    // Starts from equal shares of every strategy.
+    pub fn new(payoffs: Vec<Vec<f64>>, extinction_share: f64) -> Self {
+        let num_agents = payoffs.len();
+        Self {
+            payoffs,
+            shares: vec![1.0 / num_agents as f64; num_agents],
+            extinction_share,
+        }
+    }
+
+    pub fn from_results(results: &TournamentResults, config: &EcologyConfig) -> Self {
+        Self::new(payoff_matrix(results), config.extinction_share)
+    }
+
+    // Payoff of each strategy against the current population.
+    pub fn fitness(&self) -> Vec<f64> {
+        self.payoffs
+            .iter()
+            .map(|row| row.iter().zip(&self.shares).map(|(p, s)| p * s).sum())
+            .collect()
+    }
+
+    pub fn step(&mut self) {
+        let fitness = self.fitness();
+        for (share, fitness) in self.shares.iter_mut().zip(&fitness) {
+            *share *= fitness;
+            if *share < self.extinction_share {
+                *share = 0.0;
+            }
+        }
+        let total: f64 = self.shares.iter().sum();
+        if total > 0.0 {
+            for share in self.shares.iter_mut() {
+                *share /= total;
+            }
+        }
+    }
+
+    // Shares of every generation, starting with the initial population.
+    pub fn run(&mut self, generations: usize) -> Vec<Vec<f64>> {
+        let mut trajectory = vec![self.shares.clone()];
+        for _ in 0..generations {
+            self.step();
+            trajectory.push(self.shares.clone());
+        }
+        trajectory
+    }
+}
@@ -0,0 +1,104 @@
+// A reinforcement learning environment in the style of Gym: one seat is
+// played by the learner through `reset` and `step`, the other by a bot from
+// the sandbox acting as part of the environment. Actions and observations
+// are those of the OpenSpiel-like interface; the reward is the learner's
+// return, i.e. +1 or -1 when the game is decided and 0 otherwise.
+
+use crate::agents::GameAgent;
+use crate::duel::Duel;
+use crate::game::GameSettings;
+use crate::openspiel::{SpielError, SpielGame, SpielState};
+
+pub struct Step {
+    pub observation: Vec<f32>,
+    pub reward: f64,
+    pub done: bool,
+}
+
+pub struct DuelEnv<G: SpielGame = Duel> {
+    rules: G,
+    settings: GameSettings,
+    // Copied into a fresh opponent at every reset.
+    template: Box<dyn GameAgent<G>>,
+    opponent: Box<dyn GameAgent<G>>,
+    // The learner's player, 0 or 1.
+    pub player: usize,
+    state: SpielState<G>,
+}
+
+impl<G: SpielGame> DuelEnv<G> {
+    // The learner plays `player` (0 or 1) against `opponent`.
+    pub fn new(rules: G, opponent: Box<dyn GameAgent<G>>, player: usize) -> Self {
+        assert!(player < 2, "there is no player {}", player);
+        let settings = GameSettings::default();
+        let mut env = Self {
+            state: SpielState::new(rules.clone(), settings.clone()),
+            opponent: opponent.copy_self_to_anom(),
+            template: opponent,
+            rules,
+            settings,
+            player,
+        };
+        env.reset();
+        env
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self.reset();
+        self
+    }
+
+    pub fn num_actions(&self) -> usize {
+        self.rules.num_distinct_actions()
+    }
+
+    pub fn observation_size(&self) -> usize {
+        self.rules.observation_tensor_size()
+    }
+
+    pub fn legal_actions(&self) -> Vec<usize> {
+        self.state.legal_actions(self.player)
+    }
+
+    // Starts a new game against a fresh copy of the opponent.
+    pub fn reset(&mut self) -> Vec<f32> {
+        self.state = SpielState::new(self.rules.clone(), self.settings.clone());
+        self.opponent = self.template.copy_self_to_anom();
+        self.opponent.set_mode(self.settings.mode);
+        self.state.observation_tensor(self.player)
+    }
+
+    // Plays one turn with the learner's `action` against the opponent's choice.
+    pub fn step(&mut self, action: usize) -> Result<Step, SpielError> {
+        if self.state.is_terminal() {
+            return Err(SpielError::Terminal);
+        }
+        let opposing_player = (1 - self.player) as u64 + 1;
+        let state = &self.state.state;
+        let own_state = if opposing_player == 1 {
+            &state.player_one_state
+        } else {
+            &state.player_two_state
+        };
+        let opposing = self.opponent.decide_action(
+            own_state,
+            &state.observed_opposing_action(opposing_player),
+            &self.settings.observe_opponent(state, opposing_player),
+            &state.history.view(opposing_player),
+        );
+        let opposing = self.rules.action_id(&opposing);
+        let mut actions = [action; 2];
+        actions[1 - self.player] = opposing;
+        self.state.apply_actions(&actions)?;
+        Ok(Step {
+            observation: self.state.observation_tensor(self.player),
+            reward: self.state.returns()[self.player],
+            done: self.state.is_terminal(),
+        })
+    }
+
+    pub fn state(&self) -> &SpielState<G> {
+        &self.state
+    }
+}
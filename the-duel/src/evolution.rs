@@ -0,0 +1,314 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agents::{GameAgent, SharedRng};
+use crate::duel::Duel;
+use crate::game::{Game, GameOutcome, GameSettings};
+use crate::registry::{AgentRegistry, RegistryError};
+
+// A parameterized agent family of the registry whose parameters get evolved.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Family {
+    // random(p)
+    Random,
+    // markov(to_attack, to_finch)
+    Markov,
+    // one_step(losing, not_losing, exchange)
+    OneStep,
+}
+
+impl Family {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Family::Random => "random",
+            Family::Markov => "markov",
+            Family::OneStep => "one_step",
+        }
+    }
+
+    // Range every parameter is drawn from and kept within.
+    pub fn bounds(&self) -> &'static [(f64, f64)] {
+        match self {
+            Family::Random => &[(0.0, 1.0)],
+            Family::Markov => &[(0.0, 1.0), (0.0, 1.0)],
+            Family::OneStep => &[(-10.0, 0.0), (-10.0, 0.0), (-10.0, 0.0)],
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EvolutionConfig {
+    // Each family is evolved in a population of its own.
+    pub families: Vec<Family>,
+    pub population_size: usize,
+    pub generations: usize,
+    // Games against every opponent of the pool, per seat, to rate a genome.
+    #[serde(default = "default_games")]
+    pub games: u64,
+    // Chance of each offspring to be a blend of two parents rather than a copy of one.
+    #[serde(default = "default_crossover_rate")]
+    pub crossover_rate: f64,
+    // Chance of each parameter to be perturbed.
+    #[serde(default = "default_mutation_rate")]
+    pub mutation_rate: f64,
+    // Standard deviation of a perturbation, relative to the parameter's range.
+    #[serde(default = "default_mutation_scale")]
+    pub mutation_scale: f64,
+    // Best genomes carried over to the next generation unchanged.
+    #[serde(default = "default_elite")]
+    pub elite: usize,
+    // Genomes competing for every parent slot.
+    #[serde(default = "default_tournament_size")]
+    pub tournament_size: usize,
+}
+
+fn default_games() -> u64 {
+    5
+}
+
+fn default_crossover_rate() -> f64 {
+    0.7
+}
+
+fn default_mutation_rate() -> f64 {
+    0.2
+}
+
+fn default_mutation_scale() -> f64 {
+    0.1
+}
+
+fn default_elite() -> usize {
+    2
+}
+
+fn default_tournament_size() -> usize {
+    3
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Genome {
+    pub family: Family,
+    pub params: Vec<f64>,
+}
+
+impl Genome {
+    pub(crate) fn random<R: Rng>(family: Family, rng: &mut R) -> Self {
+        let params = family
+            .bounds()
+            .iter()
+            .map(|&(low, high)| rng.random_range(low..=high))
+            .collect();
+        Self { family, params }
+    }
+
+    // The agent specification of the genome, e.g. `markov(0.25, 0.5)`.
+    pub fn spec(&self) -> String {
+        let params: Vec<String> = self.params.iter().map(|p| format!("{:.4}", p)).collect();
+        format!("{}({})", self.family.name(), params.join(", "))
+    }
+
+    fn crossover<R: Rng>(&self, other: &Genome, rng: &mut R) -> Genome {
+        let params = self
+            .params
+            .iter()
+            .zip(&other.params)
+            .map(|(a, b)| {
+                let weight = rng.random::<f64>();
+                weight * a + (1.0 - weight) * b
+            })
+            .collect();
+        Genome {
+            family: self.family,
+            params,
+        }
+    }
+
+    fn mutate<R: Rng>(&mut self, config: &EvolutionConfig, rng: &mut R) {
+        for (param, &(low, high)) in self.params.iter_mut().zip(self.family.bounds()) {
+            if rng.random::<f64>() < config.mutation_rate {
+                // Box-Muller transform of two uniform samples
+                let u: f64 = 1.0 - rng.random::<f64>();
+                let v: f64 = rng.random();
+                let normal = (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos();
+                *param = (*param + normal * config.mutation_scale * (high - low)).clamp(low, high);
+            }
+        }
+    }
+}
+
+// Best and mean fitness of one generation of a population.
+pub struct GenerationSummary {
+    pub population: usize,
+    pub family: Family,
+    pub generation: usize,
+    pub best: Genome,
+    pub best_fitness: f64,
+    pub mean_fitness: f64,
+}
+
+// Evolves parameter vectors of agent families by tournament selection, blend
+// crossover and Gaussian mutation. A genome's fitness is its average score
+// against a fixed pool of opponents, a tie counting as half a win.
+pub struct Evolution<'a> {
+    pub rules: Duel,
+    pub settings: GameSettings,
+    pub registry: &'a AgentRegistry,
+    pub opponents: Vec<Box<dyn GameAgent>>,
+    pub config: EvolutionConfig,
+}
+
+impl<'a> Evolution<'a> {
+    pub fn new(
+        rules: Duel,
+        registry: &'a AgentRegistry,
+        opponents: Vec<Box<dyn GameAgent>>,
+        config: EvolutionConfig,
+    ) -> Self {
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            registry,
+            opponents,
+            config,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn fitness(&self, genome: &Genome, rng: &SharedRng) -> Result<f64, RegistryError> {
+        let agent = self
+            .registry
+            .build_with_damage(&genome.spec(), rng, &self.rules.damage)?;
+        let total: f64 = self
+            .opponents
+            .iter()
+            .map(|opponent| {
+                average_score(
+                    &self.rules,
+                    &self.settings,
+                    agent.as_ref(),
+                    opponent.as_ref(),
+                    self.config.games,
+                )
+            })
+            .sum();
+        Ok(total / self.opponents.len().max(1) as f64)
+    }
+
+    // Evolves every configured family and summarizes each generation.
+    pub fn run(&self, rng: &SharedRng) -> Result<Vec<GenerationSummary>, RegistryError> {
+        let mut summaries = Vec::new();
+        for (index, &family) in self.config.families.iter().enumerate() {
+            let mut population: Vec<Genome> = (0..self.config.population_size)
+                .map(|_| Genome::random(family, &mut *rng.borrow_mut()))
+                .collect();
+            for generation in 0..=self.config.generations {
+                let fitness = population
+                    .iter()
+                    .map(|genome| self.fitness(genome, rng))
+                    .collect::<Result<Vec<f64>, _>>()?;
+                summaries.push(GenerationSummary::of(
+                    index,
+                    generation,
+                    &population,
+                    &fitness,
+                ));
+                if generation == self.config.generations {
+                    break;
+                }
+                population = breed(&population, &fitness, &self.config, rng);
+            }
+        }
+        Ok(summaries)
+    }
+}
+
+impl GenerationSummary {
+    pub(crate) fn of(
+        population: usize,
+        generation: usize,
+        genomes: &[Genome],
+        fitness: &[f64],
+    ) -> Self {
+        let best = (0..genomes.len())
+            .max_by(|a, b| fitness[*a].total_cmp(&fitness[*b]))
+            .unwrap();
+        GenerationSummary {
+            population,
+            family: genomes[best].family,
+            generation,
+            best: genomes[best].clone(),
+            best_fitness: fitness[best],
+            mean_fitness: fitness.iter().sum::<f64>() / fitness.len() as f64,
+        }
+    }
+}
+
+// Average score of `agent` over `games` games in each seat against
+// `opponent`, a tie counting as half a win.
+pub(crate) fn average_score(
+    rules: &Duel,
+    settings: &GameSettings,
+    agent: &dyn GameAgent,
+    opponent: &dyn GameAgent,
+    games: u64,
+) -> f64 {
+    let play = |player_one: Box<dyn GameAgent>, player_two: Box<dyn GameAgent>| {
+        let mut game =
+            Game::new(rules.clone(), player_one, player_two).with_settings(settings.clone());
+        match game.play() {
+            GameOutcome::WIN(1) => 1.0,
+            GameOutcome::WIN(_) => 0.0,
+            _ => 0.5,
+        }
+    };
+    let mut score = 0.0;
+    for _ in 0..games {
+        score += play(agent.copy_self_to_anom(), opponent.copy_self_to_anom());
+        score += 1.0 - play(opponent.copy_self_to_anom(), agent.copy_self_to_anom());
+    }
+    score / (2 * games).max(1) as f64
+}
+
+// Index of the fittest of `tournament_size` uniformly drawn genomes.
+fn select(fitness: &[f64], config: &EvolutionConfig, rng: &SharedRng) -> usize {
+    let mut rng = rng.borrow_mut();
+    (0..config.tournament_size.max(1))
+        .map(|_| rng.random_range(0..fitness.len()))
+        .max_by(|a, b| fitness[*a].total_cmp(&fitness[*b]))
+        .unwrap()
+}
+
+// The next generation: the elite unchanged, filled up with mutated offspring
+// of selected parents.
+pub(crate) fn breed(
+    population: &[Genome],
+    fitness: &[f64],
+    config: &EvolutionConfig,
+    rng: &SharedRng,
+) -> Vec<Genome> {
+    let mut ranking: Vec<usize> = (0..population.len()).collect();
+    ranking.sort_by(|a, b| fitness[*b].total_cmp(&fitness[*a]));
+    let mut next: Vec<Genome> = ranking
+        .iter()
+        .take(config.elite)
+        .map(|&i| population[i].clone())
+        .collect();
+    while next.len() < population.len() {
+        let parent = &population[select(fitness, config, rng)];
+        let mut child = if rng.borrow_mut().random::<f64>() < config.crossover_rate {
+            let other = &population[select(fitness, config, rng)];
+            parent.crossover(other, &mut *rng.borrow_mut())
+        } else {
+            parent.clone()
+        };
+        child.mutate(config, &mut *rng.borrow_mut());
+        next.push(child);
+    }
+    next
+}
@@ -0,0 +1,72 @@
+// Experience replay for learning agents.
+//
+// A learner records every step it takes as a transition into a bounded
+// buffer, dropping the oldest once it is full, and learns from a sample of
+// past transitions besides the latest one. Copies of a learner share the
+// buffer, like they share what they learn.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use rand::Rng;
+
+// One step of a learner: the state it saw, the action it took, the reward
+// it got for it, and the state and action that followed.
+#[derive(Clone, Copy)]
+pub struct Transition<S> {
+    pub state: S,
+    pub action: usize,
+    pub reward: f64,
+    pub next: S,
+    pub next_action: usize,
+}
+
+pub struct ExperienceBuffer<S> {
+    pub capacity: usize,
+    transitions: VecDeque<Transition<S>>,
+}
+
+impl<S: Clone> ExperienceBuffer<S> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            transitions: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn push(&mut self, transition: Transition<S>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.transitions.len() == self.capacity {
+            self.transitions.pop_front();
+        }
+        self.transitions.push_back(transition);
+    }
+
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Transition<S>> {
+        self.transitions.iter()
+    }
+
+    // `count` transitions drawn uniformly with replacement, none if empty.
+    pub fn sample<R: Rng>(&self, count: usize, rng: &mut R) -> Vec<Transition<S>> {
+        if self.transitions.is_empty() {
+            return Vec::new();
+        }
+        (0..count)
+            .map(|_| self.transitions[rng.random_range(0..self.transitions.len())].clone())
+            .collect()
+    }
+}
+
+// A buffer shared by all copies of a learner.
+pub type SharedExperience<S> = Rc<RefCell<ExperienceBuffer<S>>>;
@@ -0,0 +1,130 @@
+// How far an agent is from optimal play, measured by how well the best
+// response against it does. An agent's policy is recorded from the games it
+// played as its share of attacks in every pair of both players' hit points,
+// or given analytically as a function of them. Against a fixed policy the
+// duel is a Markov decision process, see `Mdp::duel_against`, whose optimal
+// value from full hit points is the best response's chance of winning.
+//
+// The exploitability is this chance minus the value of the game, which is
+// one half for damage matrices favouring neither player: an agent playing an
+// equilibrium cannot be beaten more often than it beats its opponents, so
+// its exploitability is 0, and an agent beaten every time has 0.5. Stamina,
+// noise and other rules outside the damage matrix are ignored, as is any
+// dependence of the agent's play on the history rather than the hit points.
+
+use std::collections::HashMap;
+
+use crate::duel::{Action, DamageMatrix, Duel};
+use crate::game::{GameOutcome, GameState};
+use crate::observer::GameObserver;
+use crate::solvers::{Mdp, MdpSolution, duel_state, value_iteration};
+
+// The value of the duel for damage matrices favouring neither player.
+pub const GAME_VALUE: f64 = 0.5;
+// Best responses solve the duel's process to this residual.
+const TOLERANCE: f64 = 1e-9;
+const MAX_ITERATIONS: usize = 10_000;
+
+// An agent's attacks and turns in every pair of its own and the opposing hit
+// points it played a turn in.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecordedPolicy {
+    pub counts: HashMap<(i64, i64), [u64; 2]>,
+    pub attacks: u64,
+    pub turns: u64,
+}
+
+impl RecordedPolicy {
+    pub fn record(&mut self, own: i64, opposing: i64, action: &Action) {
+        let attacked = u64::from(*action == Action::ATTACK);
+        let counts = self.counts.entry((own, opposing)).or_default();
+        counts[0] += attacked;
+        counts[1] += 1;
+        self.attacks += attacked;
+        self.turns += 1;
+    }
+
+    // The share of attacks in the given hit points; the share over all turns
+    // in hit points never played, and one half without any turns.
+    pub fn attack_probability(&self, own: i64, opposing: i64) -> f64 {
+        match self.counts.get(&(own, opposing)) {
+            Some([attacks, turns]) => *attacks as f64 / *turns as f64,
+            None if self.turns > 0 => self.attacks as f64 / self.turns as f64,
+            None => 0.5,
+        }
+    }
+}
+
+// The best response against an opponent attacking with probability
+// `attack(own, opposing)` when it has `own` and the responder `opposing`
+// hit points.
+pub fn best_response(
+    max_hit_points: i64,
+    damage: &DamageMatrix,
+    attack: impl Fn(i64, i64) -> f64,
+) -> MdpSolution {
+    let mdp = Mdp::duel_against(max_hit_points, damage, |own, opposing| {
+        attack(opposing, own)
+    });
+    value_iteration(&mdp, TOLERANCE, MAX_ITERATIONS)
+}
+
+// The best response's chance of winning from full hit points against the
+// given opponent, see `best_response`.
+pub fn best_response_value(
+    max_hit_points: i64,
+    damage: &DamageMatrix,
+    attack: impl Fn(i64, i64) -> f64,
+) -> f64 {
+    let solution = best_response(max_hit_points, damage, attack);
+    solution.values[duel_state(max_hit_points, max_hit_points, max_hit_points)]
+}
+
+// Records every agent's policy over the games it played.
+pub struct PolicyObserver {
+    current_pairing: Option<(usize, usize)>,
+    pub policies: Vec<RecordedPolicy>,
+}
+
+impl PolicyObserver {
+    pub fn new(num_agents: usize) -> Self {
+        Self {
+            current_pairing: None,
+            policies: vec![RecordedPolicy::default(); num_agents],
+        }
+    }
+}
+
+impl GameObserver<Duel> for PolicyObserver {
+    fn on_game_start(&mut self, player_one: usize, player_two: usize) {
+        self.current_pairing = Some((player_one, player_two));
+    }
+
+    fn on_game_end(&mut self, state: &GameState<Duel>, _outcome: &GameOutcome) {
+        let Some((player_one, player_two)) = self.current_pairing else {
+            return;
+        };
+        let history = &state.history;
+        // Hit points before every turn: players start with their maximum,
+        // and every later turn starts from the states after the one before.
+        let mut one = state.player_one_state.max_hit_points;
+        let mut two = state.player_two_state.max_hit_points;
+        for (turn, (one_action, two_action)) in history
+            .player_one_actions
+            .iter()
+            .zip(&history.player_two_actions)
+            .enumerate()
+        {
+            self.policies[player_one].record(one, two, one_action);
+            self.policies[player_two].record(two, one, two_action);
+            let (Some(one_state), Some(two_state)) = (
+                history.player_one_states.get(turn),
+                history.player_two_states.get(turn),
+            ) else {
+                break;
+            };
+            one = one_state.current_hit_points;
+            two = two_state.current_hit_points;
+        }
+    }
+}
@@ -0,0 +1,482 @@
+// Two-player games in extensive form: trees of decision nodes, at which a
+// player acts, chance nodes, at which nature draws an outcome, and terminal
+// nodes paying both players. Decision nodes the acting player cannot tell
+// apart share an information set, and the player has to act alike in all of
+// them. Trees are solved by counterfactual regret minimization, which
+// converges to an equilibrium in zero-sum games of perfect recall.
+//
+// Trees are written one node per line, the root first, as
+//   <node> chance (<outcome> <probability> <child>)...
+//   <node> player <1|2> <information set> (<action> <child>)...
+//   <node> terminal <payoff one> <payoff two>
+// where probabilities are decimals or fractions like `1/3`, and `#` starts
+// a comment. Matching pennies, in which player two does not see the coin of
+// player one, reads
+//   root   player 1 coin  heads h  tails t
+//   h      player 2 guess heads hh tails ht
+//   t      player 2 guess heads th tails tt
+//   hh     terminal 1 -1
+//   ...
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::cfr::regret_matching;
+use crate::config::ConfigError;
+
+const PROBABILITY_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub label: String,
+    pub probability: f64,
+    pub child: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Chance {
+        outcomes: Vec<Outcome>,
+    },
+    // The actions of the node are those of its information set, leading to
+    // the children in the same order.
+    Decision {
+        player: usize,
+        infoset: usize,
+        children: Vec<usize>,
+    },
+    Terminal {
+        payoffs: [f64; 2],
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InformationSet {
+    pub name: String,
+    pub player: usize,
+    pub actions: Vec<String>,
+}
+
+// A behavior strategy for both players: the probabilities of the actions of
+// every information set.
+pub type TreeStrategy = Vec<Vec<f64>>;
+
+pub struct TreeSolution {
+    // The average strategy of all iterations.
+    pub strategy: TreeStrategy,
+    // Both players' expected payoffs under it.
+    pub value: [f64; 2],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameTree {
+    // Node names in the order of the text, the root first.
+    pub names: Vec<String>,
+    pub nodes: Vec<Node>,
+    pub infosets: Vec<InformationSet>,
+}
+
+// Reads `1/6` as well as `0.25`.
+fn parse_probability(token: &str) -> Option<f64> {
+    match token.split_once('/') {
+        Some((numerator, denominator)) => {
+            Some(numerator.parse::<f64>().ok()? / denominator.parse::<f64>().ok()?)
+        }
+        None => token.parse().ok(),
+    }
+}
+
+impl GameTree {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+        Self::parse(&contents).map_err(ConfigError::Invalid)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let lines: Vec<(usize, Vec<&str>)> = text
+            .lines()
+            .enumerate()
+            .map(|(number, line)| {
+                let line = line.split('#').next().unwrap_or_default();
+                (number + 1, line.split_whitespace().collect::<Vec<_>>())
+            })
+            .filter(|(_, tokens)| !tokens.is_empty())
+            .collect();
+        let mut indices = HashMap::new();
+        for (index, (number, tokens)) in lines.iter().enumerate() {
+            if indices.insert(tokens[0], index).is_some() {
+                return Err(format!(
+                    "line {}: node '{}' is defined twice",
+                    number, tokens[0]
+                ));
+            }
+        }
+        let child = |number: usize, name: &str| -> Result<usize, String> {
+            indices
+                .get(name)
+                .copied()
+                .ok_or_else(|| format!("line {}: unknown node '{}'", number, name))
+        };
+
+        let mut tree = Self {
+            names: lines
+                .iter()
+                .map(|(_, tokens)| tokens[0].to_string())
+                .collect(),
+            nodes: Vec::with_capacity(lines.len()),
+            infosets: Vec::new(),
+        };
+        let mut infoset_indices: HashMap<&str, usize> = HashMap::new();
+        for (number, tokens) in &lines {
+            let number = *number;
+            let node = match tokens.get(1).copied() {
+                Some("chance") => {
+                    let arguments = &tokens[2..];
+                    if arguments.is_empty() || arguments.len() % 3 != 0 {
+                        return Err(format!(
+                            "line {}: chance nodes list outcomes as <outcome> <probability> <child>",
+                            number
+                        ));
+                    }
+                    let outcomes = arguments
+                        .chunks(3)
+                        .map(|outcome| {
+                            Ok(Outcome {
+                                label: outcome[0].to_string(),
+                                probability: parse_probability(outcome[1]).ok_or_else(|| {
+                                    format!("line {}: invalid probability '{}'", number, outcome[1])
+                                })?,
+                                child: child(number, outcome[2])?,
+                            })
+                        })
+                        .collect::<Result<Vec<_>, String>>()?;
+                    Node::Chance { outcomes }
+                }
+                Some("player") => {
+                    let player: usize = match tokens.get(2).map(|token| token.parse()) {
+                        Some(Ok(player @ (1 | 2))) => player,
+                        _ => return Err(format!("line {}: the player has to be 1 or 2", number)),
+                    };
+                    let arguments = tokens.get(4..).unwrap_or_default();
+                    if arguments.is_empty() || arguments.len() % 2 != 0 {
+                        return Err(format!(
+                            "line {}: decision nodes name an information set and list actions as <action> <child>",
+                            number
+                        ));
+                    }
+                    let name = tokens[3];
+                    let actions: Vec<String> = arguments
+                        .chunks(2)
+                        .map(|pair| pair[0].to_string())
+                        .collect();
+                    let infoset = match infoset_indices.get(name) {
+                        Some(&infoset) => {
+                            let existing = &tree.infosets[infoset];
+                            if existing.player != player || existing.actions != actions {
+                                return Err(format!(
+                                    "line {}: the nodes of information set '{}' differ in their player or actions",
+                                    number, name
+                                ));
+                            }
+                            infoset
+                        }
+                        None => {
+                            infoset_indices.insert(name, tree.infosets.len());
+                            tree.infosets.push(InformationSet {
+                                name: name.to_string(),
+                                player,
+                                actions,
+                            });
+                            tree.infosets.len() - 1
+                        }
+                    };
+                    Node::Decision {
+                        player,
+                        infoset,
+                        children: arguments
+                            .chunks(2)
+                            .map(|pair| child(number, pair[1]))
+                            .collect::<Result<Vec<_>, String>>()?,
+                    }
+                }
+                Some("terminal") => {
+                    let payoffs: Vec<f64> = tokens[2..]
+                        .iter()
+                        .map(|token| token.parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| format!("line {}: invalid payoff", number))?;
+                    match payoffs[..] {
+                        [one, two] => Node::Terminal {
+                            payoffs: [one, two],
+                        },
+                        _ => {
+                            return Err(format!(
+                                "line {}: terminal nodes pay both players",
+                                number
+                            ));
+                        }
+                    }
+                }
+                _ => {
+                    return Err(format!(
+                        "line {}: nodes are chance, player or terminal nodes",
+                        number
+                    ));
+                }
+            };
+            tree.nodes.push(node);
+        }
+        tree.validate()?;
+        Ok(tree)
+    }
+
+    // Checks that the nodes form a tree below the root and that the outcomes
+    // of every chance node are a distribution.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.nodes.is_empty() {
+            return Err(String::from("a game tree needs a root"));
+        }
+        let mut parents = vec![0usize; self.nodes.len()];
+        for (index, node) in self.nodes.iter().enumerate() {
+            if let Node::Chance { outcomes } = node {
+                let total: f64 = outcomes.iter().map(|o| o.probability).sum();
+                if outcomes.iter().any(|o| o.probability < 0.0)
+                    || (total - 1.0).abs() > PROBABILITY_TOLERANCE
+                {
+                    return Err(format!(
+                        "the outcomes of '{}' have to be probabilities summing to 1, got {}",
+                        self.names[index], total
+                    ));
+                }
+            }
+            for child in self.children(index) {
+                parents[child] += 1;
+            }
+        }
+        // With the root unreached and every other node reached once, the
+        // nodes form a tree.
+        for (index, count) in parents.iter().enumerate() {
+            if *count != (index != 0) as usize {
+                return Err(format!(
+                    "node '{}' is reached from {} nodes, the root from none and every other node from one",
+                    self.names[index], count
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Kuhn poker: both players ante 1 and are dealt one of a jack, queen and
+    // king. Player one checks or bets 1; facing a bet, a player folds or
+    // calls, after a check player two checks or bets. Players only see their
+    // own card, and the higher card wins the pot at the showdown. Its value
+    // for player one is -1/18.
+    pub fn kuhn_poker() -> Self {
+        Self::parse(&kuhn_poker_text()).expect("the tree is valid")
+    }
+
+    fn children(&self, node: usize) -> Vec<usize> {
+        match &self.nodes[node] {
+            Node::Chance { outcomes } => outcomes.iter().map(|o| o.child).collect(),
+            Node::Decision { children, .. } => children.clone(),
+            Node::Terminal { .. } => Vec::new(),
+        }
+    }
+
+    pub fn num_infosets(&self) -> usize {
+        self.infosets.len()
+    }
+
+    // Every action of every information set equally likely.
+    pub fn uniform_strategy(&self) -> TreeStrategy {
+        self.infosets
+            .iter()
+            .map(|infoset| vec![1.0 / infoset.actions.len() as f64; infoset.actions.len()])
+            .collect()
+    }
+
+    // Both players' expected payoffs when playing `strategy`.
+    pub fn expected_payoffs(&self, strategy: &TreeStrategy) -> [f64; 2] {
+        self.expected_payoffs_from(0, strategy)
+    }
+
+    fn expected_payoffs_from(&self, node: usize, strategy: &TreeStrategy) -> [f64; 2] {
+        let weighted = |children: &mut dyn Iterator<Item = (f64, usize)>| {
+            children.fold([0.0, 0.0], |[one, two], (probability, child)| {
+                let [child_one, child_two] = self.expected_payoffs_from(child, strategy);
+                [one + probability * child_one, two + probability * child_two]
+            })
+        };
+        match &self.nodes[node] {
+            Node::Terminal { payoffs } => *payoffs,
+            Node::Chance { outcomes } => {
+                weighted(&mut outcomes.iter().map(|o| (o.probability, o.child)))
+            }
+            Node::Decision {
+                infoset, children, ..
+            } => weighted(
+                &mut strategy[*infoset]
+                    .iter()
+                    .copied()
+                    .zip(children.iter().copied()),
+            ),
+        }
+    }
+
+    // Solves the tree by `iterations` iterations of counterfactual regret
+    // minimization.
+    pub fn solve(&self, iterations: usize) -> TreeSolution {
+        let mut solver = Solver {
+            tree: self,
+            regrets: self
+                .infosets
+                .iter()
+                .map(|i| vec![0.0; i.actions.len()])
+                .collect(),
+            strategy_sums: self
+                .infosets
+                .iter()
+                .map(|i| vec![0.0; i.actions.len()])
+                .collect(),
+        };
+        for _ in 0..iterations {
+            solver.iterate(0, [1.0, 1.0, 1.0]);
+        }
+        let strategy: TreeStrategy = solver
+            .strategy_sums
+            .iter()
+            .map(|sums| {
+                if sums.iter().sum::<f64>() > 0.0 {
+                    let total: f64 = sums.iter().sum();
+                    sums.iter().map(|s| s / total).collect()
+                } else {
+                    vec![1.0 / sums.len() as f64; sums.len()]
+                }
+            })
+            .collect();
+        let value = self.expected_payoffs(&strategy);
+        TreeSolution { strategy, value }
+    }
+
+    // The strategy drawn as one line per information set.
+    pub fn render(&self, strategy: &TreeStrategy) -> String {
+        let lines: Vec<String> = self
+            .infosets
+            .iter()
+            .zip(strategy)
+            .map(|(infoset, probabilities)| {
+                let actions: Vec<String> = infoset
+                    .actions
+                    .iter()
+                    .zip(probabilities)
+                    .map(|(action, probability)| format!("{} {:.3}", action, probability))
+                    .collect();
+                format!(
+                    "player {} at {}: {}",
+                    infoset.player,
+                    infoset.name,
+                    actions.join(", ")
+                )
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+struct Solver<'a> {
+    tree: &'a GameTree,
+    regrets: Vec<Vec<f64>>,
+    strategy_sums: Vec<Vec<f64>>,
+}
+
+impl Solver<'_> {
+    // Walks the tree below `node`, reached with probability `reach` by
+    // player one, player two and chance, and returns both players' payoffs
+    // of the current strategy.
+    fn iterate(&mut self, node: usize, reach: [f64; 3]) -> [f64; 2] {
+        let tree = self.tree;
+        match &tree.nodes[node] {
+            Node::Terminal { payoffs } => *payoffs,
+            Node::Chance { outcomes } => outcomes.iter().fold([0.0, 0.0], |value, outcome| {
+                let [one, two] = self.iterate(
+                    outcome.child,
+                    [reach[0], reach[1], reach[2] * outcome.probability],
+                );
+                [
+                    value[0] + outcome.probability * one,
+                    value[1] + outcome.probability * two,
+                ]
+            }),
+            Node::Decision {
+                player,
+                infoset,
+                children,
+            } => {
+                let (own, infoset) = (*player - 1, *infoset);
+                let strategy = regret_matching(&self.regrets[infoset]);
+                let mut value = [0.0, 0.0];
+                let mut action_values = Vec::with_capacity(children.len());
+                for (probability, child) in strategy.iter().zip(children) {
+                    let mut child_reach = reach;
+                    child_reach[own] *= probability;
+                    let child_value = self.iterate(*child, child_reach);
+                    value[0] += probability * child_value[0];
+                    value[1] += probability * child_value[1];
+                    action_values.push(child_value[own]);
+                }
+                // The probability that the others play into the node.
+                let counterfactual = reach[1 - own] * reach[2];
+                for (action, action_value) in action_values.iter().enumerate() {
+                    self.regrets[infoset][action] += counterfactual * (action_value - value[own]);
+                    self.strategy_sums[infoset][action] += reach[own] * strategy[action];
+                }
+                value
+            }
+        }
+    }
+}
+
+// Kuhn poker in the text format, nodes named by the cards dealt and the
+// actions so far, `p` for a check or fold and `b` for a bet or call.
+pub fn kuhn_poker_text() -> String {
+    let cards = ['J', 'Q', 'K'];
+    let deals: Vec<(char, char)> = cards
+        .iter()
+        .flat_map(|one| cards.iter().map(move |two| (*one, *two)))
+        .filter(|(one, two)| one != two)
+        .collect();
+    let mut lines = vec![String::from("# Kuhn poker")];
+    let outcomes: Vec<String> = deals
+        .iter()
+        .map(|(one, two)| format!("{one}{two} 1/{} {one}{two}", deals.len()))
+        .collect();
+    lines.push(format!("deal chance {}", outcomes.join(" ")));
+    let rank = |card: char| cards.iter().position(|c| *c == card);
+    for (one, two) in deals {
+        let deal = format!("{one}{two}");
+        // Player one's winnings at a showdown for a pot of `stake` each.
+        let showdown = |stake: i32| {
+            if rank(one) > rank(two) { stake } else { -stake }
+        };
+        let terminal = |history: &str, winnings: i32| {
+            format!("{deal}{history} terminal {} {}", winnings, -winnings)
+        };
+        lines.push(format!("{deal} player 1 {one} pass {deal}p bet {deal}b"));
+        lines.push(format!(
+            "{deal}p player 2 {two}p pass {deal}pp bet {deal}pb"
+        ));
+        lines.push(format!(
+            "{deal}pb player 1 {one}pb pass {deal}pbp bet {deal}pbb"
+        ));
+        lines.push(format!(
+            "{deal}b player 2 {two}b pass {deal}bp bet {deal}bb"
+        ));
+        lines.push(terminal("pp", showdown(1)));
+        lines.push(terminal("pbp", -1));
+        lines.push(terminal("pbb", showdown(2)));
+        lines.push(terminal("bp", 1));
+        lines.push(terminal("bb", showdown(2)));
+    }
+    lines.join("\n") + "\n"
+}
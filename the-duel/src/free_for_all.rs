@@ -0,0 +1,557 @@
+// The free-for-all: the duel among more than two players at a table. Every
+// turn, each standing player picks an action and a target:
+//
+//   - Two players targeting each other exchange their actions by the damage
+//     matrix, exactly as in the duel.
+//   - An attack on a player facing someone else catches it off guard and
+//     deals `ambush_damage`, at no cost to the attacker. Finching at such a
+//     player does nothing.
+//
+// Players down to zero hit points leave the table, and the last player
+// standing wins. Seated in teams, players never target their teammates, and
+// the last team standing wins. The free-for-all is played with the hit points and damage
+// matrix of the duel's rules; stamina, stochastic damage and the other rule
+// extensions do not apply.
+//
+// The agents are the duel's: each one picks its target by a targeting rule
+// and then plays the turn as if dueling its target alone, seeing the turns
+// both played so far as the history of that duel.
+
+use rand::Rng;
+use rand::seq::{IndexedRandom, index};
+use serde::{Deserialize, Serialize};
+
+use crate::agents::{GameAgent, SharedRng};
+use crate::duel::{Action, Duel, PlayerState};
+use crate::game::{DrawReason, GameOutcome, GameSettings, GameState};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TargetedAction {
+    pub action: Action,
+    // Seat of the targeted player, counting from 0.
+    pub target: usize,
+}
+
+// One turn at the table: every seat's action, none for players out of the
+// game, and every player's state after the turn.
+#[derive(Clone)]
+pub struct TableTurn {
+    pub actions: Vec<Option<TargetedAction>>,
+    pub players: Vec<PlayerState>,
+}
+
+#[derive(Clone)]
+pub struct TableState {
+    pub players: Vec<PlayerState>,
+    // The team of every seat, counting from 0. Without teams, every player
+    // is a team of its own, numbered by its seat.
+    pub teams: Vec<usize>,
+    pub history: Vec<TableTurn>,
+}
+
+impl TableState {
+    pub fn new(players: Vec<PlayerState>) -> Self {
+        Self {
+            teams: (0..players.len()).collect(),
+            players,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn with_teams(mut self, teams: Vec<usize>) -> Self {
+        self.teams = teams;
+        self
+    }
+
+    pub fn is_team_game(&self) -> bool {
+        self.teams
+            .iter()
+            .enumerate()
+            .any(|(seat, team)| seat != *team)
+    }
+
+    pub fn turns(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn is_standing(&self, seat: usize) -> bool {
+        self.players[seat].current_hit_points > 0
+    }
+
+    // The seats of all players still in the game.
+    pub fn standing(&self) -> Vec<usize> {
+        (0..self.players.len())
+            .filter(|seat| self.is_standing(*seat))
+            .collect()
+    }
+
+    // The standing players of other teams than `seat`'s.
+    pub fn opponents(&self, seat: usize) -> Vec<usize> {
+        self.standing()
+            .into_iter()
+            .filter(|other| self.teams[*other] != self.teams[seat])
+            .collect()
+    }
+
+    // The standing players of `seat`'s team, other than `seat`.
+    pub fn teammates(&self, seat: usize) -> Vec<usize> {
+        self.standing()
+            .into_iter()
+            .filter(|other| *other != seat && self.teams[*other] == self.teams[seat])
+            .collect()
+    }
+
+    // The target `seat` faced in the last turn, if it played.
+    pub fn last_target(&self, seat: usize) -> Option<usize> {
+        self.history
+            .last()
+            .and_then(|turn| turn.actions[seat].as_ref())
+            .map(|action| action.target)
+    }
+
+    // The opponent that attacked `seat` in the last turn, if any did.
+    pub fn last_attacker(&self, seat: usize) -> Option<usize> {
+        let turn = self.history.last()?;
+        self.opponents(seat).into_iter().find(|other| {
+            turn.actions[*other]
+                .as_ref()
+                .is_some_and(|action| action.target == seat && action.action == Action::ATTACK)
+        })
+    }
+
+    // The game between `seat` and `target` as a duel, with `seat` as player
+    // one: the turns both played, and their states as observed under
+    // `settings`. Turns in which either of them was out are left out.
+    pub fn pairing(&self, seat: usize, target: usize, settings: &GameSettings) -> GameState<Duel> {
+        let mut state = GameState::new(self.players[seat].clone(), self.players[target].clone());
+        for turn in &self.history {
+            if let (Some(own), Some(opposing)) = (&turn.actions[seat], &turn.actions[target]) {
+                state.history.record(
+                    own.action.clone(),
+                    opposing.action.clone(),
+                    turn.players[seat].clone(),
+                    turn.players[target].clone(),
+                );
+                // Observed as they were after the turn
+                state.player_one_state = turn.players[seat].clone();
+                state.player_two_state = turn.players[target].clone();
+                state.history.observe_last_states(
+                    settings.observe_opponent(&state, 1),
+                    settings.observe_opponent(&state, 2),
+                );
+                state.player_one_action = Some(own.action.clone());
+                state.player_two_action = Some(opposing.action.clone());
+            }
+        }
+        state.player_one_state = self.players[seat].clone();
+        state.player_two_state = self.players[target].clone();
+        state
+    }
+}
+
+#[derive(Clone)]
+pub struct FreeForAll {
+    pub rules: Duel,
+    pub ambush_damage: i64,
+}
+
+impl FreeForAll {
+    pub fn new(rules: Duel) -> Self {
+        Self {
+            rules,
+            ambush_damage: 1,
+        }
+    }
+
+    pub fn with_ambush_damage(mut self, ambush_damage: i64) -> Self {
+        self.ambush_damage = ambush_damage;
+        self
+    }
+
+    pub fn initial_state(&self, players: usize) -> TableState {
+        TableState::new(vec![PlayerState::new(self.rules.max_hit_points); players])
+    }
+
+    // Plays one turn. `actions` holds an action for every seat; those of
+    // players out of the game are ignored. Targeting oneself, a teammate or
+    // a player out of the game wastes the turn.
+    pub fn advance(&self, state: &mut TableState, actions: &[TargetedAction]) {
+        let actions: Vec<Option<TargetedAction>> = (0..state.players.len())
+            .map(|seat| {
+                let action = &actions[seat];
+                let valid = action.target < state.players.len()
+                    && state.teams[action.target] != state.teams[seat]
+                    && state.is_standing(action.target);
+                (state.is_standing(seat) && valid).then(|| action.clone())
+            })
+            .collect();
+        let mut damage = vec![0; state.players.len()];
+        for (seat, action) in actions.iter().enumerate() {
+            let Some(action) = action else {
+                continue;
+            };
+            match &actions[action.target] {
+                // Face-offs are resolved once, from the lower seat
+                Some(opposing) if opposing.target == seat => {
+                    if seat < action.target {
+                        let [own, other] =
+                            self.rules.damage.damage(&action.action, &opposing.action);
+                        damage[seat] += own;
+                        damage[action.target] += other;
+                    }
+                }
+                _ => {
+                    if action.action == Action::ATTACK {
+                        damage[action.target] += self.ambush_damage;
+                    }
+                }
+            }
+        }
+        for (player, damage) in state.players.iter_mut().zip(damage) {
+            if player.current_hit_points > 0 {
+                player.current_hit_points -= damage;
+            }
+        }
+        state.history.push(TableTurn {
+            actions,
+            players: state.players.clone(),
+        });
+    }
+
+    // The last player standing wins, or in teams the last team standing
+    // as WIN of its number counting from 1. A table cleared in one turn ends
+    // in a tie.
+    pub fn check_end_condition(&self, state: &TableState) -> GameOutcome {
+        let standing = state.standing();
+        let Some(first) = standing.first() else {
+            return GameOutcome::TIE;
+        };
+        let team = state.teams[*first];
+        if standing.iter().any(|seat| state.teams[*seat] != team) {
+            GameOutcome::CONTINUE
+        } else if state.is_team_game() {
+            GameOutcome::WIN(team as u64 + 1)
+        } else {
+            GameOutcome::SURVIVOR(*first as u64 + 1)
+        }
+    }
+}
+
+// How an agent at the table picks whom to face.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Targeting {
+    // The standing opponent with the fewest hit points.
+    Weakest,
+    // The standing opponent with the most hit points.
+    Strongest,
+    // Whoever attacked the player last turn, the weakest opponent otherwise.
+    #[default]
+    Retaliate,
+    // A uniformly random standing opponent.
+    Random,
+    // The teammate's target, so that the team focuses on one opponent, the
+    // weakest opponent without a teammate's target.
+    Focus,
+    // Whoever attacked the weakest teammate last turn if it is weaker than
+    // the player, as in retaliate otherwise.
+    Protect,
+}
+
+impl Targeting {
+    // The target of `seat`, which has to have a standing opponent.
+    // `signal` is the target a teammate announced, if any.
+    pub fn target<R: Rng>(
+        &self,
+        state: &TableState,
+        seat: usize,
+        signal: Option<usize>,
+        rng: &mut R,
+    ) -> usize {
+        let opponents = state.opponents(seat);
+        let hit_points = |other: &usize| state.players[*other].current_hit_points;
+        let weakest = || {
+            *opponents
+                .iter()
+                .min_by_key(|other| hit_points(other))
+                .unwrap()
+        };
+        match self {
+            Targeting::Weakest => weakest(),
+            Targeting::Strongest => *opponents
+                .iter()
+                .max_by_key(|other| hit_points(other))
+                .unwrap(),
+            Targeting::Retaliate => state.last_attacker(seat).unwrap_or_else(weakest),
+            Targeting::Random => *opponents.choose(rng).unwrap(),
+            Targeting::Focus => signal
+                .filter(|target| opponents.contains(target))
+                .unwrap_or_else(weakest),
+            Targeting::Protect => state
+                .teammates(seat)
+                .into_iter()
+                .min_by_key(|mate| hit_points(mate))
+                .filter(|mate| hit_points(mate) < state.players[seat].current_hit_points)
+                .and_then(|mate| state.last_attacker(mate))
+                .or_else(|| state.last_attacker(seat))
+                .unwrap_or_else(weakest),
+        }
+    }
+}
+
+// A game at one table, the agents seated in order. Teammates learn each
+// other's target of the last turn; with signaling, a player also learns the
+// targets its teammates seated before it picked in the same turn.
+pub struct Table {
+    pub rules: FreeForAll,
+    pub settings: GameSettings,
+    pub agents: Vec<Box<dyn GameAgent>>,
+    pub targeting: Targeting,
+    // The team of every seat, every player on its own if missing.
+    pub teams: Option<Vec<usize>>,
+    pub signaling: bool,
+}
+
+impl Table {
+    pub fn new(rules: FreeForAll, agents: Vec<Box<dyn GameAgent>>) -> Self {
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            agents,
+            targeting: Targeting::default(),
+            teams: None,
+            signaling: false,
+        }
+    }
+
+    pub fn with_teams(mut self, teams: Vec<usize>) -> Self {
+        self.teams = Some(teams);
+        self
+    }
+
+    pub fn with_signaling(mut self, signaling: bool) -> Self {
+        self.signaling = signaling;
+        self
+    }
+
+    pub fn initial_state(&self) -> TableState {
+        let state = self.rules.initial_state(self.agents.len());
+        match &self.teams {
+            Some(teams) => state.with_teams(teams.clone()),
+            None => state,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_targeting(mut self, targeting: Targeting) -> Self {
+        self.targeting = targeting;
+        self
+    }
+
+    fn step(&mut self, state: &mut TableState, rng: &SharedRng) {
+        let mut targets: Vec<Option<usize>> = vec![None; self.agents.len()];
+        for seat in state.standing() {
+            let signal = state.teammates(seat).into_iter().find_map(|mate| {
+                if self.signaling && targets[mate].is_some() {
+                    targets[mate]
+                } else {
+                    state.last_target(mate)
+                }
+            });
+            targets[seat] =
+                Some(
+                    self.targeting
+                        .target(state, seat, signal, &mut *rng.borrow_mut()),
+                );
+        }
+        let actions: Vec<TargetedAction> = targets
+            .into_iter()
+            .enumerate()
+            .map(|(seat, target)| {
+                let Some(target) = target else {
+                    return TargetedAction {
+                        action: Action::FINCH,
+                        target: seat,
+                    };
+                };
+                let pairing = state.pairing(seat, target, &self.settings);
+                let action = self.agents[seat].decide_action(
+                    &pairing.player_one_state,
+                    &pairing.player_two_action,
+                    &self.settings.observe_opponent(&pairing, 1),
+                    &pairing.history.view(1),
+                );
+                TargetedAction { action, target }
+            })
+            .collect();
+        self.rules.advance(state, &actions);
+    }
+
+    // Steps the game from a fresh state until it is decided.
+    pub fn play(&mut self, rng: &SharedRng) -> GameOutcome {
+        let mut state = self.initial_state();
+        self.play_from(&mut state, rng)
+    }
+
+    // Steps the game from the given state until it is decided.
+    pub fn play_from(&mut self, state: &mut TableState, rng: &SharedRng) -> GameOutcome {
+        for agent in &mut self.agents {
+            agent.set_mode(self.settings.mode);
+        }
+        loop {
+            self.step(state, rng);
+            match self.rules.check_end_condition(state) {
+                GameOutcome::CONTINUE => match self.settings.max_turns {
+                    Some(limit) if state.turns() >= limit => {
+                        return GameOutcome::DRAW(DrawReason::TurnLimit(limit));
+                    }
+                    _ => {}
+                },
+                outcome => return outcome,
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct FreeForAllConfig {
+    // Players per table.
+    #[serde(default = "default_table_size")]
+    pub table_size: usize,
+    // Tables sampled from the roster.
+    #[serde(default = "default_tables")]
+    pub tables: u64,
+    #[serde(default)]
+    pub targeting: Targeting,
+    // Damage of an attack on a player facing someone else.
+    #[serde(default = "default_ambush_damage")]
+    pub ambush_damage: i64,
+}
+
+fn default_table_size() -> usize {
+    3
+}
+
+fn default_tables() -> u64 {
+    100
+}
+
+fn default_ambush_damage() -> i64 {
+    1
+}
+
+impl FreeForAllConfig {
+    pub fn validate(&self, num_agents: usize) -> Result<(), String> {
+        if self.table_size < 2 || self.table_size > num_agents {
+            return Err(format!(
+                "free-for-all tables need between 2 and {} players, got {}",
+                num_agents, self.table_size
+            ));
+        }
+        Ok(())
+    }
+}
+
+// How often every agent of the roster was seated, outlasted its table, was
+// among the last players cleared in a tie, or was still standing at a draw.
+pub struct FreeForAllResults {
+    pub agent_names: Vec<String>,
+    pub table_size: usize,
+    pub seated: Vec<u64>,
+    pub wins: Vec<u64>,
+    pub ties: Vec<u64>,
+    pub draws: Vec<u64>,
+}
+
+impl FreeForAllResults {
+    pub fn win_rate(&self, agent: usize) -> f64 {
+        self.wins[agent] as f64 / self.seated[agent].max(1) as f64
+    }
+}
+
+// Seats of a table of `size` agents sampled uniformly from a roster of
+// `roster` agents, in a random order.
+pub fn sample_table(rng: &SharedRng, roster: usize, size: usize) -> Vec<usize> {
+    index::sample(&mut *rng.borrow_mut(), roster, size).into_vec()
+}
+
+// Plays tables of agents sampled uniformly from the roster, seated in a
+// random order.
+pub struct FreeForAllTournament {
+    pub rules: FreeForAll,
+    pub settings: GameSettings,
+    pub agents: Vec<Box<dyn GameAgent>>,
+    pub config: FreeForAllConfig,
+}
+
+impl FreeForAllTournament {
+    pub fn new(rules: Duel, agents: Vec<Box<dyn GameAgent>>, config: FreeForAllConfig) -> Self {
+        Self {
+            rules: FreeForAll::new(rules).with_ambush_damage(config.ambush_damage),
+            settings: GameSettings::default(),
+            agents,
+            config,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn run(&self, rng: &SharedRng) -> FreeForAllResults {
+        let num_agents = self.agents.len();
+        let mut results = FreeForAllResults {
+            agent_names: self
+                .agents
+                .iter()
+                .map(|agent| agent.strategy_name())
+                .collect(),
+            table_size: self.config.table_size,
+            seated: vec![0; num_agents],
+            wins: vec![0; num_agents],
+            ties: vec![0; num_agents],
+            draws: vec![0; num_agents],
+        };
+        for _ in 0..self.config.tables {
+            let seats = sample_table(rng, num_agents, self.config.table_size);
+            let mut table = Table::new(
+                self.rules.clone(),
+                seats
+                    .iter()
+                    .map(|agent| self.agents[*agent].copy_self_to_anom())
+                    .collect(),
+            )
+            .with_settings(self.settings.clone())
+            .with_targeting(self.config.targeting);
+            let mut state = self.rules.initial_state(seats.len());
+            let outcome = table.play_from(&mut state, rng);
+            for agent in &seats {
+                results.seated[*agent] += 1;
+            }
+            // The players standing before the last turn
+            let before = match state.turns() {
+                0 | 1 => self.rules.initial_state(seats.len()),
+                turns => TableState::new(state.history[turns - 2].players.clone()),
+            };
+            match outcome {
+                GameOutcome::SURVIVOR(seat) => results.wins[seats[seat as usize - 1]] += 1,
+                GameOutcome::TIE => {
+                    for seat in before.standing() {
+                        results.ties[seats[seat]] += 1;
+                    }
+                }
+                _ => {
+                    for seat in state.standing() {
+                        results.draws[seats[seat]] += 1;
+                    }
+                }
+            }
+        }
+        results
+    }
+}
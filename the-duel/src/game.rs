@@ -0,0 +1,449 @@
+use std::fmt;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agents::GameAgent;
+use crate::duel::Duel;
+use crate::history::History;
+use crate::observer::{GameObserver, NullObserver};
+use crate::rng_audit::{self, AuditedRng};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GameOutcome {
+    WIN(u64),
+    // The last player standing of a game with more than two players, by
+    // seat counting from 1.
+    SURVIVOR(u64),
+    TIE,
+    DRAW(DrawReason),
+    CONTINUE,
+    INTERRUPTED,
+}
+
+// Why the engine called a game before the rules decided it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DrawReason {
+    // The game was still undecided after this many turns.
+    TurnLimit(usize),
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DrawReason::TurnLimit(turns) => write!(f, "turn limit of {} reached", turns),
+        }
+    }
+}
+
+// Who acts in a turn. In a sequential turn only `player` (1 or 2) is asked
+// for its action, and its opponent plays `pass`, which the rules ignore.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TurnOrder<A> {
+    Simultaneous,
+    Sequential { player: u64, pass: A },
+}
+
+// A game in which both players pick their action at the same time, and the
+// rules resolve both actions together. Sequential games take turns through
+// `turn_order`.
+pub trait SimultaneousGame {
+    type PlayerState: Clone;
+    type Action: Clone;
+
+    fn initial_state(&self) -> GameState<Self>
+    where
+        Self: Sized;
+
+    fn resolve_actions(
+        &self,
+        state: &mut GameState<Self>,
+        player_one_action: Self::Action,
+        player_two_action: Self::Action,
+    ) where
+        Self: Sized;
+
+    fn check_end_condition(&self, state: &GameState<Self>) -> GameOutcome
+    where
+        Self: Sized;
+
+    // The action player `player` (1 or 2) actually plays when choosing
+    // `action`. Rules restricting the moves replace illegal choices here,
+    // rules with execution noise the actions gone wrong; agents see the
+    // executed action in the history.
+    fn enforce_legality(
+        &self,
+        _state: &GameState<Self>,
+        _player: u64,
+        action: Self::Action,
+    ) -> Self::Action
+    where
+        Self: Sized,
+    {
+        action
+    }
+
+    // Who acts in the next turn of `state`; both players, unless the game
+    // is sequential.
+    fn turn_order(&self, _state: &GameState<Self>) -> TurnOrder<Self::Action>
+    where
+        Self: Sized,
+    {
+        TurnOrder::Simultaneous
+    }
+
+    // What player `observer` (1 or 2) sees of its opponent's `action`. Rules
+    // with observation noise sometimes show a different action.
+    fn observe_action(&self, _observer: u64, action: &Self::Action) -> Self::Action
+    where
+        Self: Sized,
+    {
+        action.clone()
+    }
+
+    // Restarts the random number generators of the rules from `seed`, so
+    // that a single game can be played again in isolation.
+    fn reseed(&self, _seed: u64) {}
+
+    // The player state with its hit points rounded up to one of `buckets`
+    // equal shares of the maximum, for coarse observation of the opponent.
+    fn bucket_state(state: &Self::PlayerState, _buckets: usize) -> Self::PlayerState
+    where
+        Self: Sized,
+    {
+        state.clone()
+    }
+}
+
+pub struct GameState<G: SimultaneousGame = Duel> {
+    pub player_one_state: G::PlayerState,
+    pub player_two_state: G::PlayerState,
+    pub player_one_action: Option<G::Action>,
+    pub player_two_action: Option<G::Action>,
+    pub history: History<G>,
+}
+
+impl<G: SimultaneousGame> GameState<G> {
+    pub fn new(player_one_state: G::PlayerState, player_two_state: G::PlayerState) -> Self {
+        Self {
+            player_one_state,
+            player_two_state,
+            player_one_action: None,
+            player_two_action: None,
+            history: History::new(),
+        }
+    }
+
+    // Resolves both actions by `rules`, after replacing illegal ones and
+    // the action of a player whose turn it is not, and remembers them along
+    // with what each player observes under `settings`.
+    pub fn advance(
+        &mut self,
+        rules: &G,
+        settings: &GameSettings,
+        player_one_action: G::Action,
+        player_two_action: G::Action,
+    ) where
+        G: Sized,
+    {
+        let mut player_one_action = rules.enforce_legality(self, 1, player_one_action);
+        let mut player_two_action = rules.enforce_legality(self, 2, player_two_action);
+        match rules.turn_order(self) {
+            TurnOrder::Simultaneous => {}
+            TurnOrder::Sequential { player: 1, pass } => player_two_action = pass,
+            TurnOrder::Sequential { pass, .. } => player_one_action = pass,
+        }
+        rules.resolve_actions(self, player_one_action.clone(), player_two_action.clone());
+        self.history.record(
+            player_one_action.clone(),
+            player_two_action.clone(),
+            self.player_one_state.clone(),
+            self.player_two_state.clone(),
+        );
+        self.history.observe_last(
+            rules.observe_action(1, &player_two_action),
+            rules.observe_action(2, &player_one_action),
+        );
+        self.history.observe_last_states(
+            settings.observe_opponent(self, 1),
+            settings.observe_opponent(self, 2),
+        );
+        self.player_one_action = Some(player_one_action);
+        self.player_two_action = Some(player_two_action);
+    }
+
+    // The opponent's last action as player `player` (1 or 2) observed it.
+    pub fn observed_opposing_action(&self, player: u64) -> Option<G::Action> {
+        self.history.view(player).opposing_actions.last().cloned()
+    }
+}
+
+impl<G: SimultaneousGame> Clone for GameState<G> {
+    fn clone(&self) -> Self {
+        Self {
+            player_one_state: self.player_one_state.clone(),
+            player_two_state: self.player_two_state.clone(),
+            player_one_action: self.player_one_action.clone(),
+            player_two_action: self.player_two_action.clone(),
+            history: self.history.clone(),
+        }
+    }
+}
+
+// How much of the opposing player's state the agents get to see.
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Observability {
+    // Agents only see the opponent's actions.
+    #[default]
+    Hidden,
+    // Agents additionally see the opponent's current player state.
+    OpponentState,
+    // The opponent's current state with its hit points rounded up to one of
+    // the given number of buckets, e.g. 3 for high, medium and low.
+    Bucketed(usize),
+    // The opponent's state the given number of turns ago, and nothing as
+    // long as no more than that many turns have been played.
+    Delayed(usize),
+}
+
+impl Observability {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Observability::Bucketed(0) => Err(String::from(
+                "bucketed observability needs at least one bucket",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Stream `stream` of the generator seeded by `seed`. Agents draw from stream
+// 0, random rules each from a stream of their own, so that the numbers one
+// draws do not depend on how many the others did.
+pub fn seeded_stream(seed: u64, stream: u64) -> AuditedRng {
+    let mut rng = ChaCha12Rng::seed_from_u64(seed);
+    rng.set_stream(stream);
+    AuditedRng(rng)
+}
+
+// `hit_points` out of `max_hit_points` rounded up to the upper end of one of
+// `buckets` equal shares of the maximum. Hit points down to zero or below are
+// left as they are.
+pub fn bucket_hit_points(hit_points: i64, max_hit_points: i64, buckets: usize) -> i64 {
+    if hit_points <= 0 || max_hit_points <= 0 || buckets == 0 {
+        return hit_points;
+    }
+    let buckets = buckets as i64;
+    let bucket = (hit_points.min(max_hit_points) * buckets + max_hit_points - 1) / max_hit_points;
+    (bucket * max_hit_points + buckets - 1) / buckets
+}
+
+// Whether learning agents may learn from the games they play. Evaluations
+// never change what an agent has learned, so that their results do not
+// depend on the order of the games.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentMode {
+    Train,
+    #[default]
+    Eval,
+}
+
+// Engine settings that are independent of the rules of the game being played.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameSettings {
+    pub observability: Observability,
+    // Games still undecided after this many turns end in a draw.
+    pub max_turns: Option<usize>,
+    // Passed to both agents before every game.
+    pub mode: AgentMode,
+}
+
+impl GameSettings {
+    // What player `player` (1 or 2) gets to know about its opponent's state.
+    pub fn observe_opponent<G: SimultaneousGame>(
+        &self,
+        state: &GameState<G>,
+        player: u64,
+    ) -> Option<G::PlayerState> {
+        let opponent = if player == 1 { 2 } else { 1 };
+        let current = if player == 1 {
+            &state.player_two_state
+        } else {
+            &state.player_one_state
+        };
+        match self.observability {
+            Observability::Hidden => None,
+            Observability::OpponentState | Observability::Delayed(0) => Some(current.clone()),
+            Observability::Bucketed(buckets) => Some(G::bucket_state(current, buckets)),
+            Observability::Delayed(turns) => {
+                // Entry `t` of the history is the state after turn `t`
+                let states = state.history.view(opponent).own_states;
+                states
+                    .len()
+                    .checked_sub(turns + 1)
+                    .map(|turn| states[turn].clone())
+            }
+        }
+    }
+
+    // The rules' verdict, or a draw once the turn limit is exhausted.
+    pub fn check_end_condition<G: SimultaneousGame>(
+        &self,
+        rules: &G,
+        state: &GameState<G>,
+    ) -> GameOutcome {
+        match rules.check_end_condition(state) {
+            GameOutcome::CONTINUE => match self.max_turns {
+                Some(limit) if state.history.turns() >= limit => {
+                    GameOutcome::DRAW(DrawReason::TurnLimit(limit))
+                }
+                _ => GameOutcome::CONTINUE,
+            },
+            outcome => outcome,
+        }
+    }
+}
+
+pub struct Game<G: SimultaneousGame = Duel> {
+    pub rules: G,
+    pub settings: GameSettings,
+    pub player_one_agent: Box<dyn GameAgent<G>>,
+    pub player_two_agent: Box<dyn GameAgent<G>>,
+}
+
+impl<G: SimultaneousGame> Game<G> {
+    pub fn new(
+        rules: G,
+        player_one_agent: Box<dyn GameAgent<G>>,
+        player_two_agent: Box<dyn GameAgent<G>>,
+    ) -> Self {
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            player_one_agent,
+            player_two_agent,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn initial_state(&self) -> GameState<G> {
+        self.rules.initial_state()
+    }
+
+    pub fn step_game(&mut self, state: &mut GameState<G>) {
+        // get actions for current game state, only asking the player to move
+        // in sequential turns
+        let (player_one_action, player_two_action) = match self.rules.turn_order(state) {
+            TurnOrder::Simultaneous => (self.decide(state, 1), self.decide(state, 2)),
+            TurnOrder::Sequential { player: 1, pass } => (self.decide(state, 1), pass),
+            TurnOrder::Sequential { pass, .. } => (pass, self.decide(state, 2)),
+        };
+        let _rules = rng_audit::attribute(|| "rules".to_string(), Some(state.history.turns()));
+        state.advance(
+            &self.rules,
+            &self.settings,
+            player_one_action,
+            player_two_action,
+        );
+    }
+
+    // The action the agent of player `player` (1 or 2) decides on.
+    fn decide(&mut self, state: &GameState<G>, player: u64) -> G::Action {
+        let (agent, own_state) = if player == 1 {
+            (&mut self.player_one_agent, &state.player_one_state)
+        } else {
+            (&mut self.player_two_agent, &state.player_two_state)
+        };
+        let _agent = rng_audit::attribute(
+            || format!("player {} ({})", player, agent.strategy_name()),
+            Some(state.history.turns()),
+        );
+        agent.decide_action(
+            own_state,
+            &state.observed_opposing_action(player),
+            &self.settings.observe_opponent(state, player),
+            &state.history.view(player),
+        )
+    }
+
+    // Reports why the agents that moved in a turn of the given order chose
+    // their actions, as far as they explain themselves.
+    fn report_decisions(
+        &self,
+        turn_order: &TurnOrder<G::Action>,
+        observer: &mut dyn GameObserver<G>,
+    ) {
+        let players: &[u64] = match turn_order {
+            TurnOrder::Simultaneous => &[1, 2],
+            TurnOrder::Sequential { player: 1, .. } => &[1],
+            TurnOrder::Sequential { .. } => &[2],
+        };
+        for &player in players {
+            let agent = if player == 1 {
+                &self.player_one_agent
+            } else {
+                &self.player_two_agent
+            };
+            if let Some(explanation) = agent.explain_last_decision() {
+                observer.on_decision(player, &explanation);
+            }
+        }
+    }
+
+    // The rules' verdict, or a draw once the turn limit is exhausted.
+    pub fn check_end_condition(&self, state: &GameState<G>) -> GameOutcome {
+        self.settings.check_end_condition(&self.rules, state)
+    }
+
+    // Steps the game from a fresh state until it is decided.
+    pub fn play(&mut self) -> GameOutcome {
+        let mut state = self.initial_state();
+        self.play_from(&mut state)
+    }
+
+    // Steps the game from the given state until it is decided.
+    pub fn play_from(&mut self, state: &mut GameState<G>) -> GameOutcome {
+        self.play_observed(state, &mut NullObserver)
+    }
+
+    // Steps the game from the given state until it is decided, reporting
+    // every turn and the final outcome to `observer`.
+    pub fn play_observed(
+        &mut self,
+        state: &mut GameState<G>,
+        observer: &mut dyn GameObserver<G>,
+    ) -> GameOutcome {
+        self.player_one_agent.set_mode(self.settings.mode);
+        self.player_two_agent.set_mode(self.settings.mode);
+        let _game = tracing::debug_span!(
+            "game",
+            player_one = %self.player_one_agent.strategy_name(),
+            player_two = %self.player_two_agent.strategy_name(),
+        )
+        .entered();
+        loop {
+            let turn_order = self.rules.turn_order(state);
+            self.step_game(state);
+            self.report_decisions(&turn_order, observer);
+            tracing::trace!(turn = state.history.turns() - 1, "turn played");
+            observer.on_turn(state);
+            match self.check_end_condition(state) {
+                GameOutcome::CONTINUE => {}
+                outcome => {
+                    tracing::debug!(turns = state.history.turns(), ?outcome, "game over");
+                    observer.on_game_end(state, &outcome);
+                    return outcome;
+                }
+            }
+        }
+    }
+}
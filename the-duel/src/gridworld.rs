@@ -0,0 +1,387 @@
+// A single-agent gridworld, the classic example of a Markov decision
+// process. The agent moves north, east, south or west between open cells;
+// with probability `slip` it slips and moves to either side of the intended
+// direction instead, half of the time each. Moves into walls or off the grid
+// leave it where it is. Entering a terminal cell pays the cell's reward and
+// ends the episode, every other move pays the living reward.
+//
+// Layouts are written as rows of whitespace-separated cells, the top row
+// first: `.` is an open cell, `S` the open start cell, `#` a wall, and a
+// number a terminal cell paying that reward, as in
+//   . . . +1
+//   . # . -1
+//   S . . .
+// the world of Russell and Norvig, whose optimal policy is known.
+
+use std::fmt;
+
+use rand::Rng;
+
+use crate::agents::SharedRng;
+use crate::solvers::{Mdp, MdpPolicy, Transition};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    // The directions to either side, which the agent slips into.
+    pub fn perpendicular(&self) -> [Direction; 2] {
+        match self {
+            Direction::North | Direction::South => [Direction::East, Direction::West],
+            Direction::East | Direction::West => [Direction::North, Direction::South],
+        }
+    }
+
+    pub fn arrow(&self) -> char {
+        match self {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            Direction::West => '<',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cell {
+    Open,
+    Wall,
+    Terminal(f64),
+}
+
+// The direction to move in every cell, none in walls and terminal cells.
+pub type Policy = Vec<Option<Direction>>;
+
+// The world of Russell and Norvig's "Artificial Intelligence: A Modern
+// Approach", played with a slip of 0.2 and a living reward of -0.04.
+pub const RUSSELL_NORVIG: &str = ". . . +1\n. # . -1\nS . . .";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridWorld {
+    pub width: usize,
+    pub height: usize,
+    // Row by row, the top row first.
+    pub cells: Vec<Cell>,
+    pub start: usize,
+    pub slip: f64,
+    pub living_reward: f64,
+    pub discount: f64,
+}
+
+impl GridWorld {
+    pub fn parse(layout: &str) -> Result<Self, String> {
+        let rows: Vec<Vec<&str>> = layout
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .filter(|row| !row.is_empty())
+            .collect();
+        let width = rows.first().map_or(0, |row| row.len());
+        if width == 0 || rows.iter().any(|row| row.len() != width) {
+            return Err(String::from(
+                "a gridworld needs rows of the same number of cells",
+            ));
+        }
+        let mut cells = Vec::with_capacity(width * rows.len());
+        let mut start = None;
+        for token in rows.iter().flatten() {
+            let cell = match *token {
+                "." => Cell::Open,
+                "#" => Cell::Wall,
+                "S" => {
+                    start = Some(cells.len());
+                    Cell::Open
+                }
+                reward => Cell::Terminal(
+                    reward
+                        .parse()
+                        .map_err(|_| format!("unknown gridworld cell '{}'", reward))?,
+                ),
+            };
+            cells.push(cell);
+        }
+        let start = start
+            .or_else(|| cells.iter().position(|cell| *cell == Cell::Open))
+            .ok_or("a gridworld needs an open cell to start from")?;
+        Ok(Self {
+            width,
+            height: rows.len(),
+            cells,
+            start,
+            slip: 0.2,
+            living_reward: -0.04,
+            discount: 1.0,
+        })
+    }
+
+    pub fn russell_norvig() -> Self {
+        Self::parse(RUSSELL_NORVIG).expect("the layout is valid")
+    }
+
+    // The optimal policy of `russell_norvig` with its slip and living reward.
+    pub fn russell_norvig_policy() -> Policy {
+        Self::russell_norvig()
+            .parse_policy("> > > *\n^ # ^ *\n^ < < <")
+            .expect("the policy fits the layout")
+    }
+
+    // Reads a policy drawn as `render` draws it, with whitespace between
+    // the cells.
+    pub fn parse_policy(&self, arrows: &str) -> Result<Policy, String> {
+        let policy: Policy = arrows
+            .split_whitespace()
+            .map(|token| match token {
+                "^" => Some(Direction::North),
+                ">" => Some(Direction::East),
+                "v" => Some(Direction::South),
+                "<" => Some(Direction::West),
+                _ => None,
+            })
+            .collect();
+        if policy.len() != self.num_states() {
+            return Err(format!(
+                "the policy has {} cells, the gridworld {}",
+                policy.len(),
+                self.num_states()
+            ));
+        }
+        Ok(policy)
+    }
+
+    pub fn with_slip(mut self, slip: f64) -> Self {
+        self.slip = slip;
+        self
+    }
+
+    pub fn with_living_reward(mut self, living_reward: f64) -> Self {
+        self.living_reward = living_reward;
+        self
+    }
+
+    pub fn with_discount(mut self, discount: f64) -> Self {
+        self.discount = discount;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.slip) {
+            return Err(format!(
+                "the slip has to be a probability, got {}",
+                self.slip
+            ));
+        }
+        if !(self.discount > 0.0 && self.discount <= 1.0) {
+            return Err(format!(
+                "the discount has to lie in (0, 1], got {}",
+                self.discount
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn num_states(&self) -> usize {
+        self.cells.len()
+    }
+
+    // Whether the agent moves in `state`, that is whether it is open.
+    pub fn is_open(&self, state: usize) -> bool {
+        self.cells[state] == Cell::Open
+    }
+
+    pub fn is_terminal(&self, state: usize) -> bool {
+        matches!(self.cells[state], Cell::Terminal(_))
+    }
+
+    // The cell a move from `state` in `direction` ends in.
+    pub fn neighbor(&self, state: usize, direction: Direction) -> usize {
+        let (row, column) = (state / self.width, state % self.width);
+        let next = match direction {
+            Direction::North if row > 0 => state - self.width,
+            Direction::South if row + 1 < self.height => state + self.width,
+            Direction::West if column > 0 => state - 1,
+            Direction::East if column + 1 < self.width => state + 1,
+            _ => state,
+        };
+        if self.cells[next] == Cell::Wall {
+            state
+        } else {
+            next
+        }
+    }
+
+    // The reward of moving into `next`.
+    pub fn reward(&self, next: usize) -> f64 {
+        match self.cells[next] {
+            Cell::Terminal(reward) => reward,
+            _ => self.living_reward,
+        }
+    }
+
+    // The probabilities of the cells a move from `state` in `direction` ends
+    // in; cells reached in several ways appear several times.
+    pub fn transitions(&self, state: usize, direction: Direction) -> Vec<(f64, usize)> {
+        let [left, right] = direction.perpendicular();
+        vec![
+            (1.0 - self.slip, self.neighbor(state, direction)),
+            (self.slip / 2.0, self.neighbor(state, left)),
+            (self.slip / 2.0, self.neighbor(state, right)),
+        ]
+    }
+
+    // Samples a move from `state` in `direction`, returning the next state
+    // and the reward.
+    pub fn step<R: Rng>(&self, state: usize, direction: Direction, rng: &mut R) -> (usize, f64) {
+        let [left, right] = direction.perpendicular();
+        let draw: f64 = rng.random();
+        let moved = if draw < self.slip / 2.0 {
+            left
+        } else if draw < self.slip {
+            right
+        } else {
+            direction
+        };
+        let next = self.neighbor(state, moved);
+        (next, self.reward(next))
+    }
+
+    // The discounted return of an episode from the start cell following
+    // `policy`, cut off after `max_steps` moves.
+    pub fn episode(&self, policy: &Policy, max_steps: usize, rng: &SharedRng) -> f64 {
+        let mut state = self.start;
+        let (mut total, mut weight) = (0.0, 1.0);
+        for _ in 0..max_steps {
+            let Some(direction) = policy[state] else {
+                break;
+            };
+            let (next, reward) = self.step(state, direction, &mut *rng.borrow_mut());
+            total += weight * reward;
+            weight *= self.discount;
+            state = next;
+            if self.is_terminal(state) {
+                break;
+            }
+        }
+        total
+    }
+
+    // The average return of `episodes` episodes following `policy`.
+    pub fn evaluate(
+        &self,
+        policy: &Policy,
+        episodes: usize,
+        max_steps: usize,
+        rng: &SharedRng,
+    ) -> f64 {
+        let total: f64 = (0..episodes)
+            .map(|_| self.episode(policy, max_steps, rng))
+            .sum();
+        total / episodes.max(1) as f64
+    }
+
+    // Draws a direction for every open cell.
+    pub fn random_policy<R: Rng>(&self, rng: &mut R) -> Policy {
+        (0..self.num_states())
+            .map(|state| {
+                self.is_open(state)
+                    .then(|| Direction::ALL[rng.random_range(0..Direction::ALL.len())])
+            })
+            .collect()
+    }
+
+    // The gridworld as an explicit decision process, whose states are the
+    // cells and whose actions are `Direction::ALL`, available in open cells.
+    pub fn to_mdp(&self) -> Mdp {
+        let transitions = (0..self.num_states())
+            .map(|state| {
+                Direction::ALL
+                    .iter()
+                    .map(|direction| {
+                        if !self.is_open(state) {
+                            return Vec::new();
+                        }
+                        self.transitions(state, *direction)
+                            .into_iter()
+                            .map(|(probability, next)| Transition {
+                                probability,
+                                next,
+                                reward: self.reward(next),
+                            })
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+        Mdp {
+            states: (0..self.num_states())
+                .map(|state| format!("{}:{}", state / self.width, state % self.width))
+                .collect(),
+            actions: Direction::ALL
+                .iter()
+                .map(|direction| format!("{:?}", direction))
+                .collect(),
+            transitions,
+            discount: self.discount,
+        }
+    }
+
+    // A policy of `to_mdp` in directions.
+    pub fn policy_from_mdp(&self, policy: &MdpPolicy) -> Policy {
+        policy
+            .iter()
+            .map(|action| action.map(|action| Direction::ALL[action]))
+            .collect()
+    }
+
+    // The policy drawn on the grid, walls as `#` and terminal cells as `*`.
+    pub fn render(&self, policy: &Policy) -> String {
+        let rows: Vec<String> = (0..self.height)
+            .map(|row| {
+                (0..self.width)
+                    .map(|column| {
+                        let state = row * self.width + column;
+                        match (self.cells[state], policy[state]) {
+                            (Cell::Wall, _) => '#',
+                            (Cell::Terminal(_), _) => '*',
+                            (Cell::Open, Some(direction)) => direction.arrow(),
+                            (Cell::Open, None) => '.',
+                        }
+                        .to_string()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        rows.join("\n")
+    }
+}
+
+impl fmt::Display for GridWorld {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..self.height {
+            let cells: Vec<String> = (0..self.width)
+                .map(|column| {
+                    let state = row * self.width + column;
+                    match self.cells[state] {
+                        Cell::Wall => String::from("#"),
+                        Cell::Open if state == self.start => String::from("S"),
+                        Cell::Open => String::from("."),
+                        Cell::Terminal(reward) => format!("{:+}", reward),
+                    }
+                })
+                .collect();
+            writeln!(f, "{}", cells.join(" "))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+// Client of the agent service of proto/agent.proto.
+//
+// The messages mirror the service definition by hand, so that building the
+// crate needs no protobuf compiler; they have to be kept in sync with it.
+// The client blocks on a runtime of its own, as agents decide synchronously.
+
+use std::fmt;
+use std::rc::Rc;
+
+use tokio::runtime::{Builder, Runtime};
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::{Channel, Endpoint};
+
+use crate::duel;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, prost::Enumeration)]
+#[repr(i32)]
+pub enum Action {
+    Unspecified = 0,
+    Attack = 1,
+    Finch = 2,
+}
+
+impl From<&duel::Action> for Action {
+    fn from(action: &duel::Action) -> Self {
+        match action {
+            duel::Action::ATTACK => Action::Attack,
+            duel::Action::FINCH => Action::Finch,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InfoRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct InfoResponse {
+    #[prost(string, tag = "1")]
+    pub strategy_name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ResetRequest {
+    #[prost(string, tag = "1")]
+    pub session: String,
+    #[prost(int64, tag = "2")]
+    pub max_hit_points: i64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ResetResponse {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Observation {
+    #[prost(string, tag = "1")]
+    pub session: String,
+    #[prost(uint64, tag = "2")]
+    pub turn: u64,
+    #[prost(int64, tag = "3")]
+    pub max_hit_points: i64,
+    #[prost(int64, tag = "4")]
+    pub own_hit_points: i64,
+    #[prost(int64, optional, tag = "5")]
+    pub opposing_hit_points: Option<i64>,
+    #[prost(enumeration = "Action", tag = "6")]
+    pub own_action: i32,
+    #[prost(enumeration = "Action", tag = "7")]
+    pub opposing_action: i32,
+    #[prost(int64, optional, tag = "8")]
+    pub own_stamina: Option<i64>,
+    #[prost(int64, optional, tag = "9")]
+    pub opposing_stamina: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Decision {
+    #[prost(enumeration = "Action", tag = "1")]
+    pub action: i32,
+}
+
+#[derive(Debug)]
+pub enum GrpcError {
+    Runtime(std::io::Error),
+    Transport(tonic::transport::Error),
+    Status(Box<tonic::Status>),
+    // The agent answered without an action.
+    NoAction,
+}
+
+impl fmt::Display for GrpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrpcError::Runtime(err) => write!(f, "could not start the gRPC runtime: {}", err),
+            GrpcError::Transport(err) => write!(f, "could not reach the agent: {}", err),
+            GrpcError::Status(status) => write!(f, "the agent failed: {}", status),
+            GrpcError::NoAction => write!(f, "the agent answered without an action"),
+        }
+    }
+}
+
+impl std::error::Error for GrpcError {}
+
+// A blocking connection to an agent service, shared by all copies of an agent.
+#[derive(Clone)]
+pub struct AgentClient {
+    pub endpoint: String,
+    runtime: Rc<Runtime>,
+    channel: Channel,
+}
+
+impl AgentClient {
+    pub fn connect(endpoint: &str) -> Result<Self, GrpcError> {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(GrpcError::Runtime)?;
+        let channel = runtime
+            .block_on(
+                Endpoint::from_shared(endpoint.to_string())
+                    .map_err(GrpcError::Transport)?
+                    .connect(),
+            )
+            .map_err(GrpcError::Transport)?;
+        Ok(Self {
+            endpoint: endpoint.to_string(),
+            runtime: Rc::new(runtime),
+            channel,
+        })
+    }
+
+    fn unary<Request, Response>(
+        &self,
+        method: &'static str,
+        request: Request,
+    ) -> Result<Response, GrpcError>
+    where
+        Request: prost::Message + Send + Sync + 'static,
+        Response: prost::Message + Default + Send + Sync + 'static,
+    {
+        let mut grpc = tonic::client::Grpc::new(self.channel.clone());
+        self.runtime.block_on(async move {
+            grpc.ready().await.map_err(|err| {
+                GrpcError::Status(Box::new(tonic::Status::unavailable(err.to_string())))
+            })?;
+            grpc.unary(
+                tonic::Request::new(request),
+                PathAndQuery::from_static(method),
+                ProstCodec::<Request, Response>::default(),
+            )
+            .await
+            .map(tonic::Response::into_inner)
+            .map_err(|status| GrpcError::Status(Box::new(status)))
+        })
+    }
+
+    pub fn info(&self) -> Result<InfoResponse, GrpcError> {
+        self.unary("/the_duel.agent.v1.Agent/Info", InfoRequest {})
+    }
+
+    pub fn reset(&self, request: ResetRequest) -> Result<ResetResponse, GrpcError> {
+        self.unary("/the_duel.agent.v1.Agent/Reset", request)
+    }
+
+    pub fn decide_action(&self, observation: Observation) -> Result<duel::Action, GrpcError> {
+        let decision: Decision =
+            self.unary("/the_duel.agent.v1.Agent/DecideAction", observation)?;
+        match Action::try_from(decision.action) {
+            Ok(Action::Attack) => Ok(duel::Action::ATTACK),
+            Ok(Action::Finch) => Ok(duel::Action::FINCH),
+            _ => Err(GrpcError::NoAction),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// Handicaps even out agents of different strength by giving them different
+// hit points. The search looks for the ratio of the hit points of two agents
+// at which both win equally often, by bisection over the ratio on a
+// logarithmic scale: whenever the first agent scores more than half at the
+// ratio tried, it gets fewer hit points next, otherwise more. The agent
+// ahead in hit points always starts with the rules' maximum.
+
+use serde::Deserialize;
+
+use crate::agents::GameAgent;
+use crate::duel::{Duel, StartingConditions};
+use crate::game::{Game, GameOutcome, GameSettings};
+
+#[derive(Deserialize, Clone)]
+pub struct HandicapSearchConfig {
+    // Roster indices of the two agents.
+    pub agents: (usize, usize),
+    // Games per seat at every ratio tried.
+    #[serde(default = "default_games")]
+    pub games: u64,
+    // The range of hit point ratios, first agent to second, searched.
+    #[serde(default = "default_min_ratio")]
+    pub min_ratio: f64,
+    #[serde(default = "default_max_ratio")]
+    pub max_ratio: f64,
+    // Ratios tried, each halving the range on the logarithmic scale.
+    #[serde(default = "default_steps")]
+    pub steps: usize,
+}
+
+fn default_games() -> u64 {
+    100
+}
+
+fn default_min_ratio() -> f64 {
+    0.1
+}
+
+fn default_max_ratio() -> f64 {
+    10.0
+}
+
+fn default_steps() -> usize {
+    10
+}
+
+impl HandicapSearchConfig {
+    pub fn validate(&self, num_agents: usize) -> Result<(), String> {
+        let (first, second) = self.agents;
+        if first >= num_agents || second >= num_agents {
+            return Err(format!(
+                "the handicap search names agents {} and {}, but there are only {}",
+                first, second, num_agents
+            ));
+        }
+        if !(0.0 < self.min_ratio && self.min_ratio < self.max_ratio) {
+            return Err(format!(
+                "the handicap search needs 0 < min_ratio < max_ratio, got {} and {}",
+                self.min_ratio, self.max_ratio
+            ));
+        }
+        Ok(())
+    }
+}
+
+// One ratio tried and the first agent's average score there, a tie or draw
+// counting as half a win.
+pub struct HandicapStep {
+    pub ratio: f64,
+    pub hit_points: (i64, i64),
+    pub score: f64,
+}
+
+pub struct HandicapResult {
+    pub steps: Vec<HandicapStep>,
+    // The estimated ratio at which the agents are evenly matched.
+    pub ratio: f64,
+}
+
+pub struct HandicapSearch {
+    pub rules: Duel,
+    pub settings: GameSettings,
+    pub first: Box<dyn GameAgent>,
+    pub second: Box<dyn GameAgent>,
+    pub config: HandicapSearchConfig,
+}
+
+impl HandicapSearch {
+    pub fn new(
+        rules: Duel,
+        first: Box<dyn GameAgent>,
+        second: Box<dyn GameAgent>,
+        config: HandicapSearchConfig,
+    ) -> Self {
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            first,
+            second,
+            config,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    // The hit points of both agents at `ratio`.
+    pub fn hit_points(&self, ratio: f64) -> (i64, i64) {
+        let max_hit_points = self.rules.max_hit_points;
+        let scaled = |ratio: f64| ((max_hit_points as f64 * ratio).round() as i64).max(1);
+        if ratio <= 1.0 {
+            (scaled(ratio), max_hit_points)
+        } else {
+            (max_hit_points, scaled(1.0 / ratio))
+        }
+    }
+
+    // The first agent's average score with `hit_points`, over both seats.
+    pub fn score(&self, hit_points: (i64, i64)) -> f64 {
+        let start = |max_hit_points| StartingConditions {
+            max_hit_points: Some(max_hit_points),
+            ..StartingConditions::default()
+        };
+        let play = |rules: Duel, player_one: &dyn GameAgent, player_two: &dyn GameAgent| {
+            let mut game = Game::new(
+                rules,
+                player_one.copy_self_to_anom(),
+                player_two.copy_self_to_anom(),
+            )
+            .with_settings(self.settings.clone());
+            match game.play() {
+                GameOutcome::WIN(1) => 1.0,
+                GameOutcome::WIN(_) => 0.0,
+                _ => 0.5,
+            }
+        };
+        let first_seated_first = self
+            .rules
+            .clone()
+            .with_starting_conditions(start(hit_points.0), start(hit_points.1));
+        let first_seated_second = self
+            .rules
+            .clone()
+            .with_starting_conditions(start(hit_points.1), start(hit_points.0));
+        let mut score = 0.0;
+        for _ in 0..self.config.games {
+            score += play(
+                first_seated_first.clone(),
+                self.first.as_ref(),
+                self.second.as_ref(),
+            );
+            score += 1.0
+                - play(
+                    first_seated_second.clone(),
+                    self.second.as_ref(),
+                    self.first.as_ref(),
+                );
+        }
+        score / (2 * self.config.games).max(1) as f64
+    }
+
+    pub fn run(&self) -> HandicapResult {
+        let (mut low, mut high) = (self.config.min_ratio.ln(), self.config.max_ratio.ln());
+        let mut steps = Vec::with_capacity(self.config.steps);
+        for _ in 0..self.config.steps {
+            let ratio = ((low + high) / 2.0).exp();
+            let hit_points = self.hit_points(ratio);
+            let score = self.score(hit_points);
+            if score > 0.5 {
+                high = ratio.ln();
+            } else {
+                low = ratio.ln();
+            }
+            steps.push(HandicapStep {
+                ratio,
+                hit_points,
+                score,
+            });
+        }
+        HandicapResult {
+            steps,
+            ratio: ((low + high) / 2.0).exp(),
+        }
+    }
+}
@@ -0,0 +1,275 @@
+// The Hawk-Dove game, played for a fixed number of rounds: two players
+// contest a resource of some value. A hawk escalates and takes the whole
+// resource from a dove; two doves share it, and two hawks fight, each winning
+// it half of the time and paying the cost of the fight otherwise. With a
+// fight costing more than the resource, the evolutionarily stable strategy
+// (ESS) plays hawk with probability value / cost.
+//
+// A turn of the duel is a symmetric stage game of two actions as well,
+// attacking taking the part of the hawk and finching that of the dove, so the
+// same analysis applies to its damage matrix, see `duel_stage_game`.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::duel::{Action, DamageMatrix, Duel};
+use crate::game::{GameOutcome, GameSettings, GameState, SimultaneousGame};
+use crate::observer::{GameObserver, Scored};
+use crate::openspiel::SpielGame;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Posture {
+    HAWK,
+    DOVE,
+}
+
+impl Posture {
+    pub const ALL: [Posture; 2] = [Posture::HAWK, Posture::DOVE];
+
+    // Position of the posture in `ALL`.
+    pub fn index(&self) -> usize {
+        match self {
+            Posture::HAWK => 0,
+            Posture::DOVE => 1,
+        }
+    }
+}
+
+impl fmt::Display for Posture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HawkDovePayoffs {
+    pub value: f64,
+    pub cost: f64,
+}
+
+// Maynard Smith's example of a fight costing twice the resource.
+impl Default for HawkDovePayoffs {
+    fn default() -> Self {
+        Self {
+            value: 2.0,
+            cost: 4.0,
+        }
+    }
+}
+
+impl HawkDovePayoffs {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.value > 0.0 && self.cost > 0.0) {
+            return Err(format!(
+                "the value and the cost of a fight have to be positive, got {} and {}",
+                self.value, self.cost
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn payoff(&self, own: &Posture, opposing: &Posture) -> f64 {
+        match (own, opposing) {
+            (Posture::HAWK, Posture::HAWK) => (self.value - self.cost) / 2.0,
+            (Posture::HAWK, Posture::DOVE) => self.value,
+            (Posture::DOVE, Posture::HAWK) => 0.0,
+            (Posture::DOVE, Posture::DOVE) => self.value / 2.0,
+        }
+    }
+
+    // Own payoffs by own and then opposing posture, in the order of
+    // `Posture::ALL`.
+    pub fn matrix(&self) -> [[f64; 2]; 2] {
+        Posture::ALL.map(|own| Posture::ALL.map(|opposing| self.payoff(&own, &opposing)))
+    }
+
+    // Share of hawks in the ESS: all hawks when the resource is worth the
+    // fight, a mix of value / cost otherwise.
+    pub fn ess(&self) -> f64 {
+        (self.value / self.cost).min(1.0)
+    }
+}
+
+// Hawk shares, that is shares of the first action, of every ESS of the
+// symmetric 2x2 game with own payoffs `matrix[own][opposing]`. A pure
+// strategy is stable if it does better against itself than the other
+// action does, or as well but better against the other; a mix is stable
+// if both actions do worse against themselves than the other does.
+pub fn symmetric_ess(matrix: &[[f64; 2]; 2]) -> Vec<f64> {
+    let [[a, b], [c, d]] = *matrix;
+    let mut stable = Vec::new();
+    if a > c || (a == c && b > d) {
+        stable.push(1.0);
+    }
+    if d > b || (d == b && c > a) {
+        stable.push(0.0);
+    }
+    if a < c && d < b {
+        stable.push((b - d) / ((b - d) + (c - a)));
+    }
+    stable
+}
+
+// A turn of the duel as a symmetric stage game, with attacking as the first
+// action: what a player gains by the damage it deals minus the damage it
+// takes. Asymmetric damage matrices are averaged over both seats.
+pub fn duel_stage_game(damage: &DamageMatrix) -> [[f64; 2]; 2] {
+    Action::ALL.map(|own| {
+        Action::ALL.map(|opposing| {
+            let [as_one_taken, as_one_dealt] = damage.damage(&own, &opposing);
+            let [as_two_dealt, as_two_taken] = damage.damage(&opposing, &own);
+            ((as_one_dealt - as_one_taken) + (as_two_dealt - as_two_taken)) as f64 / 2.0
+        })
+    })
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Contestant {
+    pub score: f64,
+}
+
+impl Scored for Contestant {
+    fn score(&self) -> f64 {
+        self.score
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HawkDove {
+    pub payoffs: HawkDovePayoffs,
+    pub rounds: usize,
+}
+
+impl Default for HawkDove {
+    fn default() -> Self {
+        Self {
+            payoffs: HawkDovePayoffs::default(),
+            rounds: 100,
+        }
+    }
+}
+
+impl HawkDove {
+    pub fn new(payoffs: HawkDovePayoffs, rounds: usize) -> Self {
+        Self { payoffs, rounds }
+    }
+}
+
+impl SimultaneousGame for HawkDove {
+    type PlayerState = Contestant;
+    type Action = Posture;
+
+    fn initial_state(&self) -> GameState<Self> {
+        GameState::new(Contestant::default(), Contestant::default())
+    }
+
+    fn resolve_actions(
+        &self,
+        state: &mut GameState<Self>,
+        player_one_action: Posture,
+        player_two_action: Posture,
+    ) {
+        state.player_one_state.score += self.payoffs.payoff(&player_one_action, &player_two_action);
+        state.player_two_state.score += self.payoffs.payoff(&player_two_action, &player_one_action);
+    }
+
+    fn check_end_condition(&self, state: &GameState<Self>) -> GameOutcome {
+        if state.history.turns() < self.rounds {
+            return GameOutcome::CONTINUE;
+        }
+        let (one, two) = (state.player_one_state.score, state.player_two_state.score);
+        match one.total_cmp(&two) {
+            std::cmp::Ordering::Greater => GameOutcome::WIN(1),
+            std::cmp::Ordering::Less => GameOutcome::WIN(2),
+            std::cmp::Ordering::Equal => GameOutcome::TIE,
+        }
+    }
+}
+
+// Hawk is action 0, dove action 1. The observation holds the fraction of
+// rounds played, followed by the opponent's last posture one-hot encoded.
+impl SpielGame for HawkDove {
+    fn num_distinct_actions(&self) -> usize {
+        Posture::ALL.len()
+    }
+
+    fn action_id(&self, action: &Posture) -> usize {
+        action.index()
+    }
+
+    fn action_from_id(&self, id: usize) -> Option<Posture> {
+        Posture::ALL.get(id).cloned()
+    }
+
+    fn action_to_string(&self, id: usize) -> String {
+        match self.action_from_id(id) {
+            Some(action) => action.to_string(),
+            None => format!("invalid action {}", id),
+        }
+    }
+
+    fn observation_tensor_size(&self) -> usize {
+        1 + Posture::ALL.len()
+    }
+
+    fn observation_tensor(
+        &self,
+        state: &GameState<Self>,
+        _settings: &GameSettings,
+        player: u64,
+    ) -> Vec<f32> {
+        let opposing_action = state.observed_opposing_action(player);
+        let mut tensor = vec![state.history.turns() as f32 / self.rounds.max(1) as f32];
+        tensor.extend(
+            Posture::ALL
+                .iter()
+                .map(|action| (opposing_action.as_ref() == Some(action)) as u8 as f32),
+        );
+        tensor
+    }
+}
+
+// Counts the attacks of every roster entry over the duels a tournament
+// plays, to set their share of attacks against the ESS of the stage game.
+pub struct AttackShareObserver {
+    current_pairing: Option<(usize, usize)>,
+    pub attacks: Vec<u64>,
+    pub turns: Vec<u64>,
+}
+
+impl AttackShareObserver {
+    pub fn new(num_agents: usize) -> Self {
+        Self {
+            current_pairing: None,
+            attacks: vec![0; num_agents],
+            turns: vec![0; num_agents],
+        }
+    }
+
+    pub fn attack_share(&self, agent: usize) -> f64 {
+        self.attacks[agent] as f64 / self.turns[agent].max(1) as f64
+    }
+}
+
+impl GameObserver<Duel> for AttackShareObserver {
+    fn on_game_start(&mut self, player_one: usize, player_two: usize) {
+        self.current_pairing = Some((player_one, player_two));
+    }
+
+    fn on_game_end(&mut self, state: &GameState<Duel>, _outcome: &GameOutcome) {
+        let Some((player_one, player_two)) = self.current_pairing else {
+            return;
+        };
+        let history = &state.history;
+        for (agent, actions) in [
+            (player_one, &history.player_one_actions),
+            (player_two, &history.player_two_actions),
+        ] {
+            self.attacks[agent] += actions.iter().filter(|a| **a == Action::ATTACK).count() as u64;
+            self.turns[agent] += actions.len() as u64;
+        }
+    }
+}
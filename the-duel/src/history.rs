@@ -0,0 +1,151 @@
+use crate::duel::Duel;
+use crate::game::SimultaneousGame;
+
+// Everything that happened in a game so far. Entry `t` of each vector holds
+// the action chosen in turn `t` and the player state after that turn.
+pub struct History<G: SimultaneousGame = Duel> {
+    pub player_one_actions: Vec<G::Action>,
+    pub player_two_actions: Vec<G::Action>,
+    pub player_one_states: Vec<G::PlayerState>,
+    pub player_two_states: Vec<G::PlayerState>,
+    // The opponent's actions as player one and player two observed them,
+    // which differ from the actions played under observation noise.
+    pub player_one_observations: Vec<G::Action>,
+    pub player_two_observations: Vec<G::Action>,
+    // The opponent's state after every turn as player one and player two
+    // observed it, see GameSettings::observe_opponent, None where hidden.
+    pub player_one_state_observations: Vec<Option<G::PlayerState>>,
+    pub player_two_state_observations: Vec<Option<G::PlayerState>>,
+}
+
+impl<G: SimultaneousGame> History<G> {
+    pub fn new() -> Self {
+        Self {
+            player_one_actions: Vec::new(),
+            player_two_actions: Vec::new(),
+            player_one_states: Vec::new(),
+            player_two_states: Vec::new(),
+            player_one_observations: Vec::new(),
+            player_two_observations: Vec::new(),
+            player_one_state_observations: Vec::new(),
+            player_two_state_observations: Vec::new(),
+        }
+    }
+
+    pub fn turns(&self) -> usize {
+        self.player_one_actions.len()
+    }
+
+    pub fn record(
+        &mut self,
+        player_one_action: G::Action,
+        player_two_action: G::Action,
+        player_one_state: G::PlayerState,
+        player_two_state: G::PlayerState,
+    ) {
+        self.player_one_observations.push(player_two_action.clone());
+        self.player_two_observations.push(player_one_action.clone());
+        // Hidden until observed, see `observe_last_states`
+        self.player_one_state_observations.push(None);
+        self.player_two_state_observations.push(None);
+        self.player_one_actions.push(player_one_action);
+        self.player_two_actions.push(player_two_action);
+        self.player_one_states.push(player_one_state);
+        self.player_two_states.push(player_two_state);
+    }
+
+    // Replaces what both players observed of their opponent's last action.
+    pub fn observe_last(
+        &mut self,
+        player_one_observation: G::Action,
+        player_two_observation: G::Action,
+    ) {
+        if let Some(observation) = self.player_one_observations.last_mut() {
+            *observation = player_one_observation;
+        }
+        if let Some(observation) = self.player_two_observations.last_mut() {
+            *observation = player_two_observation;
+        }
+    }
+
+    // Replaces what both players observed of their opponent's state after
+    // the last turn.
+    pub fn observe_last_states(
+        &mut self,
+        player_one_observation: Option<G::PlayerState>,
+        player_two_observation: Option<G::PlayerState>,
+    ) {
+        if let Some(observation) = self.player_one_state_observations.last_mut() {
+            *observation = player_one_observation;
+        }
+        if let Some(observation) = self.player_two_state_observations.last_mut() {
+            *observation = player_two_observation;
+        }
+    }
+
+    // The history as seen by player `player` (1 or 2), with the opponent's
+    // actions and states as the player observed them.
+    pub fn view(&self, player: u64) -> HistoryView<'_, G> {
+        if player == 1 {
+            HistoryView {
+                own_actions: &self.player_one_actions,
+                opposing_actions: &self.player_one_observations,
+                own_states: &self.player_one_states,
+                opposing_states: &self.player_one_state_observations,
+            }
+        } else {
+            HistoryView {
+                own_actions: &self.player_two_actions,
+                opposing_actions: &self.player_two_observations,
+                own_states: &self.player_two_states,
+                opposing_states: &self.player_two_state_observations,
+            }
+        }
+    }
+}
+
+// Not derived, which would require the game itself to be `Clone`.
+impl<G: SimultaneousGame> Clone for History<G> {
+    fn clone(&self) -> Self {
+        Self {
+            player_one_actions: self.player_one_actions.clone(),
+            player_two_actions: self.player_two_actions.clone(),
+            player_one_states: self.player_one_states.clone(),
+            player_two_states: self.player_two_states.clone(),
+            player_one_observations: self.player_one_observations.clone(),
+            player_two_observations: self.player_two_observations.clone(),
+            player_one_state_observations: self.player_one_state_observations.clone(),
+            player_two_state_observations: self.player_two_state_observations.clone(),
+        }
+    }
+}
+
+impl<G: SimultaneousGame> Default for History<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct HistoryView<'a, G: SimultaneousGame = Duel> {
+    pub own_actions: &'a [G::Action],
+    pub opposing_actions: &'a [G::Action],
+    pub own_states: &'a [G::PlayerState],
+    // Only what the observability of the game lets the player know.
+    pub opposing_states: &'a [Option<G::PlayerState>],
+}
+
+impl<G: SimultaneousGame> HistoryView<'_, G> {
+    pub fn turns(&self) -> usize {
+        self.own_actions.len()
+    }
+
+    // The last `n` actions of the opponent, oldest first.
+    pub fn recent_opposing_actions(&self, n: usize) -> &[G::Action] {
+        &self.opposing_actions[self.opposing_actions.len().saturating_sub(n)..]
+    }
+
+    // The last `n` actions of the own player, oldest first.
+    pub fn recent_own_actions(&self, n: usize) -> &[G::Action] {
+        &self.own_actions[self.own_actions.len().saturating_sub(n)..]
+    }
+}
@@ -0,0 +1,182 @@
+// Two-state hidden Markov models of an action stream.
+//
+// The opponent is modeled as switching between two hidden states, each
+// emitting attacks with its own probability. The parameters are fitted by
+// Baum-Welch, i.e. expectation maximization over the forward-backward
+// posteriors, with scaling to avoid underflow on long streams. A
+// MarkovRandomAgent plays its hidden state directly, so the fitted
+// transitions recover its switching probabilities.
+
+use crate::duel::Action;
+
+// Keeps probabilities estimated from few observations away from zero, from
+// which Baum-Welch could never recover.
+const FLOOR: f64 = 1e-6;
+
+// Observations are indexed 0 for a finch and 1 for an attack.
+fn observation(action: &Action) -> usize {
+    usize::from(*action == Action::ATTACK)
+}
+
+fn normalize(row: &mut [f64; 2]) {
+    row.iter_mut().for_each(|p| *p = p.max(FLOOR));
+    let total: f64 = row.iter().sum();
+    row.iter_mut().for_each(|p| *p /= total);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HiddenMarkovModel {
+    pub initial: [f64; 2],
+    // transition[i][j] is the probability of moving from state i to j.
+    pub transition: [[f64; 2]; 2],
+    // emission[i][o] is the probability of state i emitting observation o.
+    pub emission: [[f64; 2]; 2],
+}
+
+impl Default for HiddenMarkovModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HiddenMarkovModel {
+    // Starts from an attacking and a finching state, which breaks the
+    // symmetry Baum-Welch could not break on its own.
+    pub fn new() -> Self {
+        Self {
+            initial: [0.5, 0.5],
+            transition: [[0.7, 0.3], [0.3, 0.7]],
+            emission: [[0.2, 0.8], [0.8, 0.2]],
+        }
+    }
+
+    // Scaled forward probabilities, i.e. the state distribution given the
+    // observations so far, and the scaling factors, whose logarithms sum up
+    // to the log-likelihood.
+    fn forward(&self, observations: &[usize]) -> (Vec<[f64; 2]>, Vec<f64>) {
+        let mut alphas: Vec<[f64; 2]> = Vec::with_capacity(observations.len());
+        let mut scales = Vec::with_capacity(observations.len());
+        for (t, o) in observations.iter().enumerate() {
+            let mut alpha = [0.0; 2];
+            for (j, a) in alpha.iter_mut().enumerate() {
+                let prior = match t {
+                    0 => self.initial[j],
+                    _ => (0..2)
+                        .map(|i| alphas[t - 1][i] * self.transition[i][j])
+                        .sum::<f64>(),
+                };
+                *a = prior * self.emission[j][*o];
+            }
+            let scale: f64 = alpha.iter().sum();
+            alpha.iter_mut().for_each(|a| *a /= scale);
+            alphas.push(alpha);
+            scales.push(scale);
+        }
+        (alphas, scales)
+    }
+
+    fn backward(&self, observations: &[usize], scales: &[f64]) -> Vec<[f64; 2]> {
+        let mut betas = vec![[1.0; 2]; observations.len()];
+        for t in (0..observations.len().saturating_sub(1)).rev() {
+            for i in 0..2 {
+                betas[t][i] = (0..2)
+                    .map(|j| {
+                        self.transition[i][j]
+                            * self.emission[j][observations[t + 1]]
+                            * betas[t + 1][j]
+                    })
+                    .sum::<f64>()
+                    / scales[t + 1];
+            }
+        }
+        betas
+    }
+
+    pub fn log_likelihood(&self, actions: &[Action]) -> f64 {
+        let observations: Vec<usize> = actions.iter().map(observation).collect();
+        let (_, scales) = self.forward(&observations);
+        scales.iter().map(|s| s.ln()).sum()
+    }
+
+    // Runs `iterations` Baum-Welch steps from the current parameters.
+    pub fn fit(&mut self, actions: &[Action], iterations: usize) {
+        let observations: Vec<usize> = actions.iter().map(observation).collect();
+        if observations.len() < 2 {
+            return;
+        }
+        for _ in 0..iterations {
+            let (alphas, scales) = self.forward(&observations);
+            let betas = self.backward(&observations, &scales);
+            let gammas: Vec<[f64; 2]> = alphas
+                .iter()
+                .zip(&betas)
+                .map(|(alpha, beta)| {
+                    let mut gamma = [alpha[0] * beta[0], alpha[1] * beta[1]];
+                    let total: f64 = gamma.iter().sum();
+                    gamma.iter_mut().for_each(|g| *g /= total);
+                    gamma
+                })
+                .collect();
+
+            let mut transitions = [[0.0; 2]; 2];
+            for t in 0..observations.len() - 1 {
+                let mut xi = [[0.0; 2]; 2];
+                for (i, row) in xi.iter_mut().enumerate() {
+                    for (j, x) in row.iter_mut().enumerate() {
+                        *x = alphas[t][i]
+                            * self.transition[i][j]
+                            * self.emission[j][observations[t + 1]]
+                            * betas[t + 1][j];
+                    }
+                }
+                let total: f64 = xi.iter().flatten().sum();
+                for (row, xi_row) in transitions.iter_mut().zip(&xi) {
+                    for (x, xi) in row.iter_mut().zip(xi_row) {
+                        *x += xi / total;
+                    }
+                }
+            }
+            let mut emissions = [[0.0; 2]; 2];
+            for (gamma, o) in gammas.iter().zip(&observations) {
+                for (row, g) in emissions.iter_mut().zip(gamma) {
+                    row[*o] += g;
+                }
+            }
+
+            self.initial = gammas[0];
+            normalize(&mut self.initial);
+            for row in transitions.iter_mut().chain(emissions.iter_mut()) {
+                normalize(row);
+            }
+            self.transition = transitions;
+            self.emission = emissions;
+        }
+    }
+
+    // Probability that the next action is an attack given the actions so far.
+    pub fn attack_probability(&self, actions: &[Action]) -> f64 {
+        let observations: Vec<usize> = actions.iter().map(observation).collect();
+        let state = match self.forward(&observations).0.last() {
+            Some(alpha) => [
+                alpha[0] * self.transition[0][0] + alpha[1] * self.transition[1][0],
+                alpha[0] * self.transition[0][1] + alpha[1] * self.transition[1][1],
+            ],
+            None => self.initial,
+        };
+        state[0] * self.emission[0][1] + state[1] * self.emission[1][1]
+    }
+
+    // The switching probabilities of a MarkovRandomAgent, to attack and to
+    // finch, taking the state more likely to attack as the attacking one.
+    pub fn markov_probabilities(&self) -> (f64, f64) {
+        let (attacking, finching) = if self.emission[0][1] >= self.emission[1][1] {
+            (0, 1)
+        } else {
+            (1, 0)
+        };
+        (
+            self.transition[finching][attacking],
+            self.transition[attacking][finching],
+        )
+    }
+}
@@ -0,0 +1,184 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::agents::GameAgent;
+use crate::game::{Game, GameOutcome, GameSettings, SimultaneousGame};
+use crate::observer::{GameObserver, NullObserver};
+
+// Which cells around a cell count as its neighbors.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Neighborhood {
+    // The eight surrounding cells.
+    #[default]
+    Moore,
+    // The four orthogonally adjacent cells.
+    VonNeumann,
+}
+
+impl Neighborhood {
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Neighborhood::Moore => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+            Neighborhood::VonNeumann => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LatticeConfig {
+    pub width: usize,
+    pub height: usize,
+    pub generations: usize,
+    #[serde(default)]
+    pub neighborhood: Neighborhood,
+    // Games every pair of neighboring cells plays per generation.
+    #[serde(default = "default_games_per_neighbor")]
+    pub games_per_neighbor: u64,
+}
+
+fn default_games_per_neighbor() -> u64 {
+    1
+}
+
+// A grid of cells on a torus, each hosting one strategy of the roster. Every
+// generation, each cell plays all of its neighbors, then adopts the strategy
+// of the best scoring cell in its neighborhood, keeping its own on a tie.
+pub struct Lattice<G: SimultaneousGame + Clone> {
+    pub rules: G,
+    pub settings: GameSettings,
+    pub agents: Vec<Box<dyn GameAgent<G>>>,
+    pub width: usize,
+    pub height: usize,
+    pub neighborhood: Neighborhood,
+    pub games_per_neighbor: u64,
+    // Roster index of the strategy in every cell, row by row.
+    pub cells: Vec<usize>,
+}
+
+impl<G: SimultaneousGame + Clone> Lattice<G> {
+    // Fills the grid with strategies drawn uniformly from the roster.
+    pub fn new<R: Rng>(
+        rules: G,
+        agents: Vec<Box<dyn GameAgent<G>>>,
+        config: &LatticeConfig,
+        rng: &mut R,
+    ) -> Self {
+        let cells = (0..config.width * config.height)
+            .map(|_| rng.random_range(0..agents.len()))
+            .collect();
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            agents,
+            width: config.width,
+            height: config.height,
+            neighborhood: config.neighborhood,
+            games_per_neighbor: config.games_per_neighbor,
+            cells,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    fn neighbors(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        let (row, column) = ((cell / self.width) as isize, (cell % self.width) as isize);
+        self.neighborhood.offsets().iter().map(move |(dr, dc)| {
+            let row = (row + dr).rem_euclid(self.height as isize) as usize;
+            let column = (column + dc).rem_euclid(self.width as isize) as usize;
+            row * self.width + column
+        })
+    }
+
+    // Score of `player_one` against `player_two`, a tie counting as half a win.
+    fn play(
+        &self,
+        player_one: usize,
+        player_two: usize,
+        observer: &mut dyn GameObserver<G>,
+    ) -> f64 {
+        observer.on_game_start(player_one, player_two);
+        let mut game = Game::new(
+            self.rules.clone(),
+            self.agents[player_one].copy_self_to_anom(),
+            self.agents[player_two].copy_self_to_anom(),
+        )
+        .with_settings(self.settings.clone());
+        let mut state = game.initial_state();
+        match game.play_observed(&mut state, observer) {
+            GameOutcome::WIN(1) => 1.0,
+            GameOutcome::WIN(_) => 0.0,
+            _ => 0.5,
+        }
+    }
+
+    // Total score of every cell against its neighbors this generation.
+    pub fn scores(&self, observer: &mut dyn GameObserver<G>) -> Vec<f64> {
+        let mut scores = vec![0.0; self.cells.len()];
+        for cell in 0..self.cells.len() {
+            // Every neighboring pair meets once, seated in both orders in turn
+            for neighbor in self.neighbors(cell).filter(|&n| n > cell) {
+                for game in 0..self.games_per_neighbor {
+                    let (one, two) = if game % 2 == 0 {
+                        (cell, neighbor)
+                    } else {
+                        (neighbor, cell)
+                    };
+                    let score = self.play(self.cells[one], self.cells[two], observer);
+                    scores[one] += score;
+                    scores[two] += 1.0 - score;
+                }
+            }
+        }
+        scores
+    }
+
+    pub fn step_observed(&mut self, observer: &mut dyn GameObserver<G>) {
+        let scores = self.scores(observer);
+        self.cells = (0..self.cells.len())
+            .map(|cell| {
+                let mut best = cell;
+                for neighbor in self.neighbors(cell) {
+                    if scores[neighbor] > scores[best] {
+                        best = neighbor;
+                    }
+                }
+                self.cells[best]
+            })
+            .collect();
+    }
+
+    pub fn run(&mut self, generations: usize) -> Vec<Vec<usize>> {
+        self.run_observed(generations, &mut NullObserver)
+    }
+
+    // The strategy map of every generation, starting with the initial one.
+    pub fn run_observed(
+        &mut self,
+        generations: usize,
+        observer: &mut dyn GameObserver<G>,
+    ) -> Vec<Vec<usize>> {
+        let mut maps = vec![self.cells.clone()];
+        for generation in 0..generations {
+            self.step_observed(observer);
+            maps.push(self.cells.clone());
+            observer.on_round_end(generation as u64);
+            if observer.should_stop() {
+                break;
+            }
+        }
+        maps
+    }
+}
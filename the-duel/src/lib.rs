@@ -0,0 +1,122 @@
+pub mod agents;
+pub mod alpha_rank;
+pub mod arena;
+pub mod bracket;
+#[cfg(feature = "websocket")]
+pub mod broadcast;
+pub mod cfr;
+pub mod cma_es;
+pub mod coevolution;
+pub mod config;
+pub mod dsl;
+pub mod duel;
+pub mod ecology;
+pub mod env;
+pub mod evolution;
+pub mod experience;
+pub mod exploitability;
+pub mod extensive_form;
+pub mod free_for_all;
+pub mod game;
+pub mod gridworld;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod handicap;
+pub mod hawk_dove;
+pub mod history;
+pub mod hmm;
+pub mod lattice;
+pub mod logging;
+pub mod matches;
+pub mod matching_pennies;
+pub mod matrix_game;
+pub mod moran;
+pub mod nash;
+pub mod nash_averaging;
+pub mod network;
+pub mod neural;
+pub mod observer;
+pub mod openspiel;
+pub mod optimize;
+pub mod oracle;
+pub mod output;
+pub mod page_rank;
+pub mod plot;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+pub mod prisoners_dilemma;
+#[cfg(feature = "progress")]
+pub mod progress;
+pub mod public_goods;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod rating;
+pub mod registry;
+pub mod repeated;
+pub mod replay;
+pub mod replicator;
+pub mod rng_audit;
+pub mod rock_paper_scissors;
+pub mod seed_search;
+pub mod self_play;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod solvers;
+pub mod stats;
+pub mod sweep;
+pub mod swiss;
+pub mod team;
+pub mod tournament;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod ultimatum;
+pub mod voting;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use agents::GameAgent;
+pub use alpha_rank::AlphaRank;
+pub use arena::{Arena, Fighter, Move, MoveInfo};
+pub use bracket::{Bracket, BracketResults, Elimination};
+pub use cma_es::CmaEs;
+pub use coevolution::{Coevolution, HallOfFame};
+pub use config::ExperimentConfig;
+pub use duel::{
+    Action, DamageDistribution, DamageMatrix, Duel, Overtime, OvertimeMode, PlayerState,
+    RuleVariant, StaminaRules, StartingConditions, StochasticDamage,
+};
+pub use ecology::Ecology;
+pub use env::DuelEnv;
+pub use evolution::Evolution;
+pub use extensive_form::GameTree;
+pub use free_for_all::{FreeForAll, FreeForAllTournament, Table, TableState, Targeting};
+pub use game::{
+    AgentMode, DrawReason, Game, GameOutcome, GameSettings, GameState, Observability,
+    SimultaneousGame, TurnOrder,
+};
+pub use gridworld::GridWorld;
+pub use handicap::HandicapSearch;
+pub use hawk_dove::{HawkDove, HawkDovePayoffs, Posture};
+pub use history::{History, HistoryView};
+pub use lattice::Lattice;
+pub use matches::{Match, MatchFormat};
+pub use matching_pennies::{MatchingPennies, PenniesPayoffs, Penny};
+pub use matrix_game::MatrixGame;
+pub use moran::MoranProcess;
+pub use observer::{DecisionLog, GameObserver};
+pub use openspiel::{SpielGame, SpielState};
+pub use optimize::Optimization;
+pub use oracle::DoubleOracle;
+pub use page_rank::PageRank;
+pub use prisoners_dilemma::{Choice, PayoffMatrix, PrisonersDilemma};
+pub use public_goods::{PublicGoods, PublicGoodsTournament};
+pub use rating::{Glicko2, Glicko2Rating, RatingObserver};
+pub use registry::AgentRegistry;
+pub use repeated::Repeated;
+pub use replay::Replay;
+pub use replicator::ReplicatorDynamics;
+pub use rock_paper_scissors::{Hand, HandPayoffs, RockPaperScissors};
+pub use sweep::Sweep;
+pub use swiss::{SwissResults, SwissTournament};
+pub use tournament::{PairingSchedule, Tournament, TournamentResults};
+pub use ultimatum::{Bid, Ultimatum};
@@ -0,0 +1,99 @@
+// Structured logs of games, turns and pairings through `tracing`. Games run
+// in a `game` span and pairings of a tournament in a `pairing` span, with
+// events at their end: pairings at the info level, games at the debug level
+// and turns at the trace level, so long tournaments stay quiet unless asked.
+
+use std::fmt;
+use std::fs::File;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use tracing::Dispatch;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    // Human readable, multi-line events.
+    Pretty,
+    // One JSON object per event, with the fields of its spans.
+    Json,
+}
+
+// Where and how much is logged, e.g.
+//   logging = { format = "json", file = "run.jsonl", level = "debug" }
+#[derive(Deserialize, Clone)]
+pub struct LoggingConfig {
+    #[serde(default = "default_format")]
+    pub format: LogFormat,
+    // File receiving the events, standard error if missing.
+    pub file: Option<String>,
+    // Filter directives, e.g. "debug" or "info,the_duel::game=trace".
+    #[serde(default = "default_level")]
+    pub level: String,
+}
+
+fn default_format() -> LogFormat {
+    LogFormat::Pretty
+}
+
+fn default_level() -> String {
+    "info".to_string()
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: default_format(),
+            file: None,
+            level: default_level(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LoggingError {
+    Io(std::io::Error),
+    Filter(String),
+}
+
+impl fmt::Display for LoggingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoggingError::Io(err) => write!(f, "could not create log file: {}", err),
+            LoggingError::Filter(reason) => write!(f, "invalid log level: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for LoggingError {}
+
+// The subscriber logging as `config` says, to be installed for the whole
+// process with `tracing::dispatcher::set_global_default` or for a scope with
+// `tracing::dispatcher::set_default`.
+pub fn dispatch(config: &LoggingConfig) -> Result<Dispatch, LoggingError> {
+    let filter =
+        EnvFilter::try_new(&config.level).map_err(|err| LoggingError::Filter(err.to_string()))?;
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    Ok(match (&config.file, config.format) {
+        (Some(path), LogFormat::Json) => Dispatch::new(
+            builder
+                .json()
+                .with_writer(Mutex::new(File::create(path).map_err(LoggingError::Io)?))
+                .finish(),
+        ),
+        (Some(path), LogFormat::Pretty) => Dispatch::new(
+            builder
+                .pretty()
+                .with_ansi(false)
+                .with_writer(Mutex::new(File::create(path).map_err(LoggingError::Io)?))
+                .finish(),
+        ),
+        (None, LogFormat::Json) => {
+            Dispatch::new(builder.json().with_writer(std::io::stderr).finish())
+        }
+        (None, LogFormat::Pretty) => {
+            Dispatch::new(builder.pretty().with_writer(std::io::stderr).finish())
+        }
+    })
+}
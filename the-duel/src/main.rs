@@ -1,553 +1,3073 @@
-use core::num;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
 use std::io::Write;
+use std::path::Path;
 use std::rc::Rc;
-use std::{cell::RefCell, fs::File};
 
-use rand::{Rng, SeedableRng};
-use rand_chacha::ChaCha12Rng;
+use rand::SeedableRng;
+use tracing::dispatcher::DefaultGuard;
 
-#[derive(Clone)]
-pub enum Action {
-    ATTACK,
-    FINCH,
-}
-pub enum GameOutcome {
-    WIN(u64),
-    TIE,
-    CONTINUE,
-    INTERRUPTED,
+use the_duel::agents::{
+    DuelPolicy, GameAgent, HawkDoveAgent, HawkDoveStrategy, MatrixAgent, MatrixStrategy,
+    PenniesAgent, PenniesStrategy, PrisonerAgent, PrisonerStrategy, PublicGoodsAgent,
+    PublicGoodsStrategy, RandomFighter, RpsAgent, RpsStrategy, SharedRng, TacticianAgent,
+    UltimatumAgent, UltimatumStrategy,
+};
+use the_duel::alpha_rank::AlphaRanking;
+#[cfg(feature = "websocket")]
+use the_duel::broadcast::BroadcastObserver;
+use the_duel::cfr::{
+    MatrixSolution, exploitability, solve_by_fictitious_play, solve_by_regret_matching,
+    solve_matrix_game,
+};
+use the_duel::config::ConfigError;
+use the_duel::ecology::payoff_matrix;
+use the_duel::evolution::GenerationSummary;
+use the_duel::exploitability::{GAME_VALUE, PolicyObserver, best_response_value};
+use the_duel::extensive_form::kuhn_poker_text;
+use the_duel::free_for_all::FreeForAllResults;
+use the_duel::gridworld::GridWorld;
+use the_duel::handicap::HandicapResult;
+use the_duel::hawk_dove::{AttackShareObserver, duel_stage_game, symmetric_ess};
+use the_duel::hmm::HiddenMarkovModel;
+use the_duel::logging::{self, LoggingConfig};
+use the_duel::nash::{NashEquilibrium, duel_bimatrix, nash_equilibria};
+use the_duel::nash_averaging::{NashAverage, nash_average};
+use the_duel::network::NetworkDuel;
+use the_duel::neural::TrainingConfig;
+use the_duel::observer::{DecisionLog, PayoffObserver, Scored};
+use the_duel::optimize::Evaluation;
+use the_duel::oracle::OracleResults;
+use the_duel::output::{OutputFormat, csv_field, write_results};
+use the_duel::page_rank::PageRanking;
+use the_duel::plot::{hit_point_svg, lattice_svg};
+#[cfg(feature = "plugins")]
+use the_duel::plugin::load_plugins;
+#[cfg(feature = "progress")]
+use the_duel::progress::ProgressObserver;
+use the_duel::public_goods::{PublicGoodsConfig, PublicGoodsResults};
+use the_duel::repeated::{DiscountedPayoffObserver, StageAgent};
+use the_duel::replicator::FixedPoint;
+use the_duel::rng_audit::{self, AuditedRng};
+use the_duel::seed_search::{GameCondition, SeedSearch};
+use the_duel::self_play::SelfPlayConfig;
+use the_duel::solvers::{
+    Mdp, MdpSolution, duel_state, modified_policy_iteration, policy_iteration, value_iteration,
+};
+use the_duel::stats::{DistributionObserver, PairwiseComparison, pairwise_comparisons};
+use the_duel::sweep::ParameterRange;
+use the_duel::team::TeamResults;
+use the_duel::tournament::{PairingSchedule, Tournament, game_seed};
+#[cfg(feature = "tui")]
+use the_duel::tui::TuiObserver;
+use the_duel::voting::Voting;
+use the_duel::{
+    Action, AgentRegistry, AlphaRank, Arena, DamageMatrix, Ecology, ExperimentConfig, Game,
+    GameObserver, GameOutcome, GameSettings, GameState, GameTree, HandPayoffs, HawkDove,
+    HawkDovePayoffs, MatchingPennies, MatrixGame, MoranProcess, Move, PageRank, PayoffMatrix,
+    PenniesPayoffs, PrisonersDilemma, PublicGoods, PublicGoodsTournament, RatingObserver, Repeated,
+    Replay, ReplicatorDynamics, RockPaperScissors, RuleVariant, SimultaneousGame, Sweep,
+    TournamentResults, Ultimatum,
+};
+
+fn run_experiment(
+    config: &ExperimentConfig,
+    registry: &AgentRegistry,
+    tui: bool,
+    broadcast: Option<&str>,
+    progress: bool,
+) {
+    let alternative_mode = config.bracket.is_some()
+        || config.swiss.is_some()
+        || config.lattice.is_some()
+        || config.evolution.is_some()
+        || config.coevolution.is_some()
+        || config.optimization.is_some()
+        || config.oracle.is_some()
+        || config.handicap_search.is_some()
+        || config.free_for_all.is_some()
+        || config.team_duel.is_some()
+        || config.training.is_some();
+    if alternative_mode && tui {
+        exit_with_error("the terminal viewer only supports the pairing schedule");
+    }
+    if alternative_mode && broadcast.is_some() {
+        exit_with_error("the live broadcast only supports the pairing schedule");
+    }
+    if alternative_mode && progress {
+        exit_with_error("the progress bar only supports the pairing schedule");
+    }
+    if let Some(bracket) = &config.bracket {
+        let results = config
+            .build_bracket(registry, bracket)
+            .unwrap_or_else(|err| exit_with_error(err))
+            .run();
+        println!("{}", results);
+        return;
+    }
+    if let Some(evolution) = &config.evolution {
+        let rng = config.rng();
+        let summaries = config
+            .build_evolution(registry, evolution, &rng)
+            .and_then(|evolution| evolution.run(&rng).map_err(ConfigError::Agent))
+            .unwrap_or_else(|err| exit_with_error(err));
+        print_evolution(&summaries);
+        if let Some(path) = &config.output.evolution {
+            write_evolution(path, &summaries);
+        }
+        return;
+    }
+    if let Some(coevolution) = &config.coevolution {
+        let rng = config.rng();
+        let summaries = config
+            .build_coevolution(registry, coevolution)
+            .and_then(|mut coevolution| coevolution.run(&rng))
+            .unwrap_or_else(|err| exit_with_error(err));
+        print_evolution(&summaries);
+        if let Some(path) = &config.output.coevolution {
+            write_evolution(path, &summaries);
+        }
+        if let Some(path) = &coevolution.archive {
+            println!("Hall of fame written to {}", path);
+        }
+        return;
+    }
+    if let Some(optimization) = &config.optimization {
+        let rng = config.rng();
+        let evaluations = config
+            .build_optimization(registry, optimization, &rng)
+            .and_then(|optimization| optimization.run(&rng).map_err(ConfigError::Agent))
+            .unwrap_or_else(|err| exit_with_error(err));
+        print_optimization(&evaluations);
+        if let Some(path) = &config.output.optimization {
+            write_optimization(path, &evaluations);
+        }
+        return;
+    }
+    if let Some(oracle) = &config.oracle {
+        let rng = config.rng();
+        let results = config
+            .build_oracle(registry, oracle, &rng)
+            .and_then(|oracle| oracle.run(&rng).map_err(ConfigError::Agent))
+            .unwrap_or_else(|err| exit_with_error(err));
+        print_oracle(&results);
+        if let Some(path) = &config.output.oracle {
+            write_oracle(path, &results);
+        }
+        return;
+    }
+    if let Some(search) = &config.handicap_search {
+        let result = config
+            .build_handicap_search(registry, search)
+            .unwrap_or_else(|err| exit_with_error(err))
+            .run();
+        print_handicap_search(&result);
+        if let Some(path) = &config.output.handicap_search {
+            write_handicap_search(path, &result);
+        }
+        return;
+    }
+    if let Some(free_for_all) = &config.free_for_all {
+        let rng = config.rng();
+        let results = config
+            .build_free_for_all(registry, free_for_all, &rng)
+            .unwrap_or_else(|err| exit_with_error(err))
+            .run(&rng);
+        print_free_for_all(&results);
+        if let Some(path) = &config.output.free_for_all {
+            write_free_for_all(path, &results);
+        }
+        return;
+    }
+    if let Some(team_duel) = &config.team_duel {
+        let rng = config.rng();
+        let results = config
+            .build_team_duel(registry, team_duel, &rng)
+            .unwrap_or_else(|err| exit_with_error(err))
+            .run(&rng);
+        print_team_duel(&results);
+        if let Some(path) = &config.output.team_duel {
+            write_team_duel(path, &results);
+        }
+        return;
+    }
+    if let Some(training) = &config.training {
+        run_training(config, registry, training);
+        return;
+    }
+    if let Some(lattice) = &config.lattice {
+        let mut grid = config
+            .build_lattice(registry, lattice)
+            .unwrap_or_else(|err| exit_with_error(err));
+        let maps = grid.run(lattice.generations);
+        let agent_names: Vec<String> = grid.agents.iter().map(|a| a.strategy_name()).collect();
+        print_lattice(&agent_names, &maps);
+        if let Some(path) = &config.output.lattice {
+            write_lattice(path, grid.width, &maps);
+        }
+        if let Some(path) = &config.output.lattice_plot {
+            let last = maps.len() - 1;
+            std::fs::write(
+                path,
+                lattice_svg(&maps[last], grid.width, &agent_names, last),
+            )
+            .unwrap_or_else(|err| exit_with_error(err));
+            println!("Lattice plot written to {}", path);
+        }
+        return;
+    }
+    if let Some(swiss) = &config.swiss {
+        let results = config
+            .build_swiss(registry, swiss)
+            .unwrap_or_else(|err| exit_with_error(err))
+            .run();
+        print!("{}", results);
+        return;
+    }
+
+    let tournament = config
+        .build_tournament(registry)
+        .unwrap_or_else(|err| exit_with_error(err));
+    let mut rating_observer = config
+        .rating
+        .as_ref()
+        .map(|rating| RatingObserver::new(tournament.agents.len(), rating));
+    let mut distribution_observer = DistributionObserver::new();
+    let mut attack_share_observer = (config.hawk_dove || config.nash)
+        .then(|| AttackShareObserver::new(tournament.agents.len()));
+    let mut policy_observer = config
+        .exploitability
+        .then(|| PolicyObserver::new(tournament.agents.len()));
+    #[cfg(feature = "tui")]
+    let mut tui_observer = tui.then(|| {
+        TuiObserver::for_tournament(
+            tournament
+                .agents
+                .iter()
+                .map(|a| a.strategy_name())
+                .collect(),
+            config.max_hit_points,
+            tournament.schedule.pairings(tournament.agents.len()).len(),
+            tournament.num_retrials,
+        )
+    });
+    #[cfg(not(feature = "tui"))]
+    if tui {
+        require_tui();
+    }
+    #[cfg(feature = "websocket")]
+    let mut broadcast_observer = broadcast.map(|address| {
+        let observer = BroadcastObserver::bind(
+            address,
+            tournament
+                .agents
+                .iter()
+                .map(|a| a.strategy_name())
+                .collect(),
+            config.max_hit_points,
+        )
+        .unwrap_or_else(|err| exit_with_error(err));
+        println!("Broadcasting events on ws://{}", observer.local_address());
+        observer
+    });
+    #[cfg(not(feature = "websocket"))]
+    if broadcast.is_some() {
+        exit_with_error("this build has no live broadcast (enable the 'websocket' feature)");
+    }
+    #[cfg(feature = "progress")]
+    let mut progress_observer = progress.then(|| {
+        ProgressObserver::new(
+            tournament.schedule.pairings(tournament.agents.len()).len(),
+            tournament.num_retrials * tournament.format.best_of,
+        )
+    });
+    #[cfg(not(feature = "progress"))]
+    if progress {
+        exit_with_error("this build has no progress bar (enable the 'progress' feature)");
+    }
+    let results = {
+        let mut observers: Vec<&mut dyn GameObserver> = vec![&mut distribution_observer];
+        if let Some(observer) = rating_observer.as_mut() {
+            observers.push(observer);
+        }
+        if let Some(observer) = attack_share_observer.as_mut() {
+            observers.push(observer);
+        }
+        if let Some(observer) = policy_observer.as_mut() {
+            observers.push(observer);
+        }
+        #[cfg(feature = "tui")]
+        if let Some(observer) = tui_observer.as_mut() {
+            observers.push(observer);
+        }
+        #[cfg(feature = "websocket")]
+        if let Some(observer) = broadcast_observer.as_mut() {
+            observers.push(observer);
+        }
+        #[cfg(feature = "progress")]
+        if let Some(observer) = progress_observer.as_mut() {
+            observers.push(observer);
+        }
+        tournament.run_observed(&mut observers)
+    };
+    #[cfg(feature = "progress")]
+    if let Some(observer) = progress_observer {
+        observer.finish();
+    }
+    #[cfg(feature = "tui")]
+    if let Some(observer) = tui_observer {
+        observer.finish();
+    }
+    let win_matrix = results.win_matrix();
+
+    println!("{:?}", win_matrix);
+
+    if let Some(path) = &config.output.win_matrix {
+        let written = write_results(
+            Path::new(path),
+            &config.output.formats,
+            &results,
+            &config.metadata(),
+        )
+        .unwrap_or_else(|err| exit_with_error(err));
+        for path in written {
+            println!("Results written to {}", path.display());
+        }
+    }
+
+    let comparisons = pairwise_comparisons(&results, config.output.confidence);
+    print_significant_comparisons(&results, &comparisons, 1.0 - config.output.confidence);
+    if let Some(path) = &config.output.statistics {
+        write_comparisons(path, &results, &comparisons);
+    }
+
+    print_distributions(&results, &distribution_observer);
+    if let Some(path) = &config.output.distributions {
+        write_distributions(path, &results, &distribution_observer);
+    }
+
+    if let Some(observer) = &rating_observer {
+        print_ratings(&results.agent_names, observer, results.num_retrials);
+    }
+
+    if let Some(ecology) = &config.ecology {
+        let trajectory = Ecology::from_results(&results, ecology).run(ecology.generations);
+        print_ecology(&results.agent_names, &trajectory);
+        if let Some(path) = &config.output.ecology {
+            write_ecology(path, &results.agent_names, &trajectory);
+        }
+    }
+
+    if let Some(replicator) = &config.replicator {
+        let dynamics = ReplicatorDynamics::new(payoff_matrix(&results), replicator.step_size);
+        let trajectories: Vec<Vec<Vec<f64>>> = dynamics
+            .starting_points(replicator.starts, &mut *config.rng().borrow_mut())
+            .into_iter()
+            .map(|start| dynamics.trajectory(start, replicator.steps, replicator.tolerance))
+            .collect();
+        let fixed_points = dynamics.fixed_points(&trajectories, replicator.tolerance);
+        print_fixed_points(&results.agent_names, &fixed_points, trajectories.len());
+        if let Some(path) = &config.output.replicator {
+            write_trajectories(path, &results.agent_names, &trajectories);
+        }
+    }
+
+    if let Some(moran) = &config.moran {
+        let process = MoranProcess::new(payoff_matrix(&results), moran);
+        let rng = config.rng();
+        let fixation = process.fixation_probabilities(moran.runs, &mut *rng.borrow_mut());
+        print_fixation(&results.agent_names, &fixation, moran.population_size);
+        if moran.mutation_rate > 0.0 {
+            let abundance =
+                process.abundance(moran.mutation_rate, moran.steps, &mut *rng.borrow_mut());
+            print_abundance(&results.agent_names, &abundance, moran.mutation_rate);
+        }
+        if let Some(path) = &config.output.moran {
+            write_fixation(path, &results.agent_names, &fixation);
+        }
+    }
+
+    if let Some(alpha_rank) = &config.alpha_rank {
+        let ranking = AlphaRank::new(payoff_matrix(&results), alpha_rank)
+            .rank(alpha_rank.tolerance, alpha_rank.max_iterations);
+        print_alpha_rank(&results.agent_names, &ranking, alpha_rank.alpha);
+        if let Some(path) = &config.output.alpha_rank {
+            write_alpha_rank(path, &results.agent_names, &ranking);
+        }
+    }
+
+    if let Some(nash_averaging) = &config.nash_averaging {
+        let average = nash_average(&payoff_matrix(&results), nash_averaging);
+        print_nash_average(&results.agent_names, &average);
+        if let Some(path) = &config.output.nash_averaging {
+            write_nash_average(path, &results.agent_names, &average);
+        }
+    }
+
+    if let Some(page_rank) = &config.page_rank {
+        let ranking = PageRank::new(&payoff_matrix(&results)).rank(page_rank);
+        print_page_rank(&results.agent_names, &ranking, page_rank.damping);
+        if let Some(path) = &config.output.page_rank {
+            write_page_rank(path, &results.agent_names, &ranking);
+        }
+    }
+
+    if config.voting {
+        print_voting(&results.agent_names, &Voting::new(&payoff_matrix(&results)));
+    }
+
+    if let Some(observer) = &attack_share_observer {
+        if config.hawk_dove {
+            print_attack_shares(&results.agent_names, &config.damage_matrix(), observer);
+        }
+        if config.nash {
+            print_nash_attack_shares(&results.agent_names, &config.damage_matrix(), observer);
+        }
+    }
+    if let Some(observer) = &policy_observer {
+        print_exploitability(
+            &results.agent_names,
+            config.max_hit_points,
+            &config.damage_matrix(),
+            observer,
+        );
+    }
 }
 
-struct PlayerState {
+// Prints the chance of the best response against every agent's recorded
+// policy to win from full hit points, and how much it exceeds one half.
+fn print_exploitability(
+    agent_names: &[String],
     max_hit_points: i64,
-    current_hit_points: i64,
+    damage: &DamageMatrix,
+    observer: &PolicyObserver,
+) {
+    println!("Exploitability [best response's chance of winning, exploitability]:");
+    for (agent, name) in agent_names.iter().enumerate() {
+        let policy = &observer.policies[agent];
+        let value = best_response_value(max_hit_points, damage, |own, opposing| {
+            policy.attack_probability(own, opposing)
+        });
+        println!(" {}: {:.3}, {:.3}", name, value, value - GAME_VALUE);
+    }
+}
+
+// Prints the equilibria of a turn of the duel as a bimatrix game and every
+// agent's share of attacks, to compare with the equilibria's.
+fn print_nash_attack_shares(
+    agent_names: &[String],
+    damage: &DamageMatrix,
+    observer: &AttackShareObserver,
+) {
+    let bimatrix = duel_bimatrix(damage);
+    let equilibria = nash_equilibria(&bimatrix).unwrap_or_else(|err| exit_with_error(err));
+    println!("A turn as a bimatrix game [attack, finch]: {:?}", bimatrix);
+    let actions = [String::from("attack"), String::from("finch")];
+    print_nash_equilibria(&equilibria, &actions, &actions);
+    let attack = Action::ATTACK.index();
+    println!("Attack shares [share, distance to the nearest equilibrium share]:");
+    for (agent, name) in agent_names.iter().enumerate() {
+        let share = observer.attack_share(agent);
+        let distance = equilibria
+            .iter()
+            .flat_map(|equilibrium| [equilibrium.row[attack], equilibrium.column[attack]])
+            .map(|equilibrium_share| (share - equilibrium_share).abs())
+            .fold(f64::INFINITY, f64::min);
+        println!(" {}: {:.3}, {:.3}", name, share, distance);
+    }
 }
 
-struct GameState {
-    player_one_state: PlayerState,
-    player_two_state: PlayerState,
-    player_one_action: Option<Action>,
-    player_two_action: Option<Action>,
+fn print_nash_equilibria(equilibria: &[NashEquilibrium], rows: &[String], columns: &[String]) {
+    let mix = |probabilities: &[f64], names: &[String]| {
+        probabilities
+            .iter()
+            .zip(names)
+            .filter(|(probability, _)| **probability > 0.0)
+            .map(|(probability, name)| format!("{} {:.3}", name, probability))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    println!("Nash equilibria [row mix; column mix; payoffs]:");
+    for equilibrium in equilibria {
+        println!(
+            " {} {}; {}; {:.3}, {:.3}",
+            if equilibrium.is_pure() {
+                "pure "
+            } else {
+                "mixed"
+            },
+            mix(&equilibrium.row, rows),
+            mix(&equilibrium.column, columns),
+            equilibrium.payoffs[0],
+            equilibrium.payoffs[1]
+        );
+    }
 }
 
-trait GameAgent {
-    fn decide_action(
-        &mut self,
-        own_player_state: &PlayerState,
-        opposing_player_actions: &Option<Action>,
-        opposing_player_state: &Option<PlayerState>,
-    ) -> Action;
+fn print_attack_shares(
+    agent_names: &[String],
+    damage: &DamageMatrix,
+    observer: &AttackShareObserver,
+) {
+    let stage_game = duel_stage_game(damage);
+    let stable = symmetric_ess(&stage_game);
+    println!(
+        "A turn as a Hawk-Dove game [attack, finch]: {:?}, ESS attack shares: {}",
+        stage_game,
+        if stable.is_empty() {
+            String::from("none")
+        } else {
+            stable
+                .iter()
+                .map(|share| format!("{:.3}", share))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    );
+    println!("Attack shares [share, distance to the nearest ESS]:");
+    for (agent, name) in agent_names.iter().enumerate() {
+        let share = observer.attack_share(agent);
+        let distance = stable
+            .iter()
+            .map(|ess| (share - ess).abs())
+            .fold(f64::INFINITY, f64::min);
+        println!(" {}: {:.3}, {:.3}", name, share, distance);
+    }
+}
 
-    fn strategy_name(&self) -> String;
+// Logs to standard error, warnings only unless RUST_LOG asks for more.
+fn init_logging() {
+    let console = LoggingConfig {
+        level: std::env::var("RUST_LOG").unwrap_or_else(|_| "warn".to_string()),
+        ..LoggingConfig::default()
+    };
+    let dispatch = logging::dispatch(&console).unwrap_or_else(|err| exit_with_error(err));
+    tracing::dispatcher::set_global_default(dispatch).expect("logging is set up once");
+}
 
-    fn copy_self_to_anom(&self) -> Box<dyn GameAgent>;
+// Logs of an experiment go where its configuration says while the guard
+// lives, to standard error if it does not say.
+fn experiment_logging(config: &ExperimentConfig) -> Option<DefaultGuard> {
+    config.logging.as_ref().map(|logging| {
+        let dispatch = logging::dispatch(logging).unwrap_or_else(|err| exit_with_error(err));
+        tracing::dispatcher::set_default(&dispatch)
+    })
 }
 
-struct Game {
-    player_one_agent: Box<dyn GameAgent>,
-    player_two_agent: Box<dyn GameAgent>,
+fn exit_with_error(err: impl std::fmt::Display) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(1);
 }
 
-impl Game {
-    fn step_game(&mut self, state: &mut GameState) {
-        // get actions for current game state
-        let player_one_action = self.player_one_agent.decide_action(
-            &state.player_one_state,
-            &state.player_two_action,
-            &None,
+fn print_significant_comparisons(
+    results: &TournamentResults,
+    comparisons: &[PairwiseComparison],
+    significance: f64,
+) {
+    println!("Significant results (p < {:.3}):", significance);
+    for comparison in comparisons.iter().filter(|c| c.beats(significance)) {
+        println!(
+            " {} beats {}: win rate {:.3} [{:.3}, {:.3}], p = {:.2e}",
+            results.agent_names[comparison.agent],
+            results.agent_names[comparison.opponent],
+            comparison.win_rate,
+            comparison.win_rate_interval.0,
+            comparison.win_rate_interval.1,
+            comparison.p_value
         );
-        let player_two_action = self.player_two_agent.decide_action(
-            &state.player_two_state,
-            &state.player_one_action,
-            &None,
+    }
+}
+
+fn write_comparisons(path: &str, results: &TournamentResults, comparisons: &[PairwiseComparison]) {
+    let mut output = File::create(path).unwrap();
+    writeln!(
+        output,
+        "agent,opponent,wins,ties,losses,win_rate,win_rate_lower,win_rate_upper,p_value"
+    )
+    .unwrap();
+    for comparison in comparisons {
+        writeln!(
+            output,
+            "{},{},{},{},{},{},{},{},{:e}",
+            csv_field(&results.agent_names[comparison.agent]),
+            csv_field(&results.agent_names[comparison.opponent]),
+            comparison.wins,
+            comparison.ties,
+            comparison.losses,
+            comparison.win_rate,
+            comparison.win_rate_interval.0,
+            comparison.win_rate_interval.1,
+            comparison.p_value
+        )
+        .unwrap();
+    }
+}
+
+fn print_distributions(results: &TournamentResults, observer: &DistributionObserver) {
+    println!("Game length and winner HP [median (p10-p90)]:");
+    for ((player_one, player_two), distributions) in &observer.pairings {
+        let Some(lengths) = distributions.length_summary() else {
+            continue;
+        };
+        let margin = match distributions.margin_summary() {
+            Some(margin) => format!("{:.1} ({:.1}-{:.1})", margin.median, margin.p10, margin.p90),
+            None => String::from("-"),
+        };
+        println!(
+            " {} vs {}: {:.1} turns ({:.1}-{:.1}), winner HP {}",
+            results.agent_names[*player_one],
+            results.agent_names[*player_two],
+            lengths.median,
+            lengths.p10,
+            lengths.p90,
+            margin
         );
-        // Decide what happens
+    }
+}
 
-        // Check whether player attacks, or if player blocks
-        match (player_one_action, player_two_action) {
-            // Both Attack!
-            (Action::ATTACK, Action::ATTACK) => {
-                state.player_one_state.current_hit_points -= 1;
-                state.player_two_state.current_hit_points -= 1;
-            }
-            // First Attacks, Second Counters!
-            (Action::ATTACK, Action::FINCH) => {
-                state.player_one_state.current_hit_points -= 1;
-            }
-            // First Counters, Second Attacks!
-            (Action::FINCH, Action::ATTACK) => {
-                state.player_two_state.current_hit_points -= 1;
-            }
-            (Action::FINCH, Action::FINCH) => {
-                state.player_one_state.current_hit_points -= 1;
-                state.player_two_state.current_hit_points -= 1;
-            }
+fn write_distributions(path: &str, results: &TournamentResults, observer: &DistributionObserver) {
+    let mut output = File::create(path).unwrap();
+    writeln!(
+        output,
+        "player_one,player_two,statistic,count,mean,min,p10,p25,median,p75,p90,max"
+    )
+    .unwrap();
+    for ((player_one, player_two), distributions) in &observer.pairings {
+        let summaries = [
+            ("length", distributions.length_summary()),
+            ("winner_hit_points", distributions.margin_summary()),
+        ];
+        for (statistic, summary) in summaries {
+            let Some(summary) = summary else {
+                continue;
+            };
+            writeln!(
+                output,
+                "{},{},{},{},{},{},{},{},{},{},{},{}",
+                csv_field(&results.agent_names[*player_one]),
+                csv_field(&results.agent_names[*player_two]),
+                statistic,
+                summary.count,
+                summary.mean,
+                summary.min,
+                summary.p10,
+                summary.p25,
+                summary.median,
+                summary.p75,
+                summary.p90,
+                summary.max
+            )
+            .unwrap();
         }
     }
+}
 
-    fn check_end_condition(&self, state: &GameState) -> GameOutcome {
-        if state.player_one_state.current_hit_points <= 0
-            && state.player_two_state.current_hit_points <= 0
-        {
-            return GameOutcome::TIE;
-        }
-        if state.player_one_state.current_hit_points <= 0 {
-            return GameOutcome::WIN(2);
-        }
-        if state.player_two_state.current_hit_points <= 0 {
-            return GameOutcome::WIN(1);
-        }
-        return GameOutcome::CONTINUE;
+fn print_ecology(agent_names: &[String], trajectory: &[Vec<f64>]) {
+    let generations = trajectory.len() - 1;
+    println!("Population shares over {} generations:", generations);
+    let mut checkpoints = vec![0, generations / 10, generations / 2, generations];
+    checkpoints.dedup();
+    for generation in checkpoints {
+        let shares = &trajectory[generation];
+        let mut order: Vec<usize> = (0..shares.len()).filter(|&a| shares[a] > 0.0).collect();
+        order.sort_by(|a, b| shares[*b].total_cmp(&shares[*a]));
+        let leaders: Vec<String> = order
+            .iter()
+            .take(3)
+            .map(|&agent| format!("{} {:.3}", agent_names[agent], shares[agent]))
+            .collect();
+        println!(
+            " Generation {}: {} surviving, led by {}",
+            generation,
+            order.len(),
+            leaders.join(", ")
+        );
     }
 }
 
-#[derive(Clone)]
-struct AttackAgent;
+fn write_ecology(path: &str, agent_names: &[String], trajectory: &[Vec<f64>]) {
+    let mut output = File::create(path).unwrap();
+    let header: Vec<String> = agent_names.iter().map(|name| csv_field(name)).collect();
+    writeln!(output, "generation,{}", header.join(",")).unwrap();
+    for (generation, shares) in trajectory.iter().enumerate() {
+        let shares: Vec<String> = shares.iter().map(|share| share.to_string()).collect();
+        writeln!(output, "{},{}", generation, shares.join(",")).unwrap();
+    }
+}
 
-impl GameAgent for AttackAgent {
-    fn decide_action(
-        &mut self,
-        _own_player_state: &PlayerState,
-        _opposing_player_actions: &Option<Action>,
-        _opposing_player_state: &Option<PlayerState>,
-    ) -> Action {
-        return Action::ATTACK;
+fn print_fixed_points(agent_names: &[String], fixed_points: &[FixedPoint], num_starts: usize) {
+    let rested: usize = fixed_points.iter().map(|point| point.starts.len()).sum();
+    println!(
+        "Replicator dynamics: {} of {} trajectories came to rest at {} fixed points",
+        rested,
+        num_starts,
+        fixed_points.len()
+    );
+    for point in fixed_points {
+        let mut support: Vec<usize> = (0..point.shares.len())
+            .filter(|&agent| point.shares[agent] > 1e-4)
+            .collect();
+        support.sort_by(|a, b| point.shares[*b].total_cmp(&point.shares[*a]));
+        let support: Vec<String> = support
+            .iter()
+            .map(|&agent| format!("{} {:.3}", agent_names[agent], point.shares[agent]))
+            .collect();
+        println!(
+            " reached from {} start(s){}: {}",
+            point.starts.len(),
+            if point.saturated { ", Nash" } else { "" },
+            support.join(", ")
+        );
     }
+}
 
-    fn strategy_name(&self) -> String {
-        return String::from("Always Attack");
+fn write_trajectories(path: &str, agent_names: &[String], trajectories: &[Vec<Vec<f64>>]) {
+    let mut output = File::create(path).unwrap();
+    let header: Vec<String> = agent_names.iter().map(|name| csv_field(name)).collect();
+    writeln!(output, "start,step,{}", header.join(",")).unwrap();
+    for (start, trajectory) in trajectories.iter().enumerate() {
+        for (step, shares) in trajectory.iter().enumerate() {
+            let shares: Vec<String> = shares.iter().map(|share| share.to_string()).collect();
+            writeln!(output, "{},{},{}", start, step, shares.join(",")).unwrap();
+        }
     }
+}
 
-    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
-        Box::new(Self {})
+fn print_fixation(agent_names: &[String], fixation: &[Vec<f64>], population_size: usize) {
+    let neutral = 1.0 / population_size as f64;
+    let num_agents = agent_names.len();
+    // Average chance of a single mutant to take over a population of any other strategy
+    let invasion: Vec<f64> = (0..num_agents)
+        .map(|mutant| {
+            (0..num_agents)
+                .filter(|&resident| resident != mutant)
+                .map(|resident| fixation[mutant][resident])
+                .sum::<f64>()
+                / (num_agents - 1).max(1) as f64
+        })
+        .collect();
+    let mut order: Vec<usize> = (0..num_agents).collect();
+    order.sort_by(|a, b| invasion[*b].total_cmp(&invasion[*a]));
+    println!(
+        "Moran fixation probabilities of a single mutant (neutral {:.4}):",
+        neutral
+    );
+    for agent in order {
+        let resisted = (0..num_agents)
+            .filter(|&mutant| mutant != agent)
+            .all(|mutant| fixation[mutant][agent] < neutral);
+        println!(
+            " {}: {:.4} on average{}",
+            agent_names[agent],
+            invasion[agent],
+            if resisted {
+                ", resists every invader"
+            } else {
+                ""
+            }
+        );
     }
 }
 
-#[derive(Clone)]
-struct MirrorAgent;
+fn print_abundance(agent_names: &[String], abundance: &[f64], mutation_rate: f64) {
+    let mut order: Vec<usize> = (0..abundance.len()).collect();
+    order.sort_by(|a, b| abundance[*b].total_cmp(&abundance[*a]));
+    println!("Long-run abundance (mutation rate {}):", mutation_rate);
+    for agent in order {
+        println!(" {}: {:.4}", agent_names[agent], abundance[agent]);
+    }
+}
 
-impl GameAgent for MirrorAgent {
-    fn decide_action(
-        &mut self,
-        _own_player_state: &PlayerState,
-        opposing_player_actions: &Option<Action>,
-        _opposing_player_state: &Option<PlayerState>,
-    ) -> Action {
-        if let Some(action) = opposing_player_actions {
-            return action.clone();
-        } else {
-            return Action::ATTACK;
-        }
+// Row i, column j holds the fixation probability of a mutant i among residents j.
+fn write_fixation(path: &str, agent_names: &[String], fixation: &[Vec<f64>]) {
+    let mut output = File::create(path).unwrap();
+    let header: Vec<String> = agent_names.iter().map(|name| csv_field(name)).collect();
+    writeln!(output, "mutant,{}", header.join(",")).unwrap();
+    for (mutant, row) in fixation.iter().enumerate() {
+        let row: Vec<String> = row.iter().map(|p| p.to_string()).collect();
+        writeln!(
+            output,
+            "{},{}",
+            csv_field(&agent_names[mutant]),
+            row.join(",")
+        )
+        .unwrap();
     }
+}
 
-    fn strategy_name(&self) -> String {
-        return String::from("Always Mirror the opposing action");
+// Prints the agents by decreasing alpha-rank mass, each with the agent a
+// population of it is most likely taken over by.
+fn print_alpha_rank(agent_names: &[String], ranking: &AlphaRanking, alpha: f64) {
+    println!(
+        "Alpha-rank (alpha {}, {} iterations) [mass, most likely invader]:",
+        alpha, ranking.iterations
+    );
+    for (rank, agent) in ranking.order().into_iter().enumerate() {
+        let invader = (0..agent_names.len())
+            .filter(|&mutant| mutant != agent)
+            .max_by(|a, b| ranking.fixation[*a][agent].total_cmp(&ranking.fixation[*b][agent]));
+        let invader = match invader {
+            Some(mutant) => format!(
+                "{} ({:.4})",
+                agent_names[mutant], ranking.fixation[mutant][agent]
+            ),
+            None => "-".to_string(),
+        };
+        println!(
+            " {}. {}: {:.4}, {}",
+            rank + 1,
+            agent_names[agent],
+            ranking.mass[agent],
+            invader
+        );
     }
+}
 
-    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
-        Box::new(Self {})
+fn write_alpha_rank(path: &str, agent_names: &[String], ranking: &AlphaRanking) {
+    let mut output = File::create(path).unwrap();
+    writeln!(output, "rank,agent,mass").unwrap();
+    for (rank, agent) in ranking.order().into_iter().enumerate() {
+        writeln!(
+            output,
+            "{},{},{}",
+            rank + 1,
+            csv_field(&agent_names[agent]),
+            ranking.mass[agent]
+        )
+        .unwrap();
     }
 }
 
-#[derive(Clone)]
-struct RandomAgent<T: Rng + 'static> {
-    current_random: Rc<RefCell<T>>,
-    probability_of_attack: f64,
+// Prints the agents by decreasing Nash averaging rating with their share of
+// the maximum entropy equilibrium.
+fn print_nash_average(agent_names: &[String], average: &NashAverage) {
+    println!(
+        "Nash averaging (temperature {}) [rating, equilibrium share]:",
+        average.temperature
+    );
+    for agent in average.order() {
+        println!(
+            " {}: {:.4}, {:.4}",
+            agent_names[agent], average.ratings[agent], average.shares[agent]
+        );
+    }
 }
 
-impl<T: Rng> GameAgent for RandomAgent<T> {
-    fn decide_action(
-        &mut self,
-        _own_player_state: &PlayerState,
-        _opposing_player_actions: &Option<Action>,
-        _opposing_player_state: &Option<PlayerState>,
-    ) -> Action {
-        let decision = self
-            .current_random
-            .borrow_mut()
-            .random_bool(self.probability_of_attack);
-        if decision {
-            return Action::ATTACK;
-        } else {
-            return Action::FINCH;
-        }
+fn write_nash_average(path: &str, agent_names: &[String], average: &NashAverage) {
+    let mut output = File::create(path).unwrap();
+    writeln!(output, "agent,rating,share").unwrap();
+    for agent in average.order() {
+        writeln!(
+            output,
+            "{},{},{}",
+            csv_field(&agent_names[agent]),
+            average.ratings[agent],
+            average.shares[agent]
+        )
+        .unwrap();
     }
+}
 
-    fn strategy_name(&self) -> String {
-        return format!("Attack with probability {}", self.probability_of_attack);
+fn print_page_rank(agent_names: &[String], ranking: &PageRanking, damping: f64) {
+    println!(
+        "PageRank (damping {}, {} iterations):",
+        damping, ranking.iterations
+    );
+    for (rank, agent) in ranking.order().into_iter().enumerate() {
+        println!(
+            " {}. {}: {:.4}",
+            rank + 1,
+            agent_names[agent],
+            ranking.ranks[agent]
+        );
     }
+}
 
-    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
-        Box::new(Self {
-            current_random: self.current_random.clone(),
-            probability_of_attack: self.probability_of_attack,
-        })
+fn write_page_rank(path: &str, agent_names: &[String], ranking: &PageRanking) {
+    let mut output = File::create(path).unwrap();
+    writeln!(output, "rank,agent,page_rank").unwrap();
+    for (rank, agent) in ranking.order().into_iter().enumerate() {
+        writeln!(
+            output,
+            "{},{},{}",
+            rank + 1,
+            csv_field(&agent_names[agent]),
+            ranking.ranks[agent]
+        )
+        .unwrap();
     }
 }
 
-#[derive(Clone)]
-struct OneStepDecisionProcessAgent {
-    cost_losing_hp: f64,
-    cost_not_losing_hp: f64,
-    cost_equivalent_exchange: f64,
-    num_turns: i64,
-    num_attacks: i64,
-}
-
-impl GameAgent for OneStepDecisionProcessAgent {
-    fn decide_action(
-        &mut self,
-        _own_player_state: &PlayerState,
-        opposing_player_actions: &Option<Action>,
-        _opposing_player_state: &Option<PlayerState>,
-    ) -> Action {
-        if let Some(ack) = opposing_player_actions {
-            match ack {
-                Action::ATTACK => {
-                    self.num_attacks += 1;
-                }
-                Action::FINCH => {}
-            };
+fn print_voting(agent_names: &[String], voting: &Voting) {
+    let scores = voting.copeland_scores();
+    println!("Copeland scores [agents beaten minus agents lost to]:");
+    for agent in voting.order() {
+        println!(" {}: {}", agent_names[agent], scores[agent]);
+    }
+    match voting.condorcet_winner() {
+        Some(winner) => println!("Condorcet winner: {}", agent_names[winner]),
+        None => println!("No Condorcet winner"),
+    }
+    let names = |agents: &[usize]| -> String {
+        let names: Vec<&str> = agents
+            .iter()
+            .map(|&agent| agent_names[agent].as_str())
+            .collect();
+        names.join(", ")
+    };
+    println!("Smith set: {}", names(&voting.smith_set()));
+    for cycle in voting.cycles() {
+        println!("Cycle of {} agents: {}", cycle.len(), names(&cycle));
+    }
+}
+
+fn print_lattice(agent_names: &[String], maps: &[Vec<usize>]) {
+    println!("Lattice cells per strategy:");
+    for (generation, cells) in maps.iter().enumerate() {
+        let mut counts = vec![0; agent_names.len()];
+        for &agent in cells {
+            counts[agent] += 1;
         }
-        self.num_turns += 1;
+        let mut order: Vec<usize> = (0..counts.len()).filter(|&a| counts[a] > 0).collect();
+        order.sort_by(|a, b| counts[*b].cmp(&counts[*a]));
+        let counts: Vec<String> = order
+            .iter()
+            .map(|&agent| format!("{} {}", agent_names[agent], counts[agent]))
+            .collect();
+        println!(" Generation {}: {}", generation, counts.join(", "));
+    }
+}
 
-        // Guesstimate probability of attack
-        let prob = (self.num_attacks as f64) / (self.num_turns as f64);
+fn write_lattice(path: &str, width: usize, maps: &[Vec<usize>]) {
+    let mut output = File::create(path).unwrap();
+    writeln!(output, "generation,row,column,agent").unwrap();
+    for (generation, cells) in maps.iter().enumerate() {
+        for (cell, agent) in cells.iter().enumerate() {
+            writeln!(
+                output,
+                "{},{},{},{}",
+                generation,
+                cell / width,
+                cell % width,
+                agent
+            )
+            .unwrap();
+        }
+    }
+}
 
-        let attack_reward =
-            self.cost_losing_hp * (1.0 - prob) + self.cost_equivalent_exchange * prob;
-        let finch_reward =
-            self.cost_not_losing_hp * prob + self.cost_equivalent_exchange * (1.0 - prob);
+fn print_evolution(summaries: &[GenerationSummary]) {
+    println!("Best genome per generation [fitness (population mean)]:");
+    for summary in summaries {
+        println!(
+            " population {} ({}) generation {}: {} {:.3} ({:.3})",
+            summary.population + 1,
+            summary.family.name(),
+            summary.generation,
+            summary.best.spec(),
+            summary.best_fitness,
+            summary.mean_fitness
+        );
+    }
+}
 
-        if attack_reward > finch_reward {
-            return Action::ATTACK;
+fn write_evolution(path: &str, summaries: &[GenerationSummary]) {
+    let mut output = File::create(path).unwrap();
+    writeln!(
+        output,
+        "population,family,generation,best_fitness,mean_fitness,best"
+    )
+    .unwrap();
+    for summary in summaries {
+        writeln!(
+            output,
+            "{},{},{},{},{},{}",
+            summary.population + 1,
+            summary.family.name(),
+            summary.generation,
+            summary.best_fitness,
+            summary.mean_fitness,
+            csv_field(&summary.best.spec())
+        )
+        .unwrap();
+    }
+}
+
+fn print_optimization(evaluations: &[Evaluation]) {
+    println!("Evaluations [average score]:");
+    for (index, evaluation) in evaluations.iter().enumerate() {
+        println!(
+            " {}: {} {:.3}",
+            index + 1,
+            evaluation.genome.spec(),
+            evaluation.score
+        );
+    }
+    if let Some(best) = evaluations
+        .iter()
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+    {
+        println!("Best: {} {:.3}", best.genome.spec(), best.score);
+    }
+}
+
+fn print_oracle(results: &OracleResults) {
+    println!("Double oracle [pool, best response, its score against the equilibrium]:");
+    for (index, iteration) in results.iterations.iter().enumerate() {
+        println!(
+            " {}: {} agents, {} {:.3}",
+            index + 1,
+            iteration.shares.len(),
+            iteration.best_response.genome.spec(),
+            iteration.best_response.score
+        );
+    }
+    println!(
+        "Equilibrium of {} agents{}:",
+        results.agent_names.len(),
+        if results.converged {
+            ""
         } else {
-            return Action::FINCH;
+            ", not converged"
         }
+    );
+    let mut order: Vec<usize> = (0..results.shares.len())
+        .filter(|&agent| results.shares[agent] > 1e-4)
+        .collect();
+    order.sort_by(|a, b| results.shares[*b].total_cmp(&results.shares[*a]));
+    for agent in order {
+        println!(
+            " {}: {:.3}",
+            results.agent_names[agent], results.shares[agent]
+        );
     }
+}
 
-    fn strategy_name(&self) -> String {
-        return format!("Estimate Probability of Attack, and design optimal one-step decision.");
+fn print_handicap_search(result: &HandicapResult) {
+    println!("Hit point ratios [hit points, score of the first agent]:");
+    for (index, step) in result.steps.iter().enumerate() {
+        println!(
+            " {}: {:.3} [{}/{}, {:.3}]",
+            index + 1,
+            step.ratio,
+            step.hit_points.0,
+            step.hit_points.1,
+            step.score
+        );
     }
+    println!("Evenly matched at a hit point ratio of {:.3}", result.ratio);
+}
 
-    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
-        Box::new(Self {
-            cost_losing_hp: self.cost_losing_hp,
-            cost_not_losing_hp: self.cost_not_losing_hp,
-            cost_equivalent_exchange: self.cost_equivalent_exchange,
-            num_turns: self.num_turns,
-            num_attacks: self.num_attacks,
-        })
+fn write_handicap_search(path: &str, result: &HandicapResult) {
+    let mut output = File::create(path).unwrap();
+    writeln!(
+        output,
+        "step,ratio,first_hit_points,second_hit_points,score"
+    )
+    .unwrap();
+    for (index, step) in result.steps.iter().enumerate() {
+        writeln!(
+            output,
+            "{},{},{},{},{}",
+            index + 1,
+            step.ratio,
+            step.hit_points.0,
+            step.hit_points.1,
+            step.score
+        )
+        .unwrap();
+    }
+}
+
+fn print_free_for_all(results: &FreeForAllResults) {
+    println!(
+        "Free-for-all at tables of {} [tables, wins, ties, draws, win rate]:",
+        results.table_size
+    );
+    for (agent, name) in results.agent_names.iter().enumerate() {
+        println!(
+            " {}: {}, {}, {}, {}, {:.3}",
+            name,
+            results.seated[agent],
+            results.wins[agent],
+            results.ties[agent],
+            results.draws[agent],
+            results.win_rate(agent)
+        );
     }
+    println!(
+        "A win rate of {:.3} is a fair share of the wins",
+        1.0 / results.table_size as f64
+    );
 }
 
-#[derive(Clone)]
-struct MarkovRandomAgent<T: Rng + 'static> {
-    current_random: Rc<RefCell<T>>,
-    change_to_attack_prob: f64,
-    change_to_finch_prob: f64,
-    current_strategy: Action,
-}
-
-impl<T: Rng + 'static> GameAgent for MarkovRandomAgent<T> {
-    fn decide_action(
-        &mut self,
-        _own_player_state: &PlayerState,
-        _opposing_player_actions: &Option<Action>,
-        _opposing_player_state: &Option<PlayerState>,
-    ) -> Action {
-        match self.current_strategy {
-            Action::ATTACK => {
-                let decision = self
-                    .current_random
-                    .borrow_mut()
-                    .random_bool(self.change_to_finch_prob);
-                if decision {
-                    self.current_strategy = Action::FINCH;
-                }
+fn write_free_for_all(path: &str, results: &FreeForAllResults) {
+    let mut output = File::create(path).unwrap();
+    writeln!(output, "agent,tables,wins,ties,draws,win_rate").unwrap();
+    for (agent, name) in results.agent_names.iter().enumerate() {
+        writeln!(
+            output,
+            "{},{},{},{},{},{}",
+            csv_field(name),
+            results.seated[agent],
+            results.wins[agent],
+            results.ties[agent],
+            results.draws[agent],
+            results.win_rate(agent)
+        )
+        .unwrap();
+    }
+}
+
+fn print_team_duel(results: &TeamResults) {
+    println!("Team duels [wins, ties, losses against every other team]:");
+    for (team, name) in results.team_names.iter().enumerate() {
+        println!(" {} (score {:.3}):", name, results.score(team));
+        for (opponent, record) in results.records[team].iter().enumerate() {
+            if opponent != team {
+                println!(
+                    "  vs {}: {}, {}, {}",
+                    results.team_names[opponent], record.wins, record.ties, record.losses
+                );
             }
-            Action::FINCH => {
-                let decision = self
-                    .current_random
-                    .borrow_mut()
-                    .random_bool(self.change_to_attack_prob);
-                if decision {
-                    self.current_strategy = Action::ATTACK;
-                }
+        }
+    }
+}
+
+fn write_team_duel(path: &str, results: &TeamResults) {
+    let mut output = File::create(path).unwrap();
+    writeln!(output, "team,opponent,wins,ties,losses").unwrap();
+    for (team, name) in results.team_names.iter().enumerate() {
+        for (opponent, record) in results.records[team].iter().enumerate() {
+            if opponent != team {
+                writeln!(
+                    output,
+                    "{},{},{},{},{}",
+                    csv_field(name),
+                    csv_field(&results.team_names[opponent]),
+                    record.wins,
+                    record.ties,
+                    record.losses
+                )
+                .unwrap();
             }
         }
+    }
+}
+
+fn write_optimization(path: &str, evaluations: &[Evaluation]) {
+    let mut output = File::create(path).unwrap();
+    writeln!(output, "evaluation,score,best_score,spec").unwrap();
+    let mut best_score = f64::NEG_INFINITY;
+    for (index, evaluation) in evaluations.iter().enumerate() {
+        best_score = best_score.max(evaluation.score);
+        writeln!(
+            output,
+            "{},{},{},{}",
+            index + 1,
+            evaluation.score,
+            best_score,
+            csv_field(&evaluation.genome.spec())
+        )
+        .unwrap();
+    }
+}
 
-        return self.current_strategy.clone();
+fn write_oracle(path: &str, results: &OracleResults) {
+    let mut output = File::create(path).unwrap();
+    writeln!(output, "iteration,pool,score,spec").unwrap();
+    for (index, iteration) in results.iterations.iter().enumerate() {
+        writeln!(
+            output,
+            "{},{},{},{}",
+            index + 1,
+            iteration.shares.len(),
+            iteration.best_response.score,
+            csv_field(&iteration.best_response.genome.spec())
+        )
+        .unwrap();
     }
+}
+
+#[cfg(feature = "neural")]
+fn run_training(config: &ExperimentConfig, registry: &AgentRegistry, training: &TrainingConfig) {
+    let rng = config.rng();
+    let (_, summaries) = config
+        .build_training(registry, training, &rng)
+        .and_then(|training| {
+            training
+                .run(&rng)
+                .map_err(|err| ConfigError::Invalid(err.to_string()))
+        })
+        .unwrap_or_else(|err| exit_with_error(err));
 
-    fn strategy_name(&self) -> String {
-        return format!(
-            "Markov Chain with probabilities {}, {}",
-            self.change_to_attack_prob, self.change_to_finch_prob
+    println!("Episodes [average score]:");
+    let block = (summaries.len() / 10).max(1);
+    for episodes in summaries.chunks(block) {
+        let score: f64 = episodes.iter().map(|e| e.score).sum::<f64>() / episodes.len() as f64;
+        println!(
+            " {}-{}: {:.3}",
+            episodes[0].episode + 1,
+            episodes[episodes.len() - 1].episode + 1,
+            score
         );
     }
+    if let Some(path) = &training.weights {
+        println!("Network weights written to {}", path);
+    }
 
-    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
-        Box::new(Self {
-            current_random: self.current_random.clone(),
-            change_to_attack_prob: self.change_to_attack_prob,
-            change_to_finch_prob: self.change_to_finch_prob,
-            current_strategy: self.current_strategy.clone(),
-        })
+    if let Some(path) = &config.output.training {
+        let mut output = File::create(path).unwrap();
+        writeln!(output, "episode,opponent,score,baseline").unwrap();
+        for summary in &summaries {
+            writeln!(
+                output,
+                "{},{},{},{}",
+                summary.episode + 1,
+                csv_field(&summary.opponent),
+                summary.score,
+                summary.baseline
+            )
+            .unwrap();
+        }
     }
 }
 
-fn pit_agents_against_each_other() {
-    let rng = Rc::new(RefCell::new(ChaCha12Rng::seed_from_u64(106)));
-
-    let num_retrials = 5000;
-
-    let max_hp = 600;
-    let list_of_agents: Vec<Box<dyn GameAgent>> = vec![
-        Box::new(RandomAgent {
-            current_random: rng.clone(),
-            probability_of_attack: 0.1,
-        }),
-        Box::new(RandomAgent {
-            current_random: rng.clone(),
-            probability_of_attack: 0.3,
-        }),
-        Box::new(RandomAgent {
-            current_random: rng.clone(),
-            probability_of_attack: 0.5,
-        }),
-        Box::new(RandomAgent {
-            current_random: rng.clone(),
-            probability_of_attack: 0.7,
-        }),
-        Box::new(RandomAgent {
-            current_random: rng.clone(),
-            probability_of_attack: 0.9,
-        }),
-        Box::new(AttackAgent {}),
-        Box::new(MarkovRandomAgent {
-            current_random: rng.clone(),
-            change_to_attack_prob: 0.1,
-            change_to_finch_prob: 0.1,
-            current_strategy: Action::ATTACK,
-        }),
-        Box::new(MarkovRandomAgent {
-            current_random: rng.clone(),
-            change_to_attack_prob: 0.5,
-            change_to_finch_prob: 0.1,
-            current_strategy: Action::ATTACK,
-        }),
-        Box::new(MarkovRandomAgent {
-            current_random: rng.clone(),
-            change_to_attack_prob: 0.9,
-            change_to_finch_prob: 0.1,
-            current_strategy: Action::ATTACK,
-        }),
-        Box::new(MarkovRandomAgent {
-            current_random: rng.clone(),
-            change_to_attack_prob: 0.1,
-            change_to_finch_prob: 0.5,
-            current_strategy: Action::ATTACK,
-        }),
-        Box::new(MarkovRandomAgent {
-            current_random: rng.clone(),
-            change_to_attack_prob: 0.5,
-            change_to_finch_prob: 0.5,
-            current_strategy: Action::ATTACK,
-        }),
-        Box::new(MarkovRandomAgent {
-            current_random: rng.clone(),
-            change_to_attack_prob: 0.9,
-            change_to_finch_prob: 0.5,
-            current_strategy: Action::ATTACK,
-        }),
-        Box::new(MarkovRandomAgent {
-            current_random: rng.clone(),
-            change_to_attack_prob: 0.1,
-            change_to_finch_prob: 0.9,
-            current_strategy: Action::ATTACK,
-        }),
-        Box::new(MarkovRandomAgent {
-            current_random: rng.clone(),
-            change_to_attack_prob: 0.5,
-            change_to_finch_prob: 0.9,
-            current_strategy: Action::ATTACK,
-        }),
-        Box::new(MarkovRandomAgent {
-            current_random: rng.clone(),
-            change_to_attack_prob: 0.9,
-            change_to_finch_prob: 0.9,
-            current_strategy: Action::ATTACK,
-        }),
-        Box::new(MirrorAgent),
-        Box::new(OneStepDecisionProcessAgent {
-            cost_equivalent_exchange: -3.0,
-            cost_losing_hp: -3.0,
-            cost_not_losing_hp: -1.0,
-            num_turns: 0,
-            num_attacks: 0,
-        }),
-    ];
+#[cfg(not(feature = "neural"))]
+fn run_training(_: &ExperimentConfig, _: &AgentRegistry, _: &TrainingConfig) {
+    exit_with_error("this build has no neural networks (enable the 'neural' feature)")
+}
+
+fn run_self_play(config: &ExperimentConfig, registry: &AgentRegistry, self_play: &SelfPlayConfig) {
+    let rng = config.rng();
+    let curve = config
+        .build_self_play(registry, self_play, &rng)
+        .and_then(|self_play| self_play.run(&rng))
+        .unwrap_or_else(|err| exit_with_error(err));
+
+    println!("Episodes [average score against the roster, states, league]:");
+    for point in &curve {
+        println!(
+            " {}: {:.3} {} {}",
+            point.episode, point.score, point.states, point.league
+        );
+    }
+    if let Some(path) = &self_play.checkpoint {
+        println!("Checkpoints written to {}", path);
+    }
+
+    if let Some(path) = &config.output.self_play {
+        let mut output = File::create(path).unwrap();
+        writeln!(output, "episode,score,states,league").unwrap();
+        for point in &curve {
+            writeln!(
+                output,
+                "{},{},{},{}",
+                point.episode, point.score, point.states, point.league
+            )
+            .unwrap();
+        }
+        println!("Learning curve written to {}", path);
+    }
+}
+
+#[cfg(feature = "server")]
+fn run_server(address: &str, registry: AgentRegistry) {
+    println!("Serving duels on http://{}", address);
+    the_duel::server::serve(address, registry).unwrap_or_else(|err| exit_with_error(err));
+}
+
+#[cfg(not(feature = "server"))]
+fn run_server(_: &str, _: AgentRegistry) {
+    exit_with_error("this build has no HTTP server (enable the 'server' feature)")
+}
 
-    let num_agents = list_of_agents.len();
-    let mut win_matrix = vec![vec![0; num_agents]; num_agents];
-
-    // Fight two against each other
-    for agent1 in list_of_agents.iter().enumerate() {
-        for agent2 in list_of_agents.iter().enumerate() {
-            for i in 0..num_retrials {
-                let mut game = Game {
-                    player_one_agent: agent1.1.copy_self_to_anom(),
-                    player_two_agent: agent2.1.copy_self_to_anom(),
-                };
-
-                let mut state = GameState {
-                    player_one_state: PlayerState {
-                        max_hit_points: max_hp,
-                        current_hit_points: max_hp,
-                    },
-                    player_two_state: PlayerState {
-                        max_hit_points: max_hp,
-                        current_hit_points: max_hp,
-                    },
-                    player_one_action: None,
-                    player_two_action: None,
-                };
-
-                loop {
-                    // step
-                    game.step_game(&mut state);
-                    let condition = game.check_end_condition(&state);
-                    match condition {
-                        GameOutcome::WIN(id) => {
-                            if id == 1 {
-                                win_matrix[agent1.0][agent2.0] += 1;
-                            } else {
-                                win_matrix[agent2.0][agent1.0] += 1;
-                            }
-                            break;
-                        }
-                        GameOutcome::TIE => {
-                            break;
-                        }
-                        _ => {}
-                    }
-                }
+fn print_ratings(agent_names: &[String], observer: &RatingObserver, rounds: u64) {
+    let ratings = &observer.ratings.ratings;
+    let mut order: Vec<usize> = (0..ratings.len()).collect();
+    order.sort_by(|a, b| ratings[*b].rating.total_cmp(&ratings[*a].rating));
+
+    println!("Glicko-2 ratings after {} rounds:", rounds);
+    for index in order {
+        let rating = &ratings[index];
+        println!(
+            " {:7.1} ± {:5.1} (volatility {:.4}) {}",
+            rating.rating,
+            1.96 * rating.deviation,
+            rating.volatility,
+            agent_names[index]
+        );
+    }
+    if observer.converged() {
+        println!("Ratings converged, tournament stopped early");
+    }
+}
+
+// Command line options following the positional arguments of a command.
+struct Options<'a> {
+    values: HashMap<&'a str, &'a str>,
+    flags: Vec<&'a str>,
+}
+
+impl<'a> Options<'a> {
+    // Parses `--option value` pairs and bare `--flag`s, accepting only the given names.
+    fn parse(args: &'a [String], with_value: &[&str], flags: &[&str]) -> Option<Self> {
+        let mut options = Options {
+            values: HashMap::new(),
+            flags: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if with_value.contains(&arg.as_str()) {
+                options.values.insert(arg.as_str(), args.next()?.as_str());
+            } else if flags.contains(&arg.as_str()) {
+                options.flags.push(arg.as_str());
+            } else {
+                return None;
             }
         }
+        Some(options)
     }
 
-    println!("{:?}", win_matrix);
+    fn value(&self, name: &str) -> Option<&'a str> {
+        self.values.get(name).copied()
+    }
 
-    let path = "pitting-results.csv";
-    let mut output = File::create(path).unwrap();
-    for i in 0..num_agents {
-        write!(output, "{}", win_matrix[0][i]).unwrap();
-        for j in 1..num_agents {
-            write!(output, ",{}", win_matrix[j][i]).unwrap();
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+
+    // The parsed value of an option, exiting if it does not parse.
+    fn parsed_or<T: std::str::FromStr>(&self, name: &str, default: T) -> T {
+        match self.value(name) {
+            Some(value) => value.parse().unwrap_or_else(|_| {
+                exit_with_error(format!("invalid value '{}' for {}", value, name))
+            }),
+            None => default,
         }
-        write!(output, "\n").unwrap();
     }
 }
 
-fn main() {
-    println!("Initializing Game");
-
-    let max_hp = 600;
-    let rng_cell = Rc::new(RefCell::new(ChaCha12Rng::seed_from_u64(106)));
-
-    let mut game = Game {
-        player_one_agent: Box::new(OneStepDecisionProcessAgent {
-            cost_equivalent_exchange: -3.0,
-            cost_losing_hp: -3.0,
-            cost_not_losing_hp: -1.0,
-            num_turns: 0,
-            num_attacks: 0,
-        }),
-        player_two_agent: Box::new(MarkovRandomAgent {
-            current_random: rng_cell.clone(),
-            change_to_attack_prob: 0.3,
-            change_to_finch_prob: 0.6,
-            current_strategy: Action::FINCH,
-        }),
-    };
+#[cfg(not(feature = "tui"))]
+fn require_tui() -> ! {
+    exit_with_error("this build has no terminal viewer (enable the 'tui' feature)")
+}
 
-    let mut state = GameState {
-        player_one_state: PlayerState {
-            max_hit_points: max_hp,
-            current_hit_points: max_hp,
-        },
-        player_two_state: PlayerState {
-            max_hit_points: max_hp,
-            current_hit_points: max_hp,
-        },
-        player_one_action: None,
-        player_two_action: None,
+fn run_duel(
+    registry: &AgentRegistry,
+    player_one_spec: &str,
+    player_two_spec: &str,
+    options: &Options,
+) {
+    let seed = 106;
+    let max_hp = options.parsed_or("--hit-points", 600);
+    if max_hp < 1 {
+        exit_with_error("--hit-points has to be at least 1");
+    }
+    let tui = options.flag("--tui");
+    let mut decisions = options.flag("--explain").then(DecisionLog::new);
+
+    let replay = if tui {
+        #[cfg(feature = "tui")]
+        {
+            let mut observer = TuiObserver::for_duel(
+                format!("Player 1: {}", player_one_spec),
+                format!("Player 2: {}", player_two_spec),
+                max_hp,
+                std::time::Duration::from_millis(10),
+            );
+            let replay = Replay::record_observed(
+                seed,
+                max_hp,
+                GameSettings::default(),
+                player_one_spec,
+                player_two_spec,
+                registry,
+                &mut observer,
+            );
+            observer.finish();
+            replay
+        }
+        #[cfg(not(feature = "tui"))]
+        require_tui()
+    } else if let Some(decisions) = decisions.as_mut() {
+        tracing::info!("initializing game");
+        Replay::record_observed(
+            seed,
+            max_hp,
+            GameSettings::default(),
+            player_one_spec,
+            player_two_spec,
+            registry,
+            decisions,
+        )
+    } else {
+        tracing::info!("initializing game");
+        Replay::record(
+            seed,
+            max_hp,
+            GameSettings::default(),
+            player_one_spec,
+            player_two_spec,
+            registry,
+        )
+    }
+    .unwrap_or_else(|err| exit_with_error(err));
+
+    write_hit_points_csv(&replay);
+    if !tui {
+        print_replay(&replay, decisions.as_ref());
+    }
+
+    if let Some(path) = options.value("--replay") {
+        replay.save(path).unwrap_or_else(|err| exit_with_error(err));
+        println!("Replay written to {}", path);
+    }
+    if let Some(path) = options.value("--plot") {
+        std::fs::write(path, hit_point_svg(&replay)).unwrap_or_else(|err| exit_with_error(err));
+        println!("Hit point plot written to {}", path);
+    }
+}
+
+// Runs `run`, recording every random number drawn to the CSV file at `path`
+// if given.
+fn audited(path: Option<&str>, run: impl FnOnce()) {
+    let Some(path) = path else {
+        run();
+        return;
     };
+    rng_audit::start();
+    run();
+    let draws = rng_audit::finish();
+    let mut output = File::create(path).unwrap_or_else(|err| exit_with_error(err));
+    writeln!(output, "index,requester,turn,stream,kind,value").unwrap();
+    for draw in &draws {
+        writeln!(
+            output,
+            "{},{},{},{},{},{}",
+            draw.index,
+            csv_field(&draw.requester),
+            draw.turn.map_or(String::new(), |turn| turn.to_string()),
+            draw.stream,
+            draw.kind,
+            draw.value
+        )
+        .unwrap();
+    }
+    println!("{} random draws written to {}", draws.len(), path);
+}
+
+fn write_hit_points_csv(replay: &Replay) {
     let path = "results.csv";
     let mut output = File::create(path).unwrap();
-    let mut step_count = 0;
-    loop {
-        // step
-        game.step_game(&mut state);
-
-        // writeout
-
-        write!(
+    for turn in &replay.turns {
+        writeln!(
             output,
-            "{},{},{}\n",
-            step_count,
-            &state.player_one_state.current_hit_points,
-            &state.player_two_state.current_hit_points
+            "{},{},{}",
+            turn.turn, turn.player_one_hit_points, turn.player_two_hit_points
         )
         .unwrap();
+    }
+}
 
-        // check
-        let condition = game.check_end_condition(&state);
-        match condition {
-            GameOutcome::WIN(id) => {
-                println!(
-                    "Status {} [Current/Max]:\n Player 1: {}/{} HP running strategy: {}\n Player 2: {}/{} HP running strategy: {}",
-                    step_count,
-                    &state.player_one_state.current_hit_points,
-                    &state.player_one_state.max_hit_points,
-                    &game.player_one_agent.strategy_name(),
-                    &state.player_two_state.current_hit_points,
-                    &state.player_two_state.max_hit_points,
-                    &game.player_two_agent.strategy_name(),
-                );
-                println!("Player {} wins!", id);
-                break;
+// The arena's agents are not in the registry, which only knows duel agents.
+fn build_fighter(spec: &str, rules: &Arena, rng: &SharedRng) -> Box<dyn GameAgent<Arena>> {
+    match spec {
+        "random" => Box::new(RandomFighter::new(rng.clone())),
+        "tactician" => Box::new(TacticianAgent::new(rules.clone())),
+        _ => exit_with_error(format!(
+            "unknown arena agent '{}', expected random or tactician",
+            spec
+        )),
+    }
+}
+
+fn run_arena(player_one_spec: &str, player_two_spec: &str, options: &Options) {
+    let rules = Arena::new(options.parsed_or("--hit-points", 20));
+    let rng: SharedRng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(
+        options.parsed_or("--seed", 106),
+    )));
+    println!("Moves:");
+    for action in Move::ALL {
+        let info = rules.move_info(&action);
+        println!(
+            " {:<6} damage {}, healing {}, cooldown {}, {} turn(s): {}",
+            info.name, info.damage, info.healing, info.cooldown, info.turns, info.description
+        );
+    }
+    // Healing and blocked blows can stall a fight, so it ends in a draw
+    // after `--max-turns` turns
+    let max_turns = options.parsed_or("--max-turns", 1000);
+    if max_turns < 1 {
+        exit_with_error("--max-turns has to be at least 1");
+    }
+    let mut game = Game::new(
+        rules.clone(),
+        build_fighter(player_one_spec, &rules, &rng),
+        build_fighter(player_two_spec, &rules, &rng),
+    )
+    .with_settings(GameSettings {
+        max_turns: Some(max_turns),
+        ..GameSettings::default()
+    });
+    let mut state = game.initial_state();
+    let outcome = loop {
+        game.step_game(&mut state);
+        println!(
+            "Turn {}: {} {}/{} HP, {} {}/{} HP",
+            state.history.turns(),
+            state.player_one_action.expect("a turn was played"),
+            state.player_one_state.current_hit_points,
+            rules.max_hit_points,
+            state.player_two_action.expect("a turn was played"),
+            state.player_two_state.current_hit_points,
+            rules.max_hit_points,
+        );
+        match game.check_end_condition(&state) {
+            GameOutcome::CONTINUE => {}
+            outcome => break outcome,
+        }
+    };
+    match outcome {
+        GameOutcome::WIN(player) => println!("Player {} won", player),
+        outcome => println!("The game ended with {:?}", outcome),
+    }
+}
+
+// Plays Axelrod's round robin, including every strategy against itself, and
+// ranks the strategies by their average payoff per round. Without strategies
+// given, all classic strategies take part.
+fn run_prisoners_dilemma(specs: &[String], options: &Options) {
+    let payoffs = match options.value("--payoffs") {
+        Some(value) => {
+            let values: Vec<i64> = value
+                .split(',')
+                .map(|payoff| payoff.trim().parse())
+                .collect::<Result<_, _>>()
+                .unwrap_or_else(|_| exit_with_error(format!("invalid payoffs '{}'", value)));
+            let [temptation, reward, punishment, sucker] = values[..] else {
+                exit_with_error("--payoffs expects four values T,R,P,S");
+            };
+            PayoffMatrix {
+                temptation,
+                reward,
+                punishment,
+                sucker,
+            }
+        }
+        None => PayoffMatrix::default(),
+    };
+    payoffs
+        .validate()
+        .unwrap_or_else(|err| exit_with_error(err));
+    let rounds = options.parsed_or("--rounds", 200);
+    if rounds < 1 {
+        exit_with_error("--rounds has to be at least 1");
+    }
+    let rules = PrisonersDilemma::new(payoffs, rounds);
+    let rng: SharedRng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(
+        options.parsed_or("--seed", 106),
+    )));
+    let strategies: Vec<PrisonerStrategy> = if specs.is_empty() {
+        PrisonerStrategy::ALL.to_vec()
+    } else {
+        specs
+            .iter()
+            .map(|spec| {
+                PrisonerStrategy::parse(spec).unwrap_or_else(|| {
+                    exit_with_error(format!(
+                        "unknown strategy '{}', expected cooperate, defect, tit_for_tat, suspicious_tit_for_tat, tit_for_two_tats, grim_trigger, pavlov, joss(p) or random(p)",
+                        spec
+                    ))
+                })
+            })
+            .collect()
+    };
+    let agents: Vec<Box<dyn GameAgent<PrisonersDilemma>>> = strategies
+        .into_iter()
+        .map(|strategy| {
+            Box::new(PrisonerAgent::new(strategy, rng.clone()))
+                as Box<dyn GameAgent<PrisonersDilemma>>
+        })
+        .collect();
+    let mut payoff_observer = PayoffObserver::new(agents.len());
+    let results = Tournament::new(
+        rules.clone(),
+        agents,
+        options.parsed_or("--retrials", 5),
+        PairingSchedule::RoundRobin,
+    )
+    .run_observed(&mut payoff_observer);
+    println!(
+        "Iterated prisoner's dilemma of {} rounds [average payoff per round, wins]:",
+        rules.rounds
+    );
+    print_payoff_ranking(&results, &payoff_observer);
+}
+
+// Rounds of rock-paper-scissors between every pair of strategies, including
+// every strategy against itself, ranked by their average payoff per round.
+fn run_rock_paper_scissors(specs: &[String], options: &Options) {
+    let payoffs = match options.value("--payoffs") {
+        Some(value) => {
+            let values: Vec<i64> = value
+                .split(',')
+                .map(|payoff| payoff.trim().parse())
+                .collect::<Result<_, _>>()
+                .unwrap_or_else(|_| exit_with_error(format!("invalid payoffs '{}'", value)));
+            let [rock, paper, scissors] = values[..] else {
+                exit_with_error("--payoffs expects three values rock,paper,scissors");
+            };
+            HandPayoffs {
+                rock,
+                paper,
+                scissors,
+            }
+        }
+        None => HandPayoffs::default(),
+    };
+    payoffs
+        .validate()
+        .unwrap_or_else(|err| exit_with_error(err));
+    let rounds = options.parsed_or("--rounds", 100);
+    if rounds < 1 {
+        exit_with_error("--rounds has to be at least 1");
+    }
+    let rules = RockPaperScissors::new(payoffs.clone(), rounds);
+    let rng: SharedRng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(
+        options.parsed_or("--seed", 106),
+    )));
+    let strategies: Vec<RpsStrategy> = if specs.is_empty() {
+        RpsStrategy::ALL.to_vec()
+    } else {
+        specs
+            .iter()
+            .map(|spec| {
+                RpsStrategy::parse(spec).unwrap_or_else(|| {
+                    exit_with_error(format!(
+                        "unknown strategy '{}', expected rock, paper, scissors, cycle, equilibrium, beat_last, fictitious_play or markov_predictor",
+                        spec
+                    ))
+                })
+            })
+            .collect()
+    };
+    let agents: Vec<Box<dyn GameAgent<RockPaperScissors>>> = strategies
+        .into_iter()
+        .map(|strategy| {
+            Box::new(RpsAgent::new(strategy, payoffs.clone(), rng.clone()))
+                as Box<dyn GameAgent<RockPaperScissors>>
+        })
+        .collect();
+    let mut payoff_observer = PayoffObserver::new(agents.len());
+    let results = Tournament::new(
+        rules.clone(),
+        agents,
+        options.parsed_or("--retrials", 5),
+        PairingSchedule::RoundRobin,
+    )
+    .run_observed(&mut payoff_observer);
+    let [rock, paper, scissors] = payoffs.equilibrium();
+    println!(
+        "Equilibrium mix: rock {:.3}, paper {:.3}, scissors {:.3}",
+        rock, paper, scissors
+    );
+    println!(
+        "Rock-paper-scissors of {} rounds [average payoff per round, wins]:",
+        rules.rounds
+    );
+    print_payoff_ranking(&results, &payoff_observer);
+}
+
+// Plays rounds of Hawk-Dove between every pair of strategies, including
+// every strategy against itself, ranked by their average payoff per round,
+// after checking that replicator dynamics on the payoffs come to rest at the
+// ESS.
+fn run_hawk_dove(specs: &[String], options: &Options) {
+    let rounds = options.parsed_or("--rounds", 100);
+    if rounds < 1 {
+        exit_with_error("--rounds has to be at least 1");
+    }
+    let payoffs = HawkDovePayoffs {
+        value: options.parsed_or("--value", 2.0),
+        cost: options.parsed_or("--cost", 4.0),
+    };
+    payoffs
+        .validate()
+        .unwrap_or_else(|err| exit_with_error(err));
+    let matrix = payoffs.matrix();
+    let dynamics = ReplicatorDynamics::new(matrix.iter().map(|row| row.to_vec()).collect(), 0.1);
+    let rest = dynamics.trajectory(vec![0.5, 0.5], 100000, 1e-12);
+    println!(
+        "ESS hawk share {:.4}, replicator dynamics from an even split rest at {:.4}",
+        payoffs.ess(),
+        rest.last().unwrap()[0]
+    );
+
+    let rules = HawkDove::new(payoffs.clone(), rounds);
+    let rng: SharedRng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(
+        options.parsed_or("--seed", 108),
+    )));
+    let strategies: Vec<HawkDoveStrategy> = if specs.is_empty() {
+        HawkDoveStrategy::ALL.to_vec()
+    } else {
+        specs
+            .iter()
+            .map(|spec| {
+                HawkDoveStrategy::parse(spec).unwrap_or_else(|| {
+                    exit_with_error(format!(
+                        "unknown strategy '{}', expected hawk, dove, ess, mixed(p), retaliator or bully",
+                        spec
+                    ))
+                })
+            })
+            .collect()
+    };
+    let agents: Vec<Box<dyn GameAgent<HawkDove>>> = strategies
+        .into_iter()
+        .map(|strategy| {
+            Box::new(HawkDoveAgent::new(strategy, payoffs.clone(), rng.clone()))
+                as Box<dyn GameAgent<HawkDove>>
+        })
+        .collect();
+    let mut payoff_observer = PayoffObserver::new(agents.len());
+    let results = Tournament::new(
+        rules.clone(),
+        agents,
+        options.parsed_or("--retrials", 5),
+        PairingSchedule::RoundRobin,
+    )
+    .run_observed(&mut payoff_observer);
+    println!(
+        "Hawk-Dove of {} rounds [average payoff per round, wins]:",
+        rules.rounds
+    );
+    print_payoff_ranking(&results, &payoff_observer);
+}
+
+// Plays episodes of a gridworld, by default the world of Russell and Norvig,
+// following its known optimal policy and a random one.
+fn run_gridworld(options: &Options) {
+    let (world, optimal) = match options.value("--layout") {
+        Some(path) => {
+            let layout = std::fs::read_to_string(path).unwrap_or_else(|err| exit_with_error(err));
+            let world = GridWorld::parse(&layout).unwrap_or_else(|err| exit_with_error(err));
+            (world, None)
+        }
+        None => (
+            GridWorld::russell_norvig(),
+            Some(GridWorld::russell_norvig_policy()),
+        ),
+    };
+    let (slip, living_reward, discount) = (
+        options.parsed_or("--slip", world.slip),
+        options.parsed_or("--living-reward", world.living_reward),
+        options.parsed_or("--discount", world.discount),
+    );
+    let world = world
+        .with_slip(slip)
+        .with_living_reward(living_reward)
+        .with_discount(discount);
+    world.validate().unwrap_or_else(|err| exit_with_error(err));
+    let episodes = options.parsed_or("--episodes", 10000);
+    let max_steps = options.parsed_or("--max-steps", 1000);
+    let rng: SharedRng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(
+        options.parsed_or("--seed", 111),
+    )));
+    print!("{}", world);
+    println!(
+        "Average returns of {} episodes from the start, at most {} moves each:",
+        episodes, max_steps
+    );
+    if let Some(policy) = optimal {
+        println!("{}", world.render(&policy));
+        println!(
+            " Known optimal policy: {:.4}",
+            world.evaluate(&policy, episodes, max_steps, &rng)
+        );
+    }
+    let solution = compare_mdp_solvers(&world.to_mdp(), world.start, options);
+    let solved = world.policy_from_mdp(&solution.policy);
+    println!("{}", world.render(&solved));
+    println!(
+        " Value iteration: {:.4}",
+        world.evaluate(&solved, episodes, max_steps, &rng)
+    );
+    let random = world.random_policy(&mut *rng.borrow_mut());
+    println!("{}", world.render(&random));
+    println!(
+        " Random policy: {:.4}",
+        world.evaluate(&random, episodes, max_steps, &rng)
+    );
+}
+
+// Solves `mdp` by every solver of the crate and prints how long each took
+// and how close it got, the Bellman residuals of every iteration with
+// `--residuals`. Returns the solution of value iteration.
+fn compare_mdp_solvers(mdp: &Mdp, start: usize, options: &Options) -> MdpSolution {
+    let tolerance = options.parsed_or("--tolerance", 1e-9);
+    let max_iterations = options.parsed_or("--max-iterations", 10_000);
+    let evaluation_sweeps = options.parsed_or("--evaluation-sweeps", 5);
+    let solutions = [
+        (
+            String::from("value iteration"),
+            value_iteration(mdp, tolerance, max_iterations),
+        ),
+        (
+            String::from("policy iteration"),
+            policy_iteration(mdp, tolerance, max_iterations),
+        ),
+        (
+            format!("modified policy iteration, {} sweeps", evaluation_sweeps),
+            modified_policy_iteration(mdp, evaluation_sweeps, tolerance, max_iterations),
+        ),
+    ];
+    println!(
+        " {:<38} {:>10} {:>8} {:>10} {:>8}",
+        "solver", "iterations", "sweeps", "residual", "start"
+    );
+    for (name, solution) in &solutions {
+        println!(
+            " {:<38} {:>10} {:>8} {:>10.2e} {:>8.4}",
+            name,
+            solution.iterations,
+            solution.sweeps,
+            solution.residual(),
+            solution.values[start]
+        );
+        if options.flag("--residuals") {
+            let residuals: Vec<String> = solution
+                .residuals
+                .iter()
+                .map(|residual| format!("{:.2e}", residual))
+                .collect();
+            println!("   {}", residuals.join(" "));
+        }
+    }
+    let [(_, solution), ..] = solutions;
+    solution
+}
+
+// Solves the duel against an opponent attacking with a fixed probability by
+// value iteration, policy iteration and modified policy iteration, prints
+// when to attack, and checks the policy of the dynamic programming agent
+// against it.
+fn run_duel_mdp(options: &Options) {
+    let max_hit_points = options.parsed_or("--hit-points", 10);
+    let probability_of_attack = options.parsed_or("--attack", 0.5);
+    let variant = match options.value("--riposte") {
+        Some(counter) => RuleVariant::Riposte {
+            counter_damage: counter
+                .parse()
+                .unwrap_or_else(|_| exit_with_error(format!("invalid --riposte '{}'", counter))),
+        },
+        None => RuleVariant::Standard,
+    };
+    if !(0.0..=1.0).contains(&probability_of_attack) || max_hit_points < 1 {
+        exit_with_error("--attack has to be a probability and --hit-points positive");
+    }
+    let damage = variant.apply(&DamageMatrix::default());
+    damage.validate().unwrap_or_else(|err| exit_with_error(err));
+    let mdp = Mdp::duel(max_hit_points, &damage, probability_of_attack);
+    mdp.validate().unwrap_or_else(|err| exit_with_error(err));
+    println!(
+        "{} rules against attacks with probability {}:",
+        variant, probability_of_attack
+    );
+    let start = duel_state(max_hit_points, max_hit_points, max_hit_points);
+    let solution = compare_mdp_solvers(&mdp, start, options);
+    println!(
+        "Chance of winning from full hit points: {:.4}",
+        solution.values[start]
+    );
+    println!("Attack (A) or finch (F), own hit points by row, the opponent's by column:");
+    let attack = Action::ATTACK.index();
+    for own in (1..=max_hit_points).rev() {
+        let row: Vec<&str> = (1..=max_hit_points)
+            .map(
+                |opposing| match solution.policy[duel_state(max_hit_points, own, opposing)] {
+                    Some(action) if action == attack => "A",
+                    _ => "F",
+                },
+            )
+            .collect();
+        println!("{:>3} {}", own, row.join(" "));
+    }
+    // Only states whose actions differ in value count, ties may go either way.
+    let policy = DuelPolicy::solve_with_damage(max_hit_points, &damage, probability_of_attack);
+    let disagreements = (1..=max_hit_points)
+        .flat_map(|own| (1..=max_hit_points).map(move |opposing| (own, opposing)))
+        .filter(|(own, opposing)| {
+            let state = duel_state(max_hit_points, *own, *opposing);
+            let attacking = mdp.q_value(&solution.values, state, attack);
+            let finching = mdp.q_value(&solution.values, state, Action::FINCH.index());
+            (attacking - finching).abs() > 1e-6
+                && (attacking > finching) != policy.attacks(*own, *opposing)
+        })
+        .count();
+    println!(
+        "The dynamic programming agent's policy disagrees in {} states.",
+        disagreements
+    );
+}
+
+// Rounds of the public goods game in groups sampled from the strategies,
+// ranked by their average payoff per round.
+fn run_public_goods(specs: &[String], options: &Options) {
+    let defaults = PublicGoods::default();
+    let rules = PublicGoods {
+        endowment: options.parsed_or("--endowment", defaults.endowment),
+        multiplier: options.parsed_or("--multiplier", defaults.multiplier),
+        rounds: options.parsed_or("--rounds", defaults.rounds),
+        punishment_cost: options.parsed_or("--punishment-cost", defaults.punishment_cost),
+        punishment_fine: options.parsed_or("--punishment-fine", defaults.punishment_fine),
+    };
+    let config = PublicGoodsConfig {
+        group_size: options.parsed_or("--group-size", 4),
+        groups: options.parsed_or("--groups", 200),
+    };
+    rules
+        .validate(config.group_size)
+        .unwrap_or_else(|err| exit_with_error(err));
+    let rng: SharedRng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(
+        options.parsed_or("--seed", 110),
+    )));
+    let strategies: Vec<PublicGoodsStrategy> = if specs.is_empty() {
+        PublicGoodsStrategy::ALL.to_vec()
+    } else {
+        specs
+            .iter()
+            .map(|spec| {
+                PublicGoodsStrategy::parse(spec).unwrap_or_else(|| {
+                    exit_with_error(format!(
+                        "unknown strategy '{}', expected free_rider, cooperator, conditional_cooperator, punisher or random",
+                        spec
+                    ))
+                })
+            })
+            .collect()
+    };
+    config
+        .validate(strategies.len())
+        .unwrap_or_else(|err| exit_with_error(err));
+    let agents = strategies
+        .into_iter()
+        .map(|strategy| PublicGoodsAgent::new(strategy, rng.clone()))
+        .collect();
+    let results = PublicGoodsTournament::new(rules.clone(), agents, config.clone()).run(&rng);
+    println!(
+        "Public goods in groups of {} over {} rounds, pool multiplied by {} [average payoff per round, contribution rate, fines per round]:",
+        config.group_size, rules.rounds, rules.multiplier
+    );
+    print_public_goods(&results);
+}
+
+fn print_public_goods(results: &PublicGoodsResults) {
+    let mut ranking: Vec<usize> = (0..results.agent_names.len()).collect();
+    ranking.sort_by(|a, b| {
+        results
+            .average_payoff(*b)
+            .total_cmp(&results.average_payoff(*a))
+    });
+    for (rank, agent) in ranking.into_iter().enumerate() {
+        println!(
+            " {}. {}: {:.3}, {:.3}, {:.3}",
+            rank + 1,
+            results.agent_names[agent],
+            results.average_payoff(agent),
+            results.contribution_rate(agent),
+            results.fines[agent] as f64 / results.rounds[agent].max(1) as f64
+        );
+    }
+}
+
+// Ultimatums between every pair of strategies, including every strategy
+// against itself, ranked by their average payoff per turn; every ultimatum
+// takes two turns.
+fn run_ultimatum(specs: &[String], options: &Options) {
+    let rounds = options.parsed_or("--rounds", 10);
+    if rounds < 1 {
+        exit_with_error("--rounds has to be at least 1");
+    }
+    let rules = Ultimatum::new(options.parsed_or("--pie", 10), rounds);
+    rules.validate().unwrap_or_else(|err| exit_with_error(err));
+    let rng: SharedRng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(
+        options.parsed_or("--seed", 109),
+    )));
+    let strategies: Vec<UltimatumStrategy> = if specs.is_empty() {
+        UltimatumStrategy::ALL.to_vec()
+    } else {
+        specs
+            .iter()
+            .map(|spec| {
+                UltimatumStrategy::parse(spec).unwrap_or_else(|| {
+                    exit_with_error(format!(
+                        "unknown strategy '{}', expected rational, fair, threshold(f), random or adaptive",
+                        spec
+                    ))
+                })
+            })
+            .collect()
+    };
+    let agents: Vec<Box<dyn GameAgent<Ultimatum>>> = strategies
+        .into_iter()
+        .map(|strategy| {
+            Box::new(UltimatumAgent::new(strategy, rules.pie, rng.clone()))
+                as Box<dyn GameAgent<Ultimatum>>
+        })
+        .collect();
+    let mut payoff_observer = PayoffObserver::new(agents.len());
+    let results = Tournament::new(
+        rules.clone(),
+        agents,
+        options.parsed_or("--retrials", 5),
+        PairingSchedule::RoundRobin,
+    )
+    .run_observed(&mut payoff_observer);
+    println!(
+        "{} ultimatums over a pie of {} [average payoff per turn, wins]:",
+        rules.rounds, rules.pie
+    );
+    print_payoff_ranking(&results, &payoff_observer);
+}
+
+// Repeats the prisoner's dilemma, or the matrix game of a payoff file, with
+// random termination and discounting, and ranks the strategies by their
+// average discounted payoff per game. For the prisoner's dilemma, prints the
+// patience above which grim trigger sustains cooperation.
+fn run_repeated_game(specs: &[String], options: &Options) {
+    let rng: SharedRng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(
+        options.parsed_or("--seed", 113),
+    )));
+    let continuation = options.parsed_or("--continuation", 0.9);
+    let discount = options.parsed_or("--discount", 1.0);
+    let max_rounds = options.parsed_or("--max-rounds", 1000);
+    let retrials = options.parsed_or("--retrials", 20);
+    let Some(path) = options.value("--matrix") else {
+        let rules = Repeated::new(PrisonersDilemma::default(), max_rounds)
+            .with_continuation(continuation, rng.clone())
+            .with_discount(discount);
+        rules.validate().unwrap_or_else(|err| exit_with_error(err));
+        let strategies: Vec<PrisonerStrategy> = if specs.is_empty() {
+            PrisonerStrategy::ALL.to_vec()
+        } else {
+            specs
+                .iter()
+                .map(|spec| {
+                    PrisonerStrategy::parse(spec)
+                        .unwrap_or_else(|| exit_with_error(format!("unknown strategy '{}'", spec)))
+                })
+                .collect()
+        };
+        let agents: Vec<Box<dyn GameAgent<Repeated<PrisonersDilemma>>>> = strategies
+            .into_iter()
+            .map(|strategy| {
+                Box::new(StageAgent::new(Box::new(PrisonerAgent::new(
+                    strategy,
+                    rng.clone(),
+                )))) as Box<dyn GameAgent<Repeated<PrisonersDilemma>>>
+            })
+            .collect();
+        let payoffs = &rules.stage.payoffs;
+        println!(
+            "Grim trigger sustains cooperation from a patience of {:.3}, the game has {:.3}.",
+            (payoffs.temptation - payoffs.reward) as f64
+                / (payoffs.temptation - payoffs.punishment) as f64,
+            continuation * discount
+        );
+        play_repeated_game(rules, agents, retrials);
+        return;
+    };
+    let stage = MatrixGame::load(path).unwrap_or_else(|err| exit_with_error(err));
+    let rules = Repeated::new(stage.clone(), max_rounds)
+        .with_continuation(continuation, rng.clone())
+        .with_discount(discount);
+    rules.validate().unwrap_or_else(|err| exit_with_error(err));
+    let strategies: Vec<MatrixStrategy> = if specs.is_empty() {
+        MatrixStrategy::ADAPTIVE.to_vec()
+    } else {
+        specs
+            .iter()
+            .map(|spec| {
+                let strategy = MatrixStrategy::parse(spec).unwrap_or_else(|| {
+                    exit_with_error(format!(
+                        "unknown strategy '{}', expected constant(i), random, mirror, best_response, fictitious_play or regret_matching",
+                        spec
+                    ))
+                });
+                strategy
+                    .validate(&stage)
+                    .unwrap_or_else(|err| exit_with_error(err));
+                strategy
+            })
+            .collect()
+    };
+    let agents: Vec<Box<dyn GameAgent<Repeated<MatrixGame>>>> = strategies
+        .into_iter()
+        .map(|strategy| {
+            Box::new(StageAgent::new(Box::new(MatrixAgent::new(
+                strategy,
+                stage.clone(),
+                rng.clone(),
+            )))) as Box<dyn GameAgent<Repeated<MatrixGame>>>
+        })
+        .collect();
+    play_repeated_game(rules, agents, retrials);
+}
+
+fn play_repeated_game<G: SimultaneousGame + Clone + 'static>(
+    rules: Repeated<G>,
+    agents: Vec<Box<dyn GameAgent<Repeated<G>>>>,
+    retrials: u64,
+) where
+    G::PlayerState: Scored,
+{
+    let mut payoff_observer = DiscountedPayoffObserver::new(&rules, agents.len());
+    println!(
+        "Continuing with probability {}, discounting by {}, {:.1} rounds expected [average discounted payoff per game, rounds, wins]:",
+        rules.continuation,
+        rules.discount,
+        rules.expected_rounds()
+    );
+    let results = Tournament::new(rules, agents, retrials, PairingSchedule::RoundRobin)
+        .run_observed(&mut payoff_observer);
+    let mut ranking: Vec<usize> = (0..results.num_agents()).collect();
+    ranking.sort_by(|a, b| {
+        payoff_observer
+            .average_payoff(*b)
+            .total_cmp(&payoff_observer.average_payoff(*a))
+    });
+    let win_matrix = results.win_matrix();
+    for (rank, agent) in ranking.into_iter().enumerate() {
+        println!(
+            " {}. {}: {:.3}, {:.1}, {}",
+            rank + 1,
+            results.agent_names[agent],
+            payoff_observer.average_payoff(agent),
+            payoff_observer.average_rounds(agent),
+            win_matrix[agent].iter().sum::<u64>()
+        );
+    }
+}
+
+// Solves a game tree, by default Kuhn poker, by counterfactual regret
+// minimization and prints both players' strategies and expected payoffs.
+fn run_game_tree(path: Option<&str>, options: &Options) {
+    if path.is_none() && options.flag("--print") {
+        print!("{}", kuhn_poker_text());
+        return;
+    }
+    let tree = match path {
+        Some(path) => GameTree::load(path).unwrap_or_else(|err| exit_with_error(err)),
+        None => GameTree::kuhn_poker(),
+    };
+    let iterations = options.parsed_or("--iterations", 10_000);
+    let uniform = tree.expected_payoffs(&tree.uniform_strategy());
+    let solution = tree.solve(iterations);
+    println!(
+        "{} nodes, {} information sets; uniform play pays {:.4}, {:.4}",
+        tree.nodes.len(),
+        tree.num_infosets(),
+        uniform[0],
+        uniform[1]
+    );
+    println!(
+        "Average strategy of {} iterations, paying {:.4}, {:.4}:",
+        iterations, solution.value[0], solution.value[1]
+    );
+    println!("{}", tree.render(&solution.strategy));
+}
+
+// Plays the normal-form game of a payoff file between every pair of
+// strategies, ranked by their average payoff per round. Without strategies,
+// every constant strategy meets the adaptive ones.
+fn run_matrix_game(path: &str, specs: &[String], options: &Options) {
+    let game = MatrixGame::load(path).unwrap_or_else(|err| exit_with_error(err));
+    let rounds = options.parsed_or("--rounds", game.rounds);
+    if rounds < 1 {
+        exit_with_error("--rounds has to be at least 1");
+    }
+    let game = game.with_rounds(rounds);
+    let rng: SharedRng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(
+        options.parsed_or("--seed", 112),
+    )));
+    let strategies: Vec<MatrixStrategy> = if specs.is_empty() {
+        (0..game.rows.len().min(game.columns.len()))
+            .map(MatrixStrategy::Constant)
+            .chain(MatrixStrategy::ADAPTIVE)
+            .collect()
+    } else {
+        specs
+            .iter()
+            .map(|spec| {
+                let strategy = MatrixStrategy::parse(spec).unwrap_or_else(|| {
+                    exit_with_error(format!(
+                        "unknown strategy '{}', expected constant(i), random, mirror, best_response, fictitious_play or regret_matching",
+                        spec
+                    ))
+                });
+                strategy
+                    .validate(&game)
+                    .unwrap_or_else(|err| exit_with_error(err));
+                strategy
+            })
+            .collect()
+    };
+    let agents: Vec<Box<dyn GameAgent<MatrixGame>>> = strategies
+        .into_iter()
+        .map(|strategy| {
+            Box::new(MatrixAgent::new(strategy, game.clone(), rng.clone()))
+                as Box<dyn GameAgent<MatrixGame>>
+        })
+        .collect();
+    match nash_equilibria(&game.payoffs) {
+        Ok(equilibria) => print_nash_equilibria(&equilibria, &game.rows, &game.columns),
+        Err(err) => println!("No equilibria: {}", err),
+    }
+    if game.is_zero_sum() {
+        let solution = solve_matrix_game(&game.row_payoffs(), 10_000);
+        println!(
+            "The game is zero-sum with value {:.4} for the row player.",
+            solution.value
+        );
+    }
+    let mut payoff_observer = PayoffObserver::new(agents.len());
+    let results = Tournament::new(
+        game.clone(),
+        agents,
+        options.parsed_or("--retrials", 5),
+        PairingSchedule::RoundRobin,
+    )
+    .run_observed(&mut payoff_observer);
+    let name = if game.name.is_empty() {
+        path
+    } else {
+        &game.name
+    };
+    println!(
+        "{} of {} rounds [average payoff per round, wins]:",
+        name, game.rounds
+    );
+    print_payoff_ranking(&results, &payoff_observer);
+}
+
+// Solves matching pennies with every equilibrium solver of the crate and
+// compares the solutions with the known equilibrium, then plays rounds
+// between every pair of strategies, ranked by their average payoff per
+// round. Player one of every pairing matches, so pairs meet in both roles.
+fn run_matching_pennies(specs: &[String], options: &Options) {
+    let rounds = options.parsed_or("--rounds", 100);
+    if rounds < 1 {
+        exit_with_error("--rounds has to be at least 1");
+    }
+    let payoffs = match options.value("--payoffs") {
+        Some(value) => {
+            let values: Vec<i64> = value
+                .split(',')
+                .map(|payoff| payoff.trim().parse())
+                .collect::<Result<_, _>>()
+                .unwrap_or_else(|_| exit_with_error(format!("invalid payoffs '{}'", value)));
+            let [heads, tails, mismatch] = values[..] else {
+                exit_with_error("--payoffs expects three values heads,tails,mismatch");
+            };
+            PenniesPayoffs {
+                heads,
+                tails,
+                mismatch,
             }
-            GameOutcome::TIE => {
+        }
+        None => PenniesPayoffs::default(),
+    };
+    payoffs
+        .validate()
+        .unwrap_or_else(|err| exit_with_error(err));
+
+    let equilibrium = payoffs.equilibrium();
+    let matrix = payoffs.matrix();
+    let iterations = options.parsed_or("--iterations", 1000);
+    println!(
+        "Equilibrium: matcher heads {:.4}, mismatcher heads {:.4}, value {:.4}",
+        equilibrium.matcher_heads, equilibrium.mismatcher_heads, equilibrium.value
+    );
+    println!(
+        "Solvers after {} iterations [matcher heads, mismatcher heads, value, exploitability]:",
+        iterations
+    );
+    type Solver = fn(&[Vec<f64>], usize) -> MatrixSolution;
+    let solvers: [(&str, Solver); 3] = [
+        ("Regret matching+", solve_matrix_game),
+        ("Regret matching", solve_by_regret_matching),
+        ("Fictitious play", solve_by_fictitious_play),
+    ];
+    for (name, solve) in solvers {
+        let solution = solve(&matrix, iterations);
+        println!(
+            " {}: {:.4}, {:.4}, {:.4}, {:.6}",
+            name,
+            solution.row[0],
+            solution.column[0],
+            solution.value,
+            exploitability(&matrix, &solution.row, &solution.column)
+        );
+    }
+
+    let rules = MatchingPennies::new(payoffs.clone(), rounds);
+    let rng: SharedRng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(
+        options.parsed_or("--seed", 107),
+    )));
+    let strategies: Vec<PenniesStrategy> = if specs.is_empty() {
+        PenniesStrategy::ALL.to_vec()
+    } else {
+        specs
+            .iter()
+            .map(|spec| {
+                PenniesStrategy::parse(spec).unwrap_or_else(|| {
+                    exit_with_error(format!(
+                        "unknown strategy '{}', expected heads, tails, alternate, equilibrium, fictitious_play or regret_matching",
+                        spec
+                    ))
+                })
+            })
+            .collect()
+    };
+    let agents: Vec<Box<dyn GameAgent<MatchingPennies>>> = strategies
+        .into_iter()
+        .map(|strategy| {
+            Box::new(PenniesAgent::new(strategy, payoffs.clone(), rng.clone()))
+                as Box<dyn GameAgent<MatchingPennies>>
+        })
+        .collect();
+    let mut payoff_observer = PayoffObserver::new(agents.len());
+    let results = Tournament::new(
+        rules.clone(),
+        agents,
+        options.parsed_or("--retrials", 5),
+        PairingSchedule::RoundRobin,
+    )
+    .run_observed(&mut payoff_observer);
+    println!(
+        "Matching pennies of {} rounds [average payoff per round, wins]:",
+        rules.rounds
+    );
+    print_payoff_ranking(&results, &payoff_observer);
+}
+
+fn print_payoff_ranking(results: &TournamentResults, payoff_observer: &PayoffObserver) {
+    let mut ranking: Vec<usize> = (0..results.num_agents()).collect();
+    ranking.sort_by(|a, b| {
+        payoff_observer
+            .average_payoff(*b)
+            .total_cmp(&payoff_observer.average_payoff(*a))
+    });
+    let win_matrix = results.win_matrix();
+    for (rank, agent) in ranking.into_iter().enumerate() {
+        println!(
+            " {}. {}: {:.3}, {}",
+            rank + 1,
+            results.agent_names[agent],
+            payoff_observer.average_payoff(agent),
+            win_matrix[agent].iter().sum::<u64>()
+        );
+    }
+}
+
+// With `decisions`, every turn is followed by the reasoning the agents
+// reported for their actions.
+fn print_replay(replay: &Replay, decisions: Option<&DecisionLog>) {
+    let header = &replay.header;
+    for turn in &replay.turns {
+        let is_last_turn = turn.turn + 1 == replay.turns.len();
+        match &replay.outcome {
+            GameOutcome::WIN(_) if is_last_turn => {
                 println!(
-                    "Status {} [Current/Max]:\n Player 1: {}/{} HP\n Player 2: {}/{} HP",
-                    step_count,
-                    &state.player_one_state.current_hit_points,
-                    &state.player_one_state.max_hit_points,
-                    &state.player_two_state.current_hit_points,
-                    &state.player_two_state.max_hit_points
+                    "Status {} [Current/Max]:\n Player 1: {}/{} HP running strategy: {}\n Player 2: {}/{} HP running strategy: {}",
+                    turn.turn,
+                    turn.player_one_hit_points,
+                    header.max_hit_points,
+                    header.player_one.strategy_name,
+                    turn.player_two_hit_points,
+                    header.max_hit_points,
+                    header.player_two.strategy_name,
                 );
-                println!("Game ended in a Tie");
-                break;
-            }
-            GameOutcome::INTERRUPTED => {
-                panic!("Unexpected Event happened");
             }
-            GameOutcome::CONTINUE => {
+            _ => {
                 println!(
                     "Status {} [Current/Max]:\n Player 1: {}/{} HP\n Player 2: {}/{} HP",
-                    step_count,
-                    &state.player_one_state.current_hit_points,
-                    &state.player_one_state.max_hit_points,
-                    &state.player_two_state.current_hit_points,
-                    &state.player_two_state.max_hit_points
+                    turn.turn,
+                    turn.player_one_hit_points,
+                    header.max_hit_points,
+                    turn.player_two_hit_points,
+                    header.max_hit_points
                 );
             }
         }
-        step_count += 1;
+        for decision in decisions.iter().flat_map(|log| log.of_turn(turn.turn)) {
+            println!(
+                " Player {} chose {:?}: {}",
+                decision.player, decision.action, decision.explanation
+            );
+        }
+    }
+
+    match &replay.outcome {
+        GameOutcome::WIN(id) | GameOutcome::SURVIVOR(id) => println!("Player {} wins!", id),
+        GameOutcome::TIE => println!("Game ended in a Tie"),
+        GameOutcome::DRAW(reason) => println!("Game ended in a Draw ({})", reason),
+        GameOutcome::INTERRUPTED | GameOutcome::CONTINUE => {
+            panic!("Unexpected Event happened");
+        }
     }
     println!("Game finished!");
+}
+
+// Plays the local agent's seat of a duel over the network and prints the
+// game, optionally saving its replay.
+fn run_network(mut duel: NetworkDuel, options: &Options) {
+    println!(
+        "Playing {} as player {} against {}",
+        duel.agent.strategy_name(),
+        duel.seat,
+        duel.opponent_name
+    );
+    let replay = duel.play().unwrap_or_else(|err| exit_with_error(err));
+    print_replay(&replay, None);
+    if let Some(path) = options.value("--replay") {
+        replay.save(path).unwrap_or_else(|err| exit_with_error(err));
+        println!("Replay written to {}", path);
+    }
+}
+
+// Prints every turn of the games it sees.
+struct TurnPrinter;
+
+impl GameObserver for TurnPrinter {
+    fn on_game_start(&mut self, _player_one: usize, _player_two: usize) {
+        println!("Initializing Game");
+    }
+
+    fn on_turn(&mut self, state: &GameState) {
+        println!(
+            "Status {}: Player 1 {:?} {} HP, Player 2 {:?} {} HP",
+            state.history.turns() - 1,
+            state.player_one_action,
+            state.player_one_state.current_hit_points,
+            state.player_two_action,
+            state.player_two_state.current_hit_points,
+        );
+    }
+
+    fn on_game_end(&mut self, _state: &GameState, outcome: &GameOutcome) {
+        println!("Game ended with {:?}", outcome);
+    }
+}
+
+// Plays a single retrial of a pairing of the experiment's tournament again,
+// from the same seed it had in the tournament.
+fn run_reproduce(
+    config: &ExperimentConfig,
+    registry: &AgentRegistry,
+    player_one: usize,
+    player_two: usize,
+    retrial: u64,
+) {
+    let tournament = config
+        .build_tournament(registry)
+        .unwrap_or_else(|err| exit_with_error(err));
+    let num_agents = tournament.agents.len();
+    if player_one >= num_agents || player_two >= num_agents {
+        exit_with_error(format!("the roster has {} agents", num_agents));
+    }
+    let names = [player_one, player_two].map(|agent| tournament.agents[agent].strategy_name());
+    println!(
+        "Retrial {} of {} against {}, seed {}",
+        retrial,
+        names[0],
+        names[1],
+        game_seed(config.seed, player_one, player_two, retrial)
+    );
+    let outcome = tournament.play_single(player_one, player_two, retrial, &mut TurnPrinter);
+    println!("Match ended with {:?}", outcome);
+}
+
+fn run_seed_search(search: &SeedSearch, options: &Options) {
+    let from: u64 = options.parsed_or("--from", 0);
+    let count: u64 = options.parsed_or("--count", 10_000);
+    if count == 0 {
+        exit_with_error("--count has to be at least 1");
+    }
+    let to = from
+        .checked_add(count - 1)
+        .unwrap_or_else(|| exit_with_error("--from plus --count runs past the last seed"));
+    let replays = search
+        .find(from..to.saturating_add(1), options.parsed_or("--limit", 5))
+        .unwrap_or_else(|err| exit_with_error(err));
+    if replays.is_empty() {
+        println!("No game of seeds {} to {} matches", from, to);
+    }
+    for replay in &replays {
+        let (player_one_hit_points, player_two_hit_points) =
+            replay.turns.last().map_or((0, 0), |turn| {
+                (turn.player_one_hit_points, turn.player_two_hit_points)
+            });
+        println!(
+            "Seed {}: {:?} after {} turns, {} to {} HP",
+            replay.header.seed,
+            replay.outcome,
+            replay.turns.len(),
+            player_one_hit_points,
+            player_two_hit_points
+        );
+        if let Some(directory) = options.value("--save") {
+            std::fs::create_dir_all(directory).unwrap_or_else(|err| exit_with_error(err));
+            let path = Path::new(directory).join(format!("seed-{}.jsonl", replay.header.seed));
+            replay
+                .save(&path)
+                .unwrap_or_else(|err| exit_with_error(err));
+            println!(" Replay written to {}", path.display());
+        }
+    }
+}
 
-    //pit_agents_against_each_other();
+// Fits a hidden Markov model to the actions of the first agent of a duel.
+fn run_hmm(registry: &AgentRegistry, agent_spec: &str, opponent_spec: &str, options: &Options) {
+    let iterations = options.parsed_or("--iterations", 100);
+    let replay = Replay::record(
+        106,
+        600,
+        GameSettings::default(),
+        agent_spec,
+        opponent_spec,
+        registry,
+    )
+    .unwrap_or_else(|err| exit_with_error(err));
+    let actions: Vec<Action> = replay
+        .turns
+        .iter()
+        .map(|turn| turn.player_one_action.clone())
+        .collect();
+
+    let mut model = HiddenMarkovModel::new();
+    model.fit(&actions, iterations);
+    println!(
+        "Hidden Markov model of {} over {} actions:",
+        replay.header.player_one.strategy_name,
+        actions.len()
+    );
+    for (state, (transition, emission)) in model.transition.iter().zip(&model.emission).enumerate()
+    {
+        println!(
+            " State {}: attacks with {:.3}, stays with {:.3}",
+            state, emission[1], transition[state]
+        );
+    }
+    let (to_attack, to_finch) = model.markov_probabilities();
+    println!(
+        " As Markov chain: to attack {:.3}, to finch {:.3}",
+        to_attack, to_finch
+    );
+    println!(" Log-likelihood: {:.3}", model.log_likelihood(&actions));
+}
+
+fn run_sweep(
+    config: &ExperimentConfig,
+    registry: &AgentRegistry,
+    sweep: &Sweep,
+    options: &Options,
+) {
+    let rows = sweep
+        .run(config, registry)
+        .unwrap_or_else(|err| exit_with_error(err));
+
+    println!("Average score against the roster:");
+    for combination in rows.chunk_by(|a, b| a.spec == b.spec) {
+        let (score, games) = combination.iter().fold((0.0, 0), |(score, games), row| {
+            (
+                score + row.record.wins as f64 + 0.5 * row.record.ties as f64,
+                games + row.record.games(),
+            )
+        });
+        println!(
+            " {}: {:.3}",
+            combination[0].spec,
+            score / games.max(1) as f64
+        );
+    }
+
+    if let Some(path) = options.value("--output") {
+        let mut output = File::create(path).unwrap_or_else(|err| exit_with_error(err));
+        let names: Vec<&str> = sweep.ranges.iter().map(|r| r.name.as_str()).collect();
+        writeln!(
+            output,
+            "{},agent,opponent,wins,ties,losses,games,win_rate",
+            names.join(",")
+        )
+        .unwrap();
+        for row in &rows {
+            let values: Vec<String> = row.values.iter().map(|v| v.to_string()).collect();
+            writeln!(
+                output,
+                "{},{},{},{},{},{},{},{}",
+                values.join(","),
+                csv_field(&row.spec),
+                csv_field(&row.opponent),
+                row.record.wins,
+                row.record.ties,
+                row.record.losses,
+                row.record.games(),
+                row.record.wins as f64 / row.record.games().max(1) as f64
+            )
+            .unwrap();
+        }
+        println!("Sweep results written to {}", path);
+    }
+}
+
+// The built-in agents and those of the plugins in `plugins/`, or in the
+// directory named by THE_DUEL_PLUGINS.
+#[cfg(feature = "plugins")]
+fn registry_with_plugins() -> AgentRegistry {
+    let mut registry = AgentRegistry::with_builtin_agents();
+    let directory = std::env::var("THE_DUEL_PLUGINS").unwrap_or_else(|_| String::from("plugins"));
+    load_plugins(Path::new(&directory), &mut registry).unwrap_or_else(|err| exit_with_error(err));
+    registry
+}
+
+#[cfg(not(feature = "plugins"))]
+fn registry_with_plugins() -> AgentRegistry {
+    AgentRegistry::with_builtin_agents()
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    init_logging();
+    let registry = registry_with_plugins();
+
+    match args.first().map(String::as_str) {
+        // the-duel duel "one_step" "markov(0.3, 0.6, FINCH)" [--hit-points 10] [--replay duel.jsonl] [--plot hp.svg] [--tui] [--explain] [--audit-rng draws.csv]
+        // the-duel duel human "tit_for_tat" --hit-points 10
+        Some("duel") => {
+            let usage = "usage: the-duel duel <agent> <agent> [--hit-points <n>] [--replay <file>] [--plot <file>] [--tui] [--explain] [--audit-rng <file>]";
+            if args.len() < 3 {
+                exit_with_error(usage);
+            }
+            let options = Options::parse(
+                &args[3..],
+                &["--hit-points", "--replay", "--plot", "--audit-rng"],
+                &["--tui", "--explain"],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            audited(options.value("--audit-rng"), || {
+                run_duel(&registry, &args[1], &args[2], &options)
+            });
+        }
+        // the-duel arena tactician random [--hit-points 20] [--seed 1]
+        Some("arena") => {
+            let usage = "usage: the-duel arena <random|tactician> <random|tactician> [--hit-points <n>] [--max-turns <n>] [--seed <n>]";
+            if args.len() < 3 {
+                exit_with_error(usage);
+            }
+            let options =
+                Options::parse(&args[3..], &["--hit-points", "--max-turns", "--seed"], &[])
+                    .unwrap_or_else(|| exit_with_error(usage));
+            run_arena(&args[1], &args[2], &options);
+        }
+        // the-duel ipd tit_for_tat defect "joss(0.1)" [--rounds 200] [--payoffs 5,3,1,0]
+        Some("ipd") => {
+            let usage = "usage: the-duel ipd [<strategy>...] [--rounds <n>] [--payoffs <T,R,P,S>] [--retrials <n>] [--seed <n>]";
+            let strategies = args[1..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            let options = Options::parse(
+                &args[1 + strategies..],
+                &["--rounds", "--payoffs", "--retrials", "--seed"],
+                &[],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            run_prisoners_dilemma(&args[1..1 + strategies], &options);
+        }
+        // the-duel rps cycle beat_last markov_predictor [--rounds 100] [--payoffs 2,1,1]
+        Some("rps") => {
+            let usage = "usage: the-duel rps [<strategy>...] [--rounds <n>] [--payoffs <rock,paper,scissors>] [--retrials <n>] [--seed <n>]";
+            let strategies = args[1..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            let options = Options::parse(
+                &args[1 + strategies..],
+                &["--rounds", "--payoffs", "--retrials", "--seed"],
+                &[],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            run_rock_paper_scissors(&args[1..1 + strategies], &options);
+        }
+        // the-duel hawk-dove retaliator bully "mixed(0.3)" [--value 2] [--cost 4]
+        Some("hawk-dove") => {
+            let usage = "usage: the-duel hawk-dove [<strategy>...] [--value <v>] [--cost <c>] [--rounds <n>] [--retrials <n>] [--seed <n>]";
+            let strategies = args[1..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            let options = Options::parse(
+                &args[1 + strategies..],
+                &["--value", "--cost", "--rounds", "--retrials", "--seed"],
+                &[],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            run_hawk_dove(&args[1..1 + strategies], &options);
+        }
+        // the-duel ultimatum rational fair "threshold(0.3)" [--pie 10] [--rounds 10]
+        Some("ultimatum") => {
+            let usage = "usage: the-duel ultimatum [<strategy>...] [--pie <n>] [--rounds <n>] [--retrials <n>] [--seed <n>]";
+            let strategies = args[1..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            let options = Options::parse(
+                &args[1 + strategies..],
+                &["--pie", "--rounds", "--retrials", "--seed"],
+                &[],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            run_ultimatum(&args[1..1 + strategies], &options);
+        }
+        // the-duel public-goods free_rider punisher conditional_cooperator [--group-size 3] [--multiplier 1.6]
+        Some("public-goods") => {
+            let usage = "usage: the-duel public-goods [<strategy>...] [--group-size <n>] [--groups <n>] [--endowment <n>] [--multiplier <r>] [--rounds <n>] [--punishment-cost <c>] [--punishment-fine <f>] [--seed <n>]";
+            let strategies = args[1..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            let options = Options::parse(
+                &args[1 + strategies..],
+                &[
+                    "--group-size",
+                    "--groups",
+                    "--endowment",
+                    "--multiplier",
+                    "--rounds",
+                    "--punishment-cost",
+                    "--punishment-fine",
+                    "--seed",
+                ],
+                &[],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            run_public_goods(&args[1..1 + strategies], &options);
+        }
+        // the-duel mdp [--hit-points 10] [--attack 0.5] [--riposte 1] [--residuals]
+        Some("mdp") => {
+            let usage = "usage: the-duel mdp [--hit-points <n>] [--attack <p>] [--riposte <damage>] [--tolerance <t>] [--max-iterations <n>] [--evaluation-sweeps <n>] [--residuals]";
+            let options = Options::parse(
+                &args[1..],
+                &[
+                    "--hit-points",
+                    "--attack",
+                    "--riposte",
+                    "--tolerance",
+                    "--max-iterations",
+                    "--evaluation-sweeps",
+                ],
+                &["--residuals"],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            run_duel_mdp(&options);
+        }
+        // the-duel gridworld [--layout world.txt] [--slip 0.2] [--living-reward -0.04]
+        Some("gridworld") => {
+            let usage = "usage: the-duel gridworld [--layout <file>] [--slip <p>] [--living-reward <r>] [--discount <d>] [--episodes <n>] [--max-steps <n>] [--seed <n>] [--tolerance <t>] [--max-iterations <n>] [--evaluation-sweeps <n>] [--residuals]";
+            let options = Options::parse(
+                &args[1..],
+                &[
+                    "--layout",
+                    "--slip",
+                    "--living-reward",
+                    "--discount",
+                    "--episodes",
+                    "--max-steps",
+                    "--seed",
+                    "--tolerance",
+                    "--max-iterations",
+                    "--evaluation-sweeps",
+                ],
+                &["--residuals"],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            run_gridworld(&options);
+        }
+        // the-duel repeated tit_for_tat grim_trigger defect [--continuation 0.9] [--discount 0.95]
+        Some("repeated") => {
+            let usage = "usage: the-duel repeated [<strategy>...] [--matrix <game.toml>] [--continuation <p>] [--discount <d>] [--max-rounds <n>] [--retrials <n>] [--seed <n>]";
+            let strategies = args[1..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            let options = Options::parse(
+                &args[1 + strategies..],
+                &[
+                    "--matrix",
+                    "--continuation",
+                    "--discount",
+                    "--max-rounds",
+                    "--retrials",
+                    "--seed",
+                ],
+                &[],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            run_repeated_game(&args[1..1 + strategies], &options);
+        }
+        // the-duel tree [kuhn_poker.tree] [--iterations 10000] [--print]
+        Some("tree") => {
+            let usage = "usage: the-duel tree [<game.tree>] [--iterations <n>] [--print]";
+            let path = args.get(1).filter(|arg| !arg.starts_with("--"));
+            let options = Options::parse(
+                &args[1 + path.is_some() as usize..],
+                &["--iterations"],
+                &["--print"],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            run_game_tree(path.map(|path| path.as_str()), &options);
+        }
+        // the-duel matrix experiments/stag_hunt.toml mirror fictitious_play "constant(1)" [--rounds 100]
+        Some("matrix") => {
+            let usage = "usage: the-duel matrix <game.toml> [<strategy>...] [--rounds <n>] [--retrials <n>] [--seed <n>]";
+            if args.len() < 2 || args[1].starts_with("--") {
+                exit_with_error(usage);
+            }
+            let strategies = args[2..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            let options = Options::parse(
+                &args[2 + strategies..],
+                &["--rounds", "--retrials", "--seed"],
+                &[],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            run_matrix_game(&args[1], &args[2..2 + strategies], &options);
+        }
+        // the-duel pennies fictitious_play regret_matching [--payoffs 3,1,1] [--iterations 1000]
+        Some("pennies") => {
+            let usage = "usage: the-duel pennies [<strategy>...] [--payoffs <heads,tails,mismatch>] [--iterations <n>] [--rounds <n>] [--retrials <n>] [--seed <n>]";
+            let strategies = args[1..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            let options = Options::parse(
+                &args[1 + strategies..],
+                &[
+                    "--payoffs",
+                    "--iterations",
+                    "--rounds",
+                    "--retrials",
+                    "--seed",
+                ],
+                &[],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            run_matching_pennies(&args[1..1 + strategies], &options);
+        }
+        // the-duel host "one_step" 0.0.0.0:7878 [--hit-points 10] [--seed 1] [--replay duel.jsonl]
+        Some("host") => {
+            let usage = "usage: the-duel host <agent> <address> [--hit-points <n>] [--seed <n>] [--replay <file>]";
+            if args.len() < 3 {
+                exit_with_error(usage);
+            }
+            let options = Options::parse(&args[3..], &["--hit-points", "--seed", "--replay"], &[])
+                .unwrap_or_else(|| exit_with_error(usage));
+            let max_hp = options.parsed_or("--hit-points", 600);
+            if max_hp < 1 {
+                exit_with_error("--hit-points has to be at least 1");
+            }
+            println!("Waiting for an opponent on {}", args[2]);
+            let duel = NetworkDuel::host(
+                args[2].as_str(),
+                &registry,
+                &args[1],
+                options.parsed_or("--seed", 106),
+                max_hp,
+                GameSettings::default(),
+            )
+            .unwrap_or_else(|err| exit_with_error(err));
+            run_network(duel, &options);
+        }
+        // the-duel join "markov(0.3, 0.6)" localhost:7878 [--seed 2] [--replay duel.jsonl]
+        Some("join") => {
+            let usage = "usage: the-duel join <agent> <address> [--seed <n>] [--replay <file>]";
+            if args.len() < 3 {
+                exit_with_error(usage);
+            }
+            let options = Options::parse(&args[3..], &["--seed", "--replay"], &[])
+                .unwrap_or_else(|| exit_with_error(usage));
+            let duel = NetworkDuel::join(
+                args[2].as_str(),
+                &registry,
+                &args[1],
+                options.parsed_or("--seed", 106),
+            )
+            .unwrap_or_else(|err| exit_with_error(err));
+            run_network(duel, &options);
+        }
+        // the-duel serve [127.0.0.1:8080]
+        Some("serve") => {
+            if args.len() > 2 {
+                exit_with_error("usage: the-duel serve [<address>]");
+            }
+            let address = args.get(1).map_or("127.0.0.1:8080", String::as_str);
+            run_server(address, registry);
+        }
+        // the-duel replay duel.jsonl [--verify] [--plot hp.svg]
+        Some("replay") => {
+            let usage = "usage: the-duel replay <file> [--verify] [--plot <file>]";
+            if args.len() < 2 {
+                exit_with_error(usage);
+            }
+            let options = Options::parse(&args[2..], &["--plot"], &["--verify"])
+                .unwrap_or_else(|| exit_with_error(usage));
+            let replay = Replay::load(&args[1]).unwrap_or_else(|err| exit_with_error(err));
+            if options.flag("--verify") {
+                replay
+                    .verify(&registry)
+                    .unwrap_or_else(|err| exit_with_error(err));
+                println!("Replay of {} turns verified", replay.turns.len());
+            } else {
+                write_hit_points_csv(&replay);
+                print_replay(&replay, None);
+            }
+            if let Some(path) = options.value("--plot") {
+                std::fs::write(path, hit_point_svg(&replay))
+                    .unwrap_or_else(|err| exit_with_error(err));
+                println!("Hit point plot written to {}", path);
+            }
+        }
+        // the-duel seeds mirror attack winner=1 "turns>20" [--hit-points 10] [--from 0] [--count 10000] [--limit 5] [--save fixtures]
+        Some("seeds") => {
+            let usage = "usage: the-duel seeds <agent> <agent> <condition>... [--hit-points <n>] [--from <seed>] [--count <n>] [--limit <n>] [--save <directory>]";
+            if args.len() < 4 {
+                exit_with_error(usage);
+            }
+            let num_conditions = args[3..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            let conditions = args[3..3 + num_conditions]
+                .iter()
+                .map(|condition| GameCondition::parse(condition))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap_or_else(|err| exit_with_error(err));
+            let options = Options::parse(
+                &args[3 + num_conditions..],
+                &["--hit-points", "--from", "--count", "--limit", "--save"],
+                &[],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            let search = SeedSearch::new(
+                &registry,
+                &args[1],
+                &args[2],
+                options.parsed_or("--hit-points", 600),
+                conditions,
+            );
+            run_seed_search(&search, &options);
+        }
+        // the-duel hmm "markov(0.2, 0.1)" attack [--iterations 100]
+        Some("hmm") => {
+            let usage = "usage: the-duel hmm <agent> <opponent> [--iterations <n>]";
+            if args.len() < 3 {
+                exit_with_error(usage);
+            }
+            let options = Options::parse(&args[3..], &["--iterations"], &[])
+                .unwrap_or_else(|| exit_with_error(usage));
+            run_hmm(&registry, &args[1], &args[2], &options);
+        }
+        // the-duel reproduce experiments/pitting.toml 3 5 1234
+        Some("reproduce") => {
+            let usage =
+                "usage: the-duel reproduce <experiment.toml> <player one> <player two> <retrial>";
+            if args.len() != 5 {
+                exit_with_error(usage);
+            }
+            let config =
+                ExperimentConfig::load(&args[1]).unwrap_or_else(|err| exit_with_error(err));
+            let _logging = experiment_logging(&config);
+            let [player_one, player_two, retrial] = [&args[2], &args[3], &args[4]]
+                .map(|arg| arg.parse().unwrap_or_else(|_| exit_with_error(usage)));
+            run_reproduce(&config, &registry, player_one, player_two, retrial as u64);
+        }
+        // the-duel train experiments/pitting.toml
+        Some("train") => {
+            if args.len() != 2 {
+                exit_with_error("usage: the-duel train <experiment.toml>");
+            }
+            let config =
+                ExperimentConfig::load(&args[1]).unwrap_or_else(|err| exit_with_error(err));
+            let _logging = experiment_logging(&config);
+            let self_play = config.self_play.clone().unwrap_or_else(|| {
+                exit_with_error(format!("{} has no [self_play] section", args[1]))
+            });
+            run_self_play(&config, &registry, &self_play);
+        }
+        // the-duel sweep experiments/pitting.toml "random({p})" p=0.0..1.0:0.05 [--output sweep.csv]
+        Some("sweep") => {
+            let usage = "usage: the-duel sweep <experiment.toml> <agent template> <name=start..end:step>... [--output <file>]";
+            if args.len() < 4 {
+                exit_with_error(usage);
+            }
+            let config =
+                ExperimentConfig::load(&args[1]).unwrap_or_else(|err| exit_with_error(err));
+            let _logging = experiment_logging(&config);
+            let num_ranges = args[3..]
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            let ranges = args[3..3 + num_ranges]
+                .iter()
+                .map(|range| ParameterRange::parse(range))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap_or_else(|err| exit_with_error(err));
+            let options = Options::parse(&args[3 + num_ranges..], &["--output"], &[])
+                .unwrap_or_else(|| exit_with_error(usage));
+            run_sweep(&config, &registry, &Sweep::new(&args[2], ranges), &options);
+        }
+        // the-duel experiments/pitting.toml [--format csv,json,parquet] [--tui] [--broadcast 127.0.0.1:9001] [--progress] [--audit-rng draws.csv]
+        Some(path) => {
+            let usage = "usage: the-duel <experiment.toml> [--format <formats>] [--tui] [--broadcast <address>] [--progress] [--audit-rng <file>]";
+            let mut config =
+                ExperimentConfig::load(path).unwrap_or_else(|err| exit_with_error(err));
+            let _logging = experiment_logging(&config);
+            let options = Options::parse(
+                &args[1..],
+                &["--format", "--broadcast", "--audit-rng"],
+                &["--tui", "--progress"],
+            )
+            .unwrap_or_else(|| exit_with_error(usage));
+            if let Some(formats) = options.value("--format") {
+                config.output.formats = formats
+                    .split(',')
+                    .map(|name| {
+                        OutputFormat::parse(name).unwrap_or_else(|| {
+                            exit_with_error(format!("unknown output format '{}'", name))
+                        })
+                    })
+                    .collect();
+            }
+            audited(options.value("--audit-rng"), || {
+                run_experiment(
+                    &config,
+                    &registry,
+                    options.flag("--tui"),
+                    options.value("--broadcast"),
+                    options.flag("--progress"),
+                )
+            });
+        }
+        None => run_duel(
+            &registry,
+            "one_step(-3, -1, -3)",
+            "markov(0.3, 0.6, FINCH)",
+            &Options::parse(&[], &[], &[]).unwrap(),
+        ),
+    }
 }
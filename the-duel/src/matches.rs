@@ -0,0 +1,127 @@
+use serde::Deserialize;
+
+use crate::agents::GameAgent;
+use crate::game::{Game, GameOutcome, GameSettings, SimultaneousGame};
+use crate::observer::{GameObserver, NullObserver};
+use crate::tournament::PairingRecord;
+
+// How many games make up one match between two agents.
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct MatchFormat {
+    // Play at most this many games; the first agent to win a majority takes the match.
+    pub best_of: u64,
+    // Keep the same agent instances for every game of a match instead of
+    // starting each game from fresh copies, so agents can adapt within a match.
+    pub persistent_agents: bool,
+}
+
+impl MatchFormat {
+    pub fn new(best_of: u64) -> Self {
+        Self {
+            best_of,
+            persistent_agents: false,
+        }
+    }
+
+    pub fn with_persistent_agents(mut self, persistent_agents: bool) -> Self {
+        self.persistent_agents = persistent_agents;
+        self
+    }
+
+    // An even number of games could end in a tie nobody breaks.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.best_of.is_multiple_of(2) {
+            return Err(format!(
+                "a match has to be the best of an odd number of games, not {}",
+                self.best_of
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn wins_needed(&self) -> u64 {
+        self.best_of / 2 + 1
+    }
+}
+
+impl Default for MatchFormat {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+// The games of one match and who took it, seen from player one.
+pub struct MatchResult {
+    pub games: PairingRecord,
+    pub outcome: GameOutcome,
+}
+
+// A best-of-N series of games between two agents.
+pub struct Match<'a, G: SimultaneousGame + Clone> {
+    pub rules: &'a G,
+    pub settings: &'a GameSettings,
+    pub format: &'a MatchFormat,
+    pub player_one_agent: &'a dyn GameAgent<G>,
+    pub player_two_agent: &'a dyn GameAgent<G>,
+}
+
+impl<'a, G: SimultaneousGame + Clone> Match<'a, G> {
+    pub fn new(
+        rules: &'a G,
+        settings: &'a GameSettings,
+        format: &'a MatchFormat,
+        player_one_agent: &'a dyn GameAgent<G>,
+        player_two_agent: &'a dyn GameAgent<G>,
+    ) -> Self {
+        Self {
+            rules,
+            settings,
+            format,
+            player_one_agent,
+            player_two_agent,
+        }
+    }
+
+    pub fn play(&self) -> MatchResult {
+        self.play_observed(&mut NullObserver, (0, 1))
+    }
+
+    // Plays games until one agent has won a majority or `best_of` games are
+    // used up. `seats` are the roster indices reported to `observer`.
+    pub fn play_observed(
+        &self,
+        observer: &mut dyn GameObserver<G>,
+        seats: (usize, usize),
+    ) -> MatchResult {
+        let mut games = PairingRecord::default();
+        let mut agents = None;
+        while games.games() < self.format.best_of
+            && games.wins.max(games.losses) < self.format.wins_needed()
+        {
+            let (player_one_agent, player_two_agent) = match agents.take() {
+                Some(agents) if self.format.persistent_agents => agents,
+                _ => (
+                    self.player_one_agent.copy_self_to_anom(),
+                    self.player_two_agent.copy_self_to_anom(),
+                ),
+            };
+            observer.on_game_start(seats.0, seats.1);
+            let mut game = Game::new(self.rules.clone(), player_one_agent, player_two_agent)
+                .with_settings(self.settings.clone());
+            let mut state = game.initial_state();
+            games.record(&game.play_observed(&mut state, observer));
+            agents = Some((game.player_one_agent, game.player_two_agent));
+        }
+
+        // Games that ended without a winner can leave both agents short of a majority
+        let outcome = if games.wins > games.losses {
+            GameOutcome::WIN(1)
+        } else if games.losses > games.wins {
+            GameOutcome::WIN(2)
+        } else {
+            GameOutcome::TIE
+        };
+        MatchResult { games, outcome }
+    }
+}
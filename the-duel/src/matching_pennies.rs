@@ -0,0 +1,237 @@
+// Matching pennies, played for a fixed number of rounds: both players show
+// heads or tails, player one wins if the pennies match and player two if
+// they do not. The winner scores what the loser loses. In the biased game,
+// matching heads and matching tails pay differently, which moves the unique
+// mixed equilibrium away from a fair coin by a known amount, making the game
+// a benchmark for the equilibrium solvers of the crate.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{GameOutcome, GameSettings, GameState, SimultaneousGame};
+use crate::observer::Scored;
+use crate::openspiel::SpielGame;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Penny {
+    HEADS,
+    TAILS,
+}
+
+impl Penny {
+    pub const ALL: [Penny; 2] = [Penny::HEADS, Penny::TAILS];
+
+    // Position of the side in `ALL`.
+    pub fn index(&self) -> usize {
+        match self {
+            Penny::HEADS => 0,
+            Penny::TAILS => 1,
+        }
+    }
+}
+
+impl fmt::Display for Penny {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// What the matcher wins on matching heads and on matching tails, and what it
+// loses on a mismatch; 1 each in the standard game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PenniesPayoffs {
+    pub heads: i64,
+    pub tails: i64,
+    pub mismatch: i64,
+}
+
+impl Default for PenniesPayoffs {
+    fn default() -> Self {
+        Self {
+            heads: 1,
+            tails: 1,
+            mismatch: 1,
+        }
+    }
+}
+
+// The mixed equilibrium: the probabilities of heads of the matcher and of
+// the mismatcher, and the matcher's expected payoff per round.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PenniesEquilibrium {
+    pub matcher_heads: f64,
+    pub mismatcher_heads: f64,
+    pub value: f64,
+}
+
+impl PenniesPayoffs {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.heads <= 0 || self.tails <= 0 || self.mismatch <= 0 {
+            return Err(format!(
+                "all payoffs have to be positive, got heads {}, tails {} and mismatch {}",
+                self.heads, self.tails, self.mismatch
+            ));
+        }
+        Ok(())
+    }
+
+    // The matcher's payoff, which the mismatcher pays.
+    pub fn payoff(&self, matcher: &Penny, mismatcher: &Penny) -> i64 {
+        match (matcher, mismatcher) {
+            (Penny::HEADS, Penny::HEADS) => self.heads,
+            (Penny::TAILS, Penny::TAILS) => self.tails,
+            _ => -self.mismatch,
+        }
+    }
+
+    // The matcher's payoffs by its own and then the mismatcher's side, in
+    // the order of `Penny::ALL`, as the solvers of `cfr` take them.
+    pub fn matrix(&self) -> Vec<Vec<f64>> {
+        Penny::ALL
+            .iter()
+            .map(|matcher| {
+                Penny::ALL
+                    .iter()
+                    .map(|mismatcher| self.payoff(matcher, mismatcher) as f64)
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Both players make the other indifferent between heads and tails,
+    // which for this game makes them show heads equally often.
+    pub fn equilibrium(&self) -> PenniesEquilibrium {
+        let (heads, tails, mismatch) = (self.heads as f64, self.tails as f64, self.mismatch as f64);
+        let p = (tails + mismatch) / (heads + tails + 2.0 * mismatch);
+        PenniesEquilibrium {
+            matcher_heads: p,
+            mismatcher_heads: p,
+            value: p * heads - (1.0 - p) * mismatch,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Coin {
+    // Player one matches, player two mismatches.
+    pub matcher: bool,
+    pub score: i64,
+}
+
+impl Scored for Coin {
+    fn score(&self) -> f64 {
+        self.score as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchingPennies {
+    pub payoffs: PenniesPayoffs,
+    pub rounds: usize,
+}
+
+impl Default for MatchingPennies {
+    fn default() -> Self {
+        Self {
+            payoffs: PenniesPayoffs::default(),
+            rounds: 100,
+        }
+    }
+}
+
+impl MatchingPennies {
+    pub fn new(payoffs: PenniesPayoffs, rounds: usize) -> Self {
+        Self { payoffs, rounds }
+    }
+}
+
+impl SimultaneousGame for MatchingPennies {
+    type PlayerState = Coin;
+    type Action = Penny;
+
+    fn initial_state(&self) -> GameState<Self> {
+        GameState::new(
+            Coin {
+                matcher: true,
+                score: 0,
+            },
+            Coin {
+                matcher: false,
+                score: 0,
+            },
+        )
+    }
+
+    fn resolve_actions(
+        &self,
+        state: &mut GameState<Self>,
+        player_one_action: Penny,
+        player_two_action: Penny,
+    ) {
+        let payoff = self.payoffs.payoff(&player_one_action, &player_two_action);
+        state.player_one_state.score += payoff;
+        state.player_two_state.score -= payoff;
+    }
+
+    fn check_end_condition(&self, state: &GameState<Self>) -> GameOutcome {
+        if state.history.turns() < self.rounds {
+            return GameOutcome::CONTINUE;
+        }
+        let (one, two) = (state.player_one_state.score, state.player_two_state.score);
+        match one.cmp(&two) {
+            std::cmp::Ordering::Greater => GameOutcome::WIN(1),
+            std::cmp::Ordering::Less => GameOutcome::WIN(2),
+            std::cmp::Ordering::Equal => GameOutcome::TIE,
+        }
+    }
+}
+
+// Heads is action 0, tails action 1. The observation holds whether the
+// player matches and the fraction of rounds played, followed by the
+// opponent's last side one-hot encoded.
+impl SpielGame for MatchingPennies {
+    fn num_distinct_actions(&self) -> usize {
+        Penny::ALL.len()
+    }
+
+    fn action_id(&self, action: &Penny) -> usize {
+        action.index()
+    }
+
+    fn action_from_id(&self, id: usize) -> Option<Penny> {
+        Penny::ALL.get(id).cloned()
+    }
+
+    fn action_to_string(&self, id: usize) -> String {
+        match self.action_from_id(id) {
+            Some(action) => action.to_string(),
+            None => format!("invalid action {}", id),
+        }
+    }
+
+    fn observation_tensor_size(&self) -> usize {
+        2 + Penny::ALL.len()
+    }
+
+    fn observation_tensor(
+        &self,
+        state: &GameState<Self>,
+        _settings: &GameSettings,
+        player: u64,
+    ) -> Vec<f32> {
+        let opposing_action = state.observed_opposing_action(player);
+        let mut tensor = vec![
+            (player == 1) as u8 as f32,
+            state.history.turns() as f32 / self.rounds.max(1) as f32,
+        ];
+        tensor.extend(
+            Penny::ALL
+                .iter()
+                .map(|action| (opposing_action.as_ref() == Some(action)) as u8 as f32),
+        );
+        tensor
+    }
+}
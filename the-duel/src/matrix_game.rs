@@ -0,0 +1,238 @@
+// Any normal-form game of two players, given by its payoff bimatrix and
+// played for a fixed number of rounds. Player one picks a row, player two a
+// column, and each scores its entry of the cell. Games are loaded from TOML
+// files like
+//   name = "Stag hunt"
+//   rows = ["stag", "hare"]
+//   columns = ["stag", "hare"]
+//   payoffs = [[[4, 4], [0, 3]], [[3, 0], [3, 3]]]
+// where `payoffs[row][column]` holds the payoffs of player one and two.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::ConfigError;
+use crate::game::{GameOutcome, GameSettings, GameState, SimultaneousGame};
+use crate::observer::Scored;
+use crate::openspiel::SpielGame;
+
+// The index of a row for player one, of a column for player two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Strategy(pub usize);
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatrixPlayer {
+    // Player one picks rows, player two columns.
+    pub picks_rows: bool,
+    pub score: f64,
+}
+
+impl Scored for MatrixPlayer {
+    fn score(&self) -> f64 {
+        self.score
+    }
+}
+
+fn default_rounds() -> usize {
+    100
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatrixGame {
+    #[serde(default)]
+    pub name: String,
+    pub rows: Vec<String>,
+    pub columns: Vec<String>,
+    pub payoffs: Vec<Vec<[f64; 2]>>,
+    #[serde(default = "default_rounds")]
+    pub rounds: usize,
+}
+
+impl MatrixGame {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, ConfigError> {
+        let game: Self = toml::from_str(contents).map_err(ConfigError::Parse)?;
+        game.validate().map_err(ConfigError::Invalid)?;
+        Ok(game)
+    }
+
+    // Checks that there is a cell for every row and column.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rows.is_empty() || self.columns.is_empty() {
+            return Err(String::from("a matrix game needs rows and columns"));
+        }
+        if self.payoffs.len() != self.rows.len()
+            || self
+                .payoffs
+                .iter()
+                .any(|row| row.len() != self.columns.len())
+        {
+            return Err(format!(
+                "the payoffs need {} rows of {} cells",
+                self.rows.len(),
+                self.columns.len()
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn with_rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    // The number of strategies of the player picking rows or columns.
+    pub fn num_strategies(&self, picks_rows: bool) -> usize {
+        if picks_rows {
+            self.rows.len()
+        } else {
+            self.columns.len()
+        }
+    }
+
+    pub fn strategy_name(&self, picks_rows: bool, strategy: Strategy) -> &str {
+        if picks_rows {
+            &self.rows[strategy.0]
+        } else {
+            &self.columns[strategy.0]
+        }
+    }
+
+    // The payoff of playing `own` against `opposing`, for the player picking
+    // rows or columns.
+    pub fn payoff(&self, picks_rows: bool, own: Strategy, opposing: Strategy) -> f64 {
+        if picks_rows {
+            self.payoffs[own.0][opposing.0][0]
+        } else {
+            self.payoffs[opposing.0][own.0][1]
+        }
+    }
+
+    // Whether both players score the opposite of each other in every cell.
+    pub fn is_zero_sum(&self) -> bool {
+        self.payoffs
+            .iter()
+            .flatten()
+            .all(|[one, two]| (one + two).abs() < 1e-9)
+    }
+
+    // Player one's payoffs, the matrix the solvers of `cfr` take for
+    // zero-sum games.
+    pub fn row_payoffs(&self) -> Vec<Vec<f64>> {
+        self.payoffs
+            .iter()
+            .map(|row| row.iter().map(|cell| cell[0]).collect())
+            .collect()
+    }
+}
+
+impl SimultaneousGame for MatrixGame {
+    type PlayerState = MatrixPlayer;
+    type Action = Strategy;
+
+    fn initial_state(&self) -> GameState<Self> {
+        GameState::new(
+            MatrixPlayer {
+                picks_rows: true,
+                score: 0.0,
+            },
+            MatrixPlayer {
+                picks_rows: false,
+                score: 0.0,
+            },
+        )
+    }
+
+    // Strategies beyond the player's last are played as its last.
+    fn enforce_legality(
+        &self,
+        _state: &GameState<Self>,
+        player: u64,
+        action: Strategy,
+    ) -> Strategy {
+        Strategy(action.0.min(self.num_strategies(player == 1) - 1))
+    }
+
+    fn resolve_actions(
+        &self,
+        state: &mut GameState<Self>,
+        player_one_action: Strategy,
+        player_two_action: Strategy,
+    ) {
+        let [one, two] = self.payoffs[player_one_action.0][player_two_action.0];
+        state.player_one_state.score += one;
+        state.player_two_state.score += two;
+    }
+
+    fn check_end_condition(&self, state: &GameState<Self>) -> GameOutcome {
+        if state.history.turns() < self.rounds {
+            return GameOutcome::CONTINUE;
+        }
+        let (one, two) = (state.player_one_state.score, state.player_two_state.score);
+        match one.total_cmp(&two) {
+            std::cmp::Ordering::Greater => GameOutcome::WIN(1),
+            std::cmp::Ordering::Less => GameOutcome::WIN(2),
+            std::cmp::Ordering::Equal => GameOutcome::TIE,
+        }
+    }
+}
+
+// Strategies are numbered by their row or column. The observation holds
+// whether the player picks rows and the fraction of rounds played, followed
+// by the opponent's last strategy one-hot encoded.
+impl SpielGame for MatrixGame {
+    fn num_distinct_actions(&self) -> usize {
+        self.rows.len().max(self.columns.len())
+    }
+
+    fn action_id(&self, action: &Strategy) -> usize {
+        action.0
+    }
+
+    fn action_from_id(&self, id: usize) -> Option<Strategy> {
+        (id < self.num_distinct_actions()).then_some(Strategy(id))
+    }
+
+    fn action_to_string(&self, id: usize) -> String {
+        format!("strategy {}", id)
+    }
+
+    fn observation_tensor_size(&self) -> usize {
+        2 + self.num_distinct_actions()
+    }
+
+    fn observation_tensor(
+        &self,
+        state: &GameState<Self>,
+        _settings: &GameSettings,
+        player: u64,
+    ) -> Vec<f32> {
+        let opposing_action = state.observed_opposing_action(player);
+        let mut tensor = vec![
+            (player == 1) as u8 as f32,
+            state.history.turns() as f32 / self.rounds.max(1) as f32,
+        ];
+        tensor.extend(
+            (0..self.num_distinct_actions())
+                .map(|id| (opposing_action == Some(Strategy(id))) as u8 as f32),
+        );
+        tensor
+    }
+
+    fn legal_actions(&self, _state: &GameState<Self>, player: u64) -> Vec<usize> {
+        (0..self.num_strategies(player == 1)).collect()
+    }
+}
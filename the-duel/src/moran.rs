@@ -0,0 +1,171 @@
+use rand::Rng;
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct MoranConfig {
+    pub population_size: usize,
+    // Simulated invasions per pair of mutant and resident strategy.
+    #[serde(default = "default_runs")]
+    pub runs: usize,
+    // Weight of the payoff in an individual's fitness, 1 - w + w * payoff.
+    #[serde(default = "default_selection_intensity")]
+    pub selection_intensity: f64,
+    // Chance that an offspring takes up a uniformly random strategy. With a
+    // positive rate, the long-run abundance of every strategy is estimated too.
+    #[serde(default)]
+    pub mutation_rate: f64,
+    // Birth-death steps of the abundance estimate.
+    #[serde(default = "default_steps")]
+    pub steps: usize,
+}
+
+fn default_runs() -> usize {
+    1000
+}
+
+fn default_selection_intensity() -> f64 {
+    1.0
+}
+
+fn default_steps() -> usize {
+    100000
+}
+
+// A finite population of `population_size` individuals playing the
+// strategies of a payoff matrix, evolving by a frequency-dependent
+// birth-death Moran process: each step, an individual is picked to reproduce
+// with probability proportional to its fitness, and its offspring replaces a
+// uniformly picked individual.
+pub struct MoranProcess {
+    pub payoffs: Vec<Vec<f64>>,
+    pub population_size: usize,
+    pub selection_intensity: f64,
+}
+
+impl MoranProcess {
+    pub fn new(payoffs: Vec<Vec<f64>>, config: &MoranConfig) -> Self {
+        Self {
+            payoffs,
+            population_size: config.population_size,
+            selection_intensity: config.selection_intensity,
+        }
+    }
+
+    pub fn num_strategies(&self) -> usize {
+        self.payoffs.len()
+    }
+
+    fn fitness(&self, payoff: f64) -> f64 {
+        1.0 - self.selection_intensity + self.selection_intensity * payoff
+    }
+
+    // Whether a single `mutant` takes over a population of `resident`s.
+    fn invades<R: Rng>(&self, mutant: usize, resident: usize, rng: &mut R) -> bool {
+        let n = self.population_size;
+        let others = (n - 1) as f64;
+        let mut mutants = 1;
+        while mutants > 0 && mutants < n {
+            let k = mutants as f64;
+            let residents = (n - mutants) as f64;
+            let mutant_fitness = self.fitness(
+                ((k - 1.0) * self.payoffs[mutant][mutant]
+                    + residents * self.payoffs[mutant][resident])
+                    / others,
+            );
+            let resident_fitness = self.fitness(
+                (k * self.payoffs[resident][mutant]
+                    + (residents - 1.0) * self.payoffs[resident][resident])
+                    / others,
+            );
+            // Only steps that change the number of mutants matter
+            let grow = k * mutant_fitness * residents;
+            let shrink = residents * resident_fitness * k;
+            if grow + shrink <= 0.0 {
+                return false;
+            }
+            if rng.random::<f64>() * (grow + shrink) < grow {
+                mutants += 1;
+            } else {
+                mutants -= 1;
+            }
+        }
+        mutants == n
+    }
+
+    // `fixation[i][j]` estimates the probability that a single individual of
+    // strategy i takes over a population of strategy j.
+    pub fn fixation_probabilities<R: Rng>(&self, runs: usize, rng: &mut R) -> Vec<Vec<f64>> {
+        let num_strategies = self.num_strategies();
+        (0..num_strategies)
+            .map(|mutant| {
+                (0..num_strategies)
+                    .map(|resident| {
+                        let fixations = (0..runs)
+                            .filter(|_| self.invades(mutant, resident, rng))
+                            .count();
+                        fixations as f64 / runs as f64
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Time-averaged share of every strategy over `steps` birth-death steps
+    // with mutation, starting from an even split of the population.
+    pub fn abundance<R: Rng>(&self, mutation_rate: f64, steps: usize, rng: &mut R) -> Vec<f64> {
+        let num_strategies = self.num_strategies();
+        let n = self.population_size;
+        let others = (n - 1) as f64;
+        let mut counts: Vec<usize> = (0..num_strategies)
+            .map(|strategy| n / num_strategies + usize::from(strategy < n % num_strategies))
+            .collect();
+        let mut totals = vec![0.0; num_strategies];
+
+        for _ in 0..steps {
+            let weights: Vec<f64> = (0..num_strategies)
+                .map(|strategy| {
+                    if counts[strategy] == 0 {
+                        return 0.0;
+                    }
+                    let payoff: f64 = (0..num_strategies)
+                        .map(|other| {
+                            let opponents = counts[other] - usize::from(other == strategy);
+                            opponents as f64 * self.payoffs[strategy][other]
+                        })
+                        .sum::<f64>()
+                        / others;
+                    counts[strategy] as f64 * self.fitness(payoff)
+                })
+                .collect();
+            let parent = pick(&weights, rng);
+            let offspring = if rng.random::<f64>() < mutation_rate {
+                rng.random_range(0..num_strategies)
+            } else {
+                parent
+            };
+            let dying = pick(&counts.iter().map(|&c| c as f64).collect::<Vec<_>>(), rng);
+            counts[dying] -= 1;
+            counts[offspring] += 1;
+            for (total, &count) in totals.iter_mut().zip(&counts) {
+                *total += count as f64;
+            }
+        }
+        totals
+            .iter()
+            .map(|total| total / (steps as f64 * n as f64))
+            .collect()
+    }
+}
+
+// Index drawn with probability proportional to its weight.
+pub(crate) fn pick<R: Rng>(weights: &[f64], rng: &mut R) -> usize {
+    let total: f64 = weights.iter().sum();
+    let mut target = rng.random::<f64>() * total;
+    for (index, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return index;
+        }
+        target -= weight;
+    }
+    weights.iter().rposition(|w| *w > 0.0).unwrap_or(0)
+}
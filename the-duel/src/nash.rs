@@ -0,0 +1,217 @@
+// Nash equilibria of small two-player games in normal form, given as a
+// bimatrix whose cell `[row][column]` holds the payoffs of the row and the
+// column player.
+//
+// Equilibria are found by support enumeration: for every pair of equally
+// large sets of rows and columns, the mixes over each that make the other
+// player indifferent between its set are solved for, and kept if they are
+// distributions against which no strategy outside the sets does better.
+// This finds every equilibrium of nondegenerate games, pure ones as
+// supports of a single strategy. Degenerate games, in which a mixed strategy
+// has more best responses than it mixes over, may have whole segments of
+// equilibria, of which only some points are found.
+
+use crate::duel::{Action, DamageMatrix};
+
+// Payoffs and probabilities closer than this count as equal.
+const TOLERANCE: f64 = 1e-9;
+// Support enumeration looks at all 4^n pairs of supports.
+pub const MAX_STRATEGIES: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NashEquilibrium {
+    pub row: Vec<f64>,
+    pub column: Vec<f64>,
+    // The expected payoffs of the row and the column player.
+    pub payoffs: [f64; 2],
+}
+
+impl NashEquilibrium {
+    pub fn is_pure(&self) -> bool {
+        self.row
+            .iter()
+            .chain(&self.column)
+            .all(|p| *p < TOLERANCE || *p > 1.0 - TOLERANCE)
+    }
+
+    fn same_as(&self, other: &Self) -> bool {
+        let close = |a: &[f64], b: &[f64]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6);
+        close(&self.row, &other.row) && close(&self.column, &other.column)
+    }
+}
+
+// A turn of the duel as a bimatrix game with attacking as the first action:
+// each player gains the damage it deals minus the damage it takes.
+pub fn duel_bimatrix(damage: &DamageMatrix) -> Vec<Vec<[f64; 2]>> {
+    Action::ALL
+        .iter()
+        .map(|one| {
+            Action::ALL
+                .iter()
+                .map(|two| {
+                    let [one_taken, two_taken] = damage.damage(one, two);
+                    [
+                        (two_taken - one_taken) as f64,
+                        (one_taken - two_taken) as f64,
+                    ]
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// The cells in which both players play a best response to each other.
+pub fn pure_equilibria(payoffs: &[Vec<[f64; 2]>]) -> Vec<(usize, usize)> {
+    let columns = payoffs.first().map_or(0, |row| row.len());
+    (0..payoffs.len())
+        .flat_map(|row| (0..columns).map(move |column| (row, column)))
+        .filter(|&(row, column)| {
+            let [one, two] = payoffs[row][column];
+            payoffs
+                .iter()
+                .all(|other| other[column][0] <= one + TOLERANCE)
+                && payoffs[row].iter().all(|other| other[1] <= two + TOLERANCE)
+        })
+        .collect()
+}
+
+// All equilibria support enumeration finds, see above, pure ones first.
+pub fn nash_equilibria(payoffs: &[Vec<[f64; 2]>]) -> Result<Vec<NashEquilibrium>, String> {
+    let rows = payoffs.len();
+    let columns = payoffs.first().map_or(0, |row| row.len());
+    if rows == 0 || columns == 0 || payoffs.iter().any(|row| row.len() != columns) {
+        return Err(String::from(
+            "a bimatrix needs rows of the same number of cells",
+        ));
+    }
+    if rows.max(columns) > MAX_STRATEGIES {
+        return Err(format!(
+            "support enumeration takes at most {} strategies per player, got {}",
+            MAX_STRATEGIES,
+            rows.max(columns)
+        ));
+    }
+    let mut equilibria: Vec<NashEquilibrium> = Vec::new();
+    for size in 1..=rows.min(columns) {
+        for row_support in subsets(rows, size) {
+            for column_support in subsets(columns, size) {
+                let Some(equilibrium) = equilibrium_on(payoffs, &row_support, &column_support)
+                else {
+                    continue;
+                };
+                if !equilibria.iter().any(|known| known.same_as(&equilibrium)) {
+                    equilibria.push(equilibrium);
+                }
+            }
+        }
+    }
+    Ok(equilibria)
+}
+
+// The equilibrium mixing over exactly the given rows and columns, if any.
+fn equilibrium_on(
+    payoffs: &[Vec<[f64; 2]>],
+    row_support: &[usize],
+    column_support: &[usize],
+) -> Option<NashEquilibrium> {
+    let (rows, columns) = (payoffs.len(), payoffs[0].len());
+    // The column mix makes the row player indifferent between its rows.
+    let column_mix = indifferent_mix(column_support, row_support, |row, column| {
+        payoffs[row][column][0]
+    })?;
+    let row_mix = indifferent_mix(row_support, column_support, |column, row| {
+        payoffs[row][column][1]
+    })?;
+    let mut row = vec![0.0; rows];
+    for (index, probability) in row_support.iter().zip(&row_mix) {
+        row[*index] = *probability;
+    }
+    let mut column = vec![0.0; columns];
+    for (index, probability) in column_support.iter().zip(&column_mix) {
+        column[*index] = *probability;
+    }
+    let row_values: Vec<f64> = (0..rows)
+        .map(|i| (0..columns).map(|j| column[j] * payoffs[i][j][0]).sum())
+        .collect();
+    let column_values: Vec<f64> = (0..columns)
+        .map(|j| (0..rows).map(|i| row[i] * payoffs[i][j][1]).sum())
+        .collect();
+    let row_value = row_values[row_support[0]];
+    let column_value = column_values[column_support[0]];
+    if row_values.iter().any(|v| *v > row_value + TOLERANCE)
+        || column_values.iter().any(|v| *v > column_value + TOLERANCE)
+    {
+        return None;
+    }
+    Some(NashEquilibrium {
+        row,
+        column,
+        payoffs: [row_value, column_value],
+    })
+}
+
+// The distribution over `support` under which all of `opposing` pay the
+// opponent the same, where `payoff(opposing, own)` is the opponent's payoff.
+fn indifferent_mix(
+    support: &[usize],
+    opposing: &[usize],
+    payoff: impl Fn(usize, usize) -> f64,
+) -> Option<Vec<f64>> {
+    let size = support.len();
+    // Unknowns are the probabilities and the common payoff; equations are
+    // one per opposing strategy, and the probabilities summing to 1.
+    let mut matrix = vec![vec![0.0; size + 1]; size + 1];
+    let mut rhs = vec![0.0; size + 1];
+    for (equation, other) in opposing.iter().enumerate() {
+        for (unknown, own) in support.iter().enumerate() {
+            matrix[equation][unknown] = payoff(*other, *own);
+        }
+        matrix[equation][size] = -1.0;
+    }
+    matrix[size][..size].fill(1.0);
+    rhs[size] = 1.0;
+    let solution = solve_linear(matrix, rhs)?;
+    let mix = solution[..size].to_vec();
+    mix.iter()
+        .all(|p| *p > -TOLERANCE)
+        .then(|| mix.iter().map(|p| p.max(0.0)).collect())
+}
+
+// Solves `matrix * x = rhs` by Gaussian elimination, none if singular.
+pub(crate) fn solve_linear(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+    let size = rhs.len();
+    for pivot in 0..size {
+        let best = (pivot..size)
+            .max_by(|a, b| matrix[*a][pivot].abs().total_cmp(&matrix[*b][pivot].abs()))?;
+        if matrix[best][pivot].abs() < TOLERANCE {
+            return None;
+        }
+        matrix.swap(pivot, best);
+        rhs.swap(pivot, best);
+        let (above, below) = matrix.split_at_mut(pivot + 1);
+        let pivot_row = &above[pivot];
+        for (offset, row) in below.iter_mut().enumerate() {
+            let factor = row[pivot] / pivot_row[pivot];
+            for (entry, pivot_entry) in row[pivot..].iter_mut().zip(&pivot_row[pivot..]) {
+                *entry -= factor * pivot_entry;
+            }
+            rhs[pivot + 1 + offset] -= factor * rhs[pivot];
+        }
+    }
+    let mut solution = vec![0.0; size];
+    for row in (0..size).rev() {
+        let known: f64 = (row + 1..size)
+            .map(|column| matrix[row][column] * solution[column])
+            .sum();
+        solution[row] = (rhs[row] - known) / matrix[row][row];
+    }
+    Some(solution)
+}
+
+// All sets of `size` of the indices below `count`, in increasing order.
+fn subsets(count: usize, size: usize) -> Vec<Vec<usize>> {
+    (0u32..1 << count)
+        .filter(|mask| mask.count_ones() as usize == size)
+        .map(|mask| (0..count).filter(|i| mask & (1 << i) != 0).collect())
+        .collect()
+}
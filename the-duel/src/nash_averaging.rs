@@ -0,0 +1,185 @@
+// Nash averaging (Balduzzi et al., 2018) of a tournament's payoffs.
+//
+// The margins `a[i][j] = payoffs[i][j] - 1/2` of every agent over an even
+// split make up an antisymmetric, symmetric zero-sum meta-game whose value
+// is 0. Agents are rated by their margin against its maximum entropy Nash
+// equilibrium: no agent rates above 0, and those in the equilibrium's
+// support rate exactly 0. Unlike averages over the roster, the ratings do
+// not change when an agent is added again: the copies split its share of
+// the equilibrium between them, so five near-identical random agents weigh
+// no more than one.
+//
+// The maximum entropy equilibrium is approached along the logit quantal
+// response equilibria, the mixes `p` with `p = softmax(a p / temperature)`,
+// which are unique for zero-sum games and converge to it as the temperature
+// falls. Every one is solved by Newton's method on the logarithms of the
+// shares, starting from the one at the last temperature; shares outside the
+// support vanish like exp(-gap / temperature).
+
+use serde::Deserialize;
+
+use crate::nash::solve_linear;
+
+#[derive(Deserialize, Clone)]
+pub struct NashAveragingConfig {
+    // The temperature the equilibria are followed down to; the shares of
+    // agents outside the support are of the order of exp(-1 / temperature).
+    #[serde(default = "default_temperature")]
+    pub temperature: f64,
+}
+
+fn default_temperature() -> f64 {
+    1e-5
+}
+
+impl Default for NashAveragingConfig {
+    fn default() -> Self {
+        Self {
+            temperature: default_temperature(),
+        }
+    }
+}
+
+// Every temperature is this fraction of the last one.
+const COOLING: f64 = 0.8;
+const MAX_NEWTON_STEPS: usize = 100;
+const TOLERANCE: f64 = 1e-12;
+
+pub struct NashAverage {
+    // The equilibrium share of every agent.
+    pub shares: Vec<f64>,
+    // Every agent's margin against the equilibrium, 0 at best.
+    pub ratings: Vec<f64>,
+    pub temperature: f64,
+}
+
+impl NashAverage {
+    // Agents by decreasing rating, then decreasing share.
+    pub fn order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.ratings.len()).collect();
+        order.sort_by(|a, b| {
+            self.ratings[*b]
+                .total_cmp(&self.ratings[*a])
+                .then(self.shares[*b].total_cmp(&self.shares[*a]))
+        });
+        order
+    }
+}
+
+// The margins over an even split of every agent against every other,
+// averaged over both directions so that they are exactly antisymmetric.
+pub fn margins(payoffs: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let size = payoffs.len();
+    (0..size)
+        .map(|i| {
+            (0..size)
+                .map(|j| (payoffs[i][j] - payoffs[j][i]) / 2.0)
+                .collect()
+        })
+        .collect()
+}
+
+// Rates the agents of the symmetric meta-game `payoffs[i][j]`, the expected
+// score of agent i against agent j with 1/2 for an even split.
+pub fn nash_average(payoffs: &[Vec<f64>], config: &NashAveragingConfig) -> NashAverage {
+    let margins = margins(payoffs);
+    let size = margins.len();
+    let scale = margins
+        .iter()
+        .flatten()
+        .fold(0.0_f64, |largest, margin| largest.max(margin.abs()));
+    // Shares start out uniform, the equilibrium at infinite temperature.
+    let mut logs = vec![-(size as f64).ln(); size];
+    let mut temperature = scale.max(TOLERANCE);
+    let target = config.temperature.max(f64::MIN_POSITIVE);
+    loop {
+        temperature = (temperature * COOLING).max(target);
+        logs = quantal_response(&margins, logs, temperature);
+        if temperature <= target {
+            break;
+        }
+    }
+    let shares: Vec<f64> = logs.iter().map(|log| log.exp()).collect();
+    let ratings = (0..size)
+        .map(|i| (0..size).map(|j| margins[i][j] * shares[j]).sum())
+        .collect();
+    NashAverage {
+        shares,
+        ratings,
+        temperature,
+    }
+}
+
+// The logarithms of the logit quantal response equilibrium at the given
+// temperature, by Newton's method from `logs` on `x - log softmax(a p / t) = 0`
+// with `p = exp(x)`. Steps are halved while they do not reduce the residual.
+fn quantal_response(margins: &[Vec<f64>], mut logs: Vec<f64>, temperature: f64) -> Vec<f64> {
+    let size = margins.len();
+    // The residual, its largest entry and the softmax of the fitness.
+    let residual = |logs: &[f64]| -> (Vec<f64>, f64, Vec<f64>) {
+        let fitness: Vec<f64> = (0..size)
+            .map(|i| {
+                (0..size)
+                    .map(|j| margins[i][j] * logs[j].exp())
+                    .sum::<f64>()
+                    / temperature
+            })
+            .collect();
+        let log_softmax = log_normalize(&fitness);
+        let equations: Vec<f64> = (0..size).map(|i| logs[i] - log_softmax[i]).collect();
+        let norm = equations.iter().fold(0.0_f64, |n, e| n.max(e.abs()));
+        let softmax = log_softmax.iter().map(|log| log.exp()).collect();
+        (equations, norm, softmax)
+    };
+
+    let (mut equations, mut norm, mut softmax) = residual(&logs);
+    for _ in 0..MAX_NEWTON_STEPS {
+        if norm < TOLERANCE {
+            break;
+        }
+        // The derivative of `x_i - f_i + log(sum(exp(f)))` in `x_j`, with
+        // the fitness `f = a p / t`.
+        let mut jacobian = vec![vec![0.0; size]; size];
+        for j in 0..size {
+            let share = logs[j].exp() / temperature;
+            let average: f64 = (0..size).map(|k| softmax[k] * margins[k][j]).sum();
+            for i in 0..size {
+                jacobian[i][j] = -(margins[i][j] - average) * share;
+            }
+            jacobian[j][j] += 1.0;
+        }
+        let rhs: Vec<f64> = equations.iter().map(|e| -e).collect();
+        let Some(step) = solve_linear(jacobian, rhs) else {
+            break;
+        };
+        let mut length = 1.0;
+        loop {
+            let candidate: Vec<f64> = logs
+                .iter()
+                .zip(&step)
+                .map(|(log, delta)| (log + length * delta).min(0.0))
+                .collect();
+            let (candidate_equations, candidate_norm, candidate_softmax) = residual(&candidate);
+            if candidate_norm < norm || length < 1e-6 {
+                logs = candidate;
+                (equations, norm, softmax) =
+                    (candidate_equations, candidate_norm, candidate_softmax);
+                break;
+            }
+            length /= 2.0;
+        }
+    }
+    log_normalize(&logs)
+}
+
+// Logarithms of the shares proportional to the exponentials of `logs`.
+fn log_normalize(logs: &[f64]) -> Vec<f64> {
+    let largest = logs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let total = largest
+        + logs
+            .iter()
+            .map(|log| (log - largest).exp())
+            .sum::<f64>()
+            .ln();
+    logs.iter().map(|log| log - total).collect()
+}
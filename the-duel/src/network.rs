@@ -0,0 +1,312 @@
+// Duels between two processes over TCP.
+//
+// Each process hosts one agent. The host listens, plays the first seat and
+// decides the rules; the guest connects and plays the second seat. Both sides
+// resolve every turn themselves, so they only exchange actions, one JSON
+// message per line. To keep either side from waiting for the other's action
+// before picking its own, every turn is a commit-reveal handshake: both first
+// send the SHA-256 digest of their action and a random nonce, and only after
+// receiving the other's digest reveal the action and nonce, which the other
+// checks against the digest.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::rc::Rc;
+
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, Duel};
+use crate::game::{GameOutcome, GameSettings, GameState, SimultaneousGame};
+use crate::registry::{AgentRegistry, RegistryError};
+use crate::replay::{Replay, ReplayAgent, ReplayHeader};
+use crate::rng_audit::AuditedRng;
+
+// Bumped whenever the messages change.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum NetworkError {
+    Io(io::Error),
+    Agent(RegistryError),
+    Protocol(String),
+    // The opponent revealed an action that does not match its commitment.
+    BrokenCommitment { turn: usize },
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::Io(err) => write!(f, "connection failed: {}", err),
+            NetworkError::Agent(err) => write!(f, "could not build agent: {}", err),
+            NetworkError::Protocol(msg) => write!(f, "protocol violation: {}", msg),
+            NetworkError::BrokenCommitment { turn } => write!(
+                f,
+                "opponent revealed an action in turn {} that it had not committed to",
+                turn
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+impl From<io::Error> for NetworkError {
+    fn from(err: io::Error) -> Self {
+        NetworkError::Io(err)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "message", rename_all = "snake_case")]
+enum Message {
+    // Sent by the host on connecting.
+    Hello {
+        version: u32,
+        strategy_name: String,
+        max_hit_points: i64,
+        settings: GameSettings,
+    },
+    // The guest's answer to the hello.
+    Join {
+        version: u32,
+        strategy_name: String,
+    },
+    Commit {
+        digest: String,
+    },
+    Reveal {
+        action: Action,
+        nonce: String,
+    },
+}
+
+// Hex-encoded SHA-256 digest binding an action to a nonce.
+pub fn commitment(action: &Action, nonce: &str) -> String {
+    let digest = Sha256::digest(format!("{:?}:{}", action, nonce).as_bytes());
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn new(stream: TcpStream) -> Result<Self, NetworkError> {
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    fn send(&mut self, message: &Message) -> Result<(), NetworkError> {
+        let line = serde_json::to_string(message)
+            .map_err(|err| NetworkError::Protocol(err.to_string()))?;
+        writeln!(self.writer, "{}", line)?;
+        Ok(self.writer.flush()?)
+    }
+
+    fn receive(&mut self) -> Result<Message, NetworkError> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(NetworkError::Protocol(String::from(
+                "opponent closed the connection",
+            )));
+        }
+        serde_json::from_str(&line).map_err(|err| NetworkError::Protocol(err.to_string()))
+    }
+}
+
+fn check_version(version: u32) -> Result<(), NetworkError> {
+    if version == PROTOCOL_VERSION {
+        Ok(())
+    } else {
+        Err(NetworkError::Protocol(format!(
+            "opponent speaks protocol version {}, this build {}",
+            version, PROTOCOL_VERSION
+        )))
+    }
+}
+
+// One side of a networked duel, ready to play once both sides have met.
+pub struct NetworkDuel {
+    pub seed: u64,
+    pub rules: Duel,
+    pub settings: GameSettings,
+    // 1 for the host, 2 for the guest.
+    pub seat: u64,
+    pub spec: String,
+    pub agent: Box<dyn GameAgent>,
+    pub opponent_name: String,
+    connection: Connection,
+}
+
+impl NetworkDuel {
+    fn build_agent(
+        registry: &AgentRegistry,
+        spec: &str,
+        seed: u64,
+    ) -> Result<Box<dyn GameAgent>, NetworkError> {
+        let rng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(seed)));
+        registry.build(spec, &rng).map_err(NetworkError::Agent)
+    }
+
+    // Waits at `address` for a guest to connect, then sends it the rules.
+    pub fn host<A: ToSocketAddrs>(
+        address: A,
+        registry: &AgentRegistry,
+        spec: &str,
+        seed: u64,
+        max_hit_points: i64,
+        settings: GameSettings,
+    ) -> Result<Self, NetworkError> {
+        let agent = Self::build_agent(registry, spec, seed)?;
+        let (stream, _) = TcpListener::bind(address)?.accept()?;
+        let mut connection = Connection::new(stream)?;
+        connection.send(&Message::Hello {
+            version: PROTOCOL_VERSION,
+            strategy_name: agent.strategy_name(),
+            max_hit_points,
+            settings: settings.clone(),
+        })?;
+        let opponent_name = match connection.receive()? {
+            Message::Join {
+                version,
+                strategy_name,
+            } => {
+                check_version(version)?;
+                strategy_name
+            }
+            _ => return Err(NetworkError::Protocol(String::from("expected a join"))),
+        };
+        Ok(Self {
+            seed,
+            rules: Duel::new(max_hit_points),
+            settings,
+            seat: 1,
+            spec: spec.to_string(),
+            agent,
+            opponent_name,
+            connection,
+        })
+    }
+
+    // Connects to a host at `address` and takes on its rules.
+    pub fn join<A: ToSocketAddrs>(
+        address: A,
+        registry: &AgentRegistry,
+        spec: &str,
+        seed: u64,
+    ) -> Result<Self, NetworkError> {
+        let agent = Self::build_agent(registry, spec, seed)?;
+        let mut connection = Connection::new(TcpStream::connect(address)?)?;
+        let (opponent_name, max_hit_points, settings) = match connection.receive()? {
+            Message::Hello {
+                version,
+                strategy_name,
+                max_hit_points,
+                settings,
+            } => {
+                check_version(version)?;
+                (strategy_name, max_hit_points, settings)
+            }
+            _ => return Err(NetworkError::Protocol(String::from("expected a hello"))),
+        };
+        connection.send(&Message::Join {
+            version: PROTOCOL_VERSION,
+            strategy_name: agent.strategy_name(),
+        })?;
+        Ok(Self {
+            seed,
+            rules: Duel::new(max_hit_points),
+            settings,
+            seat: 2,
+            spec: spec.to_string(),
+            agent,
+            opponent_name,
+            connection,
+        })
+    }
+
+    // Commits to `action`, then reveals it, returning the opponent's action.
+    fn exchange(&mut self, action: &Action, turn: usize) -> Result<Action, NetworkError> {
+        let nonce: String = (0..16)
+            .map(|_| format!("{:02x}", rand::rng().random::<u8>()))
+            .collect();
+        self.connection.send(&Message::Commit {
+            digest: commitment(action, &nonce),
+        })?;
+        let digest = match self.connection.receive()? {
+            Message::Commit { digest } => digest,
+            _ => return Err(NetworkError::Protocol(String::from("expected a commit"))),
+        };
+        self.connection.send(&Message::Reveal {
+            action: action.clone(),
+            nonce,
+        })?;
+        match self.connection.receive()? {
+            Message::Reveal { action, nonce } if commitment(&action, &nonce) == digest => {
+                Ok(action)
+            }
+            Message::Reveal { .. } => Err(NetworkError::BrokenCommitment { turn }),
+            _ => Err(NetworkError::Protocol(String::from("expected a reveal"))),
+        }
+    }
+
+    // Plays the duel to the end and records it, the host in the first seat.
+    pub fn play(&mut self) -> Result<Replay, NetworkError> {
+        let mut state: GameState<Duel> = self.rules.initial_state();
+        self.agent.set_mode(self.settings.mode);
+        let outcome = loop {
+            let own_state = if self.seat == 1 {
+                &state.player_one_state
+            } else {
+                &state.player_two_state
+            };
+            let action = self.agent.decide_action(
+                own_state,
+                &state.observed_opposing_action(self.seat),
+                &self.settings.observe_opponent(&state, self.seat),
+                &state.history.view(self.seat),
+            );
+            let opposing = self.exchange(&action, state.history.turns())?;
+            if self.seat == 1 {
+                state.advance(&self.rules, &self.settings, action, opposing);
+            } else {
+                state.advance(&self.rules, &self.settings, opposing, action);
+            }
+            match self.settings.check_end_condition(&self.rules, &state) {
+                GameOutcome::CONTINUE => {}
+                outcome => break outcome,
+            }
+        };
+
+        let local = ReplayAgent {
+            spec: self.spec.clone(),
+            strategy_name: self.agent.strategy_name(),
+        };
+        let remote = ReplayAgent {
+            spec: String::from("remote"),
+            strategy_name: self.opponent_name.clone(),
+        };
+        let (player_one, player_two) = if self.seat == 1 {
+            (local, remote)
+        } else {
+            (remote, local)
+        };
+        let header = ReplayHeader {
+            seed: self.seed,
+            max_hit_points: self.rules.max_hit_points,
+            settings: self.settings.clone(),
+            player_one,
+            player_two,
+        };
+        Ok(Replay::from_history(header, &state.history, outcome))
+    }
+}
@@ -0,0 +1,305 @@
+// Neural network policies trained by REINFORCE.
+//
+// A small multilayer perceptron maps an observation of the duel, i.e. both
+// players' hit points and their last few actions, to the probabilities of
+// attacking and finching. It is trained by playing whole games against the
+// roster and following the gradient of the log-probability of every action
+// taken, scaled by how much better the game's score was than a running
+// baseline. The networks need the `neural` feature.
+
+use serde::Deserialize;
+
+use crate::agents::opposing_hit_points;
+use crate::duel::{Action, DamageMatrix, PlayerState};
+use crate::history::HistoryView;
+
+#[derive(Deserialize, Clone)]
+pub struct TrainingConfig {
+    // Games played, each against an opponent drawn from the roster.
+    pub episodes: usize,
+    // Units of the hidden layer.
+    #[serde(default = "default_hidden")]
+    pub hidden: usize,
+    // Last actions of both players the network observes.
+    #[serde(default = "default_history")]
+    pub history: usize,
+    #[serde(default = "default_learning_rate")]
+    pub learning_rate: f64,
+    // Weight of the latest score in the running baseline.
+    #[serde(default = "default_baseline_rate")]
+    pub baseline_rate: f64,
+    // Safetensors file receiving the trained weights. Training resumes from
+    // it if it already exists.
+    pub weights: Option<String>,
+}
+
+pub(crate) fn default_hidden() -> usize {
+    16
+}
+
+pub(crate) fn default_history() -> usize {
+    3
+}
+
+fn default_learning_rate() -> f64 {
+    0.01
+}
+
+fn default_baseline_rate() -> f64 {
+    0.05
+}
+
+fn encode(action: Option<&Action>) -> f32 {
+    match action {
+        Some(Action::ATTACK) => 1.0,
+        Some(Action::FINCH) => -1.0,
+        None => 0.0,
+    }
+}
+
+// Both players' hit points relative to the maximum, followed by the last
+// `history` actions of the opponent and then of the agent, latest first,
+// attacks as 1, finches as -1 and turns before the game as 0. ONNX policies
+// take the same observation.
+pub fn observation(
+    own_player_state: &PlayerState,
+    opposing_player_state: &Option<PlayerState>,
+    history: &HistoryView,
+    damage: &DamageMatrix,
+    length: usize,
+) -> Vec<f32> {
+    let max = own_player_state.max_hit_points as f32;
+    let opposing = opposing_hit_points(own_player_state, opposing_player_state, history, damage);
+    let mut observation = vec![
+        own_player_state.current_hit_points as f32 / max,
+        opposing as f32 / max,
+    ];
+    for actions in [history.opposing_actions, history.own_actions] {
+        observation.extend(
+            (1..=length)
+                .map(|back| encode(actions.len().checked_sub(back).and_then(|i| actions.get(i)))),
+        );
+    }
+    observation
+}
+
+#[cfg(feature = "neural")]
+pub use network::{EpisodeSummary, NeuralError, Policy, Training, Trajectory};
+
+#[cfg(feature = "neural")]
+mod network {
+    use std::cell::RefCell;
+    use std::fmt;
+    use std::path::Path;
+    use std::rc::Rc;
+
+    use candle_core::{D, DType, Device, Tensor};
+    use candle_nn::{AdamW, Linear, Module, Optimizer, ParamsAdamW, VarBuilder, VarMap};
+    use rand::Rng;
+
+    use super::TrainingConfig;
+    use crate::agents::{GameAgent, NeuralAgent, SharedRng};
+    use crate::duel::Duel;
+    use crate::game::{AgentMode, Game, GameOutcome, GameSettings};
+
+    #[derive(Debug)]
+    pub enum NeuralError {
+        Network(candle_core::Error),
+    }
+
+    impl fmt::Display for NeuralError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                NeuralError::Network(err) => write!(f, "neural network error: {}", err),
+            }
+        }
+    }
+
+    impl std::error::Error for NeuralError {}
+
+    impl From<candle_core::Error> for NeuralError {
+        fn from(err: candle_core::Error) -> Self {
+            NeuralError::Network(err)
+        }
+    }
+
+    // Observations and whether the agent attacked, of every turn of a game.
+    pub type Trajectory = Vec<(Vec<f32>, bool)>;
+
+    // A multilayer perceptron with one hidden layer of rectified units.
+    pub struct Policy {
+        pub varmap: VarMap,
+        pub hidden: usize,
+        pub history: usize,
+        first: Linear,
+        second: Linear,
+    }
+
+    impl Policy {
+        pub fn new(hidden: usize, history: usize) -> Result<Self, NeuralError> {
+            let varmap = VarMap::new();
+            let builder = VarBuilder::from_varmap(&varmap, DType::F32, &Device::Cpu);
+            let first = candle_nn::linear(2 + 2 * history, hidden, builder.pp("first"))?;
+            let second = candle_nn::linear(hidden, 2, builder.pp("second"))?;
+            Ok(Self {
+                varmap,
+                hidden,
+                history,
+                first,
+                second,
+            })
+        }
+
+        // The shape of the network has to match the one that was saved.
+        pub fn load(path: &str, hidden: usize, history: usize) -> Result<Self, NeuralError> {
+            let mut policy = Self::new(hidden, history)?;
+            policy.varmap.load(path)?;
+            Ok(policy)
+        }
+
+        pub fn save(&self, path: &str) -> Result<(), NeuralError> {
+            Ok(self.varmap.save(path)?)
+        }
+
+        // Log-probabilities of attacking and finching, one row per observation.
+        fn log_probabilities(&self, observations: &Tensor) -> candle_core::Result<Tensor> {
+            let hidden = self.first.forward(observations)?.relu()?;
+            candle_nn::ops::log_softmax(&self.second.forward(&hidden)?, D::Minus1)
+        }
+
+        pub fn attack_probability(&self, observation: &[f32]) -> f64 {
+            let probability = || -> candle_core::Result<f32> {
+                let input = Tensor::from_slice(observation, (1, observation.len()), &Device::Cpu)?;
+                self.log_probabilities(&input)?
+                    .exp()?
+                    .get(0)?
+                    .get(0)?
+                    .to_scalar()
+            };
+            // A well-formed observation cannot fail on the CPU
+            probability().map(f64::from).unwrap_or(0.5)
+        }
+
+        // Loss whose gradient is the REINFORCE estimate for one game.
+        fn loss(&self, trajectory: &Trajectory, advantage: f64) -> candle_core::Result<Tensor> {
+            let width = 2 + 2 * self.history;
+            let observations: Vec<f32> = trajectory
+                .iter()
+                .flat_map(|(observation, _)| observation.iter().copied())
+                .collect();
+            let chosen: Vec<f32> = trajectory
+                .iter()
+                .flat_map(|(_, attacked)| if *attacked { [1.0, 0.0] } else { [0.0, 1.0] })
+                .collect();
+            let turns = trajectory.len();
+            let observations = Tensor::from_vec(observations, (turns, width), &Device::Cpu)?;
+            let chosen = Tensor::from_vec(chosen, (turns, 2), &Device::Cpu)?;
+            (self.log_probabilities(&observations)? * chosen)?
+                .sum_all()?
+                .affine(-advantage / turns as f64, 0.0)
+        }
+    }
+
+    // Score of the agent in the first seat.
+    fn score(outcome: &GameOutcome) -> f64 {
+        match outcome {
+            GameOutcome::WIN(1) => 1.0,
+            GameOutcome::WIN(_) => 0.0,
+            _ => 0.5,
+        }
+    }
+
+    pub struct EpisodeSummary {
+        pub episode: usize,
+        pub opponent: String,
+        pub score: f64,
+        pub baseline: f64,
+    }
+
+    pub struct Training {
+        pub rules: Duel,
+        pub settings: GameSettings,
+        pub opponents: Vec<Box<dyn GameAgent>>,
+        pub config: TrainingConfig,
+    }
+
+    impl Training {
+        pub fn new(
+            rules: Duel,
+            opponents: Vec<Box<dyn GameAgent>>,
+            config: TrainingConfig,
+        ) -> Self {
+            Self {
+                rules,
+                settings: GameSettings::default(),
+                opponents,
+                config,
+            }
+        }
+
+        pub fn with_settings(mut self, settings: GameSettings) -> Self {
+            self.settings = settings;
+            self
+        }
+
+        // Trains the policy, starting from the saved weights if there are any,
+        // and saves it after the last episode.
+        pub fn run(
+            &self,
+            rng: &SharedRng,
+        ) -> Result<(Rc<Policy>, Vec<EpisodeSummary>), NeuralError> {
+            let policy = Rc::new(match &self.config.weights {
+                Some(path) if Path::new(path).exists() => {
+                    Policy::load(path, self.config.hidden, self.config.history)?
+                }
+                _ => Policy::new(self.config.hidden, self.config.history)?,
+            });
+            let mut optimizer = AdamW::new(
+                policy.varmap.all_vars(),
+                ParamsAdamW {
+                    lr: self.config.learning_rate,
+                    weight_decay: 0.0,
+                    ..Default::default()
+                },
+            )?;
+
+            let mut baseline = 0.5;
+            let mut summaries = Vec::new();
+            for episode in 0..self.config.episodes {
+                let opponent =
+                    &self.opponents[rng.borrow_mut().random_range(0..self.opponents.len())];
+                let trajectory = Rc::new(RefCell::new(Trajectory::new()));
+                let agent = NeuralAgent::new(rng.clone(), policy.clone(), false)
+                    .with_trajectory(trajectory.clone());
+                let outcome = Game::new(
+                    self.rules.clone(),
+                    Box::new(agent),
+                    opponent.copy_self_to_anom(),
+                )
+                .with_settings(GameSettings {
+                    mode: AgentMode::Train,
+                    ..self.settings.clone()
+                })
+                .play();
+
+                let score = score(&outcome);
+                let trajectory = trajectory.borrow();
+                if !trajectory.is_empty() {
+                    optimizer.backward_step(&policy.loss(&trajectory, score - baseline)?)?;
+                }
+                baseline += self.config.baseline_rate * (score - baseline);
+                summaries.push(EpisodeSummary {
+                    episode,
+                    opponent: opponent.strategy_name(),
+                    score,
+                    baseline,
+                });
+            }
+
+            if let Some(path) = &self.config.weights {
+                policy.save(path)?;
+            }
+            Ok((policy, summaries))
+        }
+    }
+}
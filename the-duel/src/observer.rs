@@ -0,0 +1,203 @@
+use std::ops::DerefMut;
+
+use crate::agents::DecisionExplanation;
+use crate::duel::Duel;
+use crate::game::{GameOutcome, GameState, SimultaneousGame};
+use crate::tournament::PairingRecord;
+
+// Hooks the engine calls while games are played. Every hook does nothing by
+// default, so observers only implement the events they care about.
+pub trait GameObserver<G: SimultaneousGame = Duel> {
+    // Called by a tournament before it plays a game between two roster entries.
+    fn on_game_start(&mut self, _player_one: usize, _player_two: usize) {}
+
+    // Called after every resolved turn, with the state including that turn.
+    fn on_turn(&mut self, _state: &GameState<G>) {}
+
+    // Called before `on_turn` for every player (1 or 2) whose agent moved in
+    // the turn and explained its decision.
+    fn on_decision(&mut self, _player: u64, _explanation: &DecisionExplanation) {}
+
+    // Called once a game is decided.
+    fn on_game_end(&mut self, _state: &GameState<G>, _outcome: &GameOutcome) {}
+
+    // Called before a tournament starts playing the games of one pairing.
+    fn on_match_start(&mut self, _player_one: usize, _player_two: usize) {}
+
+    // Called after a tournament finished all games of one pairing.
+    fn on_match_end(&mut self, _player_one: usize, _player_two: usize, _record: &PairingRecord) {}
+
+    // Called by an interleaved tournament after every pairing played one more game.
+    fn on_round_end(&mut self, _round: u64) {}
+
+    // Polled by a tournament after games; returning true ends the tournament early.
+    fn should_stop(&self) -> bool {
+        false
+    }
+}
+
+// Observer ignoring every event.
+pub struct NullObserver;
+
+impl<G: SimultaneousGame> GameObserver<G> for NullObserver {}
+
+// Several observers attached at once, notified in order. Works both for owned
+// (`Box<dyn GameObserver>`) and borrowed (`&mut dyn GameObserver`) observers.
+impl<G, O> GameObserver<G> for Vec<O>
+where
+    G: SimultaneousGame,
+    O: DerefMut<Target = dyn GameObserver<G>>,
+{
+    fn on_game_start(&mut self, player_one: usize, player_two: usize) {
+        for observer in self.iter_mut() {
+            observer.on_game_start(player_one, player_two);
+        }
+    }
+
+    fn on_turn(&mut self, state: &GameState<G>) {
+        for observer in self.iter_mut() {
+            observer.on_turn(state);
+        }
+    }
+
+    fn on_decision(&mut self, player: u64, explanation: &DecisionExplanation) {
+        for observer in self.iter_mut() {
+            observer.on_decision(player, explanation);
+        }
+    }
+
+    fn on_game_end(&mut self, state: &GameState<G>, outcome: &GameOutcome) {
+        for observer in self.iter_mut() {
+            observer.on_game_end(state, outcome);
+        }
+    }
+
+    fn on_match_start(&mut self, player_one: usize, player_two: usize) {
+        for observer in self.iter_mut() {
+            observer.on_match_start(player_one, player_two);
+        }
+    }
+
+    fn on_match_end(&mut self, player_one: usize, player_two: usize, record: &PairingRecord) {
+        for observer in self.iter_mut() {
+            observer.on_match_end(player_one, player_two, record);
+        }
+    }
+
+    fn on_round_end(&mut self, round: u64) {
+        for observer in self.iter_mut() {
+            observer.on_round_end(round);
+        }
+    }
+
+    fn should_stop(&self) -> bool {
+        self.iter().any(|observer| observer.should_stop())
+    }
+}
+
+// Player states of games scored by payoffs, like the matrix games.
+pub trait Scored {
+    fn score(&self) -> f64;
+}
+
+// Sums up the payoffs of every roster entry over the games a tournament
+// plays, for ranking strategies by their average payoff per round.
+pub struct PayoffObserver {
+    current_pairing: Option<(usize, usize)>,
+    pub total_payoffs: Vec<f64>,
+    pub rounds: Vec<u64>,
+}
+
+impl PayoffObserver {
+    pub fn new(num_agents: usize) -> Self {
+        Self {
+            current_pairing: None,
+            total_payoffs: vec![0.0; num_agents],
+            rounds: vec![0; num_agents],
+        }
+    }
+
+    pub fn average_payoff(&self, agent: usize) -> f64 {
+        self.total_payoffs[agent] / self.rounds[agent].max(1) as f64
+    }
+}
+
+impl<G: SimultaneousGame> GameObserver<G> for PayoffObserver
+where
+    G::PlayerState: Scored,
+{
+    fn on_game_start(&mut self, player_one: usize, player_two: usize) {
+        self.current_pairing = Some((player_one, player_two));
+    }
+
+    fn on_game_end(&mut self, state: &GameState<G>, _outcome: &GameOutcome) {
+        let Some((player_one, player_two)) = self.current_pairing else {
+            return;
+        };
+        let rounds = state.history.turns() as u64;
+        self.total_payoffs[player_one] += state.player_one_state.score();
+        self.rounds[player_one] += rounds;
+        self.total_payoffs[player_two] += state.player_two_state.score();
+        self.rounds[player_two] += rounds;
+    }
+}
+
+// A decision of an agent and why it was made.
+pub struct LoggedDecision<G: SimultaneousGame = Duel> {
+    pub turn: usize,
+    pub player: u64,
+    pub action: G::Action,
+    pub explanation: DecisionExplanation,
+}
+
+// Records every explained decision of the games it observes, for debugging
+// why agents play as they do.
+pub struct DecisionLog<G: SimultaneousGame = Duel> {
+    pending: Vec<(u64, DecisionExplanation)>,
+    pub decisions: Vec<LoggedDecision<G>>,
+}
+
+impl<G: SimultaneousGame> DecisionLog<G> {
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            decisions: Vec::new(),
+        }
+    }
+
+    // The logged decisions of the given turn.
+    pub fn of_turn(&self, turn: usize) -> impl Iterator<Item = &LoggedDecision<G>> {
+        self.decisions
+            .iter()
+            .filter(move |decision| decision.turn == turn)
+    }
+}
+
+impl<G: SimultaneousGame> Default for DecisionLog<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<G: SimultaneousGame> GameObserver<G> for DecisionLog<G> {
+    fn on_decision(&mut self, player: u64, explanation: &DecisionExplanation) {
+        self.pending.push((player, explanation.clone()));
+    }
+
+    fn on_turn(&mut self, state: &GameState<G>) {
+        let turn = state.history.turns() - 1;
+        for (player, explanation) in self.pending.drain(..) {
+            let actions = if player == 1 {
+                &state.history.player_one_actions
+            } else {
+                &state.history.player_two_actions
+            };
+            self.decisions.push(LoggedDecision {
+                turn,
+                player,
+                action: actions[turn].clone(),
+                explanation,
+            });
+        }
+    }
+}
@@ -0,0 +1,263 @@
+// The simultaneous games behind an interface modelled on OpenSpiel's `Game`
+// and `State`, so that algorithms written against it can be evaluated here.
+// As in OpenSpiel, players are numbered 0 and 1, actions are integer ids,
+// observations are flat tensors and the returns are +1 for a win, -1 for a
+// loss and 0 otherwise. Both players act at the decision nodes of
+// simultaneous turns, where the current player is `SIMULTANEOUS_PLAYER`; in
+// sequential turns it is the player to move, and its opponent's only legal
+// action is the pass of the rules.
+
+use std::fmt;
+
+use crate::duel::{Action, Duel};
+use crate::game::{GameOutcome, GameSettings, GameState, SimultaneousGame, TurnOrder};
+use crate::neural::{default_history, observation};
+
+// OpenSpiel's ids of the pseudo players.
+pub const SIMULTANEOUS_PLAYER: i64 = -2;
+pub const TERMINAL_PLAYER: i64 = -4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpielError {
+    Terminal,
+    WrongNumberOfActions(usize),
+    IllegalAction { player: usize, action: usize },
+    SimultaneousTurn,
+}
+
+impl fmt::Display for SpielError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpielError::Terminal => write!(f, "the state is terminal"),
+            SpielError::WrongNumberOfActions(count) => {
+                write!(f, "expected one action per player, got {}", count)
+            }
+            SpielError::IllegalAction { player, action } => {
+                write!(f, "action {} is illegal for player {}", action, player)
+            }
+            SpielError::SimultaneousTurn => {
+                write!(f, "both players act in this turn, apply both actions")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpielError {}
+
+// What a game has to tell about itself to be played through `SpielState`.
+// Players are given in the engine's numbering, 1 or 2.
+pub trait SpielGame: SimultaneousGame + Clone {
+    fn num_distinct_actions(&self) -> usize;
+
+    fn action_id(&self, action: &Self::Action) -> usize;
+
+    fn action_from_id(&self, id: usize) -> Option<Self::Action>;
+
+    fn action_to_string(&self, id: usize) -> String;
+
+    fn observation_tensor_size(&self) -> usize;
+
+    // What `player` observes, honouring the observability of `settings`.
+    fn observation_tensor(
+        &self,
+        state: &GameState<Self>,
+        settings: &GameSettings,
+        player: u64,
+    ) -> Vec<f32>;
+
+    fn legal_actions(&self, _state: &GameState<Self>, _player: u64) -> Vec<usize> {
+        (0..self.num_distinct_actions()).collect()
+    }
+}
+
+// Attack is action 0, finch action 1. The observation is the one neural
+// policies take, with the default number of past turns.
+impl SpielGame for Duel {
+    fn num_distinct_actions(&self) -> usize {
+        Action::ALL.len()
+    }
+
+    fn action_id(&self, action: &Action) -> usize {
+        action.index()
+    }
+
+    fn action_from_id(&self, id: usize) -> Option<Action> {
+        Action::ALL.get(id).cloned()
+    }
+
+    fn action_to_string(&self, id: usize) -> String {
+        match self.action_from_id(id) {
+            Some(action) => format!("{:?}", action),
+            None => format!("invalid action {}", id),
+        }
+    }
+
+    fn observation_tensor_size(&self) -> usize {
+        2 + 2 * default_history()
+    }
+
+    fn observation_tensor(
+        &self,
+        state: &GameState<Self>,
+        settings: &GameSettings,
+        player: u64,
+    ) -> Vec<f32> {
+        let own_state = if player == 1 {
+            &state.player_one_state
+        } else {
+            &state.player_two_state
+        };
+        observation(
+            own_state,
+            &settings.observe_opponent(state, player),
+            &state.history.view(player),
+            &self.damage,
+            default_history(),
+        )
+    }
+}
+
+// A game in progress, cloneable so that search algorithms can branch off it.
+#[derive(Clone)]
+pub struct SpielState<G: SpielGame = Duel> {
+    pub rules: G,
+    pub settings: GameSettings,
+    pub state: GameState<G>,
+    outcome: GameOutcome,
+}
+
+impl<G: SpielGame> SpielState<G> {
+    // The initial state of a game played by `rules` under `settings`.
+    pub fn new(rules: G, settings: GameSettings) -> Self {
+        Self {
+            state: rules.initial_state(),
+            rules,
+            settings,
+            outcome: GameOutcome::CONTINUE,
+        }
+    }
+
+    pub fn num_players(&self) -> usize {
+        2
+    }
+
+    pub fn current_player(&self) -> i64 {
+        if self.is_terminal() {
+            return TERMINAL_PLAYER;
+        }
+        match self.rules.turn_order(&self.state) {
+            TurnOrder::Simultaneous => SIMULTANEOUS_PLAYER,
+            TurnOrder::Sequential { player, .. } => player as i64 - 1,
+        }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.outcome != GameOutcome::CONTINUE
+    }
+
+    pub fn outcome(&self) -> &GameOutcome {
+        &self.outcome
+    }
+
+    pub fn legal_actions(&self, player: usize) -> Vec<usize> {
+        if self.is_terminal() {
+            return Vec::new();
+        }
+        match self.rules.turn_order(&self.state) {
+            TurnOrder::Sequential {
+                player: mover,
+                pass,
+            } if mover != player as u64 + 1 => {
+                vec![self.rules.action_id(&pass)]
+            }
+            _ => self.rules.legal_actions(&self.state, player as u64 + 1),
+        }
+    }
+
+    // Applies the action of the player to move in a sequential turn.
+    pub fn apply_action(&mut self, action: usize) -> Result<(), SpielError> {
+        if self.is_terminal() {
+            return Err(SpielError::Terminal);
+        }
+        let TurnOrder::Sequential { player, pass } = self.rules.turn_order(&self.state) else {
+            return Err(SpielError::SimultaneousTurn);
+        };
+        let pass = self.rules.action_id(&pass);
+        if player == 1 {
+            self.apply_actions(&[action, pass])
+        } else {
+            self.apply_actions(&[pass, action])
+        }
+    }
+
+    // Resolves one turn from the actions of both players, in player order.
+    pub fn apply_actions(&mut self, actions: &[usize]) -> Result<(), SpielError> {
+        if self.is_terminal() {
+            return Err(SpielError::Terminal);
+        }
+        if actions.len() != 2 {
+            return Err(SpielError::WrongNumberOfActions(actions.len()));
+        }
+        let mut decoded = Vec::with_capacity(2);
+        for (player, &action) in actions.iter().enumerate() {
+            let legal = self.legal_actions(player).contains(&action);
+            match self.rules.action_from_id(action) {
+                Some(decoded_action) if legal => decoded.push(decoded_action),
+                _ => return Err(SpielError::IllegalAction { player, action }),
+            }
+        }
+        let player_two_action = decoded.pop().expect("two actions");
+        let player_one_action = decoded.pop().expect("two actions");
+        self.state.advance(
+            &self.rules,
+            &self.settings,
+            player_one_action,
+            player_two_action,
+        );
+        self.outcome = self.settings.check_end_condition(&self.rules, &self.state);
+        Ok(())
+    }
+
+    // +1 for the winner, -1 for the loser, 0 for both on a tie or draw or
+    // while the game is running.
+    pub fn returns(&self) -> Vec<f64> {
+        match self.outcome {
+            GameOutcome::WIN(1) => vec![1.0, -1.0],
+            GameOutcome::WIN(_) => vec![-1.0, 1.0],
+            _ => vec![0.0, 0.0],
+        }
+    }
+
+    pub fn observation_tensor(&self, player: usize) -> Vec<f32> {
+        self.rules
+            .observation_tensor(&self.state, &self.settings, player as u64 + 1)
+    }
+
+    // The joint actions played so far, one pair per turn.
+    pub fn history(&self) -> Vec<[usize; 2]> {
+        let history = &self.state.history;
+        history
+            .player_one_actions
+            .iter()
+            .zip(&history.player_two_actions)
+            .map(|(one, two)| [self.rules.action_id(one), self.rules.action_id(two)])
+            .collect()
+    }
+}
+
+impl<G: SpielGame> fmt::Display for SpielState<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let turns: Vec<String> = self
+            .history()
+            .iter()
+            .map(|[one, two]| {
+                format!(
+                    "{}/{}",
+                    self.rules.action_to_string(*one),
+                    self.rules.action_to_string(*two)
+                )
+            })
+            .collect();
+        write!(f, "[{}]", turns.join(", "))
+    }
+}
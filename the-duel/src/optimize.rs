@@ -0,0 +1,350 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::agents::{GameAgent, SharedRng};
+use crate::cma_es::CmaEs;
+use crate::duel::Duel;
+use crate::evolution::{Family, Genome, average_score};
+use crate::game::GameSettings;
+use crate::registry::{AgentRegistry, RegistryError};
+use crate::stats::normal_cdf;
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptimizationMethod {
+    // Gaussian process regression with expected improvement, for few expensive evaluations.
+    #[default]
+    Bayesian,
+    // Covariance matrix adaptation, for larger budgets and more parameters.
+    CmaEs,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct OptimizationConfig {
+    pub family: Family,
+    #[serde(default)]
+    pub method: OptimizationMethod,
+    // Parameter vectors evaluated against the opponents, initial samples included.
+    pub budget: usize,
+    // Uniformly drawn parameter vectors evaluated before the model takes over.
+    #[serde(default = "default_initial_samples")]
+    pub initial_samples: usize,
+    // Uniformly drawn candidates the expected improvement is maximized over.
+    #[serde(default = "default_candidates")]
+    pub candidates: usize,
+    // Games against every opponent, per seat, to evaluate a parameter vector.
+    #[serde(default = "default_games")]
+    pub games: u64,
+    // Correlation length of the kernel, relative to every parameter's range.
+    #[serde(default = "default_length_scale")]
+    pub length_scale: f64,
+    // Variance of the evaluation noise, relative to the variance of the scores.
+    #[serde(default = "default_noise")]
+    pub noise: f64,
+    // Initial CMA-ES step size, relative to every parameter's range.
+    #[serde(default = "default_sigma")]
+    pub sigma: f64,
+    // Candidates of every CMA-ES generation; 4 + 3 ln(parameters) if omitted.
+    pub population_size: Option<usize>,
+}
+
+fn default_initial_samples() -> usize {
+    5
+}
+
+fn default_candidates() -> usize {
+    1000
+}
+
+fn default_games() -> u64 {
+    5
+}
+
+fn default_length_scale() -> f64 {
+    0.2
+}
+
+fn default_noise() -> f64 {
+    0.01
+}
+
+fn default_sigma() -> f64 {
+    0.3
+}
+
+// A parameter vector and its average score against the opponents.
+pub struct Evaluation {
+    pub genome: Genome,
+    pub score: f64,
+}
+
+// Gaussian process regression with a squared exponential kernel over
+// parameter vectors scaled to the unit cube, on standardized scores.
+struct GaussianProcess {
+    points: Vec<Vec<f64>>,
+    mean: f64,
+    deviation: f64,
+    length_scale: f64,
+    // Lower Cholesky factor of the kernel matrix plus noise.
+    cholesky: Vec<Vec<f64>>,
+    // The kernel matrix plus noise solved for the standardized scores.
+    weights: Vec<f64>,
+}
+
+impl GaussianProcess {
+    fn fit(points: Vec<Vec<f64>>, scores: &[f64], length_scale: f64, noise: f64) -> Self {
+        let n = scores.len();
+        let mean = scores.iter().sum::<f64>() / n as f64;
+        let variance = scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n as f64;
+        let deviation = if variance > 0.0 { variance.sqrt() } else { 1.0 };
+        let targets: Vec<f64> = scores.iter().map(|s| (s - mean) / deviation).collect();
+
+        let mut cholesky = vec![vec![0.0; n]; n];
+        for i in 0..n {
+            for j in 0..=i {
+                let mut sum = kernel(&points[i], &points[j], length_scale);
+                if i == j {
+                    sum += noise;
+                }
+                sum -= (0..j).map(|k| cholesky[i][k] * cholesky[j][k]).sum::<f64>();
+                cholesky[i][j] = if i == j {
+                    sum.max(1e-12).sqrt()
+                } else {
+                    sum / cholesky[j][j]
+                };
+            }
+        }
+        let weights = solve_upper(&cholesky, &solve_lower(&cholesky, &targets));
+        Self {
+            points,
+            mean,
+            deviation,
+            length_scale,
+            cholesky,
+            weights,
+        }
+    }
+
+    // Posterior mean and standard deviation of the score at `point`.
+    fn predict(&self, point: &[f64]) -> (f64, f64) {
+        let covariances: Vec<f64> = self
+            .points
+            .iter()
+            .map(|p| kernel(p, point, self.length_scale))
+            .collect();
+        let mean = covariances
+            .iter()
+            .zip(&self.weights)
+            .map(|(k, w)| k * w)
+            .sum::<f64>();
+        let explained = solve_lower(&self.cholesky, &covariances);
+        let variance = 1.0 - explained.iter().map(|v| v * v).sum::<f64>();
+        (
+            self.mean + self.deviation * mean,
+            self.deviation * variance.max(0.0).sqrt(),
+        )
+    }
+}
+
+fn kernel(a: &[f64], b: &[f64], length_scale: f64) -> f64 {
+    let distance: f64 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum();
+    (-distance / (2.0 * length_scale * length_scale)).exp()
+}
+
+fn solve_lower(lower: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let mut x = vec![0.0; b.len()];
+    for i in 0..b.len() {
+        let sum: f64 = (0..i).map(|k| lower[i][k] * x[k]).sum();
+        x[i] = (b[i] - sum) / lower[i][i];
+    }
+    x
+}
+
+// Solves the transpose of `lower`.
+fn solve_upper(lower: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let mut x = vec![0.0; b.len()];
+    for i in (0..b.len()).rev() {
+        let sum: f64 = (i + 1..b.len()).map(|k| lower[k][i] * x[k]).sum();
+        x[i] = (b[i] - sum) / lower[i][i];
+    }
+    x
+}
+
+// Expected amount by which a score distributed as N(mean, deviation²)
+// exceeds `best`.
+fn expected_improvement(mean: f64, deviation: f64, best: f64) -> f64 {
+    if deviation <= 0.0 {
+        return (mean - best).max(0.0);
+    }
+    let z = (mean - best) / deviation;
+    let density = (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt();
+    (mean - best) * normal_cdf(z) + deviation * density
+}
+
+// Tunes the parameters of an agent family to maximize its average score
+// against a fixed set of opponents within a budget of evaluations. The
+// average is weighted by `weights` if given, and opponents of negligible
+// weight are not played at all.
+//
+// Bayesian optimization evaluates a few uniformly drawn samples, then every
+// candidate of highest expected improvement under a Gaussian process fitted
+// to all evaluations so far. CMA-ES starts from the middle of the parameter
+// ranges and evaluates whole generations of candidates.
+pub struct Optimization<'a> {
+    pub rules: Duel,
+    pub settings: GameSettings,
+    pub registry: &'a AgentRegistry,
+    pub opponents: Vec<Box<dyn GameAgent>>,
+    pub weights: Option<Vec<f64>>,
+    pub config: OptimizationConfig,
+}
+
+// Opponents weighing less than this are left out of the average.
+const NEGLIGIBLE_WEIGHT: f64 = 1e-6;
+
+impl<'a> Optimization<'a> {
+    pub fn new(
+        rules: Duel,
+        registry: &'a AgentRegistry,
+        opponents: Vec<Box<dyn GameAgent>>,
+        config: OptimizationConfig,
+    ) -> Self {
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            registry,
+            opponents,
+            weights: None,
+            config,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    // Weighs the score against every opponent, e.g. by its share of an
+    // equilibrium of the opponents' meta-game.
+    pub fn with_weights(mut self, weights: Vec<f64>) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    fn weight(&self, opponent: usize) -> f64 {
+        self.weights
+            .as_ref()
+            .map_or(1.0, |weights| weights.get(opponent).copied().unwrap_or(0.0))
+    }
+
+    pub fn score(&self, genome: &Genome, rng: &SharedRng) -> Result<f64, RegistryError> {
+        let agent = self
+            .registry
+            .build_with_damage(&genome.spec(), rng, &self.rules.damage)?;
+        let mut total = 0.0;
+        let mut total_weight = 0.0;
+        for (index, opponent) in self.opponents.iter().enumerate() {
+            let weight = self.weight(index);
+            if weight < NEGLIGIBLE_WEIGHT {
+                continue;
+            }
+            total += weight
+                * average_score(
+                    &self.rules,
+                    &self.settings,
+                    agent.as_ref(),
+                    opponent.as_ref(),
+                    self.config.games,
+                );
+            total_weight += weight;
+        }
+        Ok(if total_weight > 0.0 {
+            total / total_weight
+        } else {
+            0.0
+        })
+    }
+
+    fn genome(&self, point: &[f64]) -> Genome {
+        let family = self.config.family;
+        let params = point
+            .iter()
+            .zip(family.bounds())
+            .map(|(u, &(low, high))| low + u * (high - low))
+            .collect();
+        Genome { family, params }
+    }
+
+    // The next point of the unit cube to evaluate.
+    fn propose(&self, points: &[Vec<f64>], scores: &[f64], rng: &SharedRng) -> Vec<f64> {
+        let dimensions = self.config.family.bounds().len();
+        let mut rng = rng.borrow_mut();
+        let mut uniform = || -> Vec<f64> { (0..dimensions).map(|_| rng.random()).collect() };
+        if points.len() < self.config.initial_samples.max(1) {
+            return uniform();
+        }
+        let process = GaussianProcess::fit(
+            points.to_vec(),
+            scores,
+            self.config.length_scale,
+            self.config.noise,
+        );
+        let best = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        (0..self.config.candidates.max(1))
+            .map(|_| {
+                let candidate = uniform();
+                let (mean, deviation) = process.predict(&candidate);
+                (expected_improvement(mean, deviation, best), candidate)
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap()
+            .1
+    }
+
+    // Every evaluation in the order it was made.
+    pub fn run(&self, rng: &SharedRng) -> Result<Vec<Evaluation>, RegistryError> {
+        match self.config.method {
+            OptimizationMethod::Bayesian => self.run_bayesian(rng),
+            OptimizationMethod::CmaEs => self.run_cma_es(rng),
+        }
+    }
+
+    fn run_bayesian(&self, rng: &SharedRng) -> Result<Vec<Evaluation>, RegistryError> {
+        let mut points = Vec::new();
+        let mut scores = Vec::new();
+        let mut evaluations = Vec::new();
+        for _ in 0..self.config.budget {
+            let point = self.propose(&points, &scores, rng);
+            let genome = self.genome(&point);
+            let score = self.score(&genome, rng)?;
+            points.push(point);
+            scores.push(score);
+            evaluations.push(Evaluation { genome, score });
+        }
+        Ok(evaluations)
+    }
+
+    // The last generation is cut short if the budget runs out.
+    fn run_cma_es(&self, rng: &SharedRng) -> Result<Vec<Evaluation>, RegistryError> {
+        let dimensions = self.config.family.bounds().len();
+        let mut strategy = CmaEs::new(
+            vec![0.5; dimensions],
+            self.config.sigma,
+            self.config.population_size,
+        );
+        let mut evaluations = Vec::new();
+        while evaluations.len() < self.config.budget {
+            let mut candidates = strategy.ask(&mut *rng.borrow_mut());
+            candidates.truncate(self.config.budget - evaluations.len());
+            let mut scores = Vec::new();
+            for candidate in &candidates {
+                let genome = self.genome(candidate);
+                let score = self.score(&genome, rng)?;
+                scores.push(score);
+                evaluations.push(Evaluation { genome, score });
+            }
+            strategy.tell(&candidates, &scores);
+        }
+        Ok(evaluations)
+    }
+}
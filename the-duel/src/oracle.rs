@@ -0,0 +1,168 @@
+// Equilibrium discovery by a double oracle, in its policy space response
+// oracle (PSRO) form: starting from the roster as the pool of agents, every
+// iteration plays the meta-game between the agents of the pool, solves it
+// for its maximum entropy Nash equilibrium, see `nash_averaging`, and tunes
+// an agent of a family to score best against the pool weighted by the
+// equilibrium, see `Optimization`. That best response joins the pool, until
+// it no longer beats the equilibrium by more than the tolerance: no agent of
+// the family then does much better against the pool's equilibrium than an
+// even split, which makes it an equilibrium of the family and the roster.
+
+use serde::Deserialize;
+
+use crate::agents::{GameAgent, SharedRng};
+use crate::duel::Duel;
+use crate::evolution::average_score;
+use crate::game::GameSettings;
+use crate::nash_averaging::{NashAveragingConfig, nash_average};
+use crate::optimize::{Evaluation, Optimization, OptimizationConfig};
+use crate::registry::{AgentRegistry, RegistryError};
+
+#[derive(Deserialize, Clone)]
+pub struct OracleConfig {
+    // Best responses tuned at most, each adding an agent to the pool.
+    pub iterations: usize,
+    // Games between every two agents of the pool, per seat, for the meta-game.
+    #[serde(default = "default_games")]
+    pub games: u64,
+    // Stop once the best response scores at most this much above one half
+    // against the equilibrium.
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+    // How the best responses are tuned, e.g.
+    //   best_response = { family = "markov", budget = 20 }
+    pub best_response: OptimizationConfig,
+}
+
+fn default_games() -> u64 {
+    5
+}
+
+fn default_tolerance() -> f64 {
+    0.01
+}
+
+// The equilibrium of the pool at the start of an iteration and the best
+// response tuned against it.
+pub struct OracleIteration {
+    pub shares: Vec<f64>,
+    pub best_response: Evaluation,
+}
+
+pub struct OracleResults {
+    // Names of the roster followed by the best responses that joined it.
+    pub agent_names: Vec<String>,
+    pub iterations: Vec<OracleIteration>,
+    // The equilibrium of the final pool.
+    pub shares: Vec<f64>,
+    // Whether the last best response no longer beat the equilibrium.
+    pub converged: bool,
+}
+
+pub struct DoubleOracle<'a> {
+    pub rules: Duel,
+    pub settings: GameSettings,
+    pub registry: &'a AgentRegistry,
+    pub pool: Vec<Box<dyn GameAgent>>,
+    pub config: OracleConfig,
+}
+
+impl<'a> DoubleOracle<'a> {
+    pub fn new(
+        rules: Duel,
+        registry: &'a AgentRegistry,
+        pool: Vec<Box<dyn GameAgent>>,
+        config: OracleConfig,
+    ) -> Self {
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            registry,
+            pool,
+            config,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    fn score(&self, agent: &dyn GameAgent, opponent: &dyn GameAgent) -> f64 {
+        average_score(
+            &self.rules,
+            &self.settings,
+            agent,
+            opponent,
+            self.config.games,
+        )
+    }
+
+    // Adds `agent` to the pool and its scores against the others to the
+    // meta-game `payoffs`, one half against itself.
+    fn extend(&mut self, payoffs: &mut Vec<Vec<f64>>, agent: Box<dyn GameAgent>) {
+        let scores: Vec<f64> = self
+            .pool
+            .iter()
+            .map(|opponent| self.score(agent.as_ref(), opponent.as_ref()))
+            .collect();
+        for (row, score) in payoffs.iter_mut().zip(&scores) {
+            row.push(1.0 - score);
+        }
+        payoffs.push(scores.into_iter().chain([0.5]).collect());
+        self.pool.push(agent);
+    }
+
+    pub fn run(mut self, rng: &SharedRng) -> Result<OracleResults, RegistryError> {
+        let equilibrium = NashAveragingConfig::default();
+        // The meta-game of the roster, built up one agent at a time.
+        let mut payoffs = Vec::new();
+        for agent in std::mem::take(&mut self.pool) {
+            self.extend(&mut payoffs, agent);
+        }
+        let mut iterations = Vec::new();
+        let mut converged = false;
+        for _ in 0..self.config.iterations {
+            let shares = nash_average(&payoffs, &equilibrium).shares;
+            let optimization = Optimization::new(
+                self.rules.clone(),
+                self.registry,
+                self.pool.iter().map(|a| a.copy_self_to_anom()).collect(),
+                self.config.best_response.clone(),
+            )
+            .with_settings(self.settings.clone())
+            .with_weights(shares.clone());
+            let Some(best_response) = optimization
+                .run(rng)?
+                .into_iter()
+                .max_by(|a, b| a.score.total_cmp(&b.score))
+            else {
+                break;
+            };
+            converged = best_response.score <= 0.5 + self.config.tolerance;
+            let agent = (!converged)
+                .then(|| {
+                    self.registry.build_with_damage(
+                        &best_response.genome.spec(),
+                        rng,
+                        &self.rules.damage,
+                    )
+                })
+                .transpose()?;
+            iterations.push(OracleIteration {
+                shares,
+                best_response,
+            });
+            match agent {
+                Some(agent) => self.extend(&mut payoffs, agent),
+                None => break,
+            }
+        }
+        Ok(OracleResults {
+            agent_names: self.pool.iter().map(|a| a.strategy_name()).collect(),
+            iterations,
+            shares: nash_average(&payoffs, &equilibrium).shares,
+            converged,
+        })
+    }
+}
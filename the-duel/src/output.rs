@@ -0,0 +1,322 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::tournament::TournamentResults;
+
+#[derive(Debug)]
+pub enum OutputError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Parquet(String),
+    Unsupported(OutputFormat),
+}
+
+impl fmt::Display for OutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputError::Io(err) => write!(f, "could not write results: {}", err),
+            OutputError::Json(err) => write!(f, "could not encode results as JSON: {}", err),
+            OutputError::Parquet(err) => write!(f, "could not write parquet results: {}", err),
+            OutputError::Unsupported(format) => write!(
+                f,
+                "this build does not support {} output (enable the '{}' feature)",
+                format.name(),
+                format.name()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OutputError {}
+
+impl From<io::Error> for OutputError {
+    fn from(err: io::Error) -> Self {
+        OutputError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    Csv,
+    Json,
+    Parquet,
+}
+
+impl OutputFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Json => "json",
+            OutputFormat::Parquet => "parquet",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<OutputFormat> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "csv" => Some(OutputFormat::Csv),
+            "json" => Some(OutputFormat::Json),
+            "parquet" => Some(OutputFormat::Parquet),
+            _ => None,
+        }
+    }
+
+    pub fn writer(&self) -> Box<dyn ResultWriter> {
+        match self {
+            OutputFormat::Csv => Box::new(CsvWriter),
+            OutputFormat::Json => Box::new(JsonWriter),
+            OutputFormat::Parquet => Box::new(ParquetWriter),
+        }
+    }
+
+    // `path` with its extension replaced by the one of this format.
+    pub fn path_for(&self, path: &Path) -> PathBuf {
+        path.with_extension(self.name())
+    }
+}
+
+// A backend persisting tournament results in one file format.
+pub trait ResultWriter {
+    fn write(
+        &self,
+        path: &Path,
+        results: &TournamentResults,
+        metadata: &ResultMetadata,
+    ) -> Result<(), OutputError>;
+}
+
+// Writes the results in every given format, deriving each file name from `path`.
+pub fn write_results(
+    path: &Path,
+    formats: &[OutputFormat],
+    results: &TournamentResults,
+    metadata: &ResultMetadata,
+) -> Result<Vec<PathBuf>, OutputError> {
+    let mut written = Vec::new();
+    for format in formats {
+        let format_path = format.path_for(path);
+        format.writer().write(&format_path, results, metadata)?;
+        written.push(format_path);
+    }
+    Ok(written)
+}
+
+// Parameters of a run, written next to its results so they stay interpretable.
+#[derive(Clone, Serialize)]
+pub struct ResultMetadata {
+    pub seed: u64,
+    pub max_hit_points: i64,
+    pub num_retrials: u64,
+    pub best_of: u64,
+    pub variant: String,
+}
+
+impl ResultMetadata {
+    pub fn entries(&self, results: &TournamentResults) -> Vec<(&'static str, String)> {
+        vec![
+            ("seed", self.seed.to_string()),
+            ("max_hit_points", self.max_hit_points.to_string()),
+            ("num_retrials", self.num_retrials.to_string()),
+            ("retrials_played", results.num_retrials.to_string()),
+            ("best_of", self.best_of.to_string()),
+            ("variant", self.variant.clone()),
+            ("num_agents", results.num_agents().to_string()),
+        ]
+    }
+}
+
+// Quotes a CSV field if it contains a separator, quote or line break.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Writes the win matrix as CSV. Lines starting with `#` hold the metadata;
+// the cell in row i and column j counts the games agent j won against agent i.
+pub fn write_win_matrix_csv<W: Write>(
+    output: &mut W,
+    results: &TournamentResults,
+    metadata: &ResultMetadata,
+) -> io::Result<()> {
+    for (key, value) in metadata.entries(results) {
+        writeln!(output, "# {}: {}", key, value)?;
+    }
+    writeln!(
+        output,
+        "# cell (row, column): wins of column agent against row agent"
+    )?;
+
+    let win_matrix = results.win_matrix();
+    write!(output, "agent")?;
+    for name in &results.agent_names {
+        write!(output, ",{}", csv_field(name))?;
+    }
+    writeln!(output)?;
+    for (i, name) in results.agent_names.iter().enumerate() {
+        write!(output, "{}", csv_field(name))?;
+        for row in &win_matrix {
+            write!(output, ",{}", row[i])?;
+        }
+        writeln!(output)?;
+    }
+    Ok(())
+}
+
+pub struct CsvWriter;
+
+impl ResultWriter for CsvWriter {
+    fn write(
+        &self,
+        path: &Path,
+        results: &TournamentResults,
+        metadata: &ResultMetadata,
+    ) -> Result<(), OutputError> {
+        let mut output = BufWriter::new(File::create(path)?);
+        write_win_matrix_csv(&mut output, results, metadata)?;
+        output.flush()?;
+        Ok(())
+    }
+}
+
+// Writes a single JSON document holding the metadata, the agents, the record
+// of every pairing and the win matrix.
+pub struct JsonWriter;
+
+#[derive(Serialize)]
+struct JsonResults<'a> {
+    metadata: &'a ResultMetadata,
+    #[serde(flatten)]
+    results: &'a TournamentResults,
+    win_matrix: Vec<Vec<u64>>,
+}
+
+impl ResultWriter for JsonWriter {
+    fn write(
+        &self,
+        path: &Path,
+        results: &TournamentResults,
+        metadata: &ResultMetadata,
+    ) -> Result<(), OutputError> {
+        let document = JsonResults {
+            metadata,
+            results,
+            win_matrix: results.win_matrix(),
+        };
+        let mut output = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut output, &document).map_err(OutputError::Json)?;
+        writeln!(output)?;
+        output.flush()?;
+        Ok(())
+    }
+}
+
+// Writes one row per pairing (player one, player two, wins, ties, losses),
+// the metadata going into the file's key-value metadata.
+pub struct ParquetWriter;
+
+#[cfg(not(feature = "parquet"))]
+impl ResultWriter for ParquetWriter {
+    fn write(
+        &self,
+        _path: &Path,
+        _results: &TournamentResults,
+        _metadata: &ResultMetadata,
+    ) -> Result<(), OutputError> {
+        Err(OutputError::Unsupported(OutputFormat::Parquet))
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl ResultWriter for ParquetWriter {
+    fn write(
+        &self,
+        path: &Path,
+        results: &TournamentResults,
+        metadata: &ResultMetadata,
+    ) -> Result<(), OutputError> {
+        use std::sync::Arc;
+
+        use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+        use parquet::file::metadata::KeyValue;
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use parquet::schema::parser::parse_message_type;
+
+        let parquet_error =
+            |err: parquet::errors::ParquetError| OutputError::Parquet(err.to_string());
+
+        let schema = parse_message_type(
+            "message pairing {
+                REQUIRED INT64 player_one_index;
+                REQUIRED BYTE_ARRAY player_one (UTF8);
+                REQUIRED INT64 player_two_index;
+                REQUIRED BYTE_ARRAY player_two (UTF8);
+                REQUIRED INT64 wins;
+                REQUIRED INT64 ties;
+                REQUIRED INT64 losses;
+            }",
+        )
+        .map_err(parquet_error)?;
+        let key_values = metadata
+            .entries(results)
+            .into_iter()
+            .map(|(key, value)| KeyValue::new(key.to_string(), value))
+            .collect();
+        let properties = WriterProperties::builder()
+            .set_key_value_metadata(Some(key_values))
+            .build();
+
+        let name = |index: usize| ByteArray::from(results.agent_names[index].as_str());
+        let pairings = &results.pairings;
+        let int_columns: [Vec<i64>; 5] = [
+            pairings.iter().map(|p| p.player_one as i64).collect(),
+            pairings.iter().map(|p| p.player_two as i64).collect(),
+            pairings.iter().map(|p| p.record.wins as i64).collect(),
+            pairings.iter().map(|p| p.record.ties as i64).collect(),
+            pairings.iter().map(|p| p.record.losses as i64).collect(),
+        ];
+        let name_columns: [Vec<ByteArray>; 2] = [
+            pairings.iter().map(|p| name(p.player_one)).collect(),
+            pairings.iter().map(|p| name(p.player_two)).collect(),
+        ];
+
+        let file = File::create(path)?;
+        let mut writer = SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))
+            .map_err(parquet_error)?;
+        let mut row_group = writer.next_row_group().map_err(parquet_error)?;
+        let mut column_index = 0;
+        while let Some(mut column) = row_group.next_column().map_err(parquet_error)? {
+            match column_index {
+                1 | 3 => {
+                    column
+                        .typed::<ByteArrayType>()
+                        .write_batch(&name_columns[column_index / 2], None, None)
+                        .map_err(parquet_error)?;
+                }
+                _ => {
+                    let values = match column_index {
+                        0 => &int_columns[0],
+                        2 => &int_columns[1],
+                        index => &int_columns[index - 2],
+                    };
+                    column
+                        .typed::<Int64Type>()
+                        .write_batch(values, None, None)
+                        .map_err(parquet_error)?;
+                }
+            }
+            column.close().map_err(parquet_error)?;
+            column_index += 1;
+        }
+        row_group.close().map_err(parquet_error)?;
+        writer.close().map_err(parquet_error)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct PageRankConfig {
+    // Chance of following a defeat rather than jumping to a uniformly
+    // random agent.
+    #[serde(default = "default_damping")]
+    pub damping: f64,
+    // Ranks are iterated until none moves by more.
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: usize,
+}
+
+fn default_damping() -> f64 {
+    0.85
+}
+
+fn default_tolerance() -> f64 {
+    1e-12
+}
+
+fn default_max_iterations() -> usize {
+    10_000
+}
+
+// PageRank on the directed graph of who beats whom: every agent points to
+// the agents beating it, weighted by their margin over an even split,
+// `payoffs[j][i] - 1/2` for agent j beating agent i. A random walker follows
+// a defeat with probability `damping` and otherwise jumps to a uniformly
+// random agent, and an agent's rank is the share of time spent at it. Beating
+// a strong agent counts more than beating a weak one, since the strong agent
+// passes on more rank; undefeated agents pass theirs on to every agent alike.
+pub struct PageRank {
+    // `edges[i][j]` is the margin by which agent j beats agent i, 0 if it
+    // does not.
+    pub edges: Vec<Vec<f64>>,
+}
+
+pub struct PageRanking {
+    pub ranks: Vec<f64>,
+    pub iterations: usize,
+}
+
+impl PageRanking {
+    // Agents by decreasing rank.
+    pub fn order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.ranks.len()).collect();
+        order.sort_by(|a, b| self.ranks[*b].total_cmp(&self.ranks[*a]));
+        order
+    }
+}
+
+impl PageRank {
+    // `payoffs[i][j]` is the expected score of agent i against agent j with
+    // 1/2 for an even split, see `payoff_matrix`.
+    pub fn new(payoffs: &[Vec<f64>]) -> Self {
+        let size = payoffs.len();
+        let edges = (0..size)
+            .map(|loser| {
+                (0..size)
+                    .map(|winner| (payoffs[winner][loser] - 0.5).max(0.0))
+                    .collect()
+            })
+            .collect();
+        Self { edges }
+    }
+
+    pub fn num_agents(&self) -> usize {
+        self.edges.len()
+    }
+
+    // Power iteration from equal ranks.
+    pub fn rank(&self, config: &PageRankConfig) -> PageRanking {
+        let size = self.num_agents();
+        let uniform = 1.0 / size.max(1) as f64;
+        let out_weights: Vec<f64> = self.edges.iter().map(|row| row.iter().sum()).collect();
+        let mut ranks = vec![uniform; size];
+        let mut iterations = 0;
+        while iterations < config.max_iterations {
+            iterations += 1;
+            // Rank of undefeated agents, spread over everyone.
+            let dangling: f64 = (0..size)
+                .filter(|&agent| out_weights[agent] <= 0.0)
+                .map(|agent| ranks[agent])
+                .sum();
+            let next: Vec<f64> = (0..size)
+                .map(|winner| {
+                    let followed: f64 = (0..size)
+                        .filter(|&loser| out_weights[loser] > 0.0)
+                        .map(|loser| ranks[loser] * self.edges[loser][winner] / out_weights[loser])
+                        .sum();
+                    (1.0 - config.damping) * uniform
+                        + config.damping * (followed + dangling * uniform)
+                })
+                .collect();
+            let change = next
+                .iter()
+                .zip(&ranks)
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f64::max);
+            ranks = next;
+            if change < config.tolerance {
+                break;
+            }
+        }
+        PageRanking { ranks, iterations }
+    }
+}
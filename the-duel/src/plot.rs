@@ -0,0 +1,230 @@
+use std::fmt::Write;
+
+use crate::replay::Replay;
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 450.0;
+const MARGIN_LEFT: f64 = 60.0;
+const MARGIN_RIGHT: f64 = 20.0;
+const MARGIN_TOP: f64 = 40.0;
+const MARGIN_BOTTOM: f64 = 90.0;
+const NUM_TICKS: usize = 5;
+const PLAYER_COLORS: [&str; 2] = ["#440154", "#21918c"];
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Renders both players' hit points over the turns of a duel as an SVG document.
+pub fn hit_point_svg(replay: &Replay) -> String {
+    let header = &replay.header;
+    let max_turn = replay.turns.len().max(1) as f64;
+    let max_hit_points = header.max_hit_points.max(1) as f64;
+
+    let plot_width = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_height = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+    let x = |turn: f64| MARGIN_LEFT + turn / max_turn * plot_width;
+    let y =
+        |hit_points: f64| MARGIN_TOP + (1.0 - hit_points.max(0.0) / max_hit_points) * plot_height;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}" font-family="sans-serif" font-size="12">"#
+    )
+    .unwrap();
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+    writeln!(
+        svg,
+        r#"<text x="{}" y="24" text-anchor="middle" font-size="16">Hit points over time (seed {})</text>"#,
+        WIDTH / 2.0,
+        header.seed
+    )
+    .unwrap();
+
+    // Grid and axis labels
+    for tick in 0..=NUM_TICKS {
+        let fraction = tick as f64 / NUM_TICKS as f64;
+        let hit_points = fraction * max_hit_points;
+        let turn = fraction * max_turn;
+        writeln!(
+            svg,
+            r##"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="#dddddd"/>"##,
+            MARGIN_LEFT,
+            y(hit_points),
+            MARGIN_LEFT + plot_width,
+            y(hit_points)
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="end">{:.0}</text>"#,
+            MARGIN_LEFT - 6.0,
+            y(hit_points) + 4.0,
+            hit_points
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{:.0}</text>"#,
+            x(turn),
+            MARGIN_TOP + plot_height + 16.0,
+            turn
+        )
+        .unwrap();
+    }
+    writeln!(
+        svg,
+        r#"<rect x="{MARGIN_LEFT}" y="{MARGIN_TOP}" width="{plot_width}" height="{plot_height}" fill="none" stroke="black"/>"#
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">Turn</text>"#,
+        MARGIN_LEFT + plot_width / 2.0,
+        MARGIN_TOP + plot_height + 34.0
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"<text x="16" y="{:.1}" text-anchor="middle" transform="rotate(-90 16 {:.1})">Hit points</text>"#,
+        MARGIN_TOP + plot_height / 2.0,
+        MARGIN_TOP + plot_height / 2.0
+    )
+    .unwrap();
+
+    // Curves, starting from full health before the first turn
+    let players = [
+        (&header.player_one.strategy_name, 0),
+        (&header.player_two.strategy_name, 1),
+    ];
+    for (name, player) in players {
+        let mut points = format!("{:.1},{:.1}", x(0.0), y(max_hit_points));
+        for turn in &replay.turns {
+            let hit_points = if player == 0 {
+                turn.player_one_hit_points
+            } else {
+                turn.player_two_hit_points
+            };
+            write!(
+                points,
+                " {:.1},{:.1}",
+                x(turn.turn as f64 + 1.0),
+                y(hit_points as f64)
+            )
+            .unwrap();
+        }
+        writeln!(
+            svg,
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="1.5"/>"#,
+            points, PLAYER_COLORS[player]
+        )
+        .unwrap();
+
+        let legend_y = HEIGHT - 30.0 + 16.0 * player as f64;
+        writeln!(
+            svg,
+            r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="3"/>"#,
+            MARGIN_LEFT,
+            legend_y - 4.0,
+            MARGIN_LEFT + 20.0,
+            legend_y - 4.0,
+            PLAYER_COLORS[player]
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}">Player {}: {}</text>"#,
+            MARGIN_LEFT + 26.0,
+            legend_y,
+            player + 1,
+            escape(name)
+        )
+        .unwrap();
+    }
+
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
+
+const CELL_SIZE: f64 = 12.0;
+
+// Renders one generation of a strategy lattice, one colored square per cell,
+// with a legend of the strategies still present.
+pub fn lattice_svg(
+    cells: &[usize],
+    width: usize,
+    agent_names: &[String],
+    generation: usize,
+) -> String {
+    let height = cells.len() / width.max(1);
+    let color = |agent: usize| {
+        format!(
+            "hsl({:.0},65%,50%)",
+            agent as f64 * 360.0 / agent_names.len() as f64
+        )
+    };
+    let mut present: Vec<usize> = cells.to_vec();
+    present.sort();
+    present.dedup();
+
+    let map_width = width as f64 * CELL_SIZE;
+    let map_height = height as f64 * CELL_SIZE;
+    let svg_width = (map_width + 2.0 * MARGIN_RIGHT).max(WIDTH);
+    let svg_height = MARGIN_TOP + map_height + 20.0 + 16.0 * present.len() as f64;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{svg_width}" height="{svg_height}" viewBox="0 0 {svg_width} {svg_height}" font-family="sans-serif" font-size="12">"#
+    )
+    .unwrap();
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+    writeln!(
+        svg,
+        r#"<text x="{}" y="24" text-anchor="middle" font-size="16">Strategies after generation {}</text>"#,
+        svg_width / 2.0,
+        generation
+    )
+    .unwrap();
+
+    let left = (svg_width - map_width) / 2.0;
+    for (cell, &agent) in cells.iter().enumerate() {
+        writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="{CELL_SIZE}" height="{CELL_SIZE}" fill="{}"/>"#,
+            left + (cell % width) as f64 * CELL_SIZE,
+            MARGIN_TOP + (cell / width) as f64 * CELL_SIZE,
+            color(agent)
+        )
+        .unwrap();
+    }
+
+    for (entry, &agent) in present.iter().enumerate() {
+        let legend_y = MARGIN_TOP + map_height + 30.0 + 16.0 * entry as f64;
+        let count = cells.iter().filter(|&&a| a == agent).count();
+        writeln!(
+            svg,
+            r#"<rect x="{:.1}" y="{:.1}" width="10" height="10" fill="{}"/>"#,
+            left,
+            legend_y - 9.0,
+            color(agent)
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}">{} ({} cells)</text>"#,
+            left + 16.0,
+            legend_y,
+            escape(&agent_names[agent]),
+            count
+        )
+        .unwrap();
+    }
+
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
@@ -0,0 +1,269 @@
+// Agents shipped as compiled plugins, i.e. shared libraries loaded at
+// startup from a plugins directory. The interface between the sandbox and a
+// plugin is plain C, so plugins can be written in any language and built
+// with any compiler version. A plugin exports
+//
+//   const PluginDeclaration *the_duel_plugin(void);
+//
+// returning a declaration that lives as long as the library. The declaration
+// names the agent, which is registered under that name, and holds the
+// functions creating, asking and destroying agent instances. Actions are
+// encoded as 0 for ATTACK and 1 for FINCH. The loader needs the `plugins`
+// feature.
+
+use std::ffi::{CStr, c_char, c_void};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use libloading::{Library, Symbol};
+use rand::Rng;
+
+use crate::agents::{GameAgent, Observation, SharedRng};
+use crate::duel::{Action, PlayerState};
+use crate::history::HistoryView;
+use crate::registry::AgentRegistry;
+
+// Bumped whenever the declaration or the observation change.
+pub const PLUGIN_ABI_VERSION: u32 = 2;
+
+// The symbol every plugin exports.
+pub const PLUGIN_SYMBOL: &[u8] = b"the_duel_plugin";
+
+// The observation of `Observation`, with -1 for missing values.
+#[repr(C)]
+pub struct PluginObservation {
+    pub turn: u64,
+    pub max_hit_points: i64,
+    pub own_hit_points: i64,
+    // Only meaningful if `opposing_hit_points_known` is 1.
+    pub opposing_hit_points: i64,
+    pub opposing_hit_points_known: u8,
+    pub own_action: i32,
+    pub opposing_action: i32,
+    // -1 if the rules use no stamina or the opponent's state is hidden.
+    pub own_stamina: i64,
+    pub opposing_stamina: i64,
+}
+
+fn encode(action: &Option<Action>) -> i32 {
+    match action {
+        Some(Action::ATTACK) => 0,
+        Some(Action::FINCH) => 1,
+        None => -1,
+    }
+}
+
+impl From<&Observation> for PluginObservation {
+    fn from(observation: &Observation) -> Self {
+        Self {
+            turn: observation.turn as u64,
+            max_hit_points: observation.max_hit_points,
+            own_hit_points: observation.own_hit_points,
+            opposing_hit_points: observation.opposing_hit_points.unwrap_or(-1),
+            opposing_hit_points_known: observation.opposing_hit_points.is_some() as u8,
+            own_action: encode(&observation.own_action),
+            opposing_action: encode(&observation.opposing_action),
+            own_stamina: observation.own_stamina.unwrap_or(-1),
+            opposing_stamina: observation.opposing_stamina.unwrap_or(-1),
+        }
+    }
+}
+
+#[repr(C)]
+pub struct PluginDeclaration {
+    pub abi_version: u32,
+    // NUL-terminated name the agent is registered under.
+    pub name: *const c_char,
+    // A new agent instance drawing its random numbers from `seed`.
+    pub create: unsafe extern "C" fn(seed: u64) -> *mut c_void,
+    pub decide_action:
+        unsafe extern "C" fn(agent: *mut c_void, observation: *const PluginObservation) -> u32,
+    pub destroy: unsafe extern "C" fn(agent: *mut c_void),
+}
+
+#[derive(Debug)]
+pub enum PluginError {
+    Io(io::Error),
+    Load {
+        path: PathBuf,
+        err: libloading::Error,
+    },
+    AbiVersion {
+        path: PathBuf,
+        version: u32,
+    },
+    InvalidName(PathBuf),
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PluginError::Io(err) => write!(f, "could not read plugins directory: {}", err),
+            PluginError::Load { path, err } => {
+                write!(f, "could not load plugin {}: {}", path.display(), err)
+            }
+            PluginError::AbiVersion { path, version } => write!(
+                f,
+                "plugin {} was built for ABI version {}, expected {}",
+                path.display(),
+                version,
+                PLUGIN_ABI_VERSION
+            ),
+            PluginError::InvalidName(path) => {
+                write!(f, "plugin {} declares no valid name", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PluginError {}
+
+impl From<io::Error> for PluginError {
+    fn from(err: io::Error) -> Self {
+        PluginError::Io(err)
+    }
+}
+
+// A loaded plugin. The library stays loaded for as long as agents use it.
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+    declaration: *const PluginDeclaration,
+    _library: Library,
+}
+
+impl Plugin {
+    pub fn load(path: &Path) -> Result<Self, PluginError> {
+        let load = |err| PluginError::Load {
+            path: path.to_path_buf(),
+            err,
+        };
+        // SAFETY: loading runs the library's initialisers; plugins are
+        // trusted like any other code the sandbox runs.
+        let library = unsafe { Library::new(path) }.map_err(load)?;
+        let declaration = unsafe {
+            let entry: Symbol<unsafe extern "C" fn() -> *const PluginDeclaration> =
+                library.get(PLUGIN_SYMBOL).map_err(load)?;
+            entry()
+        };
+        // SAFETY: the plugin promises a declaration living as long as the library
+        let declared = unsafe { declaration.as_ref() }
+            .ok_or_else(|| PluginError::InvalidName(path.to_path_buf()))?;
+        if declared.abi_version != PLUGIN_ABI_VERSION {
+            return Err(PluginError::AbiVersion {
+                path: path.to_path_buf(),
+                version: declared.abi_version,
+            });
+        }
+        if declared.name.is_null() {
+            return Err(PluginError::InvalidName(path.to_path_buf()));
+        }
+        let name = unsafe { CStr::from_ptr(declared.name) }
+            .to_str()
+            .map_err(|_| PluginError::InvalidName(path.to_path_buf()))?
+            .to_string();
+        Ok(Self {
+            name,
+            path: path.to_path_buf(),
+            declaration,
+            _library: library,
+        })
+    }
+
+    fn declaration(&self) -> &PluginDeclaration {
+        // SAFETY: checked to be non-null on loading, valid while the library is loaded
+        unsafe { &*self.declaration }
+    }
+}
+
+// Plays by an agent instance created by a plugin. Every copy creates its
+// own instance, seeded from the experiment's generator.
+pub struct PluginAgent {
+    plugin: Rc<Plugin>,
+    rng: SharedRng,
+    instance: *mut c_void,
+}
+
+impl PluginAgent {
+    pub fn new(plugin: Rc<Plugin>, rng: SharedRng) -> Self {
+        let seed = rng.borrow_mut().random::<u64>();
+        let instance = unsafe { (plugin.declaration().create)(seed) };
+        Self {
+            plugin,
+            rng,
+            instance,
+        }
+    }
+}
+
+impl Drop for PluginAgent {
+    fn drop(&mut self) {
+        unsafe { (self.plugin.declaration().destroy)(self.instance) }
+    }
+}
+
+impl GameAgent for PluginAgent {
+    fn decide_action(
+        &mut self,
+        own_player_state: &PlayerState,
+        opposing_player_actions: &Option<Action>,
+        opposing_player_state: &Option<PlayerState>,
+        history: &HistoryView,
+    ) -> Action {
+        let observation = PluginObservation::from(&Observation::new(
+            own_player_state,
+            opposing_player_actions,
+            opposing_player_state,
+            history,
+        ));
+        match unsafe { (self.plugin.declaration().decide_action)(self.instance, &observation) } {
+            0 => Action::ATTACK,
+            1 => Action::FINCH,
+            other => panic!(
+                "plugin {} answered with invalid action {}",
+                self.plugin.path.display(),
+                other
+            ),
+        }
+    }
+
+    fn strategy_name(&self) -> String {
+        format!("Plugin agent {}", self.plugin.name)
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent> {
+        Box::new(Self::new(self.plugin.clone(), self.rng.clone()))
+    }
+}
+
+// Loads every shared library in `directory` and registers its agent,
+// returning the names of the registered agents. A missing directory holds
+// no plugins.
+pub fn load_plugins(
+    directory: &Path,
+    registry: &mut AgentRegistry,
+) -> Result<Vec<String>, PluginError> {
+    if !directory.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|path| {
+        path.extension()
+            .is_some_and(|extension| extension == std::env::consts::DLL_EXTENSION)
+    });
+    paths.sort();
+    let mut names = Vec::new();
+    for path in paths {
+        let plugin = Rc::new(Plugin::load(&path)?);
+        names.push(plugin.name.clone());
+        registry.register(&plugin.name.clone(), move |_, rng| {
+            Ok(Box::new(PluginAgent::new(plugin.clone(), rng.clone())))
+        });
+    }
+    Ok(names)
+}
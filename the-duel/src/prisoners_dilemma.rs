@@ -0,0 +1,202 @@
+// The iterated prisoner's dilemma: every round both players cooperate or
+// defect and score by the payoff matrix, for a fixed number of rounds. The
+// player with the higher total score wins, although in Axelrod's tradition
+// strategies are ranked by their average payoff rather than their wins,
+// see PayoffObserver.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{GameOutcome, GameSettings, GameState, SimultaneousGame};
+use crate::observer::Scored;
+use crate::openspiel::SpielGame;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Choice {
+    COOPERATE,
+    DEFECT,
+}
+
+impl Choice {
+    pub const ALL: [Choice; 2] = [Choice::COOPERATE, Choice::DEFECT];
+
+    // Position of the choice in `ALL`.
+    pub fn index(&self) -> usize {
+        match self {
+            Choice::COOPERATE => 0,
+            Choice::DEFECT => 1,
+        }
+    }
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// The payoff of a player: the temptation to defect against a cooperator,
+// the reward of mutual cooperation, the punishment of mutual defection and
+// the sucker's payoff of cooperating against a defector.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PayoffMatrix {
+    pub temptation: i64,
+    pub reward: i64,
+    pub punishment: i64,
+    pub sucker: i64,
+}
+
+// Axelrod's payoffs.
+impl Default for PayoffMatrix {
+    fn default() -> Self {
+        Self {
+            temptation: 5,
+            reward: 3,
+            punishment: 1,
+            sucker: 0,
+        }
+    }
+}
+
+impl PayoffMatrix {
+    // Checks that the payoffs make a prisoner's dilemma, in which mutual
+    // cooperation also beats taking turns at exploiting each other.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.temptation > self.reward
+            && self.reward > self.punishment
+            && self.punishment > self.sucker)
+        {
+            return Err(format!(
+                "the payoffs need temptation > reward > punishment > sucker, got {} {} {} {}",
+                self.temptation, self.reward, self.punishment, self.sucker
+            ));
+        }
+        if 2 * self.reward <= self.temptation + self.sucker {
+            return Err(format!(
+                "the payoffs need 2 * reward > temptation + sucker, got {} and {}",
+                2 * self.reward,
+                self.temptation + self.sucker
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn payoff(&self, own: &Choice, opposing: &Choice) -> i64 {
+        match (own, opposing) {
+            (Choice::COOPERATE, Choice::COOPERATE) => self.reward,
+            (Choice::COOPERATE, Choice::DEFECT) => self.sucker,
+            (Choice::DEFECT, Choice::COOPERATE) => self.temptation,
+            (Choice::DEFECT, Choice::DEFECT) => self.punishment,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Prisoner {
+    pub score: i64,
+}
+
+impl Scored for Prisoner {
+    fn score(&self) -> f64 {
+        self.score as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrisonersDilemma {
+    pub payoffs: PayoffMatrix,
+    pub rounds: usize,
+}
+
+impl Default for PrisonersDilemma {
+    fn default() -> Self {
+        Self {
+            payoffs: PayoffMatrix::default(),
+            rounds: 200,
+        }
+    }
+}
+
+impl PrisonersDilemma {
+    pub fn new(payoffs: PayoffMatrix, rounds: usize) -> Self {
+        Self { payoffs, rounds }
+    }
+}
+
+impl SimultaneousGame for PrisonersDilemma {
+    type PlayerState = Prisoner;
+    type Action = Choice;
+
+    fn initial_state(&self) -> GameState<Self> {
+        GameState::new(Prisoner::default(), Prisoner::default())
+    }
+
+    fn resolve_actions(
+        &self,
+        state: &mut GameState<Self>,
+        player_one_action: Choice,
+        player_two_action: Choice,
+    ) {
+        state.player_one_state.score += self.payoffs.payoff(&player_one_action, &player_two_action);
+        state.player_two_state.score += self.payoffs.payoff(&player_two_action, &player_one_action);
+    }
+
+    fn check_end_condition(&self, state: &GameState<Self>) -> GameOutcome {
+        if state.history.turns() < self.rounds {
+            return GameOutcome::CONTINUE;
+        }
+        let (one, two) = (state.player_one_state.score, state.player_two_state.score);
+        match one.cmp(&two) {
+            std::cmp::Ordering::Greater => GameOutcome::WIN(1),
+            std::cmp::Ordering::Less => GameOutcome::WIN(2),
+            std::cmp::Ordering::Equal => GameOutcome::TIE,
+        }
+    }
+}
+
+// Cooperation is action 0, defection action 1. The observation holds the
+// fraction of rounds played, followed by the opponent's last choice one-hot
+// encoded.
+impl SpielGame for PrisonersDilemma {
+    fn num_distinct_actions(&self) -> usize {
+        Choice::ALL.len()
+    }
+
+    fn action_id(&self, action: &Choice) -> usize {
+        action.index()
+    }
+
+    fn action_from_id(&self, id: usize) -> Option<Choice> {
+        Choice::ALL.get(id).cloned()
+    }
+
+    fn action_to_string(&self, id: usize) -> String {
+        match self.action_from_id(id) {
+            Some(action) => action.to_string(),
+            None => format!("invalid action {}", id),
+        }
+    }
+
+    fn observation_tensor_size(&self) -> usize {
+        1 + Choice::ALL.len()
+    }
+
+    fn observation_tensor(
+        &self,
+        state: &GameState<Self>,
+        _settings: &GameSettings,
+        player: u64,
+    ) -> Vec<f32> {
+        let opposing_action = state.observed_opposing_action(player);
+        let mut tensor = vec![state.history.turns() as f32 / self.rounds.max(1) as f32];
+        tensor.extend(
+            Choice::ALL
+                .iter()
+                .map(|action| (opposing_action.as_ref() == Some(action)) as u8 as f32),
+        );
+        tensor
+    }
+}
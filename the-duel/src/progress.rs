@@ -0,0 +1,65 @@
+// A progress bar on standard error for long tournaments: games played out of
+// all games scheduled, games per second, the estimated time remaining and
+// the pairings completed so far. Needs the `progress` feature.
+
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::game::{GameOutcome, GameState, SimultaneousGame};
+use crate::observer::GameObserver;
+use crate::tournament::PairingRecord;
+
+const TEMPLATE: &str =
+    "{elapsed_precise} [{bar:40}] {pos}/{len} games ({per_sec}, ETA {eta}) {msg}";
+
+pub struct ProgressObserver {
+    bar: ProgressBar,
+    num_pairings: usize,
+    pairings_done: usize,
+}
+
+impl ProgressObserver {
+    // Progress of `num_pairings` pairings of at most `games_per_pairing`
+    // games each; best-of matches decided early leave the bar short.
+    pub fn new(num_pairings: usize, games_per_pairing: u64) -> Self {
+        let bar = ProgressBar::new(num_pairings as u64 * games_per_pairing);
+        bar.set_style(
+            ProgressStyle::with_template(TEMPLATE)
+                .expect("valid progress template")
+                .progress_chars("=> "),
+        );
+        // Keeps the elapsed time and the rate moving during long games.
+        bar.enable_steady_tick(Duration::from_millis(200));
+        let mut observer = Self {
+            bar,
+            num_pairings,
+            pairings_done: 0,
+        };
+        observer.show_pairings();
+        observer
+    }
+
+    fn show_pairings(&mut self) {
+        self.bar.set_message(format!(
+            "{}/{} pairings",
+            self.pairings_done, self.num_pairings
+        ));
+    }
+
+    // Leaves the final state of the bar on the terminal.
+    pub fn finish(self) {
+        self.bar.finish();
+    }
+}
+
+impl<G: SimultaneousGame> GameObserver<G> for ProgressObserver {
+    fn on_game_end(&mut self, _state: &GameState<G>, _outcome: &GameOutcome) {
+        self.bar.inc(1);
+    }
+
+    fn on_match_end(&mut self, _player_one: usize, _player_two: usize, _record: &PairingRecord) {
+        self.pairings_done += 1;
+        self.show_pairings();
+    }
+}
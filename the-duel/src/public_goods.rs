@@ -0,0 +1,266 @@
+// The public goods game among a group of players, played for a fixed number
+// of rounds. Every round, each player receives an endowment and contributes
+// some of it to a common pool; the pool is multiplied and shared equally
+// among the whole group, so that every contribution pays its contributor less
+// than it costs, while paying the group more. After seeing the contributions,
+// players may punish others, paying `punishment_cost` to fine each of them
+// `punishment_fine`, as in the experiments of Fehr and Gächter.
+//
+// Groups are sampled from the roster as the tables of the free-for-all are,
+// see `sample_table`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::agents::{PublicGoodsAgent, SharedRng};
+use crate::free_for_all::sample_table;
+
+// What a player does in a round: its contribution to the pool and the seats
+// it punishes for the contributions of the last round.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Contribution {
+    pub amount: u32,
+    pub punish: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PublicGoods {
+    pub endowment: u32,
+    pub multiplier: f64,
+    pub rounds: usize,
+    pub punishment_cost: f64,
+    pub punishment_fine: f64,
+}
+
+impl Default for PublicGoods {
+    fn default() -> Self {
+        Self {
+            endowment: 20,
+            multiplier: 1.6,
+            rounds: 10,
+            punishment_cost: 1.0,
+            punishment_fine: 3.0,
+        }
+    }
+}
+
+impl PublicGoods {
+    // Checks that the game is a social dilemma for groups of `group_size`:
+    // contributing pays the group, but not the contributor.
+    pub fn validate(&self, group_size: usize) -> Result<(), String> {
+        if group_size < 2 {
+            return Err(format!(
+                "groups need at least 2 players, got {}",
+                group_size
+            ));
+        }
+        if !(self.multiplier > 1.0 && self.multiplier < group_size as f64) {
+            return Err(format!(
+                "the multiplier has to lie between 1 and the group size {}, got {}",
+                group_size, self.multiplier
+            ));
+        }
+        if self.punishment_cost < 0.0 || self.punishment_fine < 0.0 {
+            return Err(format!(
+                "punishing can neither pay nor reward, got cost {} and fine {}",
+                self.punishment_cost, self.punishment_fine
+            ));
+        }
+        Ok(())
+    }
+
+    // Every player's payoff of a round: what it kept of its endowment and
+    // its share of the multiplied pool, less the cost of the punishments it
+    // dealt and the fines of those it received.
+    pub fn payoffs(&self, contributions: &[Contribution]) -> Vec<f64> {
+        let pool: u32 = contributions.iter().map(|c| c.amount).sum();
+        let share = self.multiplier * pool as f64 / contributions.len() as f64;
+        let mut payoffs: Vec<f64> = contributions
+            .iter()
+            .map(|c| (self.endowment - c.amount) as f64 + share)
+            .collect();
+        for (seat, contribution) in contributions.iter().enumerate() {
+            for &target in &contribution.punish {
+                if target != seat && target < contributions.len() {
+                    payoffs[seat] -= self.punishment_cost;
+                    payoffs[target] -= self.punishment_fine;
+                }
+            }
+        }
+        payoffs
+    }
+}
+
+// The rounds a group played so far, every seat's contribution and payoff.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroupState {
+    pub contributions: Vec<Vec<Contribution>>,
+    pub payoffs: Vec<Vec<f64>>,
+}
+
+impl GroupState {
+    pub fn rounds(&self) -> usize {
+        self.contributions.len()
+    }
+
+    // The amounts every seat contributed in the last round.
+    pub fn last_amounts(&self) -> Option<Vec<u32>> {
+        self.contributions
+            .last()
+            .map(|round| round.iter().map(|c| c.amount).collect())
+    }
+
+    // The average amount the others of `seat` contributed in the last round.
+    pub fn others_average(&self, seat: usize) -> Option<f64> {
+        let amounts = self.last_amounts()?;
+        let others: Vec<u32> = amounts
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != seat)
+            .map(|(_, amount)| *amount)
+            .collect();
+        Some(others.iter().sum::<u32>() as f64 / others.len().max(1) as f64)
+    }
+}
+
+// A group of agents playing the rules, seated in order.
+pub struct Group {
+    pub rules: PublicGoods,
+    pub agents: Vec<PublicGoodsAgent>,
+}
+
+impl Group {
+    pub fn new(rules: PublicGoods, agents: Vec<PublicGoodsAgent>) -> Self {
+        Self { rules, agents }
+    }
+
+    // Plays all rounds, returning the group's state after the last.
+    pub fn play(&mut self) -> GroupState {
+        let mut state = GroupState::default();
+        for _ in 0..self.rules.rounds {
+            let contributions: Vec<Contribution> = (0..self.agents.len())
+                .map(|seat| {
+                    let mut contribution = self.agents[seat].decide(&self.rules, seat, &state);
+                    contribution.amount = contribution.amount.min(self.rules.endowment);
+                    contribution
+                })
+                .collect();
+            state.payoffs.push(self.rules.payoffs(&contributions));
+            state.contributions.push(contributions);
+        }
+        state
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PublicGoodsConfig {
+    // Players per group.
+    #[serde(default = "default_group_size")]
+    pub group_size: usize,
+    // Groups sampled from the roster.
+    #[serde(default = "default_groups")]
+    pub groups: u64,
+}
+
+fn default_group_size() -> usize {
+    4
+}
+
+fn default_groups() -> u64 {
+    200
+}
+
+impl PublicGoodsConfig {
+    pub fn validate(&self, num_agents: usize) -> Result<(), String> {
+        if self.group_size > num_agents {
+            return Err(format!(
+                "groups of {} players need as many strategies, got {}",
+                self.group_size, num_agents
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Every agent's total payoff, contributions and fines over the rounds it
+// played.
+pub struct PublicGoodsResults {
+    pub agent_names: Vec<String>,
+    pub endowment: u32,
+    pub rounds: Vec<u64>,
+    pub payoffs: Vec<f64>,
+    pub contributions: Vec<u64>,
+    pub fines: Vec<u64>,
+}
+
+impl PublicGoodsResults {
+    pub fn average_payoff(&self, agent: usize) -> f64 {
+        self.payoffs[agent] / self.rounds[agent].max(1) as f64
+    }
+
+    // The average share of the endowment the agent contributed.
+    pub fn contribution_rate(&self, agent: usize) -> f64 {
+        self.contributions[agent] as f64
+            / (self.rounds[agent].max(1) * self.endowment as u64).max(1) as f64
+    }
+}
+
+// Plays groups of agents sampled uniformly from the roster.
+pub struct PublicGoodsTournament {
+    pub rules: PublicGoods,
+    pub agents: Vec<PublicGoodsAgent>,
+    pub config: PublicGoodsConfig,
+}
+
+impl PublicGoodsTournament {
+    pub fn new(
+        rules: PublicGoods,
+        agents: Vec<PublicGoodsAgent>,
+        config: PublicGoodsConfig,
+    ) -> Self {
+        Self {
+            rules,
+            agents,
+            config,
+        }
+    }
+
+    pub fn run(&self, rng: &SharedRng) -> PublicGoodsResults {
+        let num_agents = self.agents.len();
+        let mut results = PublicGoodsResults {
+            agent_names: self
+                .agents
+                .iter()
+                .map(|agent| agent.strategy_name())
+                .collect(),
+            endowment: self.rules.endowment,
+            rounds: vec![0; num_agents],
+            payoffs: vec![0.0; num_agents],
+            contributions: vec![0; num_agents],
+            fines: vec![0; num_agents],
+        };
+        for _ in 0..self.config.groups {
+            let seats = sample_table(rng, num_agents, self.config.group_size);
+            let mut group = Group::new(
+                self.rules.clone(),
+                seats
+                    .iter()
+                    .map(|agent| self.agents[*agent].clone())
+                    .collect(),
+            );
+            let state = group.play();
+            for (round, contributions) in state.contributions.iter().enumerate() {
+                for (seat, agent) in seats.iter().enumerate() {
+                    results.rounds[*agent] += 1;
+                    results.payoffs[*agent] += state.payoffs[round][seat];
+                    results.contributions[*agent] += contributions[seat].amount as u64;
+                    results.fines[*agent] += contributions
+                        .iter()
+                        .filter(|c| c.punish.contains(&seat))
+                        .count() as u64;
+                }
+            }
+        }
+        results
+    }
+}
@@ -0,0 +1,185 @@
+// Python bindings, so that the engine can be combined with Python analysis
+// and machine learning tooling. Build and install the module with
+//
+//   maturin develop --manifest-path the-duel/Cargo.toml
+//
+// and use it from Python:
+//
+//   import the_duel
+//
+//   class TitForTat:
+//       def decide_action(self, observation):
+//           return observation["opposing_action"] or "FINCH"
+//
+//   game = the_duel.Game(TitForTat(), "markov(0.3, 0.6)", max_hit_points=10)
+//   while not game.is_over:
+//       print(game.step())
+//   results = the_duel.run_tournament(["attack", "mirror", TitForTat()], num_retrials=10)
+//
+// Agents are given either as a registry specification or as a Python object
+// with a `decide_action` method, see `PythonAgent`. Results are returned as
+// dicts in the layout of the JSON output. The bindings need the `python`
+// feature.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rand::SeedableRng;
+use serde::Serialize;
+
+use crate::agents::{GameAgent, PythonAgent, SharedRng};
+use crate::duel::Duel;
+use crate::game::{Game, GameOutcome, GameState};
+use crate::registry::AgentRegistry;
+use crate::rng_audit::AuditedRng;
+use crate::tournament::{PairingSchedule, Tournament};
+
+fn to_python<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let text =
+        serde_json::to_string(value).map_err(|err| PyValueError::new_err(err.to_string()))?;
+    Ok(py.import("json")?.call_method1("loads", (text,))?.unbind())
+}
+
+fn seeded_rng(seed: u64) -> SharedRng {
+    Rc::new(RefCell::new(AuditedRng::seed_from_u64(seed)))
+}
+
+// A registry specification such as "markov(0.3, 0.6)" or a Python agent object.
+fn build_agent(agent: &Bound<'_, PyAny>, rng: &SharedRng) -> PyResult<Box<dyn GameAgent>> {
+    match agent.extract::<String>() {
+        Ok(spec) => AgentRegistry::with_builtin_agents()
+            .build(&spec, rng)
+            .map_err(|err| PyValueError::new_err(err.to_string())),
+        Err(_) => Ok(Box::new(PythonAgent::new(agent.clone().unbind()))),
+    }
+}
+
+// A duel stepped turn by turn from Python.
+#[pyclass(unsendable, name = "Game")]
+pub struct PyGame {
+    game: Game,
+    state: GameState<Duel>,
+    outcome: GameOutcome,
+}
+
+#[pymethods]
+impl PyGame {
+    #[new]
+    #[pyo3(signature = (player_one, player_two, max_hit_points = 600, seed = 0))]
+    fn new(
+        player_one: &Bound<'_, PyAny>,
+        player_two: &Bound<'_, PyAny>,
+        max_hit_points: i64,
+        seed: u64,
+    ) -> PyResult<Self> {
+        let rng = seeded_rng(seed);
+        let mut game = Game::new(
+            Duel::new(max_hit_points),
+            build_agent(player_one, &rng)?,
+            build_agent(player_two, &rng)?,
+        );
+        game.player_one_agent.set_mode(game.settings.mode);
+        game.player_two_agent.set_mode(game.settings.mode);
+        Ok(Self {
+            state: game.initial_state(),
+            game,
+            outcome: GameOutcome::CONTINUE,
+        })
+    }
+
+    #[getter]
+    fn is_over(&self) -> bool {
+        self.outcome != GameOutcome::CONTINUE
+    }
+
+    // Plays one turn and returns the resulting state.
+    fn step(&mut self, py: Python<'_>) -> PyResult<PyObject> {
+        if self.is_over() {
+            return Err(PyRuntimeError::new_err("the game is over"));
+        }
+        self.game.step_game(&mut self.state);
+        self.outcome = self.game.check_end_condition(&self.state);
+        self.state(py)
+    }
+
+    // Plays until the game is decided and returns the outcome.
+    fn run(&mut self, py: Python<'_>) -> PyResult<PyObject> {
+        while !self.is_over() {
+            self.game.step_game(&mut self.state);
+            self.outcome = self.game.check_end_condition(&self.state);
+        }
+        to_python(py, &self.outcome)
+    }
+
+    // The hit points, last actions and outcome so far.
+    fn state(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let state = PyDict::new(py);
+        state.set_item("turn", self.state.history.turns())?;
+        state.set_item("max_hit_points", self.game.rules.max_hit_points)?;
+        for (key, agent, player_state, action) in [
+            (
+                "player_one",
+                &self.game.player_one_agent,
+                &self.state.player_one_state,
+                &self.state.player_one_action,
+            ),
+            (
+                "player_two",
+                &self.game.player_two_agent,
+                &self.state.player_two_state,
+                &self.state.player_two_action,
+            ),
+        ] {
+            let seat = PyDict::new(py);
+            seat.set_item("strategy_name", agent.strategy_name())?;
+            seat.set_item("hit_points", player_state.current_hit_points)?;
+            seat.set_item("stamina", player_state.stamina)?;
+            seat.set_item("last_action", to_python(py, action)?)?;
+            state.set_item(key, seat)?;
+        }
+        state.set_item("outcome", to_python(py, &self.outcome)?)?;
+        Ok(state.into_any().unbind())
+    }
+}
+
+// The names of the agents known to the registry.
+#[pyfunction]
+fn agent_names() -> Vec<String> {
+    AgentRegistry::with_builtin_agents()
+        .names()
+        .map(String::from)
+        .collect()
+}
+
+// Plays a tournament between the given agents and returns its results.
+#[pyfunction]
+#[pyo3(signature = (agents, num_retrials = 1, max_hit_points = 600, seed = 0, schedule = "round_robin"))]
+fn run_tournament(
+    py: Python<'_>,
+    agents: Vec<Bound<'_, PyAny>>,
+    num_retrials: u64,
+    max_hit_points: i64,
+    seed: u64,
+    schedule: &str,
+) -> PyResult<PyObject> {
+    let schedule: PairingSchedule = serde_json::from_value(serde_json::Value::from(schedule))
+        .map_err(|_| PyValueError::new_err(format!("unknown schedule '{}'", schedule)))?;
+    let rng = seeded_rng(seed);
+    let agents = agents
+        .iter()
+        .map(|agent| build_agent(agent, &rng))
+        .collect::<PyResult<Vec<_>>>()?;
+    let results = Tournament::new(Duel::new(max_hit_points), agents, num_retrials, schedule).run();
+    to_python(py, &results)
+}
+
+#[pymodule]
+fn the_duel(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyGame>()?;
+    module.add_function(wrap_pyfunction!(agent_names, module)?)?;
+    module.add_function(wrap_pyfunction!(run_tournament, module)?)?;
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use std::f64::consts::PI;
+
+use serde::Deserialize;
+
+use crate::game::{GameOutcome, GameState, SimultaneousGame};
+use crate::observer::GameObserver;
+use crate::tournament::PairingRecord;
+
+// Conversion factor between the Glicko and the internal Glicko-2 scale.
+const GLICKO2_SCALE: f64 = 173.7178;
+const CONVERGENCE_TOLERANCE: f64 = 0.000001;
+// Deviations never grow beyond the deviation of an unrated player.
+const MAX_DEVIATION: f64 = 350.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glicko2Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Default for Glicko2Rating {
+    fn default() -> Self {
+        Self {
+            rating: 1500.0,
+            deviation: MAX_DEVIATION,
+            volatility: 0.06,
+        }
+    }
+}
+
+impl Glicko2Rating {
+    fn mu(&self) -> f64 {
+        (self.rating - 1500.0) / GLICKO2_SCALE
+    }
+
+    fn phi(&self) -> f64 {
+        self.deviation / GLICKO2_SCALE
+    }
+
+    // 95% interval of the true rating.
+    pub fn interval(&self) -> (f64, f64) {
+        (
+            self.rating - 1.96 * self.deviation,
+            self.rating + 1.96 * self.deviation,
+        )
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+fn expected_score(mu: f64, opponent_mu: f64, opponent_phi: f64) -> f64 {
+    1.0 / (1.0 + (-g(opponent_phi) * (mu - opponent_mu)).exp())
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct RatingConfig {
+    // System constant constraining how fast volatility changes.
+    pub tau: f64,
+    // Stop the tournament once every deviation has dropped below this value.
+    pub convergence_deviation: Option<f64>,
+}
+
+impl Default for RatingConfig {
+    fn default() -> Self {
+        Self {
+            tau: 0.5,
+            convergence_deviation: None,
+        }
+    }
+}
+
+// Glicko-2 ratings of a roster.
+pub struct Glicko2 {
+    pub tau: f64,
+    pub ratings: Vec<Glicko2Rating>,
+}
+
+impl Glicko2 {
+    pub fn new(num_players: usize, tau: f64) -> Self {
+        Self {
+            tau,
+            ratings: vec![Glicko2Rating::default(); num_players],
+        }
+    }
+
+    // Rates one period of games given as (player, opponent, score of player),
+    // where the score is 1 for a win, 0.5 for a tie and 0 for a loss. Players
+    // without games in the period keep their rating.
+    pub fn rate_period(&mut self, games: &[(usize, usize, f64)]) {
+        let mut results = vec![Vec::new(); self.ratings.len()];
+        for &(player, opponent, score) in games {
+            if player == opponent {
+                continue;
+            }
+            results[player].push((self.ratings[opponent], score));
+            results[opponent].push((self.ratings[player], 1.0 - score));
+        }
+        for (player, results) in results.iter().enumerate() {
+            if !results.is_empty() {
+                self.ratings[player] = self.updated(&self.ratings[player], results);
+            }
+        }
+    }
+
+    // Rating after a period against the given opponents with the given scores.
+    pub fn updated(
+        &self,
+        rating: &Glicko2Rating,
+        results: &[(Glicko2Rating, f64)],
+    ) -> Glicko2Rating {
+        let mu = rating.mu();
+        let phi = rating.phi();
+        let sigma = rating.volatility;
+
+        if results.is_empty() {
+            let phi_star = (phi * phi + sigma * sigma).sqrt();
+            return Glicko2Rating {
+                deviation: (phi_star * GLICKO2_SCALE).min(MAX_DEVIATION),
+                ..*rating
+            };
+        }
+
+        let mut variance_inv = 0.0;
+        let mut improvement = 0.0;
+        for (opponent, score) in results {
+            let g_opponent = g(opponent.phi());
+            let expected = expected_score(mu, opponent.mu(), opponent.phi());
+            variance_inv += g_opponent * g_opponent * expected * (1.0 - expected);
+            improvement += g_opponent * (score - expected);
+        }
+        let variance = 1.0 / variance_inv;
+        let delta = variance * improvement;
+
+        let new_sigma = self.updated_volatility(phi, sigma, variance, delta);
+        let phi_star = (phi * phi + new_sigma * new_sigma).sqrt();
+        let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / variance).sqrt();
+        let new_mu = mu + new_phi * new_phi * improvement;
+
+        Glicko2Rating {
+            rating: new_mu * GLICKO2_SCALE + 1500.0,
+            deviation: (new_phi * GLICKO2_SCALE).min(MAX_DEVIATION),
+            volatility: new_sigma,
+        }
+    }
+
+    // Illinois variant of regula falsi, as in Glickman's description of Glicko-2.
+    fn updated_volatility(&self, phi: f64, sigma: f64, variance: f64, delta: f64) -> f64 {
+        let tau = self.tau;
+        let a = (sigma * sigma).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            let denominator = phi * phi + variance + ex;
+            ex * (delta * delta - phi * phi - variance - ex) / (2.0 * denominator * denominator)
+                - (x - a) / (tau * tau)
+        };
+
+        let mut lower = a;
+        let mut upper = if delta * delta > phi * phi + variance {
+            (delta * delta - phi * phi - variance).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * tau) < 0.0 {
+                k += 1.0;
+            }
+            a - k * tau
+        };
+
+        let mut f_lower = f(lower);
+        let mut f_upper = f(upper);
+        while (upper - lower).abs() > CONVERGENCE_TOLERANCE {
+            let candidate = lower + (lower - upper) * f_lower / (f_upper - f_lower);
+            let f_candidate = f(candidate);
+            if f_candidate * f_upper <= 0.0 {
+                lower = upper;
+                f_lower = f_upper;
+            } else {
+                f_lower /= 2.0;
+            }
+            upper = candidate;
+            f_upper = f_candidate;
+        }
+        (lower / 2.0).exp()
+    }
+
+    pub fn max_deviation(&self) -> f64 {
+        self.ratings
+            .iter()
+            .map(|rating| rating.deviation)
+            .fold(0.0, f64::max)
+    }
+}
+
+// Observer rating the roster of a tournament, using every round of an
+// interleaved tournament (or every pairing otherwise) as a rating period.
+pub struct RatingObserver {
+    pub ratings: Glicko2,
+    pub convergence_deviation: Option<f64>,
+    current_pairing: Option<(usize, usize)>,
+    period: Vec<(usize, usize, f64)>,
+}
+
+impl RatingObserver {
+    pub fn new(num_players: usize, config: &RatingConfig) -> Self {
+        Self {
+            ratings: Glicko2::new(num_players, config.tau),
+            convergence_deviation: config.convergence_deviation,
+            current_pairing: None,
+            period: Vec::new(),
+        }
+    }
+
+    fn finish_period(&mut self) {
+        self.ratings.rate_period(&self.period);
+        self.period.clear();
+    }
+
+    pub fn converged(&self) -> bool {
+        match self.convergence_deviation {
+            Some(threshold) => self.ratings.max_deviation() < threshold,
+            None => false,
+        }
+    }
+}
+
+impl<G: SimultaneousGame> GameObserver<G> for RatingObserver {
+    fn on_game_start(&mut self, player_one: usize, player_two: usize) {
+        self.current_pairing = Some((player_one, player_two));
+    }
+
+    fn on_game_end(&mut self, _state: &GameState<G>, outcome: &GameOutcome) {
+        let Some((player_one, player_two)) = self.current_pairing else {
+            return;
+        };
+        let score = match outcome {
+            GameOutcome::WIN(1) | GameOutcome::SURVIVOR(1) => 1.0,
+            GameOutcome::WIN(_) | GameOutcome::SURVIVOR(_) => 0.0,
+            GameOutcome::TIE | GameOutcome::DRAW(_) => 0.5,
+            GameOutcome::CONTINUE | GameOutcome::INTERRUPTED => return,
+        };
+        self.period.push((player_one, player_two, score));
+    }
+
+    fn on_round_end(&mut self, _round: u64) {
+        self.finish_period();
+    }
+
+    fn on_match_end(&mut self, _player_one: usize, _player_two: usize, _record: &PairingRecord) {
+        self.finish_period();
+    }
+
+    fn should_stop(&self) -> bool {
+        self.converged()
+    }
+}
@@ -0,0 +1,746 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Bound, RangeBounds, RangeFrom, RangeInclusive};
+use std::rc::Rc;
+
+#[cfg(feature = "grpc")]
+use crate::agents::GrpcAgent;
+#[cfg(feature = "lua")]
+use crate::agents::LuaAgent;
+#[cfg(feature = "neural")]
+use crate::agents::NeuralAgent;
+#[cfg(feature = "onnx")]
+use crate::agents::OnnxAgent;
+use crate::agents::{
+    AttackAgent, AttackModel, CfrAgent, DynamicProgrammingAgent, Estimator, Exp3Agent, Exp3Variant,
+    ExpectimaxAgent, ExternalAgent, GameAgent, GrimTriggerAgent, HedgeAgent, HmmAgent, HumanAgent,
+    MarkovPredictorAgent, MarkovRandomAgent, MirrorAgent, ModelKind, OneStepDecisionProcessAgent,
+    ParticleFilterAgent, PatternMatchingAgent, PavlovAgent, RandomAgent, RuleAgent, SharedRng,
+    TdAgent, TdRule, ThompsonSamplingAgent, TitForTatAgent,
+};
+use crate::duel::{Action, DamageMatrix, RuleVariant};
+#[cfg(feature = "neural")]
+use crate::neural::Policy;
+use crate::self_play::Checkpoint;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistryError {
+    Malformed(String),
+    UnknownAgent(String),
+    MissingArgument {
+        agent: String,
+        argument: String,
+    },
+    InvalidArgument {
+        agent: String,
+        argument: String,
+        value: String,
+    },
+    OutOfRange {
+        agent: String,
+        argument: String,
+        value: String,
+        expected: String,
+    },
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::Malformed(spec) => write!(f, "malformed agent specification '{}'", spec),
+            RegistryError::UnknownAgent(name) => write!(f, "unknown agent '{}'", name),
+            RegistryError::MissingArgument { agent, argument } => {
+                write!(f, "agent '{}' is missing argument '{}'", agent, argument)
+            }
+            RegistryError::InvalidArgument {
+                agent,
+                argument,
+                value,
+            } => write!(
+                f,
+                "agent '{}' got invalid value '{}' for argument '{}'",
+                agent, value, argument
+            ),
+            RegistryError::OutOfRange {
+                agent,
+                argument,
+                value,
+                expected,
+            } => write!(
+                f,
+                "agent '{}' got '{}' for argument '{}', expected a value in {}",
+                agent, value, argument, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+// Ranges of the arguments checked by `AgentArgs::f64_in`.
+pub const PROBABILITY: RangeInclusive<f64> = 0.0..=1.0;
+pub const NON_NEGATIVE: RangeFrom<f64> = 0.0..;
+pub const POSITIVE: (Bound<f64>, Bound<f64>) = (Bound::Excluded(0.0), Bound::Unbounded);
+// Step sizes, learning rates and decays.
+pub const RATE: (Bound<f64>, Bound<f64>) = (Bound::Excluded(0.0), Bound::Included(1.0));
+
+// A range in interval notation, e.g. `(0, 1]`.
+fn interval<T: fmt::Display>(range: &impl RangeBounds<T>) -> String {
+    let start = match range.start_bound() {
+        Bound::Included(start) => format!("[{}", start),
+        Bound::Excluded(start) => format!("({}", start),
+        Bound::Unbounded => String::from("(-inf"),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => format!("{}]", end),
+        Bound::Excluded(end) => format!("{})", end),
+        Bound::Unbounded => String::from("inf)"),
+    };
+    format!("{}, {}", start, end)
+}
+
+// `value` of `agent`'s `argument` if it lies in `range`, shared with agents
+// configured by tables, see AgentConfig::validate.
+pub fn check_range<T: PartialOrd + fmt::Display>(
+    agent: &str,
+    argument: &str,
+    value: T,
+    range: impl RangeBounds<T>,
+) -> Result<T, RegistryError> {
+    if range.contains(&value) {
+        Ok(value)
+    } else {
+        Err(RegistryError::OutOfRange {
+            agent: agent.to_string(),
+            argument: argument.to_string(),
+            value: value.to_string(),
+            expected: interval(&range),
+        })
+    }
+}
+
+// The arguments of an agent specification such as `markov(0.5, to_finch=0.1)`.
+// Arguments can be given by position or by name.
+pub struct AgentArgs {
+    pub agent: String,
+    // Everything between the parentheses.
+    pub text: String,
+    positional: Vec<String>,
+    named: BTreeMap<String, String>,
+    // The damage matrix of the game the agent is built for.
+    pub damage: DamageMatrix,
+}
+
+impl AgentArgs {
+    pub fn parse(spec: &str) -> Result<Self, RegistryError> {
+        let spec = spec.trim();
+        let malformed = || RegistryError::Malformed(spec.to_string());
+        let (agent, arguments) = match spec.find('(') {
+            Some(open) => {
+                let arguments = spec[open + 1..].strip_suffix(')').ok_or_else(malformed)?;
+                (spec[..open].trim(), arguments)
+            }
+            None => (spec, ""),
+        };
+        if agent.is_empty() {
+            return Err(malformed());
+        }
+
+        let mut positional = Vec::new();
+        let mut named = BTreeMap::new();
+        for argument in arguments
+            .split(',')
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+        {
+            match argument.split_once('=') {
+                Some((key, value)) => {
+                    named.insert(key.trim().to_string(), value.trim().to_string());
+                }
+                None => {
+                    if !named.is_empty() {
+                        return Err(malformed());
+                    }
+                    positional.push(argument.to_string())
+                }
+            }
+        }
+
+        Ok(Self {
+            agent: agent.to_string(),
+            text: arguments.trim().to_string(),
+            positional,
+            named,
+            damage: DamageMatrix::default(),
+        })
+    }
+
+    // Looks up an argument by one of its names, falling back to its position.
+    pub fn raw(&self, position: usize, names: &[&str]) -> Option<&str> {
+        names
+            .iter()
+            .find_map(|name| self.named.get(*name))
+            .or_else(|| self.positional.get(position))
+            .map(String::as_str)
+    }
+
+    pub fn f64(&self, position: usize, names: &[&str]) -> Result<f64, RegistryError> {
+        let value = self
+            .raw(position, names)
+            .ok_or_else(|| RegistryError::MissingArgument {
+                agent: self.agent.clone(),
+                argument: names[0].to_string(),
+            })?;
+        match value.parse::<f64>() {
+            Ok(number) if number.is_finite() => Ok(number),
+            _ => Err(RegistryError::InvalidArgument {
+                agent: self.agent.clone(),
+                argument: names[0].to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+
+    pub fn f64_or(
+        &self,
+        position: usize,
+        names: &[&str],
+        default: f64,
+    ) -> Result<f64, RegistryError> {
+        match self.raw(position, names) {
+            Some(_) => self.f64(position, names),
+            None => Ok(default),
+        }
+    }
+
+    pub fn f64_in(
+        &self,
+        position: usize,
+        names: &[&str],
+        range: impl RangeBounds<f64>,
+    ) -> Result<f64, RegistryError> {
+        let value = self.f64(position, names)?;
+        self.check(names, value, range)
+    }
+
+    pub fn f64_in_or(
+        &self,
+        position: usize,
+        names: &[&str],
+        range: impl RangeBounds<f64>,
+        default: f64,
+    ) -> Result<f64, RegistryError> {
+        match self.raw(position, names) {
+            Some(_) => self.f64_in(position, names, range),
+            None => Ok(default),
+        }
+    }
+
+    // A count, length or number of turns, which has to be written as an
+    // integer.
+    pub fn usize(&self, position: usize, names: &[&str]) -> Result<usize, RegistryError> {
+        let value = self
+            .raw(position, names)
+            .ok_or_else(|| RegistryError::MissingArgument {
+                agent: self.agent.clone(),
+                argument: names[0].to_string(),
+            })?;
+        value.parse().map_err(|_| RegistryError::InvalidArgument {
+            agent: self.agent.clone(),
+            argument: names[0].to_string(),
+            value: value.to_string(),
+        })
+    }
+
+    pub fn usize_or(
+        &self,
+        position: usize,
+        names: &[&str],
+        default: usize,
+    ) -> Result<usize, RegistryError> {
+        match self.raw(position, names) {
+            Some(_) => self.usize(position, names),
+            None => Ok(default),
+        }
+    }
+
+    pub fn usize_in(
+        &self,
+        position: usize,
+        names: &[&str],
+        range: impl RangeBounds<usize>,
+    ) -> Result<usize, RegistryError> {
+        let value = self.usize(position, names)?;
+        self.check(names, value, range)
+    }
+
+    pub fn usize_in_or(
+        &self,
+        position: usize,
+        names: &[&str],
+        range: impl RangeBounds<usize>,
+        default: usize,
+    ) -> Result<usize, RegistryError> {
+        match self.raw(position, names) {
+            Some(_) => self.usize_in(position, names, range),
+            None => Ok(default),
+        }
+    }
+
+    fn check<T: PartialOrd + fmt::Display>(
+        &self,
+        names: &[&str],
+        value: T,
+        range: impl RangeBounds<T>,
+    ) -> Result<T, RegistryError> {
+        check_range(&self.agent, names[0], value, range)
+    }
+
+    // An estimator named by `estimator` (full, window or decay) or implied by
+    // a `window` or `decay` argument, e.g. `estimator=decay, decay=0.9` or
+    // just `window=50`.
+    pub fn estimator(&self, position: usize) -> Result<Estimator, RegistryError> {
+        let invalid = |value: &str| RegistryError::InvalidArgument {
+            agent: self.agent.clone(),
+            argument: String::from("estimator"),
+            value: value.to_string(),
+        };
+        let name = match self.raw(position, &["estimator"]) {
+            Some(name) => name,
+            None if self.named.contains_key("window") => "window",
+            None if self.named.contains_key("decay") => "decay",
+            None => "full",
+        };
+        match name {
+            "full" => Ok(Estimator::Full),
+            "window" => Ok(Estimator::Window(self.usize_in(
+                usize::MAX,
+                &["window"],
+                1..,
+            )?)),
+            "decay" => Ok(Estimator::Decay(self.f64_in(
+                usize::MAX,
+                &["decay"],
+                RATE,
+            )?)),
+            _ => Err(invalid(name)),
+        }
+    }
+
+    pub fn bool_or(
+        &self,
+        position: usize,
+        names: &[&str],
+        default: bool,
+    ) -> Result<bool, RegistryError> {
+        match self.raw(position, names) {
+            None => Ok(default),
+            Some(value) => value.parse().map_err(|_| RegistryError::InvalidArgument {
+                agent: self.agent.clone(),
+                argument: names[0].to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+
+    pub fn action_or(
+        &self,
+        position: usize,
+        names: &[&str],
+        default: Action,
+    ) -> Result<Action, RegistryError> {
+        match self.raw(position, names) {
+            None => Ok(default),
+            Some(value) => match value.to_ascii_uppercase().as_str() {
+                "ATTACK" => Ok(Action::ATTACK),
+                "FINCH" => Ok(Action::FINCH),
+                _ => Err(RegistryError::InvalidArgument {
+                    agent: self.agent.clone(),
+                    argument: names[0].to_string(),
+                    value: value.to_string(),
+                }),
+            },
+        }
+    }
+}
+
+pub type AgentConstructor =
+    Box<dyn Fn(&AgentArgs, &SharedRng) -> Result<Box<dyn GameAgent>, RegistryError>>;
+
+pub struct AgentRegistry {
+    constructors: BTreeMap<String, AgentConstructor>,
+}
+
+impl AgentRegistry {
+    pub fn empty() -> Self {
+        Self {
+            constructors: BTreeMap::new(),
+        }
+    }
+
+    // A registry knowing every agent shipped with the crate.
+    pub fn with_builtin_agents() -> Self {
+        let mut registry = Self::empty();
+        registry.register("attack", |_, _| Ok(Box::new(AttackAgent)));
+        registry.register("mirror", |_, _| Ok(Box::new(MirrorAgent)));
+        registry.register("tit_for_tat", |args, _| {
+            Ok(Box::new(TitForTatAgent::new(args.action_or(
+                0,
+                &["initial"],
+                Action::FINCH,
+            )?)))
+        });
+        registry.register("grim_trigger", |_, _| Ok(Box::new(GrimTriggerAgent::new())));
+        registry.register("human", |_, _| Ok(Box::new(HumanAgent::new())));
+        registry.register("pavlov", |args, _| {
+            Ok(Box::new(PavlovAgent::new(args.action_or(
+                0,
+                &["initial"],
+                Action::FINCH,
+            )?)))
+        });
+        registry.register("random", |args, rng| {
+            Ok(Box::new(RandomAgent::new(
+                rng.clone(),
+                args.f64_in(0, &["p", "probability_of_attack"], PROBABILITY)?,
+            )))
+        });
+        // The rule is taken verbatim, so it may contain `=` but no commas
+        registry.register("rule", |args, rng| {
+            Ok(Box::new(
+                RuleAgent::new(rng.clone(), &args.text)
+                    .map_err(|_| RegistryError::InvalidArgument {
+                        agent: args.agent.clone(),
+                        argument: String::from("rule"),
+                        value: args.text.clone(),
+                    })?
+                    .with_damage(args.damage.clone()),
+            ))
+        });
+        registry.register("markov", |args, rng| {
+            Ok(Box::new(MarkovRandomAgent::new(
+                rng.clone(),
+                args.f64_in(0, &["to_attack", "change_to_attack_prob"], PROBABILITY)?,
+                args.f64_in(1, &["to_finch", "change_to_finch_prob"], PROBABILITY)?,
+                args.action_or(2, &["initial", "initial_strategy"], Action::ATTACK)?,
+            )))
+        });
+        registry.register("one_step", |args, _| {
+            let estimator = args.estimator(3)?;
+            Ok(Box::new(
+                OneStepDecisionProcessAgent::new(
+                    args.f64_or(0, &["losing", "cost_losing_hp"], -3.0)?,
+                    args.f64_or(1, &["not_losing", "cost_not_losing_hp"], -1.0)?,
+                    args.f64_or(2, &["exchange", "cost_equivalent_exchange"], -3.0)?,
+                )
+                .with_estimator(estimator)
+                .with_prior(
+                    args.f64_in_or(usize::MAX, &["prior_attacks"], NON_NEGATIVE, 0.0)?,
+                    args.f64_in_or(usize::MAX, &["prior_finches"], NON_NEGATIVE, 0.0)?,
+                )
+                .with_burn_in(
+                    args.usize_or(usize::MAX, &["burn_in"], 0)?,
+                    args.action_or(usize::MAX, &["burn_in_action"], Action::ATTACK)?,
+                ),
+            ))
+        });
+        registry.register("predictor", |args, _| {
+            Ok(Box::new(MarkovPredictorAgent::new(
+                args.usize_or(0, &["order"], 2)?,
+                args.f64_or(1, &["losing", "cost_losing_hp"], -3.0)?,
+                args.f64_or(2, &["not_losing", "cost_not_losing_hp"], -1.0)?,
+                args.f64_or(3, &["exchange", "cost_equivalent_exchange"], -3.0)?,
+            )))
+        });
+        registry.register("pattern", |args, _| {
+            Ok(Box::new(PatternMatchingAgent::new(
+                args.usize_or(0, &["max_length", "length"], 8)?,
+                args.f64_or(1, &["losing", "cost_losing_hp"], -3.0)?,
+                args.f64_or(2, &["not_losing", "cost_not_losing_hp"], -1.0)?,
+                args.f64_or(3, &["exchange", "cost_equivalent_exchange"], -3.0)?,
+            )))
+        });
+        registry.register("hmm", |args, _| {
+            Ok(Box::new(HmmAgent::new(
+                args.usize_in_or(0, &["refit_interval", "refit"], 1.., 10)?,
+                args.usize_or(1, &["iterations"], 20)?,
+                args.f64_or(2, &["losing", "cost_losing_hp"], -3.0)?,
+                args.f64_or(3, &["not_losing", "cost_not_losing_hp"], -1.0)?,
+                args.f64_or(4, &["exchange", "cost_equivalent_exchange"], -3.0)?,
+            )))
+        });
+        registry.register("particle", |args, rng| {
+            Ok(Box::new(ParticleFilterAgent::new(
+                rng.clone(),
+                args.usize_in_or(0, &["particles", "num_particles"], 1.., 500)?,
+                args.f64_in_or(1, &["accuracy"], PROBABILITY, 0.95)?,
+                args.f64_in_or(2, &["jitter"], PROBABILITY, 0.02)?,
+                args.f64_or(3, &["losing", "cost_losing_hp"], -3.0)?,
+                args.f64_or(4, &["not_losing", "cost_not_losing_hp"], -1.0)?,
+                args.f64_or(5, &["exchange", "cost_equivalent_exchange"], -3.0)?,
+            )))
+        });
+        #[cfg(feature = "neural")]
+        registry.register("neural", |args, rng| {
+            let path = args
+                .raw(0, &["weights"])
+                .ok_or_else(|| RegistryError::MissingArgument {
+                    agent: args.agent.clone(),
+                    argument: String::from("weights"),
+                })?;
+            let policy = Policy::load(
+                path,
+                args.usize_in_or(1, &["hidden"], 1.., 16)?,
+                args.usize_or(2, &["history"], 3)?,
+            )
+            .map_err(|_| RegistryError::InvalidArgument {
+                agent: args.agent.clone(),
+                argument: String::from("weights"),
+                value: path.to_string(),
+            })?;
+            Ok(Box::new(
+                NeuralAgent::new(
+                    rng.clone(),
+                    Rc::new(policy),
+                    args.bool_or(3, &["greedy"], false)?,
+                )
+                .with_damage(args.damage.clone()),
+            ))
+        });
+        #[cfg(feature = "onnx")]
+        registry.register("onnx", |args, rng| {
+            let path = args
+                .raw(0, &["model"])
+                .ok_or_else(|| RegistryError::MissingArgument {
+                    agent: args.agent.clone(),
+                    argument: String::from("model"),
+                })?;
+            let agent = OnnxAgent::load(
+                rng.clone(),
+                path,
+                args.usize_or(1, &["history"], 3)?,
+                args.bool_or(2, &["greedy"], false)?,
+            )
+            .map_err(|_| RegistryError::InvalidArgument {
+                agent: args.agent.clone(),
+                argument: String::from("model"),
+                value: path.to_string(),
+            })?;
+            Ok(Box::new(agent.with_damage(args.damage.clone())))
+        });
+        registry.register("external", |args, _| {
+            let command =
+                args.raw(0, &["command"])
+                    .ok_or_else(|| RegistryError::MissingArgument {
+                        agent: args.agent.clone(),
+                        argument: String::from("command"),
+                    })?;
+            Ok(Box::new(ExternalAgent::new(
+                command.split_whitespace().map(String::from).collect(),
+            )))
+        });
+        #[cfg(feature = "lua")]
+        registry.register("lua", |args, rng| {
+            let path = args
+                .raw(0, &["script"])
+                .ok_or_else(|| RegistryError::MissingArgument {
+                    agent: args.agent.clone(),
+                    argument: String::from("script"),
+                })?;
+            let agent =
+                LuaAgent::load(rng.clone(), path).map_err(|_| RegistryError::InvalidArgument {
+                    agent: args.agent.clone(),
+                    argument: String::from("script"),
+                    value: path.to_string(),
+                })?;
+            Ok(Box::new(agent))
+        });
+        #[cfg(feature = "grpc")]
+        registry.register("grpc", |args, _| {
+            let endpoint =
+                args.raw(0, &["endpoint"])
+                    .ok_or_else(|| RegistryError::MissingArgument {
+                        agent: args.agent.clone(),
+                        argument: String::from("endpoint"),
+                    })?;
+            let agent =
+                GrpcAgent::connect(endpoint).map_err(|_| RegistryError::InvalidArgument {
+                    agent: args.agent.clone(),
+                    argument: String::from("endpoint"),
+                    value: endpoint.to_string(),
+                })?;
+            Ok(Box::new(agent))
+        });
+        registry.register("hedge", |args, _| {
+            Ok(Box::new(HedgeAgent::new(
+                args.f64_in_or(0, &["learning_rate", "eta"], NON_NEGATIVE, 0.5)?,
+                args.usize_or(1, &["order"], 3)?,
+                args.f64_or(2, &["losing", "cost_losing_hp"], -3.0)?,
+                args.f64_or(3, &["not_losing", "cost_not_losing_hp"], -1.0)?,
+                args.f64_or(4, &["exchange", "cost_equivalent_exchange"], -3.0)?,
+            )))
+        });
+        registry.register("thompson", |args, rng| {
+            Ok(Box::new(ThompsonSamplingAgent::new(
+                rng.clone(),
+                args.f64_or(0, &["losing", "cost_losing_hp"], -3.0)?,
+                args.f64_or(1, &["not_losing", "cost_not_losing_hp"], -1.0)?,
+                args.f64_or(2, &["exchange", "cost_equivalent_exchange"], -3.0)?,
+                args.f64_in_or(3, &["prior_attacks"], POSITIVE, 1.0)?,
+                args.f64_in_or(4, &["prior_finches"], POSITIVE, 1.0)?,
+            )))
+        });
+        registry.register("exp3", |args, rng| {
+            Ok(Box::new(Exp3Agent::new(
+                rng.clone(),
+                Exp3Variant::Exp3,
+                args.f64_in_or(0, &["gamma", "exploration"], RATE, 0.05)?,
+                0.0,
+            )))
+        });
+        registry.register("exp3_ix", |args, rng| {
+            Ok(Box::new(Exp3Agent::new(
+                rng.clone(),
+                Exp3Variant::Exp3Ix,
+                args.f64_in_or(1, &["gamma", "exploration"], NON_NEGATIVE, 0.025)?,
+                args.f64_in_or(0, &["eta", "learning_rate"], NON_NEGATIVE, 0.05)?,
+            )))
+        });
+        // Solves the game's damage matrix, cfr(100, riposte=1) adds the riposte
+        // variant on top of it
+        registry.register("cfr", |args, rng| {
+            let counter_damage =
+                args.usize_in_or(1, &["riposte", "counter_damage"], 0..=i64::MAX as usize, 0)?
+                    as i64;
+            let variant = if counter_damage > 0 {
+                RuleVariant::Riposte { counter_damage }
+            } else {
+                RuleVariant::Standard
+            };
+            Ok(Box::new(
+                CfrAgent::new(rng.clone(), args.usize_in_or(0, &["iterations"], 1.., 100)?)
+                    .with_damage(variant.apply(&args.damage)),
+            ))
+        });
+        registry.register("dynamic", |args, _| {
+            let model = match args.raw(0, &["p", "probability_of_attack"]) {
+                Some(_) => AttackModel::Fixed(args.f64_in(
+                    0,
+                    &["p", "probability_of_attack"],
+                    PROBABILITY,
+                )?),
+                None => AttackModel::Estimated {
+                    prior_attacks: args.f64_in_or(1, &["prior_attacks"], POSITIVE, 1.0)?,
+                    prior_finches: args.f64_in_or(2, &["prior_finches"], POSITIVE, 1.0)?,
+                },
+            };
+            Ok(Box::new(
+                DynamicProgrammingAgent::new(
+                    model,
+                    args.f64_in_or(3, &["resolution"], RATE, 0.01)?,
+                )
+                .with_damage(args.damage.clone()),
+            ))
+        });
+        registry.register("expectimax", |args, _| {
+            let model = match args.raw(1, &["model"]) {
+                None => ModelKind::Frequency,
+                Some(name) => {
+                    ModelKind::parse(name).ok_or_else(|| RegistryError::InvalidArgument {
+                        agent: args.agent.clone(),
+                        argument: String::from("model"),
+                        value: name.to_string(),
+                    })?
+                }
+            };
+            Ok(Box::new(
+                ExpectimaxAgent::new(
+                    model.build(args.usize_or(2, &["order"], 2)?),
+                    args.usize_or(0, &["depth"], 3)?,
+                )
+                .with_damage(args.damage.clone()),
+            ))
+        });
+        for (name, rule) in [
+            ("q_learning", TdRule::QLearning),
+            ("sarsa", TdRule::Sarsa),
+            ("expected_sarsa", TdRule::ExpectedSarsa),
+        ] {
+            registry.register(name, move |args, rng| {
+                let mut agent = TdAgent::new(
+                    rng.clone(),
+                    rule,
+                    args.f64_in_or(0, &["alpha", "learning_rate"], RATE, 0.1)?,
+                    args.f64_in_or(1, &["gamma", "discount"], PROBABILITY, 0.9)?,
+                    args.f64_in_or(2, &["epsilon", "exploration"], PROBABILITY, 0.1)?,
+                );
+                let replay = args.usize_or(usize::MAX, &["replay"], 0)?;
+                if replay > 0 {
+                    let capacity = args.usize_in_or(usize::MAX, &["replay_capacity"], 1.., 1000)?;
+                    agent = agent.with_replay(capacity, replay);
+                }
+                Ok(Box::new(match args.raw(3, &["table"]) {
+                    Some(path) => {
+                        let checkpoint =
+                            Checkpoint::load(path).map_err(|_| RegistryError::InvalidArgument {
+                                agent: args.agent.clone(),
+                                argument: String::from("table"),
+                                value: path.to_string(),
+                            })?;
+                        agent.with_table(Rc::new(RefCell::new(checkpoint.table())))
+                    }
+                    None => agent,
+                }))
+            });
+        }
+        registry
+    }
+
+    pub fn register<F>(&mut self, name: &str, constructor: F)
+    where
+        F: Fn(&AgentArgs, &SharedRng) -> Result<Box<dyn GameAgent>, RegistryError> + 'static,
+    {
+        self.constructors
+            .insert(name.to_string(), Box::new(constructor));
+    }
+
+    // Forgets every agent not named in `names`, e.g. before building agents
+    // from specifications sent by untrusted clients.
+    pub fn retain(&mut self, names: &[&str]) {
+        self.constructors
+            .retain(|name, _| names.contains(&name.as_str()));
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.constructors.keys().map(String::as_str)
+    }
+
+    pub fn build(&self, spec: &str, rng: &SharedRng) -> Result<Box<dyn GameAgent>, RegistryError> {
+        self.build_with_damage(spec, rng, &DamageMatrix::default())
+    }
+
+    // Builds an agent for a game played by `damage` rather than the standard
+    // damage matrix, which agents that plan ahead or recount hidden hit
+    // points depend on.
+    pub fn build_with_damage(
+        &self,
+        spec: &str,
+        rng: &SharedRng,
+        damage: &DamageMatrix,
+    ) -> Result<Box<dyn GameAgent>, RegistryError> {
+        let mut args = AgentArgs::parse(spec)?;
+        args.damage = damage.clone();
+        let constructor = self
+            .constructors
+            .get(&args.agent)
+            .ok_or_else(|| RegistryError::UnknownAgent(args.agent.clone()))?;
+        constructor(&args, rng)
+    }
+}
+
+impl Default for AgentRegistry {
+    fn default() -> Self {
+        Self::with_builtin_agents()
+    }
+}
@@ -0,0 +1,352 @@
+// Any stage game played over and over, for folk-theorem experiments. After
+// every round the game continues with probability `continuation`, so that
+// the number of rounds is geometric, up to `max_rounds`; payoffs of round
+// `t` (from 0) are weighed by `discount` to the power of `t`. Either one
+// models the players' patience: with continuation `p` and no discount, the
+// expected total payoff equals the discounted payoff of the endless game
+// with a discount of `p`.
+//
+// Every round resolves the actions by the stage game's rules, starting from
+// the players' current states, and a round ending the stage game, like a
+// knockout in a duel, ends the repeated game as well. The stage game's own
+// rounds are ignored. Players keep their states, and stage payoffs are the
+// changes of their scores.
+
+use std::cell::Cell;
+
+use rand::Rng;
+
+use crate::agents::{DecisionExplanation, GameAgent, SharedRng};
+use crate::game::{AgentMode, GameOutcome, GameState, SimultaneousGame, TurnOrder};
+use crate::history::HistoryView;
+use crate::observer::{GameObserver, Scored};
+
+pub struct Repeated<G: SimultaneousGame> {
+    pub stage: G,
+    pub continuation: f64,
+    pub discount: f64,
+    pub max_rounds: usize,
+    // Draws whether the game continues, needed for a continuation below 1.
+    pub current_random: Option<SharedRng>,
+    // The number of rounds of the current game, drawn when it starts.
+    horizon: Cell<usize>,
+}
+
+impl<G: SimultaneousGame + Clone> Clone for Repeated<G> {
+    fn clone(&self) -> Self {
+        Self {
+            stage: self.stage.clone(),
+            continuation: self.continuation,
+            discount: self.discount,
+            max_rounds: self.max_rounds,
+            current_random: self.current_random.clone(),
+            horizon: self.horizon.clone(),
+        }
+    }
+}
+
+impl<G: SimultaneousGame> Repeated<G> {
+    // Repeats the stage game exactly `max_rounds` times, undiscounted.
+    pub fn new(stage: G, max_rounds: usize) -> Self {
+        Self {
+            stage,
+            continuation: 1.0,
+            discount: 1.0,
+            max_rounds,
+            current_random: None,
+            horizon: Cell::new(max_rounds),
+        }
+    }
+
+    pub fn with_continuation(mut self, continuation: f64, rng: SharedRng) -> Self {
+        self.continuation = continuation;
+        self.current_random = Some(rng);
+        self
+    }
+
+    pub fn with_discount(mut self, discount: f64) -> Self {
+        self.discount = discount;
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.continuation > 0.0 && self.continuation <= 1.0) {
+            return Err(format!(
+                "the continuation probability has to lie in (0, 1], got {}",
+                self.continuation
+            ));
+        }
+        if !(self.discount > 0.0 && self.discount <= 1.0) {
+            return Err(format!(
+                "the discount has to lie in (0, 1], got {}",
+                self.discount
+            ));
+        }
+        if self.max_rounds == 0 {
+            return Err(String::from("a repeated game needs at least one round"));
+        }
+        Ok(())
+    }
+
+    // The expected number of rounds, the mean of the truncated geometric
+    // distribution.
+    pub fn expected_rounds(&self) -> f64 {
+        if self.continuation >= 1.0 {
+            return self.max_rounds as f64;
+        }
+        (1.0 - self.continuation.powi(self.max_rounds as i32)) / (1.0 - self.continuation)
+    }
+
+    // Rounds played until the first stop, at most `max_rounds`.
+    fn draw_horizon(&self) -> usize {
+        let Some(rng) = self
+            .current_random
+            .as_ref()
+            .filter(|_| self.continuation < 1.0)
+        else {
+            return self.max_rounds;
+        };
+        let mut rng = rng.borrow_mut();
+        let mut rounds = 1;
+        while rounds < self.max_rounds && rng.random_bool(self.continuation) {
+            rounds += 1;
+        }
+        rounds
+    }
+}
+
+impl<G: SimultaneousGame> Repeated<G>
+where
+    G::PlayerState: Scored,
+{
+    // Both players' discounted payoffs of the rounds played so far.
+    pub fn discounted_payoffs(&self, state: &GameState<Self>) -> [f64; 2] {
+        let initial = self.initial_scores();
+        [
+            discounted_payoff(self.discount, initial[0], &state.history.player_one_states),
+            discounted_payoff(self.discount, initial[1], &state.history.player_two_states),
+        ]
+    }
+
+    // Both players' scores before the first round.
+    pub fn initial_scores(&self) -> [f64; 2] {
+        let initial = self.stage.initial_state();
+        [
+            initial.player_one_state.score(),
+            initial.player_two_state.score(),
+        ]
+    }
+
+    // A fresh stage game state holding the players' current states.
+    fn stage_state(&self, state: &GameState<Self>) -> GameState<G> {
+        GameState::new(
+            state.player_one_state.clone(),
+            state.player_two_state.clone(),
+        )
+    }
+}
+
+// The discounted sum of the score changes from `initial` over `states`, one
+// per round.
+fn discounted_payoff<S: Scored>(discount: f64, initial: f64, states: &[S]) -> f64 {
+    let mut previous = initial;
+    let mut weight = 1.0;
+    let mut total = 0.0;
+    for state in states {
+        total += weight * (state.score() - previous);
+        previous = state.score();
+        weight *= discount;
+    }
+    total
+}
+
+impl<G: SimultaneousGame> SimultaneousGame for Repeated<G>
+where
+    G::PlayerState: Scored,
+{
+    type PlayerState = G::PlayerState;
+    type Action = G::Action;
+
+    fn initial_state(&self) -> GameState<Self> {
+        self.horizon.set(self.draw_horizon());
+        let stage = self.stage.initial_state();
+        GameState::new(stage.player_one_state, stage.player_two_state)
+    }
+
+    fn resolve_actions(
+        &self,
+        state: &mut GameState<Self>,
+        player_one_action: G::Action,
+        player_two_action: G::Action,
+    ) {
+        let mut stage = self.stage_state(state);
+        self.stage
+            .resolve_actions(&mut stage, player_one_action, player_two_action);
+        state.player_one_state = stage.player_one_state;
+        state.player_two_state = stage.player_two_state;
+    }
+
+    fn check_end_condition(&self, state: &GameState<Self>) -> GameOutcome {
+        let outcome = self.stage.check_end_condition(&self.stage_state(state));
+        if outcome != GameOutcome::CONTINUE || state.history.turns() < self.horizon.get() {
+            return outcome;
+        }
+        let [one, two] = self.discounted_payoffs(state);
+        match one.total_cmp(&two) {
+            std::cmp::Ordering::Greater => GameOutcome::WIN(1),
+            std::cmp::Ordering::Less => GameOutcome::WIN(2),
+            std::cmp::Ordering::Equal => GameOutcome::TIE,
+        }
+    }
+
+    fn enforce_legality(
+        &self,
+        state: &GameState<Self>,
+        player: u64,
+        action: G::Action,
+    ) -> G::Action {
+        self.stage
+            .enforce_legality(&self.stage_state(state), player, action)
+    }
+
+    fn turn_order(&self, state: &GameState<Self>) -> TurnOrder<G::Action> {
+        self.stage.turn_order(&self.stage_state(state))
+    }
+
+    fn observe_action(&self, observer: u64, action: &G::Action) -> G::Action {
+        self.stage.observe_action(observer, action)
+    }
+
+    fn reseed(&self, seed: u64) {
+        self.stage.reseed(seed);
+    }
+
+    fn bucket_state(state: &G::PlayerState, buckets: usize) -> G::PlayerState {
+        G::bucket_state(state, buckets)
+    }
+}
+
+// Plays the repeated game with an agent of its stage game, which sees the
+// same states, actions and history.
+pub struct StageAgent<G: SimultaneousGame> {
+    pub agent: Box<dyn GameAgent<G>>,
+}
+
+impl<G: SimultaneousGame> StageAgent<G> {
+    pub fn new(agent: Box<dyn GameAgent<G>>) -> Self {
+        Self { agent }
+    }
+}
+
+impl<G: SimultaneousGame + 'static> GameAgent<Repeated<G>> for StageAgent<G>
+where
+    G::PlayerState: Scored,
+{
+    fn decide_action(
+        &mut self,
+        own_player_state: &G::PlayerState,
+        opposing_player_actions: &Option<G::Action>,
+        opposing_player_state: &Option<G::PlayerState>,
+        history: &HistoryView<Repeated<G>>,
+    ) -> G::Action {
+        self.agent.decide_action(
+            own_player_state,
+            opposing_player_actions,
+            opposing_player_state,
+            &HistoryView {
+                own_actions: history.own_actions,
+                opposing_actions: history.opposing_actions,
+                own_states: history.own_states,
+                opposing_states: history.opposing_states,
+            },
+        )
+    }
+
+    fn strategy_name(&self) -> String {
+        self.agent.strategy_name()
+    }
+
+    fn set_mode(&mut self, mode: AgentMode) {
+        self.agent.set_mode(mode);
+    }
+
+    fn explain_last_decision(&self) -> Option<DecisionExplanation> {
+        self.agent.explain_last_decision()
+    }
+
+    fn belief_snapshot(&self) -> Vec<(String, f64)> {
+        self.agent.belief_snapshot()
+    }
+
+    fn copy_self_to_anom(&self) -> Box<dyn GameAgent<Repeated<G>>> {
+        Box::new(Self::new(self.agent.copy_self_to_anom()))
+    }
+}
+
+// Every agent's discounted payoffs over the repeated games it played.
+pub struct DiscountedPayoffObserver {
+    current_pairing: Option<(usize, usize)>,
+    discount: f64,
+    initial_scores: [f64; 2],
+    pub total_payoffs: Vec<f64>,
+    pub games: Vec<u64>,
+    pub rounds: Vec<u64>,
+}
+
+impl DiscountedPayoffObserver {
+    pub fn new<G: SimultaneousGame>(rules: &Repeated<G>, num_agents: usize) -> Self
+    where
+        G::PlayerState: Scored,
+    {
+        Self {
+            current_pairing: None,
+            discount: rules.discount,
+            initial_scores: rules.initial_scores(),
+            total_payoffs: vec![0.0; num_agents],
+            games: vec![0; num_agents],
+            rounds: vec![0; num_agents],
+        }
+    }
+
+    // The agent's average discounted payoff per game.
+    pub fn average_payoff(&self, agent: usize) -> f64 {
+        self.total_payoffs[agent] / self.games[agent].max(1) as f64
+    }
+
+    pub fn average_rounds(&self, agent: usize) -> f64 {
+        self.rounds[agent] as f64 / self.games[agent].max(1) as f64
+    }
+}
+
+impl<G: SimultaneousGame> GameObserver<Repeated<G>> for DiscountedPayoffObserver
+where
+    G::PlayerState: Scored,
+{
+    fn on_game_start(&mut self, player_one: usize, player_two: usize) {
+        self.current_pairing = Some((player_one, player_two));
+    }
+
+    fn on_game_end(&mut self, state: &GameState<Repeated<G>>, _outcome: &GameOutcome) {
+        let Some((player_one, player_two)) = self.current_pairing else {
+            return;
+        };
+        let history = &state.history;
+        let rounds = history.turns() as u64;
+        for (agent, initial, states) in [
+            (
+                player_one,
+                self.initial_scores[0],
+                &history.player_one_states,
+            ),
+            (
+                player_two,
+                self.initial_scores[1],
+                &history.player_two_states,
+            ),
+        ] {
+            self.total_payoffs[agent] += discounted_payoff(self.discount, initial, states);
+            self.games[agent] += 1;
+            self.rounds[agent] += rounds;
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::duel::{Action, Duel};
+use crate::game::{Game, GameOutcome, GameSettings};
+use crate::history::History;
+use crate::observer::{GameObserver, NullObserver};
+use crate::registry::{AgentRegistry, RegistryError};
+use crate::rng_audit::AuditedRng;
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    Json {
+        line: usize,
+        error: serde_json::Error,
+    },
+    Format(String),
+    Agent(RegistryError),
+    Mismatch {
+        turn: usize,
+        expected: String,
+        found: String,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "could not access replay file: {}", err),
+            ReplayError::Json { line, error } => {
+                write!(f, "invalid replay record on line {}: {}", line, error)
+            }
+            ReplayError::Format(msg) => write!(f, "invalid replay file: {}", msg),
+            ReplayError::Agent(err) => write!(f, "could not rebuild agent: {}", err),
+            ReplayError::Mismatch {
+                turn,
+                expected,
+                found,
+            } => write!(
+                f,
+                "replay diverges at turn {}: recorded {}, replayed {}",
+                turn, expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(err: std::io::Error) -> Self {
+        ReplayError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayAgent {
+    // Registry specification the agent was built from.
+    pub spec: String,
+    pub strategy_name: String,
+}
+
+// Everything needed to play the recorded game again.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayHeader {
+    pub seed: u64,
+    pub max_hit_points: i64,
+    #[serde(default)]
+    pub settings: GameSettings,
+    pub player_one: ReplayAgent,
+    pub player_two: ReplayAgent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayTurn {
+    pub turn: usize,
+    pub player_one_action: Action,
+    pub player_two_action: Action,
+    pub player_one_hit_points: i64,
+    pub player_two_hit_points: i64,
+}
+
+// One line of a replay file.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+enum ReplayRecord {
+    Header(ReplayHeader),
+    Turn(ReplayTurn),
+    End { outcome: GameOutcome },
+}
+
+pub struct Replay {
+    pub header: ReplayHeader,
+    pub turns: Vec<ReplayTurn>,
+    pub outcome: GameOutcome,
+}
+
+impl Replay {
+    pub fn from_history(
+        header: ReplayHeader,
+        history: &History<Duel>,
+        outcome: GameOutcome,
+    ) -> Self {
+        let turns = (0..history.turns())
+            .map(|turn| ReplayTurn {
+                turn,
+                player_one_action: history.player_one_actions[turn].clone(),
+                player_two_action: history.player_two_actions[turn].clone(),
+                player_one_hit_points: history.player_one_states[turn].current_hit_points,
+                player_two_hit_points: history.player_two_states[turn].current_hit_points,
+            })
+            .collect();
+        Self {
+            header,
+            turns,
+            outcome,
+        }
+    }
+
+    // Builds both agents from their specifications and plays a full duel.
+    pub fn record(
+        seed: u64,
+        max_hit_points: i64,
+        settings: GameSettings,
+        player_one_spec: &str,
+        player_two_spec: &str,
+        registry: &AgentRegistry,
+    ) -> Result<Self, ReplayError> {
+        Self::record_observed(
+            seed,
+            max_hit_points,
+            settings,
+            player_one_spec,
+            player_two_spec,
+            registry,
+            &mut NullObserver,
+        )
+    }
+
+    // Like `record`, reporting the game to `observer` while it is played.
+    pub fn record_observed(
+        seed: u64,
+        max_hit_points: i64,
+        settings: GameSettings,
+        player_one_spec: &str,
+        player_two_spec: &str,
+        registry: &AgentRegistry,
+        observer: &mut dyn GameObserver<Duel>,
+    ) -> Result<Self, ReplayError> {
+        let rng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(seed)));
+        let player_one_agent = registry
+            .build(player_one_spec, &rng)
+            .map_err(ReplayError::Agent)?;
+        let player_two_agent = registry
+            .build(player_two_spec, &rng)
+            .map_err(ReplayError::Agent)?;
+
+        let header = ReplayHeader {
+            seed,
+            max_hit_points,
+            settings: settings.clone(),
+            player_one: ReplayAgent {
+                spec: player_one_spec.to_string(),
+                strategy_name: player_one_agent.strategy_name(),
+            },
+            player_two: ReplayAgent {
+                spec: player_two_spec.to_string(),
+                strategy_name: player_two_agent.strategy_name(),
+            },
+        };
+
+        let mut game = Game::new(
+            Duel::new(max_hit_points),
+            player_one_agent,
+            player_two_agent,
+        )
+        .with_settings(settings);
+        let mut state = game.initial_state();
+        let outcome = game.play_observed(&mut state, observer);
+        Ok(Self::from_history(header, &state.history, outcome))
+    }
+
+    // Plays the game again from the header and checks it unfolds exactly as recorded.
+    pub fn verify(&self, registry: &AgentRegistry) -> Result<(), ReplayError> {
+        let replayed = Self::record(
+            self.header.seed,
+            self.header.max_hit_points,
+            self.header.settings.clone(),
+            &self.header.player_one.spec,
+            &self.header.player_two.spec,
+            registry,
+        )?;
+
+        for (turn, (recorded, replayed)) in self.turns.iter().zip(&replayed.turns).enumerate() {
+            if recorded != replayed {
+                return Err(ReplayError::Mismatch {
+                    turn,
+                    expected: format!("{:?}", recorded),
+                    found: format!("{:?}", replayed),
+                });
+            }
+        }
+        if self.turns.len() != replayed.turns.len() || self.outcome != replayed.outcome {
+            return Err(ReplayError::Mismatch {
+                turn: self.turns.len().min(replayed.turns.len()),
+                expected: format!("{:?} after {} turns", self.outcome, self.turns.len()),
+                found: format!(
+                    "{:?} after {} turns",
+                    replayed.outcome,
+                    replayed.turns.len()
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ReplayError> {
+        let mut output = BufWriter::new(File::create(path)?);
+        let mut write_record = |record: &ReplayRecord| -> Result<(), ReplayError> {
+            let line = serde_json::to_string(record)
+                .map_err(|error| ReplayError::Json { line: 0, error })?;
+            writeln!(output, "{}", line)?;
+            Ok(())
+        };
+
+        write_record(&ReplayRecord::Header(self.header.clone()))?;
+        for turn in &self.turns {
+            write_record(&ReplayRecord::Turn(turn.clone()))?;
+        }
+        write_record(&ReplayRecord::End {
+            outcome: self.outcome.clone(),
+        })?;
+        output.flush()?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        let input = BufReader::new(File::open(path)?);
+        let mut header = None;
+        let mut turns = Vec::new();
+        let mut outcome = None;
+
+        for (index, line) in input.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str(&line).map_err(|error| ReplayError::Json {
+                line: index + 1,
+                error,
+            })?;
+            match record {
+                ReplayRecord::Header(h) => header = Some(h),
+                ReplayRecord::Turn(turn) => turns.push(turn),
+                ReplayRecord::End { outcome: o } => outcome = Some(o),
+            }
+        }
+
+        Ok(Self {
+            header: header.ok_or_else(|| ReplayError::Format("missing header record".into()))?,
+            turns,
+            outcome: outcome.ok_or_else(|| ReplayError::Format("missing end record".into()))?,
+        })
+    }
+}
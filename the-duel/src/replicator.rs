@@ -0,0 +1,153 @@
+use rand::Rng;
+use serde::Deserialize;
+
+#[derive(Deserialize, Clone)]
+pub struct ReplicatorConfig {
+    // Euler steps taken from every starting point.
+    pub steps: usize,
+    #[serde(default = "default_step_size")]
+    pub step_size: f64,
+    // Starting points: the barycenter followed by random interior points.
+    #[serde(default = "default_starts")]
+    pub starts: usize,
+    // A trajectory whose last step moved less than this has come to rest.
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+}
+
+fn default_step_size() -> f64 {
+    0.1
+}
+
+fn default_starts() -> usize {
+    10
+}
+
+fn default_tolerance() -> f64 {
+    1e-9
+}
+
+// A rest point of the dynamics reached by at least one trajectory.
+pub struct FixedPoint {
+    pub shares: Vec<f64>,
+    // Indices of the trajectories that ended here.
+    pub starts: Vec<usize>,
+    // No strategy outside the support would do better than the population
+    // average, which makes the rest point a symmetric Nash equilibrium.
+    pub saturated: bool,
+}
+
+// Discrete replicator dynamics
+//   x_i <- x_i + h * x_i * ((A x)_i - x^T A x)
+// over the strategy simplex of the payoff matrix A.
+pub struct ReplicatorDynamics {
+    pub payoffs: Vec<Vec<f64>>,
+    pub step_size: f64,
+}
+
+impl ReplicatorDynamics {
+    pub fn new(payoffs: Vec<Vec<f64>>, step_size: f64) -> Self {
+        Self { payoffs, step_size }
+    }
+
+    pub fn num_strategies(&self) -> usize {
+        self.payoffs.len()
+    }
+
+    fn fitness(&self, shares: &[f64]) -> Vec<f64> {
+        self.payoffs
+            .iter()
+            .map(|row| row.iter().zip(shares).map(|(p, s)| p * s).sum())
+            .collect()
+    }
+
+    fn average_fitness(shares: &[f64], fitness: &[f64]) -> f64 {
+        shares.iter().zip(fitness).map(|(s, f)| s * f).sum()
+    }
+
+    pub fn step(&self, shares: &[f64]) -> Vec<f64> {
+        let fitness = self.fitness(shares);
+        let average = Self::average_fitness(shares, &fitness);
+        let mut next: Vec<f64> = shares
+            .iter()
+            .zip(&fitness)
+            .map(|(s, f)| (s + self.step_size * s * (f - average)).max(0.0))
+            .collect();
+        let total: f64 = next.iter().sum();
+        for share in next.iter_mut() {
+            *share /= total;
+        }
+        next
+    }
+
+    // Points along the trajectory from `start`, stopping early once it rests.
+    pub fn trajectory(&self, start: Vec<f64>, steps: usize, tolerance: f64) -> Vec<Vec<f64>> {
+        let mut trajectory = vec![start];
+        for _ in 0..steps {
+            let current = trajectory.last().unwrap();
+            let next = self.step(current);
+            let moved = distance(current, &next);
+            trajectory.push(next);
+            if moved < tolerance {
+                break;
+            }
+        }
+        trajectory
+    }
+
+    // The barycenter followed by `count - 1` points drawn uniformly from the
+    // interior of the simplex.
+    pub fn starting_points<R: Rng>(&self, count: usize, rng: &mut R) -> Vec<Vec<f64>> {
+        let n = self.num_strategies();
+        let mut points = vec![vec![1.0 / n as f64; n]];
+        while points.len() < count {
+            let weights: Vec<f64> = (0..n).map(|_| -(1.0 - rng.random::<f64>()).ln()).collect();
+            let total: f64 = weights.iter().sum();
+            points.push(weights.iter().map(|w| w / total).collect());
+        }
+        points.truncate(count);
+        points
+    }
+
+    // Groups the end points of resting trajectories that lie within
+    // `tolerance.sqrt()` of each other into fixed points.
+    pub fn fixed_points(&self, trajectories: &[Vec<Vec<f64>>], tolerance: f64) -> Vec<FixedPoint> {
+        let mut fixed_points: Vec<FixedPoint> = Vec::new();
+        for (start, trajectory) in trajectories.iter().enumerate() {
+            let end = trajectory.last().unwrap();
+            let rested = trajectory.len() < 2
+                || distance(&trajectory[trajectory.len() - 2], end) < tolerance;
+            if !rested {
+                continue;
+            }
+            match fixed_points
+                .iter_mut()
+                .find(|point| distance(&point.shares, end) < tolerance.sqrt())
+            {
+                Some(point) => point.starts.push(start),
+                None => {
+                    let fitness = self.fitness(end);
+                    let average = Self::average_fitness(end, &fitness);
+                    let saturated = end
+                        .iter()
+                        .zip(&fitness)
+                        .all(|(s, f)| *s > tolerance.sqrt() || *f <= average + tolerance.sqrt());
+                    fixed_points.push(FixedPoint {
+                        shares: end.clone(),
+                        starts: vec![start],
+                        saturated,
+                    });
+                }
+            }
+        }
+        fixed_points
+    }
+}
+
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
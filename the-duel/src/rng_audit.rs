@@ -0,0 +1,168 @@
+// An audit of the random numbers drawn, for diagnosing nondeterminism such as
+// results depending on the order of the roster. All generators of the engine
+// are `AuditedRng`s; while an audit runs on a thread, every number they draw
+// there is recorded with who asked for it, a player's agent or the rules,
+// and the turn, as attributed by the game being played. Without an audit a
+// draw costs one check of a thread local more than a plain `ChaCha12Rng`.
+
+use std::cell::{Cell, RefCell};
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Draw {
+    // Position of the draw among all recorded.
+    pub index: usize,
+    // Who the draw is attributed to, e.g. "player 1 (<strategy>)" or "rules"
+    // within the pairing and retrial of a tournament, empty outside of games.
+    pub requester: String,
+    pub turn: Option<usize>,
+    // The stream of the generator, see `seeded_stream`.
+    pub stream: u64,
+    // "u32", "u64" or the number of bytes filled.
+    pub kind: String,
+    // The number drawn, 0 for filled bytes.
+    pub value: u64,
+}
+
+struct Audit {
+    requester: String,
+    turn: Option<usize>,
+    draws: Vec<Draw>,
+}
+
+thread_local! {
+    static RUNNING: Cell<bool> = const { Cell::new(false) };
+    static AUDIT: RefCell<Option<Audit>> = const { RefCell::new(None) };
+}
+
+// Starts recording the draws of this thread, dropping those of an audit
+// still running.
+pub fn start() {
+    RUNNING.set(true);
+    AUDIT.with(|audit| {
+        *audit.borrow_mut() = Some(Audit {
+            requester: String::new(),
+            turn: None,
+            draws: Vec::new(),
+        })
+    });
+}
+
+// Stops recording and returns the draws recorded since `start`.
+pub fn finish() -> Vec<Draw> {
+    RUNNING.set(false);
+    AUDIT
+        .with(|audit| audit.borrow_mut().take())
+        .map(|audit| audit.draws)
+        .unwrap_or_default()
+}
+
+#[inline]
+pub fn is_running() -> bool {
+    RUNNING.get()
+}
+
+// Attributes the draws to `requester`, within the requester attributed so
+// far, and to `turn` if given until the returned guard is dropped. The
+// requester is only named while an audit runs.
+pub fn attribute(requester: impl FnOnce() -> String, turn: Option<usize>) -> Attribution {
+    if !is_running() {
+        return Attribution { outer: None };
+    }
+    let outer = AUDIT.with(|audit| {
+        audit.borrow_mut().as_mut().map(|audit| {
+            let inner = match audit.requester.as_str() {
+                "" => requester(),
+                outer => format!("{} / {}", outer, requester()),
+            };
+            let turn = turn.or(audit.turn);
+            (
+                std::mem::replace(&mut audit.requester, inner),
+                std::mem::replace(&mut audit.turn, turn),
+            )
+        })
+    });
+    Attribution { outer }
+}
+
+// Restores the attribution it replaced when dropped.
+pub struct Attribution {
+    outer: Option<(String, Option<usize>)>,
+}
+
+impl Drop for Attribution {
+    fn drop(&mut self) {
+        if let Some(outer) = self.outer.take() {
+            AUDIT.with(|audit| {
+                if let Some(audit) = audit.borrow_mut().as_mut() {
+                    (audit.requester, audit.turn) = outer;
+                }
+            });
+        }
+    }
+}
+
+fn record(stream: u64, kind: impl FnOnce() -> String, value: u64) {
+    if !is_running() {
+        return;
+    }
+    AUDIT.with(|audit| {
+        if let Some(audit) = audit.borrow_mut().as_mut() {
+            audit.draws.push(Draw {
+                index: audit.draws.len(),
+                requester: audit.requester.clone(),
+                turn: audit.turn,
+                stream,
+                kind: kind(),
+                value,
+            });
+        }
+    });
+}
+
+// A `ChaCha12Rng` whose draws are recorded while an audit runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditedRng(pub ChaCha12Rng);
+
+impl From<ChaCha12Rng> for AuditedRng {
+    fn from(rng: ChaCha12Rng) -> Self {
+        Self(rng)
+    }
+}
+
+impl RngCore for AuditedRng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        let value = self.0.next_u32();
+        record(self.0.get_stream(), || "u32".to_string(), value as u64);
+        value
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        let value = self.0.next_u64();
+        record(self.0.get_stream(), || "u64".to_string(), value);
+        value
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.0.fill_bytes(dst);
+        record(self.0.get_stream(), || format!("{} bytes", dst.len()), 0);
+    }
+}
+
+impl SeedableRng for AuditedRng {
+    type Seed = <ChaCha12Rng as SeedableRng>::Seed;
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self(ChaCha12Rng::from_seed(seed))
+    }
+
+    fn seed_from_u64(state: u64) -> Self {
+        Self(ChaCha12Rng::seed_from_u64(state))
+    }
+}
@@ -0,0 +1,246 @@
+// Rock-paper-scissors, played for a fixed number of rounds: rock beats
+// scissors, paper beats rock and scissors beat paper. The winner of a round
+// scores the payoff of its winning hand and the loser loses as much, so that
+// the game stays zero-sum; equal hands score nothing. With unequal payoffs
+// the game is biased, and its unique equilibrium is no longer uniform.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{GameOutcome, GameSettings, GameState, SimultaneousGame};
+use crate::observer::Scored;
+use crate::openspiel::SpielGame;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Hand {
+    ROCK,
+    PAPER,
+    SCISSORS,
+}
+
+impl Hand {
+    pub const ALL: [Hand; 3] = [Hand::ROCK, Hand::PAPER, Hand::SCISSORS];
+
+    // Position of the hand in `ALL`.
+    pub fn index(&self) -> usize {
+        match self {
+            Hand::ROCK => 0,
+            Hand::PAPER => 1,
+            Hand::SCISSORS => 2,
+        }
+    }
+
+    // The hand this one beats.
+    pub fn beats(&self) -> Hand {
+        match self {
+            Hand::ROCK => Hand::SCISSORS,
+            Hand::PAPER => Hand::ROCK,
+            Hand::SCISSORS => Hand::PAPER,
+        }
+    }
+
+    // The hand beating this one.
+    pub fn beaten_by(&self) -> Hand {
+        match self {
+            Hand::ROCK => Hand::PAPER,
+            Hand::PAPER => Hand::SCISSORS,
+            Hand::SCISSORS => Hand::ROCK,
+        }
+    }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// What winning with every hand pays, 1 each in the standard game.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HandPayoffs {
+    pub rock: i64,
+    pub paper: i64,
+    pub scissors: i64,
+}
+
+impl Default for HandPayoffs {
+    fn default() -> Self {
+        Self {
+            rock: 1,
+            paper: 1,
+            scissors: 1,
+        }
+    }
+}
+
+impl HandPayoffs {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rock <= 0 || self.paper <= 0 || self.scissors <= 0 {
+            return Err(format!(
+                "winning has to pay, got rock {}, paper {} and scissors {}",
+                self.rock, self.paper, self.scissors
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn winning(&self, hand: &Hand) -> i64 {
+        match hand {
+            Hand::ROCK => self.rock,
+            Hand::PAPER => self.paper,
+            Hand::SCISSORS => self.scissors,
+        }
+    }
+
+    pub fn payoff(&self, own: &Hand, opposing: &Hand) -> i64 {
+        if own.beats() == *opposing {
+            self.winning(own)
+        } else if opposing.beats() == *own {
+            -self.winning(opposing)
+        } else {
+            0
+        }
+    }
+
+    // Expected payoff of playing `own` against hands drawn from
+    // `distribution`, in the order of `Hand::ALL`.
+    pub fn expected_payoff(&self, own: &Hand, distribution: &[f64; 3]) -> f64 {
+        Hand::ALL
+            .iter()
+            .map(|opposing| distribution[opposing.index()] * self.payoff(own, opposing) as f64)
+            .sum()
+    }
+
+    // The hand with the highest expected payoff against `distribution`.
+    pub fn best_response(&self, distribution: &[f64; 3]) -> Hand {
+        Hand::ALL
+            .into_iter()
+            .max_by(|a, b| {
+                self.expected_payoff(a, distribution)
+                    .total_cmp(&self.expected_payoff(b, distribution))
+            })
+            .unwrap()
+    }
+
+    // The equilibrium mix, in the order of `Hand::ALL`. Every hand is
+    // played in proportion to what the hand it beats pays when winning, so
+    // that no hand does better than another against the mix.
+    pub fn equilibrium(&self) -> [f64; 3] {
+        let total = (self.rock + self.paper + self.scissors) as f64;
+        [
+            self.scissors as f64 / total,
+            self.rock as f64 / total,
+            self.paper as f64 / total,
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RpsPlayer {
+    pub score: i64,
+}
+
+impl Scored for RpsPlayer {
+    fn score(&self) -> f64 {
+        self.score as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RockPaperScissors {
+    pub payoffs: HandPayoffs,
+    pub rounds: usize,
+}
+
+impl Default for RockPaperScissors {
+    fn default() -> Self {
+        Self {
+            payoffs: HandPayoffs::default(),
+            rounds: 100,
+        }
+    }
+}
+
+impl RockPaperScissors {
+    pub fn new(payoffs: HandPayoffs, rounds: usize) -> Self {
+        Self { payoffs, rounds }
+    }
+}
+
+impl SimultaneousGame for RockPaperScissors {
+    type PlayerState = RpsPlayer;
+    type Action = Hand;
+
+    fn initial_state(&self) -> GameState<Self> {
+        GameState::new(RpsPlayer::default(), RpsPlayer::default())
+    }
+
+    fn resolve_actions(
+        &self,
+        state: &mut GameState<Self>,
+        player_one_action: Hand,
+        player_two_action: Hand,
+    ) {
+        let payoff = self.payoffs.payoff(&player_one_action, &player_two_action);
+        state.player_one_state.score += payoff;
+        state.player_two_state.score -= payoff;
+    }
+
+    fn check_end_condition(&self, state: &GameState<Self>) -> GameOutcome {
+        if state.history.turns() < self.rounds {
+            return GameOutcome::CONTINUE;
+        }
+        let (one, two) = (state.player_one_state.score, state.player_two_state.score);
+        match one.cmp(&two) {
+            std::cmp::Ordering::Greater => GameOutcome::WIN(1),
+            std::cmp::Ordering::Less => GameOutcome::WIN(2),
+            std::cmp::Ordering::Equal => GameOutcome::TIE,
+        }
+    }
+}
+
+// Hands are numbered as in `Hand::ALL`. The observation holds the fraction
+// of rounds played, followed by the opponent's last hand one-hot encoded.
+impl SpielGame for RockPaperScissors {
+    fn num_distinct_actions(&self) -> usize {
+        Hand::ALL.len()
+    }
+
+    fn action_id(&self, action: &Hand) -> usize {
+        action.index()
+    }
+
+    fn action_from_id(&self, id: usize) -> Option<Hand> {
+        Hand::ALL.get(id).cloned()
+    }
+
+    fn action_to_string(&self, id: usize) -> String {
+        match self.action_from_id(id) {
+            Some(action) => action.to_string(),
+            None => format!("invalid action {}", id),
+        }
+    }
+
+    fn observation_tensor_size(&self) -> usize {
+        1 + Hand::ALL.len()
+    }
+
+    fn observation_tensor(
+        &self,
+        state: &GameState<Self>,
+        _settings: &GameSettings,
+        player: u64,
+    ) -> Vec<f32> {
+        let opposing_action = state.observed_opposing_action(player);
+        let mut tensor = vec![state.history.turns() as f32 / self.rounds.max(1) as f32];
+        tensor.extend(
+            Hand::ALL
+                .iter()
+                .map(|action| (opposing_action.as_ref() == Some(action)) as u8 as f32),
+        );
+        tensor
+    }
+}
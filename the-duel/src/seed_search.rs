@@ -0,0 +1,150 @@
+// Scans seeds for duels with a given outcome, e.g. a seed on which the
+// mirror agent beats the attack agent or one lasting over a thousand turns,
+// for illustrative examples and regression fixtures. Every game found is a
+// replay, so that it can be saved and played again exactly.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::game::{GameOutcome, GameSettings};
+use crate::registry::AgentRegistry;
+use crate::replay::{Replay, ReplayError};
+
+#[derive(Debug)]
+pub struct ConditionError(pub String);
+
+impl fmt::Display for ConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "malformed condition '{}', expected winner=<1|2>, tie, draw, turns<n>, turns>n, margin<n> or margin>n",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ConditionError {}
+
+// A condition on the outcome of a game.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameCondition {
+    Winner(u64),
+    Tie,
+    Draw,
+    TurnsAbove(usize),
+    TurnsBelow(usize),
+    // The difference of the players' hit points at the end.
+    MarginAbove(i64),
+    MarginBelow(i64),
+}
+
+impl GameCondition {
+    // Parses `winner=1`, `tie`, `draw`, `turns>1000` or `margin<3`.
+    pub fn parse(condition: &str) -> Result<Self, ConditionError> {
+        let malformed = || ConditionError(condition.to_string());
+        let condition = condition.trim();
+        match condition {
+            "tie" => return Ok(GameCondition::Tie),
+            "draw" => return Ok(GameCondition::Draw),
+            _ => {}
+        }
+        if let Some(winner) = condition.strip_prefix("winner=") {
+            return match winner.trim() {
+                "1" => Ok(GameCondition::Winner(1)),
+                "2" => Ok(GameCondition::Winner(2)),
+                _ => Err(malformed()),
+            };
+        }
+        let (name, above, bound) = match condition.split_once(['<', '>']) {
+            Some((name, bound)) => (name.trim(), condition.contains('>'), bound.trim()),
+            None => return Err(malformed()),
+        };
+        match (name, above) {
+            ("turns", true) => bound.parse().map(GameCondition::TurnsAbove),
+            ("turns", false) => bound.parse().map(GameCondition::TurnsBelow),
+            ("margin", true) => bound.parse().map(GameCondition::MarginAbove),
+            ("margin", false) => bound.parse().map(GameCondition::MarginBelow),
+            _ => return Err(malformed()),
+        }
+        .map_err(|_| malformed())
+    }
+
+    pub fn holds(&self, replay: &Replay) -> bool {
+        let margin = replay.turns.last().map_or(0, |turn| {
+            (turn.player_one_hit_points - turn.player_two_hit_points).abs()
+        });
+        match self {
+            GameCondition::Winner(player) => matches!(
+                replay.outcome,
+                GameOutcome::WIN(winner) | GameOutcome::SURVIVOR(winner) if winner == *player
+            ),
+            GameCondition::Tie => replay.outcome == GameOutcome::TIE,
+            GameCondition::Draw => matches!(replay.outcome, GameOutcome::DRAW(_)),
+            GameCondition::TurnsAbove(turns) => replay.turns.len() > *turns,
+            GameCondition::TurnsBelow(turns) => replay.turns.len() < *turns,
+            GameCondition::MarginAbove(bound) => margin > *bound,
+            GameCondition::MarginBelow(bound) => margin < *bound,
+        }
+    }
+}
+
+pub struct SeedSearch<'a> {
+    pub registry: &'a AgentRegistry,
+    pub player_one_spec: String,
+    pub player_two_spec: String,
+    pub max_hit_points: i64,
+    pub settings: GameSettings,
+    // Every one has to hold.
+    pub conditions: Vec<GameCondition>,
+}
+
+impl<'a> SeedSearch<'a> {
+    pub fn new(
+        registry: &'a AgentRegistry,
+        player_one_spec: &str,
+        player_two_spec: &str,
+        max_hit_points: i64,
+        conditions: Vec<GameCondition>,
+    ) -> Self {
+        Self {
+            registry,
+            player_one_spec: player_one_spec.to_string(),
+            player_two_spec: player_two_spec.to_string(),
+            max_hit_points,
+            settings: GameSettings::default(),
+            conditions,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    // The games meeting every condition, in the order of their seeds, up to
+    // `limit` of them.
+    pub fn find(&self, seeds: Range<u64>, limit: usize) -> Result<Vec<Replay>, ReplayError> {
+        let mut found = Vec::new();
+        for seed in seeds {
+            if found.len() >= limit {
+                break;
+            }
+            let replay = Replay::record(
+                seed,
+                self.max_hit_points,
+                self.settings.clone(),
+                &self.player_one_spec,
+                &self.player_two_spec,
+                self.registry,
+            )?;
+            if self
+                .conditions
+                .iter()
+                .all(|condition| condition.holds(&replay))
+            {
+                found.push(replay);
+            }
+        }
+        Ok(found)
+    }
+}
@@ -0,0 +1,300 @@
+// Self-play training of temporal difference learners.
+//
+// The learner plays game after game against copies of itself, which share
+// its table and so learn from both seats, and against a league. The league
+// holds frozen past versions of the learner, taken every few episodes, and
+// exploiters: learners of their own that only ever play the learner, so
+// that they find its weaknesses. When the learner is frozen, so are the
+// exploiters, which then start over. League opponents are drawn by priority,
+// the ones the learner scores worst against most often, so that the learner
+// does not overfit to a single adversary. Every few episodes a greedy copy
+// is evaluated against the roster for the learning curve, and the table is
+// checkpointed so that it can be loaded into a tournament afterwards.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agents::{DuelState, GameAgent, QTable, SharedRng, TdAgent, TdRule};
+use crate::config::{ConfigError, TdConfig};
+use crate::duel::Duel;
+use crate::evolution::average_score;
+use crate::game::{AgentMode, Game, GameOutcome, GameSettings};
+use crate::moran::pick;
+
+#[derive(Deserialize, Clone)]
+pub struct SelfPlayConfig {
+    #[serde(default = "default_rule")]
+    pub rule: TdRule,
+    #[serde(flatten)]
+    pub learner: TdConfig,
+    pub episodes: usize,
+    // Every this many episodes, a frozen copy of the learner and of every
+    // exploiter joins the league. Without it, exploiters never start over.
+    pub freeze_every: Option<usize>,
+    // Share of the episodes played against the league rather than copies.
+    #[serde(default = "default_frozen_share")]
+    pub frozen_share: f64,
+    // Frozen versions kept in the league, dropping the oldest.
+    #[serde(default = "default_pool_size")]
+    pub pool_size: usize,
+    #[serde(default)]
+    pub exploiters: usize,
+    // League opponents are drawn with weight (1 - score) ^ priority, the
+    // score being the learner's average against them; 0 draws uniformly.
+    #[serde(default)]
+    pub priority: f64,
+    // Every this many episodes, a greedy copy plays the roster.
+    #[serde(default = "default_evaluate_every")]
+    pub evaluate_every: usize,
+    // Games against every opponent, per seat, to evaluate the learner.
+    #[serde(default = "default_games")]
+    pub games: u64,
+    // JSON file receiving the learner's table every `checkpoint_every`
+    // episodes and after the last one. `{episode}` in the name is replaced by
+    // the episode, otherwise the file is overwritten.
+    pub checkpoint: Option<String>,
+    pub checkpoint_every: Option<usize>,
+}
+
+fn default_rule() -> TdRule {
+    TdRule::QLearning
+}
+
+fn default_frozen_share() -> f64 {
+    0.5
+}
+
+fn default_pool_size() -> usize {
+    1
+}
+
+fn default_evaluate_every() -> usize {
+    100
+}
+
+fn default_games() -> u64 {
+    5
+}
+
+// A learner's table as saved between runs.
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub rule: TdRule,
+    pub episode: usize,
+    pub table: Vec<(DuelState, [f64; 2])>,
+}
+
+impl Checkpoint {
+    pub fn of(rule: TdRule, episode: usize, table: &QTable) -> Self {
+        Self {
+            rule,
+            episode,
+            table: table
+                .iter()
+                .map(|(state, values)| (*state, *values))
+                .collect(),
+        }
+    }
+
+    pub fn table(&self) -> QTable {
+        self.table.iter().copied().collect()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+        serde_json::from_str(&contents).map_err(|err| ConfigError::Invalid(err.to_string()))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ConfigError> {
+        let contents =
+            serde_json::to_string(self).map_err(|err| ConfigError::Invalid(err.to_string()))?;
+        fs::write(path, contents).map_err(ConfigError::Io)
+    }
+}
+
+// Average score of a greedy copy of the learner against the roster.
+pub struct CurvePoint {
+    pub episode: usize,
+    pub score: f64,
+    // States in the learner's table.
+    pub states: usize,
+    // Frozen versions and exploiters in the league.
+    pub league: usize,
+}
+
+// An opponent in the league and the learner's results against it.
+struct Member {
+    agent: TdAgent,
+    score: f64,
+    games: u64,
+}
+
+impl Member {
+    fn new(agent: TdAgent) -> Self {
+        Self {
+            agent,
+            score: 0.0,
+            games: 0,
+        }
+    }
+
+    // Average score of the learner, starting out from a tie.
+    fn average(&self) -> f64 {
+        (self.score + 0.5) / (self.games + 1) as f64
+    }
+}
+
+// Score of the learner in the first seat.
+fn score(outcome: &GameOutcome) -> f64 {
+    match outcome {
+        GameOutcome::WIN(1) => 1.0,
+        GameOutcome::WIN(_) => 0.0,
+        _ => 0.5,
+    }
+}
+
+pub struct SelfPlay {
+    pub rules: Duel,
+    pub settings: GameSettings,
+    pub opponents: Vec<Box<dyn GameAgent>>,
+    pub config: SelfPlayConfig,
+}
+
+impl SelfPlay {
+    pub fn new(rules: Duel, opponents: Vec<Box<dyn GameAgent>>, config: SelfPlayConfig) -> Self {
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            opponents,
+            config,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    fn evaluate(&self, learner: &TdAgent) -> f64 {
+        let greedy = learner.frozen();
+        let total: f64 = self
+            .opponents
+            .iter()
+            .map(|opponent| {
+                average_score(
+                    &self.rules,
+                    &GameSettings {
+                        mode: AgentMode::Eval,
+                        ..self.settings.clone()
+                    },
+                    &greedy,
+                    opponent.as_ref(),
+                    self.config.games,
+                )
+            })
+            .sum();
+        total / self.opponents.len().max(1) as f64
+    }
+
+    fn checkpoint(&self, learner: &TdAgent, episode: usize) -> Result<(), ConfigError> {
+        if let Some(path) = &self.config.checkpoint {
+            let path = path.replace("{episode}", &episode.to_string());
+            Checkpoint::of(self.config.rule, episode, &learner.table.borrow()).save(path)?;
+        }
+        Ok(())
+    }
+
+    fn fresh_learner(&self, rng: &SharedRng) -> TdAgent {
+        self.config.learner.learner(rng, self.config.rule)
+    }
+
+    // Starts from the learner's `table` checkpoint if one is configured.
+    pub fn run(&self, rng: &SharedRng) -> Result<Vec<CurvePoint>, ConfigError> {
+        let table = match &self.config.learner.table {
+            Some(path) => Checkpoint::load(path)?.table(),
+            None => QTable::new(),
+        };
+        let learner = self
+            .fresh_learner(rng)
+            .with_table(Rc::new(RefCell::new(table)));
+        let mut pool: VecDeque<Member> = VecDeque::new();
+        if self.config.freeze_every.is_some() {
+            pool.push_back(Member::new(learner.frozen()));
+        }
+        let mut exploiters: Vec<Member> = (0..self.config.exploiters)
+            .map(|_| Member::new(self.fresh_learner(rng)))
+            .collect();
+
+        let mut curve = Vec::new();
+        let evaluate_every = self.config.evaluate_every.max(1);
+        for episode in 1..=self.config.episodes {
+            let league = pool.len() + exploiters.len();
+            let drawn = if league > 0 && rng.borrow_mut().random_bool(self.config.frozen_share) {
+                let weights: Vec<f64> = pool
+                    .iter()
+                    .chain(&exploiters)
+                    .map(|member| (1.0 - member.average()).powf(self.config.priority))
+                    .collect();
+                Some(pick(&weights, &mut *rng.borrow_mut()))
+            } else {
+                None
+            };
+            let member = drawn.map(|index| {
+                if index < pool.len() {
+                    &mut pool[index]
+                } else {
+                    &mut exploiters[index - pool.len()]
+                }
+            });
+            let opponent = match &member {
+                Some(member) => member.agent.copy_self_to_anom(),
+                None => learner.copy_self_to_anom(),
+            };
+            let outcome = Game::new(self.rules.clone(), learner.copy_self_to_anom(), opponent)
+                .with_settings(GameSettings {
+                    mode: AgentMode::Train,
+                    ..self.settings.clone()
+                })
+                .play();
+            if let Some(member) = member {
+                member.score += score(&outcome);
+                member.games += 1;
+            }
+
+            if let Some(every) = self.config.freeze_every
+                && episode.is_multiple_of(every.max(1))
+            {
+                pool.push_back(Member::new(learner.frozen()));
+                for exploiter in exploiters.iter_mut() {
+                    pool.push_back(Member::new(exploiter.agent.frozen()));
+                    *exploiter = Member::new(self.fresh_learner(rng));
+                }
+                while pool.len() > self.config.pool_size {
+                    pool.pop_front();
+                }
+            }
+            if episode.is_multiple_of(evaluate_every) || episode == self.config.episodes {
+                curve.push(CurvePoint {
+                    episode,
+                    score: self.evaluate(&learner),
+                    states: learner.table.borrow().len(),
+                    league: pool.len() + exploiters.len(),
+                });
+            }
+            let checkpoint_due = self
+                .config
+                .checkpoint_every
+                .is_some_and(|every| episode.is_multiple_of(every.max(1)));
+            if checkpoint_due || episode == self.config.episodes {
+                self.checkpoint(&learner, episode)?;
+            }
+        }
+        Ok(curve)
+    }
+}
@@ -0,0 +1,494 @@
+// Duels as an HTTP API, so that programs in any language can drive the
+// engine. Every seat of a game is played either by an agent from the
+// registry or by the client, which submits the seat's actions. A turn is
+// resolved once every client seat has submitted its action; games between
+// two agents are played to the end right away. Bodies are JSON:
+//
+//   POST   /games                {"player_one": null, "player_two": "markov(0.3, 0.6)",
+//                                 "max_hit_points": 10, "seed": 1}
+//   GET    /games
+//   GET    /games/{id}
+//   POST   /games/{id}/actions   {"player": 1, "action": "ATTACK"}
+//   GET    /games/{id}/result
+//   DELETE /games/{id}
+//
+// Requests are handled one at a time. Clients may only ask for the agents in
+// `SERVED_AGENTS`, with arguments within `ARGUMENT_LIMITS`, and for at most
+// `MAX_HIT_POINTS` hit points; an agent failing during a request ends its
+// game with an error instead of the server. The server needs the `server`
+// feature.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::agents::GameAgent;
+use crate::duel::{Action, Duel};
+use crate::game::{GameOutcome, GameSettings, GameState, SimultaneousGame};
+use crate::registry::{AgentArgs, AgentRegistry, RegistryError};
+use crate::replay::{Replay, ReplayAgent, ReplayHeader, ReplayTurn};
+use crate::rng_audit::AuditedRng;
+
+// The agents clients may ask for. None of them runs other programs, reads
+// files, connects elsewhere or waits for input on the terminal. cfr and
+// dynamic are left out, they solve a table of every pair of hit points.
+pub const SERVED_AGENTS: &[&str] = &[
+    "attack",
+    "mirror",
+    "tit_for_tat",
+    "grim_trigger",
+    "pavlov",
+    "random",
+    "rule",
+    "markov",
+    "one_step",
+    "predictor",
+    "pattern",
+    "hmm",
+    "particle",
+    "hedge",
+    "thompson",
+    "exp3",
+    "exp3_ix",
+    "expectimax",
+];
+
+// The largest values served of the arguments the time or memory an agent
+// takes per turn grows with, by agent, position and names of the argument.
+pub const ARGUMENT_LIMITS: &[(&str, usize, &[&str], usize)] = &[
+    ("expectimax", 0, &["depth"], 6),
+    ("expectimax", 2, &["order"], 16),
+    ("particle", 0, &["particles", "num_particles"], 10_000),
+    ("hmm", 1, &["iterations"], 50),
+    ("predictor", 0, &["order"], 16),
+    ("pattern", 0, &["max_length", "length"], 16),
+    ("hedge", 1, &["order"], 16),
+];
+
+// Every turn costs a hit point, so this also bounds the length of a game.
+pub const MAX_HIT_POINTS: i64 = 1_000;
+
+// Whether `spec` stays within `ARGUMENT_LIMITS`.
+fn check_limits(spec: &str) -> Result<(), RegistryError> {
+    let args = AgentArgs::parse(spec)?;
+    for (agent, position, names, max) in ARGUMENT_LIMITS {
+        if args.agent == *agent && args.raw(*position, names).is_some() {
+            args.usize_in(*position, names, 0..=*max)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+pub struct NewGame {
+    // Registry specifications; a seat without one is played by the client.
+    pub player_one: Option<String>,
+    pub player_two: Option<String>,
+    #[serde(default = "default_max_hit_points")]
+    pub max_hit_points: i64,
+    // Seeds the agents' random numbers, the game's id if omitted.
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub settings: GameSettings,
+}
+
+fn default_max_hit_points() -> i64 {
+    600
+}
+
+#[derive(Deserialize)]
+pub struct SubmittedAction {
+    // 1 or 2.
+    pub player: u64,
+    pub action: Action,
+}
+
+enum Seat {
+    Agent(Box<dyn GameAgent>),
+    // The action the client submitted for the next turn.
+    Client(Option<Action>),
+}
+
+#[derive(Serialize)]
+pub struct SeatView {
+    pub strategy_name: String,
+    pub client: bool,
+    pub hit_points: i64,
+    pub last_action: Option<Action>,
+}
+
+#[derive(Serialize)]
+pub struct GameView {
+    pub id: u64,
+    pub turn: usize,
+    pub max_hit_points: i64,
+    pub player_one: SeatView,
+    pub player_two: SeatView,
+    pub outcome: GameOutcome,
+    // Client seats whose action for the next turn is still missing.
+    pub waiting_for: Vec<u64>,
+}
+
+// A finished game in the form of a replay.
+#[derive(Serialize)]
+pub struct GameResult {
+    pub header: ReplayHeader,
+    pub turns: Vec<ReplayTurn>,
+    pub outcome: GameOutcome,
+}
+
+pub struct ServerGame {
+    pub id: u64,
+    pub header: ReplayHeader,
+    rules: Duel,
+    settings: GameSettings,
+    state: GameState<Duel>,
+    seats: [Seat; 2],
+    outcome: GameOutcome,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound,
+    BadRequest(String),
+    Conflict(String),
+    // An agent failed, which ended its game.
+    Internal(String),
+}
+
+impl ApiError {
+    fn status(&self) -> u16 {
+        match self {
+            ApiError::NotFound => 404,
+            ApiError::BadRequest(_) => 400,
+            ApiError::Conflict(_) => 409,
+            ApiError::Internal(_) => 500,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ApiError::NotFound => String::from("not found"),
+            ApiError::BadRequest(msg) | ApiError::Conflict(msg) | ApiError::Internal(msg) => {
+                msg.clone()
+            }
+        }
+    }
+}
+
+impl ServerGame {
+    pub fn new(id: u64, request: NewGame, registry: &AgentRegistry) -> Result<Self, ApiError> {
+        if !(1..=MAX_HIT_POINTS).contains(&request.max_hit_points) {
+            return Err(ApiError::BadRequest(format!(
+                "max_hit_points has to be between 1 and {}",
+                MAX_HIT_POINTS
+            )));
+        }
+        request
+            .settings
+            .observability
+            .validate()
+            .map_err(ApiError::BadRequest)?;
+        let seed = request.seed.unwrap_or(id);
+        let rng = Rc::new(RefCell::new(AuditedRng::seed_from_u64(seed)));
+        let seat = |spec: &Option<String>| -> Result<(Seat, ReplayAgent), ApiError> {
+            match spec {
+                Some(spec) => {
+                    check_limits(spec).map_err(|err| ApiError::BadRequest(err.to_string()))?;
+                    let mut agent =
+                        panic::catch_unwind(AssertUnwindSafe(|| registry.build(spec, &rng)))
+                            .map_err(|_| ApiError::Internal(format!("agent '{}' failed", spec)))?
+                            .map_err(|err| ApiError::BadRequest(err.to_string()))?;
+                    agent.set_mode(request.settings.mode);
+                    let strategy_name = agent.strategy_name();
+                    Ok((
+                        Seat::Agent(agent),
+                        ReplayAgent {
+                            spec: spec.clone(),
+                            strategy_name,
+                        },
+                    ))
+                }
+                None => Ok((
+                    Seat::Client(None),
+                    ReplayAgent {
+                        spec: String::from("client"),
+                        strategy_name: String::from("Client of the HTTP API"),
+                    },
+                )),
+            }
+        };
+        let (one, player_one) = seat(&request.player_one)?;
+        let (two, player_two) = seat(&request.player_two)?;
+        let rules = Duel::new(request.max_hit_points);
+        let mut game = Self {
+            id,
+            header: ReplayHeader {
+                seed,
+                max_hit_points: request.max_hit_points,
+                settings: request.settings.clone(),
+                player_one,
+                player_two,
+            },
+            state: rules.initial_state(),
+            rules,
+            settings: request.settings,
+            seats: [one, two],
+            outcome: GameOutcome::CONTINUE,
+        };
+        game.resolve()?;
+        Ok(game)
+    }
+
+    pub fn waiting_for(&self) -> Vec<u64> {
+        if self.outcome != GameOutcome::CONTINUE {
+            return Vec::new();
+        }
+        (1..=2)
+            .filter(|player| matches!(self.seats[*player as usize - 1], Seat::Client(None)))
+            .collect()
+    }
+
+    pub fn submit(&mut self, submitted: SubmittedAction) -> Result<(), ApiError> {
+        if self.outcome != GameOutcome::CONTINUE {
+            return Err(ApiError::Conflict(String::from("the game is over")));
+        }
+        match self
+            .seats
+            .get_mut((submitted.player as usize).wrapping_sub(1))
+        {
+            Some(Seat::Client(pending @ None)) => *pending = Some(submitted.action),
+            Some(Seat::Client(Some(_))) => {
+                return Err(ApiError::Conflict(format!(
+                    "player {} already acted this turn",
+                    submitted.player
+                )));
+            }
+            Some(Seat::Agent(_)) => {
+                return Err(ApiError::BadRequest(format!(
+                    "player {} is played by an agent",
+                    submitted.player
+                )));
+            }
+            None => {
+                return Err(ApiError::BadRequest(format!(
+                    "there is no player {}",
+                    submitted.player
+                )));
+            }
+        }
+        self.resolve()
+    }
+
+    // Plays turns for as long as no client action is missing. A panicking
+    // agent leaves the game in no state to go on.
+    fn resolve(&mut self) -> Result<(), ApiError> {
+        while self.outcome == GameOutcome::CONTINUE && self.waiting_for().is_empty() {
+            panic::catch_unwind(AssertUnwindSafe(|| self.play_turn())).map_err(|_| {
+                ApiError::Internal(format!(
+                    "an agent failed in turn {} of game {}",
+                    self.state.history.turns() + 1,
+                    self.id
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn play_turn(&mut self) {
+        let state = &self.state;
+        let mut actions = Vec::with_capacity(2);
+        for (index, seat) in self.seats.iter_mut().enumerate() {
+            let player = index as u64 + 1;
+            let own_state = if player == 1 {
+                &state.player_one_state
+            } else {
+                &state.player_two_state
+            };
+            actions.push(match seat {
+                Seat::Agent(agent) => agent.decide_action(
+                    own_state,
+                    &state.observed_opposing_action(player),
+                    &self.settings.observe_opponent(state, player),
+                    &state.history.view(player),
+                ),
+                Seat::Client(pending) => pending.take().expect("no action is missing"),
+            });
+        }
+        let player_two_action = actions.pop().expect("two actions");
+        let player_one_action = actions.pop().expect("two actions");
+        self.state.advance(
+            &self.rules,
+            &self.settings,
+            player_one_action,
+            player_two_action,
+        );
+        self.outcome = self.settings.check_end_condition(&self.rules, &self.state);
+    }
+
+    pub fn view(&self) -> GameView {
+        let seat = |index: usize, agent: &ReplayAgent| SeatView {
+            strategy_name: agent.strategy_name.clone(),
+            client: matches!(self.seats[index], Seat::Client(_)),
+            hit_points: if index == 0 {
+                self.state.player_one_state.current_hit_points
+            } else {
+                self.state.player_two_state.current_hit_points
+            },
+            last_action: if index == 0 {
+                self.state.player_one_action.clone()
+            } else {
+                self.state.player_two_action.clone()
+            },
+        };
+        GameView {
+            id: self.id,
+            turn: self.state.history.turns(),
+            max_hit_points: self.header.max_hit_points,
+            player_one: seat(0, &self.header.player_one),
+            player_two: seat(1, &self.header.player_two),
+            outcome: self.outcome.clone(),
+            waiting_for: self.waiting_for(),
+        }
+    }
+
+    pub fn result(&self) -> Result<GameResult, ApiError> {
+        if self.outcome == GameOutcome::CONTINUE {
+            return Err(ApiError::Conflict(String::from(
+                "the game is still running",
+            )));
+        }
+        let replay = Replay::from_history(
+            self.header.clone(),
+            &self.state.history,
+            self.outcome.clone(),
+        );
+        Ok(GameResult {
+            header: replay.header,
+            turns: replay.turns,
+            outcome: replay.outcome,
+        })
+    }
+}
+
+// All games of a server, by id.
+pub struct GameTable {
+    pub registry: AgentRegistry,
+    games: BTreeMap<u64, ServerGame>,
+    next_id: u64,
+}
+
+fn json<T: Serialize>(value: &T) -> Result<String, ApiError> {
+    serde_json::to_string(value).map_err(|err| ApiError::BadRequest(err.to_string()))
+}
+
+fn parse<'a, T: Deserialize<'a>>(body: &'a str) -> Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|err| ApiError::BadRequest(err.to_string()))
+}
+
+impl GameTable {
+    // Serves the agents of `registry` that are in `SERVED_AGENTS`.
+    pub fn new(mut registry: AgentRegistry) -> Self {
+        registry.retain(SERVED_AGENTS);
+        Self {
+            registry,
+            games: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+
+    fn game(&mut self, id: &str) -> Result<&mut ServerGame, ApiError> {
+        let id: u64 = id.parse().map_err(|_| ApiError::NotFound)?;
+        self.games.get_mut(&id).ok_or(ApiError::NotFound)
+    }
+
+    // The status code and JSON body answering a request.
+    pub fn handle(&mut self, method: &Method, path: &str, body: &str) -> (u16, String) {
+        let segments: Vec<&str> = path
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let answer = match (method, segments.as_slice()) {
+            (Method::Post, ["games"]) => parse(body).and_then(|request| {
+                let game = ServerGame::new(self.next_id, request, &self.registry)?;
+                self.next_id += 1;
+                let view = json(&game.view())?;
+                self.games.insert(game.id, game);
+                Ok((201, view))
+            }),
+            (Method::Get, ["games"]) => json(
+                &self
+                    .games
+                    .values()
+                    .map(ServerGame::view)
+                    .collect::<Vec<_>>(),
+            )
+            .map(|view| (200, view)),
+            (Method::Get, ["games", id]) => self
+                .game(id)
+                .and_then(|game| json(&game.view()))
+                .map(|view| (200, view)),
+            (Method::Delete, ["games", id]) => self.game(id).map(|game| game.id).map(|id| {
+                self.games.remove(&id);
+                (204, String::new())
+            }),
+            (Method::Post, ["games", id, "actions"]) => parse(body).and_then(|submitted| {
+                let game = self.game(id)?;
+                if let Err(err) = game.submit(submitted) {
+                    if let ApiError::Internal(_) = err {
+                        let id = game.id;
+                        self.games.remove(&id);
+                    }
+                    return Err(err);
+                }
+                json(&game.view()).map(|view| (200, view))
+            }),
+            (Method::Get, ["games", id, "result"]) => self
+                .game(id)
+                .and_then(|game| json(&game.result()?))
+                .map(|result| (200, result)),
+            _ => Err(ApiError::NotFound),
+        };
+        answer.unwrap_or_else(|err| {
+            (
+                err.status(),
+                serde_json::json!({ "error": err.message() }).to_string(),
+            )
+        })
+    }
+}
+
+fn respond(mut request: Request, table: &mut GameTable) -> io::Result<()> {
+    let mut body = String::new();
+    request.as_reader().read_to_string(&mut body)?;
+    let (status, answer) = table.handle(request.method(), request.url(), &body);
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json").expect("a valid header");
+    request.respond(
+        Response::from_string(answer)
+            .with_status_code(status)
+            .with_header(content_type),
+    )
+}
+
+// Serves the API at `address` until the process is stopped.
+pub fn serve(address: &str, registry: AgentRegistry) -> io::Result<()> {
+    let server = Server::http(address).map_err(io::Error::other)?;
+    let mut table = GameTable::new(registry);
+    for request in server.incoming_requests() {
+        // A client hanging up is no reason to stop serving the others
+        if let Err(err) = respond(request, &mut table) {
+            eprintln!("could not answer a request: {}", err);
+        }
+    }
+    Ok(())
+}
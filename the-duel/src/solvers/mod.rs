@@ -0,0 +1,205 @@
+// Exact solvers of Markov decision processes given explicitly: every state
+// lists, for every action available in it, the probabilities of the next
+// states and the rewards of moving there. They serve as ground truth for the
+// planning agents, which solve their models of the game on the fly.
+//
+// The duel becomes such a process from one player's point of view once the
+// opponent is fixed: states are both players' hit points, and the
+// opponent's random action is part of the transitions, see `Mdp::duel`.
+
+pub mod policy_iteration;
+pub mod value_iteration;
+
+pub use policy_iteration::{modified_policy_iteration, policy_iteration};
+pub use value_iteration::{MdpSolution, value_iteration};
+
+use crate::duel::{Action, DamageMatrix};
+
+const PROBABILITY_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub probability: f64,
+    pub next: usize,
+    pub reward: f64,
+}
+
+// The action to take in every state, none in terminal states.
+pub type MdpPolicy = Vec<Option<usize>>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mdp {
+    pub states: Vec<String>,
+    pub actions: Vec<String>,
+    // For every state and action, the transitions of taking it, none if the
+    // action is not available. States without actions are terminal.
+    pub transitions: Vec<Vec<Vec<Transition>>>,
+    pub discount: f64,
+}
+
+impl Mdp {
+    // Checks that the transitions of every available action are a
+    // distribution over the states.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.discount > 0.0 && self.discount <= 1.0) {
+            return Err(format!(
+                "the discount has to lie in (0, 1], got {}",
+                self.discount
+            ));
+        }
+        if self.transitions.len() != self.states.len() {
+            return Err(format!(
+                "{} states need as many lists of transitions, got {}",
+                self.states.len(),
+                self.transitions.len()
+            ));
+        }
+        for (state, actions) in self.transitions.iter().enumerate() {
+            if actions.len() != self.actions.len() {
+                return Err(format!(
+                    "state '{}' needs transitions for all {} actions",
+                    self.states[state],
+                    self.actions.len()
+                ));
+            }
+            for (action, transitions) in actions.iter().enumerate() {
+                if transitions.is_empty() {
+                    continue;
+                }
+                let total: f64 = transitions.iter().map(|t| t.probability).sum();
+                if transitions
+                    .iter()
+                    .any(|t| t.probability < 0.0 || t.next >= self.states.len())
+                    || (total - 1.0).abs() > PROBABILITY_TOLERANCE
+                {
+                    return Err(format!(
+                        "the transitions of '{}' in state '{}' have to be a distribution over the states",
+                        self.actions[action], self.states[state]
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn num_states(&self) -> usize {
+        self.states.len()
+    }
+
+    // The actions available in `state`.
+    pub fn available_actions(&self, state: usize) -> impl Iterator<Item = usize> + '_ {
+        self.transitions[state]
+            .iter()
+            .enumerate()
+            .filter(|(_, transitions)| !transitions.is_empty())
+            .map(|(action, _)| action)
+    }
+
+    pub fn is_terminal(&self, state: usize) -> bool {
+        self.available_actions(state).next().is_none()
+    }
+
+    // The expected reward plus discounted value of taking `action` in
+    // `state`, given the values of all states.
+    pub fn q_value(&self, values: &[f64], state: usize, action: usize) -> f64 {
+        self.transitions[state][action]
+            .iter()
+            .map(|t| t.probability * (t.reward + self.discount * values[t.next]))
+            .sum()
+    }
+
+    // The best action in `state` and its value, the first of equals; none
+    // in terminal states, which are worth nothing.
+    pub fn best_action(&self, values: &[f64], state: usize) -> (Option<usize>, f64) {
+        self.available_actions(state)
+            .map(|action| (Some(action), self.q_value(values, state, action)))
+            .fold((None, 0.0), |best, (action, value)| match best {
+                (Some(_), best_value) if best_value >= value => best,
+                _ => (action, value),
+            })
+    }
+
+    // The policy taking the best action in every state.
+    pub fn greedy_policy(&self, values: &[f64]) -> MdpPolicy {
+        (0..self.num_states())
+            .map(|state| self.best_action(values, state).0)
+            .collect()
+    }
+
+    // The duel from player one's point of view against an opponent attacking
+    // with probability `probability_of_attack`, both starting with
+    // `max_hit_points`, see `duel_against`.
+    pub fn duel(max_hit_points: i64, damage: &DamageMatrix, probability_of_attack: f64) -> Self {
+        Self::duel_against(max_hit_points, damage, |_, _| probability_of_attack)
+    }
+
+    // The duel from player one's point of view against an opponent attacking
+    // with probability `attack(own, opposing)` when player one has `own` and
+    // the opponent `opposing` hit points. Non-terminal states are indexed by
+    // `duel_state`, followed by the terminal states of a loss, a tie and a
+    // win, which pay 0, 0.5 and 1 on entering: the value of a state is the
+    // chance of winning it, a tie counting as half a win. Hit points above
+    // the maximum after healing count as the maximum.
+    pub fn duel_against(
+        max_hit_points: i64,
+        damage: &DamageMatrix,
+        attack: impl Fn(i64, i64) -> f64,
+    ) -> Self {
+        let size = max_hit_points.max(0) as usize;
+        let (loss, tie, win) = (size * size, size * size + 1, size * size + 2);
+        let mut states: Vec<String> = (0..size * size)
+            .map(|state| format!("{}:{}", state / size + 1, state % size + 1))
+            .collect();
+        states.extend(["loss", "tie", "win"].map(String::from));
+        let mut transitions = vec![vec![Vec::new(); Action::ALL.len()]; states.len()];
+        for own in 1..=max_hit_points {
+            for opposing in 1..=max_hit_points {
+                let state = duel_state(max_hit_points, own, opposing);
+                let probability_of_attack = attack(own, opposing);
+                for action in Action::ALL {
+                    let mut outcomes: Vec<Transition> = Vec::new();
+                    for (opposing_action, probability) in [
+                        (Action::ATTACK, probability_of_attack),
+                        (Action::FINCH, 1.0 - probability_of_attack),
+                    ] {
+                        let [own_damage, opposing_damage] =
+                            damage.damage(&action, &opposing_action);
+                        let own = (own - own_damage).min(max_hit_points);
+                        let opposing = (opposing - opposing_damage).min(max_hit_points);
+                        let (next, reward) = match (own <= 0, opposing <= 0) {
+                            (true, true) => (tie, 0.5),
+                            (true, false) => (loss, 0.0),
+                            (false, true) => (win, 1.0),
+                            (false, false) => (duel_state(max_hit_points, own, opposing), 0.0),
+                        };
+                        match outcomes.iter_mut().find(|t| t.next == next) {
+                            Some(transition) => transition.probability += probability,
+                            None => outcomes.push(Transition {
+                                probability,
+                                next,
+                                reward,
+                            }),
+                        }
+                    }
+                    outcomes.retain(|t| t.probability > 0.0);
+                    transitions[state][action.index()] = outcomes;
+                }
+            }
+        }
+        Self {
+            states,
+            actions: Action::ALL
+                .iter()
+                .map(|action| format!("{:?}", action))
+                .collect(),
+            transitions,
+            discount: 1.0,
+        }
+    }
+}
+
+// The state of `Mdp::duel` in which player one has `own` and the opponent
+// `opposing` hit points, both at least 1.
+pub fn duel_state(max_hit_points: i64, own: i64, opposing: i64) -> usize {
+    ((own - 1) * max_hit_points + opposing - 1) as usize
+}
@@ -0,0 +1,121 @@
+// Policy iteration: evaluates the current policy, then switches every state
+// to the best action under the policy's values, until no state switches.
+// Starting from the first available action everywhere, it usually needs far
+// fewer iterations than value iteration, each costing a whole evaluation.
+//
+// Modified policy iteration evaluates every policy by only a fixed number of
+// sweeps, trading off between the two: one sweep makes it value iteration,
+// sweeping until the values settle makes it policy iteration. It stops once
+// the Bellman residual falls below the tolerance.
+//
+// Policies are evaluated by sweeping, rather than solving the linear system,
+// so that improper policies of undiscounted processes, which never end,
+// merely get very bad values within `MAX_EVALUATION_SWEEPS`.
+
+use crate::solvers::value_iteration::MdpSolution;
+use crate::solvers::{Mdp, MdpPolicy};
+
+const MAX_EVALUATION_SWEEPS: usize = 10_000;
+
+// Sweeps `values` towards those of `policy` in place, at most `max_sweeps`
+// times or until no value changes by more than `tolerance`. Returns the
+// number of sweeps.
+pub fn evaluate_policy(
+    mdp: &Mdp,
+    policy: &MdpPolicy,
+    values: &mut [f64],
+    tolerance: f64,
+    max_sweeps: usize,
+) -> usize {
+    let mut sweeps = 0;
+    while sweeps < max_sweeps {
+        let mut change: f64 = 0.0;
+        for state in 0..mdp.num_states() {
+            let value = policy[state].map_or(0.0, |action| mdp.q_value(values, state, action));
+            change = change.max((value - values[state]).abs());
+            values[state] = value;
+        }
+        sweeps += 1;
+        if change <= tolerance {
+            break;
+        }
+    }
+    sweeps
+}
+
+// The policy taking the best action under `values` in every state, keeping
+// the action of `policy` unless another one is strictly better, and the
+// Bellman residual of the values.
+fn improve(mdp: &Mdp, policy: &MdpPolicy, values: &[f64]) -> (MdpPolicy, f64) {
+    let mut residual: f64 = 0.0;
+    let improved = (0..mdp.num_states())
+        .map(|state| {
+            let (best, value) = mdp.best_action(values, state);
+            residual = residual.max((value - values[state]).abs());
+            match policy[state] {
+                Some(action) if mdp.q_value(values, state, action) >= value => Some(action),
+                _ => best,
+            }
+        })
+        .collect();
+    (improved, residual)
+}
+
+pub fn policy_iteration(mdp: &Mdp, tolerance: f64, max_iterations: usize) -> MdpSolution {
+    iterate_policies(mdp, None, tolerance, max_iterations)
+}
+
+pub fn modified_policy_iteration(
+    mdp: &Mdp,
+    evaluation_sweeps: usize,
+    tolerance: f64,
+    max_iterations: usize,
+) -> MdpSolution {
+    iterate_policies(
+        mdp,
+        Some(evaluation_sweeps.max(1)),
+        tolerance,
+        max_iterations,
+    )
+}
+
+// Evaluates every policy by `evaluation_sweeps`, or until the values settle
+// if none, and improves it.
+fn iterate_policies(
+    mdp: &Mdp,
+    evaluation_sweeps: Option<usize>,
+    tolerance: f64,
+    max_iterations: usize,
+) -> MdpSolution {
+    let mut policy: MdpPolicy = (0..mdp.num_states())
+        .map(|state| mdp.available_actions(state).next())
+        .collect();
+    let mut values = vec![0.0; mdp.num_states()];
+    let mut residuals = Vec::new();
+    let mut sweeps = 0;
+    while residuals.len() < max_iterations {
+        sweeps += evaluate_policy(
+            mdp,
+            &policy,
+            &mut values,
+            tolerance,
+            evaluation_sweeps.unwrap_or(MAX_EVALUATION_SWEEPS),
+        );
+        let (improved, residual) = improve(mdp, &policy, &values);
+        residuals.push(residual);
+        let stable = improved == policy;
+        policy = improved;
+        match evaluation_sweeps {
+            None if stable => break,
+            Some(_) if residual <= tolerance => break,
+            _ => {}
+        }
+    }
+    MdpSolution {
+        values,
+        policy,
+        iterations: residuals.len(),
+        sweeps,
+        residuals,
+    }
+}
@@ -0,0 +1,54 @@
+// Value iteration: repeatedly replaces the value of every state by the
+// value of its best action, until no value changes by more than the
+// tolerance. With a discount below 1 the values converge to the optimal ones
+// from any start; undiscounted processes converge as long as every policy
+// either ends or pays ever less, as those of the duel and the gridworld do.
+
+use crate::solvers::{Mdp, MdpPolicy};
+
+pub struct MdpSolution {
+    pub values: Vec<f64>,
+    // The greedy policy of the values.
+    pub policy: MdpPolicy,
+    pub iterations: usize,
+    // Sweeps over all states updating values, one per iteration of value
+    // iteration, those evaluating the policies for policy iteration.
+    pub sweeps: usize,
+    // The Bellman residual of every iteration, the largest change of a value
+    // by a step of value iteration; the last one tells how close the values
+    // are to optimal.
+    pub residuals: Vec<f64>,
+}
+
+impl MdpSolution {
+    pub fn residual(&self) -> f64 {
+        self.residuals.last().copied().unwrap_or(f64::INFINITY)
+    }
+
+    pub fn converged(&self, tolerance: f64) -> bool {
+        self.residual() <= tolerance
+    }
+}
+
+// Sweeps over all states in place, each sweep using the values updated
+// earlier in it, for at most `max_iterations` sweeps.
+pub fn value_iteration(mdp: &Mdp, tolerance: f64, max_iterations: usize) -> MdpSolution {
+    let mut values = vec![0.0; mdp.num_states()];
+    let mut residuals = Vec::new();
+    while residuals.len() < max_iterations && residuals.last().is_none_or(|r| *r > tolerance) {
+        let mut residual: f64 = 0.0;
+        for state in 0..mdp.num_states() {
+            let (_, value) = mdp.best_action(&values, state);
+            residual = residual.max((value - values[state]).abs());
+            values[state] = value;
+        }
+        residuals.push(residual);
+    }
+    MdpSolution {
+        policy: mdp.greedy_policy(&values),
+        values,
+        iterations: residuals.len(),
+        sweeps: residuals.len(),
+        residuals,
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+use crate::duel::Duel;
+use crate::game::{GameOutcome, GameState};
+use crate::observer::GameObserver;
+use crate::tournament::TournamentResults;
+
+// Quantile function of the standard normal distribution (Acklam's rational
+// approximation, relative error below 1.2e-9).
+#[allow(clippy::excessive_precision)]
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969683028665376e+01,
+        2.209460984245205e+02,
+        -2.759285104469687e+02,
+        1.383577518672690e+02,
+        -3.066479806614716e+01,
+        2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01,
+        1.615858368580409e+02,
+        -1.556989798598866e+02,
+        6.680131188771972e+01,
+        -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03,
+        -3.223964580411365e-01,
+        -2.400758277161838e+00,
+        -2.549732539343734e+00,
+        4.374664141464968e+00,
+        2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-03,
+        3.224671290700398e-01,
+        2.445134137142996e+00,
+        3.754408661907416e+00,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    if p < P_LOW {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -normal_quantile(1.0 - p)
+    }
+}
+
+// Distribution function of the standard normal distribution (Chebyshev
+// fit of the complementary error function, relative error below 1.2e-7).
+#[allow(clippy::excessive_precision)]
+pub fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / 2.0_f64.sqrt();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let erfc = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        1.0 - 0.5 * erfc
+    } else {
+        0.5 * erfc
+    }
+}
+
+// Natural logarithm of the gamma function (Lanczos approximation, g = 7).
+#[allow(clippy::excessive_precision)]
+pub fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.5203681218851,
+        -1259.1392167224028,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507343278686905,
+        -0.13857109526572012,
+        9.984_369_578_019_572e-6,
+        1.5056327351493116e-7,
+    ];
+    if x < 0.5 {
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    for (i, coefficient) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += coefficient / (x + i as f64);
+    }
+    let t = x + 7.5;
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+fn ln_binomial_pmf(k: u64, n: u64, p: f64) -> f64 {
+    ln_gamma(n as f64 + 1.0) - ln_gamma(k as f64 + 1.0) - ln_gamma((n - k) as f64 + 1.0)
+        + k as f64 * p.ln()
+        + (n - k) as f64 * (1.0 - p).ln()
+}
+
+// Wilson score interval for a binomial proportion at the given confidence level.
+pub fn wilson_interval(successes: u64, trials: u64, confidence: f64) -> (f64, f64) {
+    if trials == 0 {
+        return (0.0, 1.0);
+    }
+    let z = normal_quantile(0.5 + confidence / 2.0);
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let denominator = 1.0 + z * z / n;
+    let center = (p + z * z / (2.0 * n)) / denominator;
+    let half_width = z * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt() / denominator;
+    (
+        (center - half_width).max(0.0),
+        (center + half_width).min(1.0),
+    )
+}
+
+// Exact two-sided sign test: the probability, if both agents were equally
+// strong, of a split of decisive games at least as lopsided as `wins` to `losses`.
+pub fn sign_test_p_value(wins: u64, losses: u64) -> f64 {
+    let n = wins + losses;
+    if n == 0 {
+        return 1.0;
+    }
+    let extreme = wins.min(losses);
+    let tail: f64 = (0..=extreme)
+        .map(|k| ln_binomial_pmf(k, n, 0.5).exp())
+        .sum();
+    (2.0 * tail).min(1.0)
+}
+
+pub struct PairwiseComparison {
+    pub agent: usize,
+    pub opponent: usize,
+    pub wins: u64,
+    pub ties: u64,
+    pub losses: u64,
+    // Fraction of all games won, ties counting as not won.
+    pub win_rate: f64,
+    pub win_rate_interval: (f64, f64),
+    // Sign test of "agent and opponent are equally strong", ignoring ties.
+    pub p_value: f64,
+}
+
+impl PairwiseComparison {
+    // Whether the data supports "agent beats opponent" at the given significance level.
+    pub fn beats(&self, significance: f64) -> bool {
+        self.wins > self.losses && self.p_value < significance
+    }
+}
+
+// Compares every pair of distinct agents that met in the tournament, once
+// from the perspective of each agent.
+pub fn pairwise_comparisons(
+    results: &TournamentResults,
+    confidence: f64,
+) -> Vec<PairwiseComparison> {
+    let mut comparisons = Vec::new();
+    for agent in 0..results.num_agents() {
+        for opponent in 0..results.num_agents() {
+            if agent == opponent {
+                continue;
+            }
+            let record = results.record(agent, opponent);
+            let games = record.games();
+            if games == 0 {
+                continue;
+            }
+            comparisons.push(PairwiseComparison {
+                agent,
+                opponent,
+                wins: record.wins,
+                ties: record.ties,
+                losses: record.losses,
+                win_rate: record.wins as f64 / games as f64,
+                win_rate_interval: wilson_interval(record.wins, games, confidence),
+                p_value: sign_test_p_value(record.wins, record.losses),
+            });
+        }
+    }
+    comparisons
+}
+
+// Location and spread of a sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub min: f64,
+    pub p10: f64,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub max: f64,
+}
+
+impl Summary {
+    pub fn of(values: &[f64]) -> Option<Summary> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        Some(Summary {
+            count: sorted.len(),
+            mean: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            min: sorted[0],
+            p10: percentile(&sorted, 0.10),
+            p25: percentile(&sorted, 0.25),
+            median: percentile(&sorted, 0.50),
+            p75: percentile(&sorted, 0.75),
+            p90: percentile(&sorted, 0.90),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+// Percentile of already sorted values, interpolating linearly between ranks.
+pub fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    let rank = fraction * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+// Game lengths and victory margins collected for one pairing.
+#[derive(Default, Clone)]
+pub struct PairingDistributions {
+    // Number of turns of every game.
+    pub lengths: Vec<f64>,
+    // Hit points the winner had left, for every game that was not a tie.
+    pub winner_hit_points: Vec<f64>,
+}
+
+impl PairingDistributions {
+    pub fn length_summary(&self) -> Option<Summary> {
+        Summary::of(&self.lengths)
+    }
+
+    pub fn margin_summary(&self) -> Option<Summary> {
+        Summary::of(&self.winner_hit_points)
+    }
+}
+
+// Observer collecting how long and how decisive the games of every pairing were.
+#[derive(Default)]
+pub struct DistributionObserver {
+    pub pairings: BTreeMap<(usize, usize), PairingDistributions>,
+    current_pairing: Option<(usize, usize)>,
+}
+
+impl DistributionObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl GameObserver<Duel> for DistributionObserver {
+    fn on_game_start(&mut self, player_one: usize, player_two: usize) {
+        self.current_pairing = Some((player_one, player_two));
+    }
+
+    fn on_game_end(&mut self, state: &GameState<Duel>, outcome: &GameOutcome) {
+        let Some(pairing) = self.current_pairing else {
+            return;
+        };
+        let distributions = self.pairings.entry(pairing).or_default();
+        distributions.lengths.push(state.history.turns() as f64);
+        match outcome {
+            GameOutcome::WIN(1) => distributions
+                .winner_hit_points
+                .push(state.player_one_state.current_hit_points as f64),
+            GameOutcome::WIN(_) | GameOutcome::SURVIVOR(_) => distributions
+                .winner_hit_points
+                .push(state.player_two_state.current_hit_points as f64),
+            GameOutcome::TIE
+            | GameOutcome::DRAW(_)
+            | GameOutcome::CONTINUE
+            | GameOutcome::INTERRUPTED => {}
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+
+use crate::config::{ConfigError, ExperimentConfig};
+use crate::registry::AgentRegistry;
+use crate::tournament::{PairingRecord, PairingSchedule, Tournament};
+
+#[derive(Debug)]
+pub enum SweepError {
+    Range(String),
+    Config(ConfigError),
+}
+
+impl fmt::Display for SweepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SweepError::Range(range) => write!(
+                f,
+                "malformed parameter range '{}', expected name=start..end:step",
+                range
+            ),
+            SweepError::Config(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for SweepError {}
+
+// The values `start, start + step, ...` up to and including `end`.
+#[derive(Clone)]
+pub struct ParameterRange {
+    pub name: String,
+    pub start: f64,
+    pub end: f64,
+    pub step: f64,
+}
+
+impl ParameterRange {
+    // Parses `p=0.0..1.0:0.05`.
+    pub fn parse(range: &str) -> Result<Self, SweepError> {
+        let malformed = || SweepError::Range(range.to_string());
+        let (name, values) = range.split_once('=').ok_or_else(malformed)?;
+        let (bounds, step) = values.split_once(':').ok_or_else(malformed)?;
+        let (start, end) = bounds.split_once("..").ok_or_else(malformed)?;
+        let parse = |value: &str| value.trim().parse::<f64>().map_err(|_| malformed());
+        let range = ParameterRange {
+            name: name.trim().to_string(),
+            start: parse(start)?,
+            end: parse(end)?,
+            step: parse(step)?,
+        };
+        if range.name.is_empty() || range.step <= 0.0 || range.end < range.start {
+            return Err(malformed());
+        }
+        Ok(range)
+    }
+
+    pub fn values(&self) -> Vec<f64> {
+        // Tolerate rounding so that the end of e.g. 0.0..1.0:0.1 is included,
+        // and round the values so they print as 0.3 rather than 0.30000000000000004
+        let count = ((self.end - self.start) / self.step + 1e-9).floor() as usize;
+        (0..=count)
+            .map(|i| ((self.start + i as f64 * self.step) * 1e9).round() / 1e9)
+            .collect()
+    }
+}
+
+// One row of the long-format sweep table: the record of the swept agent,
+// over both seats, against one opponent of the roster.
+pub struct SweepRow {
+    pub values: Vec<f64>,
+    pub spec: String,
+    pub opponent: String,
+    pub record: PairingRecord,
+}
+
+// Runs the experiment's roster against an agent specification template such
+// as `markov({a}, {f})` for every combination of parameter values.
+pub struct Sweep {
+    pub template: String,
+    pub ranges: Vec<ParameterRange>,
+}
+
+impl Sweep {
+    pub fn new(template: &str, ranges: Vec<ParameterRange>) -> Self {
+        Self {
+            template: template.to_string(),
+            ranges,
+        }
+    }
+
+    // Every combination of parameter values, the last parameter varying fastest.
+    pub fn combinations(&self) -> Vec<Vec<f64>> {
+        self.ranges
+            .iter()
+            .fold(vec![Vec::new()], |combinations, range| {
+                combinations
+                    .iter()
+                    .flat_map(|combination| {
+                        range.values().into_iter().map(move |value| {
+                            let mut combination = combination.clone();
+                            combination.push(value);
+                            combination
+                        })
+                    })
+                    .collect()
+            })
+    }
+
+    pub fn spec(&self, values: &[f64]) -> String {
+        self.ranges
+            .iter()
+            .zip(values)
+            .fold(self.template.clone(), |spec, (range, value)| {
+                spec.replace(&format!("{{{}}}", range.name), &value.to_string())
+            })
+    }
+
+    // Plays one tournament per combination, each with the experiment's seed,
+    // pitting the swept agent against every roster agent in both seats.
+    pub fn run(
+        &self,
+        config: &ExperimentConfig,
+        registry: &AgentRegistry,
+    ) -> Result<Vec<SweepRow>, SweepError> {
+        let mut rows = Vec::new();
+        for values in self.combinations() {
+            let spec = self.spec(&values);
+            let rng = config.rng();
+            let mut agents = config
+                .build_agents(registry, &rng)
+                .map_err(SweepError::Config)?;
+            let swept = agents.len();
+            agents.push(
+                registry
+                    .build_with_damage(&spec, &rng, &config.damage_matrix())
+                    .map_err(|err| SweepError::Config(ConfigError::Agent(err)))?,
+            );
+            let pairings = (0..swept)
+                .flat_map(|opponent| [(swept, opponent), (opponent, swept)])
+                .collect();
+            let results = Tournament::new(
+                config.rules(),
+                agents,
+                config.num_retrials,
+                PairingSchedule::Custom(pairings),
+            )
+            .with_settings(config.game.clone())
+            .with_match_format(config.match_format.clone())
+            .run();
+            for opponent in 0..swept {
+                rows.push(SweepRow {
+                    values: values.clone(),
+                    spec: spec.clone(),
+                    opponent: results.agent_names[opponent].clone(),
+                    record: results.record(swept, opponent),
+                });
+            }
+        }
+        Ok(rows)
+    }
+}
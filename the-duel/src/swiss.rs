@@ -0,0 +1,210 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::agents::GameAgent;
+use crate::game::{GameOutcome, GameSettings, SimultaneousGame};
+use crate::matches::{Match, MatchFormat};
+use crate::observer::{GameObserver, NullObserver};
+use crate::tournament::PairingRecord;
+
+// One pairing of a Swiss round, or a bye when `player_two` is missing.
+#[derive(Clone, Serialize)]
+pub struct SwissPairing {
+    pub player_one: usize,
+    pub player_two: Option<usize>,
+    pub record: PairingRecord,
+    pub outcome: GameOutcome,
+}
+
+#[derive(Serialize)]
+pub struct SwissStanding {
+    pub agent: usize,
+    pub score: f64,
+    // Sum of the final scores of all opponents faced, which breaks ties
+    // between agents with the same score.
+    pub buchholz: f64,
+}
+
+#[derive(Serialize)]
+pub struct SwissResults {
+    pub agent_names: Vec<String>,
+    pub rounds: Vec<Vec<SwissPairing>>,
+    // Best agent first.
+    pub standings: Vec<SwissStanding>,
+}
+
+impl fmt::Display for SwissResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (round, pairings) in self.rounds.iter().enumerate() {
+            writeln!(f, "Round {}:", round + 1)?;
+            for pairing in pairings {
+                let player_one = &self.agent_names[pairing.player_one];
+                match pairing.player_two {
+                    Some(player_two) => writeln!(
+                        f,
+                        " {} vs {}: {}-{}-{}",
+                        player_one,
+                        self.agent_names[player_two],
+                        pairing.record.wins,
+                        pairing.record.ties,
+                        pairing.record.losses
+                    )?,
+                    None => writeln!(f, " {} gets a bye", player_one)?,
+                }
+            }
+        }
+        writeln!(f, "Standings [score (Buchholz)]:")?;
+        for (rank, standing) in self.standings.iter().enumerate() {
+            writeln!(
+                f,
+                " {}. {}: {:.1} ({:.1})",
+                rank + 1,
+                self.agent_names[standing.agent],
+                standing.score,
+                standing.buchholz
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Match points of player one and player two for the outcome of their match.
+fn points(outcome: &GameOutcome) -> (f64, f64) {
+    match outcome {
+        GameOutcome::WIN(1) => (1.0, 0.0),
+        GameOutcome::WIN(_) => (0.0, 1.0),
+        _ => (0.5, 0.5),
+    }
+}
+
+pub struct SwissTournament<G: SimultaneousGame + Clone> {
+    pub rules: G,
+    pub settings: GameSettings,
+    pub agents: Vec<Box<dyn GameAgent<G>>>,
+    pub num_rounds: usize,
+    pub format: MatchFormat,
+}
+
+impl<G: SimultaneousGame + Clone> SwissTournament<G> {
+    pub fn new(rules: G, agents: Vec<Box<dyn GameAgent<G>>>, num_rounds: usize) -> Self {
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            agents,
+            num_rounds,
+            format: MatchFormat::default(),
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_match_format(mut self, format: MatchFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn run(&self) -> SwissResults {
+        self.run_observed(&mut NullObserver)
+    }
+
+    pub fn run_observed(&self, observer: &mut dyn GameObserver<G>) -> SwissResults {
+        let num_agents = self.agents.len();
+        let mut scores: Vec<f64> = vec![0.0; num_agents];
+        let mut opponents: Vec<Vec<usize>> = vec![Vec::new(); num_agents];
+        let mut had_bye = vec![false; num_agents];
+        let mut rounds = Vec::new();
+
+        for round in 0..self.num_rounds {
+            // Rank by score, keeping roster order between equal scores
+            let mut ranking: Vec<usize> = (0..num_agents).collect();
+            ranking.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+
+            let mut pairings = Vec::new();
+            if ranking.len() % 2 == 1 {
+                // The lowest ranked agent without a bye sits this round out
+                let position = ranking
+                    .iter()
+                    .rposition(|&agent| !had_bye[agent])
+                    .unwrap_or(ranking.len() - 1);
+                let agent = ranking.remove(position);
+                had_bye[agent] = true;
+                scores[agent] += 1.0;
+                pairings.push(SwissPairing {
+                    player_one: agent,
+                    player_two: None,
+                    record: PairingRecord::default(),
+                    outcome: GameOutcome::WIN(1),
+                });
+            }
+
+            // Pair every agent with the closest ranked agent it has not met
+            // yet, falling back to a rematch once it has met all of them.
+            let mut unpaired = ranking;
+            let mut round_pairings = Vec::new();
+            while !unpaired.is_empty() {
+                let agent = unpaired.remove(0);
+                let position = unpaired
+                    .iter()
+                    .position(|opponent| !opponents[agent].contains(opponent))
+                    .unwrap_or(0);
+                let opponent = unpaired.remove(position);
+                round_pairings.push((agent, opponent));
+            }
+
+            for (player_one, player_two) in round_pairings {
+                observer.on_match_start(player_one, player_two);
+                let result = Match::new(
+                    &self.rules,
+                    &self.settings,
+                    &self.format,
+                    self.agents[player_one].as_ref(),
+                    self.agents[player_two].as_ref(),
+                )
+                .play_observed(observer, (player_one, player_two));
+                let mut record = PairingRecord::default();
+                record.record(&result.outcome);
+                observer.on_match_end(player_one, player_two, &record);
+
+                let (one_points, two_points) = points(&result.outcome);
+                scores[player_one] += one_points;
+                scores[player_two] += two_points;
+                opponents[player_one].push(player_two);
+                opponents[player_two].push(player_one);
+                pairings.push(SwissPairing {
+                    player_one,
+                    player_two: Some(player_two),
+                    record: result.games,
+                    outcome: result.outcome,
+                });
+            }
+            rounds.push(pairings);
+            observer.on_round_end(round as u64);
+            if observer.should_stop() {
+                break;
+            }
+        }
+
+        let mut standings: Vec<SwissStanding> = (0..num_agents)
+            .map(|agent| SwissStanding {
+                agent,
+                score: scores[agent],
+                buchholz: opponents[agent].iter().map(|&o| scores[o]).sum(),
+            })
+            .collect();
+        standings.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then(b.buchholz.total_cmp(&a.buchholz))
+        });
+
+        SwissResults {
+            agent_names: self.agents.iter().map(|a| a.strategy_name()).collect(),
+            rounds,
+            standings,
+        }
+    }
+}
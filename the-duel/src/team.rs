@@ -0,0 +1,153 @@
+// Team duels: two teams of two agents at a table of the free-for-all,
+// sharing their win condition. Seats alternate between the teams, so that
+// the first team takes seats 1 and 3. Teammates coordinate by their
+// targeting rule, which sees the teammate's state and target, and the
+// optional signaling channel.
+
+use serde::Deserialize;
+
+use crate::agents::{GameAgent, SharedRng};
+use crate::duel::Duel;
+use crate::free_for_all::{FreeForAll, Table, Targeting};
+use crate::game::GameSettings;
+use crate::tournament::PairingRecord;
+
+#[derive(Deserialize, Clone)]
+pub struct TeamConfig {
+    // Roster indices of the two members of every team.
+    pub teams: Vec<(usize, usize)>,
+    // Games per pair of teams and seating.
+    #[serde(default = "default_games")]
+    pub games: u64,
+    #[serde(default = "default_targeting")]
+    pub targeting: Targeting,
+    // Whether the team's second player learns the target its teammate picks
+    // in the same turn rather than the one of the last turn.
+    #[serde(default)]
+    pub signaling: bool,
+    // Damage of an attack on a player facing someone else.
+    #[serde(default = "default_ambush_damage")]
+    pub ambush_damage: i64,
+}
+
+fn default_games() -> u64 {
+    50
+}
+
+fn default_targeting() -> Targeting {
+    Targeting::Focus
+}
+
+fn default_ambush_damage() -> i64 {
+    1
+}
+
+impl TeamConfig {
+    pub fn validate(&self, num_agents: usize) -> Result<(), String> {
+        if self.teams.len() < 2 {
+            return Err(String::from("team duels need at least two teams"));
+        }
+        for (first, second) in &self.teams {
+            if *first >= num_agents || *second >= num_agents {
+                return Err(format!(
+                    "a team names agents {} and {}, but there are only {}",
+                    first, second, num_agents
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// The record of every team against every other, seen from the first, over
+// both seatings.
+pub struct TeamResults {
+    pub team_names: Vec<String>,
+    pub records: Vec<Vec<PairingRecord>>,
+}
+
+impl TeamResults {
+    // Average score of `team` against all other teams, a tie or draw
+    // counting as half a win.
+    pub fn score(&self, team: usize) -> f64 {
+        let mut total = PairingRecord::default();
+        for record in &self.records[team] {
+            total.add(record);
+        }
+        (total.wins as f64 + 0.5 * total.ties as f64) / total.games().max(1) as f64
+    }
+}
+
+pub struct TeamDuel {
+    pub rules: FreeForAll,
+    pub settings: GameSettings,
+    pub agents: Vec<Box<dyn GameAgent>>,
+    pub config: TeamConfig,
+}
+
+impl TeamDuel {
+    pub fn new(rules: Duel, agents: Vec<Box<dyn GameAgent>>, config: TeamConfig) -> Self {
+        Self {
+            rules: FreeForAll::new(rules).with_ambush_damage(config.ambush_damage),
+            settings: GameSettings::default(),
+            agents,
+            config,
+        }
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    // Plays `first` against `second`, with `first` as team 1.
+    fn record(
+        &self,
+        first: (usize, usize),
+        second: (usize, usize),
+        rng: &SharedRng,
+    ) -> PairingRecord {
+        let mut record = PairingRecord::default();
+        for _ in 0..self.config.games {
+            let mut table = Table::new(
+                self.rules.clone(),
+                [first.0, second.0, first.1, second.1]
+                    .iter()
+                    .map(|agent| self.agents[*agent].copy_self_to_anom())
+                    .collect(),
+            )
+            .with_settings(self.settings.clone())
+            .with_teams(vec![0, 1, 0, 1])
+            .with_targeting(self.config.targeting)
+            .with_signaling(self.config.signaling);
+            record.record(&table.play(rng));
+        }
+        record
+    }
+
+    pub fn run(&self, rng: &SharedRng) -> TeamResults {
+        let teams = &self.config.teams;
+        let mut records = vec![vec![PairingRecord::default(); teams.len()]; teams.len()];
+        for first in 0..teams.len() {
+            for second in (first + 1)..teams.len() {
+                let mut record = self.record(teams[first], teams[second], rng);
+                record.add(&self.record(teams[second], teams[first], rng).reversed());
+                records[second][first] = record.reversed();
+                records[first][second] = record;
+            }
+        }
+        TeamResults {
+            team_names: teams
+                .iter()
+                .map(|(first, second)| {
+                    format!(
+                        "{} + {}",
+                        self.agents[*first].strategy_name(),
+                        self.agents[*second].strategy_name()
+                    )
+                })
+                .collect(),
+            records,
+        }
+    }
+}
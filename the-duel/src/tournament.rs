@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::span::EnteredSpan;
+
+use crate::agents::{GameAgent, SharedRng};
+use crate::game::{GameOutcome, GameSettings, SimultaneousGame, seeded_stream};
+use crate::matches::{Match, MatchFormat};
+use crate::observer::{GameObserver, NullObserver};
+use crate::rng_audit;
+
+// Which seatings of the roster get played against each other.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PairingSchedule {
+    // Every ordered pair, including every agent against itself.
+    RoundRobin,
+    // Every ordered pair of distinct agents.
+    RoundRobinWithoutSelfPlay,
+    // Every unordered pair of distinct agents, played once with the lower index seated first.
+    SingleRoundRobin,
+    // An explicit list of (player one, player two) roster indices.
+    Custom(Vec<(usize, usize)>),
+}
+
+impl PairingSchedule {
+    // Whether a custom schedule only names agents of a roster of `num_agents`.
+    pub fn validate(&self, num_agents: usize) -> Result<(), String> {
+        if let PairingSchedule::Custom(pairs) = self
+            && let Some((first, second)) = pairs
+                .iter()
+                .find(|(first, second)| *first >= num_agents || *second >= num_agents)
+        {
+            return Err(format!(
+                "the schedule pairs agents {} and {}, but there are only {} agents",
+                first, second, num_agents
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn pairings(&self, num_agents: usize) -> Vec<(usize, usize)> {
+        match self {
+            PairingSchedule::RoundRobin => (0..num_agents)
+                .flat_map(|i| (0..num_agents).map(move |j| (i, j)))
+                .collect(),
+            PairingSchedule::RoundRobinWithoutSelfPlay => (0..num_agents)
+                .flat_map(|i| {
+                    (0..num_agents)
+                        .filter(move |j| *j != i)
+                        .map(move |j| (i, j))
+                })
+                .collect(),
+            PairingSchedule::SingleRoundRobin => (0..num_agents)
+                .flat_map(|i| ((i + 1)..num_agents).map(move |j| (i, j)))
+                .collect(),
+            PairingSchedule::Custom(pairings) => pairings.clone(),
+        }
+    }
+}
+
+// Outcome counts of one seating, seen from player one. With a best-of-N
+// match format these count matches rather than single games.
+#[derive(Clone, Default, Serialize)]
+pub struct PairingRecord {
+    pub wins: u64,
+    pub ties: u64,
+    pub losses: u64,
+}
+
+impl PairingRecord {
+    pub fn games(&self) -> u64 {
+        self.wins + self.ties + self.losses
+    }
+
+    pub fn record(&mut self, outcome: &GameOutcome) {
+        match outcome {
+            GameOutcome::WIN(1) | GameOutcome::SURVIVOR(1) => self.wins += 1,
+            GameOutcome::WIN(_) | GameOutcome::SURVIVOR(_) => self.losses += 1,
+            // A game called at the turn limit scores like a tie
+            GameOutcome::TIE | GameOutcome::DRAW(_) => self.ties += 1,
+            GameOutcome::CONTINUE | GameOutcome::INTERRUPTED => {}
+        }
+    }
+
+    pub fn reversed(&self) -> PairingRecord {
+        PairingRecord {
+            wins: self.losses,
+            ties: self.ties,
+            losses: self.wins,
+        }
+    }
+
+    pub fn add(&mut self, other: &PairingRecord) {
+        self.wins += other.wins;
+        self.ties += other.ties;
+        self.losses += other.losses;
+    }
+}
+
+#[derive(Serialize)]
+pub struct PairingResult {
+    pub player_one: usize,
+    pub player_two: usize,
+    pub record: PairingRecord,
+}
+
+#[derive(Serialize)]
+pub struct TournamentResults {
+    pub agent_names: Vec<String>,
+    pub num_retrials: u64,
+    pub pairings: Vec<PairingResult>,
+}
+
+impl TournamentResults {
+    pub fn num_agents(&self) -> usize {
+        self.agent_names.len()
+    }
+
+    // Combined record of `agent` against `opponent`, over both seatings.
+    pub fn record(&self, agent: usize, opponent: usize) -> PairingRecord {
+        let mut total = PairingRecord::default();
+        for pairing in &self.pairings {
+            if pairing.player_one == agent && pairing.player_two == opponent {
+                total.add(&pairing.record);
+            }
+            if pairing.player_two == agent && pairing.player_one == opponent {
+                total.add(&pairing.record.reversed());
+            }
+        }
+        total
+    }
+
+    // `win_matrix()[i][j]` counts the games agent i won against agent j.
+    pub fn win_matrix(&self) -> Vec<Vec<u64>> {
+        let num_agents = self.num_agents();
+        let mut win_matrix = vec![vec![0; num_agents]; num_agents];
+        for pairing in &self.pairings {
+            win_matrix[pairing.player_one][pairing.player_two] += pairing.record.wins;
+            win_matrix[pairing.player_two][pairing.player_one] += pairing.record.losses;
+        }
+        win_matrix
+    }
+}
+
+pub struct Tournament<G: SimultaneousGame + Clone> {
+    pub rules: G,
+    pub settings: GameSettings,
+    pub agents: Vec<Box<dyn GameAgent<G>>>,
+    pub num_retrials: u64,
+    pub schedule: PairingSchedule,
+    // Play the schedule in rounds of one game per pairing instead of playing
+    // all retrials of a pairing back to back, so that stopping early still
+    // leaves every pairing with the same number of games.
+    pub interleaved: bool,
+    // Each retrial of a pairing is one match of this format.
+    pub format: MatchFormat,
+    // Rules replacing `rules` for a (player one, player two) pairing, e.g.
+    // to give one of the agents a handicap.
+    pub pairing_rules: HashMap<(usize, usize), G>,
+    // The experiment's seed and the generator the agents draw from, to play
+    // every match from a seed of its own, see `with_game_seeds`.
+    pub game_seeds: Option<(u64, SharedRng)>,
+}
+
+impl<G: SimultaneousGame + Clone> Tournament<G> {
+    pub fn new(
+        rules: G,
+        agents: Vec<Box<dyn GameAgent<G>>>,
+        num_retrials: u64,
+        schedule: PairingSchedule,
+    ) -> Self {
+        Self {
+            rules,
+            settings: GameSettings::default(),
+            agents,
+            num_retrials,
+            schedule,
+            interleaved: false,
+            format: MatchFormat::default(),
+            pairing_rules: HashMap::new(),
+            game_seeds: None,
+        }
+    }
+
+    pub fn with_pairing_rules(mut self, player_one: usize, player_two: usize, rules: G) -> Self {
+        self.pairing_rules.insert((player_one, player_two), rules);
+        self
+    }
+
+    pub fn with_settings(mut self, settings: GameSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    pub fn with_interleaving(mut self, interleaved: bool) -> Self {
+        self.interleaved = interleaved;
+        self
+    }
+
+    pub fn with_match_format(mut self, format: MatchFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    // Plays every match from a seed derived from `seed`, the names of the
+    // two agents and the retrial, see `game_seed`, restarting `rng`, the
+    // generator the agents draw from, and the generators of the rules from
+    // it. Results then no longer depend on the order of the schedule, and any
+    // single match can be played again in isolation with `play_single`.
+    pub fn with_game_seeds(mut self, seed: u64, rng: SharedRng) -> Self {
+        self.game_seeds = Some((seed, rng));
+        self
+    }
+
+    pub fn run(&self) -> TournamentResults {
+        self.run_observed(&mut NullObserver)
+    }
+
+    // Runs the tournament, stopping early as soon as `observer` asks to.
+    pub fn run_observed(&self, observer: &mut dyn GameObserver<G>) -> TournamentResults {
+        let schedule = self.schedule.pairings(self.agents.len());
+        let mut pairings: Vec<PairingResult> = Vec::new();
+        let mut num_retrials = self.num_retrials;
+
+        if self.interleaved {
+            pairings = schedule
+                .iter()
+                .map(|&(player_one, player_two)| {
+                    observer.on_match_start(player_one, player_two);
+                    PairingResult {
+                        player_one,
+                        player_two,
+                        record: PairingRecord::default(),
+                    }
+                })
+                .collect();
+            for round in 0..self.num_retrials {
+                let _round = tracing::info_span!("round", round).entered();
+                for pairing in pairings.iter_mut() {
+                    let _pairing = self.pairing_span(pairing.player_one, pairing.player_two);
+                    let outcome =
+                        self.play_single(pairing.player_one, pairing.player_two, round, observer);
+                    pairing.record.record(&outcome);
+                }
+                observer.on_round_end(round);
+                if observer.should_stop() {
+                    num_retrials = round + 1;
+                    break;
+                }
+            }
+            for pairing in &pairings {
+                let _pairing = self.pairing_span(pairing.player_one, pairing.player_two);
+                log_record(&pairing.record);
+                observer.on_match_end(pairing.player_one, pairing.player_two, &pairing.record);
+            }
+        } else {
+            // Fight two against each other
+            'schedule: for (player_one, player_two) in schedule {
+                let _pairing = self.pairing_span(player_one, player_two);
+                observer.on_match_start(player_one, player_two);
+                let mut record = PairingRecord::default();
+                let mut stopped = false;
+                for retrial in 0..self.num_retrials {
+                    record.record(&self.play_single(player_one, player_two, retrial, observer));
+                    if observer.should_stop() {
+                        stopped = true;
+                        break;
+                    }
+                }
+                log_record(&record);
+                observer.on_match_end(player_one, player_two, &record);
+                pairings.push(PairingResult {
+                    player_one,
+                    player_two,
+                    record,
+                });
+                if stopped {
+                    break 'schedule;
+                }
+            }
+        }
+
+        TournamentResults {
+            agent_names: self.agents.iter().map(|a| a.strategy_name()).collect(),
+            num_retrials,
+            pairings,
+        }
+    }
+
+    // The span of the games between two agents of the roster.
+    fn pairing_span(&self, player_one: usize, player_two: usize) -> EnteredSpan {
+        tracing::info_span!(
+            "pairing",
+            player_one = %self.agents[player_one].strategy_name(),
+            player_two = %self.agents[player_two].strategy_name(),
+        )
+        .entered()
+    }
+
+    // Plays one match of the tournament's format, the given retrial of the
+    // pairing, and returns who took it.
+    pub fn play_single(
+        &self,
+        player_one: usize,
+        player_two: usize,
+        retrial: u64,
+        observer: &mut dyn GameObserver<G>,
+    ) -> GameOutcome {
+        let rules = self
+            .pairing_rules
+            .get(&(player_one, player_two))
+            .unwrap_or(&self.rules);
+        let _match = rng_audit::attribute(
+            || {
+                format!(
+                    "{} vs {}, retrial {}",
+                    self.agents[player_one].strategy_name(),
+                    self.agents[player_two].strategy_name(),
+                    retrial
+                )
+            },
+            None,
+        );
+        if let Some((seed, rng)) = &self.game_seeds {
+            let seed = game_seed(*seed, player_one, player_two, retrial);
+            tracing::debug!(retrial, seed, "seeding match");
+            *rng.borrow_mut() = seeded_stream(seed, 0);
+            rules.reseed(seed);
+        }
+        Match::new(
+            rules,
+            &self.settings,
+            &self.format,
+            self.agents[player_one].as_ref(),
+            self.agents[player_two].as_ref(),
+        )
+        .play_observed(observer, (player_one, player_two))
+        .outcome
+    }
+}
+
+// The seed of the given retrial of the roster's `player_one` against its
+// `player_two` in an experiment seeded by `seed`: the first eight bytes of a
+// SHA-256 digest, the same on every platform. Agents are told apart by their
+// roster index, as several may share a strategy name.
+pub fn game_seed(seed: u64, player_one: usize, player_two: usize, retrial: u64) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(seed.to_le_bytes());
+    for index in [player_one, player_two] {
+        hasher.update((index as u64).to_le_bytes());
+    }
+    hasher.update(retrial.to_le_bytes());
+    let digest = hasher.finalize();
+    u64::from_le_bytes(digest[..8].try_into().expect("a digest of 32 bytes"))
+}
+
+fn log_record(record: &PairingRecord) {
+    tracing::info!(
+        wins = record.wins,
+        ties = record.ties,
+        losses = record.losses,
+        "pairing finished"
+    );
+}
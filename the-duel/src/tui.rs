@@ -0,0 +1,326 @@
+use std::time::{Duration, Instant};
+
+use ratatui::DefaultTerminal;
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Gauge, Paragraph};
+
+use crate::duel::Duel;
+use crate::game::{GameOutcome, GameState};
+use crate::observer::GameObserver;
+use crate::tournament::PairingRecord;
+
+#[derive(Clone, Copy)]
+enum CellStatus {
+    Pending,
+    Running,
+    // Share of the games the row agent won.
+    Done(f64),
+}
+
+struct DuelView {
+    player_one: String,
+    player_two: String,
+    player_one_hit_points: i64,
+    player_two_hit_points: i64,
+    max_hit_points: i64,
+    turn: usize,
+    outcome: Option<GameOutcome>,
+}
+
+struct TournamentView {
+    agent_names: Vec<String>,
+    grid: Vec<Vec<CellStatus>>,
+    games_played: u64,
+    total_games: u64,
+}
+
+// Terminal viewer drawing live hit point bars of the running duel and, for
+// tournaments, a grid showing the progress and outcome of every pairing.
+// Pressing `q` asks a running tournament to stop.
+pub struct TuiObserver {
+    terminal: DefaultTerminal,
+    duel: DuelView,
+    tournament: Option<TournamentView>,
+    turn_delay: Duration,
+    redraw_interval: Duration,
+    last_draw: Instant,
+    quit: bool,
+}
+
+impl TuiObserver {
+    // Viewer for a single duel, pausing `turn_delay` after every turn so it can be followed.
+    pub fn for_duel(
+        player_one: String,
+        player_two: String,
+        max_hit_points: i64,
+        turn_delay: Duration,
+    ) -> Self {
+        Self {
+            terminal: ratatui::init(),
+            duel: DuelView {
+                player_one,
+                player_two,
+                player_one_hit_points: max_hit_points,
+                player_two_hit_points: max_hit_points,
+                max_hit_points,
+                turn: 0,
+                outcome: None,
+            },
+            tournament: None,
+            turn_delay,
+            redraw_interval: Duration::ZERO,
+            last_draw: Instant::now(),
+            quit: false,
+        }
+    }
+
+    // Viewer for a tournament over the given roster and number of pairings.
+    pub fn for_tournament(
+        agent_names: Vec<String>,
+        max_hit_points: i64,
+        num_pairings: usize,
+        num_retrials: u64,
+    ) -> Self {
+        let num_agents = agent_names.len();
+        Self {
+            terminal: ratatui::init(),
+            duel: DuelView {
+                player_one: String::new(),
+                player_two: String::new(),
+                player_one_hit_points: max_hit_points,
+                player_two_hit_points: max_hit_points,
+                max_hit_points,
+                turn: 0,
+                outcome: None,
+            },
+            tournament: Some(TournamentView {
+                agent_names,
+                grid: vec![vec![CellStatus::Pending; num_agents]; num_agents],
+                games_played: 0,
+                total_games: num_pairings as u64 * num_retrials,
+            }),
+            turn_delay: Duration::ZERO,
+            redraw_interval: Duration::from_millis(50),
+            last_draw: Instant::now(),
+            quit: false,
+        }
+    }
+
+    // Shows the final state until a key is pressed, then restores the terminal.
+    pub fn finish(mut self) {
+        self.draw();
+        if !self.quit {
+            loop {
+                if let Ok(Event::Key(key)) = event::read()
+                    && key.kind == KeyEventKind::Press
+                {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn poll_quit(&mut self) {
+        while let Ok(true) = event::poll(Duration::ZERO) {
+            if let Ok(Event::Key(key)) = event::read()
+                && key.kind == KeyEventKind::Press
+                && key.code == KeyCode::Char('q')
+            {
+                self.quit = true;
+            }
+        }
+    }
+
+    fn draw_throttled(&mut self) {
+        if self.last_draw.elapsed() >= self.redraw_interval {
+            self.poll_quit();
+            self.draw();
+            self.last_draw = Instant::now();
+        }
+    }
+
+    fn draw(&mut self) {
+        let duel = &self.duel;
+        let tournament = &self.tournament;
+        let quit = self.quit;
+        let _ = self
+            .terminal
+            .draw(|frame| render(frame, duel, tournament.as_ref(), quit));
+    }
+}
+
+impl Drop for TuiObserver {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
+fn hit_point_gauge<'a>(name: &'a str, hit_points: i64, max_hit_points: i64) -> Gauge<'a> {
+    let ratio = (hit_points.max(0) as f64 / max_hit_points.max(1) as f64).clamp(0.0, 1.0);
+    let color = if ratio > 0.5 {
+        Color::Green
+    } else if ratio > 0.2 {
+        Color::Yellow
+    } else {
+        Color::Red
+    };
+    Gauge::default()
+        .block(Block::bordered().title(name))
+        .gauge_style(Style::new().fg(color))
+        .ratio(ratio)
+        .label(format!("{}/{} HP", hit_points, max_hit_points))
+}
+
+// Red for a win rate of 0, green for 1.
+fn win_rate_color(win_rate: f64) -> Color {
+    let red = (255.0 * (1.0 - win_rate)).round() as u8;
+    let green = (255.0 * win_rate).round() as u8;
+    Color::Rgb(red, green, 40)
+}
+
+fn render(frame: &mut Frame, duel: &DuelView, tournament: Option<&TournamentView>, quit: bool) {
+    let [duel_area, rest] =
+        Layout::vertical([Constraint::Length(8), Constraint::Min(0)]).areas(frame.area());
+    render_duel(frame, duel, duel_area);
+    if let Some(tournament) = tournament {
+        render_tournament(frame, tournament, rest, quit);
+    }
+}
+
+fn render_duel(frame: &mut Frame, duel: &DuelView, area: Rect) {
+    let [status, player_one, player_two] = Layout::vertical([
+        Constraint::Length(2),
+        Constraint::Length(3),
+        Constraint::Length(3),
+    ])
+    .areas(area);
+
+    let status_text = match &duel.outcome {
+        Some(GameOutcome::WIN(id)) => format!("Turn {} - Player {} wins!", duel.turn, id),
+        Some(GameOutcome::TIE) => format!("Turn {} - Game ended in a Tie", duel.turn),
+        Some(GameOutcome::DRAW(reason)) => {
+            format!("Turn {} - Game ended in a Draw ({})", duel.turn, reason)
+        }
+        _ => format!("Turn {}", duel.turn),
+    };
+    frame.render_widget(Paragraph::new(status_text.bold()), status);
+    frame.render_widget(
+        hit_point_gauge(
+            &duel.player_one,
+            duel.player_one_hit_points,
+            duel.max_hit_points,
+        ),
+        player_one,
+    );
+    frame.render_widget(
+        hit_point_gauge(
+            &duel.player_two,
+            duel.player_two_hit_points,
+            duel.max_hit_points,
+        ),
+        player_two,
+    );
+}
+
+fn render_tournament(frame: &mut Frame, tournament: &TournamentView, area: Rect, quit: bool) {
+    let [progress, grid] =
+        Layout::vertical([Constraint::Length(3), Constraint::Min(0)]).areas(area);
+
+    let ratio = if tournament.total_games == 0 {
+        1.0
+    } else {
+        (tournament.games_played as f64 / tournament.total_games as f64).min(1.0)
+    };
+    let title = if quit {
+        "Tournament (stopping...)"
+    } else {
+        "Tournament (q to stop)"
+    };
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::bordered().title(title))
+            .gauge_style(Style::new().fg(Color::Cyan))
+            .ratio(ratio)
+            .label(format!(
+                "{}/{} games",
+                tournament.games_played, tournament.total_games
+            )),
+        progress,
+    );
+
+    let mut lines = Vec::new();
+    for (row, name) in tournament.agent_names.iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("{:>3} ", row))];
+        for cell in &tournament.grid[row] {
+            let span = match cell {
+                CellStatus::Pending => Span::styled("··", Style::new().fg(Color::DarkGray)),
+                CellStatus::Running => Span::styled("▒▒", Style::new().fg(Color::Yellow)),
+                CellStatus::Done(win_rate) => {
+                    Span::styled("██", Style::new().fg(win_rate_color(*win_rate)))
+                }
+            };
+            spans.push(span);
+        }
+        spans.push(Span::raw(format!(" {}", name)));
+        lines.push(Line::from(spans));
+    }
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title("Win rate of row against column")),
+        grid,
+    );
+}
+
+impl GameObserver<Duel> for TuiObserver {
+    fn on_game_start(&mut self, player_one: usize, player_two: usize) {
+        if let Some(tournament) = &self.tournament {
+            self.duel.player_one = tournament.agent_names[player_one].clone();
+            self.duel.player_two = tournament.agent_names[player_two].clone();
+        }
+        self.duel.outcome = None;
+    }
+
+    fn on_turn(&mut self, state: &GameState<Duel>) {
+        self.duel.turn = state.history.turns();
+        self.duel.player_one_hit_points = state.player_one_state.current_hit_points;
+        self.duel.player_two_hit_points = state.player_two_state.current_hit_points;
+        self.duel.max_hit_points = state.player_one_state.max_hit_points;
+        self.draw_throttled();
+        if !self.turn_delay.is_zero() {
+            std::thread::sleep(self.turn_delay);
+        }
+    }
+
+    fn on_game_end(&mut self, _state: &GameState<Duel>, outcome: &GameOutcome) {
+        self.duel.outcome = Some(outcome.clone());
+        if let Some(tournament) = &mut self.tournament {
+            tournament.games_played += 1;
+        }
+        self.draw_throttled();
+    }
+
+    fn on_match_start(&mut self, player_one: usize, player_two: usize) {
+        if let Some(tournament) = &mut self.tournament {
+            tournament.grid[player_one][player_two] = CellStatus::Running;
+        }
+    }
+
+    fn on_match_end(&mut self, player_one: usize, player_two: usize, record: &PairingRecord) {
+        if let Some(tournament) = &mut self.tournament
+            && record.games() > 0
+        {
+            let games = record.games() as f64;
+            tournament.grid[player_one][player_two] = CellStatus::Done(record.wins as f64 / games);
+            tournament.grid[player_two][player_one] =
+                CellStatus::Done(record.losses as f64 / games);
+        }
+        self.draw_throttled();
+    }
+
+    fn should_stop(&self) -> bool {
+        self.quit
+    }
+}
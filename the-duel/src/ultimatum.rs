@@ -0,0 +1,257 @@
+// The ultimatum game, the first sequential game of the crate: the proposer
+// offers the responder a share of a pie, and the responder accepts, splitting
+// the pie as offered, or rejects, leaving both with nothing. Every ultimatum
+// takes two turns, the proposer moving first and the responder, who sees the
+// offer as its opponent's last action, second. A game plays a number of
+// ultimatums with the players swapping roles after each, so that both
+// propose equally often.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{GameOutcome, GameSettings, GameState, SimultaneousGame, TurnOrder};
+use crate::observer::Scored;
+use crate::openspiel::SpielGame;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Bid {
+    // The responder's share of the pie.
+    OFFER(u32),
+    ACCEPT,
+    REJECT,
+    // What the player not to move plays.
+    WAIT,
+}
+
+impl fmt::Display for Bid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bid::OFFER(share) => write!(f, "OFFER {}", share),
+            bid => write!(f, "{:?}", bid),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Role {
+    Proposer,
+    Responder,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bargainer {
+    // The player's role in the current ultimatum.
+    pub role: Role,
+    pub score: i64,
+}
+
+impl Scored for Bargainer {
+    fn score(&self) -> f64 {
+        self.score as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Ultimatum {
+    pub pie: u32,
+    pub rounds: usize,
+}
+
+impl Default for Ultimatum {
+    fn default() -> Self {
+        Self {
+            pie: 10,
+            rounds: 10,
+        }
+    }
+}
+
+impl Ultimatum {
+    pub fn new(pie: u32, rounds: usize) -> Self {
+        Self { pie, rounds }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pie == 0 {
+            return Err(String::from("the pie has to be at least 1"));
+        }
+        Ok(())
+    }
+
+    // The player (1 or 2) proposing in the ultimatum `state` is at.
+    fn proposer(state: &GameState<Self>) -> u64 {
+        if state.player_one_state.role == Role::Proposer {
+            1
+        } else {
+            2
+        }
+    }
+}
+
+impl SimultaneousGame for Ultimatum {
+    type PlayerState = Bargainer;
+    type Action = Bid;
+
+    fn initial_state(&self) -> GameState<Self> {
+        GameState::new(
+            Bargainer {
+                role: Role::Proposer,
+                score: 0,
+            },
+            Bargainer {
+                role: Role::Responder,
+                score: 0,
+            },
+        )
+    }
+
+    // The proposer moves on even turns, the responder on odd ones.
+    fn turn_order(&self, state: &GameState<Self>) -> TurnOrder<Bid> {
+        let proposer = Self::proposer(state);
+        let player = if state.history.turns().is_multiple_of(2) {
+            proposer
+        } else {
+            3 - proposer
+        };
+        TurnOrder::Sequential {
+            player,
+            pass: Bid::WAIT,
+        }
+    }
+
+    // Offers beyond the pie offer all of it, proposers not offering offer
+    // nothing and responders not answering reject.
+    fn enforce_legality(&self, state: &GameState<Self>, player: u64, action: Bid) -> Bid {
+        let proposing = Self::proposer(state) == player;
+        match (proposing, action) {
+            (true, Bid::OFFER(share)) => Bid::OFFER(share.min(self.pie)),
+            (true, _) => Bid::OFFER(0),
+            (false, Bid::ACCEPT) => Bid::ACCEPT,
+            (false, _) => Bid::REJECT,
+        }
+    }
+
+    fn resolve_actions(
+        &self,
+        state: &mut GameState<Self>,
+        player_one_action: Bid,
+        player_two_action: Bid,
+    ) {
+        let proposer = Self::proposer(state);
+        let response = if proposer == 1 {
+            player_two_action
+        } else {
+            player_one_action
+        };
+        if response == Bid::WAIT {
+            return;
+        }
+        let offer = if proposer == 1 {
+            state.history.player_one_actions.last()
+        } else {
+            state.history.player_two_actions.last()
+        };
+        if let (Bid::ACCEPT, Some(Bid::OFFER(share))) = (response, offer) {
+            let (own, offered) = (i64::from(self.pie - share), i64::from(*share));
+            if proposer == 1 {
+                state.player_one_state.score += own;
+                state.player_two_state.score += offered;
+            } else {
+                state.player_one_state.score += offered;
+                state.player_two_state.score += own;
+            }
+        }
+        (state.player_one_state.role, state.player_two_state.role) =
+            (state.player_two_state.role, state.player_one_state.role);
+    }
+
+    fn check_end_condition(&self, state: &GameState<Self>) -> GameOutcome {
+        if state.history.turns() < 2 * self.rounds {
+            return GameOutcome::CONTINUE;
+        }
+        let (one, two) = (state.player_one_state.score, state.player_two_state.score);
+        match one.cmp(&two) {
+            std::cmp::Ordering::Greater => GameOutcome::WIN(1),
+            std::cmp::Ordering::Less => GameOutcome::WIN(2),
+            std::cmp::Ordering::Equal => GameOutcome::TIE,
+        }
+    }
+}
+
+// Offers of every share are actions 0 to pie, followed by accepting,
+// rejecting and waiting. The observation holds whether the player proposes,
+// the fraction of ultimatums played, the share of the pie on the table for a
+// responder and the responder's last answer for a proposer.
+impl SpielGame for Ultimatum {
+    fn num_distinct_actions(&self) -> usize {
+        self.pie as usize + 4
+    }
+
+    fn action_id(&self, action: &Bid) -> usize {
+        let pie = self.pie as usize;
+        match action {
+            Bid::OFFER(share) => *share as usize,
+            Bid::ACCEPT => pie + 1,
+            Bid::REJECT => pie + 2,
+            Bid::WAIT => pie + 3,
+        }
+    }
+
+    fn action_from_id(&self, id: usize) -> Option<Bid> {
+        let pie = self.pie as usize;
+        match id {
+            id if id <= pie => Some(Bid::OFFER(id as u32)),
+            id if id == pie + 1 => Some(Bid::ACCEPT),
+            id if id == pie + 2 => Some(Bid::REJECT),
+            id if id == pie + 3 => Some(Bid::WAIT),
+            _ => None,
+        }
+    }
+
+    fn action_to_string(&self, id: usize) -> String {
+        match self.action_from_id(id) {
+            Some(action) => action.to_string(),
+            None => format!("invalid action {}", id),
+        }
+    }
+
+    fn observation_tensor_size(&self) -> usize {
+        5
+    }
+
+    fn observation_tensor(
+        &self,
+        state: &GameState<Self>,
+        _settings: &GameSettings,
+        player: u64,
+    ) -> Vec<f32> {
+        let own = if player == 1 {
+            &state.player_one_state
+        } else {
+            &state.player_two_state
+        };
+        let opposing_action = state.observed_opposing_action(player);
+        let offer = match opposing_action {
+            Some(Bid::OFFER(share)) => share as f32 / self.pie as f32,
+            _ => 0.0,
+        };
+        vec![
+            (own.role == Role::Proposer) as u8 as f32,
+            (state.history.turns() / 2) as f32 / self.rounds.max(1) as f32,
+            offer,
+            (opposing_action == Some(Bid::ACCEPT)) as u8 as f32,
+            (opposing_action == Some(Bid::REJECT)) as u8 as f32,
+        ]
+    }
+
+    // Proposers may offer any share, responders accept or reject.
+    fn legal_actions(&self, state: &GameState<Self>, player: u64) -> Vec<usize> {
+        if Self::proposer(state) == player {
+            (0..=self.pie as usize).collect()
+        } else {
+            vec![self.action_id(&Bid::ACCEPT), self.action_id(&Bid::REJECT)]
+        }
+    }
+}
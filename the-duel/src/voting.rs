@@ -0,0 +1,112 @@
+// Voting-theory summaries of pairwise results, seeing every pairing as a
+// majority vote between its two agents. `payoffs[i][j]` is the expected score
+// of agent i against agent j with 1/2 for an even split, see `payoff_matrix`:
+// i beats j if it scores more than one half against it.
+//
+// The Copeland score counts the agents an agent beats minus those it loses
+// to. A Condorcet winner beats every other agent and need not exist: dominance
+// may run in cycles, A beats B beats C beats A. The agents then fall into
+// cycles, the sets of agents each beaten by another agent of the set in turn,
+// and the top cycle, or Smith set, is the smallest set of agents beating
+// every agent outside it.
+
+use std::cmp::Reverse;
+
+pub struct Voting {
+    // `beats[i][j]` whether agent i beats agent j.
+    pub beats: Vec<Vec<bool>>,
+}
+
+impl Voting {
+    pub fn new(payoffs: &[Vec<f64>]) -> Self {
+        let beats = payoffs
+            .iter()
+            .map(|row| row.iter().map(|&payoff| payoff > 0.5).collect())
+            .collect();
+        Self { beats }
+    }
+
+    pub fn num_agents(&self) -> usize {
+        self.beats.len()
+    }
+
+    pub fn copeland_scores(&self) -> Vec<i64> {
+        (0..self.num_agents())
+            .map(|agent| {
+                (0..self.num_agents())
+                    .map(
+                        |other| match (self.beats[agent][other], self.beats[other][agent]) {
+                            (true, _) => 1,
+                            (_, true) => -1,
+                            _ => 0,
+                        },
+                    )
+                    .sum()
+            })
+            .collect()
+    }
+
+    // Agents by decreasing Copeland score.
+    pub fn order(&self) -> Vec<usize> {
+        let scores = self.copeland_scores();
+        let mut order: Vec<usize> = (0..self.num_agents()).collect();
+        order.sort_by(|a, b| scores[*b].cmp(&scores[*a]));
+        order
+    }
+
+    pub fn condorcet_winner(&self) -> Option<usize> {
+        (0..self.num_agents()).find(|&agent| {
+            (0..self.num_agents()).all(|other| other == agent || self.beats[agent][other])
+        })
+    }
+
+    // The sets of at least two agents that beat each other in turn, by
+    // decreasing size: every agent of a cycle leads to every other one by a
+    // chain of agents each beating the next.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let reaches = closure(self.beats.clone());
+        let mut assigned = vec![false; self.num_agents()];
+        let mut cycles = Vec::new();
+        for agent in 0..self.num_agents() {
+            if assigned[agent] || !reaches[agent][agent] {
+                continue;
+            }
+            let cycle: Vec<usize> = (0..self.num_agents())
+                .filter(|&other| reaches[agent][other] && reaches[other][agent])
+                .collect();
+            for &member in &cycle {
+                assigned[member] = true;
+            }
+            cycles.push(cycle);
+        }
+        cycles.sort_by_key(|cycle| Reverse(cycle.len()));
+        cycles
+    }
+
+    // The smallest set of agents beating every agent outside it: those that
+    // lead to every other agent by a chain of agents each not losing to the
+    // next.
+    pub fn smith_set(&self) -> Vec<usize> {
+        let size = self.num_agents();
+        let not_losing = (0..size)
+            .map(|agent| (0..size).map(|other| !self.beats[other][agent]).collect())
+            .collect();
+        let reaches = closure(not_losing);
+        (0..size)
+            .filter(|&agent| reaches[agent].iter().all(|&reached| reached))
+            .collect()
+    }
+}
+
+// The transitive closure of a relation, by Warshall's algorithm.
+fn closure(mut relation: Vec<Vec<bool>>) -> Vec<Vec<bool>> {
+    for via in 0..relation.len() {
+        let through = relation[via].clone();
+        for row in relation.iter_mut().filter(|row| row[via]) {
+            for (related, &onwards) in row.iter_mut().zip(&through) {
+                *related |= onwards;
+            }
+        }
+    }
+    relation
+}
@@ -0,0 +1,158 @@
+// JavaScript bindings for running duels in the browser. Build with
+//
+//   wasm-pack build the-duel --target web -- --features wasm
+//
+// and drive the engine from JavaScript:
+//
+//   const playground = new Playground(42);
+//   playground.registerAgent("coin", (observation) =>
+//       Math.random() < 0.5 ? "ATTACK" : "FINCH");
+//   const game = playground.createGame("coin", "markov(0.3, 0.6)", 10);
+//   while (!game.isOver) console.log(game.step());
+//
+// JavaScript agents get the observation of `Observation` and return the name
+// of their action. The bindings need the `wasm` feature.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use js_sys::{Function, JSON};
+use rand::SeedableRng;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::agents::{JsAgent, SharedRng};
+use crate::duel::{Action, Duel};
+use crate::game::{Game, GameOutcome, GameState};
+use crate::registry::AgentRegistry;
+use crate::rng_audit::AuditedRng;
+
+#[derive(Serialize)]
+struct SeatView {
+    strategy_name: String,
+    hit_points: i64,
+    last_action: Option<Action>,
+}
+
+#[derive(Serialize)]
+struct DuelView {
+    turn: usize,
+    max_hit_points: i64,
+    player_one: SeatView,
+    player_two: SeatView,
+    outcome: GameOutcome,
+}
+
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    let text = serde_json::to_string(value)?;
+    JSON::parse(&text).map_err(|_| JsError::new("could not convert to JavaScript"))
+}
+
+// The agents known to the browser, built-in and registered from JavaScript,
+// sharing one seeded random number generator.
+#[wasm_bindgen]
+pub struct Playground {
+    registry: AgentRegistry,
+    rng: SharedRng,
+}
+
+#[wasm_bindgen]
+impl Playground {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u32) -> Self {
+        Self {
+            registry: AgentRegistry::with_builtin_agents(),
+            rng: Rc::new(RefCell::new(AuditedRng::seed_from_u64(seed as u64))),
+        }
+    }
+
+    // Makes `decide` available under `name` in agent specifications.
+    #[wasm_bindgen(js_name = registerAgent)]
+    pub fn register_agent(&mut self, name: &str, decide: Function) {
+        let agent_name = name.to_string();
+        self.registry.register(name, move |_, _| {
+            Ok(Box::new(JsAgent::new(agent_name.clone(), decide.clone())))
+        });
+    }
+
+    #[wasm_bindgen(js_name = agentNames)]
+    pub fn agent_names(&self) -> Vec<String> {
+        self.registry.names().map(String::from).collect()
+    }
+
+    // A fresh duel between two agent specifications such as `markov(0.3, 0.6)`.
+    #[wasm_bindgen(js_name = createGame)]
+    pub fn create_game(
+        &self,
+        player_one: &str,
+        player_two: &str,
+        max_hit_points: u32,
+    ) -> Result<DuelGame, JsError> {
+        let mut game = Game::new(
+            Duel::new(max_hit_points as i64),
+            self.registry.build(player_one, &self.rng)?,
+            self.registry.build(player_two, &self.rng)?,
+        );
+        game.player_one_agent.set_mode(game.settings.mode);
+        game.player_two_agent.set_mode(game.settings.mode);
+        Ok(DuelGame {
+            state: game.initial_state(),
+            game,
+            outcome: GameOutcome::CONTINUE,
+        })
+    }
+}
+
+// A duel stepped turn by turn from JavaScript.
+#[wasm_bindgen]
+pub struct DuelGame {
+    game: Game,
+    state: GameState<Duel>,
+    outcome: GameOutcome,
+}
+
+#[wasm_bindgen]
+impl DuelGame {
+    #[wasm_bindgen(getter, js_name = isOver)]
+    pub fn is_over(&self) -> bool {
+        self.outcome != GameOutcome::CONTINUE
+    }
+
+    // Plays one turn and returns the resulting state.
+    pub fn step(&mut self) -> Result<JsValue, JsError> {
+        if self.is_over() {
+            return Err(JsError::new("the game is over"));
+        }
+        self.game.step_game(&mut self.state);
+        self.outcome = self.game.check_end_condition(&self.state);
+        self.state()
+    }
+
+    // Plays until the game is decided and returns the final state.
+    pub fn run(&mut self) -> Result<JsValue, JsError> {
+        while !self.is_over() {
+            self.step()?;
+        }
+        self.state()
+    }
+
+    // The hit points, last actions and outcome so far.
+    pub fn state(&self) -> Result<JsValue, JsError> {
+        let state = &self.state;
+        to_js(&DuelView {
+            turn: state.history.turns(),
+            max_hit_points: self.game.rules.max_hit_points,
+            player_one: SeatView {
+                strategy_name: self.game.player_one_agent.strategy_name(),
+                hit_points: state.player_one_state.current_hit_points,
+                last_action: state.player_one_action.clone(),
+            },
+            player_two: SeatView {
+                strategy_name: self.game.player_two_agent.strategy_name(),
+                hit_points: state.player_two_state.current_hit_points,
+                last_action: state.player_two_action.clone(),
+            },
+            outcome: self.outcome.clone(),
+        })
+    }
+}